@@ -13,12 +13,15 @@
 //
 
 #![no_std]
+extern crate alloc;
 
+use alloc::vec::Vec;
 use core::panic::PanicInfo;
 use getrandom::{register_custom_getrandom, Error};
 use linked_list_allocator::LockedHeap;
-#[allow(unused_imports)]
-use {zenoh_buffers, zenoh_codec, zenoh_protocol};
+use zenoh_buffers::{reader::HasReader, writer::HasWriter};
+use zenoh_codec::{RCodec, WCodec, Zenoh060};
+use zenoh_protocol::transport::TransportMessage;
 
 #[panic_handler]
 fn dummy_panic_handler(_: &PanicInfo) -> ! {
@@ -32,6 +35,21 @@ fn dummy_get_rand(_: &mut [u8]) -> Result<(), Error> {
     Ok(())
 }
 
+// Encodes and decodes a message with the same wire implementation as the std targets
+fn codec_roundtrip() -> bool {
+    let codec = Zenoh060::default();
+    let msg = TransportMessage::make_ping(42, None);
+    let mut buffer = Vec::new();
+    let mut writer = buffer.writer();
+    if codec.write(&mut writer, &msg).is_err() {
+        return false;
+    }
+    let mut reader = buffer.reader();
+    let res: Result<TransportMessage, _> = codec.read(&mut reader);
+    res.map_or(false, |m| m == msg)
+}
+
 fn main() {
     register_custom_getrandom!(dummy_get_rand);
+    let _ = codec_roundtrip();
 }
@@ -62,6 +62,19 @@ impl BBuf {
     }
 }
 
+// An empty buffer with the capacity of the given memory, allowing to reuse it
+impl From<Box<[u8]>> for BBuf {
+    fn from(buffer: Box<[u8]>) -> Self {
+        Self { buffer, len: 0 }
+    }
+}
+
+impl From<BBuf> for Box<[u8]> {
+    fn from(bbuf: BBuf) -> Self {
+        bbuf.buffer
+    }
+}
+
 // Writer
 impl HasWriter for &mut BBuf {
     type Writer = Self;
@@ -294,6 +294,87 @@ impl<'a> SiphonableReader for ZBufReader<'a> {
     }
 }
 
+impl<'a> ZBufReader<'a> {
+    /// The number of bytes of the buffer before the cursor.
+    pub fn position(&self) -> usize {
+        self.inner.len() - self.remaining()
+    }
+
+    // Moves the cursor to `pos`, or to the end of the buffer if `pos` is beyond it
+    fn set_position(&mut self, mut pos: usize) {
+        self.cursor = ZBufPos::default();
+        for slice in self.inner.slices.as_ref() {
+            if pos < slice.len() {
+                self.cursor.byte = pos;
+                return;
+            }
+            pos -= slice.len();
+            self.cursor.slice += 1;
+        }
+    }
+}
+
+// The std::io traits allow to feed the payloads to the deserializers without a contiguous copy,
+// while a ZSlice can be wrapped into a std::io::Cursor since it implements AsRef<[u8]>
+#[cfg(feature = "std")]
+impl<'a> std::io::Read for ZBufReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match Reader::read(self, buf) {
+            Ok(n) => Ok(n.get()),
+            Err(_) => Ok(0),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> std::io::BufRead for ZBufReader<'a> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        // Skip the empty slices, an empty buffer meaning the end of the reader
+        while let Some(slice) = self.inner.slices.get(self.cursor.slice) {
+            if self.cursor.byte < slice.len() {
+                return Ok(&slice.as_slice()[self.cursor.byte..]);
+            }
+            self.cursor.slice += 1;
+            self.cursor.byte = 0;
+        }
+        Ok(&[])
+    }
+
+    fn consume(&mut self, mut amt: usize) {
+        // Move the cursor from its current slice rather than from the start of the buffer
+        while let Some(slice) = self.inner.slices.get(self.cursor.slice) {
+            let left = slice.len() - self.cursor.byte;
+            if amt < left {
+                self.cursor.byte += amt;
+                return;
+            }
+            amt -= left;
+            self.cursor.slice += 1;
+            self.cursor.byte = 0;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> std::io::Seek for ZBufReader<'a> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            std::io::SeekFrom::Start(n) => n as i128,
+            std::io::SeekFrom::End(n) => self.inner.len() as i128 + n as i128,
+            std::io::SeekFrom::Current(n) => self.position() as i128 + n as i128,
+        };
+        if target < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+        // Seeking beyond the end moves the cursor to the end of the buffer
+        self.set_position(target.min(self.inner.len() as i128) as usize);
+        Ok(self.position() as u64)
+    }
+}
+
 // ZSlice iterator
 pub struct ZBufSliceIterator<'a, 'b> {
     reader: &'a mut ZBufReader<'b>,
@@ -345,10 +426,14 @@ impl Iterator for ZBufSliceIterator<'_, '_> {
 }
 
 // Writer
+#[cfg(feature = "std")]
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
 #[derive(Debug)]
 pub struct ZBufWriter<'a> {
     inner: &'a mut ZBuf,
     cache: Arc<Vec<u8>>,
+    chunk_size: Option<NonZeroUsize>,
 }
 
 impl<'a> HasWriter for &'a mut ZBuf {
@@ -358,25 +443,78 @@ impl<'a> HasWriter for &'a mut ZBuf {
         ZBufWriter {
             inner: self,
             cache: Arc::new(Vec::new()),
+            chunk_size: None,
         }
     }
 }
 
-impl Writer for ZBufWriter<'_> {
-    fn write(&mut self, bytes: &[u8]) -> Result<NonZeroUsize, DidntWrite> {
-        if bytes.is_empty() {
-            return Err(DidntWrite);
+impl ZBufWriter<'_> {
+    /// Writes the bytes in chunks of `chunk_size` bytes allocated once, a new chunk being started
+    /// when the current one is full. The written bytes are thus never moved by a reallocation, and
+    /// the ZBuf is made of one slice per chunk, each sent as is by the codec.
+    pub fn with_chunk_size(mut self, chunk_size: NonZeroUsize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Makes room for `additional` contiguous bytes. If the current chunk can't hold them, a new
+    /// chunk of at least `additional` bytes is started rather than reallocating the current one.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.cache.capacity() - self.cache.len() < additional {
+            let capacity = self
+                .chunk_size
+                .map_or(additional, |chunk_size| chunk_size.get().max(additional));
+            self.cache = Arc::new(Vec::with_capacity(capacity));
         }
-        self.write_exact(bytes)?;
-        // Safety: this operation is safe since we check if bytes is empty
-        Ok(unsafe { NonZeroUsize::new_unchecked(bytes.len()) })
     }
 
-    fn write_exact(&mut self, bytes: &[u8]) -> Result<(), DidntWrite> {
-        let cache = get_mut_unchecked(&mut self.cache);
-        let prev_cache_len = cache.len();
-        cache.extend_from_slice(bytes);
-        let cache_len = cache.len();
+    /// Appends all the bytes of `reader` until its end, reading them directly in the chunks.
+    /// Returns the number of bytes appended.
+    #[cfg(feature = "std")]
+    pub fn extend_from_reader<R>(&mut self, mut reader: R) -> std::io::Result<usize>
+    where
+        R: std::io::Read,
+    {
+        let mut total = 0;
+        loop {
+            if self.cache.capacity() == self.cache.len() {
+                self.reserve(self.chunk_size.map_or(READ_CHUNK_SIZE, NonZeroUsize::get));
+            }
+            let cache = get_mut_unchecked(&mut self.cache);
+            let prev_cache_len = cache.len();
+            cache.resize(cache.capacity(), 0);
+            let res = reader.read(&mut cache[prev_cache_len..]);
+            let len = *res.as_ref().unwrap_or(&0);
+            cache.truncate(prev_cache_len + len);
+            match res {
+                Ok(0) => return Ok(total),
+                Ok(len) => {
+                    self.push_cache(prev_cache_len);
+                    total += len;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // The number of bytes out of `len` to write on the cache, starting a new chunk if it is full
+    fn chunk(&mut self, len: usize) -> usize {
+        match self.chunk_size {
+            Some(chunk_size) => {
+                if self.cache.capacity() == self.cache.len() {
+                    self.cache = Arc::new(Vec::with_capacity(chunk_size.get()));
+                }
+                len.min(self.cache.capacity() - self.cache.len())
+            }
+            None => len,
+        }
+    }
+
+    // Extends the last slice of the ZBuf with the bytes written on the cache after
+    // `prev_cache_len`, or pushes a new slice of them
+    fn push_cache(&mut self, prev_cache_len: usize) {
+        let cache_len = self.cache.len();
 
         // Verify we are writing on the cache
         if let Some(ZSlice {
@@ -388,10 +526,10 @@ impl Writer for ZBufWriter<'_> {
                 // Verify the ZSlice is actually a Vec<u8>
                 if let Some(b) = buf.as_any().downcast_ref::<Vec<u8>>() {
                     // Verify the Vec<u8> of the ZSlice is exactly the one from the cache
-                    if core::ptr::eq(cache.as_ptr(), b.as_ptr()) {
+                    if core::ptr::eq(self.cache.as_ptr(), b.as_ptr()) {
                         // Simply update the slice length
                         *end = cache_len;
-                        return Ok(());
+                        return;
                     }
                 }
             }
@@ -402,6 +540,28 @@ impl Writer for ZBufWriter<'_> {
             start: prev_cache_len,
             end: cache_len,
         });
+    }
+}
+
+impl Writer for ZBufWriter<'_> {
+    fn write(&mut self, bytes: &[u8]) -> Result<NonZeroUsize, DidntWrite> {
+        if bytes.is_empty() {
+            return Err(DidntWrite);
+        }
+        self.write_exact(bytes)?;
+        // Safety: this operation is safe since we check if bytes is empty
+        Ok(unsafe { NonZeroUsize::new_unchecked(bytes.len()) })
+    }
+
+    fn write_exact(&mut self, mut bytes: &[u8]) -> Result<(), DidntWrite> {
+        while !bytes.is_empty() {
+            let len = self.chunk(bytes.len());
+            let cache = get_mut_unchecked(&mut self.cache);
+            let prev_cache_len = cache.len();
+            cache.extend_from_slice(&bytes[..len]);
+            self.push_cache(prev_cache_len);
+            bytes = &bytes[len..];
+        }
         Ok(())
     }
 
@@ -422,6 +582,10 @@ impl Writer for ZBufWriter<'_> {
     where
         F: FnOnce(&mut [u8]) -> usize,
     {
+        // The slot has to be contiguous, thus in a single chunk
+        if self.chunk_size.is_some() {
+            self.reserve(len);
+        }
         let cache = get_mut_unchecked(&mut self.cache);
         let prev_cache_len = cache.len();
         cache.reserve(len);
@@ -429,32 +593,7 @@ impl Writer for ZBufWriter<'_> {
             len = f(mem::transmute(&mut cache.spare_capacity_mut()[..len]));
             cache.set_len(prev_cache_len + len);
         }
-        let cache_len = cache.len();
-
-        // Verify we are writing on the cache
-        if let Some(ZSlice {
-            buf, ref mut end, ..
-        }) = self.inner.slices.last_mut()
-        {
-            // Verify the previous length of the cache is the right one
-            if *end == prev_cache_len {
-                // Verify the ZSlice is actually a Vec<u8>
-                if let Some(b) = buf.as_any().downcast_ref::<Vec<u8>>() {
-                    // Verify the Vec<u8> of the ZSlice is exactly the one from the cache
-                    if core::ptr::eq(cache.as_ptr(), b.as_ptr()) {
-                        // Simply update the slice length
-                        *end = cache_len;
-                        return NonZeroUsize::new(len).ok_or(DidntWrite);
-                    }
-                }
-            }
-        }
-
-        self.inner.slices.push(ZSlice {
-            buf: self.cache.clone(),
-            start: prev_cache_len,
-            end: cache_len,
-        });
+        self.push_cache(prev_cache_len);
         NonZeroUsize::new(len).ok_or(DidntWrite)
     }
 }
@@ -522,4 +661,108 @@ mod tests {
 
         assert_eq!(zbuf1, zbuf2);
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn zbuf_io_reader() {
+        use super::{ZBuf, ZSlice};
+        use crate::reader::HasReader;
+        use std::io::{BufRead, Read, Seek, SeekFrom};
+
+        let slice: ZSlice = b"hello\nzenoh\nworld".to_vec().into();
+        let mut zbuf = ZBuf::default();
+        zbuf.push_zslice(slice.new_sub_slice(0, 3).unwrap());
+        zbuf.push_zslice(slice.new_sub_slice(3, 3).unwrap());
+        zbuf.push_zslice(slice.new_sub_slice(3, 9).unwrap());
+        zbuf.push_zslice(slice.new_sub_slice(9, 17).unwrap());
+
+        let mut bytes = vec![];
+        let mut reader = zbuf.reader();
+        assert_eq!(reader.read_to_end(&mut bytes).unwrap(), 17);
+        assert_eq!(bytes, slice.as_slice());
+        assert_eq!(Read::read(&mut reader, &mut [0; 4]).unwrap(), 0);
+
+        let lines = zbuf
+            .reader()
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(lines, ["hello", "zenoh", "world"]);
+
+        let mut reader = zbuf.reader();
+        assert_eq!(reader.fill_buf().unwrap(), b"hel");
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().unwrap(), b"l");
+        reader.consume(4);
+        assert_eq!(reader.fill_buf().unwrap(), b"zen");
+        assert_eq!(reader.position(), 6);
+        reader.consume(20);
+        assert!(reader.fill_buf().unwrap().is_empty());
+        assert_eq!(reader.position(), 17);
+
+        let mut reader = zbuf.reader();
+        let mut bytes = [0; 5];
+        assert_eq!(reader.seek(SeekFrom::Start(6)).unwrap(), 6);
+        reader.read_exact(&mut bytes).unwrap();
+        assert_eq!(&bytes, b"zenoh");
+        assert_eq!(reader.seek(SeekFrom::Current(-3)).unwrap(), 8);
+        assert_eq!(reader.seek(SeekFrom::End(-5)).unwrap(), 12);
+        reader.read_exact(&mut bytes).unwrap();
+        assert_eq!(&bytes, b"world");
+        assert_eq!(reader.seek(SeekFrom::End(10)).unwrap(), 17);
+        assert!(reader.seek(SeekFrom::Current(-20)).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn zbuf_chunked_writer() {
+        use super::ZBuf;
+        use crate::{
+            writer::{HasWriter, Writer},
+            SplitBuffer,
+        };
+        use core::num::NonZeroUsize;
+
+        let bytes = (0..32).collect::<Vec<u8>>();
+
+        // The writes are split on the chunks, which are never reallocated
+        let mut zbuf = ZBuf::default();
+        let mut writer = zbuf.writer().with_chunk_size(NonZeroUsize::new(8).unwrap());
+        writer.write_exact(&bytes[..6]).unwrap();
+        writer.write_exact(&bytes[6..20]).unwrap();
+        assert_eq!(zbuf.contiguous().as_ref(), &bytes[..20]);
+        assert_eq!(
+            zbuf.slices().map(|s| s.len()).collect::<Vec<_>>(),
+            [8, 8, 4]
+        );
+
+        // A reservation larger than the chunk size starts a larger chunk
+        let mut zbuf = ZBuf::default();
+        let mut writer = zbuf.writer().with_chunk_size(NonZeroUsize::new(8).unwrap());
+        writer.write_exact(&bytes[..4]).unwrap();
+        writer.reserve(12);
+        writer.write_exact(&bytes[4..16]).unwrap();
+        assert_eq!(zbuf.contiguous().as_ref(), &bytes[..16]);
+        assert_eq!(zbuf.slices().map(|s| s.len()).collect::<Vec<_>>(), [4, 12]);
+
+        // The bytes of a reader are appended chunk by chunk
+        let mut zbuf = ZBuf::default();
+        let mut writer = zbuf.writer().with_chunk_size(NonZeroUsize::new(8).unwrap());
+        writer.write_exact(&bytes[..2]).unwrap();
+        assert_eq!(writer.extend_from_reader(&bytes[2..]).unwrap(), 30);
+        assert_eq!(zbuf.contiguous().as_ref(), bytes.as_slice());
+        assert_eq!(
+            zbuf.slices().map(|s| s.len()).collect::<Vec<_>>(),
+            [8, 8, 8, 8]
+        );
+
+        // Without chunk size, the slices are sized on the writes: the bytes of the reader do not
+        // fit on the slice of the first write and go on a new one
+        let mut zbuf = ZBuf::default();
+        let mut writer = zbuf.writer();
+        writer.write_exact(&bytes[..6]).unwrap();
+        assert_eq!(writer.extend_from_reader(&bytes[6..]).unwrap(), 26);
+        assert_eq!(zbuf.contiguous().as_ref(), bytes.as_slice());
+        assert_eq!(zbuf.slices().len(), 2);
+    }
 }
@@ -372,3 +372,73 @@ pub const ZN_TLS_CLIENT_AUTH_DEFAULT: &str = ZN_FALSE;
 pub const ZN_QUERIES_DEFAULT_TIMEOUT_KEY: u64 = 0x88;
 pub const ZN_QUERIES_DEFAULT_TIMEOUT_STR: &str = "local_routing";
 pub const ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT: &str = "10000";
+
+/// Configures the window in milliseconds within which the handshake cookies
+/// received on connectionless links are accepted.
+/// String key: `"replay_window"`.
+/// Accepted values: `<unsigned integer>`.
+/// Default value: `10000`.
+pub const ZN_REPLAY_WINDOW_KEY: u64 = 0x89;
+pub const ZN_REPLAY_WINDOW_STR: &str = "replay_window";
+pub const ZN_REPLAY_WINDOW_DEFAULT: &str = "10000";
+
+/// Configures the maximum number of handshake cookies remembered to detect replays
+/// on connectionless links.
+/// String key: `"replay_cache_size"`.
+/// Accepted values: `<unsigned integer>`.
+/// Default value: `1024`.
+pub const ZN_REPLAY_CACHE_SIZE_KEY: u64 = 0x8a;
+pub const ZN_REPLAY_CACHE_SIZE_STR: &str = "replay_cache_size";
+pub const ZN_REPLAY_CACHE_SIZE_DEFAULT: &str = "1024";
+
+/// Configures the interval in milliseconds at which each unicast link is pinged
+/// to measure its round-trip time, 0 to disable.
+/// String key: `"ping_interval"`.
+/// Accepted values: `<unsigned integer>`.
+/// Default value: `0`.
+pub const ZN_PING_INTERVAL_KEY: u64 = 0x8b;
+pub const ZN_PING_INTERVAL_STR: &str = "ping_interval";
+pub const ZN_PING_INTERVAL_DEFAULT: &str = "0";
+
+/// Configures whether the messages are scheduled on the unicast link with the lowest
+/// average round-trip time.
+/// String key: `"rtt_scheduling"`.
+/// Accepted values: `"true"`, `"false"`.
+/// Default value: `"false"`.
+pub const ZN_RTT_SCHEDULING_KEY: u64 = 0x8c;
+pub const ZN_RTT_SCHEDULING_STR: &str = "rtt_scheduling";
+pub const ZN_RTT_SCHEDULING_DEFAULT: &str = ZN_FALSE;
+
+/// Configures whether the keep-alive messages are only sent on the unicast links that carried
+/// no traffic within the keep-alive interval, the traffic keeping the link alive.
+/// String key: `"adaptive_keep_alive"`.
+/// Accepted values: `"true"`, `"false"`.
+/// Default value: `"true"`.
+pub const ZN_ADAPTIVE_KEEP_ALIVE_KEY: u64 = 0x8d;
+pub const ZN_ADAPTIVE_KEEP_ALIVE_STR: &str = "adaptive_keep_alive";
+pub const ZN_ADAPTIVE_KEEP_ALIVE_DEFAULT: &str = ZN_TRUE;
+
+/// Configures whether the batches sent on the stream-oriented links are preceded by a preamble,
+/// used on a link only if the remote side configures it too.
+/// String key: `"preamble"`.
+/// Accepted values: `"true"`, `"false"`.
+/// Default value: `"false"`.
+pub const ZN_LINK_PREAMBLE_KEY: u64 = 0x8e;
+pub const ZN_LINK_PREAMBLE_STR: &str = "preamble";
+pub const ZN_LINK_PREAMBLE_DEFAULT: &str = ZN_FALSE;
+
+/// Configures the maximum number of consecutive batches failing to decode on a link before the
+/// link is closed, the remainder of such a batch being skipped.
+/// String key: `"max_decode_errors"`.
+/// Accepted values: `<unsigned integer>`.
+/// Default value: `"3"`.
+pub const ZN_LINK_RX_MAX_DECODE_ERRORS_KEY: u64 = 0x8f;
+pub const ZN_LINK_RX_MAX_DECODE_ERRORS_STR: &str = "max_decode_errors";
+pub const ZN_LINK_RX_MAX_DECODE_ERRORS_DEFAULT: &str = "3";
+
+/// The group key with which the Join messages of the multicast transports are signed, the
+/// multicast traffic of the peers not presenting a Join signed with the same key being ignored.
+/// String key: `"join_key"`.
+/// Accepted values: `<string>`.
+pub const ZN_JOIN_KEY_KEY: u64 = 0x90;
+pub const ZN_JOIN_KEY_STR: &str = "join_key";
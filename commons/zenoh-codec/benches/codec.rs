@@ -84,6 +84,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         payload: ZBuf::from(vec![0u8; 8]),
         congestion_control: CongestionControl::default(),
         reply_context: None,
+        unit: false,
     };
 
     // Calculate the number of messages
@@ -121,6 +122,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         payload: ZBuf::from(vec![0u8; 8]),
         congestion_control: CongestionControl::default(),
         reply_context: None,
+        unit: false,
     };
 
     let mut writer = buff.writer();
@@ -153,6 +155,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         payload: ZBuf::from(vec![0u8; 8]),
         congestion_control: CongestionControl::default(),
         reply_context: None,
+        unit: false,
     };
 
     let mut writer = buff.writer();
@@ -185,6 +188,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         payload: ZBuf::from(vec![0u8; 1_000_000]),
         congestion_control: CongestionControl::default(),
         reply_context: None,
+        unit: false,
     };
     c.bench_function("Fragmentation ZBuf Write", |b| {
         b.iter(|| {
@@ -203,6 +207,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         payload: ZBuf::from(vec![0u8; 1_000_000]),
         congestion_control: CongestionControl::default(),
         reply_context: None,
+        unit: false,
     };
 
     let mut writer = buff.writer();
@@ -233,6 +238,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         payload: ZBuf::from(vec![0u8; 1_000_000]),
         congestion_control: CongestionControl::default(),
         reply_context: None,
+        unit: false,
     };
 
     let mut writer = buff.writer();
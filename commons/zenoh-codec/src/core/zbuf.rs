@@ -48,7 +48,10 @@ where
     fn read(self, reader: &mut R) -> Result<ZBuf, Self::Error> {
         let len: usize = self.read(&mut *reader)?;
         let mut zbuf = ZBuf::default();
-        reader.read_zslices(len, |s| zbuf.push_zslice(s))?;
+        // An empty payload does not retain the buffer it is read from
+        if len > 0 {
+            reader.read_zslices(len, |s| zbuf.push_zslice(s))?;
+        }
         Ok(zbuf)
     }
 }
@@ -100,7 +103,9 @@ where
             match kind {
                 super::zslice::kind::RAW => {
                     let len: usize = self.codec.read(&mut *reader)?;
-                    reader.read_zslices(len, |s| zbuf.push_zslice(s))?;
+                    if len > 0 {
+                        reader.read_zslices(len, |s| zbuf.push_zslice(s))?;
+                    }
                 }
                 super::zslice::kind::SHM_INFO => {
                     let bytes: Vec<u8> = self.codec.read(&mut *reader)?;
@@ -26,6 +26,8 @@ mod scouting;
 mod transport;
 mod zenoh;
 
+pub use transport::find_preamble;
+
 use zenoh_protocol::{core::Reliability, zenoh::ReplyContext};
 
 pub trait WCodec<Message, Buffer> {
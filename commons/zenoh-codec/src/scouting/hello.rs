@@ -12,15 +12,15 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::{RCodec, WCodec, Zenoh060, Zenoh060Header};
-use alloc::{vec, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
     writer::{DidntWrite, Writer},
 };
 use zenoh_protocol::{
     common::imsg,
-    core::{Locator, WhatAmI, ZInt, ZenohId},
-    scouting::Hello,
+    core::{Locator, Property, WhatAmI, ZInt, ZenohId},
+    scouting::{Hello, HelloMetadata},
     transport::tmsg,
 };
 
@@ -108,6 +108,64 @@ where
             zid,
             whatami,
             locators,
+            metadata: None,
         })
     }
 }
+
+// HelloMetadata
+impl<W> WCodec<&HelloMetadata, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &HelloMetadata) -> Self::Output {
+        let property = |key: ZInt, value: &[u8]| Property {
+            key,
+            value: value.to_vec(),
+        };
+        let mut ps = vec![];
+        if let Some(cluster) = x.cluster.as_ref() {
+            ps.push(property(HelloMetadata::CLUSTER, cluster.as_bytes()));
+        }
+        if let Some(region) = x.region.as_ref() {
+            ps.push(property(HelloMetadata::REGION, region.as_bytes()));
+        }
+        if let Some(load) = x.load {
+            ps.push(property(HelloMetadata::LOAD, &[load]));
+        }
+        for feature in x.features.iter() {
+            ps.push(property(HelloMetadata::FEATURE, feature.as_bytes()));
+        }
+        self.write(&mut *writer, ps.as_slice())
+    }
+}
+
+impl<R> RCodec<HelloMetadata, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<HelloMetadata, Self::Error> {
+        let string = |value: Vec<u8>| String::from_utf8(value).map_err(|_| DidntRead);
+
+        let ps: Vec<Property> = self.read(&mut *reader)?;
+        let mut metadata = HelloMetadata::default();
+        for p in ps {
+            match p.key {
+                HelloMetadata::CLUSTER => metadata.cluster = Some(string(p.value)?),
+                HelloMetadata::REGION => metadata.region = Some(string(p.value)?),
+                HelloMetadata::LOAD => match p.value.as_slice() {
+                    [load] => metadata.load = Some(*load),
+                    _ => return Err(DidntRead),
+                },
+                HelloMetadata::FEATURE => metadata.features.push(string(p.value)?),
+                // The metadata added by the newer versions are ignored
+                _ => {}
+            }
+        }
+        Ok(metadata)
+    }
+}
@@ -15,13 +15,15 @@ mod hello;
 mod scout;
 
 use crate::{RCodec, WCodec, Zenoh060, Zenoh060Header};
+use alloc::vec;
 use zenoh_buffers::{
-    reader::{DidntRead, Reader},
-    writer::{DidntWrite, Writer},
+    reader::{DidntRead, HasReader, Reader},
+    writer::{DidntWrite, HasWriter, Writer},
+    ZBuf,
 };
 use zenoh_protocol::{
     common::{imsg, Attachment},
-    scouting::{ScoutingBody, ScoutingMessage},
+    scouting::{Hello, HelloMetadata, ScoutingBody, ScoutingMessage},
 };
 
 impl<W> WCodec<&ScoutingMessage, &mut W> for Zenoh060
@@ -31,8 +33,20 @@ where
     type Output = Result<(), DidntWrite>;
 
     fn write(self, writer: &mut W, x: &ScoutingMessage) -> Self::Output {
-        if let Some(a) = x.attachment.as_ref() {
-            self.write(&mut *writer, a)?;
+        // The metadata of a Hello is carried by its attachment
+        let metadata = match &x.body {
+            ScoutingBody::Hello(h) => h.metadata.as_ref(),
+            ScoutingBody::Scout(_) => None,
+        };
+        match (metadata, x.attachment.as_ref()) {
+            (Some(_), Some(_)) => return Err(DidntWrite),
+            (Some(m), None) => {
+                let mut buffer = vec![];
+                self.write(&mut buffer.writer(), m)?;
+                self.write(&mut *writer, &Attachment::new(ZBuf::from(buffer)))?;
+            }
+            (None, Some(a)) => self.write(&mut *writer, a)?,
+            (None, None) => {}
         }
 
         match &x.body {
@@ -54,7 +68,7 @@ where
             ..Default::default()
         };
 
-        let attachment = if imsg::mid(codec.header) == imsg::id::ATTACHMENT {
+        let mut attachment = if imsg::mid(codec.header) == imsg::id::ATTACHMENT {
             let a: Attachment = codec.read(&mut *reader)?;
             codec.header = self.read(&mut *reader)?;
             Some(a)
@@ -63,7 +77,19 @@ where
         };
         let body = match imsg::mid(codec.header) {
             imsg::id::SCOUT => ScoutingBody::Scout(codec.read(&mut *reader)?),
-            imsg::id::HELLO => ScoutingBody::Hello(codec.read(&mut *reader)?),
+            imsg::id::HELLO => {
+                let mut hello: Hello = codec.read(&mut *reader)?;
+                // An attachment not holding metadata is left as is
+                if let Some(a) = attachment.as_ref() {
+                    let mut reader = a.buffer.reader();
+                    let metadata: Result<HelloMetadata, DidntRead> = self.read(&mut reader);
+                    if let (Ok(m), false) = (metadata, reader.can_read()) {
+                        hello.metadata = Some(m);
+                        attachment = None;
+                    }
+                }
+                ScoutingBody::Hello(hello)
+            }
             _ => return Err(DidntRead),
         };
         Ok(ScoutingMessage { body, attachment })
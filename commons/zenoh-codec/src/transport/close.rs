@@ -18,7 +18,7 @@ use zenoh_buffers::{
 };
 use zenoh_protocol::{
     common::imsg,
-    core::ZenohId,
+    core::{Locator, ZenohId},
     transport::{tmsg, Close},
 };
 
@@ -37,6 +37,9 @@ where
         if x.link_only {
             header |= tmsg::flag::K;
         }
+        if x.locator.is_some() {
+            header |= tmsg::flag::L;
+        }
         self.write(&mut *writer, header)?;
 
         // Body
@@ -44,6 +47,9 @@ where
             self.write(&mut *writer, p)?;
         }
         self.write(&mut *writer, x.reason)?;
+        if let Some(l) = x.locator.as_ref() {
+            self.write(&mut *writer, l)?;
+        }
         Ok(())
     }
 }
@@ -82,11 +88,18 @@ where
             None
         };
         let reason: u8 = self.codec.read(&mut *reader)?;
+        let locator = if imsg::has_flag(self.header, tmsg::flag::L) {
+            let locator: Locator = self.codec.read(&mut *reader)?;
+            Some(locator)
+        } else {
+            None
+        };
 
         Ok(Close {
             zid,
             reason,
             link_only,
+            locator,
         })
     }
 }
@@ -33,7 +33,7 @@ where
 
     fn write(self, writer: &mut W, x: &InitSyn) -> Self::Output {
         fn has_options(x: &InitSyn) -> bool {
-            x.is_qos
+            x.is_qos || x.is_preamble || x.extensions != 0
         }
 
         fn options(x: &InitSyn) -> ZInt {
@@ -41,6 +41,10 @@ where
             if x.is_qos {
                 options |= tmsg::init_options::QOS;
             }
+            if x.is_preamble {
+                options |= tmsg::init_options::PREAMBLE;
+            }
+            options |= x.extensions & tmsg::init_options::EXTENSIONS;
             options
         }
 
@@ -110,6 +114,8 @@ where
             SEQ_NUM_RES
         };
         let is_qos = imsg::has_option(options, tmsg::init_options::QOS);
+        let is_preamble = imsg::has_option(options, tmsg::init_options::PREAMBLE);
+        let extensions = options & tmsg::init_options::EXTENSIONS;
 
         Ok(InitSyn {
             version,
@@ -117,6 +123,8 @@ where
             zid,
             sn_resolution,
             is_qos,
+            is_preamble,
+            extensions,
         })
     }
 }
@@ -130,7 +138,7 @@ where
 
     fn write(self, writer: &mut W, x: &InitAck) -> Self::Output {
         fn has_options(x: &InitAck) -> bool {
-            x.is_qos
+            x.is_qos || x.is_preamble || x.extensions != 0
         }
 
         fn options(x: &InitAck) -> ZInt {
@@ -138,6 +146,10 @@ where
             if x.is_qos {
                 options |= tmsg::init_options::QOS;
             }
+            if x.is_preamble {
+                options |= tmsg::init_options::PREAMBLE;
+            }
+            options |= x.extensions & tmsg::init_options::EXTENSIONS;
             options
         }
 
@@ -208,6 +220,8 @@ where
             None
         };
         let is_qos = imsg::has_option(options, tmsg::init_options::QOS);
+        let is_preamble = imsg::has_option(options, tmsg::init_options::PREAMBLE);
+        let extensions = options & tmsg::init_options::EXTENSIONS;
         let cookie: ZSlice = self.codec.read(&mut *reader)?;
 
         Ok(InitAck {
@@ -215,6 +229,8 @@ where
             zid,
             sn_resolution,
             is_qos,
+            is_preamble,
+            extensions,
             cookie,
         })
     }
@@ -12,7 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::{RCodec, WCodec, Zenoh060, Zenoh060Header};
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 use core::time::Duration;
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
@@ -37,6 +37,9 @@ where
             if x.is_qos() {
                 options |= tmsg::join_options::QOS;
             }
+            if x.token.is_some() {
+                options |= tmsg::join_options::AUTH;
+            }
             options
         }
 
@@ -82,7 +85,9 @@ where
                 }
             }
         }
-        // true
+        if let Some(token) = x.token.as_ref() {
+            self.write(&mut *writer, token.as_slice())?;
+        }
         Ok(())
     }
 }
@@ -147,6 +152,11 @@ where
                 best_effort: self.codec.read(&mut *reader)?,
             })
         };
+        let token: Option<Vec<u8>> = if imsg::has_option(options, tmsg::join_options::AUTH) {
+            Some(self.codec.read(&mut *reader)?)
+        } else {
+            None
+        };
 
         Ok(Join {
             version,
@@ -155,6 +165,7 @@ where
             lease,
             sn_resolution,
             next_sns,
+            token,
         })
     }
 }
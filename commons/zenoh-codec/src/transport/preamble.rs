@@ -0,0 +1,61 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::{RCodec, WCodec, Zenoh060};
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_protocol::transport::Preamble;
+
+impl<W> WCodec<&Preamble, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &Preamble) -> Self::Output {
+        writer.write_exact(&x.to_bytes())
+    }
+}
+
+impl<R> RCodec<Preamble, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Preamble, Self::Error> {
+        let mut bytes = [0_u8; Preamble::LEN];
+        reader.read_exact(&mut bytes)?;
+        if bytes[..Preamble::MAGIC.len()] != Preamble::MAGIC {
+            return Err(DidntRead);
+        }
+        Ok(Preamble {
+            version: bytes[Preamble::MAGIC.len()],
+        })
+    }
+}
+
+/// Returns the offset in `bytes` of the first preamble of the given `version`, if any.
+///
+/// This is meant to resynchronize on the batches of a stream link after a decoding error: the
+/// bytes are skipped up to the returned offset, where the next batch is expected to start.
+/// Any occurrence of the preamble within the payload of a batch is a false positive, so the batch
+/// found at the offset must still be decoded successfully to be trusted.
+pub fn find_preamble(bytes: &[u8], version: u8) -> Option<usize> {
+    let preamble = Preamble { version }.to_bytes();
+    bytes
+        .windows(Preamble::LEN)
+        .position(|window| window == preamble)
+}
@@ -24,7 +24,7 @@ use zenoh_buffers::{
 use zenoh_protocol::{
     common::imsg,
     core::{CongestionControl, Encoding, SampleKind, WireExpr, ZInt, ZenohId},
-    zenoh::{zmsg, Data, DataInfo, ReplierInfo, ReplyContext},
+    zenoh::{zmsg, Data, DataInfo, ReplierInfo, ReplyContext, TraceContext},
 };
 
 // ReplyContext
@@ -88,6 +88,35 @@ where
     }
 }
 
+// TraceContext
+impl<W> WCodec<&TraceContext, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &TraceContext) -> Self::Output {
+        writer.write_exact(&x.trace_id)?;
+        writer.write_exact(&x.span_id)?;
+        self.write(&mut *writer, x.flags)
+    }
+}
+
+impl<R> RCodec<TraceContext, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<TraceContext, Self::Error> {
+        let mut tc = TraceContext::default();
+        reader.read_exact(&mut tc.trace_id)?;
+        reader.read_exact(&mut tc.span_id)?;
+        tc.flags = self.read(&mut *reader)?;
+        Ok(tc)
+    }
+}
+
 // DataInfo
 impl<W> WCodec<&DataInfo, &mut W> for Zenoh060
 where
@@ -111,6 +140,12 @@ where
         if x.timestamp.is_some() {
             options |= zmsg::data::info::TIMESTAMP;
         }
+        if x.trace_context.is_some() {
+            options |= zmsg::data::info::TRACE_CONTEXT;
+        }
+        if x.latched {
+            options |= zmsg::data::info::LATCHED;
+        }
         if x.source_id.is_some() {
             options |= zmsg::data::info::SRCID;
         }
@@ -128,6 +163,9 @@ where
         if let Some(ts) = x.timestamp.as_ref() {
             self.write(&mut *writer, ts)?;
         }
+        if let Some(tc) = x.trace_context.as_ref() {
+            self.write(&mut *writer, tc)?;
+        }
         if let Some(si) = x.source_id.as_ref() {
             self.write(&mut *writer, si)?;
         }
@@ -152,6 +190,7 @@ where
         {
             info.sliced = imsg::has_option(options, zmsg::data::info::SLICED);
         }
+        info.latched = imsg::has_option(options, zmsg::data::info::LATCHED);
         if imsg::has_option(options, zmsg::data::info::KIND) {
             let kind: ZInt = self.read(&mut *reader)?;
             info.kind = kind.try_into().map_err(|_| DidntRead)?;
@@ -164,6 +203,10 @@ where
             let timestamp: Timestamp = self.read(&mut *reader)?;
             info.timestamp = Some(timestamp);
         }
+        if imsg::has_option(options, zmsg::data::info::TRACE_CONTEXT) {
+            let trace_context: TraceContext = self.read(&mut *reader)?;
+            info.trace_context = Some(trace_context);
+        }
         if imsg::has_option(options, zmsg::data::info::SRCID) {
             let source_id: ZenohId = self.read(&mut *reader)?;
             info.source_id = Some(source_id);
@@ -189,6 +232,19 @@ where
             self.write(&mut *writer, reply_context)?;
         }
 
+        if x.unit && x.is_unit() {
+            // Only the key expression is encoded, in a Unit message
+            let mut header = zmsg::id::UNIT | zmsg::flag::E;
+            if x.key.has_suffix() {
+                header |= zmsg::flag::K;
+            }
+            if x.congestion_control == CongestionControl::Drop {
+                header |= zmsg::flag::D;
+            }
+            self.write(&mut *writer, header)?;
+            return self.write(&mut *writer, &x.key);
+        }
+
         // Header
         let mut header = zmsg::id::DATA;
         if x.data_info.is_some() {
@@ -261,7 +317,9 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<Data, Self::Error> {
-        if imsg::mid(self.header) != zmsg::id::DATA {
+        let unit =
+            imsg::mid(self.header) == zmsg::id::UNIT && imsg::has_flag(self.header, zmsg::flag::E);
+        if imsg::mid(self.header) != zmsg::id::DATA && !unit {
             return Err(DidntRead);
         }
 
@@ -277,6 +335,17 @@ where
         };
         let key: WireExpr<'static> = ccond.read(&mut *reader)?;
 
+        if unit {
+            return Ok(Data {
+                key,
+                data_info: None,
+                payload: ZBuf::default(),
+                congestion_control,
+                reply_context: self.reply_context,
+                unit,
+            });
+        }
+
         #[cfg(feature = "shared-memory")]
         let mut is_sliced = false;
 
@@ -309,6 +378,7 @@ where
             payload,
             congestion_control,
             reply_context: self.reply_context,
+            unit,
         })
     }
 }
@@ -570,7 +570,11 @@ where
         let complete: ZInt = self.read(&mut *reader)?;
         let distance: ZInt = self.read(&mut *reader)?;
 
-        Ok(QueryableInfo { complete, distance })
+        Ok(QueryableInfo {
+            complete,
+            distance,
+            load_balanced: false,
+        })
     }
 }
 
@@ -584,9 +588,12 @@ where
     fn write(self, writer: &mut W, x: &Queryable) -> Self::Output {
         // Header
         let mut header = zmsg::declaration::id::QUERYABLE;
-        if x.info != QueryableInfo::default() {
+        if x.info.complete != 0 || x.info.distance != 0 {
             header |= zmsg::flag::Q;
         }
+        if x.info.load_balanced {
+            header |= zmsg::flag::L;
+        }
         if x.key.has_suffix() {
             header |= zmsg::flag::K;
         }
@@ -634,11 +641,12 @@ where
         };
         let key: WireExpr<'static> = ccond.read(&mut *reader)?;
 
-        let info: QueryableInfo = if imsg::has_flag(self.header, zmsg::flag::Q) {
+        let mut info: QueryableInfo = if imsg::has_flag(self.header, zmsg::flag::Q) {
             self.codec.read(&mut *reader)?
         } else {
             QueryableInfo::default()
         };
+        info.load_balanced = imsg::has_flag(self.header, zmsg::flag::L);
 
         Ok(Queryable { key, info })
     }
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+mod congestion;
 mod data;
 mod declare;
 mod linkstate;
@@ -56,6 +57,7 @@ where
             ZenohBody::Query(q) => self.write(&mut *writer, q),
             ZenohBody::Declare(d) => self.write(&mut *writer, d),
             ZenohBody::LinkStateList(l) => self.write(&mut *writer, l),
+            ZenohBody::CongestionDrop(c) => self.write(&mut *writer, c),
         }
     }
 }
@@ -119,6 +121,9 @@ where
                 };
                 match imsg::mid(rodec.header) {
                     zmsg::id::DATA => ZenohBody::Data(rodec.read(&mut *reader)?),
+                    zmsg::id::UNIT if imsg::has_flag(rodec.header, zmsg::flag::E) => {
+                        ZenohBody::Data(rodec.read(&mut *reader)?)
+                    }
                     zmsg::id::UNIT => ZenohBody::Unit(rodec.read(&mut *reader)?),
                     _ => return Err(DidntRead),
                 }
@@ -135,12 +140,17 @@ where
                     header: codec.header,
                     ..Default::default()
                 };
-                ZenohBody::Unit(rodec.read(&mut *reader)?)
+                if imsg::has_flag(rodec.header, zmsg::flag::E) {
+                    ZenohBody::Data(rodec.read(&mut *reader)?)
+                } else {
+                    ZenohBody::Unit(rodec.read(&mut *reader)?)
+                }
             }
             zmsg::id::PULL => ZenohBody::Pull(codec.read(&mut *reader)?),
             zmsg::id::QUERY => ZenohBody::Query(codec.read(&mut *reader)?),
             zmsg::id::DECLARE => ZenohBody::Declare(codec.read(&mut *reader)?),
             zmsg::id::LINK_STATE_LIST => ZenohBody::LinkStateList(codec.read(&mut *reader)?),
+            zmsg::id::CONGESTION_DROP => ZenohBody::CongestionDrop(codec.read(&mut *reader)?),
             _ => return Err(DidntRead),
         };
 
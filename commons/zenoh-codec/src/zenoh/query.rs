@@ -12,7 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::{RCodec, WCodec, Zenoh060, Zenoh060Condition, Zenoh060Header};
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
     writer::{DidntWrite, Writer},
@@ -20,7 +20,7 @@ use zenoh_buffers::{
 };
 use zenoh_protocol::{
     common::imsg,
-    core::{ConsolidationMode, QueryTarget, WireExpr, ZInt},
+    core::{ConsolidationMode, QueryTarget, WireExpr, ZInt, ZenohId},
     zenoh::{zmsg, DataInfo, Query, QueryBody},
 };
 
@@ -78,12 +78,7 @@ where
     type Output = Result<(), DidntWrite>;
 
     fn write(self, writer: &mut W, x: &ConsolidationMode) -> Self::Output {
-        let cm: ZInt = match x {
-            ConsolidationMode::None => 0,
-            ConsolidationMode::Monotonic => 1,
-            ConsolidationMode::Latest => 2,
-        };
-        self.write(&mut *writer, cm)?;
+        self.write(&mut *writer, consolidation_to_zint(x))?;
         Ok(())
     }
 }
@@ -96,13 +91,24 @@ where
 
     fn read(self, reader: &mut R) -> Result<ConsolidationMode, Self::Error> {
         let cm: ZInt = self.read(&mut *reader)?;
-        let cm = match cm {
-            0 => ConsolidationMode::None,
-            1 => ConsolidationMode::Monotonic,
-            2 => ConsolidationMode::Latest,
-            _ => return Err(DidntRead),
-        };
-        Ok(cm)
+        consolidation_from_zint(cm).ok_or(DidntRead)
+    }
+}
+
+fn consolidation_to_zint(x: &ConsolidationMode) -> ZInt {
+    match x {
+        ConsolidationMode::None => 0,
+        ConsolidationMode::Monotonic => 1,
+        ConsolidationMode::Latest => 2,
+    }
+}
+
+fn consolidation_from_zint(cm: ZInt) -> Option<ConsolidationMode> {
+    match cm {
+        0 => Some(ConsolidationMode::None),
+        1 => Some(ConsolidationMode::Monotonic),
+        2 => Some(ConsolidationMode::Latest),
+        _ => None,
     }
 }
 
@@ -161,7 +167,23 @@ where
         if let Some(t) = x.target.as_ref() {
             self.write(&mut *writer, t)?;
         }
-        self.write(&mut *writer, &x.consolidation)?;
+        let mut options = consolidation_to_zint(&x.consolidation);
+        if x.timeout.is_some() {
+            options |= zmsg::query_options::TIMEOUT;
+        }
+        if x.destinations.is_some() {
+            options |= zmsg::query_options::DESTINATIONS;
+        }
+        self.write(&mut *writer, options)?;
+        if let Some(timeout) = x.timeout {
+            self.write(&mut *writer, timeout)?;
+        }
+        if let Some(destinations) = x.destinations.as_ref() {
+            self.write(&mut *writer, destinations.len())?;
+            for zid in destinations {
+                self.write(&mut *writer, zid)?;
+            }
+        }
         if let Some(b) = x.body.as_ref() {
             self.write(&mut *writer, b)?;
         }
@@ -210,7 +232,27 @@ where
         } else {
             None
         };
-        let consolidation: ConsolidationMode = self.codec.read(&mut *reader)?;
+        let options: ZInt = self.codec.read(&mut *reader)?;
+        let consolidation =
+            consolidation_from_zint(options & zmsg::query_options::CONSOLIDATION_MASK)
+                .ok_or(DidntRead)?;
+        let timeout = if imsg::has_option(options, zmsg::query_options::TIMEOUT) {
+            let timeout: ZInt = self.codec.read(&mut *reader)?;
+            Some(timeout)
+        } else {
+            None
+        };
+        let destinations = if imsg::has_option(options, zmsg::query_options::DESTINATIONS) {
+            let len: usize = self.codec.read(&mut *reader)?;
+            let mut destinations = Vec::with_capacity(len.min(64));
+            for _ in 0..len {
+                let zid: ZenohId = self.codec.read(&mut *reader)?;
+                destinations.push(zid);
+            }
+            Some(destinations)
+        } else {
+            None
+        };
         let body = if imsg::has_flag(self.header, zmsg::flag::B) {
             let qb: QueryBody = self.codec.read(&mut *reader)?;
             Some(qb)
@@ -224,6 +266,8 @@ where
             qid,
             target,
             consolidation,
+            timeout,
+            destinations,
             body,
         })
     }
@@ -75,7 +75,8 @@ where
     type Error = DidntRead;
 
     fn read(self, _reader: &mut R) -> Result<Unit, Self::Error> {
-        if imsg::mid(self.header) != zmsg::id::UNIT {
+        // A Unit with the E flag is a Data
+        if imsg::mid(self.header) != zmsg::id::UNIT || imsg::has_flag(self.header, zmsg::flag::E) {
             return Err(DidntRead);
         }
 
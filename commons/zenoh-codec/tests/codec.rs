@@ -179,6 +179,40 @@ fn codec_hello() {
     run!(Hello, Hello::rand());
 }
 
+#[test]
+fn codec_hello_metadata() {
+    run!(HelloMetadata, HelloMetadata::rand());
+
+    // The unknown metadata, e.g. added by a newer version, are skipped
+    let ps = vec![
+        Property {
+            key: HelloMetadata::CLUSTER,
+            value: b"cluster".to_vec(),
+        },
+        Property {
+            key: 0xff,
+            value: b"unknown".to_vec(),
+        },
+        Property {
+            key: HelloMetadata::LOAD,
+            value: vec![42],
+        },
+    ];
+    let mut buff = vec![];
+    let codec = Zenoh060::default();
+    codec.write(&mut buff.writer(), ps.as_slice()).unwrap();
+    let mut reader = buff.reader();
+    let metadata: HelloMetadata = codec.read(&mut reader).unwrap();
+    assert_eq!(
+        metadata,
+        HelloMetadata {
+            cluster: Some("cluster".to_string()),
+            load: Some(42),
+            ..Default::default()
+        }
+    );
+}
+
 #[test]
 fn codec_scout() {
     run!(Scout, Scout::rand());
@@ -225,6 +259,32 @@ fn codec_keep_alive() {
     run!(KeepAlive, KeepAlive::rand());
 }
 
+#[test]
+fn codec_preamble() {
+    run!(Preamble, Preamble::rand());
+
+    let preamble = Preamble::new();
+    let mut bytes = vec![0x5a, 0x4e, 0x00, 0x5a];
+    bytes.extend_from_slice(&preamble.to_bytes());
+    bytes.extend_from_slice(&[0x02, 0x00]);
+    assert_eq!(find_preamble(&bytes, Preamble::VERSION), Some(4));
+    assert_eq!(find_preamble(&bytes[5..], Preamble::VERSION), None);
+    assert_eq!(find_preamble(&bytes, Preamble::VERSION + 1), None);
+
+    let mut reader = bytes[4..].reader();
+    let codec = Zenoh060::default();
+    let read: Preamble = codec.read(&mut reader).unwrap();
+    assert_eq!(read, preamble);
+    let mut reader = bytes.reader();
+    assert!(RCodec::<Preamble, _>::read(codec, &mut reader).is_err());
+}
+
+#[test]
+fn codec_ping_pong() {
+    run!(Ping, Ping::rand());
+    run!(Pong, Pong::rand());
+}
+
 #[test]
 fn codec_frame_header() {
     run!(FrameHeader, FrameHeader::rand());
@@ -256,11 +316,58 @@ fn codec_data_info() {
     run!(DataInfo, DataInfo::rand());
 }
 
+#[test]
+fn codec_trace_context() {
+    run!(TraceContext, TraceContext::rand());
+}
+
 #[test]
 fn codec_data() {
     run!(Data, Data::rand());
 }
 
+#[test]
+fn codec_data_unit() {
+    let unit = || {
+        let mut data = Data::rand();
+        data.data_info = None;
+        data.payload = ZBuf::default();
+        data
+    };
+    run!(Data, unit());
+
+    // A Data without info nor payload is still encoded as a Data, its empty payload being
+    // read without retaining any slice of the buffer it is read from
+    let mut data = unit();
+    data.reply_context = None;
+    let mut buff = vec![];
+    let codec = Zenoh060::default();
+    codec.write(&mut buff.writer(), &data).unwrap();
+    assert_eq!(imsg::mid(buff[0]), zmsg::id::DATA);
+
+    let mut reader = buff.reader();
+    let read: Data = codec.read(&mut reader).unwrap();
+    assert_eq!(read, data);
+    assert_eq!(read.payload.zslices().count(), 0);
+
+    // Unless it is to be encoded as a Unit, on the transports that agreed on it
+    data.unit = true;
+    run!(Data, data.clone());
+    let mut buff = vec![];
+    codec.write(&mut buff.writer(), &data).unwrap();
+    assert_eq!(imsg::mid(buff[0]), zmsg::id::UNIT);
+    assert!(imsg::has_flag(buff[0], zmsg::flag::E));
+
+    let mut reader = buff.reader();
+    let read: Data = codec.read(&mut reader).unwrap();
+    assert_eq!(read, data);
+    let mut reader = buff.reader();
+    let read: ZenohMessage = codec.read(&mut reader).unwrap();
+    assert!(matches!(read.body, ZenohBody::Data(_)));
+    let mut reader = buff.reader();
+    assert!(RCodec::<Unit, _>::read(codec, &mut reader).is_err());
+}
+
 #[test]
 fn codec_unit() {
     run!(Unit, Unit::rand());
@@ -336,6 +443,11 @@ fn codec_link_state_list() {
     run!(LinkStateList, LinkStateList::rand());
 }
 
+#[test]
+fn codec_congestion_drop() {
+    run!(CongestionDrop, CongestionDrop::rand());
+}
+
 #[test]
 fn codec_zenoh() {
     run!(
@@ -39,6 +39,7 @@ pub mod scouting {
         pub const enabled: bool = true;
         pub const address: ([u8; 4], u16) = ([224, 0, 0, 224], 7446);
         pub const interface: &str = "auto";
+        pub const ttl: u32 = 1;
         pub mod autoconnect {
             pub const router: &crate::WhatAmIMatcher = // ""
                 &crate::WhatAmIMatcher(unsafe { std::num::NonZeroU8::new_unchecked(128) });
@@ -55,6 +56,24 @@ pub mod scouting {
             mode_accessor!(bool);
         }
     }
+    pub mod mdns {
+        pub const enabled: bool = false;
+        pub mod autoconnect {
+            pub const router: &crate::WhatAmIMatcher = // ""
+                &crate::WhatAmIMatcher(unsafe { std::num::NonZeroU8::new_unchecked(128) });
+            pub const peer: &crate::WhatAmIMatcher = // "router|peer"
+                &crate::WhatAmIMatcher(unsafe { std::num::NonZeroU8::new_unchecked(131) });
+            pub const client: &crate::WhatAmIMatcher = // "router|peer"
+                &crate::WhatAmIMatcher(unsafe { std::num::NonZeroU8::new_unchecked(131) });
+            mode_accessor!(crate::WhatAmIMatcher);
+        }
+        pub mod advertise {
+            pub const router: &bool = &true;
+            pub const peer: &bool = &true;
+            pub const client: &bool = &false;
+            mode_accessor!(bool);
+        }
+    }
     pub mod gossip {
         pub const enabled: bool = true;
         pub const multihop: bool = false;
@@ -94,6 +113,54 @@ pub mod routing {
     }
     pub mod peer {
         pub const mode: &str = "peer_to_peer";
+        pub const forwarding: bool = false;
+    }
+    pub mod congestion_drop {
+        pub const notify: bool = false;
+        pub const interval: u64 = 1000;
+    }
+    pub mod latched {
+        pub const max_samples: usize = 10000;
+    }
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod slow_consumer {
+    pub const threshold: u64 = 0;
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod watchdog {
+    pub const threshold: u64 = 0;
+    pub const policy: &str = "log";
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod executor {
+    pub const mode: &str = "global";
+    pub const threads: usize = 1;
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod health {
+    pub const min_routers: usize = 0;
+    pub const min_peers: usize = 0;
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod audit {
+    pub mod file {
+        pub const max_size: u64 = 10485760;
+        pub const max_files: usize = 5;
+    }
+    pub mod syslog {
+        pub const enabled: bool = false;
+        pub const socket: &str = "/dev/log";
     }
 }
 
@@ -104,6 +171,10 @@ impl Default for TransportUnicastConf {
             accept_pending: Some(100),
             max_sessions: Some(1000),
             max_links: Some(1),
+            replay_window: Some(10000),
+            replay_cache_size: Some(1024),
+            ping_interval: Some(0),
+            rtt_scheduling: Some(false),
         }
     }
 }
@@ -113,6 +184,8 @@ impl Default for TransportMulticastConf {
         Self {
             join_interval: Some(2500),
             max_sessions: Some(1000),
+            join_key: None,
+            ignore: vec![],
         }
     }
 }
@@ -131,9 +204,14 @@ impl Default for LinkTxConf {
             sequence_number_resolution: Some((2 as ZInt).pow(28)),
             lease: Some(10000),
             keep_alive: Some(4),
+            adaptive_keep_alive: Some(true),
+            preamble: Some(false),
             batch_size: Some(u16::MAX),
+            max_message_size: Some(2_usize.pow(30)),
             queue: QueueConf::default(),
             threads: Some(num),
+            affinity: None,
+            priority: None,
         }
     }
 }
@@ -143,6 +221,8 @@ impl Default for QueueConf {
         Self {
             size: QueueSizeConf::default(),
             backoff: Some(100),
+            watermarks: QueueWatermarksConf::default(),
+            pacing: QueuePacingConf::default(),
         }
     }
 }
@@ -172,6 +252,32 @@ impl Default for LinkRxConf {
         Self {
             buffer_size: Some(u16::MAX as usize),
             max_message_size: Some(2_usize.pow(30)),
+            max_decode_errors: Some(3),
+            callback_workers: Some(0),
+            affinity: None,
+            priority: None,
+            limits: LinkRxLimitsConf::default(),
+        }
+    }
+}
+
+impl Default for LinkPoolConf {
+    fn default() -> Self {
+        Self {
+            size: Some(8 * 1024 * 1024),
+        }
+    }
+}
+
+impl Default for PskConf {
+    fn default() -> Self {
+        Self {
+            key_id: None,
+            key: None,
+            keys: vec![],
+            keys_file: None,
+            max_failures: Some(5),
+            lockout_duration: Some(60000),
         }
     }
 }
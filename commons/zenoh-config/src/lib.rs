@@ -14,6 +14,7 @@
 
 //! Configuration to pass to `zenoh::open()` and `zenoh::scout()` functions and associated constants.
 pub mod defaults;
+pub mod secrets;
 use serde::{
     de::{self, MapAccess, Visitor},
     Deserialize, Serialize,
@@ -134,7 +135,13 @@ validated_struct::validator! {
                 /// Whether multicast scouting is enabled or not. If left empty, `zenohd` will set it according to the presence of the `--no-multicast-scouting` argument.
                 enabled: Option<bool>,
                 /// The socket which should be used for multicast scouting. `zenohd` will use `224.0.0.224:7446` by default if none is provided.
+                /// It may be an IPv6 group, e.g. `[ff02::224]:7446`.
                 address: Option<SocketAddr>,
+                /// An IPv6 group to scout on simultaneously with `address`, e.g. `[ff02::224]:7446`.
+                /// The Hello messages received from a same instance on both groups are only handled once.
+                address_v6: Option<SocketAddr>,
+                /// The time-to-live (IPv4) or hop limit (IPv6) of the multicast scout messages.
+                ttl: Option<u32>,
                 /// The network interface which should be used for multicast scouting. `zenohd` will automatically select an interface if none is provided.
                 interface: Option<String>,
                 /// Which type of Zenoh instances to automatically establish sessions with upon discovery through UDP multicast.
@@ -143,6 +150,27 @@ validated_struct::validator! {
                 /// Whether or not to listen for scout messages on UDP multicast and reply to them.
                 listen: Option<ModeDependentValue<bool>>,
             },
+            /// The unicast scouting configuration, for the networks where multicast is blocked.
+            /// The Hello messages received in reply are handled along with the multicast ones, according to `multicast.autoconnect`.
+            pub unicast: #[derive(Default)]
+            ScoutingUnicastConf {
+                /// The UDP addresses of the gateways to send the scout messages to.
+                pub gateways: Vec<SocketAddr>,
+                /// The UDP address on which to listen for unicast scout messages and reply to them.
+                listen: Option<SocketAddr>,
+            },
+            /// The mDNS/DNS-SD scouting configuration, advertising and browsing the `_zenoh._tcp` and `_zenoh._udp` services.
+            /// It coexists with the multicast scouting and requires zenoh to be built with the `mdns` feature.
+            pub mdns: #[derive(Default)]
+            ScoutingMdnsConf {
+                /// Whether mDNS scouting is enabled or not.
+                enabled: Option<bool>,
+                /// Which type of Zenoh instances to automatically establish sessions with upon discovery through mDNS.
+                #[serde(deserialize_with = "treat_error_as_none")]
+                autoconnect: Option<ModeDependentValue<WhatAmIMatcher>>,
+                /// Whether or not to advertise the tcp and udp locators of this instance.
+                advertise: Option<ModeDependentValue<bool>>,
+            },
             /// The gossip scouting configuration.
             pub gossip: #[derive(Default)]
             GossipConf {
@@ -158,6 +186,48 @@ validated_struct::validator! {
                 #[serde(deserialize_with = "treat_error_as_none")]
                 autoconnect: Option<ModeDependentValue<WhatAmIMatcher>>,
             },
+            /// The metadata advertised in the Hello messages replied to the scouts.
+            pub hello: #[derive(Default)]
+            HelloConf {
+                /// The name of the cluster this instance belongs to.
+                cluster: Option<String>,
+                /// The region this instance is located in.
+                region: Option<String>,
+                /// A hint of the load of this instance, from 0 (idle) to 100 (saturated).
+                load: Option<u8>,
+                /// The protocol features supported by this instance.
+                features: Option<Vec<String>>,
+            },
+            /// Restricts the sessions automatically established upon scouting to the instances whose Hello metadata matches.
+            /// The instances not advertising any metadata only match the filters without `cluster`, `region` and `features`.
+            pub filter: #[derive(Default)]
+            ScoutingFilterConf {
+                /// Only connect to the instances of this cluster.
+                cluster: Option<String>,
+                /// Only connect to the instances located in this region.
+                region: Option<String>,
+                /// Only connect to the instances whose advertised load doesn't exceed this value.
+                max_load: Option<u8>,
+                /// Only connect to the instances supporting all these features.
+                features: Option<Vec<String>>,
+                /// Only connect to the instances matching these rules. The zid rules are checked again
+                /// against the zid of the opened session, which is closed if it doesn't match them.
+                pub allow: #[derive(Default)]
+                ScoutingAllowConf {
+                    /// The zids of the instances to connect to. A trailing `*` matches any zid starting with the given prefix.
+                    zids: Vec<String>,
+                    /// The subnets of the locators to connect to, in CIDR notation. The locators outside them are ignored.
+                    subnets: Vec<String>,
+                } where (scouting_allow_validator),
+                /// Never connect to the instances matching these rules, even if allowed.
+                pub deny: #[derive(Default)]
+                ScoutingDenyConf {
+                    /// The zids of the instances not to connect to. A trailing `*` matches any zid starting with the given prefix.
+                    zids: Vec<String>,
+                    /// The subnets of the locators not to connect to, in CIDR notation.
+                    subnets: Vec<String>,
+                } where (scouting_deny_validator),
+            },
         },
 
         /// Configuration of data messages timestamps management.
@@ -191,6 +261,35 @@ validated_struct::validator! {
             PeerRoutingConf {
                 /// The routing strategy to use in peers. ("peer_to_peer" or "linkstate").
                 mode: Option<String>,
+                /// When set to true a "peer_to_peer" peer forwards the data and queries
+                /// between the nodes it is connected to and not connected to each other, as a
+                /// router would. The data are timestamped and routed only once by each
+                /// forwarding peer.
+                forwarding: Option<bool>,
+            },
+            /// A list of key-expressions for which the data messages and bytes received and
+            /// transmitted on the included key-expressions are accounted.
+            accounting: Vec<OwnedKeyExpr>,
+            /// The notification of the best-effort data messages dropped because of congestion.
+            pub congestion_drop: #[derive(Default)]
+            CongestionDropConf {
+                /// When set to true, the number of data messages dropped because of congestion while
+                /// being routed is notified back to the node they were received from.
+                notify: Option<bool>,
+                /// The minimum interval in milliseconds between two notifications to a same node.
+                interval: Option<ZInt>,
+            },
+            /// The interceptors applied, in order, on the messages exchanged with the remote nodes.
+            interceptors: Vec<InterceptorConf>,
+            /// The static routes forwarding the data routed on some key-expressions to a given next hop,
+            /// taking precedence over the learned routes while the next hop is connected.
+            static_routes: Vec<StaticRouteConf>,
+            /// The latched samples kept by this node for the late subscribers.
+            pub latched: #[derive(Default)]
+            LatchedConf {
+                /// The maximum number of key expressions whose latched sample is kept, the samples
+                /// published on other key expressions being not latched once it is reached (default: 10000).
+                max_samples: Option<usize>,
             },
         },
 
@@ -202,6 +301,13 @@ validated_struct::validator! {
             /// A list of key-expressions for which all included publishers will be aggregated into.
             publishers: Vec<OwnedKeyExpr>,
         },
+
+        /// Payload transformations applied on the publication and delivery paths.
+        pub transform: #[derive(Default)]
+        TransformConf {
+            /// A list of rules encrypting the payloads published on the matching key-expressions.
+            encryption: Vec<EncryptionRule>,
+        },
         pub transport: #[derive(Default)]
         TransportConf {
             pub unicast: TransportUnicastConf {
@@ -213,12 +319,30 @@ validated_struct::validator! {
                 max_sessions: Option<usize>,
                 /// Maximum number of unicast incoming links per transport session (default: 1)
                 max_links: Option<usize>,
+                /// Window in milliseconds within which the handshake cookies received on connectionless links
+                /// (e.g. UDP) are accepted. Older cookies are considered replayed (default: 10000).
+                replay_window: Option<ZInt>,
+                /// Maximum number of handshake cookies remembered within the window to detect replays
+                /// on connectionless links. Handshakes are rejected while the cache is full (default: 1024).
+                replay_cache_size: Option<usize>,
+                /// Interval in milliseconds at which each link is pinged to measure its round-trip time.
+                /// The remote peers must support the ping messages. Set to 0 to disable (default: 0).
+                ping_interval: Option<ZInt>,
+                /// Whether the messages are scheduled on the suitable link with the lowest average round-trip
+                /// time rather than on the first one. Requires `ping_interval` (default: false).
+                rtt_scheduling: Option<bool>,
             },
             pub multicast: TransportMulticastConf {
                 /// Link join interval duration in milliseconds (default: 2500)
                 join_interval: Option<ZInt>,
                 /// Maximum number of multicast sessions (default: 1000)
                 max_sessions: Option<usize>,
+                /// The group key signing the Join messages, the traffic of the peers whose Join messages are not
+                /// signed with the same key is ignored. It may reference a secret, see [`SecretsConf`].
+                /// This is not an authentication of the peers, as the signed Join messages can be replayed.
+                join_key: Option<String>,
+                /// The ids of the peers whose Join messages, and thus traffic, are ignored.
+                ignore: Vec<String>,
             },
             pub qos: QoSConf {
                 /// Whether QoS is enabled or not.
@@ -230,6 +354,8 @@ validated_struct::validator! {
                 // An optional whitelist of protocols to be used for accepting and opening sessions.
                 // If not configured, all the supported protocols are automatically whitelisted.
                 pub protocols: Option<Vec<String>>,
+                // An optional blacklist of protocols, applied on top of the whitelist.
+                pub denied_protocols: Option<Vec<String>>,
                 pub tx: LinkTxConf {
                     /// The largest value allowed for Zenoh message sequence numbers (wrappring to 0 when reached). When establishing a session with another Zenoh instance, the lowest value of the two instances will be used.
                     /// Defaults to 2^28.
@@ -238,8 +364,15 @@ validated_struct::validator! {
                     lease: Option<ZInt>,
                     /// Number fo keep-alive messages in a link lease duration (default: 4)
                     keep_alive: Option<usize>,
+                    /// Whether the keep-alive messages are only sent on idle unicast links, any traffic keeping the link alive (default: true)
+                    adaptive_keep_alive: Option<bool>,
+                    /// Whether the batches of stream links are preceded by a preamble, if the remote side enables it too (default: false)
+                    preamble: Option<bool>,
                     /// Zenoh's MTU equivalent (default: 2^16-1)
                     batch_size: Option<u16>,
+                    /// Maximum size in bytes of a message sent on the links (default: 1GiB).
+                    /// Larger messages are refused with an error rather than fragmented.
+                    max_message_size: Option<usize>,
                     pub queue: QueueConf {
                         /// The size of each priority queue indicates the number of batches a given queue can contain.
                         /// The amount of memory being allocated for each queue is then SIZE_XXX * BATCH_SIZE.
@@ -258,10 +391,45 @@ validated_struct::validator! {
                         } where (queue_size_validator),
                         /// The initial exponential backoff time in nanoseconds to allow the batching to eventually progress.
                         /// Higher values lead to a more aggressive batching but it will introduce additional latency.
-                        backoff: Option<ZInt>
+                        backoff: Option<ZInt>,
+                        /// The watermarks of the queues of the unicast links, in number of batches waiting to be sent for all the priorities.
+                        /// An event is emitted when the high watermark is reached and when the queue drains down to the low one.
+                        pub watermarks: #[derive(Default)]
+                        QueueWatermarksConf {
+                            /// The high watermark, disabled if unset (default: unset).
+                            high: Option<usize>,
+                            /// The low watermark, lower than the high one (default: half of the high one).
+                            low: Option<usize>,
+                            /// Whether the data_low and background messages with the drop congestion control are dropped
+                            /// while the queue is above its high watermark (default: false).
+                            drop_low_priority: Option<bool>,
+                        },
+                        /// The pacing of the queues of the links, spreading the batches of the paced priorities over time
+                        /// without dropping them.
+                        pub pacing: #[derive(Default)]
+                        QueuePacingConf {
+                            /// The pacing interval in microseconds (default: 1000).
+                            interval: Option<ZInt>,
+                            /// The maximum number of bytes sent per interval for each priority, unpaced if unset (default: unset).
+                            pub bytes: #[derive(Default)]
+                            QueuePacingBytesConf {
+                                control: Option<usize>,
+                                real_time: Option<usize>,
+                                interactive_high: Option<usize>,
+                                interactive_low: Option<usize>,
+                                data_high: Option<usize>,
+                                data: Option<usize>,
+                                data_low: Option<usize>,
+                                background: Option<usize>,
+                            },
+                        },
                     },
                     // Number of threads used for TX
                     threads: Option<usize>,
+                    /// CPU cores the TX threads are pinned to (default: any core). Linux only.
+                    affinity: Option<Vec<usize>>,
+                    /// Real-time (SCHED_FIFO) priority of the TX threads, from 1 to 99 (default: none). Linux only.
+                    priority: Option<u8>,
                 },
                 pub rx: LinkRxConf {
                     /// Receiving buffer size in bytes for each link
@@ -273,6 +441,32 @@ validated_struct::validator! {
                     /// Maximum size of the defragmentation buffer at receiver end (default: 1GiB).
                     /// Fragmented messages that are larger than the configured size will be dropped.
                     max_message_size: Option<usize>,
+                    /// Maximum number of consecutive batches failing to decode before closing the link (default: 3).
+                    /// The messages skipped on a reliable link are lost, the reception resuming with the next batch.
+                    max_decode_errors: Option<usize>,
+                    /// Number of threads running the callbacks of the received messages (default: 0).
+                    /// With 0, the callbacks run in the rx task of the link, which a slow callback blocks.
+                    callback_workers: Option<usize>,
+                    /// CPU cores the callback workers are pinned to (default: any core). Linux only.
+                    affinity: Option<Vec<usize>>,
+                    /// Real-time (SCHED_FIFO) priority of the callback workers, from 1 to 99 (default: none). Linux only.
+                    priority: Option<u8>,
+                    /// Ingress limits of each unicast link, checked before decoding (default: none).
+                    /// A peer exceeding them has its transport closed.
+                    pub limits: #[derive(Default)]
+                    LinkRxLimitsConf {
+                        /// Maximum size in bytes of a received frame.
+                        max_frame_size: Option<usize>,
+                        /// Maximum size in bytes of a message reassembled from fragments.
+                        max_message_size: Option<usize>,
+                        /// Maximum number of frames received per second.
+                        max_batch_rate: Option<u32>,
+                    },
+                },
+                pub pool: LinkPoolConf {
+                    /// Maximum amount of memory in bytes kept for reuse by the pool of tx batches and rx buffers
+                    /// shared by all the links, once released by a closed link (default: 8MiB). Set to 0 to disable.
+                    size: Option<usize>,
                 },
                 pub tls: #[derive(Default)]
                 TLSConf {
@@ -282,6 +476,11 @@ validated_struct::validator! {
                     client_auth: Option<bool>,
                     client_private_key: Option<String>,
                     client_certificate: Option<String>,
+                    /// Period in milliseconds at which the certificate and key files are checked for changes and reloaded.
+                    /// Existing sessions are not affected by a reload. Set to 0 to disable (default: 10000).
+                    reload_interval: Option<ZInt>,
+                    /// Delay in seconds before the certificate expiry at which a warning is emitted (default: 604800).
+                    expiry_warning: Option<ZInt>,
                 },
             },
             pub shared_memory: SharedMemoryConf {
@@ -309,6 +508,51 @@ validated_struct::validator! {
                     key_size: Option<usize>,
                     known_keys_file: Option<String>,
                 },
+                /// The configuration of pre-shared-key authentication.
+                /// A key implies a key-id is required.
+                pub psk: PskConf {
+                    /// The key-id presented when opening a session.
+                    key_id: Option<String>,
+                    /// The hex encoded key associated to `key_id`.
+                    key: Option<String>,
+                    /// The keys accepted when a session is opened by a remote peer.
+                    keys: Vec<PskKey>,
+                    /// The path to a file containing additional accepted keys, a file containing `<key-id>:<hex key>`
+                    keys_file: Option<String>,
+                    /// Number of consecutive failed attempts after which a key-id is locked out (default: 5).
+                    max_failures: Option<usize>,
+                    /// Duration in milliseconds during which a locked out key-id is rejected (default: 60000).
+                    lockout_duration: Option<ZInt>,
+                } where (psk_conf_validator),
+                /// The resolution of the secrets of this section. Besides inline values, `user`, `password`,
+                /// `private_key_pem`, PSK keys and TLS private keys may reference `env:<VAR>`, `file:<PATH>`
+                /// or `exec:<NAME>`.
+                pub secrets: #[derive(Default)]
+                SecretsConf {
+                    /// The command run to resolve the `exec:<NAME>` secrets, `NAME` is passed as its last argument.
+                    exec_provider: Option<String>,
+                    /// Whether secret files accessible by group or others are rejected instead of reported (default: false).
+                    strict_permissions: Option<bool>,
+                },
+            },
+            /// Quotas enforced on the messages received from remote nodes on unicast transports.
+            /// A transport exceeding one of its quotas is closed. Unset quotas are not enforced.
+            ///
+            /// The quotas protect against misbehaving nodes, not against malicious ones: they are keyed
+            /// on the zenoh id a remote node declares, which is not authenticated. A node can thus claim
+            /// the quotas of another one, or escape its own ones by opening transports with other zenoh ids.
+            pub quotas: #[derive(Default)]
+            QuotasConf {
+                /// Maximum number of subscribers declared by a remote node.
+                max_subscribers: Option<usize>,
+                /// Maximum number of queryables declared by a remote node.
+                max_queryables: Option<usize>,
+                /// Maximum number of queries issued by a remote node still waiting for their final reply.
+                max_pending_queries: Option<usize>,
+                /// Maximum number of payload bytes per second published by a remote node.
+                max_bytes_per_sec: Option<usize>,
+                /// Quotas overriding the above ones for specific remote nodes.
+                identities: Vec<QuotaRule>,
             },
         },
         /// Configuration of the admin space.
@@ -329,7 +573,105 @@ validated_struct::validator! {
                 #[serde(default = "set_false")]
                 pub write: bool,
             },
-
+            /// The captures started through the admin space.
+            pub capture: #[derive(Default)]
+            AdminSpaceCaptureConf {
+                /// The directory in which the capture files are created, the captures to a file
+                /// being refused if not set.
+                directory: Option<String>,
+            },
+        },
+        /// Configuration of the audit log, recording security-relevant events (sessions opened and closed,
+        /// authentication failures, access denials and admin space configuration changes).
+        /// The audit log is kept separate from the debug logging and is disabled if no sink is configured.
+        pub audit: #[derive(Default)]
+        AuditConf {
+            /// Appends the audit records to a file.
+            pub file: #[derive(Default)]
+            AuditFileConf {
+                /// The path of the audit log file.
+                path: Option<String>,
+                /// Size in bytes above which the file is rotated, 0 disables the rotation (default: 10485760).
+                max_size: Option<u64>,
+                /// Number of rotated files kept next to the current one (default: 5).
+                max_files: Option<usize>,
+            },
+            /// Sends the audit records to the local syslog daemon (unix only).
+            pub syslog: #[derive(Default)]
+            AuditSyslogConf {
+                /// Whether the audit records are sent to syslog (default: false).
+                enabled: Option<bool>,
+                /// The path of the syslog socket (default: "/dev/log").
+                socket: Option<String>,
+            },
+        },
+        /// Detection of the slow consumers: the peers whose tx queue and the subscribers whose delivery
+        /// stay saturated longer than the threshold are reported.
+        pub slow_consumer: #[derive(Default)]
+        SlowConsumerConf {
+            /// Duration in milliseconds above which a saturated consumer is reported, 0 disables the
+            /// detection (default: 0).
+            threshold: Option<ZInt>,
+        },
+        /// Memory budget of the runtime, accounting the memory of the defragmentation buffers, the tx queues and
+        /// the routing tables.
+        pub memory: #[derive(Default)]
+        MemoryConf {
+            /// The memory budget in bytes, unlimited if not set.
+            limit: Option<usize>,
+            /// The percentage of the budget above which the policy applies (default: 90).
+            threshold: Option<u8>,
+            /// The actions taken while the memory used is above the threshold.
+            pub policy: #[derive(Default)]
+            MemoryPolicyConf {
+                /// Reject the new sessions (default: true).
+                reject_sessions: Option<bool>,
+                /// Drop the best effort messages (default: true).
+                drop_best_effort: Option<bool>,
+                /// Defer the subscribers and queryables declared by the remote nodes until the memory recovers (default: false).
+                shed_declarations: Option<bool>,
+            },
+        },
+        /// Watchdog of the internal tasks (links rx and tx tasks, scouting), reporting the tasks making no progress.
+        /// The watchdog is shared by all the zenoh instances of the process and started by the first one enabling it.
+        pub watchdog: #[derive(Default)]
+        WatchdogConf {
+            /// Duration in milliseconds without progress above which a task is reported as stalled, 0 disables
+            /// the watchdog (default: 0).
+            threshold: Option<ZInt>,
+            /// What to do with a stalled task: "log" logs a diagnostic of the task, "restart" also restarts it
+            /// when possible, e.g. by closing the link of a stalled link task (default: "log").
+            policy: Option<String>,
+        },
+        /// The executor on which the internal tasks are spawned. It is shared by all the zenoh instances of the
+        /// process and initialized by the first one.
+        pub executor: #[derive(Default)]
+        ExecutorConf {
+            /// "global" spawns the tasks on the global async-std executor, "threads" on `threads` dedicated threads,
+            /// "current_thread" on the threads blocking on zenoh operations (default: "global").
+            mode: Option<String>,
+            /// The number of threads of the "threads" mode (default: 1).
+            threads: Option<usize>,
+        },
+        /// Configuration of the health endpoint of zenohd, answering the liveness and readiness probes
+        /// (e.g. of Kubernetes) over HTTP.
+        pub health: #[derive(Default)]
+        HealthConf {
+            /// The address on which the health endpoint listens, with format `<local_ip>:<port_number>`.
+            /// The health endpoint is disabled if not set.
+            listen: Option<String>,
+            /// Minimum number of connected routers for zenohd to be ready (default: 0).
+            min_routers: Option<usize>,
+            /// Minimum number of connected peers for zenohd to be ready (default: 0).
+            min_peers: Option<usize>,
+        },
+        /// Configuration of the logging.
+        pub logging: #[derive(Default)]
+        LoggingConf {
+            /// Per-module log filters with format `<module>=<level>,...` (e.g. "zenoh_transport=debug"),
+            /// taking precedence over the `RUST_LOG` environment variable for these modules.
+            /// They can be changed at runtime, e.g. through the admin space.
+            filters: Option<String>,
         },
         /// A list of directories where plugins may be searched for if no `__path__` was specified for them.
         /// The executable's current directory will be added to the search paths.
@@ -351,6 +693,99 @@ impl Default for PermissionsConf {
     }
 }
 
+/// A payload encryption rule, see [`TransformConf`].
+///
+/// Exactly one of `key` and `key_file` must be provided. Keys are 256-bit long and hex encoded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptionRule {
+    /// The key-expressions whose payloads are encrypted with this rule.
+    pub key_exprs: Vec<OwnedKeyExpr>,
+    /// The hex encoded key.
+    #[serde(default)]
+    pub key: Option<String>,
+    /// The path to a file containing the hex encoded key.
+    #[serde(default)]
+    pub key_file: Option<String>,
+}
+
+/// A pre-shared key accepted by the PSK authentication, see [`PskConf`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PskKey {
+    /// The key-id presented by the remote peer.
+    pub id: String,
+    /// The hex encoded key.
+    pub key: String,
+}
+
+/// Quotas applying to specific remote nodes, see [`QuotasConf`].
+///
+/// Unset quotas fall back on the ones of [`QuotasConf`]. As the zenoh ids are declared by the
+/// remote nodes themselves, any node can claim the quotas of a rule by using one of its `zids`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuotaRule {
+    /// The zenoh ids of the remote nodes this rule applies to.
+    pub zids: Vec<ZenohId>,
+    #[serde(default)]
+    pub max_subscribers: Option<usize>,
+    #[serde(default)]
+    pub max_queryables: Option<usize>,
+    #[serde(default)]
+    pub max_pending_queries: Option<usize>,
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<usize>,
+}
+
+/// The direction of the messages an interceptor is applied to, see [`InterceptorConf`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterceptorFlow {
+    /// The messages received from a remote node, before they are routed.
+    Ingress,
+    /// The messages sent to a remote node, once routed.
+    Egress,
+}
+
+/// An interceptor applied on the messages exchanged with remote nodes, see [`RoutingConf`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterceptorConf {
+    /// The name under which the factory of the interceptor is registered.
+    pub name: String,
+    /// The flows the interceptor is applied to (default: both).
+    #[serde(default)]
+    pub flows: Option<Vec<InterceptorFlow>>,
+    /// The network interfaces the interceptor is applied to (default: all).
+    /// A remote node is intercepted if one of the links it is connected with when its session
+    /// is opened has a local address on one of these interfaces.
+    #[serde(default)]
+    pub interfaces: Option<Vec<String>>,
+    /// The remote nodes the interceptor is applied to (default: all).
+    #[serde(default)]
+    pub zids: Option<Vec<ZenohId>>,
+    /// The configuration given to the factory of the interceptor.
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+/// A static route forwarding data to a given next hop, see [`RoutingConf`].
+///
+/// Exactly one of `zid` and `endpoint` must be set.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StaticRouteConf {
+    /// The key expressions the data routed on is forwarded to the next hop.
+    pub key_exprs: Vec<OwnedKeyExpr>,
+    /// The zenoh id of the next hop.
+    #[serde(default)]
+    pub zid: Option<ZenohId>,
+    /// The endpoint the next hop is connected with.
+    #[serde(default)]
+    pub endpoint: Option<EndPoint>,
+}
+
 fn set_true() -> bool {
     true
 }
@@ -767,6 +1202,31 @@ fn user_conf_validator(u: &UserConf) -> bool {
     (u.password().is_none() && u.user().is_none()) || (u.password().is_some() && u.user().is_some())
 }
 
+fn psk_conf_validator(p: &PskConf) -> bool {
+    p.key().is_none() == p.key_id().is_none()
+}
+
+fn zid_patterns_validator(zids: &[String]) -> bool {
+    zids.iter().all(|zid| {
+        let prefix = zid.strip_suffix('*').unwrap_or(zid);
+        prefix.len() <= 32 && prefix.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn subnets_validator(subnets: &[String]) -> bool {
+    subnets
+        .iter()
+        .all(|subnet| subnet.parse::<zenoh_util::net::IpSubnet>().is_ok())
+}
+
+fn scouting_allow_validator(a: &ScoutingAllowConf) -> bool {
+    zid_patterns_validator(a.zids()) && subnets_validator(a.subnets())
+}
+
+fn scouting_deny_validator(d: &ScoutingDenyConf) -> bool {
+    zid_patterns_validator(d.zids()) && subnets_validator(d.subnets())
+}
+
 /// This part of the configuration is highly dynamic (any [`serde_json::Value`] may be put in there), but should follow this scheme:
 /// ```javascript
 /// plugins: {
@@ -784,6 +1244,9 @@ fn user_conf_validator(u: &UserConf) -> bool {
 ///         // If any path is specified, file-search will be disabled, and the first path leading to
 ///         // an existing file will be used
 ///         __path__: string | [string],
+///         // Defaults to `"never"`. With `"on-failure"`, `zenohd` will retry starting the plugin with
+///         // an exponential backoff should it fail to start, crash or exit.
+///         __restart__: "never" | "on-failure",
 ///         // [plugin_name] may require additional configuration
 ///         ...
 ///     }
@@ -804,11 +1267,18 @@ pub fn sift_privates(value: &mut serde_json::Value) {
         }
     }
 }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PluginRestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+}
 #[derive(Debug, Clone)]
 pub struct PluginLoad {
     pub name: String,
     pub paths: Option<Vec<String>>,
     pub required: bool,
+    pub restart: PluginRestartPolicy,
 }
 impl PluginsConfig {
     pub fn sift_privates(&mut self) {
@@ -822,15 +1292,21 @@ impl PluginsConfig {
                 Some(Value::Bool(b)) => *b,
                 _ => panic!("Plugin '{}' has an invalid '__required__' configuration property (must be a boolean)", name)
             };
+            let restart = match value.get("__restart__") {
+                None => PluginRestartPolicy::Never,
+                Some(Value::String(s)) if s == "never" => PluginRestartPolicy::Never,
+                Some(Value::String(s)) if s == "on-failure" => PluginRestartPolicy::OnFailure,
+                _ => panic!("Plugin '{}' has an invalid '__restart__' configuration property (must be either \"never\" or \"on-failure\")", name)
+            };
             if let Some(paths) = value.get("__path__"){
                 let paths = match paths {
                     Value::String(s) => vec![s.clone()],
                     Value::Array(a) => a.iter().map(|s| if let Value::String(s) = s {s.clone()} else {panic!("Plugin '{}' has an invalid '__path__' configuration property (must be either string or array of strings)", name)}).collect(),
                     _ => panic!("Plugin '{}' has an invalid '__path__' configuration property (must be either string or array of strings)", name)
                 };
-                PluginLoad {name: name.clone(), paths: Some(paths), required}
+                PluginLoad {name: name.clone(), paths: Some(paths), required, restart}
             } else {
-                PluginLoad {name: name.clone(), paths: None, required}
+                PluginLoad {name: name.clone(), paths: None, required, restart}
             }
         })
     }
@@ -0,0 +1,185 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Resolution of the secrets referenced in the configuration.
+//!
+//! A secret value may either be given inline or reference an external source:
+//! - `env:<VAR>` reads the environment variable `VAR`;
+//! - `file:<PATH>` reads the content of the file at `PATH`;
+//! - `exec:<NAME>` runs the `transport/auth/secrets/exec_provider` command with `NAME` as last argument
+//!   and reads its standard output.
+//!
+//! Values read from external sources are trimmed of their leading and trailing whitespaces.
+use super::SecretsConf;
+use std::path::Path;
+use std::process::Command;
+use zenoh_result::{bail, zerror, ZResult};
+
+const ENV_PREFIX: &str = "env:";
+const FILE_PREFIX: &str = "file:";
+const EXEC_PREFIX: &str = "exec:";
+
+/// The source of a secret value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SecretSource<'a> {
+    Inline(&'a str),
+    Env(&'a str),
+    File(&'a str),
+    Exec(&'a str),
+}
+
+impl<'a> SecretSource<'a> {
+    pub fn parse(value: &'a str) -> Self {
+        if let Some(var) = value.strip_prefix(ENV_PREFIX) {
+            SecretSource::Env(var)
+        } else if let Some(path) = value.strip_prefix(FILE_PREFIX) {
+            SecretSource::File(path)
+        } else if let Some(name) = value.strip_prefix(EXEC_PREFIX) {
+            SecretSource::Exec(name)
+        } else {
+            SecretSource::Inline(value)
+        }
+    }
+}
+
+impl SecretsConf {
+    /// Resolves a secret value, fetching it from its external source if any.
+    pub fn resolve(&self, value: &str) -> ZResult<String> {
+        match SecretSource::parse(value) {
+            SecretSource::Inline(value) => Ok(value.to_string()),
+            SecretSource::Env(var) => {
+                std::env::var(var)
+                    .map(|v| v.trim().to_string())
+                    .map_err(|e| {
+                        zerror!("Unable to read secret from environment `{}`: {}", var, e).into()
+                    })
+            }
+            SecretSource::File(path) => self.read_file(path).map(|v| v.trim().to_string()),
+            SecretSource::Exec(name) => self.exec(name),
+        }
+    }
+
+    /// Reads a file containing secrets, checking that it is not accessible by group or others.
+    ///
+    /// Loose permissions are rejected if `strict_permissions` is set, and only reported otherwise.
+    pub fn read_file(&self, path: &str) -> ZResult<String> {
+        self.check_permissions(path)?;
+        std::fs::read_to_string(path)
+            .map_err(|e| zerror!("Unable to read secret file `{}`: {}", path, e).into())
+    }
+
+    pub fn check_permissions<P: AsRef<Path>>(&self, path: P) -> ZResult<()> {
+        let path = path.as_ref();
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| zerror!("Unable to access secret file `{}`: {}", path.display(), e))?;
+        self.check_metadata(path, &metadata)
+    }
+
+    /// Checks the permissions of a secret file from its already fetched metadata, e.g. when
+    /// fetched asynchronously.
+    #[cfg(unix)]
+    pub fn check_metadata<P: AsRef<Path>>(
+        &self,
+        path: P,
+        metadata: &std::fs::Metadata,
+    ) -> ZResult<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = path.as_ref();
+        let mode = metadata.permissions().mode();
+        if mode & 0o077 != 0 {
+            if self.strict_permissions().unwrap_or(false) {
+                bail!(
+                    "Secret file `{}` is accessible by group or others (mode {:o})",
+                    path.display(),
+                    mode & 0o777
+                );
+            }
+            log::warn!(
+                "Secret file `{}` is accessible by group or others (mode {:o})",
+                path.display(),
+                mode & 0o777
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn check_metadata<P: AsRef<Path>>(
+        &self,
+        _path: P,
+        _metadata: &std::fs::Metadata,
+    ) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn exec(&self, name: &str) -> ZResult<String> {
+        let provider = match self.exec_provider() {
+            Some(provider) => provider,
+            None => bail!(
+                "Unable to resolve secret `{}`: no exec provider configured",
+                name
+            ),
+        };
+        let mut args = provider.split_whitespace();
+        let program = args
+            .next()
+            .ok_or_else(|| zerror!("Invalid secrets exec provider: `{}`", provider))?;
+        let output = Command::new(program)
+            .args(args)
+            .arg(name)
+            .output()
+            .map_err(|e| zerror!("Unable to run secrets exec provider `{}`: {}", program, e))?;
+        if !output.status.success() {
+            bail!(
+                "Secrets exec provider `{}` failed for `{}`: {}",
+                program,
+                name,
+                output.status
+            );
+        }
+        let secret = String::from_utf8(output.stdout)
+            .map_err(|e| zerror!("Invalid secret returned for `{}`: {}", name, e))?;
+        Ok(secret.trim().to_string())
+    }
+}
+
+mod tests {
+    #[test]
+    fn secrets() {
+        use super::{SecretSource, SecretsConf};
+
+        assert_eq!(SecretSource::parse("plain"), SecretSource::Inline("plain"));
+        assert_eq!(SecretSource::parse("env:VAR"), SecretSource::Env("VAR"));
+        assert_eq!(SecretSource::parse("file:/a/b"), SecretSource::File("/a/b"));
+        assert_eq!(SecretSource::parse("exec:name"), SecretSource::Exec("name"));
+
+        let conf = SecretsConf::default();
+        assert_eq!(conf.resolve("plain").unwrap(), "plain");
+
+        std::env::set_var("ZENOH_TEST_SECRET", " s3cr3t\n");
+        assert_eq!(conf.resolve("env:ZENOH_TEST_SECRET").unwrap(), "s3cr3t");
+        assert!(conf.resolve("env:ZENOH_TEST_SECRET_UNSET").is_err());
+
+        // No provider configured
+        assert!(conf.resolve("exec:name").is_err());
+
+        #[cfg(unix)]
+        {
+            let mut conf = SecretsConf::default();
+            conf.set_exec_provider(Some("echo -n".to_string())).unwrap();
+            assert_eq!(conf.resolve("exec:name").unwrap(), "name");
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The executor on which the internal tasks (links, routing, admin space, etc.) are spawned.
+//!
+//! By default the tasks are spawned on the global async-std executor. [`init`] allows to spawn
+//! them instead on dedicated threads, on the threads calling [`block_on`] (e.g. on single-threaded
+//! targets), or on an executor provided by the application. The executor is shared by all the
+//! zenoh instances of the process and must be initialized before the first one is opened.
+use async_executor::Executor;
+use once_cell::sync::OnceCell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use zenoh_result::{bail, ZResult};
+
+/// A task spawned on a [`Spawner`].
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// An executor provided by the application.
+pub trait Spawner: Send + Sync {
+    /// Spawns a task, which must be polled to completion.
+    fn spawn(&self, future: BoxFuture);
+}
+
+impl Spawner for Executor<'static> {
+    fn spawn(&self, future: BoxFuture) {
+        Executor::spawn(self, future).detach();
+    }
+}
+
+/// The executor on which the tasks are spawned.
+#[derive(Clone)]
+pub enum ExecutorMode {
+    /// The global async-std executor.
+    Global,
+    /// An executor running on the given number of dedicated threads.
+    Threads(usize),
+    /// An executor running on the threads calling [`block_on`].
+    CurrentThread,
+    /// An executor provided by the application.
+    Custom(Arc<dyn Spawner>),
+}
+
+impl ExecutorMode {
+    /// Parses a mode of the configuration, `threads` being only used by the `threads` mode.
+    pub fn from_config(mode: &str, threads: usize) -> ZResult<Self> {
+        match mode {
+            "global" => Ok(ExecutorMode::Global),
+            "threads" => Ok(ExecutorMode::Threads(threads)),
+            "current_thread" => Ok(ExecutorMode::CurrentThread),
+            _ => bail!(
+                r#"Invalid executor mode `{}`: expected "global", "threads" or "current_thread""#,
+                mode
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for ExecutorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutorMode::Global => write!(f, "global"),
+            ExecutorMode::Threads(n) => write!(f, "threads({n})"),
+            ExecutorMode::CurrentThread => write!(f, "current_thread"),
+            ExecutorMode::Custom(_) => write!(f, "custom"),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Inner {
+    Global,
+    // Driven by dedicated threads, or by the threads calling `block_on` if `local`
+    Owned {
+        executor: Arc<Executor<'static>>,
+        local: bool,
+    },
+    Custom(Arc<dyn Spawner>),
+}
+
+static EXECUTOR: OnceCell<Inner> = OnceCell::new();
+
+impl Inner {
+    fn new(mode: ExecutorMode) -> ZResult<Self> {
+        let inner = match mode {
+            ExecutorMode::Global => Inner::Global,
+            ExecutorMode::Threads(0) => bail!("The executor needs at least one thread"),
+            ExecutorMode::Threads(n) => {
+                let executor = Arc::new(Executor::new());
+                for i in 0..n {
+                    let executor = executor.clone();
+                    std::thread::Builder::new()
+                        .name(format!("zenoh-exec-{i}"))
+                        .spawn(move || {
+                            async_std::task::block_on(executor.run(std::future::pending::<()>()))
+                        })?;
+                }
+                Inner::Owned {
+                    executor,
+                    local: false,
+                }
+            }
+            ExecutorMode::CurrentThread => Inner::Owned {
+                executor: Arc::new(Executor::new()),
+                local: true,
+            },
+            ExecutorMode::Custom(spawner) => Inner::Custom(spawner),
+        };
+        Ok(inner)
+    }
+}
+
+/// Initializes the executor of the process.
+///
+/// Fails if the executor has already been initialized, or if a task has already been spawned.
+pub fn init(mode: ExecutorMode) -> ZResult<()> {
+    let mut initialized = false;
+    EXECUTOR.get_or_try_init(|| {
+        initialized = true;
+        Inner::new(mode)
+    })?;
+    if !initialized {
+        bail!("The executor has already been initialized");
+    }
+    Ok(())
+}
+
+/// Initializes the executor of the process with the mode returned by `mode`, unless it has already
+/// been initialized. `mode` is only called if the executor is to be initialized.
+pub fn get_or_init<F>(mode: F) -> ZResult<()>
+where
+    F: FnOnce() -> ZResult<ExecutorMode>,
+{
+    EXECUTOR.get_or_try_init(|| Inner::new(mode()?))?;
+    Ok(())
+}
+
+/// Returns `true` if the executor has been initialized, explicitly or by spawning a task.
+pub fn is_initialized() -> bool {
+    EXECUTOR.get().is_some()
+}
+
+/// Returns `true` if the tasks are spawned on the global async-std executor.
+pub fn is_global() -> bool {
+    matches!(EXECUTOR.get(), None | Some(Inner::Global))
+}
+
+fn inner() -> &'static Inner {
+    // The executor can no longer be initialized once a task is spawned
+    EXECUTOR.get_or_init(|| Inner::Global)
+}
+
+/// A handle awaiting the output of a spawned task.
+///
+/// Dropping the handle detaches the task, which keeps running.
+pub struct JoinHandle<T> {
+    recv: flume::r#async::RecvFut<'static, T>,
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        Pin::new(&mut self.recv)
+            .poll(cx)
+            .map(|res| res.expect("The task has been cancelled"))
+    }
+}
+
+fn with_handle<F>(future: F) -> (BoxFuture, JoinHandle<F::Output>)
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (sender, receiver) = flume::bounded(1);
+    let task = Box::pin(async move {
+        let _ = sender.send(future.await);
+    });
+    let handle = JoinHandle {
+        recv: receiver.into_recv_async(),
+    };
+    (task, handle)
+}
+
+/// Spawns a task on the executor of the process.
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match inner() {
+        Inner::Global => {
+            let (task, handle) = with_handle(future);
+            async_std::task::spawn(task);
+            handle
+        }
+        Inner::Owned { executor, .. } => spawn_on(&**executor, future),
+        Inner::Custom(spawner) => spawn_on(&**spawner, future),
+    }
+}
+
+/// Spawns a task on the given [`Spawner`].
+pub fn spawn_on<F>(spawner: &dyn Spawner, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (task, handle) = with_handle(future);
+    spawner.spawn(task);
+    handle
+}
+
+/// Blocks the current thread on `future`, as done by the `res_sync()` resolution of the zenoh
+/// operations.
+///
+/// In the `current_thread` mode, the spawned tasks are run while blocking.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    match EXECUTOR.get() {
+        Some(Inner::Owned {
+            executor,
+            local: true,
+        }) => async_std::task::block_on(executor.run(future)),
+        _ => async_std::task::block_on(future),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn executor_modes() {
+        assert!(matches!(
+            ExecutorMode::from_config("global", 2),
+            Ok(ExecutorMode::Global)
+        ));
+        assert!(matches!(
+            ExecutorMode::from_config("threads", 2),
+            Ok(ExecutorMode::Threads(2))
+        ));
+        assert!(ExecutorMode::from_config("tokio", 2).is_err());
+        assert!(init(ExecutorMode::Threads(0)).is_err());
+
+        init(ExecutorMode::CurrentThread).unwrap();
+        assert!(init(ExecutorMode::Global).is_err());
+        // The mode is not even computed once initialized
+        get_or_init(|| panic!("The executor is already initialized")).unwrap();
+        assert!(!is_global());
+        // The spawned tasks are run by the thread blocking on their handle
+        let handle = spawn(async { 40 + 2 });
+        assert_eq!(block_on(handle), 42);
+    }
+}
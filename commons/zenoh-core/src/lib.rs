@@ -18,6 +18,7 @@
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
 pub use lazy_static::lazy_static;
+pub mod executor;
 pub mod macros;
 pub use macros::*;
 use std::future::{Future, Ready};
@@ -161,7 +162,7 @@ where
     F: Future<Output = To> + Send,
 {
     fn res_sync(self) -> <Self as Resolvable>::To {
-        async_std::task::block_on(self.0)
+        executor::block_on(self.0)
     }
 }
 
@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::PseudoRng;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::RngCore;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// An authenticated cipher based on AES-256-GCM.
+///
+/// The encrypted output is laid out as `nonce | ciphertext | tag`.
+pub struct AeadCipher {
+    inner: Aes256Gcm,
+}
+
+impl AeadCipher {
+    pub const KEY_SIZE: usize = 32;
+    pub const NONCE_SIZE: usize = 12;
+    pub const TAG_SIZE: usize = 16;
+
+    pub fn new(key: [u8; Self::KEY_SIZE]) -> AeadCipher {
+        AeadCipher {
+            inner: Aes256Gcm::new(&key.into()),
+        }
+    }
+
+    pub fn encrypt(&self, bytes: &[u8], prng: &mut PseudoRng) -> ZResult<Vec<u8>> {
+        let mut nonce = [0_u8; Self::NONCE_SIZE];
+        prng.fill_bytes(&mut nonce);
+
+        let encrypted = self
+            .inner
+            .encrypt(Nonce::from_slice(&nonce), bytes)
+            .map_err(|e| zerror!("Encryption failed: {}", e))?;
+
+        let mut output = Vec::with_capacity(Self::NONCE_SIZE + encrypted.len());
+        output.extend_from_slice(&nonce);
+        output.extend_from_slice(&encrypted);
+        Ok(output)
+    }
+
+    pub fn decrypt(&self, bytes: &[u8]) -> ZResult<Vec<u8>> {
+        if bytes.len() < Self::NONCE_SIZE + Self::TAG_SIZE {
+            bail!("Invalid bytes length to decrypt: {}", bytes.len());
+        }
+
+        let (nonce, encrypted) = bytes.split_at(Self::NONCE_SIZE);
+        let decrypted = self
+            .inner
+            .decrypt(Nonce::from_slice(nonce), encrypted)
+            .map_err(|e| zerror!("Decryption failed: {}", e))?;
+        Ok(decrypted)
+    }
+}
+
+mod tests {
+    #[test]
+    fn aead() {
+        use super::{AeadCipher, PseudoRng};
+        use rand::{RngCore, SeedableRng};
+
+        let mut prng = PseudoRng::from_entropy();
+        let mut key = [0_u8; AeadCipher::KEY_SIZE];
+        prng.fill_bytes(&mut key);
+        let cipher = AeadCipher::new(key);
+
+        let clear = "This is a medium string with some text".as_bytes().to_vec();
+        let encrypted = cipher.encrypt(&clear, &mut prng).unwrap();
+        assert_eq!(
+            encrypted.len(),
+            clear.len() + AeadCipher::NONCE_SIZE + AeadCipher::TAG_SIZE
+        );
+        let decrypted = cipher.decrypt(&encrypted).unwrap();
+        assert_eq!(clear, decrypted);
+
+        // Tampering with the ciphertext must be detected
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+        assert!(cipher.decrypt(&tampered).is_err());
+
+        // A different key must not decrypt
+        let mut other = [0_u8; AeadCipher::KEY_SIZE];
+        prng.fill_bytes(&mut other);
+        let other = AeadCipher::new(other);
+        assert!(other.decrypt(&encrypted).is_err());
+    }
+}
@@ -24,3 +24,10 @@ pub fn sign(key: &[u8], data: &[u8]) -> ZResult<Vec<u8>> {
 pub fn digest(data: &[u8]) -> Vec<u8> {
     Sha3_256::digest(data).as_slice().to_vec()
 }
+
+/// Verifies in constant time that `tag` is the HMAC of `data` with `key`.
+pub fn verify(key: &[u8], data: &[u8], tag: &[u8]) -> ZResult<bool> {
+    let mut hmac = Hmac::<Sha3_256>::new_from_slice(key)?;
+    hmac.update(data);
+    Ok(hmac.verify_slice(tag).is_ok())
+}
@@ -17,9 +17,11 @@
 //! This crate is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
+mod aead;
 mod cipher;
 pub mod hmac;
 mod prng;
 
+pub use aead::*;
 pub use cipher::*;
 pub use prng::*;
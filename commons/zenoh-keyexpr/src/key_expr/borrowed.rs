@@ -265,6 +265,38 @@ impl keyexpr {
         result
     }
 
+    /// Returns the parts of `self` bound to each wildcard of `pattern`, in the order of the wildcards,
+    /// or `None` if `pattern` doesn't include `self`.
+    ///
+    /// A `*` binds a single chunk, a `**` binds zero or more chunks (the empty string if it binds none)
+    /// and a `$*` binds the part of a chunk it matches. Should several bindings be possible, the wildcards
+    /// bind as few characters as possible, from left to right.
+    ///
+    /// `self` is expected to be a key without wildcards: its wildcards are matched as regular characters.
+    ///
+    /// # Examples:
+    /// ```
+    /// # use zenoh_keyexpr::keyexpr;
+    /// assert_eq!(
+    ///     Some(vec!["robot1"]),
+    ///     keyexpr::new("robot/robot1/pose").unwrap().captures(keyexpr::new("robot/*/pose").unwrap())
+    /// );
+    /// assert_eq!(
+    ///     Some(vec!["a/b", "2"]),
+    ///     keyexpr::new("demo/a/b/arm-2").unwrap().captures(keyexpr::new("demo/**/arm-$*").unwrap())
+    /// );
+    /// assert_eq!(
+    ///     None,
+    ///     keyexpr::new("robot/robot1/speed").unwrap().captures(keyexpr::new("robot/*/pose").unwrap())
+    /// );
+    /// ```
+    pub fn captures<'a>(&'a self, pattern: &keyexpr) -> Option<Vec<&'a str>> {
+        let chunks: Vec<&str> = self.split('/').collect();
+        let pattern: Vec<&str> = pattern.split('/').collect();
+        let mut captures = vec![];
+        capture_chunks(self, &chunks, &pattern, &mut captures).then_some(captures)
+    }
+
     pub fn as_str(&self) -> &str {
         self
     }
@@ -294,6 +326,91 @@ impl keyexpr {
     }
 }
 
+// Binds the wildcards of `pattern` to `chunks`, which are sub-slices of `key`.
+fn capture_chunks<'a>(
+    key: &'a str,
+    chunks: &[&'a str],
+    pattern: &[&str],
+    captures: &mut Vec<&'a str>,
+) -> bool {
+    let (head, pattern_tail) = match pattern.split_first() {
+        Some(split) => split,
+        None => return chunks.is_empty(),
+    };
+    let len = captures.len();
+    match *head {
+        "**" => {
+            for n in 0..=chunks.len() {
+                let bound = if n == 0 {
+                    ""
+                } else {
+                    let (first, last) = (chunks[0], chunks[n - 1]);
+                    let start = first.as_ptr() as usize - key.as_ptr() as usize;
+                    let end = last.as_ptr() as usize - key.as_ptr() as usize + last.len();
+                    &key[start..end]
+                };
+                captures.push(bound);
+                if capture_chunks(key, &chunks[n..], pattern_tail, captures) {
+                    return true;
+                }
+                captures.truncate(len);
+            }
+            false
+        }
+        _ => {
+            let (chunk, chunks_tail) = match chunks.split_first() {
+                Some(split) => split,
+                None => return false,
+            };
+            let matched = if *head == "*" {
+                captures.push(chunk);
+                true
+            } else if head.contains("$*") {
+                let mut literals = head.split("$*");
+                let first = literals.next().unwrap_or_default();
+                let literals: Vec<&str> = literals.collect();
+                match chunk.strip_prefix(first) {
+                    Some(rest) => capture_sub_chunk(rest, &literals, captures),
+                    None => false,
+                }
+            } else {
+                head == chunk
+            };
+            if matched && capture_chunks(key, chunks_tail, pattern_tail, captures) {
+                return true;
+            }
+            captures.truncate(len);
+            false
+        }
+    }
+}
+
+// Binds the `$*` preceding each of `literals` to the parts of `chunk` in between them.
+fn capture_sub_chunk<'a>(chunk: &'a str, literals: &[&str], captures: &mut Vec<&'a str>) -> bool {
+    let (literal, tail) = match literals.split_first() {
+        Some(split) => split,
+        None => return chunk.is_empty(),
+    };
+    if tail.is_empty() {
+        return match chunk.strip_suffix(literal) {
+            Some(bound) => {
+                captures.push(bound);
+                true
+            }
+            None => false,
+        };
+    }
+    let len = captures.len();
+    for (i, _) in chunk.match_indices(literal) {
+        captures.push(&chunk[..i]);
+        if capture_sub_chunk(&chunk[i + literal.len()..], tail, captures) {
+            return true;
+        }
+        captures.truncate(len);
+    }
+    false
+}
+
 impl Div for &keyexpr {
     type Output = OwnedKeyExpr;
     fn div(self, rhs: Self) -> Self::Output {
@@ -0,0 +1,237 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{keyexpr, OwnedKeyExpr};
+use alloc::{format, string::String, vec::Vec};
+use core::convert::TryFrom;
+use core::fmt::Write;
+use zenoh_result::{bail, ZResult};
+
+// The characters percent-encoded in a chunk, on top of the ones of the escape sequences
+const ESCAPED_CHARS: [char; 6] = ['/', '*', '$', '#', '?', '%'];
+// A chunk can't be empty in a key expression
+const EMPTY_CHUNK: &str = "%";
+
+/// Escapes `chunk` into a valid key expression chunk.
+///
+/// The characters that can't appear in a chunk (`/`, `*`, `$`, `#`, `?`) and `%` are
+/// percent-encoded, e.g. `$SYS` becomes `%24SYS`, and an empty chunk becomes `%`.
+pub fn escape_chunk(chunk: &str) -> String {
+    if chunk.is_empty() {
+        return EMPTY_CHUNK.into();
+    }
+    let mut escaped = String::with_capacity(chunk.len());
+    for c in chunk.chars() {
+        if ESCAPED_CHARS.contains(&c) {
+            let _ = write!(escaped, "%{:02X}", c as u8);
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Reverts the [`escape_chunk`] of a key expression chunk.
+pub fn unescape_chunk(chunk: &str) -> ZResult<String> {
+    if chunk == EMPTY_CHUNK {
+        return Ok(String::new());
+    }
+    let bytes = chunk.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            match chunk
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(b) => unescaped.push(b),
+                None => bail!(
+                    "Invalid escape sequence in key expression chunk `{}`",
+                    chunk
+                ),
+            }
+            i += 3;
+        } else {
+            unescaped.push(bytes[i]);
+            i += 1;
+        }
+    }
+    match String::from_utf8(unescaped) {
+        Ok(unescaped) => Ok(unescaped),
+        Err(_) => bail!("Invalid UTF-8 escaped in key expression chunk `{}`", chunk),
+    }
+}
+
+// Unescapes a chunk of `key_expr`, which can't be a wildcard
+fn unescape_concrete_chunk(chunk: &str, key_expr: &keyexpr) -> ZResult<String> {
+    if chunk.contains('*') {
+        bail!(
+            "Key expression `{}` can't be mapped back: its wildcards have no equivalent",
+            key_expr
+        );
+    }
+    unescape_chunk(chunk)
+}
+
+/// Maps a `/` separated path, e.g. the path of a URL, to a key expression whose chunks are the
+/// [escaped](escape_chunk) path segments: `/a b/c*` becomes `%/a b/c%2A`.
+pub fn path_to_keyexpr(path: &str) -> ZResult<OwnedKeyExpr> {
+    let chunks: Vec<String> = path.split('/').map(escape_chunk).collect();
+    OwnedKeyExpr::try_from(chunks.join("/"))
+}
+
+/// Reverts the [`path_to_keyexpr`] of a key expression without wildcards.
+pub fn keyexpr_to_path(key_expr: &keyexpr) -> ZResult<String> {
+    let chunks = key_expr
+        .as_str()
+        .split('/')
+        .map(|chunk| unescape_concrete_chunk(chunk, key_expr))
+        .collect::<ZResult<Vec<String>>>()?;
+    Ok(chunks.join("/"))
+}
+
+/// Maps an MQTT topic name or filter to a key expression: the `+` and trailing `#` wildcards
+/// become `*` and `**`, and the other levels are [escaped](escape_chunk).
+pub fn mqtt_topic_to_keyexpr(topic: &str) -> ZResult<OwnedKeyExpr> {
+    let mut chunks = Vec::new();
+    let mut levels = topic.split('/').peekable();
+    while let Some(level) = levels.next() {
+        match level {
+            "+" => chunks.push(String::from("*")),
+            "#" if levels.peek().is_none() => chunks.push(String::from("**")),
+            _ if level.contains(&['+', '#'][..]) => bail!(
+                "Invalid MQTT topic `{}`: `+` and `#` must be whole levels, and `#` the last one",
+                topic
+            ),
+            _ => chunks.push(escape_chunk(level)),
+        }
+    }
+    OwnedKeyExpr::autocanonize(chunks.join("/"))
+}
+
+/// Reverts the [`mqtt_topic_to_keyexpr`] of a key expression, whose only wildcards may be `*`
+/// chunks and a trailing `**` chunk.
+pub fn keyexpr_to_mqtt_topic(key_expr: &keyexpr) -> ZResult<String> {
+    let mut levels = Vec::new();
+    let mut chunks = key_expr.as_str().split('/').peekable();
+    while let Some(chunk) = chunks.next() {
+        match chunk {
+            "*" => levels.push(String::from("+")),
+            "**" if chunks.peek().is_none() => levels.push(String::from("#")),
+            _ => {
+                let level = unescape_concrete_chunk(chunk, key_expr)?;
+                if level.contains(&['+', '#'][..]) {
+                    bail!(
+                        "Key expression `{}` has no MQTT equivalent: `+` and `#` are reserved",
+                        key_expr
+                    );
+                }
+                levels.push(level)
+            }
+        }
+    }
+    Ok(levels.join("/"))
+}
+
+/// Maps a DDS topic of `partition`, if any, to a key expression: the [escaped](escape_chunk)
+/// partition as first chunk followed by the topic name mapped as a [path](path_to_keyexpr),
+/// e.g. the ROS 2 topic `rt/chatter` of partition `robot/1` becomes `robot%2F1/rt/chatter`.
+pub fn dds_topic_to_keyexpr(topic: &str, partition: Option<&str>) -> ZResult<OwnedKeyExpr> {
+    let topic = path_to_keyexpr(topic)?;
+    match partition {
+        Some(partition) => OwnedKeyExpr::try_from(format!("{}/{}", escape_chunk(partition), topic)),
+        None => Ok(topic),
+    }
+}
+
+/// Reverts the [`dds_topic_to_keyexpr`] of a key expression without wildcards, returning the
+/// partition, if `with_partition`, and the topic name.
+pub fn keyexpr_to_dds_topic(
+    key_expr: &keyexpr,
+    with_partition: bool,
+) -> ZResult<(Option<String>, String)> {
+    if !with_partition {
+        return Ok((None, keyexpr_to_path(key_expr)?));
+    }
+    match key_expr.as_str().split_once('/') {
+        Some((partition, topic)) => Ok((
+            Some(unescape_concrete_chunk(partition, key_expr)?),
+            keyexpr_to_path(keyexpr::new(topic)?)?,
+        )),
+        None => bail!(
+            "Key expression `{}` has no topic after its partition",
+            key_expr
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks() {
+        for chunk in ["", "a", "$SYS", "a*b", "a/b", "100%", "?#", "é"] {
+            let escaped = escape_chunk(chunk);
+            assert!(keyexpr::new(escaped.as_str()).is_ok(), "{}", escaped);
+            assert_eq!(unescape_chunk(&escaped).unwrap(), chunk);
+        }
+        assert_eq!(escape_chunk("$SYS"), "%24SYS");
+        assert!(unescape_chunk("%2").is_err());
+        assert!(unescape_chunk("%+1").is_err());
+        assert!(unescape_chunk("%FF").is_err());
+    }
+
+    #[test]
+    fn paths() {
+        for path in ["a/b/c", "/a b/c*", "a//b/", "", "$x/%y"] {
+            let key_expr = path_to_keyexpr(path).unwrap();
+            assert_eq!(keyexpr_to_path(&key_expr).unwrap(), path);
+        }
+        assert_eq!(path_to_keyexpr("/a b/c*").unwrap().as_str(), "%/a b/c%2A");
+        assert!(keyexpr_to_path(keyexpr::new("a/*").unwrap()).is_err());
+    }
+
+    #[test]
+    fn mqtt_topics() {
+        for topic in ["a/b", "$SYS/broker", "a/+/c", "a/#", "+/#", "#", "/a"] {
+            let key_expr = mqtt_topic_to_keyexpr(topic).unwrap();
+            assert_eq!(keyexpr_to_mqtt_topic(&key_expr).unwrap(), topic);
+        }
+        assert_eq!(mqtt_topic_to_keyexpr("a/+/#").unwrap().as_str(), "a/*/**");
+        assert!(mqtt_topic_to_keyexpr("a/#/b").is_err());
+        assert!(mqtt_topic_to_keyexpr("a/b+").is_err());
+        assert!(keyexpr_to_mqtt_topic(keyexpr::new("a/**/b").unwrap()).is_err());
+        assert!(keyexpr_to_mqtt_topic(keyexpr::new("a/b*").unwrap()).is_err());
+        assert!(keyexpr_to_mqtt_topic(keyexpr::new("a/+").unwrap()).is_err());
+    }
+
+    #[test]
+    fn dds_topics() {
+        let key_expr = dds_topic_to_keyexpr("rt/chatter", Some("robot/1")).unwrap();
+        assert_eq!(key_expr.as_str(), "robot%2F1/rt/chatter");
+        assert_eq!(
+            keyexpr_to_dds_topic(&key_expr, true).unwrap(),
+            (Some("robot/1".into()), "rt/chatter".into())
+        );
+        let key_expr = dds_topic_to_keyexpr("rt/chatter", None).unwrap();
+        assert_eq!(key_expr.as_str(), "rt/chatter");
+        assert_eq!(
+            keyexpr_to_dds_topic(&key_expr, false).unwrap(),
+            (None, "rt/chatter".into())
+        );
+        assert!(keyexpr_to_dds_topic(keyexpr::new("p").unwrap(), true).is_err());
+    }
+}
@@ -36,6 +36,9 @@ pub mod include;
 /// The average user doesn't need to bother with it.
 pub mod intersect;
 pub(crate) mod utils;
+/// Helpers to map the hierarchical names of other protocols (URL paths, MQTT topics, DDS topics and partitions)
+/// to canonical Key Expressions and back, escaping the characters that are invalid in Key Expressions.
+pub mod mapping;
 
 /// Exposes a random Key Expression generator to help with testing.
 #[cfg(any(test, feature = "test"))]
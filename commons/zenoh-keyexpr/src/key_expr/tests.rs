@@ -148,6 +148,45 @@ fn inclusions() {
     assert!(!includes("x/$*d", "x/$*e"));
 }
 
+fn captures<'a>(key: &'a str, pattern: &str) -> Option<Vec<&'a str>> {
+    keyexpr::new(key)
+        .unwrap()
+        .captures(keyexpr::new(pattern).unwrap())
+}
+
+#[test]
+fn captures_wildcards() {
+    assert_eq!(captures("a/b/c", "a/b/c"), Some(vec![]));
+    assert_eq!(captures("a/b/c", "a/b"), None);
+    assert_eq!(captures("a/b", "a/b/c"), None);
+    assert_eq!(captures("robot/r1/pose", "robot/*/pose"), Some(vec!["r1"]));
+    assert_eq!(captures("robot/r1/speed", "robot/*/pose"), None);
+    assert_eq!(captures("robot/pose", "robot/*/pose"), None);
+    assert_eq!(captures("a/b/c", "*/*/*"), Some(vec!["a", "b", "c"]));
+    assert_eq!(captures("a", "a/**"), Some(vec![""]));
+    assert_eq!(captures("a/b/c", "a/**"), Some(vec!["b/c"]));
+    assert_eq!(captures("a/b/c", "**"), Some(vec!["a/b/c"]));
+    assert_eq!(captures("a/b/c", "**/c"), Some(vec!["a/b"]));
+    assert_eq!(captures("a/c/c", "**/c/**"), Some(vec!["a", "c"]));
+    assert_eq!(captures("a/b/c/d", "a/**/*"), Some(vec!["b/c", "d"]));
+    assert_eq!(captures("a/b/c/d", "a/*/**/d"), Some(vec!["b", "c"]));
+    assert_eq!(captures("a/d", "a/**/d"), Some(vec![""]));
+    assert_eq!(captures("a/b", "a/**/b/*"), None);
+    assert_eq!(captures("arm-2", "arm-$*"), Some(vec!["2"]));
+    assert_eq!(captures("arm-", "arm-$*"), Some(vec![""]));
+    assert_eq!(captures("left-arm", "$*-arm"), Some(vec!["left"]));
+    assert_eq!(captures("a-b-c", "$*-$*"), Some(vec!["a", "b-c"]));
+    assert_eq!(captures("xyzdefxyz", "xyz$*xyz"), Some(vec!["def"]));
+    assert_eq!(captures("xyz", "xyz$*xyz"), None);
+    assert_eq!(captures("abxxcxxd", "ab$*c$*d"), Some(vec!["xx", "xx"]));
+    assert_eq!(
+        captures("a/b/xyzdefxyz", "**/xyz$*xyz"),
+        Some(vec!["a/b", "def"])
+    );
+    // Wildcards in the key are matched as regular characters
+    assert_eq!(captures("a/*", "a/*"), Some(vec!["*"]));
+}
+
 #[test]
 fn fuzz() {
     const FUZZ_ROUNDS: usize = 100_000;
@@ -331,6 +331,29 @@ fn fuzz() {
     test_keyarctree(&keys)
 }
 
+#[test]
+fn key_value_pairs() {
+    let mut tree: KeBoxTree<u8> = KeBoxTree::new();
+    tree.insert(into_ke("a/b/c"), 1);
+    tree.insert(into_ke("a/*/d"), 2);
+    tree.insert(into_ke("e/**"), 3);
+    let ke = |s: &str| OwnedKeyExpr::try_from(s).unwrap();
+
+    let found = tree
+        .intersecting_key_value_pairs(into_ke("a/**"))
+        .map(|(k, w)| (k, *w))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found.get(&ke("a/b/c")), Some(&1));
+    assert_eq!(found.get(&ke("a/*/d")), Some(&2));
+
+    let found = tree
+        .included_key_value_pairs(into_ke("a/b/*"))
+        .map(|(k, w)| (k, *w))
+        .collect::<Vec<_>>();
+    assert_eq!(found, [(ke("a/b/c"), 1)]);
+}
+
 #[test]
 fn pruning() {
     let mut rng = rand::thread_rng();
@@ -233,6 +233,8 @@ pub trait AsNodeMut<T: ?Sized>: AsNode<T> {
 }
 
 type Keys<I, Item> = core::iter::FilterMap<I, fn(Item) -> Option<OwnedKeyExpr>>;
+type KeyValuePairs<'a, I, Item, Weight> =
+    core::iter::FilterMap<I, fn(Item) -> Option<(OwnedKeyExpr, &'a Weight)>>;
 fn filter_map_weighted_node_to_key<N: IKeyExprTreeNodeMut<W>, I: AsNode<N>, W>(
     item: I,
 ) -> Option<OwnedKeyExpr> {
@@ -268,6 +270,34 @@ pub trait IKeyExprTreeExt<'a, Weight>: IKeyExprTree<'a, Weight> {
         self.included_nodes(key)
             .filter_map(filter_map_weighted_node_to_key)
     }
+    /// Returns an iterator over the KEs contained in the tree that intersect with `key`, along with their Weight
+    fn intersecting_key_value_pairs(
+        &'a self,
+        key: &'a keyexpr,
+    ) -> KeyValuePairs<'a, Self::Intersection, Self::IntersectionItem, Weight>
+    where
+        Self::IntersectionItem: AsNode<Self::Node>,
+        Self::Node: IKeyExprTreeNode<Weight>,
+    {
+        self.intersecting_nodes(key).filter_map(|node| {
+            unsafe { core::mem::transmute::<_, Option<&Weight>>(node.as_node().weight()) }
+                .map(|w| (node.as_node().keyexpr(), w))
+        })
+    }
+    /// Returns an iterator over the KEs contained in the tree that are included by `key`, along with their Weight
+    fn included_key_value_pairs(
+        &'a self,
+        key: &'a keyexpr,
+    ) -> KeyValuePairs<'a, Self::Inclusion, Self::InclusionItem, Weight>
+    where
+        Self::InclusionItem: AsNode<Self::Node>,
+        Self::Node: IKeyExprTreeNode<Weight>,
+    {
+        self.included_nodes(key).filter_map(|node| {
+            unsafe { core::mem::transmute::<_, Option<&Weight>>(node.as_node().weight()) }
+                .map(|w| (node.as_node().keyexpr(), w))
+        })
+    }
     /// Iterates through weighted nodes, yielding their KE and Weight.
     #[allow(clippy::type_complexity)]
     fn key_value_pairs(
@@ -23,6 +23,7 @@ use zenoh_keyexpr::format::{
     macro_support::{self, SegmentBuilder},
     KeFormat,
 };
+use zenoh_keyexpr::{keyexpr, OwnedKeyExpr};
 
 const RUSTC_VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/version.rs"));
 
@@ -247,6 +248,30 @@ pub fn kedefine(tokens: TokenStream) -> TokenStream {
     quote!(#(#content)*).into()
 }
 
+/// Validate a key expression literal at compile time.
+///
+/// `ke!($lit)` results in a `&'static keyexpr` expression, and fails to compile if `$lit` isn't a valid key expression in its canon form.
+#[proc_macro]
+pub fn ke(tokens: TokenStream) -> TokenStream {
+    let lit: syn::LitStr = match syn::parse(tokens) {
+        Ok(lit) => lit,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let source = lit.value();
+    match keyexpr::new(source.as_str()) {
+        Ok(_) => quote!(unsafe { ::zenoh::key_expr::keyexpr::from_str_unchecked(#source) }).into(),
+        Err(e) => {
+            let message = match OwnedKeyExpr::autocanonize(source.clone()) {
+                Ok(canon) => format!("{e}, its canon form is `{canon}`"),
+                Err(_) => e.to_string(),
+            };
+            syn::Error::new(lit.span(), message)
+                .to_compile_error()
+                .into()
+        }
+    }
+}
+
 struct FormatUsage {
     id: syn::Expr,
     assigns: Vec<(syn::Expr, syn::Expr)>,
@@ -42,6 +42,7 @@ pub mod imsg {
         pub const PULL: u8 = 0x0e;
         pub const UNIT: u8 = 0x0f;
         pub const LINK_STATE_LIST: u8 = 0x10;
+        pub const CONGESTION_DROP: u8 = 0x11;
 
         // Message decorators
         pub const PRIORITY: u8 = 0x1c;
@@ -455,6 +455,9 @@ pub struct SubInfo {
 pub struct QueryableInfo {
     pub complete: ZInt, // Default 0: incomplete
     pub distance: ZInt, // Default 0: no distance
+    /// Whether the queries matching several equivalent load balanced queryables are routed to
+    /// only one of them.
+    pub load_balanced: bool, // Default false: not load balanced
 }
 
 /// The kind of consolidation.
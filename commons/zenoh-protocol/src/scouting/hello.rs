@@ -11,8 +11,8 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::core::{Locator, WhatAmI, ZenohId};
-use alloc::vec::Vec;
+use crate::core::{Locator, WhatAmI, ZInt, ZenohId};
+use alloc::{string::String, vec::Vec};
 use core::fmt;
 
 /// # Hello message
@@ -46,12 +46,98 @@ use core::fmt;
 /// +---------------+
 /// ~   [Locators]  ~ if L==1 -- Otherwise src-address is the locator
 /// +---------------+
+///
+/// The optional metadata is carried by the Attachment decorating the HELLO message.
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Hello {
     pub zid: Option<ZenohId>,
     pub whatami: WhatAmI,
     pub locators: Vec<Locator>,
+    pub metadata: Option<HelloMetadata>,
+}
+
+/// # Hello metadata
+///
+/// The metadata advertised along with a HELLO message, allowing the scouts to select the
+/// nodes to connect to. It is serialized as a list of properties in the Attachment decorating
+/// the HELLO message, each feature being a distinct property:
+///
+/// ```text
+///  key   value
+/// +-----+------------------+
+/// |  1  | cluster (utf-8)  |
+/// |  2  | region (utf-8)   |
+/// |  3  | load (1 byte)    |
+/// |  4  | feature (utf-8)  |
+/// +-----+------------------+
+/// ```
+///
+/// The properties with another key are skipped, so that newer versions may add metadata.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HelloMetadata {
+    /// The name of the cluster the node belongs to.
+    pub cluster: Option<String>,
+    /// The region the node is located in.
+    pub region: Option<String>,
+    /// The load of the node, from 0 (idle) to 100 (saturated).
+    pub load: Option<u8>,
+    /// The protocol features supported by the node.
+    pub features: Vec<String>,
+}
+
+impl HelloMetadata {
+    pub const CLUSTER: ZInt = 1;
+    pub const REGION: ZInt = 2;
+    pub const LOAD: ZInt = 3;
+    pub const FEATURE: ZInt = 4;
+
+    pub fn is_empty(&self) -> bool {
+        self.cluster.is_none()
+            && self.region.is_none()
+            && self.load.is_none()
+            && self.features.is_empty()
+    }
+
+    #[cfg(feature = "test")]
+    pub fn rand() -> Self {
+        use rand::{
+            distributions::{Alphanumeric, DistString},
+            Rng,
+        };
+
+        const MIN: usize = 2;
+        const MAX: usize = 16;
+
+        let mut rng = rand::thread_rng();
+
+        let cluster = if rng.gen_bool(0.5) {
+            let len = rng.gen_range(MIN..MAX);
+            Some(Alphanumeric.sample_string(&mut rng, len))
+        } else {
+            None
+        };
+        let region = if rng.gen_bool(0.5) {
+            let len = rng.gen_range(MIN..MAX);
+            Some(Alphanumeric.sample_string(&mut rng, len))
+        } else {
+            None
+        };
+        let load = rng.gen_bool(0.5).then(|| rng.gen_range(0..=100));
+        let features = (0..rng.gen_range(0..4))
+            .map(|_| {
+                let len = rng.gen_range(MIN..MAX);
+                Alphanumeric.sample_string(&mut rng, len)
+            })
+            .collect();
+
+        Self {
+            cluster,
+            region,
+            load,
+            features,
+        }
+    }
 }
 
 impl fmt::Display for Hello {
@@ -60,6 +146,7 @@ impl fmt::Display for Hello {
             .field("zid", &self.zid)
             .field("whatami", &self.whatami)
             .field("locators", &self.locators)
+            .field("metadata", &self.metadata)
             .finish()
     }
 }
@@ -82,10 +169,12 @@ impl Hello {
         } else {
             vec![]
         };
+        // The metadata is serialized with the attachment, see ScoutingMessage
         Self {
             zid,
             whatami,
             locators,
+            metadata: None,
         }
     }
 }
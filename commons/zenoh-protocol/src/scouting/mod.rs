@@ -55,6 +55,7 @@ impl ScoutingMessage {
         zid: Option<ZenohId>,
         whatami: Option<WhatAmI>,
         locators: Option<Vec<Locator>>,
+        metadata: Option<HelloMetadata>,
         attachment: Option<Attachment>,
     ) -> ScoutingMessage {
         let whatami = whatami.unwrap_or(WhatAmI::Router);
@@ -65,6 +66,7 @@ impl ScoutingMessage {
                 zid,
                 whatami,
                 locators,
+                metadata,
             }),
             attachment,
             #[cfg(feature = "stats")]
@@ -84,9 +86,14 @@ impl ScoutingMessage {
             None
         };
 
-        let body = match rng.gen_range(0..2) {
-            0 => ScoutingBody::Hello(Hello::rand()),
-            1 => ScoutingBody::Scout(Scout::rand()),
+        // The attachment of a Hello carries its metadata
+        let (body, attachment) = match rng.gen_range(0..2) {
+            0 => {
+                let mut hello = Hello::rand();
+                hello.metadata = attachment.is_some().then(HelloMetadata::rand);
+                (ScoutingBody::Hello(hello), None)
+            }
+            1 => (ScoutingBody::Scout(Scout::rand()), attachment),
             _ => unreachable!(),
         };
 
@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::core::ZenohId;
+use crate::core::{Locator, ZenohId};
 
 /// # Close message
 ///
@@ -28,23 +28,28 @@ use crate::core::ZenohId;
 ///
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
-/// |X|K|I|  CLOSE  |
+/// |L|K|I|  CLOSE  |
 /// +-+-+-+-+-------+
 /// ~    peer_id    ~  if I==1 -- PID of the target peer.
 /// +---------------+
 /// |     reason    |
 /// +---------------+
+/// ~    locator    ~  if L==1 -- Locator the peer should reconnect to.
+/// +---------------+
 ///
 /// - if K==0 then close the whole zenoh transport.
 /// - if K==1 then close the transport link the CLOSE message was sent on (e.g., TCP socket) but
 ///           keep the whole transport open. NOTE: the transport will be automatically closed when
 ///           the transport's lease period expires.
+/// - if L==1 then the closing node suggests a locator to reconnect to (e.g., when a router is
+///           being drained), usually together with the MIGRATE reason.
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Close {
     pub zid: Option<ZenohId>,
     pub reason: u8,
     pub link_only: bool,
+    pub locator: Option<Locator>,
 }
 
 impl Close {
@@ -61,11 +66,17 @@ impl Close {
         };
         let reason: u8 = rng.gen();
         let link_only = rng.gen_bool(0.5);
+        let locator = if rng.gen_bool(0.5) {
+            Some(Locator::rand())
+        } else {
+            None
+        };
 
         Self {
             zid,
             reason,
             link_only,
+            locator,
         }
     }
 }
@@ -32,7 +32,7 @@ use zenoh_buffers::ZSlice;
 /// +-+-+-+-+-+-+-+-+
 /// |O|S|A|   INIT  |
 /// +-+-+-+-+-------+
-/// ~             |Q~ if O==1
+/// ~         |X|P|Q~ if O==1
 /// +---------------+
 /// | v_maj | v_min | if A==0 -- Protocol Version VMaj.VMin
 /// +-------+-------+
@@ -49,6 +49,10 @@ use zenoh_buffers::ZSlice;
 ///     if A==1 and S==0 then the agreed resolution is the one communicated by the initiator.
 ///
 /// - if Q==1 then the initiator/responder support QoS.
+/// - if P==1 then the initiator/responder precede the batches sent on stream links with a PREAMBLE.
+///   The preamble is used on a link only if both sides set P==1.
+/// - the X bits announce the zenoh message extensions understood by the initiator/responder
+///   (see init_options::EXTENSIONS). An extension is used on a transport only if both sides set it.
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InitSyn {
@@ -57,11 +61,14 @@ pub struct InitSyn {
     pub zid: ZenohId,
     pub sn_resolution: ZInt,
     pub is_qos: bool,
+    pub is_preamble: bool,
+    pub extensions: ZInt,
 }
 
 impl InitSyn {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
+        use super::tmsg;
         use crate::defaults::SEQ_NUM_RES;
         use rand::Rng;
 
@@ -76,6 +83,8 @@ impl InitSyn {
             SEQ_NUM_RES
         };
         let is_qos = rng.gen_bool(0.5);
+        let is_preamble = rng.gen_bool(0.5);
+        let extensions = rng.gen::<ZInt>() & tmsg::init_options::EXTENSIONS;
 
         Self {
             version,
@@ -83,6 +92,8 @@ impl InitSyn {
             zid,
             sn_resolution,
             is_qos,
+            is_preamble,
+            extensions,
         }
     }
 }
@@ -93,12 +104,15 @@ pub struct InitAck {
     pub zid: ZenohId,
     pub sn_resolution: Option<ZInt>,
     pub is_qos: bool,
+    pub is_preamble: bool,
+    pub extensions: ZInt,
     pub cookie: ZSlice,
 }
 
 impl InitAck {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
+        use super::tmsg;
         use rand::Rng;
 
         const MIN: usize = 32;
@@ -114,6 +128,8 @@ impl InitAck {
             None
         };
         let is_qos = rng.gen_bool(0.5);
+        let is_preamble = rng.gen_bool(0.5);
+        let extensions = rng.gen::<ZInt>() & tmsg::init_options::EXTENSIONS;
         let cookie = ZSlice::rand(rng.gen_range(MIN..=MAX));
 
         Self {
@@ -121,6 +137,8 @@ impl InitAck {
             zid,
             sn_resolution,
             is_qos,
+            is_preamble,
+            extensions,
             cookie,
         }
     }
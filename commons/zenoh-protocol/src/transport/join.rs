@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::core::{ConduitSnList, WhatAmI, ZInt, ZenohId};
+use alloc::vec::Vec;
 use core::time::Duration;
 
 /// # Join message
@@ -29,7 +30,7 @@ use core::time::Duration;
 /// +-+-+-+-+-+-+-+-+
 /// |O|S|T|   JOIN  |
 /// +-+-+-+-+-------+
-/// ~             |Q~ if O==1
+/// ~           |A|Q~ if O==1
 /// +---------------+
 /// | v_maj | v_min | -- Protocol Version VMaj.VMin
 /// +-------+-------+
@@ -43,8 +44,11 @@ use core::time::Duration;
 /// +---------------+
 /// ~   [next_sn]   ~ (***)
 /// +---------------+
+/// ~     token     ~ if A==1
+/// +---------------+
 ///
 /// - if Q==1 then the sender supports QoS.
+/// - if A==1 then the sender presents a token authorizing it to join the group.
 ///
 /// (*)   if T==1 then the lease period is expressed in seconds, otherwise in milliseconds
 /// (**)  if S==0 then 2^28 is assumed.
@@ -60,6 +64,7 @@ pub struct Join {
     pub lease: Duration,
     pub sn_resolution: ZInt,
     pub next_sns: ConduitSnList,
+    pub token: Option<Vec<u8>>,
 }
 
 impl Join {
@@ -101,6 +106,10 @@ impl Join {
                 best_effort: rng.gen(),
             })
         };
+        let token = rng.gen_bool(0.5).then(|| {
+            let len = rng.gen_range(1..=64);
+            (0..len).map(|_| rng.gen()).collect()
+        });
 
         Self {
             version,
@@ -109,6 +118,7 @@ impl Join {
             lease,
             sn_resolution,
             next_sns,
+            token,
         }
     }
 }
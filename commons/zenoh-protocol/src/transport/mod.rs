@@ -17,11 +17,14 @@ mod init;
 mod join;
 mod keepalive;
 mod open;
+mod ping;
+mod preamble;
 
 use crate::{
     common::Attachment,
-    core::{Channel, ConduitSnList, WhatAmI, ZInt, ZenohId},
+    core::{Channel, ConduitSnList, Locator, WhatAmI, ZInt, ZenohId},
 };
+use alloc::vec::Vec;
 pub use close::*;
 use core::time::Duration;
 pub use frame::*;
@@ -29,6 +32,8 @@ pub use init::*;
 pub use join::*;
 pub use keepalive::*;
 pub use open::*;
+pub use ping::*;
+pub use preamble::*;
 use zenoh_buffers::ZSlice;
 
 pub mod tmsg {
@@ -83,12 +88,23 @@ pub mod tmsg {
         use super::ZInt;
 
         pub const QOS: ZInt = 1 << 0; // 0x01 QoS       if PRIORITY==1 then the transport supports QoS
+        pub const PREAMBLE: ZInt = 1 << 1; // 0x02 Preamble  if PREAMBLE==1 then the batches of stream links are preceded by a preamble
+        pub const QUERY_TIMEOUT: ZInt = 1 << 2; // 0x04 QryTimeout if QUERY_TIMEOUT==1 then the queries may carry a timeout
+        pub const CONGESTION_DROP: ZInt = 1 << 3; // 0x08 CgDrop    if CONGESTION_DROP==1 then the CongestionDrop messages are understood
+        pub const QUERY_DESTINATIONS: ZInt = 1 << 4; // 0x10 QryDest   if QUERY_DESTINATIONS==1 then the queries may carry destinations
+        pub const UNIT_DATA: ZInt = 1 << 5; // 0x20 UnitData  if UNIT_DATA==1 then the Data without DataInfo nor payload may be encoded as Unit
+        pub const TRACE_CONTEXT: ZInt = 1 << 6; // 0x40 TraceCtx  if TRACE_CONTEXT==1 then the DataInfo may carry a trace context
+
+        // The zenoh message extensions understood by a node
+        pub const EXTENSIONS: ZInt =
+            QUERY_TIMEOUT | CONGESTION_DROP | QUERY_DESTINATIONS | UNIT_DATA | TRACE_CONTEXT;
     }
 
     pub mod join_options {
         use super::ZInt;
 
         pub const QOS: ZInt = 1 << 0; // 0x01 QoS       if PRIORITY==1 then the transport supports QoS
+        pub const AUTH: ZInt = 1 << 1; // 0x02 Auth      if AUTH==1 then the Join carries a token
     }
 
     // Reason for the Close message
@@ -99,6 +115,8 @@ pub mod tmsg {
         pub const MAX_SESSIONS: u8 = 0x03;
         pub const MAX_LINKS: u8 = 0x04;
         pub const EXPIRED: u8 = 0x05;
+        pub const QUOTA_EXCEEDED: u8 = 0x06;
+        pub const MIGRATE: u8 = 0x07;
     }
 
     pub fn close_reason_to_str(reason: u8) -> &'static str {
@@ -109,6 +127,8 @@ pub mod tmsg {
             close_reason::MAX_SESSIONS => "MAX_SESSIONS",
             close_reason::MAX_LINKS => "MAX_LINKS",
             close_reason::EXPIRED => "EXPIRED",
+            close_reason::QUOTA_EXCEEDED => "QUOTA_EXCEEDED",
+            close_reason::MIGRATE => "MIGRATE",
             _ => "UNKNOWN",
         }
     }
@@ -137,6 +157,8 @@ pub enum TransportBody {
     Join(Join),
     Close(Close),
     KeepAlive(KeepAlive),
+    Ping(Ping),
+    Pong(Pong),
     Frame(Frame),
 }
 
@@ -155,6 +177,8 @@ impl TransportMessage {
         zid: ZenohId,
         sn_resolution: ZInt,
         is_qos: bool,
+        is_preamble: bool,
+        extensions: ZInt,
         attachment: Option<Attachment>,
     ) -> TransportMessage {
         TransportMessage {
@@ -164,6 +188,8 @@ impl TransportMessage {
                 zid,
                 sn_resolution,
                 is_qos,
+                is_preamble,
+                extensions,
             }),
             attachment,
             #[cfg(feature = "stats")]
@@ -176,6 +202,8 @@ impl TransportMessage {
         zid: ZenohId,
         sn_resolution: Option<ZInt>,
         is_qos: bool,
+        is_preamble: bool,
+        extensions: ZInt,
         cookie: ZSlice,
         attachment: Option<Attachment>,
     ) -> TransportMessage {
@@ -185,6 +213,8 @@ impl TransportMessage {
                 zid,
                 sn_resolution,
                 is_qos,
+                is_preamble,
+                extensions,
                 cookie,
             }),
             attachment,
@@ -231,6 +261,7 @@ impl TransportMessage {
         lease: Duration,
         sn_resolution: ZInt,
         next_sns: ConduitSnList,
+        token: Option<Vec<u8>>,
         attachment: Option<Attachment>,
     ) -> TransportMessage {
         TransportMessage {
@@ -241,6 +272,7 @@ impl TransportMessage {
                 lease,
                 sn_resolution,
                 next_sns,
+                token,
             }),
             attachment,
             #[cfg(feature = "stats")]
@@ -259,6 +291,26 @@ impl TransportMessage {
                 zid,
                 reason,
                 link_only,
+                locator: None,
+            }),
+            attachment,
+            #[cfg(feature = "stats")]
+            size: None,
+        }
+    }
+
+    pub fn make_migrate(
+        zid: Option<ZenohId>,
+        locator: Locator,
+        link_only: bool,
+        attachment: Option<Attachment>,
+    ) -> TransportMessage {
+        TransportMessage {
+            body: TransportBody::Close(Close {
+                zid,
+                reason: tmsg::close_reason::MIGRATE,
+                link_only,
+                locator: Some(locator),
             }),
             attachment,
             #[cfg(feature = "stats")]
@@ -278,6 +330,24 @@ impl TransportMessage {
         }
     }
 
+    pub fn make_ping(hash: ZInt, attachment: Option<Attachment>) -> TransportMessage {
+        TransportMessage {
+            body: TransportBody::Ping(Ping { hash }),
+            attachment,
+            #[cfg(feature = "stats")]
+            size: None,
+        }
+    }
+
+    pub fn make_pong(hash: ZInt, attachment: Option<Attachment>) -> TransportMessage {
+        TransportMessage {
+            body: TransportBody::Pong(Pong { hash }),
+            attachment,
+            #[cfg(feature = "stats")]
+            size: None,
+        }
+    }
+
     pub fn make_frame(
         channel: Channel,
         sn: ZInt,
@@ -308,7 +378,7 @@ impl TransportMessage {
             None
         };
 
-        let body = match rng.gen_range(0..8) {
+        let body = match rng.gen_range(0..10) {
             0 => TransportBody::InitSyn(InitSyn::rand()),
             1 => TransportBody::InitAck(InitAck::rand()),
             2 => TransportBody::OpenSyn(OpenSyn::rand()),
@@ -317,6 +387,8 @@ impl TransportMessage {
             5 => TransportBody::Close(Close::rand()),
             6 => TransportBody::KeepAlive(KeepAlive::rand()),
             7 => TransportBody::Frame(Frame::rand()),
+            8 => TransportBody::Ping(Ping::rand()),
+            9 => TransportBody::Pong(Pong::rand()),
             _ => unreachable!(),
         };
 
@@ -0,0 +1,66 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::core::ZInt;
+
+/// # PingPong message
+///
+/// ```text
+/// NOTE: 16 bits (2 bytes) may be prepended to the serialized message indicating the total length
+///       in bytes of the message, resulting in the maximum length of a message being 65_535 bytes.
+///       This is necessary in those stream-oriented transports (e.g., TCP) that do not preserve
+///       the boundary of the serialized messages. The length is encoded as little-endian.
+///       In any case, the length of a message must not exceed 65_535 bytes.
+///
+/// The PING message is sent periodically on each link to measure its round-trip time.
+/// It is answered on the same link with a PONG message carrying the same hash.
+///
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |X|X|P| P_PONG  |
+/// +-+-+-+-+-------+
+/// ~     hash      ~ -- Opaque value echoed by the PONG, e.g. the time at which the PING was sent.
+/// +---------------+
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ping {
+    pub hash: ZInt,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pong {
+    pub hash: ZInt,
+}
+
+impl Ping {
+    #[cfg(feature = "test")]
+    pub fn rand() -> Self {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+
+        Self { hash: rng.gen() }
+    }
+}
+
+impl Pong {
+    #[cfg(feature = "test")]
+    pub fn rand() -> Self {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+
+        Self { hash: rng.gen() }
+    }
+}
@@ -0,0 +1,72 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+/// # Preamble
+///
+/// ```text
+/// The PREAMBLE is not a message: it is optionally prepended to each batch sent on the stream-oriented
+/// links (e.g., TCP), before the 16 bits length of the batch, when both sides have agreed on it in the
+/// INIT messages. It makes a capture of such a link self-describing and allows a reader that lost
+/// the boundary of the batches, e.g. after a decoding error, to resynchronize on the next batch.
+///
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |     0x5a      | -- 'Z'
+/// +---------------+
+/// |     0x4e      | -- 'N'
+/// +---------------+
+/// |     0x48      | -- 'H'
+/// +---------------+
+/// |    version    | -- the version of the framing
+/// +---------------+
+/// %   length      % -- 16 bits little-endian length of the batch
+/// +---------------+
+/// ~    batch      ~
+/// +---------------+
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preamble {
+    pub version: u8,
+}
+
+impl Preamble {
+    pub const MAGIC: [u8; 3] = *b"ZNH";
+    pub const VERSION: u8 = 0x01;
+    pub const LEN: usize = Self::MAGIC.len() + 1;
+
+    pub const fn new() -> Self {
+        Self {
+            version: Self::VERSION,
+        }
+    }
+
+    pub const fn to_bytes(&self) -> [u8; Self::LEN] {
+        [Self::MAGIC[0], Self::MAGIC[1], Self::MAGIC[2], self.version]
+    }
+
+    #[cfg(feature = "test")]
+    pub fn rand() -> Self {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+
+        Self { version: rng.gen() }
+    }
+}
+
+impl Default for Preamble {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,56 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::core::ZInt;
+use alloc::string::String;
+
+/// # CongestionDrop message
+///
+/// ```text
+/// NOTE: only sent on the transports that agreed on tmsg::init_options::CONGESTION_DROP.
+///
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |X|X|X| CG_DROP |
+/// +-+-+-+---------+
+/// ~    KeyExpr    ~ -- The key expression of the dropped data messages
+/// +---------------+
+/// ~    dropped    ~ -- The number of data messages dropped
+/// +---------------+
+/// ```
+///
+/// The CongestionDrop message notifies a node that data messages on the key expression it sent
+/// have been dropped downstream because of congestion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CongestionDrop {
+    pub key_expr: String,
+    pub dropped: ZInt,
+}
+
+impl CongestionDrop {
+    #[cfg(feature = "test")]
+    pub fn rand() -> Self {
+        use rand::{
+            distributions::{Alphanumeric, DistString},
+            Rng,
+        };
+
+        let mut rng = rand::thread_rng();
+
+        let len = rng.gen_range(1..16);
+        let key_expr = Alphanumeric.sample_string(&mut rng, len);
+        let dropped: ZInt = rng.gen();
+
+        Self { key_expr, dropped }
+    }
+}
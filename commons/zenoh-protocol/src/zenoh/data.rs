@@ -12,7 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::core::{CongestionControl, Encoding, SampleKind, Timestamp, WireExpr, ZInt, ZenohId};
-use zenoh_buffers::ZBuf;
+use core::fmt;
+use zenoh_buffers::{SplitBuffer, ZBuf};
 
 /// # ReplyContext decorator
 ///
@@ -75,6 +76,73 @@ impl ReplyContext {
     }
 }
 
+/// # TraceContext
+///
+/// The TraceContext identifies the distributed trace and the span a message belongs to, following
+/// the W3C Trace Context model, so that the spans created on each hop can be correlated. It is
+/// only sent to the peers that agreed on tmsg::init_options::TRACE_CONTEXT, the trace stopping at
+/// the others.
+///
+/// ```text
+///
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+---------+
+/// ~   trace_id    ~ 16 bytes
+/// +---------------+
+/// ~    span_id    ~ 8 bytes
+/// +---------------+
+/// |     flags     |
+/// +---------------+
+///
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub flags: u8,
+}
+
+impl TraceContext {
+    /// The trace has been sampled by the originator.
+    pub const SAMPLED: u8 = 0x01;
+
+    /// Returns a context with the same trace and flags, but a new `span_id`.
+    pub fn child(&self, span_id: [u8; 8]) -> Self {
+        Self {
+            trace_id: self.trace_id,
+            span_id,
+            flags: self.flags,
+        }
+    }
+
+    #[cfg(feature = "test")]
+    pub fn rand() -> Self {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        Self {
+            trace_id: rng.gen(),
+            span_id: rng.gen(),
+            flags: rng.gen(),
+        }
+    }
+}
+
+/// Formats the context as the value of a W3C `traceparent` header.
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "00-")?;
+        for b in self.trace_id.iter() {
+            write!(f, "{b:02x}")?;
+        }
+        write!(f, "-")?;
+        for b in self.span_id.iter() {
+            write!(f, "{b:02x}")?;
+        }
+        write!(f, "-{:02x}", self.flags)
+    }
+}
+
 /// # DataInfo
 ///
 /// DataInfo data structure is optionally included in Data messages
@@ -86,8 +154,8 @@ impl ReplyContext {
 /// -  1: Payload kind
 /// -  2: Payload encoding
 /// -  3: Payload timestamp
-/// -  4: Reserved
-/// -  5: Reserved
+/// -  4: Payload trace_context
+/// -  5: Payload latched
 /// -  6: Reserved
 /// -  7: Payload source_id
 /// -  8: Payload source_sn
@@ -103,12 +171,16 @@ impl ReplyContext {
 /// +---------------+
 /// ~   timestamp   ~ if options & (1 << 3)
 /// +---------------+
+/// ~ trace_context ~ if options & (1 << 4) -- only sent when tmsg::init_options::TRACE_CONTEXT is agreed
+/// +---------------+
 /// ~   source_id   ~ if options & (1 << 7)
 /// +---------------+
 /// ~   source_sn   ~ if options & (1 << 8)
 /// +---------------+
 ///
 /// - if options & (1 << 0) then the payload is sliced
+/// - if options & (1 << 5) then the payload is latched: the routers keep the most recent latched
+///   payload of each key expression and deliver it to the subscribers declared afterwards
 ///
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -120,6 +192,8 @@ pub struct DataInfo {
     pub timestamp: Option<Timestamp>,
     pub source_id: Option<ZenohId>,
     pub source_sn: Option<ZInt>,
+    pub trace_context: Option<TraceContext>,
+    pub latched: bool,
 }
 
 impl DataInfo {
@@ -140,6 +214,8 @@ impl DataInfo {
         });
         let source_id = rng.gen_bool(0.5).then(ZenohId::rand);
         let source_sn = rng.gen_bool(0.5).then(|| rng.gen());
+        let trace_context = rng.gen_bool(0.5).then(TraceContext::rand);
+        let latched = rng.gen_bool(0.5);
 
         Self {
             #[cfg(feature = "shared-memory")]
@@ -149,6 +225,8 @@ impl DataInfo {
             timestamp,
             source_id,
             source_sn,
+            trace_context,
+            latched,
         }
     }
 }
@@ -167,6 +245,17 @@ impl DataInfo {
 /// ~    Payload    ~
 /// +---------------+
 ///
+/// On the transports that agreed on tmsg::init_options::UNIT_DATA, a Data message without DataInfo
+/// nor payload (e.g. a pure event) may be encoded as a Unit message with the E flag set instead,
+/// carrying only its key expression:
+///
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |K|E|D|  UNIT   |
+/// +-+-+-+---------+
+/// ~    KeyExpr     ~ if K==1 -- Only numerical id
+/// +---------------+
+///
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Data {
@@ -175,9 +264,17 @@ pub struct Data {
     pub payload: ZBuf,
     pub congestion_control: CongestionControl,
     pub reply_context: Option<ReplyContext>,
+    // Whether the message is encoded as a Unit when it has neither DataInfo nor payload,
+    // only set on the transports that agreed on tmsg::init_options::UNIT_DATA
+    pub unit: bool,
 }
 
 impl Data {
+    /// Returns `true` if the message has neither DataInfo nor payload, e.g. a pure event.
+    pub fn is_unit(&self) -> bool {
+        self.data_info.is_none() && self.payload.is_empty()
+    }
+
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
         use rand::Rng;
@@ -213,6 +310,7 @@ impl Data {
             payload,
             congestion_control,
             reply_context,
+            unit: false,
         }
     }
 }
@@ -244,13 +244,15 @@ impl ForgetSubscriber {
 /// ```text
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
-/// |K|Q|X|  QABLE  |
+/// |K|Q|L|  QABLE  |
 /// +---------------+
 /// ~    KeyExpr     ~ if K==1 then key_expr has suffix
 /// +---------------+
 /// ~   QablInfo    ~ if Q==1
 /// +---------------+
 /// ```
+///
+/// The queryable is load balanced with its equivalent queryables if L==1.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Queryable {
     pub key: WireExpr<'static>,
@@ -267,7 +269,12 @@ impl Queryable {
         let key = WireExpr::rand();
         let complete: ZInt = rng.gen();
         let distance: ZInt = rng.gen();
-        let info = QueryableInfo { complete, distance };
+        let load_balanced: bool = rng.gen();
+        let info = QueryableInfo {
+            complete,
+            distance,
+            load_balanced,
+        };
 
         Self { key, info }
     }
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+mod congestion;
 mod data;
 mod declare;
 mod linkstate;
@@ -23,9 +24,11 @@ use crate::{
     common::Attachment,
     core::{
         Channel, CongestionControl, ConsolidationMode, QueryTarget, Reliability, WireExpr, ZInt,
+        ZenohId,
     },
 };
 use alloc::{string::String, vec::Vec};
+pub use congestion::*;
 use core::fmt;
 pub use data::*;
 pub use declare::*;
@@ -53,6 +56,7 @@ pub mod zmsg {
         pub const PULL: u8 = imsg::id::PULL;
         pub const UNIT: u8 = imsg::id::UNIT;
         pub const LINK_STATE_LIST: u8 = imsg::id::LINK_STATE_LIST;
+        pub const CONGESTION_DROP: u8 = imsg::id::CONGESTION_DROP;
 
         // Message decorators
         pub const PRIORITY: u8 = imsg::id::PRIORITY;
@@ -65,9 +69,11 @@ pub mod zmsg {
     pub mod flag {
         pub const B: u8 = 1 << 6; // 0x40 QueryBody     if B==1 then QueryBody is present
         pub const D: u8 = 1 << 5; // 0x20 Drop          if D==1 then the message can be dropped
+        pub const E: u8 = 1 << 6; // 0x40 Event         if E==1 then the Unit carries a key expression, i.e. it is a Data without payload
         pub const F: u8 = 1 << 5; // 0x20 Final         if F==1 then this is the final message (e.g., ReplyContext, Pull)
         pub const I: u8 = 1 << 6; // 0x40 DataInfo      if I==1 then DataInfo is present
         pub const K: u8 = 1 << 7; // 0x80 KeySuffix     if K==1 then key_expr has suffix
+        pub const L: u8 = 1 << 5; // 0x20 LoadBalanced  if L==1 then the queryable is load balanced
         pub const N: u8 = 1 << 6; // 0x40 MaxSamples    if N==1 then the MaxSamples is indicated
         pub const P: u8 = 1 << 0; // 0x01 Zid           if P==1 then the zid is present
         pub const Q: u8 = 1 << 6; // 0x40 QueryableInfo if Q==1 then the queryable info is present
@@ -90,14 +96,22 @@ pub mod zmsg {
             pub const KIND: ZInt = 1 << 1; // 0x02
             pub const ENCODING: ZInt = 1 << 2; // 0x04
             pub const TIMESTAMP: ZInt = 1 << 3; // 0x08
-                                                // 0x10: Reserved
-                                                // 0x20: Reserved
-                                                // 0x40: Reserved
+            pub const TRACE_CONTEXT: ZInt = 1 << 4; // 0x10
+            pub const LATCHED: ZInt = 1 << 5; // 0x20
+                                              // 0x40: Reserved
             pub const SRCID: ZInt = 1 << 7; // 0x80
             pub const SRCSN: ZInt = 1 << 8; // 0x100
         }
     }
 
+    pub mod query_options {
+        use super::ZInt;
+
+        pub const CONSOLIDATION_MASK: ZInt = 0x0f;
+        pub const TIMEOUT: ZInt = 1 << 4; // 0x10 only sent when tmsg::init_options::QUERY_TIMEOUT is agreed
+        pub const DESTINATIONS: ZInt = 1 << 5; // 0x20 only sent when tmsg::init_options::QUERY_DESTINATIONS is agreed
+    }
+
     pub mod declaration {
         pub mod id {
             // Declarations
@@ -175,6 +189,10 @@ pub mod zmsg {
             priority: Priority::Control,
             reliability: Reliability::Reliable,
         };
+        pub const CONGESTION_DROP: Channel = Channel {
+            priority: Priority::Data,
+            reliability: Reliability::BestEffort,
+        };
     }
 
     // Default congestion control for each Zenoh Message
@@ -188,6 +206,7 @@ pub mod zmsg {
         pub const REPLY: CongestionControl = CongestionControl::Block;
         pub const UNIT: CongestionControl = CongestionControl::Block;
         pub const LINK_STATE_LIST: CongestionControl = CongestionControl::Block;
+        pub const CONGESTION_DROP: CongestionControl = CongestionControl::Drop;
     }
 }
 
@@ -201,6 +220,7 @@ pub enum ZenohBody {
     Query(Query),
     Declare(Declare),
     LinkStateList(LinkStateList),
+    CongestionDrop(CongestionDrop),
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -248,6 +268,7 @@ impl ZenohMessage {
                 payload,
                 congestion_control,
                 reply_context,
+                unit: false,
             }),
             channel,
             routing_context,
@@ -306,6 +327,8 @@ impl ZenohMessage {
         qid: ZInt,
         target: Option<QueryTarget>,
         consolidation: ConsolidationMode,
+        timeout: Option<ZInt>,
+        destinations: Option<Vec<ZenohId>>,
         body: Option<QueryBody>,
         routing_context: Option<RoutingContext>,
         attachment: Option<Attachment>,
@@ -317,6 +340,8 @@ impl ZenohMessage {
                 qid,
                 target,
                 consolidation,
+                timeout,
+                destinations,
                 body,
             }),
             channel: zmsg::default_channel::QUERY,
@@ -341,6 +366,33 @@ impl ZenohMessage {
         }
     }
 
+    pub fn make_congestion_drop(
+        key_expr: String,
+        dropped: ZInt,
+        attachment: Option<Attachment>,
+    ) -> ZenohMessage {
+        ZenohMessage {
+            body: ZenohBody::CongestionDrop(CongestionDrop { key_expr, dropped }),
+            channel: zmsg::default_channel::CONGESTION_DROP,
+            routing_context: None,
+            attachment,
+            #[cfg(feature = "stats")]
+            size: None,
+        }
+    }
+
+    // -- Message Accessors
+    #[inline]
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        match &self.body {
+            ZenohBody::Data(data) => data
+                .data_info
+                .as_ref()
+                .and_then(|info| info.trace_context.as_ref()),
+            _ => None,
+        }
+    }
+
     // -- Message Predicates
     #[inline]
     pub fn is_reliable(&self) -> bool {
@@ -349,20 +401,20 @@ impl ZenohMessage {
 
     #[inline]
     pub fn is_droppable(&self) -> bool {
-        if !self.is_reliable() {
-            return true;
-        }
+        !self.is_reliable() || self.congestion_control() == CongestionControl::Drop
+    }
 
-        let cc = match &self.body {
+    #[inline]
+    pub fn congestion_control(&self) -> CongestionControl {
+        match &self.body {
             ZenohBody::Data(data) => data.congestion_control,
             ZenohBody::Unit(unit) => unit.congestion_control,
             ZenohBody::Declare(_) => zmsg::default_congestion_control::DECLARE,
             ZenohBody::Pull(_) => zmsg::default_congestion_control::PULL,
             ZenohBody::Query(_) => zmsg::default_congestion_control::QUERY,
             ZenohBody::LinkStateList(_) => zmsg::default_congestion_control::LINK_STATE_LIST,
-        };
-
-        cc == CongestionControl::Drop
+            ZenohBody::CongestionDrop(_) => zmsg::default_congestion_control::CONGESTION_DROP,
+        }
     }
 }
 
@@ -418,13 +470,14 @@ impl ZenohMessage {
             priority,
             reliability,
         };
-        let body = match rng.gen_range(0..6) {
+        let body = match rng.gen_range(0..7) {
             0 => ZenohBody::Data(Data::rand()),
             1 => ZenohBody::Unit(Unit::rand()),
             2 => ZenohBody::Pull(Pull::rand()),
             3 => ZenohBody::Query(Query::rand()),
             4 => ZenohBody::Declare(Declare::rand()),
             5 => ZenohBody::LinkStateList(LinkStateList::rand()),
+            6 => ZenohBody::CongestionDrop(CongestionDrop::rand()),
             _ => unreachable!(),
         };
 
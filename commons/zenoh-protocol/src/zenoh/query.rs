@@ -12,10 +12,10 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::{
-    core::{ConsolidationMode, QueryTarget, WireExpr, ZInt},
+    core::{ConsolidationMode, QueryTarget, WireExpr, ZInt, ZenohId},
     zenoh::DataInfo,
 };
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use zenoh_buffers::ZBuf;
 
 /// # QueryBody
@@ -68,10 +68,22 @@ impl QueryBody {
 /// +---------------+
 /// ~     target    ~ if T==1
 /// +---------------+
-/// ~ consolidation ~
+/// ~ consolidation ~ the options of the query are or-ed with the consolidation mode
+/// +---------------+
+/// ~    timeout    ~ if options & TIMEOUT
+/// +---------------+
+/// ~ destinations  ~ if options & DESTINATIONS -- the number of zids followed by the zids
 /// +---------------+
 /// ~   QueryBody   ~ if B==1
 /// +---------------+
+/// ```
+///
+/// The timeout is the time in milliseconds left before the query expires at its origin when the
+/// message is sent, allowing the routers and queryables to abandon the expired queries.
+///
+/// The destinations restrict the query to the queryables of the runtimes with the given zids, the
+/// routers only forwarding it towards them. They are only sent to the nodes having agreed on the
+/// QUERY_DESTINATIONS option in INIT.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Query {
     pub key: WireExpr<'static>,
@@ -79,6 +91,8 @@ pub struct Query {
     pub qid: ZInt,
     pub target: Option<QueryTarget>,
     pub consolidation: ConsolidationMode,
+    pub timeout: Option<ZInt>,
+    pub destinations: Option<Vec<ZenohId>>,
     pub body: Option<QueryBody>,
 }
 
@@ -128,6 +142,14 @@ impl Query {
         .choose(&mut rng)
         .unwrap();
 
+        let timeout = rng.gen_bool(0.5).then(|| rng.gen());
+
+        let destinations = rng.gen_bool(0.5).then(|| {
+            (0..rng.gen_range(1..MAX))
+                .map(|_| ZenohId::rand())
+                .collect()
+        });
+
         let body = if rng.gen_bool(0.5) {
             Some(QueryBody::rand())
         } else {
@@ -140,6 +162,8 @@ impl Query {
             qid,
             target,
             consolidation,
+            timeout,
+            destinations,
             body,
         }
     }
@@ -23,6 +23,8 @@ use crate::core::CongestionControl;
 /// +-+-+-+---------+
 ///
 /// ```
+///
+/// A Unit with the E flag set is a [`Data`](super::Data) message without DataInfo nor payload.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Unit {
     pub congestion_control: CongestionControl,
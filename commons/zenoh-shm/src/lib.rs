@@ -19,12 +19,18 @@ use std::{
     collections::{binary_heap::BinaryHeap, HashMap},
     fmt, mem,
     sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+    time::{Duration, Instant},
 };
 use zenoh_buffers::ZSliceBuffer;
 use zenoh_result::{bail, zerror, ShmError, ZResult};
 
+mod provider;
+pub use provider::*;
+
 const MIN_FREE_CHUNK_SIZE: usize = 1_024;
 const ACCOUNTED_OVERHEAD: usize = 4_096;
+// The shared memory segments are mapped at page boundaries
+const MAX_ALIGNMENT: usize = 4_096;
 const ZENOH_SHM_PREFIX: &str = "zenoh_shm_zid";
 
 // Chunk header
@@ -63,6 +69,34 @@ impl PartialEq for Chunk {
     }
 }
 
+// A chunk referenced by at least one buffer the last time it was garbage collected
+#[derive(Debug)]
+struct BusyChunk {
+    chunk: Chunk,
+    since: Instant,
+    leaked: bool,
+}
+
+/// How a [`SharedMemoryManager`] detects the buffers held beyond a grace period, e.g. by crashed
+/// or stuck readers.
+///
+/// The held buffers are detected and reported when the manager is garbage collected. They are
+/// never freed while referenced: a buffer is only reclaimed once its last reference is released.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeakPolicy {
+    /// The duration after which a buffer still referenced is considered leaked.
+    pub grace_period: Duration,
+}
+
+/// The buffer statistics of a [`SharedMemoryManager`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SharedMemoryStats {
+    /// The number of buffers currently allocated.
+    pub busy: usize,
+    /// The number of buffers detected as held beyond the grace period.
+    pub leaked: usize,
+}
+
 /// Informations about a [`SharedMemoryBuf`].
 ///
 /// This that can be serialized and can be used to retrieve the [`SharedMemoryBuf`] in a remote process.
@@ -170,7 +204,16 @@ impl SharedMemoryBuf {
 
     pub fn dec_ref_count(&self) {
         let rc = self.rc_ptr.load(Ordering::SeqCst);
-        unsafe { (*rc).fetch_sub(1, Ordering::SeqCst) };
+        // Releasing a reference the buffer does not have would free it under its owners
+        if unsafe { (*rc).fetch_update(Ordering::SeqCst, Ordering::SeqCst, |c| c.checked_sub(1)) }
+            .is_err()
+        {
+            log::error!(
+                "Refusing to release a reference of the unreferenced shared memory buffer at offset {} of {}",
+                self.info.offset,
+                self.info.shm_manager
+            );
+        }
     }
 
     pub fn as_slice(&self) -> &[u8] {
@@ -308,8 +351,12 @@ pub struct SharedMemoryManager {
     available: usize,
     own_segment: Shmem,
     free_list: BinaryHeap<Chunk>,
-    busy_list: Vec<Chunk>,
+    busy_list: Vec<BusyChunk>,
     alignment: usize,
+    // The chunk header is padded so that the buffers are aligned
+    header_size: usize,
+    leak_policy: Option<LeakPolicy>,
+    leaked: usize,
 }
 
 unsafe impl Send for SharedMemoryManager {}
@@ -318,6 +365,27 @@ impl SharedMemoryManager {
     /// Creates a new SharedMemoryManager managing allocations of a region of the
     /// given size.
     pub fn make(id: String, size: usize) -> ZResult<SharedMemoryManager> {
+        Self::make_aligned(id, size, mem::align_of::<ChunkHeaderType>())
+    }
+
+    /// Creates a new SharedMemoryManager managing allocations of a region of the
+    /// given size, whose buffers are aligned at the given alignment.
+    ///
+    /// The alignment must be a power of two, between the alignment of the chunk header
+    /// and 4096.
+    pub fn make_aligned(id: String, size: usize, alignment: usize) -> ZResult<SharedMemoryManager> {
+        if !alignment.is_power_of_two()
+            || alignment < mem::align_of::<ChunkHeaderType>()
+            || alignment > MAX_ALIGNMENT
+        {
+            return Err(ShmError(zerror!(
+                "Invalid SharedMemoryManager alignment {}: must be a power of two between {} and {}",
+                alignment,
+                mem::align_of::<ChunkHeaderType>(),
+                MAX_ALIGNMENT
+            ))
+            .into());
+        }
         let mut temp_dir = std::env::temp_dir();
         let file_name: String = format!("{ZENOH_SHM_PREFIX}_{id}");
         temp_dir.push(file_name);
@@ -326,7 +394,7 @@ impl SharedMemoryManager {
             .ok_or_else(|| ShmError(zerror!("Unable to parse tmp directory: {:?}", temp_dir)))?
             .to_string();
         log::trace!("Creating file at: {}", path);
-        let real_size = size + ACCOUNTED_OVERHEAD;
+        let real_size = align_addr_at(size + ACCOUNTED_OVERHEAD, alignment);
         let shmem = match ShmemConf::new()
             .size(real_size)
             .flink(path.clone())
@@ -361,7 +429,10 @@ impl SharedMemoryManager {
             own_segment: shmem,
             free_list,
             busy_list,
-            alignment: mem::align_of::<ChunkHeaderType>(),
+            alignment,
+            header_size: align_addr_at(CHUNK_HEADER_SIZE, alignment),
+            leak_policy: None,
+            leaked: 0,
         };
         log::trace!(
             "Created SharedMemoryManager for {:?}",
@@ -370,31 +441,83 @@ impl SharedMemoryManager {
         Ok(shm)
     }
 
+    // The padding before the chunk header, which immediately precedes the buffer
+    fn header_padding(&self) -> usize {
+        self.header_size - CHUNK_HEADER_SIZE
+    }
+
     fn free_chunk_map_to_shmbuf(&self, chunk: &Chunk) -> SharedMemoryBuf {
+        // The info points to the chunk header, as expected by the SharedMemoryReader
         let info = SharedMemoryBufInfo {
-            offset: chunk.offset,
-            length: chunk.size,
+            offset: chunk.offset + self.header_padding(),
+            length: chunk.size - self.header_padding(),
             shm_manager: self.segment_path.clone(),
             kind: 0,
         };
-        let rc = chunk.base_addr as *mut ChunkHeaderType;
+        let rc = unsafe { chunk.base_addr.add(self.header_padding()) } as *mut ChunkHeaderType;
         unsafe { (*rc).store(1, Ordering::SeqCst) };
         let rc_ptr = AtomicPtr::<ChunkHeaderType>::new(rc);
         SharedMemoryBuf {
             rc_ptr,
-            buf: AtomicPtr::<u8>::new(unsafe { chunk.base_addr.add(CHUNK_HEADER_SIZE) }),
-            len: chunk.size - CHUNK_HEADER_SIZE,
+            buf: AtomicPtr::<u8>::new(unsafe { chunk.base_addr.add(self.header_size) }),
+            len: chunk.size - self.header_size,
             info,
         }
     }
 
+    /// The size of the managed region.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The amount of memory not allocated, including the buffers no longer used but not
+    /// garbage collected yet.
+    pub fn available(&self) -> usize {
+        self.available
+    }
+
+    /// The alignment of the allocated buffers.
+    pub fn alignment(&self) -> usize {
+        self.alignment
+    }
+
+    /// Sets how the buffers held beyond a grace period are treated, `None` disabling their
+    /// detection.
+    pub fn set_leak_policy(&mut self, policy: Option<LeakPolicy>) {
+        self.leak_policy = policy;
+    }
+
+    pub fn stats(&self) -> SharedMemoryStats {
+        SharedMemoryStats {
+            busy: self.busy_list.len(),
+            leaked: self.leaked,
+        }
+    }
+
     pub fn alloc(&mut self, len: usize) -> ZResult<SharedMemoryBuf> {
         log::trace!("SharedMemoryManager::alloc({})", len);
-        // Always allocate a size that will keep the proper alignment requirements
-        let required_len = align_addr_at(len + CHUNK_HEADER_SIZE, self.alignment);
+        let required_len = self.required_len(len);
         if self.available < required_len {
             self.garbage_collect();
         }
+        if self.available >= required_len {
+            self.try_alloc(len)
+        } else {
+            let e = zerror!( "SharedMemoryManager does not have sufficient free memory to allocate {} bytes, try de-fragmenting!", len);
+            log::warn!("{}", e);
+            Err(e.into())
+        }
+    }
+
+    fn required_len(&self, len: usize) -> usize {
+        // Always allocate a size that will keep the proper alignment requirements
+        align_addr_at(len + self.header_size, self.alignment)
+    }
+
+    /// Allocates a buffer without garbage collecting the buffers no longer used.
+    pub fn try_alloc(&mut self, len: usize) -> ZResult<SharedMemoryBuf> {
+        log::trace!("SharedMemoryManager::try_alloc({})", len);
+        let required_len = self.required_len(len);
         if self.available >= required_len {
             // The strategy taken is the same for some Unix System V implementations -- as described in the
             // famous Bach's book --  in essence keep an ordered list of free slot and always look for the
@@ -416,7 +539,11 @@ impl SharedMemoryManager {
                     let shm_buf = self.free_chunk_map_to_shmbuf(&chunk);
                     log::trace!("The allocated Chunk is ({:?})", &chunk);
                     log::trace!("Allocated Shared Memory Buffer: {:?}", &shm_buf);
-                    self.busy_list.push(chunk);
+                    self.busy_list.push(BusyChunk {
+                        chunk,
+                        since: Instant::now(),
+                        leaked: false,
+                    });
                     Ok(shm_buf)
                 }
                 Some(c) => {
@@ -431,14 +558,17 @@ impl SharedMemoryManager {
                 }
             }
         } else {
-            let e = zerror!( "SharedMemoryManager does not have sufficient free memory to allocate {} bytes, try de-fragmenting!", len);
-            log::warn!("{}", e);
+            let e = zerror!(
+                "SharedMemoryManager does not have sufficient free memory to allocate {} bytes",
+                len
+            );
+            log::trace!("{}", e);
             Err(e.into())
         }
     }
 
-    fn is_free_chunk(chunk: &Chunk) -> bool {
-        let rc_ptr = chunk.base_addr as *mut ChunkHeaderType;
+    fn is_free_chunk(&self, chunk: &Chunk) -> bool {
+        let rc_ptr = unsafe { chunk.base_addr.add(self.header_padding()) } as *mut ChunkHeaderType;
         let rc = unsafe { (*rc_ptr).load(Ordering::SeqCst) };
         rc == 0
     }
@@ -492,20 +622,34 @@ impl SharedMemoryManager {
     }
 
     /// Returns the amount of memory freed
+    ///
+    /// The buffers held beyond the grace period of the [`LeakPolicy`] are reported as leaked,
+    /// only the buffers no longer referenced being freed.
     pub fn garbage_collect(&mut self) -> usize {
         log::trace!("Running Garbage Collector");
 
+        let now = Instant::now();
         let mut freed = 0;
-        let (free, busy) = self
-            .busy_list
-            .iter()
-            .partition(|&c| SharedMemoryManager::is_free_chunk(c));
-        self.busy_list = busy;
-
-        for f in free {
-            freed += f.size;
-            log::trace!("Garbage Collecting Chunk: {:?}", f);
-            self.free_list.push(f)
+        for mut b in mem::take(&mut self.busy_list) {
+            if !self.is_free_chunk(&b.chunk) {
+                if let Some(policy) = self.leak_policy {
+                    if !b.leaked && now.duration_since(b.since) >= policy.grace_period {
+                        b.leaked = true;
+                        self.leaked += 1;
+                        log::warn!(
+                            "Shared memory buffer at offset {} of {} held for more than {:?}",
+                            b.chunk.offset,
+                            self.segment_path,
+                            policy.grace_period
+                        );
+                    }
+                }
+                self.busy_list.push(b);
+                continue;
+            }
+            freed += b.chunk.size;
+            log::trace!("Garbage Collecting Chunk: {:?}", b.chunk);
+            self.free_list.push(b.chunk)
         }
         self.available += freed;
         freed
@@ -0,0 +1,436 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::{LeakPolicy, SharedMemoryBuf, SharedMemoryManager, SharedMemoryStats};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_result::{bail, zerror, ShmError, ZResult};
+
+// The period at which a blocked allocation is retried
+const BLOCK_RETRY_PERIOD: Duration = Duration::from_millis(1);
+
+/// What a [`SharedMemoryProvider`] does when its segments are full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WhenFull {
+    /// Fails immediately.
+    Fail,
+    /// Blocks until enough memory is freed, failing once the given duration elapsed.
+    Block(Duration),
+}
+
+/// How a [`SharedMemoryProvider`] tries to free memory when an allocation fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocPolicy {
+    /// Whether the buffers no longer used are garbage collected before retrying.
+    pub garbage_collect: bool,
+    /// Whether the segments are defragmented before retrying.
+    pub defragment: bool,
+    /// What to do if the allocation still fails.
+    pub when_full: WhenFull,
+}
+
+impl Default for AllocPolicy {
+    fn default() -> Self {
+        AllocPolicy {
+            garbage_collect: true,
+            defragment: true,
+            when_full: WhenFull::Fail,
+        }
+    }
+}
+
+struct Segment {
+    name: String,
+    manager: SharedMemoryManager,
+}
+
+/// A provider of shared memory buffers, allocated in several named segments.
+///
+/// The buffers are allocated in the first segment with enough free memory, in the order the segments
+/// were added to the [`SharedMemoryProviderBuilder`]. A provider can be cloned and shared between
+/// threads, the clones allocating in the same segments.
+#[derive(Clone)]
+pub struct SharedMemoryProvider {
+    segments: Arc<Mutex<Vec<Segment>>>,
+    policy: AllocPolicy,
+}
+
+impl SharedMemoryProvider {
+    /// Returns a builder of a provider whose segments are identified by `id`, which must be unique
+    /// on the host.
+    pub fn builder<S: Into<String>>(id: S) -> SharedMemoryProviderBuilder {
+        SharedMemoryProviderBuilder {
+            id: id.into(),
+            segments: vec![],
+            alignment: std::mem::align_of::<usize>(),
+            policy: AllocPolicy::default(),
+            leak_policy: None,
+        }
+    }
+
+    /// Allocates a buffer of at least `len` bytes in the first segment with enough free memory.
+    ///
+    /// With [`WhenFull::Block`], the calling thread is blocked until enough memory is freed: use
+    /// [`alloc_async`](Self::alloc_async) instead in async code.
+    pub fn alloc(&self, len: usize) -> ZResult<SharedMemoryBuf> {
+        self.alloc_with(len, |_| true)
+    }
+
+    /// Allocates a buffer of at least `len` bytes in the segment named `segment`.
+    ///
+    /// With [`WhenFull::Block`], the calling thread is blocked until enough memory is freed: use
+    /// [`alloc_in_async`](Self::alloc_in_async) instead in async code.
+    pub fn alloc_in(&self, segment: &str, len: usize) -> ZResult<SharedMemoryBuf> {
+        self.check_segment(segment)?;
+        self.alloc_with(len, |s| s.name == segment)
+    }
+
+    /// Allocates a buffer of at least `len` bytes in the first segment with enough free memory,
+    /// waiting asynchronously until enough memory is freed with [`WhenFull::Block`].
+    pub async fn alloc_async(&self, len: usize) -> ZResult<SharedMemoryBuf> {
+        self.alloc_with_async(len, |_| true).await
+    }
+
+    /// Allocates a buffer of at least `len` bytes in the segment named `segment`, waiting
+    /// asynchronously until enough memory is freed with [`WhenFull::Block`].
+    pub async fn alloc_in_async(&self, segment: &str, len: usize) -> ZResult<SharedMemoryBuf> {
+        self.check_segment(segment)?;
+        self.alloc_with_async(len, |s| s.name == segment).await
+    }
+
+    fn check_segment(&self, segment: &str) -> ZResult<()> {
+        if !self.segments().iter().any(|s| s == segment) {
+            bail!("Unknown shared memory segment: {}", segment);
+        }
+        Ok(())
+    }
+
+    fn alloc_with<F: Fn(&Segment) -> bool>(&self, len: usize, f: F) -> ZResult<SharedMemoryBuf> {
+        let deadline = self.deadline();
+        loop {
+            if let Some(buf) = self.try_alloc_with(len, &f) {
+                return Ok(buf);
+            }
+            match deadline {
+                Some(deadline) if Instant::now() < deadline => {
+                    std::thread::sleep(BLOCK_RETRY_PERIOD)
+                }
+                _ => return Err(full(len)),
+            }
+        }
+    }
+
+    async fn alloc_with_async<F: Fn(&Segment) -> bool>(
+        &self,
+        len: usize,
+        f: F,
+    ) -> ZResult<SharedMemoryBuf> {
+        let deadline = self.deadline();
+        loop {
+            if let Some(buf) = self.try_alloc_with(len, &f) {
+                return Ok(buf);
+            }
+            match deadline {
+                Some(deadline) if Instant::now() < deadline => {
+                    async_std::task::sleep(BLOCK_RETRY_PERIOD).await
+                }
+                _ => return Err(full(len)),
+            }
+        }
+    }
+
+    // The time at which a blocked allocation fails, none if it fails immediately
+    fn deadline(&self) -> Option<Instant> {
+        match self.policy.when_full {
+            WhenFull::Fail => None,
+            WhenFull::Block(timeout) => Some(Instant::now() + timeout),
+        }
+    }
+
+    // Tries to allocate once in the segments matching `f`, freeing memory according to the policy
+    fn try_alloc_with<F: Fn(&Segment) -> bool>(&self, len: usize, f: F) -> Option<SharedMemoryBuf> {
+        let mut segments = zlock(&self.segments);
+        let try_alloc = |segments: &mut Vec<Segment>| {
+            segments
+                .iter_mut()
+                .filter(|s| f(s))
+                .find_map(|s| s.manager.try_alloc(len).ok())
+        };
+        if let Some(buf) = try_alloc(&mut segments) {
+            return Some(buf);
+        }
+        if self.policy.garbage_collect {
+            for s in segments.iter_mut().filter(|s| f(s)) {
+                s.manager.garbage_collect();
+            }
+            if let Some(buf) = try_alloc(&mut segments) {
+                return Some(buf);
+            }
+        }
+        if self.policy.defragment {
+            for s in segments.iter_mut().filter(|s| f(s)) {
+                s.manager.defragment();
+            }
+            return try_alloc(&mut segments);
+        }
+        None
+    }
+
+    /// The names of the segments.
+    pub fn segments(&self) -> Vec<String> {
+        zlock(&self.segments)
+            .iter()
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    /// The amount of memory not allocated in each segment.
+    pub fn available(&self) -> Vec<(String, usize)> {
+        zlock(&self.segments)
+            .iter()
+            .map(|s| (s.name.clone(), s.manager.available()))
+            .collect()
+    }
+
+    /// The buffer statistics of all the segments.
+    pub fn stats(&self) -> SharedMemoryStats {
+        zlock(&self.segments)
+            .iter()
+            .map(|s| s.manager.stats())
+            .fold(SharedMemoryStats::default(), |acc, s| SharedMemoryStats {
+                busy: acc.busy + s.busy,
+                leaked: acc.leaked + s.leaked,
+            })
+    }
+
+    /// Garbage collects the buffers no longer used in all the segments, returning the amount of
+    /// memory freed.
+    pub fn garbage_collect(&self) -> usize {
+        zlock(&self.segments)
+            .iter_mut()
+            .map(|s| s.manager.garbage_collect())
+            .sum()
+    }
+
+    /// Defragments all the segments, returning the amount of memory defragmented.
+    pub fn defragment(&self) -> usize {
+        zlock(&self.segments)
+            .iter_mut()
+            .map(|s| s.manager.defragment())
+            .sum()
+    }
+}
+
+impl fmt::Debug for SharedMemoryProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedMemoryProvider")
+            .field("segments", &self.segments())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+fn full(len: usize) -> zenoh_result::Error {
+    ShmError(zerror!(
+        "Unable to allocate {} bytes of shared memory: segments full",
+        len
+    ))
+    .into()
+}
+
+// The segments remain consistent if a thread panicked while holding the lock
+fn zlock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A builder of [`SharedMemoryProvider`].
+pub struct SharedMemoryProviderBuilder {
+    id: String,
+    segments: Vec<(String, usize)>,
+    alignment: usize,
+    policy: AllocPolicy,
+    leak_policy: Option<LeakPolicy>,
+}
+
+impl SharedMemoryProviderBuilder {
+    /// Adds a segment of `size` bytes named `name`.
+    pub fn segment<S: Into<String>>(mut self, name: S, size: usize) -> Self {
+        self.segments.push((name.into(), size));
+        self
+    }
+
+    /// Sets the alignment of the allocated buffers, a power of two up to 4096.
+    pub fn alignment(mut self, alignment: usize) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets the allocation policy.
+    pub fn policy(mut self, policy: AllocPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets how the buffers held beyond a grace period are detected in all the segments.
+    pub fn leak_policy(mut self, policy: LeakPolicy) -> Self {
+        self.leak_policy = Some(policy);
+        self
+    }
+
+    pub fn build(self) -> ZResult<SharedMemoryProvider> {
+        if self.segments.is_empty() {
+            bail!("A SharedMemoryProvider needs at least one segment");
+        }
+        let mut segments: Vec<Segment> = Vec::with_capacity(self.segments.len());
+        for (name, size) in self.segments {
+            if segments.iter().any(|s| s.name == name) {
+                bail!("Duplicated shared memory segment: {}", name);
+            }
+            let mut manager = SharedMemoryManager::make_aligned(
+                format!("{}_{}", self.id, name),
+                size,
+                self.alignment,
+            )?;
+            manager.set_leak_policy(self.leak_policy);
+            segments.push(Segment { name, manager });
+        }
+        Ok(SharedMemoryProvider {
+            segments: Arc::new(Mutex::new(segments)),
+            policy: self.policy,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shm_provider() {
+        let id = format!("test_provider_{}", std::process::id());
+        assert!(SharedMemoryProvider::builder(&id).build().is_err());
+        assert!(SharedMemoryProvider::builder(&id)
+            .segment("a", 4096)
+            .alignment(3)
+            .build()
+            .is_err());
+
+        let provider = SharedMemoryProvider::builder(&id)
+            .segment("small", 4096)
+            .segment("large", 65536)
+            .alignment(64)
+            .policy(AllocPolicy {
+                garbage_collect: true,
+                defragment: true,
+                when_full: WhenFull::Fail,
+            })
+            .build()
+            .unwrap();
+        assert_eq!(provider.segments(), vec!["small", "large"]);
+
+        let buf = provider.alloc(100).unwrap();
+        assert!(buf.len() >= 100);
+        assert_eq!(buf.as_slice().as_ptr() as usize % 64, 0);
+        assert!(buf.owner().ends_with("small"));
+
+        let large = provider.alloc_in("large", 10_000).unwrap();
+        assert!(large.owner().ends_with("large"));
+        assert!(provider.alloc_in("unknown", 1).is_err());
+
+        // Filling the large segment fails, and succeeds again once the buffer is dropped
+        assert!(provider.alloc_in("large", 65_536).is_err());
+        drop(large);
+        let large = provider.alloc_in("large", 65_536).unwrap();
+        assert_eq!(large.as_slice().as_ptr() as usize % 64, 0);
+    }
+
+    #[test]
+    fn shm_provider_block() {
+        let id = format!("test_provider_block_{}", std::process::id());
+        let provider = SharedMemoryProvider::builder(&id)
+            .segment("data", 4096)
+            .policy(AllocPolicy {
+                garbage_collect: true,
+                defragment: true,
+                when_full: WhenFull::Block(Duration::from_secs(10)),
+            })
+            .build()
+            .unwrap();
+
+        // The blocked allocation waits without blocking the executor for the buffer to be freed
+        async_std::task::block_on(async {
+            let full = provider.alloc_async(4096).await.unwrap();
+            let release = async_std::task::spawn(async move {
+                async_std::task::sleep(Duration::from_millis(100)).await;
+                drop(full);
+            });
+            let buf = provider.alloc_async(4096).await.unwrap();
+            assert!(buf.len() >= 4096);
+            release.await;
+        });
+
+        let provider = SharedMemoryProvider::builder(&id)
+            .segment("timeout", 4096)
+            .policy(AllocPolicy {
+                garbage_collect: true,
+                defragment: true,
+                when_full: WhenFull::Block(Duration::from_millis(10)),
+            })
+            .build()
+            .unwrap();
+        let _full = provider.alloc(4096).unwrap();
+        assert!(async_std::task::block_on(provider.alloc_async(1)).is_err());
+    }
+
+    #[test]
+    fn shm_provider_leaks() {
+        let id = format!("test_provider_leaks_{}", std::process::id());
+        let provider = SharedMemoryProvider::builder(&id)
+            .segment("data", 4096)
+            .leak_policy(LeakPolicy {
+                grace_period: Duration::ZERO,
+            })
+            .build()
+            .unwrap();
+
+        // A buffer still referenced is reported as leaked, but not freed
+        let mut held = provider.alloc(1024).unwrap();
+        unsafe { held.as_mut_slice().fill(0xab) };
+        let clone = held.clone();
+        assert_eq!(provider.garbage_collect(), 0);
+        let stats = provider.stats();
+        assert_eq!(stats.busy, 1);
+        assert_eq!(stats.leaked, 1);
+
+        // The memory of the held buffer is not reallocated
+        let mut others = vec![];
+        while let Ok(mut buf) = provider.alloc(512) {
+            unsafe { buf.as_mut_slice().fill(0) };
+            others.push(buf);
+        }
+        assert!(!others.is_empty());
+        assert!(held.as_slice().iter().all(|b| *b == 0xab));
+        assert_eq!(held.ref_count(), 2);
+
+        // The buffer is freed once its last reference is released
+        drop(clone);
+        drop(others);
+        assert_eq!(held.ref_count(), 1);
+        drop(held);
+        assert!(provider.garbage_collect() > 0);
+        assert_eq!(provider.stats().busy, 0);
+        assert_eq!(provider.stats().leaked, 1);
+        assert_eq!(provider.alloc(1024).unwrap().ref_count(), 1);
+    }
+}
@@ -55,6 +55,15 @@ impl<T, F: Fn() -> T> RecyclingObjectPool<T, F> {
         let obj = self.inner.pull().await;
         RecyclingObject::new(obj, Arc::downgrade(&self.inner))
     }
+
+    /// Removes the objects currently available in the pool, e.g. to reuse them elsewhere.
+    pub fn drain(&self) -> Vec<T> {
+        let mut objs = vec![];
+        while let Some(obj) = self.inner.try_pull() {
+            objs.push(obj);
+        }
+        objs
+    }
 }
 
 #[derive(Clone)]
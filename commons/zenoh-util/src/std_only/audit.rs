@@ -0,0 +1,387 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Audit log of the security-relevant events, kept separate from the debug logging.
+//!
+//! Events are only formatted and dispatched when at least one [`AuditSink`] is registered.
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zenoh_core::{zlock, zread, zwrite};
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_result::ZResult;
+
+/// A security-relevant event.
+#[derive(Clone, Debug)]
+pub enum AuditEvent {
+    /// A session with a remote node has been opened.
+    SessionOpened {
+        zid: ZenohId,
+        whatami: WhatAmI,
+        link: Option<String>,
+    },
+    /// A session with a remote node has been closed.
+    SessionClosed { zid: ZenohId },
+    /// A remote node failed to authenticate while opening a session.
+    AuthenticationFailed {
+        zid: Option<ZenohId>,
+        link: String,
+        method: &'static str,
+        reason: String,
+    },
+    /// A remote node has been denied an operation.
+    AccessDenied { zid: ZenohId, reason: String },
+    /// A change of the configuration has been requested through the admin space.
+    ///
+    /// Build it with [`AuditEvent::config_changed`], which redacts the values that may hold secrets.
+    ConfigChanged {
+        key: String,
+        value: Option<String>,
+        result: Result<(), String>,
+    },
+    /// An action has been requested through the admin space.
+    ActionRequested {
+        key: String,
+        result: Result<(), String>,
+    },
+}
+
+// The fragments of the configuration keys whose values may hold secrets, e.g. `password`,
+// `private_key_pem`, `psk/keys`, `join_key` or `secrets/exec`
+const SECRET_FRAGMENTS: [&str; 5] = ["password", "private", "key", "secret", "token"];
+
+/// The value recorded in place of a redacted configuration value.
+pub const REDACTED: &str = "<redacted>";
+
+impl AuditEvent {
+    /// A [`AuditEvent::ConfigChanged`] event, whose value is redacted if `key` or, for a value
+    /// setting a whole section, one of the keys of `value` may hold a secret.
+    pub fn config_changed(
+        key: String,
+        value: Option<String>,
+        result: Result<(), String>,
+    ) -> AuditEvent {
+        let is_secret = |s: &str| {
+            let s = s.to_ascii_lowercase();
+            SECRET_FRAGMENTS.iter().any(|f| s.contains(f))
+        };
+        let value = match value {
+            Some(value) if is_secret(&key) || is_secret(&value) => Some(REDACTED.to_string()),
+            value => value,
+        };
+        AuditEvent::ConfigChanged { key, value, result }
+    }
+}
+
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl fmt::Display for AuditEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditEvent::SessionOpened { zid, whatami, link } => {
+                write!(f, "event=session_open zid={zid} whatami={whatami}")?;
+                if let Some(link) = link {
+                    write!(f, " link={}", Quoted(link))?;
+                }
+                Ok(())
+            }
+            AuditEvent::SessionClosed { zid } => write!(f, "event=session_close zid={zid}"),
+            AuditEvent::AuthenticationFailed {
+                zid,
+                link,
+                method,
+                reason,
+            } => {
+                write!(f, "event=auth_failure")?;
+                if let Some(zid) = zid {
+                    write!(f, " zid={zid}")?;
+                }
+                write!(
+                    f,
+                    " link={} method={method} reason={}",
+                    Quoted(link),
+                    Quoted(reason)
+                )
+            }
+            AuditEvent::AccessDenied { zid, reason } => {
+                write!(f, "event=access_denied zid={zid} reason={}", Quoted(reason))
+            }
+            AuditEvent::ConfigChanged { key, value, result } => {
+                write!(f, "event=config_change key={}", Quoted(key))?;
+                match value {
+                    Some(value) => write!(f, " value={}", Quoted(value))?,
+                    None => write!(f, " value=<deleted>")?,
+                }
+                match result {
+                    Ok(()) => write!(f, " result=ok"),
+                    Err(e) => write!(f, " result=error reason={}", Quoted(e)),
+                }
+            }
+            AuditEvent::ActionRequested { key, result } => {
+                write!(f, "event=action_request key={}", Quoted(key))?;
+                match result {
+                    Ok(()) => write!(f, " result=ok"),
+                    Err(e) => write!(f, " result=error reason={}", Quoted(e)),
+                }
+            }
+        }
+    }
+}
+
+/// An [`AuditEvent`] along with the time at which it was recorded.
+#[derive(Clone, Debug)]
+pub struct AuditRecord {
+    pub time: SystemTime,
+    pub event: AuditEvent,
+}
+
+impl fmt::Display for AuditRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        write!(f, "time={time} {}", self.event)
+    }
+}
+
+/// A destination of the audit records.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord);
+}
+
+lazy_static! {
+    static ref SINKS: RwLock<Vec<Arc<dyn AuditSink>>> = RwLock::new(vec![]);
+}
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Registers a sink to which all the subsequent audit records are dispatched.
+pub fn add_sink(sink: Arc<dyn AuditSink>) {
+    zwrite!(SINKS).push(sink);
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Returns `true` if at least one sink is registered.
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Dispatches `event` to the registered sinks.
+pub fn record(event: AuditEvent) {
+    if !is_enabled() {
+        return;
+    }
+    let record = AuditRecord {
+        time: SystemTime::now(),
+        event,
+    };
+    for sink in zread!(SINKS).iter() {
+        sink.record(&record);
+    }
+}
+
+/// An [`AuditSink`] appending the records to a file, rotated once it exceeds a given size.
+///
+/// On rotation, `<path>` is renamed `<path>.1`, `<path>.1` is renamed `<path>.2` and so on
+/// up to `<path>.<max_files>`, the oldest file being removed.
+pub struct FileAuditSink {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: Mutex<(File, u64)>,
+}
+
+impl FileAuditSink {
+    pub fn new<P: AsRef<Path>>(path: P, max_size: u64, max_files: usize) -> ZResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(FileAuditSink {
+            path,
+            max_size,
+            max_files,
+            file: Mutex::new((file, size)),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    fn rotate(&self) -> std::io::Result<File> {
+        if self.max_files == 0 {
+            return OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path);
+        }
+        let _ = fs::remove_file(self.rotated_path(self.max_files));
+        for i in (1..self.max_files).rev() {
+            let _ = fs::rename(self.rotated_path(i), self.rotated_path(i + 1));
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let line = format!("{record}\n");
+        let mut guard = zlock!(self.file);
+        if self.max_size > 0 && guard.1 + line.len() as u64 > self.max_size && guard.1 > 0 {
+            match self.rotate() {
+                Ok(file) => *guard = (file, 0),
+                Err(e) => log::error!("Unable to rotate audit log {:?}: {}", self.path, e),
+            }
+        }
+        match guard.0.write_all(line.as_bytes()) {
+            Ok(()) => guard.1 += line.len() as u64,
+            Err(e) => log::error!("Unable to write audit log {:?}: {}", self.path, e),
+        }
+    }
+}
+
+/// An [`AuditSink`] sending the records to the local syslog daemon.
+#[cfg(unix)]
+pub struct SyslogAuditSink {
+    socket: std::os::unix::net::UnixDatagram,
+    path: PathBuf,
+    tag: String,
+}
+
+#[cfg(unix)]
+impl SyslogAuditSink {
+    /// Facility `authpriv` (10), severity `notice` (5).
+    const PRIORITY: u8 = 10 * 8 + 5;
+
+    pub fn new<P: AsRef<Path>>(path: P, tag: &str) -> ZResult<Self> {
+        Ok(SyslogAuditSink {
+            socket: std::os::unix::net::UnixDatagram::unbound()?,
+            path: path.as_ref().to_path_buf(),
+            tag: tag.to_string(),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl AuditSink for SyslogAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let msg = format!(
+            "<{}>{}[{}]: {}",
+            Self::PRIORITY,
+            self.tag,
+            std::process::id(),
+            record.event
+        );
+        if let Err(e) = self.socket.send_to(msg.as_bytes(), &self.path) {
+            log::error!(
+                "Unable to send audit record to syslog {:?}: {}",
+                self.path,
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn audit_file_rotation() {
+        let dir = std::env::temp_dir().join(format!("zenoh-audit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+        let sink = FileAuditSink::new(&path, 64, 2).unwrap();
+        let record = AuditRecord {
+            time: UNIX_EPOCH,
+            event: AuditEvent::SessionClosed {
+                zid: ZenohId::try_from([1]).unwrap(),
+            },
+        };
+        for _ in 0..5 {
+            sink.record(&record);
+        }
+        assert!(path.exists());
+        assert!(sink.rotated_path(1).exists());
+        assert!(sink.rotated_path(2).exists());
+        assert!(!sink.rotated_path(3).exists());
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "time=0 event=session_close zid=01\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn audit_config_redaction() {
+        let value = |key: &str, value: &str| match AuditEvent::config_changed(
+            key.to_string(),
+            Some(value.to_string()),
+            Ok(()),
+        ) {
+            AuditEvent::ConfigChanged { value, .. } => value.unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!(value("scouting/multicast/enabled", "false"), "false");
+        assert_eq!(
+            value("transport/auth/usrpwd/password", r#""pwd""#),
+            REDACTED
+        );
+        assert_eq!(
+            value("transport/auth/psk/keys", r#"[{"key_id":"a"}]"#),
+            REDACTED
+        );
+        assert_eq!(
+            value("transport/link/tls/server_private_key", r#""pem""#),
+            REDACTED
+        );
+        assert_eq!(
+            value(
+                "transport/auth",
+                r#"{usrpwd: {user: "u", PASSWORD: "pwd"}}"#
+            ),
+            REDACTED
+        );
+    }
+
+    #[test]
+    fn audit_action_request() {
+        let event = AuditEvent::ActionRequested {
+            key: "transports/01/close".to_string(),
+            result: Err("adminspace.permissions.write=false".to_string()),
+        };
+        assert_eq!(
+            event.to_string(),
+            r#"event=action_request key="transports/01/close" result=error reason="adminspace.permissions.write=false""#
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Per-module log filters that can be changed at runtime.
+//!
+//! The filters only apply once a [`RuntimeFilterLogger`] has been installed with [`init`].
+//! A module without any runtime filter is filtered by the default filter given to [`init`].
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+use zenoh_core::{zread, zwrite};
+use zenoh_result::{bail, ZResult};
+
+lazy_static! {
+    // Sorted by decreasing module path length, so that the most specific filter matches first
+    static ref FILTERS: RwLock<Vec<(String, LevelFilter)>> = RwLock::new(vec![]);
+}
+// The most verbose level of the default filter plus one, 0 until a logger is installed with `init`
+static DEFAULT_MAX_LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+/// Parses a list of filters with format `<module>=<level>,...`, e.g. `zenoh_transport=debug`.
+pub fn parse_filters(spec: &str) -> ZResult<Vec<(String, LevelFilter)>> {
+    let mut filters = vec![];
+    for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        let (module, level) = match directive.split_once('=') {
+            Some((module, level)) => (module.trim(), level.trim()),
+            None => bail!(
+                "Invalid log filter `{}`: expected `<module>=<level>`",
+                directive
+            ),
+        };
+        if module.is_empty() {
+            bail!("Invalid log filter `{}`: empty module", directive);
+        }
+        let level = match LevelFilter::from_str(level) {
+            Ok(level) => level,
+            Err(_) => bail!(
+                "Invalid log filter `{}`: unknown level `{}`",
+                directive,
+                level
+            ),
+        };
+        filters.push((module.to_string(), level));
+    }
+    Ok(filters)
+}
+
+/// Replaces the runtime filters.
+pub fn set_filters(mut filters: Vec<(String, LevelFilter)>) {
+    filters.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+    // The max level is left to the installed logger if it isn't a `RuntimeFilterLogger`
+    let max = default_max_level().map(|default| {
+        filters
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(default, LevelFilter::max)
+    });
+    *zwrite!(FILTERS) = filters;
+    if let Some(max) = max {
+        log::set_max_level(max);
+    }
+}
+
+/// Returns the runtime filters.
+pub fn filters() -> Vec<(String, LevelFilter)> {
+    zread!(FILTERS).clone()
+}
+
+/// Returns the level of the most specific runtime filter matching `target`, if any.
+pub fn filter_for(target: &str) -> Option<LevelFilter> {
+    zread!(FILTERS)
+        .iter()
+        .find(|(module, _)| {
+            target
+                .strip_prefix(module.as_str())
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with("::"))
+        })
+        .map(|(_, level)| *level)
+}
+
+fn default_max_level() -> Option<LevelFilter> {
+    match DEFAULT_MAX_LEVEL.load(Ordering::Relaxed) {
+        0 => None,
+        1 => Some(LevelFilter::Off),
+        2 => Some(LevelFilter::Error),
+        3 => Some(LevelFilter::Warn),
+        4 => Some(LevelFilter::Info),
+        5 => Some(LevelFilter::Debug),
+        _ => Some(LevelFilter::Trace),
+    }
+}
+
+/// A [`Log`] applying the runtime filters before forwarding the records to an inner logger.
+///
+/// The inner logger must not filter the records itself, or the runtime filters could only
+/// lower the levels.
+pub struct RuntimeFilterLogger<L, F> {
+    inner: L,
+    default: F,
+}
+
+impl<L, F> Log for RuntimeFilterLogger<L, F>
+where
+    L: Log,
+    F: Fn(&Metadata) -> bool + Send + Sync,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match filter_for(metadata.target()) {
+            Some(level) => metadata.level() <= level,
+            None => (self.default)(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record)
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// Installs a [`RuntimeFilterLogger`] as the global logger.
+///
+/// `default` filters the records of the modules without runtime filter, and `default_max_level`
+/// is the most verbose level it enables.
+pub fn init<L, F>(
+    inner: L,
+    default: F,
+    default_max_level: LevelFilter,
+) -> Result<(), SetLoggerError>
+where
+    L: Log + 'static,
+    F: Fn(&Metadata) -> bool + Send + Sync + 'static,
+{
+    log::set_boxed_logger(Box::new(RuntimeFilterLogger { inner, default }))?;
+    DEFAULT_MAX_LEVEL.store(default_max_level as usize + 1, Ordering::Relaxed);
+    set_filters(filters());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_log_filters() {
+        assert!(parse_filters("zenoh").is_err());
+        assert!(parse_filters("zenoh=loud").is_err());
+        assert!(parse_filters("").unwrap().is_empty());
+
+        set_filters(parse_filters("zenoh=info, zenoh_transport::unicast=trace").unwrap());
+        assert_eq!(filter_for("zenoh"), Some(LevelFilter::Info));
+        assert_eq!(filter_for("zenoh::net"), Some(LevelFilter::Info));
+        assert_eq!(
+            filter_for("zenoh_transport::unicast::rx"),
+            Some(LevelFilter::Trace)
+        );
+        assert_eq!(filter_for("zenoh_transport"), None);
+        assert_eq!(filter_for("zenoh_plugin_rest"), None);
+    }
+}
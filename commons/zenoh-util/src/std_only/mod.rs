@@ -1,9 +1,13 @@
+pub mod audit;
 pub mod ffi;
 mod lib_loader;
+pub mod log_filter;
 pub mod net;
+pub mod sched;
 pub mod time_range;
 pub use lib_loader::*;
 pub mod timer;
+pub mod watchdog;
 pub use timer::*;
 /// The "ZENOH_HOME" environement variable name
 pub const ZENOH_HOME_ENV_VAR: &str = "ZENOH_HOME";
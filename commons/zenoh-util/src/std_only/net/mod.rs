@@ -12,7 +12,9 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use async_std::net::TcpStream;
+use std::fmt;
 use std::net::{IpAddr, Ipv6Addr};
+use std::str::FromStr;
 use std::time::Duration;
 use zenoh_core::zconfigurable;
 use zenoh_result::{bail, ZResult};
@@ -94,10 +96,14 @@ pub fn get_interface(name: &str) -> ZResult<Option<IpAddr>> {
     {
         for iface in pnet_datalink::interfaces() {
             if iface.name == name {
-                for ifaddr in &iface.ips {
-                    if ifaddr.is_ipv4() {
-                        return Ok(Some(ifaddr.ip()));
-                    }
+                // Prefer IPv4, but IPv6-only interfaces are found too
+                if let Some(ifaddr) = iface
+                    .ips
+                    .iter()
+                    .find(|ifaddr| ifaddr.is_ipv4())
+                    .or_else(|| iface.ips.first())
+                {
+                    return Ok(Some(ifaddr.ip()));
                 }
             }
             for ifaddr in &iface.ips {
@@ -174,6 +180,69 @@ pub fn get_interface(name: &str) -> ZResult<Option<IpAddr>> {
     }
 }
 
+/// Get the index of the network interface having the address `addr`, to scope IPv6 multicast
+pub fn get_index_of_interface(addr: IpAddr) -> ZResult<u32> {
+    #[cfg(unix)]
+    {
+        match pnet_datalink::interfaces()
+            .into_iter()
+            .find(|iface| iface.ips.iter().any(|ip| ip.ip() == addr))
+        {
+            Some(iface) => Ok(iface.index),
+            None => bail!("No interface found with address {}", addr),
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        unsafe {
+            use crate::ffi;
+            use winapi::um::iptypes::IP_ADAPTER_ADDRESSES_LH;
+
+            let mut ret;
+            let mut retries = 0;
+            let mut size: u32 = *WINDOWS_GET_ADAPTERS_ADDRESSES_BUF_SIZE;
+            let mut buffer: Vec<u8>;
+            loop {
+                buffer = Vec::with_capacity(size as usize);
+                ret = winapi::um::iphlpapi::GetAdaptersAddresses(
+                    winapi::shared::ws2def::AF_UNSPEC.try_into().unwrap(),
+                    0,
+                    std::ptr::null_mut(),
+                    buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH,
+                    &mut size,
+                );
+                if ret != winapi::shared::winerror::ERROR_BUFFER_OVERFLOW {
+                    break;
+                }
+                if retries >= *WINDOWS_GET_ADAPTERS_ADDRESSES_MAX_RETRIES {
+                    break;
+                }
+                retries += 1;
+            }
+
+            if ret != 0 {
+                bail!("GetAdaptersAddresses returned {}", ret)
+            }
+
+            let mut next_iface = (buffer.as_ptr() as *mut IP_ADAPTER_ADDRESSES_LH).as_ref();
+            while let Some(iface) = next_iface {
+                let mut next_ucast_addr = iface.FirstUnicastAddress.as_ref();
+                while let Some(ucast_addr) = next_ucast_addr {
+                    if let Ok(ifaddr) = ffi::win::sockaddr_to_addr(ucast_addr.Address) {
+                        if ifaddr.ip() == addr {
+                            return Ok(iface.Ipv6IfIndex);
+                        }
+                    }
+                    next_ucast_addr = ucast_addr.Next.as_ref();
+                }
+                next_iface = iface.Next.as_ref();
+            }
+            bail!("No interface found with address {}", addr)
+        }
+    }
+}
+
 /// Get the network interface to bind the UDP sending port to when not specified by user
 pub fn get_multicast_interfaces() -> Vec<IpAddr> {
     #[cfg(unix)]
@@ -421,3 +490,67 @@ pub fn get_ipv6_ipaddrs() -> Vec<IpAddr> {
         .chain(priv_ipv4_addrs)
         .collect()
 }
+
+/// An IP subnet in CIDR notation, e.g. `192.168.1.0/24` or `fd00::/8`.
+///
+/// An address without prefix length is a subnet of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpSubnet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpSubnet {
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        fn mask(bits: u32, prefix_len: u8) -> u128 {
+            match u32::from(prefix_len) {
+                0 => 0,
+                len => (u128::MAX << (128 - len)) >> (128 - bits),
+            }
+        }
+
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = mask(32, self.prefix_len);
+                u128::from(u32::from(net)) & mask == u128::from(u32::from(*addr)) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = mask(128, self.prefix_len);
+                u128::from(net) & mask == u128::from(*addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpSubnet {
+    type Err = zenoh_result::Error;
+
+    fn from_str(s: &str) -> ZResult<Self> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+        let addr: IpAddr = match addr.parse() {
+            Ok(addr) => addr,
+            Err(e) => bail!("Invalid subnet `{}`: {}", s, e),
+        };
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len.map(str::parse::<u8>) {
+            None => max,
+            Some(Ok(len)) if len <= max => len,
+            Some(_) => bail!(
+                "Invalid subnet `{}`: prefix length must be at most {}",
+                s,
+                max
+            ),
+        };
+        Ok(IpSubnet { addr, prefix_len })
+    }
+}
+
+impl fmt::Display for IpSubnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
@@ -0,0 +1,121 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Scheduling of the threads dedicated to zenoh: CPU affinity and real-time priority.
+//!
+//! The scheduling is only supported on Linux, where the real-time priority requires the
+//! `CAP_SYS_NICE` capability.
+use zenoh_result::{bail, ZResult};
+
+/// The scheduling of a thread.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThreadSchedule {
+    /// The CPU cores the thread is pinned to, any core if empty.
+    pub affinity: Vec<usize>,
+    /// The real-time (`SCHED_FIFO`) priority of the thread, from 1 to 99, or the default
+    /// scheduling policy if `None`.
+    pub priority: Option<u8>,
+}
+
+impl ThreadSchedule {
+    pub fn new(affinity: Option<Vec<usize>>, priority: Option<u8>) -> ZResult<Self> {
+        if let Some(priority) = priority {
+            if !(1..=99).contains(&priority) {
+                bail!(
+                    "Invalid thread priority {}: expected a value from 1 to 99",
+                    priority
+                );
+            }
+        }
+        Ok(ThreadSchedule {
+            affinity: affinity.unwrap_or_default(),
+            priority,
+        })
+    }
+
+    /// Returns `true` if the thread is left to the default scheduling of the OS.
+    pub fn is_default(&self) -> bool {
+        self.affinity.is_empty() && self.priority.is_none()
+    }
+
+    /// Applies the scheduling to the calling thread.
+    #[cfg(target_os = "linux")]
+    pub fn apply(&self) -> ZResult<()> {
+        if !self.affinity.is_empty() {
+            // SAFETY: the set is initialized by CPU_ZERO and the cores are checked against its size
+            let res = unsafe {
+                let mut set: libc::cpu_set_t = std::mem::zeroed();
+                libc::CPU_ZERO(&mut set);
+                for core in self.affinity.iter() {
+                    if *core >= libc::CPU_SETSIZE as usize {
+                        bail!("Invalid CPU core {}", core);
+                    }
+                    libc::CPU_SET(*core, &mut set);
+                }
+                libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+            };
+            if res != 0 {
+                bail!(
+                    "Unable to pin the thread to the CPU cores {:?}: {}",
+                    self.affinity,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+        if let Some(priority) = self.priority {
+            let param = libc::sched_param {
+                sched_priority: priority as libc::c_int,
+            };
+            // SAFETY: the parameters are valid for the calling thread
+            let res = unsafe {
+                libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param)
+            };
+            if res != 0 {
+                bail!(
+                    "Unable to set the real-time priority {} of the thread: {}",
+                    priority,
+                    std::io::Error::from_raw_os_error(res)
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the scheduling to the calling thread.
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply(&self) -> ZResult<()> {
+        if !self.is_default() {
+            bail!("The scheduling of the threads is only supported on Linux");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_schedule() {
+        assert!(ThreadSchedule::new(None, Some(0)).is_err());
+        assert!(ThreadSchedule::new(None, Some(100)).is_err());
+        assert!(ThreadSchedule::new(None, None).unwrap().is_default());
+        let schedule = ThreadSchedule::new(Some(vec![0]), None).unwrap();
+        assert!(!schedule.is_default());
+        #[cfg(target_os = "linux")]
+        std::thread::spawn(move || schedule.apply().unwrap())
+            .join()
+            .unwrap();
+    }
+}
@@ -21,7 +21,7 @@ use std::collections::BinaryHeap;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
-use zenoh_core::zconfigurable;
+use zenoh_core::{executor, zconfigurable};
 
 zconfigurable! {
     static ref TIMER_EVENTS_CHANNEL_SIZE: usize = 1;
@@ -210,7 +210,7 @@ impl Timer {
         if spawn_blocking {
             task::spawn_blocking(|| task::block_on(fut));
         } else {
-            task::spawn(fut);
+            executor::spawn(fut);
         }
 
         // Return the timer object
@@ -240,7 +240,7 @@ impl Timer {
             if spawn_blocking {
                 task::spawn_blocking(|| task::block_on(fut));
             } else {
-                task::spawn(fut);
+                executor::spawn(fut);
             }
         }
     }
@@ -0,0 +1,278 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Watchdog of the internal tasks, reporting the tasks that stop making progress.
+//!
+//! The monitored tasks report their progress through a [`TaskProbe`]. A task is stalled when it hasn't
+//! made progress for longer than the threshold, unless it is idle, i.e. waiting for an event that may
+//! never come. The stalls are detected from a dedicated thread, so that they are also detected when
+//! the async executors are starved. The probes are no-ops until the watchdog is started with [`start`].
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use zenoh_core::{zread, zwrite};
+use zenoh_result::{bail, ZError, ZResult};
+
+/// What the watchdog does when it detects a stalled task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchdogPolicy {
+    /// Logs a diagnostic of the stalled task.
+    Log,
+    /// Logs a diagnostic of the stalled task and restarts it, if it can be restarted.
+    Restart,
+}
+
+impl FromStr for WatchdogPolicy {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "log" => Ok(WatchdogPolicy::Log),
+            "restart" => Ok(WatchdogPolicy::Restart),
+            _ => bail!(
+                r#"Invalid watchdog policy `{}`: expected "log" or "restart""#,
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for WatchdogPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchdogPolicy::Log => write!(f, "log"),
+            WatchdogPolicy::Restart => write!(f, "restart"),
+        }
+    }
+}
+
+/// Returns the task-specific diagnostic of a stalled task, e.g. its queue depths.
+pub type Diagnostic = Box<dyn Fn() -> String + Send + Sync>;
+/// Restarts a stalled task.
+pub type Restart = Box<dyn Fn() + Send + Sync>;
+
+struct TaskState {
+    name: String,
+    // Microseconds since EPOCH
+    last_progress: AtomicU64,
+    idle: AtomicBool,
+    stalled: AtomicBool,
+    diagnostic: Option<Diagnostic>,
+    restart: Option<Restart>,
+}
+
+impl TaskState {
+    fn elapsed(&self, now: Instant) -> Duration {
+        let last = Duration::from_micros(self.last_progress.load(Ordering::Relaxed));
+        now.saturating_duration_since(*EPOCH + last)
+    }
+}
+
+lazy_static! {
+    static ref EPOCH: Instant = Instant::now();
+    static ref TASKS: RwLock<Vec<Arc<TaskState>>> = RwLock::new(vec![]);
+}
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[inline]
+fn now_micros() -> u64 {
+    EPOCH.elapsed().as_micros() as u64
+}
+
+/// Returns `true` if the watchdog has been started.
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// The handle through which a monitored task reports its progress.
+///
+/// The task stops being monitored when its probe is dropped.
+pub struct TaskProbe(Option<Arc<TaskState>>);
+
+impl TaskProbe {
+    /// Records that the task made progress, and that it is no longer idle.
+    #[inline]
+    pub fn progress(&self) {
+        if let Some(state) = self.0.as_ref() {
+            state.last_progress.store(now_micros(), Ordering::Relaxed);
+            state.idle.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Records that the task is idle until its next progress.
+    #[inline]
+    pub fn idle(&self) {
+        if let Some(state) = self.0.as_ref() {
+            state.idle.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for TaskProbe {
+    fn drop(&mut self) {
+        if let Some(state) = self.0.take() {
+            zwrite!(TASKS).retain(|s| !Arc::ptr_eq(s, &state));
+        }
+    }
+}
+
+/// Registers a task to be monitored by the watchdog.
+///
+/// `diagnostic` completes the diagnostic logged when the task stalls, and `restart` is called when
+/// the task stalls with the [`WatchdogPolicy::Restart`] policy.
+pub fn register(
+    name: String,
+    diagnostic: Option<Diagnostic>,
+    restart: Option<Restart>,
+) -> TaskProbe {
+    if !is_enabled() {
+        return TaskProbe(None);
+    }
+    let state = Arc::new(TaskState {
+        name,
+        last_progress: AtomicU64::new(now_micros()),
+        idle: AtomicBool::new(false),
+        stalled: AtomicBool::new(false),
+        diagnostic,
+        restart,
+    });
+    zwrite!(TASKS).push(state.clone());
+    TaskProbe(Some(state))
+}
+
+/// Starts the watchdog, reporting the tasks making no progress for longer than `threshold`.
+///
+/// Only the tasks registered after the watchdog has been started are monitored, and the watchdog
+/// can't be started twice.
+pub fn start(threshold: Duration, policy: WatchdogPolicy) -> ZResult<()> {
+    if threshold.is_zero() {
+        bail!("Invalid watchdog threshold: must be greater than 0");
+    }
+    if ENABLED.swap(true, Ordering::AcqRel) {
+        bail!("The watchdog is already started");
+    }
+    let res = std::thread::Builder::new()
+        .name("zenoh-watchdog".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(threshold / 2);
+            check(threshold, policy);
+        });
+    if let Err(e) = res {
+        ENABLED.store(false, Ordering::Release);
+        bail!("Unable to start the watchdog: {}", e);
+    }
+    log::debug!(
+        "Watchdog started with threshold {:?} and policy {}",
+        threshold,
+        policy
+    );
+    Ok(())
+}
+
+fn check(threshold: Duration, policy: WatchdogPolicy) {
+    let now = Instant::now();
+    for state in zread!(TASKS).iter() {
+        let elapsed = state.elapsed(now);
+        if elapsed <= threshold || state.idle.load(Ordering::Relaxed) {
+            if state.stalled.swap(false, Ordering::Relaxed) {
+                log::info!("Task `{}` is making progress again", state.name);
+            }
+            continue;
+        }
+        if state.stalled.swap(true, Ordering::Relaxed) {
+            continue;
+        }
+        let last_progress = humantime::format_rfc3339_millis(SystemTime::now() - elapsed);
+        let diagnostic = state
+            .diagnostic
+            .as_ref()
+            .map(|d| format!(" ({})", d()))
+            .unwrap_or_default();
+        log::warn!(
+            "Task `{}` stalled: no progress for {} ms, last progress at {}{}",
+            state.name,
+            elapsed.as_millis(),
+            last_progress,
+            diagnostic
+        );
+        if policy == WatchdogPolicy::Restart {
+            match state.restart.as_ref() {
+                Some(restart) => {
+                    log::warn!("Restarting stalled task `{}`", state.name);
+                    restart();
+                }
+                None => log::warn!("Stalled task `{}` can't be restarted", state.name),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn watchdog_stalled_task() {
+        assert_eq!(
+            "restart".parse::<WatchdogPolicy>().unwrap(),
+            WatchdogPolicy::Restart
+        );
+        assert!("kill".parse::<WatchdogPolicy>().is_err());
+
+        // Probes registered before the watchdog is started are no-ops
+        assert!(register("disabled".to_string(), None, None).0.is_none());
+
+        let threshold = Duration::from_millis(100);
+        // The watchdog is enabled without its thread, so that the test drives the checks
+        ENABLED.store(true, Ordering::Release);
+        let restarts = Arc::new(AtomicUsize::new(0));
+        let c_restarts = restarts.clone();
+        let probe = register(
+            "test".to_string(),
+            Some(Box::new(|| "queued: 0".to_string())),
+            Some(Box::new(move || {
+                c_restarts.fetch_add(1, Ordering::Relaxed);
+            })),
+        );
+        let state = probe.0.clone().unwrap();
+
+        check(threshold, WatchdogPolicy::Restart);
+        assert!(!state.stalled.load(Ordering::Relaxed));
+
+        std::thread::sleep(threshold * 2);
+        check(threshold, WatchdogPolicy::Restart);
+        assert!(state.stalled.load(Ordering::Relaxed));
+        // A stall is only reported once
+        check(threshold, WatchdogPolicy::Restart);
+        assert_eq!(restarts.load(Ordering::Relaxed), 1);
+
+        probe.progress();
+        check(threshold, WatchdogPolicy::Restart);
+        assert!(!state.stalled.load(Ordering::Relaxed));
+
+        // Idle tasks aren't stalled
+        probe.idle();
+        std::thread::sleep(threshold * 2);
+        check(threshold, WatchdogPolicy::Restart);
+        assert!(!state.stalled.load(Ordering::Relaxed));
+        assert_eq!(restarts.load(Ordering::Relaxed), 1);
+
+        drop(probe);
+        assert!(zread!(TASKS).is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Certificate monitoring shared by the TLS-based links.
+//!
+//! Links register the certificates they load in a process-wide registry so that their
+//! expiry can be inspected (e.g. through the admin space), a certificate used by several
+//! links staying registered until none of them uses it. They rely on a
+//! [`FileWatcher`] to detect on-disk changes and reload their configuration.
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zenoh_result::{zerror, ZResult};
+
+/// Default period in milliseconds at which certificate files are checked for changes.
+pub const TLS_RELOAD_INTERVAL_DEFAULT: u64 = 10_000;
+/// Default delay in seconds before expiry at which a certificate is reported as expiring.
+pub const TLS_EXPIRY_WARNING_DEFAULT: u64 = 7 * 24 * 3600;
+
+/// The status of a certificate in use by a link.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct CertificateStatus {
+    /// The path of the certificate file, or `<raw:HASH>` if provided inline, where `HASH`
+    /// identifies the certificate by its content.
+    pub path: String,
+    /// The expiry date of the certificate, in seconds since the UNIX epoch.
+    pub not_after: u64,
+    /// The last time the certificate has been (re)loaded, in seconds since the UNIX epoch.
+    pub loaded_at: u64,
+}
+
+impl CertificateStatus {
+    /// Whether the certificate expires within `within`.
+    pub fn expires_within(&self, within: Duration) -> bool {
+        now().saturating_add(within.as_secs()) >= self.not_after
+    }
+}
+
+/// An event related to a certificate in use by a link.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CertificateEvent {
+    Reloaded { path: String, not_after: u64 },
+    ReloadFailed { path: String, error: String },
+    ExpiresSoon { path: String, not_after: u64 },
+}
+
+#[derive(Default)]
+struct CertificateRegistry {
+    // The certificates in use, with the number of links using each of them: several links may
+    // use the same certificate file
+    certificates: HashMap<String, (CertificateStatus, usize)>,
+    subscribers: Vec<flume::Sender<CertificateEvent>>,
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: Mutex<CertificateRegistry> = Mutex::new(CertificateRegistry::default());
+}
+
+/// Returns the status of all the certificates currently in use.
+pub fn list() -> Vec<CertificateStatus> {
+    let guard = REGISTRY.lock().unwrap();
+    guard
+        .certificates
+        .values()
+        .map(|(status, _)| status.clone())
+        .collect()
+}
+
+/// Subscribes to the certificate events.
+pub fn subscribe() -> flume::Receiver<CertificateEvent> {
+    let (tx, rx) = flume::unbounded();
+    REGISTRY.lock().unwrap().subscribers.push(tx);
+    rx
+}
+
+/// Registers a certificate in use by one more link.
+pub fn register_certificate(path: &str, not_after: u64) {
+    let status = CertificateStatus {
+        path: path.to_string(),
+        not_after,
+        loaded_at: now(),
+    };
+    let mut guard = REGISTRY.lock().unwrap();
+    let entry = guard
+        .certificates
+        .entry(status.path.clone())
+        .or_insert((status.clone(), 0));
+    entry.0 = status;
+    entry.1 += 1;
+}
+
+/// Updates a registered certificate after it has been reloaded.
+pub fn update_certificate(path: &str, not_after: u64) {
+    if let Some((status, _)) = REGISTRY.lock().unwrap().certificates.get_mut(path) {
+        status.not_after = not_after;
+        status.loaded_at = now();
+    }
+}
+
+/// Unregisters a certificate no more in use by a link, which is removed once no link uses it.
+pub fn unregister_certificate(path: &str) {
+    let mut guard = REGISTRY.lock().unwrap();
+    if let Some((_, count)) = guard.certificates.get_mut(path) {
+        *count -= 1;
+        if *count == 0 {
+            guard.certificates.remove(path);
+        }
+    }
+}
+
+/// Notifies an event to all the subscribers.
+pub fn notify_certificate_event(event: CertificateEvent) {
+    let mut guard = REGISTRY.lock().unwrap();
+    guard.subscribers.retain(|s| s.send(event.clone()).is_ok());
+}
+
+/// Returns the expiry date, in seconds since the UNIX epoch, of the first certificate of a PEM file.
+pub fn pem_not_after(pem: &[u8]) -> ZResult<u64> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(pem)
+        .map_err(|e| zerror!("Invalid PEM certificate: {}", e))?;
+    let cert = pem
+        .parse_x509()
+        .map_err(|e| zerror!("Invalid X509 certificate: {}", e))?;
+    Ok(cert.validity().not_after.timestamp().max(0) as u64)
+}
+
+/// Tracks the certificate in use by a listener, keeping the registry up to date and
+/// notifying [`CertificateEvent`]s on reload and when expiry is near.
+pub struct CertificateMonitor {
+    path: String,
+    inline: bool,
+    not_after: Option<u64>,
+    warning: Duration,
+    warned: bool,
+}
+
+impl CertificateMonitor {
+    pub fn new(path: Option<&str>, warning: Duration) -> Self {
+        CertificateMonitor {
+            path: path.unwrap_or("<raw>").to_string(),
+            inline: path.is_none(),
+            not_after: None,
+            warning,
+            warned: false,
+        }
+    }
+
+    /// Records a newly loaded PEM certificate.
+    pub fn loaded(&mut self, pem: &[u8]) {
+        let reload = self.not_after.is_some();
+        if self.inline {
+            let path = inline_path(pem);
+            if path != self.path {
+                if self.not_after.take().is_some() {
+                    unregister_certificate(&self.path);
+                }
+                self.path = path;
+            }
+        }
+        match pem_not_after(pem) {
+            Ok(not_after) => {
+                if self.not_after.is_some() {
+                    update_certificate(&self.path, not_after);
+                } else {
+                    register_certificate(&self.path, not_after);
+                }
+                self.not_after = Some(not_after);
+                self.warned = false;
+                if reload {
+                    log::info!("Reloaded TLS certificate {}", self.path);
+                    notify_certificate_event(CertificateEvent::Reloaded {
+                        path: self.path.clone(),
+                        not_after,
+                    });
+                }
+            }
+            Err(e) => log::warn!(
+                "Unable to read expiry of TLS certificate {}: {}",
+                self.path,
+                e
+            ),
+        }
+    }
+
+    /// Records a failed attempt to reload the certificate.
+    pub fn failed(&self, error: &dyn std::fmt::Display) {
+        log::warn!("Unable to reload TLS certificate {}: {}", self.path, error);
+        notify_certificate_event(CertificateEvent::ReloadFailed {
+            path: self.path.clone(),
+            error: error.to_string(),
+        });
+    }
+
+    /// Notifies once per loaded certificate that its expiry is near.
+    pub fn check_expiry(&mut self) {
+        let not_after = match self.not_after {
+            Some(not_after) => not_after,
+            None => return,
+        };
+        if !self.warned && now().saturating_add(self.warning.as_secs()) >= not_after {
+            log::warn!(
+                "TLS certificate {} expires at {} (UNIX time)",
+                self.path,
+                not_after
+            );
+            notify_certificate_event(CertificateEvent::ExpiresSoon {
+                path: self.path.clone(),
+                not_after,
+            });
+            self.warned = true;
+        }
+    }
+}
+
+impl Drop for CertificateMonitor {
+    fn drop(&mut self) {
+        if self.not_after.is_some() {
+            unregister_certificate(&self.path);
+        }
+    }
+}
+
+/// Polls a set of files and reports whether any of them has been modified.
+pub struct FileWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl FileWatcher {
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let files = paths
+            .into_iter()
+            .map(|p| {
+                let p = p.into();
+                let modified = modified(&p);
+                (p, modified)
+            })
+            .collect();
+        FileWatcher { files }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns `true` if any of the watched files has been modified since the last call.
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last) in self.files.iter_mut() {
+            let current = modified(path);
+            if current != *last {
+                *last = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+// Distinct inline certificates are registered under distinct keys
+fn inline_path(pem: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    pem.hash(&mut hasher);
+    format!("<raw:{:016x}>", hasher.finish())
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+mod tests {
+    #[allow(dead_code)]
+    const CERT_A: &str = "-----BEGIN CERTIFICATE-----
+MIIDLjCCAhagAwIBAgIIW1mAtJWJAJYwDQYJKoZIhvcNAQELBQAwIDEeMBwGA1UE
+AxMVbWluaWNhIHJvb3QgY2EgNGRjYzJmMCAXDTIzMDMwNjE2NDEwNloYDzIxMjMw
+MzA2MTY0MTA2WjAUMRIwEAYDVQQDEwlsb2NhbGhvc3QwggEiMA0GCSqGSIb3DQEB
+AQUAA4IBDwAwggEKAoIBAQCYMLJKooc+YRlKEMfeV09pX9myH34eUcUuT0fXS8lm
+PlZ/NW7mm5lDwa8EUg61WuXQv2ouQDptmIcdeb/w4RW93Xflkyng1Xbd91OwQBJd
++8ZVBjzL7hSRk3QPDqx/CVBU/I1GmXKzb6cWzq1fTkOn1WLNXf21I6p7+N3qHLPF
+JQeoVq1HBBFcAjTgJnpyQNvRGLDuLTK+OsWEGib2U8qrgiRdkaBLkxGXSlGABlOo
+cQyW/zOhf4pwb2Z/JAge2mRW5IcexCPBWint8ydPsoJDds8j5+AyYCD6HUhHX0Ob
+Qkz73OW7f2PQhuTK2uzKy0Yz6lNFt2nuzaWC04wIW3T7AgMBAAGjdjB0MA4GA1Ud
+DwEB/wQEAwIFoDAdBgNVHSUEFjAUBggrBgEFBQcDAQYIKwYBBQUHAwIwDAYDVR0T
+AQH/BAIwADAfBgNVHSMEGDAWgBTX46+p+Po1npE6QLQ7mMI+83s6qDAUBgNVHREE
+DTALgglsb2NhbGhvc3QwDQYJKoZIhvcNAQELBQADggEBAAxrmQPG54ybKgMVliN8
+Mg5povSdPIVVnlU/HOVG9yxzAOav/xQP003M4wqpatWxI8tR1PcLuZf0EPmcdJgb
+tVl9nZMVZtveQnYMlU8PpkEVu56VM4Zr3rH9liPRlr0JEAXODdKw76kWKzmdqWZ/
+rzhup3Ek7iEX6T5j/cPUvTWtMD4VEK2I7fgoKSHIX8MIVzqM7cuboGWPtS3eRNXl
+MgvahA4TwLEXPEe+V1WAq6nSb4g2qSXWIDpIsy/O1WGS/zzRnKvXu9/9NkXWqZMl
+C1LSpiiQUaRSglOvYf/Zx6r+4BOS4OaaArwHkecZQqBSCcBLEAyb/FaaXdBowI0U
+PQ4=
+-----END CERTIFICATE-----";
+
+    #[allow(dead_code)]
+    const CERT_B: &str = "-----BEGIN CERTIFICATE-----
+MIIDLjCCAhagAwIBAgIIeUtmIdFQznMwDQYJKoZIhvcNAQELBQAwIDEeMBwGA1UE
+AxMVbWluaWNhIHJvb3QgY2EgMDc4ZGE3MCAXDTIzMDMwNjE2MDMxOFoYDzIxMjMw
+MzA2MTYwMzE4WjAUMRIwEAYDVQQDEwlsb2NhbGhvc3QwggEiMA0GCSqGSIb3DQEB
+AQUAA4IBDwAwggEKAoIBAQCx+oC6ESU3gefJ6oui9J3hB76c2/kDAKNI74cWIXfT
+He9DUeKpEDRSbIWVKoGcUfdNQebglxp3jRB+tfx/XU0oZl2m8oewxipiNmdiREUZ
+Lazh9DJoNtXkzTqzdQNfwRM+BjjVjx8IpNJV2L2IeTBxWtczFS7ggEHHQLWvYZKj
+eCQgGdRwQt0V1pQ5Jt0KKkmFueTCLESvaHs9fHBtrtIhmBm1FpBZqTVUT1vvXqp7
+eIy4yFoR+j9SgWZ5kI+7myl/Bo5mycKzFE+TYiNvOWwdMnT2Uz3CZsQUcExUBd6M
+tOT75Kte3yMBJmE16f/YbPItA0Cq4af3yUIxDpKwT28tAgMBAAGjdjB0MA4GA1Ud
+DwEB/wQEAwIFoDAdBgNVHSUEFjAUBggrBgEFBQcDAQYIKwYBBQUHAwIwDAYDVR0T
+AQH/BAIwADAfBgNVHSMEGDAWgBTWfAmQ/BUIQm/9/llJJs2jUMWzGzAUBgNVHREE
+DTALgglsb2NhbGhvc3QwDQYJKoZIhvcNAQELBQADggEBAG/POnBob0S7iYwsbtI2
+3LTTbRnmseIErtJuJmI9yYzgVIm6sUSKhlIUfAIm4rfRuzE94KFeWR2w9RabxOJD
+wjYLLKvQ6rFY5g2AV/J0TwDjYuq0absdaDPZ8MKJ+/lpGYK3Te+CTOfq5FJRFt1q
+GOkXAxnNpGg0obeRWRKFiAMHbcw6a8LIMfRjCooo3+uSQGsbVzGxSB4CYo720KcC
+9vB1K9XALwzoqCewP4aiQsMY1GWpAmzXJftY3w+lka0e9dBYcdEdOqxSoZb5OBBZ
+p5e60QweRuJsb60aUaCG8HoICevXYK2fFqCQdlb5sIqQqXyN2K6HuKAFywsjsGyJ
+abY=
+-----END CERTIFICATE-----";
+
+    #[test]
+    fn certificate_reload() {
+        use super::FileWatcher;
+        use super::{list, pem_not_after, subscribe, CertificateEvent, CertificateMonitor};
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join(format!("zenoh-cert-{}.pem", std::process::id()));
+        let path_str = path.to_str().unwrap().to_string();
+        let not_after_a = pem_not_after(CERT_A.as_bytes()).unwrap();
+        let not_after_b = pem_not_after(CERT_B.as_bytes()).unwrap();
+        assert_ne!(not_after_a, not_after_b);
+
+        std::fs::write(&path, CERT_A).unwrap();
+        let mut watcher = FileWatcher::new([&path]);
+        let mut monitor = CertificateMonitor::new(Some(&path_str), Duration::from_secs(0));
+        monitor.loaded(&std::fs::read(&path).unwrap());
+        let events = subscribe();
+        assert!(!watcher.changed());
+
+        // Some filesystems have a coarse modification time resolution
+        std::thread::sleep(Duration::from_millis(1_100));
+        std::fs::write(&path, CERT_B).unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        // The new certificate is the one reloaded and registered
+        monitor.loaded(&std::fs::read(&path).unwrap());
+        let status = list().into_iter().find(|s| s.path == path_str).unwrap();
+        assert_eq!(status.not_after, not_after_b);
+        assert!(events.try_iter().any(|e| matches!(
+            e,
+            CertificateEvent::Reloaded { path, not_after } if path == path_str && not_after == not_after_b
+        )));
+
+        drop(monitor);
+        assert!(list().into_iter().all(|s| s.path != path_str));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn inline_certificates() {
+        use super::{list, CertificateMonitor};
+        use std::time::Duration;
+
+        // Distinct inline certificates don't overwrite each other
+        let mut monitor_a = CertificateMonitor::new(None, Duration::from_secs(0));
+        monitor_a.loaded(CERT_A.as_bytes());
+        let mut monitor_b = CertificateMonitor::new(None, Duration::from_secs(0));
+        monitor_b.loaded(CERT_B.as_bytes());
+        assert_ne!(monitor_a.path, monitor_b.path);
+        let paths = list().into_iter().map(|s| s.path).collect::<Vec<_>>();
+        assert!(paths.contains(&monitor_a.path));
+        assert!(paths.contains(&monitor_b.path));
+
+        drop(monitor_a);
+        let paths = list().into_iter().map(|s| s.path).collect::<Vec<_>>();
+        assert!(paths.contains(&monitor_b.path));
+    }
+
+    #[test]
+    fn shared_certificates() {
+        use super::{list, CertificateMonitor};
+        use std::time::Duration;
+
+        // A certificate used by several links stays registered until none uses it
+        let path = "shared-certificate.pem";
+        let mut monitor_a = CertificateMonitor::new(Some(path), Duration::from_secs(0));
+        monitor_a.loaded(CERT_A.as_bytes());
+        let mut monitor_b = CertificateMonitor::new(Some(path), Duration::from_secs(0));
+        monitor_b.loaded(CERT_A.as_bytes());
+        monitor_b.loaded(CERT_A.as_bytes());
+
+        drop(monitor_a);
+        assert!(list().into_iter().any(|s| s.path == path));
+        drop(monitor_b);
+        assert!(list().into_iter().all(|s| s.path != path));
+    }
+
+    #[test]
+    fn expiry() {
+        use super::{now, CertificateStatus};
+        use std::time::Duration;
+
+        let status = CertificateStatus {
+            path: "cert.pem".to_string(),
+            not_after: now() + 3600,
+            loaded_at: now(),
+        };
+        assert!(!status.expires_within(Duration::from_secs(60)));
+        assert!(status.expires_within(Duration::from_secs(7200)));
+    }
+}
@@ -38,7 +38,10 @@ use zenoh_protocol::{
     core::{EndPoint, Locator},
     transport::TransportMessage,
 };
-use zenoh_result::{zerror, ZResult};
+use zenoh_result::{zerror, ZError, ZResult};
+
+pub mod certificates;
+pub mod secrets;
 
 /*************************************/
 /*            GENERAL                */
@@ -53,6 +56,23 @@ pub struct Link {
     pub is_streamed: bool,
 }
 
+/// The error of a link whose security handshake (e.g. TLS) with the remote end failed, as opposed
+/// to a remote end that couldn't be reached.
+#[derive(Debug)]
+pub struct LinkHandshakeError(pub ZError);
+
+impl std::error::Error for LinkHandshakeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl fmt::Display for LinkHandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[async_trait]
 pub trait LocatorInspector: Default {
     fn protocol(&self) -> &str;
@@ -0,0 +1,46 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Resolution of the private keys of the TLS-based links.
+use zenoh_cfg_properties::Properties;
+use zenoh_config::{secrets::SecretSource, SecretsConf};
+use zenoh_result::{zerror, ZResult};
+
+/// Inserts a private key property: a path (possibly prefixed with `file:`) is passed as is after
+/// checking its permissions, while `env:` and `exec:` references are resolved into the raw key.
+pub async fn insert_private_key(
+    properties: &mut Properties,
+    secrets: &SecretsConf,
+    value: &str,
+    file_key: &str,
+    raw_key: &str,
+) -> ZResult<()> {
+    match SecretSource::parse(value) {
+        SecretSource::Inline(path) | SecretSource::File(path) => {
+            let metadata = async_std::fs::metadata(path)
+                .await
+                .map_err(|e| zerror!("Unable to access secret file `{}`: {}", path, e))?;
+            secrets.check_metadata(path, &metadata)?;
+            properties.insert(file_key.into(), path.into());
+        }
+        SecretSource::Env(_) | SecretSource::Exec(_) => {
+            // The exec providers are blocking commands
+            let secrets = secrets.clone();
+            let value = value.to_string();
+            let key = async_std::task::spawn_blocking(move || secrets.resolve(&value)).await?;
+            properties.insert(raw_key.into(), key);
+        }
+    }
+    Ok(())
+}
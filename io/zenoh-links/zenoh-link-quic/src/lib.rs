@@ -20,14 +20,15 @@
 use async_std::net::ToSocketAddrs;
 use async_trait::async_trait;
 use config::{
-    TLS_ROOT_CA_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_FILE, TLS_SERVER_PRIVATE_KEY_FILE,
+    TLS_EXPIRY_WARNING, TLS_RELOAD_INTERVAL, TLS_ROOT_CA_CERTIFICATE_FILE,
+    TLS_SERVER_CERTIFICATE_FILE, TLS_SERVER_PRIVATE_KEY_FILE, TLS_SERVER_PRIVATE_KEY_RAW,
 };
 use std::net::SocketAddr;
 use webpki::DnsNameRef;
 use zenoh_cfg_properties::Properties;
 use zenoh_config::{Config, Locator};
 use zenoh_core::zconfigurable;
-use zenoh_link_commons::{ConfigurationInspector, LocatorInspector};
+use zenoh_link_commons::{secrets::insert_private_key, ConfigurationInspector, LocatorInspector};
 use zenoh_protocol::core::endpoint::Address;
 use zenoh_result::{bail, zerror, ZResult};
 
@@ -65,6 +66,7 @@ impl ConfigurationInspector<Config> for QuicConfigurator {
         let mut properties = Properties::default();
 
         let c = config.transport().link().tls();
+        let secrets = config.transport().auth().secrets();
         if let Some(tls_ca_certificate) = c.root_ca_certificate() {
             properties.insert(
                 TLS_ROOT_CA_CERTIFICATE_FILE.into(),
@@ -72,10 +74,14 @@ impl ConfigurationInspector<Config> for QuicConfigurator {
             );
         }
         if let Some(tls_server_private_key) = c.server_private_key() {
-            properties.insert(
-                TLS_SERVER_PRIVATE_KEY_FILE.into(),
-                tls_server_private_key.into(),
-            );
+            insert_private_key(
+                &mut properties,
+                secrets,
+                tls_server_private_key,
+                TLS_SERVER_PRIVATE_KEY_FILE,
+                TLS_SERVER_PRIVATE_KEY_RAW,
+            )
+            .await?;
         }
         if let Some(tls_server_certificate) = c.server_certificate() {
             properties.insert(
@@ -83,6 +89,12 @@ impl ConfigurationInspector<Config> for QuicConfigurator {
                 tls_server_certificate.into(),
             );
         }
+        if let Some(tls_reload_interval) = c.reload_interval() {
+            properties.insert(TLS_RELOAD_INTERVAL.into(), tls_reload_interval.to_string());
+        }
+        if let Some(tls_expiry_warning) = c.expiry_warning() {
+            properties.insert(TLS_EXPIRY_WARNING.into(), tls_expiry_warning.to_string());
+        }
 
         Ok(properties)
     }
@@ -112,6 +124,14 @@ pub mod config {
 
     pub const TLS_SERVER_CERTIFICATE_FILE: &str = ZN_TLS_SERVER_CERTIFICATE_STR;
     pub const TLS_SERVER_CERTIFICATE_RAW: &str = "tls_server_certificate_raw";
+
+    pub const TLS_RELOAD_INTERVAL: &str = "tls_reload_interval";
+    pub const TLS_RELOAD_INTERVAL_DEFAULT: u64 =
+        zenoh_link_commons::certificates::TLS_RELOAD_INTERVAL_DEFAULT;
+
+    pub const TLS_EXPIRY_WARNING: &str = "tls_expiry_warning";
+    pub const TLS_EXPIRY_WARNING_DEFAULT: u64 =
+        zenoh_link_commons::certificates::TLS_EXPIRY_WARNING_DEFAULT;
 }
 
 async fn get_quic_addr(address: &Address<'_>) -> ZResult<SocketAddr> {
@@ -20,7 +20,6 @@ use async_std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use async_std::prelude::FutureExt;
 use async_std::sync::Mutex as AsyncMutex;
 use async_std::task;
-use async_std::task::JoinHandle;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::fmt;
@@ -29,10 +28,16 @@ use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
-use zenoh_core::{zasynclock, zread, zwrite};
+use zenoh_core::{
+    executor::{self, JoinHandle},
+    zasynclock, zread, zwrite,
+};
 use zenoh_link_commons::{
-    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+    certificates::{CertificateMonitor, FileWatcher},
+    LinkHandshakeError, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait,
+    NewLinkChannelSender,
 };
+use zenoh_protocol::core::endpoint::Config;
 use zenoh_protocol::core::{EndPoint, Locator};
 use zenoh_result::{bail, zerror, ZResult};
 use zenoh_sync::Signal;
@@ -282,7 +287,16 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
             .connect(addr, host)
             .map_err(|e| zerror!("Can not create a new QUIC link bound to {}: {}", host, e))?
             .await
-            .map_err(|e| zerror!("Can not create a new QUIC link bound to {}: {}", host, e))?;
+            .map_err(|e| {
+                // A transport error is raised by the remote end, e.g. on a failed crypto handshake
+                let handshake = matches!(e, quinn::ConnectionError::TransportError(_));
+                let e = zerror!("Can not create a new QUIC link bound to {}: {}", host, e);
+                if handshake {
+                    zenoh_result::Error::from(LinkHandshakeError(e))
+                } else {
+                    e.into()
+                }
+            })?;
 
         let (send, recv) = quic_conn
             .open_bi()
@@ -310,62 +324,16 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
 
         let addr = get_quic_addr(&epaddr).await?;
 
-        let f = if let Some(value) = epconf.get(TLS_SERVER_CERTIFICATE_RAW) {
-            value.as_bytes().to_vec()
-        } else if let Some(value) = epconf.get(TLS_SERVER_CERTIFICATE_FILE) {
-            async_std::fs::read(value)
-                .await
-                .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
-        } else {
-            bail!("No QUIC CA certificate has been provided.");
-        };
-        let certificates = rustls_pemfile::certs(&mut BufReader::new(f.as_slice()))
-            .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
-            .drain(..)
-            .map(rustls::Certificate)
-            .collect();
-
-        // Private keys
-        let f = if let Some(value) = epconf.get(TLS_SERVER_PRIVATE_KEY_RAW) {
-            value.as_bytes().to_vec()
-        } else if let Some(value) = epconf.get(TLS_SERVER_PRIVATE_KEY_FILE) {
-            async_std::fs::read(value)
-                .await
-                .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
-        } else {
-            bail!("No QUIC CA private key has been provided.");
-        };
-        let private_key = rustls::PrivateKey(
-            rustls_pemfile::read_all(&mut BufReader::new(f.as_slice()))
-                .map_err(|e| zerror!("Invalid QUIC CA private key file: {}", e))?
-                .iter()
-                .filter_map(|x| match x {
-                    rustls_pemfile::Item::RSAKey(k)
-                    | rustls_pemfile::Item::PKCS8Key(k)
-                    | rustls_pemfile::Item::ECKey(k) => Some(k.to_vec()),
-                    _ => None,
-                })
-                .take(1)
-                .next()
-                .ok_or_else(|| zerror!("No QUIC CA private key has been provided."))?,
+        let (server_config, certificate) = load_server_config(&epconf).await?;
+        let expiry_warning = epconf
+            .get(TLS_EXPIRY_WARNING)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(TLS_EXPIRY_WARNING_DEFAULT);
+        let mut monitor = CertificateMonitor::new(
+            epconf.get(TLS_SERVER_CERTIFICATE_FILE),
+            Duration::from_secs(expiry_warning),
         );
-
-        // Server config
-        let mut server_crypto = rustls::ServerConfig::builder()
-            .with_safe_defaults()
-            .with_no_client_auth()
-            .with_single_cert(certificates, private_key)?;
-        server_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
-        let mut server_config = quinn::ServerConfig::with_crypto(Arc::new(server_crypto));
-
-        // We do not accept unidireactional streams.
-        Arc::get_mut(&mut server_config.transport)
-            .unwrap()
-            .max_concurrent_uni_streams(0_u8.into());
-        // For the time being we only allow one bidirectional stream
-        Arc::get_mut(&mut server_config.transport)
-            .unwrap()
-            .max_concurrent_bidi_streams(1_u8.into());
+        monitor.loaded(&certificate);
 
         // Initialize the Endpoint
         let quic_endpoint = quinn::Endpoint::server(server_config, addr)
@@ -387,12 +355,20 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
         let active = Arc::new(AtomicBool::new(true));
         let signal = Signal::new();
 
+        // Spawn the reload loop for the listener certificates
+        executor::spawn(reload_task(
+            endpoint.clone(),
+            quic_endpoint.clone(),
+            monitor,
+            signal.clone(),
+        ));
+
         let c_active = active.clone();
         let c_signal = signal.clone();
         let c_manager = self.manager.clone();
         let c_listeners = self.listeners.clone();
         let c_addr = local_addr;
-        let handle = task::spawn(async move {
+        let handle = executor::spawn(async move {
             // Wait for the accept loop to terminate
             let res = accept_task(quic_endpoint, c_active, c_signal, c_manager).await;
             zwrite!(c_listeners).remove(&c_addr);
@@ -467,6 +443,120 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
     }
 }
 
+async fn load_server_config(epconf: &Config<'_>) -> ZResult<(quinn::ServerConfig, Vec<u8>)> {
+    let certificate = if let Some(value) = epconf.get(TLS_SERVER_CERTIFICATE_RAW) {
+        value.as_bytes().to_vec()
+    } else if let Some(value) = epconf.get(TLS_SERVER_CERTIFICATE_FILE) {
+        async_std::fs::read(value)
+            .await
+            .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
+    } else {
+        bail!("No QUIC CA certificate has been provided.");
+    };
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(certificate.as_slice()))
+        .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
+        .drain(..)
+        .map(rustls::Certificate)
+        .collect();
+
+    // Private keys
+    let f = if let Some(value) = epconf.get(TLS_SERVER_PRIVATE_KEY_RAW) {
+        value.as_bytes().to_vec()
+    } else if let Some(value) = epconf.get(TLS_SERVER_PRIVATE_KEY_FILE) {
+        async_std::fs::read(value)
+            .await
+            .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
+    } else {
+        bail!("No QUIC CA private key has been provided.");
+    };
+    let private_key = rustls::PrivateKey(
+        rustls_pemfile::read_all(&mut BufReader::new(f.as_slice()))
+            .map_err(|e| zerror!("Invalid QUIC CA private key file: {}", e))?
+            .iter()
+            .filter_map(|x| match x {
+                rustls_pemfile::Item::RSAKey(k)
+                | rustls_pemfile::Item::PKCS8Key(k)
+                | rustls_pemfile::Item::ECKey(k) => Some(k.to_vec()),
+                _ => None,
+            })
+            .take(1)
+            .next()
+            .ok_or_else(|| zerror!("No QUIC CA private key has been provided."))?,
+    );
+
+    // Server config
+    let mut server_crypto = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certificates, private_key)?;
+    server_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
+    let mut server_config = quinn::ServerConfig::with_crypto(Arc::new(server_crypto));
+
+    // We do not accept unidireactional streams.
+    Arc::get_mut(&mut server_config.transport)
+        .unwrap()
+        .max_concurrent_uni_streams(0_u8.into());
+    // For the time being we only allow one bidirectional stream
+    Arc::get_mut(&mut server_config.transport)
+        .unwrap()
+        .max_concurrent_bidi_streams(1_u8.into());
+
+    Ok((server_config, certificate))
+}
+
+async fn reload_task(
+    endpoint: EndPoint,
+    quic_endpoint: quinn::Endpoint,
+    mut monitor: CertificateMonitor,
+    signal: Signal,
+) {
+    let epconf = endpoint.config();
+    let interval = epconf
+        .get(TLS_RELOAD_INTERVAL)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(TLS_RELOAD_INTERVAL_DEFAULT);
+    let mut watcher = FileWatcher::new(
+        [
+            TLS_SERVER_CERTIFICATE_FILE,
+            TLS_SERVER_PRIVATE_KEY_FILE,
+            TLS_ROOT_CA_CERTIFICATE_FILE,
+        ]
+        .iter()
+        .filter_map(|k| epconf.get(k)),
+    );
+    monitor.check_expiry();
+    if interval == 0 {
+        signal.wait().await;
+        return;
+    }
+
+    loop {
+        let tick = async {
+            task::sleep(Duration::from_millis(interval)).await;
+            true
+        };
+        let stop = async {
+            signal.wait().await;
+            false
+        };
+        if !tick.race(stop).await {
+            break;
+        }
+
+        if watcher.changed() {
+            // Existing connections are not affected: only the new ones use the new certificates
+            match load_server_config(&epconf).await {
+                Ok((server_config, certificate)) => {
+                    quic_endpoint.set_server_config(Some(server_config));
+                    monitor.loaded(&certificate);
+                }
+                Err(e) => monitor.failed(&e),
+            }
+        }
+        monitor.check_expiry();
+    }
+}
+
 async fn accept_task(
     endpoint: quinn::Endpoint,
     active: Arc<AtomicBool>,
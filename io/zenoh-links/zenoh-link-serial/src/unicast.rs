@@ -14,7 +14,6 @@
 
 use async_std::prelude::*;
 use async_std::sync::Mutex as AsyncMutex;
-use async_std::task::JoinHandle;
 use async_std::task::{self};
 use async_trait::async_trait;
 use std::cell::UnsafeCell;
@@ -23,7 +22,10 @@ use std::fmt;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
-use zenoh_core::{zasynclock, zread, zwrite};
+use zenoh_core::{
+    executor::{self, JoinHandle},
+    zasynclock, zread, zwrite,
+};
 use zenoh_link_commons::{
     ConstructibleLinkManagerUnicast, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait,
     NewLinkChannelSender,
@@ -314,7 +316,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastSerial {
         let c_signal = signal.clone();
         let c_manager = self.manager.clone();
         let c_listeners = self.listeners.clone();
-        let handle = task::spawn(async move {
+        let handle = executor::spawn(async move {
             // Wait for the accept loop to terminate
             let res = accept_read_task(
                 link,
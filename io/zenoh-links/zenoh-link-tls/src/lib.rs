@@ -23,12 +23,14 @@ use async_std::net::ToSocketAddrs;
 use async_trait::async_trait;
 use config::{
     TLS_CLIENT_AUTH, TLS_CLIENT_CERTIFICATE_FILE, TLS_CLIENT_PRIVATE_KEY_FILE,
+    TLS_CLIENT_PRIVATE_KEY_RAW, TLS_EXPIRY_WARNING, TLS_RELOAD_INTERVAL,
     TLS_ROOT_CA_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_FILE, TLS_SERVER_PRIVATE_KEY_FILE,
+    TLS_SERVER_PRIVATE_KEY_RAW,
 };
 use zenoh_cfg_properties::Properties;
 use zenoh_config::{Config, ZN_FALSE, ZN_TRUE};
 use zenoh_core::zconfigurable;
-use zenoh_link_commons::{ConfigurationInspector, LocatorInspector};
+use zenoh_link_commons::{secrets::insert_private_key, ConfigurationInspector, LocatorInspector};
 use zenoh_protocol::core::{endpoint::Address, Locator};
 use zenoh_result::{bail, zerror, ZResult};
 
@@ -63,6 +65,7 @@ impl ConfigurationInspector<Config> for TlsConfigurator {
         let mut properties = Properties::default();
 
         let c = config.transport().link().tls();
+        let secrets = config.transport().auth().secrets();
         if let Some(tls_ca_certificate) = c.root_ca_certificate() {
             properties.insert(
                 TLS_ROOT_CA_CERTIFICATE_FILE.into(),
@@ -70,10 +73,14 @@ impl ConfigurationInspector<Config> for TlsConfigurator {
             );
         }
         if let Some(tls_server_private_key) = c.server_private_key() {
-            properties.insert(
-                TLS_SERVER_PRIVATE_KEY_FILE.into(),
-                tls_server_private_key.into(),
-            );
+            insert_private_key(
+                &mut properties,
+                secrets,
+                tls_server_private_key,
+                TLS_SERVER_PRIVATE_KEY_FILE,
+                TLS_SERVER_PRIVATE_KEY_RAW,
+            )
+            .await?;
         }
         if let Some(tls_server_certificate) = c.server_certificate() {
             properties.insert(
@@ -81,6 +88,12 @@ impl ConfigurationInspector<Config> for TlsConfigurator {
                 tls_server_certificate.into(),
             );
         }
+        if let Some(tls_reload_interval) = c.reload_interval() {
+            properties.insert(TLS_RELOAD_INTERVAL.into(), tls_reload_interval.to_string());
+        }
+        if let Some(tls_expiry_warning) = c.expiry_warning() {
+            properties.insert(TLS_EXPIRY_WARNING.into(), tls_expiry_warning.to_string());
+        }
         if let Some(tls_client_auth) = c.client_auth() {
             match tls_client_auth {
                 true => properties.insert(TLS_CLIENT_AUTH.into(), ZN_TRUE.into()),
@@ -88,10 +101,14 @@ impl ConfigurationInspector<Config> for TlsConfigurator {
             };
         }
         if let Some(tls_client_private_key) = c.client_private_key() {
-            properties.insert(
-                TLS_CLIENT_PRIVATE_KEY_FILE.into(),
-                tls_client_private_key.into(),
-            );
+            insert_private_key(
+                &mut properties,
+                secrets,
+                tls_client_private_key,
+                TLS_CLIENT_PRIVATE_KEY_FILE,
+                TLS_CLIENT_PRIVATE_KEY_RAW,
+            )
+            .await?;
         }
         if let Some(tls_client_certificate) = c.client_certificate() {
             properties.insert(
@@ -136,6 +153,14 @@ pub mod config {
 
     pub const TLS_CLIENT_AUTH: &str = ZN_TLS_CLIENT_AUTH_STR;
     pub const TLS_CLIENT_AUTH_DEFAULT: &str = ZN_TLS_CLIENT_AUTH_DEFAULT;
+
+    pub const TLS_RELOAD_INTERVAL: &str = "tls_reload_interval";
+    pub const TLS_RELOAD_INTERVAL_DEFAULT: u64 =
+        zenoh_link_commons::certificates::TLS_RELOAD_INTERVAL_DEFAULT;
+
+    pub const TLS_EXPIRY_WARNING: &str = "tls_expiry_warning";
+    pub const TLS_EXPIRY_WARNING_DEFAULT: u64 =
+        zenoh_link_commons::certificates::TLS_EXPIRY_WARNING_DEFAULT;
 }
 
 pub async fn get_tls_addr(address: &Address<'_>) -> ZResult<SocketAddr> {
@@ -24,7 +24,6 @@ use async_std::net::{SocketAddr, TcpListener, TcpStream};
 use async_std::prelude::FutureExt;
 use async_std::sync::Mutex as AsyncMutex;
 use async_std::task;
-use async_std::task::JoinHandle;
 use async_trait::async_trait;
 use futures::io::AsyncReadExt;
 use futures::io::AsyncWriteExt;
@@ -39,9 +38,14 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 pub use webpki::*;
-use zenoh_core::{zasynclock, zread, zwrite};
+use zenoh_core::{
+    executor::{self, JoinHandle},
+    zasynclock, zread, zwrite,
+};
 use zenoh_link_commons::{
-    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+    certificates::{CertificateMonitor, FileWatcher},
+    LinkHandshakeError, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait,
+    NewLinkChannelSender,
 };
 use zenoh_protocol::core::endpoint::Config;
 use zenoh_protocol::core::{EndPoint, Locator};
@@ -309,11 +313,11 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
             .connect(server_name.to_owned(), tcp_stream)
             .await
             .map_err(|e| {
-                zerror!(
+                LinkHandshakeError(zerror!(
                     "Can not create a new TLS link bound to {:?}: {}",
                     server_name,
                     e
-                )
+                ))
             })?;
         let tls_stream = TlsStream::Client(tls_stream);
 
@@ -333,6 +337,15 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
         let tls_server_config = TlsServerConfig::new(&epconf)
             .await
             .map_err(|e| zerror!("Cannot create a new TLS listener on {addr}. {e}"))?;
+        let expiry_warning = epconf
+            .get(TLS_EXPIRY_WARNING)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(TLS_EXPIRY_WARNING_DEFAULT);
+        let mut monitor = CertificateMonitor::new(
+            epconf.get(TLS_SERVER_CERTIFICATE_FILE),
+            Duration::from_secs(expiry_warning),
+        );
+        monitor.loaded(&tls_server_config.certificate);
 
         // Initialize the TcpListener
         let socket = TcpListener::bind(addr)
@@ -345,17 +358,27 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
         let local_port = local_addr.port();
 
         // Initialize the TlsAcceptor
-        let acceptor = TlsAcceptor::from(Arc::new(tls_server_config.server_config));
+        let acceptor = Arc::new(RwLock::new(TlsAcceptor::from(Arc::new(
+            tls_server_config.server_config,
+        ))));
         let active = Arc::new(AtomicBool::new(true));
         let signal = Signal::new();
 
+        // Spawn the reload loop for the listener certificates
+        executor::spawn(reload_task(
+            endpoint.clone(),
+            acceptor.clone(),
+            monitor,
+            signal.clone(),
+        ));
+
         // Spawn the accept loop for the listener
         let c_active = active.clone();
         let c_signal = signal.clone();
         let c_manager = self.manager.clone();
         let c_listeners = self.listeners.clone();
         let c_addr = local_addr;
-        let handle = task::spawn(async move {
+        let handle = executor::spawn(async move {
             // Wait for the accept loop to terminate
             let res = accept_task(socket, acceptor, c_active, c_signal, c_manager).await;
             zwrite!(c_listeners).remove(&c_addr);
@@ -437,7 +460,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
 
 async fn accept_task(
     socket: TcpListener,
-    acceptor: TlsAcceptor,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
     active: Arc<AtomicBool>,
     signal: Signal,
     manager: NewLinkChannelSender,
@@ -483,8 +506,9 @@ async fn accept_task(
                 continue;
             }
         };
-        // Accept the TLS connection
-        let tls_stream = match acceptor.accept(tcp_stream).await {
+        // Accept the TLS connection with the latest loaded certificates
+        let current = zread!(acceptor).clone();
+        let tls_stream = match current.accept(tcp_stream).await {
             Ok(stream) => TlsStream::Server(stream),
             Err(e) => {
                 let e = format!("Can not accept TLS connection: {e}");
@@ -506,8 +530,62 @@ async fn accept_task(
     Ok(())
 }
 
+async fn reload_task(
+    endpoint: EndPoint,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+    mut monitor: CertificateMonitor,
+    signal: Signal,
+) {
+    let epconf = endpoint.config();
+    let interval = epconf
+        .get(TLS_RELOAD_INTERVAL)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(TLS_RELOAD_INTERVAL_DEFAULT);
+    let mut watcher = FileWatcher::new(
+        [
+            TLS_SERVER_CERTIFICATE_FILE,
+            TLS_SERVER_PRIVATE_KEY_FILE,
+            TLS_ROOT_CA_CERTIFICATE_FILE,
+        ]
+        .iter()
+        .filter_map(|k| epconf.get(k)),
+    );
+    monitor.check_expiry();
+    if interval == 0 {
+        signal.wait().await;
+        return;
+    }
+
+    loop {
+        let tick = async {
+            task::sleep(Duration::from_millis(interval)).await;
+            true
+        };
+        let stop = async {
+            signal.wait().await;
+            false
+        };
+        if !tick.race(stop).await {
+            break;
+        }
+
+        if watcher.changed() {
+            // Existing links are not affected: only the new connections use the new certificates
+            match TlsServerConfig::new(&epconf).await {
+                Ok(config) => {
+                    *zwrite!(acceptor) = TlsAcceptor::from(Arc::new(config.server_config));
+                    monitor.loaded(&config.certificate);
+                }
+                Err(e) => monitor.failed(&e),
+            }
+        }
+        monitor.check_expiry();
+    }
+}
+
 struct TlsServerConfig {
     server_config: ServerConfig,
+    certificate: Vec<u8>,
 }
 
 impl TlsServerConfig {
@@ -570,7 +648,10 @@ impl TlsServerConfig {
                 .with_single_cert(certs, keys.remove(0))
                 .map_err(|e| zerror!(e))?
         };
-        Ok(TlsServerConfig { server_config: sc })
+        Ok(TlsServerConfig {
+            server_config: sc,
+            certificate: tls_server_certificate,
+        })
     }
 
     async fn load_tls_private_key(config: &Config<'_>) -> ZResult<Vec<u8>> {
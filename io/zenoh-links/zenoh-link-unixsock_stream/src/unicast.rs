@@ -16,7 +16,6 @@ use async_std::os::unix::net::{UnixListener, UnixStream};
 use async_std::path::PathBuf;
 use async_std::prelude::FutureExt;
 use async_std::task;
-use async_std::task::JoinHandle;
 use async_trait::async_trait;
 use futures::io::AsyncReadExt;
 use futures::io::AsyncWriteExt;
@@ -29,7 +28,10 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use uuid::Uuid;
-use zenoh_core::{zread, zwrite};
+use zenoh_core::{
+    executor::{self, JoinHandle},
+    zread, zwrite,
+};
 use zenoh_link_commons::{
     LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
 };
@@ -368,7 +370,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
         let c_manager = self.manager.clone();
         let c_listeners = self.listeners.clone();
         let c_path = local_path_str.to_owned();
-        let handle = task::spawn(async move {
+        let handle = executor::spawn(async move {
             // Wait for the accept loop to terminate
             let res = accept_task(socket, c_active, c_signal, c_manager).await;
             zwrite!(c_listeners).remove(&c_path);
@@ -0,0 +1,329 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Capture of the batches exchanged on the links, for debugging purposes.
+//!
+//! Captures can be started and stopped at runtime. While no capture is running, the only
+//! cost on the links is a single atomic load per batch.
+use lazy_static::lazy_static;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zenoh_buffers::reader::{HasReader, Reader};
+use zenoh_buffers::writer::HasWriter;
+use zenoh_codec::{RCodec, WCodec, Zenoh060};
+use zenoh_core::{zlock, zread, zwrite};
+use zenoh_protocol::transport::TransportMessage;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The direction of a captured batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Tx,
+    Rx,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Tx => write!(f, "tx"),
+            Direction::Rx => write!(f, "rx"),
+        }
+    }
+}
+
+/// A batch sent or received on a link, without the length prefix of the streamed links.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedBatch {
+    pub time: SystemTime,
+    pub direction: Direction,
+    pub link: String,
+    pub bytes: Vec<u8>,
+}
+
+impl CapturedBatch {
+    /// Decodes the [`TransportMessage`]s of the batch.
+    pub fn messages(&self) -> ZResult<Vec<TransportMessage>> {
+        let codec = Zenoh060::default();
+        let mut reader = self.bytes.as_slice().reader();
+        let mut messages = vec![];
+        while reader.can_read() {
+            let msg: TransportMessage = codec
+                .read(&mut reader)
+                .map_err(|_| zerror!("Invalid batch captured on link {}", self.link))?;
+            messages.push(msg);
+        }
+        Ok(messages)
+    }
+}
+
+/// A destination of the captured batches.
+pub trait CaptureSink: Send + Sync {
+    fn capture(&self, batch: &CapturedBatch);
+}
+
+/// The identifier of a running capture, used to stop it.
+pub type CaptureId = usize;
+
+struct Capture {
+    id: CaptureId,
+    link: Option<String>,
+    sink: Arc<dyn CaptureSink>,
+}
+
+lazy_static! {
+    static ref CAPTURES: RwLock<Vec<Capture>> = RwLock::new(vec![]);
+}
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts capturing the batches of the links whose description contains `link`, or of all the
+/// links if `None`, to `sink`.
+pub fn start(link: Option<String>, sink: Arc<dyn CaptureSink>) -> CaptureId {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut captures = zwrite!(CAPTURES);
+    captures.push(Capture { id, link, sink });
+    ENABLED.store(true, Ordering::Release);
+    id
+}
+
+/// Stops the capture `id`, returning `false` if it wasn't running.
+pub fn stop(id: CaptureId) -> bool {
+    let mut captures = zwrite!(CAPTURES);
+    let len = captures.len();
+    captures.retain(|c| c.id != id);
+    ENABLED.store(!captures.is_empty(), Ordering::Release);
+    captures.len() != len
+}
+
+/// Stops all the running captures.
+pub fn stop_all() {
+    let mut captures = zwrite!(CAPTURES);
+    captures.clear();
+    ENABLED.store(false, Ordering::Release);
+}
+
+/// Returns `true` if at least one capture is running.
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+#[inline]
+pub(crate) fn capture(direction: Direction, link: &dyn fmt::Display, bytes: &[u8]) {
+    if is_enabled() {
+        dispatch(direction, link, bytes);
+    }
+}
+
+#[inline]
+pub(crate) fn capture_message(
+    direction: Direction,
+    link: &dyn fmt::Display,
+    msg: &TransportMessage,
+) {
+    if is_enabled() {
+        let mut bytes = vec![];
+        let codec = Zenoh060::default();
+        if codec.write(&mut bytes.writer(), msg).is_ok() {
+            dispatch(direction, link, &bytes);
+        }
+    }
+}
+
+#[cold]
+fn dispatch(direction: Direction, link: &dyn fmt::Display, bytes: &[u8]) {
+    let link = link.to_string();
+    let captures = zread!(CAPTURES);
+    let mut matching = captures
+        .iter()
+        .filter(|c| c.link.as_ref().map_or(true, |l| link.contains(l.as_str())))
+        .peekable();
+    if matching.peek().is_none() {
+        return;
+    }
+    let batch = CapturedBatch {
+        time: SystemTime::now(),
+        direction,
+        link,
+        bytes: bytes.to_vec(),
+    };
+    for c in matching {
+        c.sink.capture(&batch);
+    }
+}
+
+/*************************************/
+/*            CAPTURE FILE           */
+/*************************************/
+// The capture files start with `MAGIC` followed by the `VERSION` byte, then contain one record
+// per batch:
+//   - the time of the capture, in microseconds since the UNIX epoch (u64 LE)
+//   - the direction, 0 for tx and 1 for rx (u8)
+//   - the length of the link description (u16 LE), followed by the description in UTF-8
+//   - the length of the batch (u32 LE), followed by the batch
+const MAGIC: &[u8; 4] = b"ZCAP";
+const VERSION: u8 = 1;
+
+/// A [`CaptureSink`] writing the batches to a capture file, readable with [`read_capture_file`].
+pub struct FileCaptureSink {
+    file: Mutex<BufWriter<File>>,
+}
+
+impl FileCaptureSink {
+    /// Creates the capture file `name` in `directory`. `name` must be a plain file name and the
+    /// file must not exist yet, so that a capture can neither escape `directory` nor overwrite
+    /// an existing file.
+    pub fn new<P: AsRef<Path>>(directory: P, name: &str) -> ZResult<Self> {
+        let mut components = Path::new(name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => {}
+            _ => bail!("Invalid capture file name `{}`", name),
+        }
+        let path = directory.as_ref().join(name);
+        let file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| zerror!("Unable to create capture file {:?}: {}", path, e))?;
+        let mut file = BufWriter::new(file);
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+        file.flush()?;
+        Ok(FileCaptureSink {
+            file: Mutex::new(file),
+        })
+    }
+
+    fn write(file: &mut BufWriter<File>, batch: &CapturedBatch) -> std::io::Result<()> {
+        let time = batch
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        let link = &batch.link.as_bytes()[..batch.link.len().min(u16::MAX as usize)];
+        file.write_all(&time.to_le_bytes())?;
+        file.write_all(&[batch.direction as u8])?;
+        file.write_all(&(link.len() as u16).to_le_bytes())?;
+        file.write_all(link)?;
+        file.write_all(&(batch.bytes.len() as u32).to_le_bytes())?;
+        file.write_all(&batch.bytes)?;
+        file.flush()
+    }
+}
+
+impl CaptureSink for FileCaptureSink {
+    fn capture(&self, batch: &CapturedBatch) {
+        if let Err(e) = Self::write(&mut zlock!(self.file), batch) {
+            log::error!("Unable to write capture file: {}", e);
+        }
+    }
+}
+
+/// Reads the batches of a capture file written by a [`FileCaptureSink`].
+pub fn read_capture_file<P: AsRef<Path>>(path: P) -> ZResult<Vec<CapturedBatch>> {
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> ZResult<&'a [u8]> {
+        if bytes.len() < len {
+            bail!("Truncated capture file");
+        }
+        let (head, tail) = bytes.split_at(len);
+        *bytes = tail;
+        Ok(head)
+    }
+
+    let mut content = vec![];
+    File::open(path)?.read_to_end(&mut content)?;
+    let mut bytes = content.as_slice();
+    if take(&mut bytes, MAGIC.len())? != MAGIC {
+        bail!("Not a capture file");
+    }
+    let version = take(&mut bytes, 1)?[0];
+    if version != VERSION {
+        bail!("Unsupported capture file version: {}", version);
+    }
+
+    let mut batches = vec![];
+    while !bytes.is_empty() {
+        let time = u64::from_le_bytes(take(&mut bytes, 8)?.try_into().unwrap());
+        let direction = match take(&mut bytes, 1)?[0] {
+            0 => Direction::Tx,
+            1 => Direction::Rx,
+            d => bail!("Invalid direction in capture file: {}", d),
+        };
+        let len = u16::from_le_bytes(take(&mut bytes, 2)?.try_into().unwrap()) as usize;
+        let link = String::from_utf8(take(&mut bytes, len)?.to_vec())
+            .map_err(|e| zerror!("Invalid link in capture file: {}", e))?;
+        let len = u32::from_le_bytes(take(&mut bytes, 4)?.try_into().unwrap()) as usize;
+        batches.push(CapturedBatch {
+            time: UNIX_EPOCH + Duration::from_micros(time),
+            direction,
+            link,
+            bytes: take(&mut bytes, len)?.to_vec(),
+        });
+    }
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_file_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("zenoh-capture-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("link.zcap");
+
+        let id = start(
+            Some("tcp/127.0.0.1:7447".into()),
+            Arc::new(FileCaptureSink::new(&dir, "link.zcap").unwrap()),
+        );
+        assert!(is_enabled());
+        let msg = TransportMessage::make_keep_alive(None, None);
+        capture_message(
+            Direction::Tx,
+            &"tcp/127.0.0.1:7447 => tcp/10.0.0.1:1234",
+            &msg,
+        );
+        capture(Direction::Rx, &"udp/127.0.0.1:7447", &[0, 1, 2]);
+        assert!(stop(id));
+        assert!(!stop(id));
+        capture_message(Direction::Rx, &"tcp/127.0.0.1:7447", &msg);
+
+        let batches = read_capture_file(&path).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].direction, Direction::Tx);
+        assert_eq!(batches[0].link, "tcp/127.0.0.1:7447 => tcp/10.0.0.1:1234");
+        assert_eq!(batches[0].messages().unwrap(), vec![msg]);
+
+        // The capture files are only created in the directory, and never overwritten
+        assert!(FileCaptureSink::new(&dir, "link.zcap").is_err());
+        for name in [
+            "",
+            ".",
+            "..",
+            "../link.zcap",
+            "sub/link.zcap",
+            "/tmp/link.zcap",
+        ] {
+            assert!(FileCaptureSink::new(&dir, name).is_err());
+        }
+        assert_eq!(read_capture_file(&path).unwrap().len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -20,7 +20,7 @@ use zenoh_buffers::{
 use zenoh_codec::{WCodec, Zenoh060};
 use zenoh_protocol::{
     core::{Channel, Reliability, ZInt},
-    transport::{FrameHeader, FrameKind, TransportMessage},
+    transport::{FrameHeader, FrameKind, Preamble, TransportMessage},
     zenoh::ZenohMessage,
 };
 
@@ -95,6 +95,8 @@ pub(crate) struct WBatch {
     buffer: BBuf,
     // It is a streamed batch
     is_streamed: bool,
+    // The preamble preceding the length of a streamed batch, if any
+    preamble: Option<Preamble>,
     // The current frame being serialized: BestEffort/Reliable
     current_frame: CurrentFrame,
     // The latest SN
@@ -106,9 +108,15 @@ pub(crate) struct WBatch {
 
 impl WBatch {
     pub(crate) fn new(size: u16, is_streamed: bool) -> Self {
+        Self::with_buffer(BBuf::with_capacity(size as usize), is_streamed)
+    }
+
+    /// Creates a batch on the given buffer, whose capacity is the batch size.
+    pub(crate) fn with_buffer(buffer: BBuf, is_streamed: bool) -> Self {
         let mut batch = Self {
-            buffer: BBuf::with_capacity(size as usize),
+            buffer,
             is_streamed,
+            preamble: None,
             current_frame: CurrentFrame::None,
             latest_sn: LatestSn {
                 reliable: None,
@@ -124,6 +132,18 @@ impl WBatch {
         batch
     }
 
+    /// Precedes the length of a streamed batch with a [`Preamble`].
+    pub(crate) fn with_preamble(mut self, preamble: bool) -> Self {
+        self.preamble = (preamble && self.is_streamed).then(Preamble::new);
+        self.clear();
+        self
+    }
+
+    /// Releases the buffer of the batch, e.g. to give it back to a pool.
+    pub(crate) fn into_buffer(self) -> BBuf {
+        self.buffer
+    }
+
     /// Verify that the [`SerializationBatch`][SerializationBatch] has no serialized bytes.
     #[inline(always)]
     pub(crate) fn is_empty(&self) -> bool {
@@ -133,11 +153,16 @@ impl WBatch {
     /// Get the total number of bytes that have been serialized on the [`SerializationBatch`][SerializationBatch].
     #[inline(always)]
     pub(crate) fn len(&self) -> u16 {
-        let len = self.buffer.len() as u16;
+        self.buffer.len() as u16 - self.header_len() as u16
+    }
+
+    // The number of bytes preceding the serialized messages
+    #[inline(always)]
+    fn header_len(&self) -> usize {
         if self.is_streamed() {
-            len - (LENGTH_BYTES.len() as u16)
+            self.preamble.map_or(0, |_| Preamble::LEN) + LENGTH_BYTES.len()
         } else {
-            len
+            0
         }
     }
 
@@ -160,6 +185,9 @@ impl WBatch {
         }
         if self.is_streamed() {
             let mut writer = self.buffer.writer();
+            if let Some(preamble) = self.preamble.as_ref() {
+                let _ = writer.write_exact(&preamble.to_bytes());
+            }
             let _ = writer.write_exact(&LENGTH_BYTES[..]);
         }
     }
@@ -170,7 +198,9 @@ impl WBatch {
     pub(crate) fn write_len(&mut self) {
         if self.is_streamed() {
             let length = self.len();
-            self.buffer.as_mut_slice()[..LENGTH_BYTES.len()].copy_from_slice(&length.to_le_bytes());
+            let start = self.header_len() - LENGTH_BYTES.len();
+            self.buffer.as_mut_slice()[start..start + LENGTH_BYTES.len()]
+                .copy_from_slice(&length.to_le_bytes());
         }
     }
 
@@ -179,6 +209,12 @@ impl WBatch {
     pub(crate) fn as_bytes(&self) -> &[u8] {
         self.buffer.as_slice()
     }
+
+    /// Get a `&[u8]` to access the serialized messages, i.e. without the preamble and length of stream-based protocols.
+    #[inline(always)]
+    pub(crate) fn payload(&self) -> &[u8] {
+        &self.buffer.as_slice()[self.header_len()..]
+    }
 }
 
 impl Encode<&TransportMessage> for &mut WBatch {
@@ -413,4 +449,28 @@ mod tests {
         assert_ne!(batch.len(), 0);
         zmsgs_in.push(zmsg.clone());
     }
+
+    #[test]
+    fn serialization_batch_preamble() {
+        let mut batch = WBatch::new(u16::MAX, true).with_preamble(true);
+        assert!(batch.is_empty());
+
+        let tmsg = TransportMessage::make_keep_alive(None, None);
+        batch.encode(&tmsg).unwrap();
+        batch.write_len();
+
+        let bytes = batch.as_bytes();
+        let len = batch.len() as usize;
+        assert_eq!(bytes[..Preamble::LEN], Preamble::new().to_bytes());
+        assert_eq!(
+            bytes[Preamble::LEN..Preamble::LEN + 2],
+            (len as u16).to_le_bytes()
+        );
+        assert_eq!(batch.payload().len(), len);
+        assert_eq!(bytes.len(), Preamble::LEN + 2 + len);
+
+        // The preamble is only used on streamed batches
+        let batch = WBatch::new(u16::MAX, false).with_preamble(true);
+        assert!(batch.as_bytes().is_empty());
+    }
 }
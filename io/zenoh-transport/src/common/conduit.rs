@@ -13,6 +13,7 @@
 //
 use super::defragmentation::DefragBuffer;
 use super::seq_num::{SeqNum, SeqNumGenerator};
+use crate::memory::MemoryBudget;
 use std::sync::{Arc, Mutex};
 use zenoh_core::zlock;
 use zenoh_protocol::core::{ConduitSn, Reliability, ZInt};
@@ -47,9 +48,10 @@ impl TransportChannelRx {
         reliability: Reliability,
         sn_resolution: ZInt,
         defrag_buff_size: usize,
+        memory: Arc<MemoryBudget>,
     ) -> ZResult<TransportChannelRx> {
         let sn = SeqNum::make(0, sn_resolution)?;
-        let defrag = DefragBuffer::make(reliability, sn_resolution, defrag_buff_size)?;
+        let defrag = DefragBuffer::make(reliability, sn_resolution, defrag_buff_size, memory)?;
         let tch = TransportChannelRx { sn, defrag };
         Ok(tch)
     }
@@ -100,10 +102,20 @@ impl TransportConduitRx {
     pub(crate) fn make(
         sn_resolution: ZInt,
         defrag_buff_size: usize,
+        memory: &Arc<MemoryBudget>,
     ) -> ZResult<TransportConduitRx> {
-        let rch = TransportChannelRx::make(Reliability::Reliable, sn_resolution, defrag_buff_size)?;
-        let bch =
-            TransportChannelRx::make(Reliability::BestEffort, sn_resolution, defrag_buff_size)?;
+        let rch = TransportChannelRx::make(
+            Reliability::Reliable,
+            sn_resolution,
+            defrag_buff_size,
+            memory.clone(),
+        )?;
+        let bch = TransportChannelRx::make(
+            Reliability::BestEffort,
+            sn_resolution,
+            defrag_buff_size,
+            memory.clone(),
+        )?;
         let ctr = TransportConduitRx {
             reliable: Arc::new(Mutex::new(rch)),
             best_effort: Arc::new(Mutex::new(bch)),
@@ -12,6 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::seq_num::SeqNum;
+use crate::memory::{MemoryBudget, MemorySubsystem};
+use std::sync::Arc;
 use zenoh_buffers::{reader::HasReader, SplitBuffer, ZBuf, ZSlice};
 use zenoh_codec::{RCodec, Zenoh060Reliability};
 use zenoh_protocol::{
@@ -27,6 +29,7 @@ pub(crate) struct DefragBuffer {
     buffer: ZBuf,
     capacity: usize,
     len: usize,
+    memory: Arc<MemoryBudget>,
 }
 
 impl DefragBuffer {
@@ -34,6 +37,7 @@ impl DefragBuffer {
         reliability: Reliability,
         sn_resolution: ZInt,
         capacity: usize,
+        memory: Arc<MemoryBudget>,
     ) -> ZResult<DefragBuffer> {
         let db = DefragBuffer {
             reliability,
@@ -41,6 +45,7 @@ impl DefragBuffer {
             buffer: ZBuf::default(),
             capacity,
             len: 0,
+            memory,
         };
         Ok(db)
     }
@@ -50,9 +55,15 @@ impl DefragBuffer {
         self.buffer.is_empty()
     }
 
+    #[inline(always)]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
     #[inline(always)]
     pub(crate) fn clear(&mut self) {
         self.buffer.clear();
+        self.memory.release(MemorySubsystem::Defrag, self.len);
         self.len = 0;
     }
 
@@ -67,7 +78,7 @@ impl DefragBuffer {
             bail!("Expected SN {}, received {}", self.sn.get(), sn)
         }
 
-        let new_len = self.len + zslice.len();
+        let new_len = self.len.saturating_add(zslice.len());
         if new_len > self.capacity {
             self.clear();
             bail!(
@@ -76,6 +87,16 @@ impl DefragBuffer {
                 self.capacity
             )
         }
+        if !self
+            .memory
+            .try_reserve(MemorySubsystem::Defrag, zslice.len())
+        {
+            self.clear();
+            bail!(
+                "Defragmentation buffer dropped: {} bytes exceed the memory budget.",
+                new_len
+            )
+        }
 
         self.sn.increment();
         self.buffer.push_zslice(zslice);
@@ -93,3 +114,9 @@ impl DefragBuffer {
         res
     }
 }
+
+impl Drop for DefragBuffer {
+    fn drop(&mut self) {
+        self.memory.release(MemorySubsystem::Defrag, self.len);
+    }
+}
@@ -15,6 +15,8 @@ pub(crate) mod batch;
 pub(crate) mod conduit;
 pub(crate) mod defragmentation;
 pub(crate) mod pipeline;
+pub(crate) mod pool;
 pub(crate) mod seq_num;
 #[cfg(feature = "stats")]
 pub(crate) mod stats;
+pub(crate) mod workers;
@@ -16,17 +16,22 @@ use crate::common::batch::WError;
 // use super::batch::SerializationBatch;
 use super::batch::{Encode, WBatch};
 use super::conduit::{TransportChannelTx, TransportConduitTx};
+use super::pool::BufferPool;
+use crate::memory::{MemoryBudget, MemorySubsystem};
+use crate::pacing::{Pacer, QueuePacing};
+use crate::watermark::WatermarkDetector;
 use async_std::prelude::FutureExt;
-use flume::{bounded, Receiver, Sender};
+use flume::{bounded, Receiver, RecvTimeoutError, Sender};
 use ringbuffer_spsc::{RingBuffer, RingBufferReader, RingBufferWriter};
-use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use zenoh_buffers::{
     reader::{HasReader, Reader},
     writer::HasWriter,
-    ZBuf,
+    BBuf, ZBuf,
 };
 use zenoh_codec::{WCodec, Zenoh060};
 use zenoh_config::QueueSizeConf;
@@ -44,19 +49,117 @@ type NanoSeconds = u32;
 const RBLEN: usize = QueueSizeConf::MAX;
 const TSLOT: NanoSeconds = 100;
 
+thread_local! {
+    // The deadline of the blocking pushes of the current thread, and whether one has expired
+    static PUSH_DEADLINE: Cell<Option<Instant>> = Cell::new(None);
+    static PUSH_EXPIRED: Cell<bool> = Cell::new(false);
+    // The number of droppable messages dropped by the pushes of the current thread on congestion
+    static PUSH_DROPPED: Cell<usize> = Cell::new(0);
+}
+
+/// Runs `f` with the pushes of the current thread blocking on full transmission pipelines, or on
+/// their queues held by other pushes, giving up, and dropping their message, at `deadline`.
+///
+/// Returns the result of `f` and whether any push gave up.
+pub fn with_push_deadline<R>(deadline: Instant, f: impl FnOnce() -> R) -> (R, bool) {
+    let previous = PUSH_DEADLINE.with(|d| d.replace(Some(deadline)));
+    let expired = PUSH_EXPIRED.with(|e| e.replace(false));
+    let res = f();
+    PUSH_DEADLINE.with(|d| d.set(previous));
+    (res, PUSH_EXPIRED.with(|e| e.replace(expired)))
+}
+
+/// Runs `f` counting the droppable messages its pushes on the current thread drop because
+/// the transmission pipelines are full.
+///
+/// Returns the result of `f` and the number of dropped messages. The messages dropped within a
+/// nested call are only counted by that call.
+pub fn count_congestion_drops<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let previous = PUSH_DROPPED.with(|d| d.replace(0));
+    let res = f();
+    (res, PUSH_DROPPED.with(|d| d.replace(previous)))
+}
+
+// Inner structure to allocate the serialization batches of a queue on first use, up to the
+// queue size, so that the queues of the priorities that are never used take no memory
+struct BatchAllocator {
+    remaining: usize,
+    allocated: usize,
+    // The number of batches given back to the pool once sent, to be taken from it again
+    released: Arc<AtomicUsize>,
+    batch_size: u16,
+    is_streamed: bool,
+    preamble: bool,
+    pool: Option<Arc<BufferPool>>,
+    memory: Option<Arc<MemoryBudget>>,
+}
+
+impl BatchAllocator {
+    fn allocate(&mut self) -> Option<WBatch> {
+        if let Some(pool) = self.pool.as_ref() {
+            if self
+                .released
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Some(self.batch(pool.take(self.batch_size as usize)));
+            }
+        }
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.allocated += 1;
+        if let Some(memory) = self.memory.as_ref() {
+            memory.reserve(MemorySubsystem::TxQueues, self.batch_size as usize);
+        }
+        let batch = match self.pool.as_ref() {
+            Some(pool) => self.batch(pool.take(self.batch_size as usize)),
+            None => WBatch::new(self.batch_size, self.is_streamed).with_preamble(self.preamble),
+        };
+        Some(batch)
+    }
+
+    fn batch(&self, buffer: Box<[u8]>) -> WBatch {
+        WBatch::with_buffer(BBuf::from(buffer), self.is_streamed).with_preamble(self.preamble)
+    }
+}
+
+impl Drop for BatchAllocator {
+    fn drop(&mut self) {
+        if let Some(memory) = self.memory.as_ref() {
+            memory.release(
+                MemorySubsystem::TxQueues,
+                self.allocated * self.batch_size as usize,
+            );
+        }
+    }
+}
+
 // Inner structure to reuse serialization batches
 struct StageInRefill {
     n_ref_r: Receiver<()>,
     s_ref_r: RingBufferReader<WBatch, RBLEN>,
+    batches: BatchAllocator,
 }
 
 impl StageInRefill {
     fn pull(&mut self) -> Option<WBatch> {
-        self.s_ref_r.pull()
+        self.s_ref_r.pull().or_else(|| self.batches.allocate())
     }
 
     fn wait(&self) -> bool {
-        self.n_ref_r.recv().is_ok()
+        match PUSH_DEADLINE.with(Cell::get) {
+            Some(deadline) => match self.n_ref_r.recv_deadline(deadline) {
+                Ok(()) => true,
+                Err(RecvTimeoutError::Timeout) => {
+                    PUSH_EXPIRED.with(|e| e.set(true));
+                    false
+                }
+                Err(RecvTimeoutError::Disconnected) => false,
+            },
+            None => self.n_ref_r.recv().is_ok(),
+        }
     }
 }
 
@@ -66,6 +169,8 @@ struct StageInOut {
     s_out_w: RingBufferWriter<WBatch, RBLEN>,
     bytes: Arc<AtomicU16>,
     backoff: Arc<AtomicBool>,
+    queued: Arc<AtomicUsize>,
+    watermarks: Option<Arc<WatermarkDetector>>,
 }
 
 impl StageInOut {
@@ -79,7 +184,12 @@ impl StageInOut {
 
     #[inline]
     fn move_batch(&mut self, batch: WBatch) {
-        let _ = self.s_out_w.push(batch);
+        if self.s_out_w.push(batch).is_none() {
+            let queued = self.queued.fetch_add(1, Ordering::Relaxed) + 1;
+            if let Some(watermarks) = &self.watermarks {
+                watermarks.enqueued(queued);
+            }
+        }
         self.bytes.store(0, Ordering::Relaxed);
         let _ = self.n_out_w.try_send(());
     }
@@ -113,6 +223,21 @@ struct StageIn {
     s_out: StageInOut,
     mutex: StageInMutex,
     fragbuf: ZBuf,
+    max_message_size: usize,
+}
+
+// The batches are given back to the pool when the pipeline is dropped
+impl Drop for StageIn {
+    fn drop(&mut self) {
+        if let Some(pool) = self.s_ref.batches.pool.as_ref() {
+            while let Some(batch) = self.s_ref.s_ref_r.pull() {
+                pool.give(batch.into_buffer().into());
+            }
+            if let Some(batch) = self.mutex.current().take() {
+                pool.give(batch.into_buffer().into());
+            }
+        }
+    }
 }
 
 impl StageIn {
@@ -140,6 +265,7 @@ impl StageIn {
                                     // The yield is to avoid the writing task to spin
                                     // indefinitely and monopolize the CPU usage.
                                     thread::yield_now();
+                                    PUSH_DROPPED.with(|d| d.set(d.get() + 1));
                                     return false;
                                 } else {
                                     if !self.s_ref.wait() {
@@ -210,9 +336,21 @@ impl StageIn {
         // Take the expandable buffer and serialize the totality of the message
         self.fragbuf.clear();
 
+        // The messages larger than the maximum size are dropped rather than sent in vain, as
+        // the remote side would not reassemble them
         let mut writer = self.fragbuf.writer();
         let codec = Zenoh060::default();
-        codec.write(&mut writer, &*msg).unwrap();
+        if codec.write(&mut writer, &*msg).is_err() || self.fragbuf.len() > self.max_message_size {
+            log::error!(
+                "Zenoh message dropped because its {} bytes exceed the maximum message size of {} bytes",
+                self.fragbuf.len(),
+                self.max_message_size
+            );
+            // Restore the sequence number
+            tch.sn.set(sn).unwrap();
+            self.fragbuf.clear();
+            return false;
+        }
 
         // Fragment the whole message
         let mut reader = self.fragbuf.reader();
@@ -353,12 +491,26 @@ struct StageOutIn {
     s_out_r: RingBufferReader<WBatch, RBLEN>,
     current: Arc<Mutex<Option<WBatch>>>,
     backoff: Backoff,
+    queued: Arc<AtomicUsize>,
+    watermarks: Option<Arc<WatermarkDetector>>,
 }
 
 impl StageOutIn {
+    #[inline]
+    fn pull_out(&mut self) -> Option<WBatch> {
+        let batch = self.s_out_r.pull();
+        if batch.is_some() {
+            let queued = self.queued.fetch_sub(1, Ordering::Relaxed) - 1;
+            if let Some(watermarks) = &self.watermarks {
+                watermarks.dequeued(queued);
+            }
+        }
+        batch
+    }
+
     #[inline]
     fn try_pull(&mut self) -> Pull {
-        if let Some(mut batch) = self.s_out_r.pull() {
+        if let Some(mut batch) = self.pull_out() {
             batch.write_len();
             self.backoff.stop();
             return Pull::Some(batch);
@@ -377,7 +529,7 @@ impl StageOutIn {
                 // No new bytes have been written on the batch, try to pull
                 if let Ok(mut g) = self.current.try_lock() {
                     // First try to pull from stage OUT
-                    if let Some(mut batch) = self.s_out_r.pull() {
+                    if let Some(mut batch) = self.pull_out() {
                         batch.write_len();
                         self.backoff.stop();
                         return Pull::Some(batch);
@@ -400,7 +552,7 @@ impl StageOutIn {
             }
             std::cmp::Ordering::Less => {
                 // There should be a new batch in Stage OUT
-                if let Some(mut batch) = self.s_out_r.pull() {
+                if let Some(mut batch) = self.pull_out() {
                     batch.write_len();
                     self.backoff.stop();
                     return Pull::Some(batch);
@@ -421,11 +573,21 @@ impl StageOutIn {
 struct StageOutRefill {
     n_ref_w: Sender<()>,
     s_ref_w: RingBufferWriter<WBatch, RBLEN>,
+    pool: Option<Arc<BufferPool>>,
+    released: Arc<AtomicUsize>,
+    batch_size: u16,
 }
 
 impl StageOutRefill {
     fn refill(&mut self, batch: WBatch) {
-        assert!(self.s_ref_w.push(batch).is_none());
+        // The sent batch is given back to the pool, to be reused by any link, unless it is full
+        match self.pool.as_ref() {
+            Some(pool) if pool.has_room(self.batch_size as usize) => {
+                pool.give(batch.into_buffer().into());
+                self.released.fetch_add(1, Ordering::AcqRel);
+            }
+            _ => assert!(self.s_ref_w.push(batch).is_none()),
+        }
         let _ = self.n_ref_w.try_send(());
     }
 }
@@ -433,12 +595,34 @@ impl StageOutRefill {
 struct StageOut {
     s_in: StageOutIn,
     s_ref: StageOutRefill,
+    pool: Option<Arc<BufferPool>>,
+    pacer: Option<Pacer>,
+}
+
+impl Drop for StageOut {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.as_ref() {
+            while let Some(batch) = self.s_in.pull_out() {
+                pool.give(batch.into_buffer().into());
+            }
+        }
+    }
 }
 
 impl StageOut {
     #[inline]
     fn try_pull(&mut self) -> Pull {
-        self.s_in.try_pull()
+        // Leave the batches in the queue until the next interval once the pacing budget is spent
+        if let Some(pacer) = self.pacer.as_mut() {
+            if let Some(delay) = pacer.delay(Instant::now()) {
+                return Pull::Backoff(delay.as_nanos().min(NanoSeconds::MAX as u128) as NanoSeconds);
+            }
+        }
+        let pull = self.s_in.try_pull();
+        if let (Pull::Some(batch), Some(pacer)) = (&pull, self.pacer.as_mut()) {
+            pacer.account(batch.len() as usize);
+        }
+        pull
     }
 
     #[inline]
@@ -449,7 +633,7 @@ impl StageOut {
     fn drain(&mut self, guard: &mut MutexGuard<'_, Option<WBatch>>) -> Vec<WBatch> {
         let mut batches = vec![];
         // Empty the ring buffer
-        while let Some(mut batch) = self.s_in.s_out_r.pull() {
+        while let Some(mut batch) = self.s_in.pull_out() {
             batch.write_len();
             batches.push(batch);
         }
@@ -461,21 +645,32 @@ impl StageOut {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub(crate) struct TransmissionPipelineConf {
     pub(crate) is_streamed: bool,
+    pub(crate) preamble: bool,
     pub(crate) batch_size: u16,
+    pub(crate) max_message_size: usize,
     pub(crate) queue_size: [usize; Priority::NUM],
     pub(crate) backoff: Duration,
+    pub(crate) watermarks: Option<Arc<WatermarkDetector>>,
+    // The budget in which the allocated batches are accounted, if any
+    pub(crate) memory: Option<Arc<MemoryBudget>>,
+    pub(crate) pacing: Option<QueuePacing>,
 }
 
 impl Default for TransmissionPipelineConf {
     fn default() -> Self {
         Self {
             is_streamed: false,
+            preamble: false,
             batch_size: u16::MAX,
+            max_message_size: usize::MAX,
             queue_size: [1; Priority::NUM],
             backoff: Duration::from_micros(1),
+            watermarks: None,
+            memory: None,
+            pacing: None,
         }
     }
 }
@@ -484,9 +679,11 @@ impl Default for TransmissionPipelineConf {
 pub(crate) struct TransmissionPipeline;
 impl TransmissionPipeline {
     // A MPSC pipeline
+    // The batches are taken from the pool if any, and given back to it when the pipeline is dropped
     pub(crate) fn make(
         config: TransmissionPipelineConf,
         conduit: &[TransportConduitTx],
+        pool: Option<Arc<BufferPool>>,
     ) -> (TransmissionPipelineProducer, TransmissionPipelineConsumer) {
         let mut stage_in = vec![];
         let mut stage_out = vec![];
@@ -497,23 +694,40 @@ impl TransmissionPipeline {
         } else {
             config.queue_size.iter()
         };
+        let pacer = |prio: usize| {
+            config.pacing.and_then(|pacing| {
+                let prio = if conduit.len() == 1 {
+                    Priority::default() as usize
+                } else {
+                    prio
+                };
+                pacing.bytes[prio].map(|bytes| Pacer::new(bytes, pacing.interval))
+            })
+        };
 
         // Create the channel for notifying that new batches are in the out ring buffer
         // This is a MPSC channel
         let (n_out_w, n_out_r) = bounded(1);
+        // The number of batches ready to be pulled, for all the priorities
+        let queued = Arc::new(AtomicUsize::new(0));
 
         for (prio, num) in size_iter.enumerate() {
             assert!(*num != 0 && *num <= RBLEN);
 
-            // Create the refill ring buffer
+            // Create the refill ring buffer, filled with batches as they are allocated
             // This is a SPSC ring buffer
-            let (mut s_ref_w, s_ref_r) = RingBuffer::<WBatch, RBLEN>::init();
-            // Fill the refill ring buffer with batches
-            for _ in 0..*num {
-                assert!(s_ref_w
-                    .push(WBatch::new(config.batch_size, config.is_streamed))
-                    .is_none());
-            }
+            let (s_ref_w, s_ref_r) = RingBuffer::<WBatch, RBLEN>::init();
+            let released = Arc::new(AtomicUsize::new(0));
+            let batches = BatchAllocator {
+                remaining: *num,
+                allocated: 0,
+                released: released.clone(),
+                batch_size: config.batch_size,
+                is_streamed: config.is_streamed,
+                preamble: config.preamble,
+                pool: pool.clone(),
+                memory: config.memory.clone(),
+            };
             // Create the channel for notifying that new batches are in the refill ring buffer
             // This is a SPSC channel
             let (n_ref_w, n_ref_r) = bounded(1);
@@ -526,18 +740,25 @@ impl TransmissionPipeline {
             let backoff = Arc::new(AtomicBool::new(false));
 
             stage_in.push(Mutex::new(StageIn {
-                s_ref: StageInRefill { n_ref_r, s_ref_r },
+                s_ref: StageInRefill {
+                    n_ref_r,
+                    s_ref_r,
+                    batches,
+                },
                 s_out: StageInOut {
                     n_out_w: n_out_w.clone(),
                     s_out_w,
                     bytes: bytes.clone(),
                     backoff: backoff.clone(),
+                    queued: queued.clone(),
+                    watermarks: config.watermarks.clone(),
                 },
                 mutex: StageInMutex {
                     current: current.clone(),
                     conduit: conduit[prio].clone(),
                 },
                 fragbuf: ZBuf::default(),
+                max_message_size: config.max_message_size,
             }));
 
             // The stage out for this priority
@@ -546,8 +767,18 @@ impl TransmissionPipeline {
                     s_out_r,
                     current,
                     backoff: Backoff::new(bytes, backoff),
+                    queued: queued.clone(),
+                    watermarks: config.watermarks.clone(),
+                },
+                s_ref: StageOutRefill {
+                    n_ref_w,
+                    s_ref_w,
+                    pool: pool.clone(),
+                    released,
+                    batch_size: config.batch_size,
                 },
-                s_ref: StageOutRefill { n_ref_w, s_ref_w },
+                pool: pool.clone(),
+                pacer: pacer(prio),
             });
         }
 
@@ -555,6 +786,8 @@ impl TransmissionPipeline {
         let producer = TransmissionPipelineProducer {
             stage_in: stage_in.into_boxed_slice().into(),
             active: active.clone(),
+            queued,
+            watermarks: config.watermarks,
         };
         let consumer = TransmissionPipelineConsumer {
             stage_out: stage_out.into_boxed_slice(),
@@ -571,11 +804,28 @@ pub(crate) struct TransmissionPipelineProducer {
     // Each priority queue has its own Mutex
     stage_in: Arc<[Mutex<StageIn>]>,
     active: Arc<AtomicBool>,
+    queued: Arc<AtomicUsize>,
+    watermarks: Option<Arc<WatermarkDetector>>,
 }
 
 impl TransmissionPipelineProducer {
     #[inline]
     pub(crate) fn push_zenoh_message(&self, mut msg: ZenohMessage) -> bool {
+        let span = tracing::trace_span!(
+            "tx",
+            priority = ?msg.channel.priority,
+            trace = tracing::field::Empty
+        );
+        if let Some(tc) = msg.trace_context() {
+            span.record("trace", tracing::field::display(tc));
+        }
+        let _enter = span.enter();
+        // Shed the droppable low priority messages while the queue is above its high watermark
+        if let Some(watermarks) = &self.watermarks {
+            if watermarks.drops(msg.channel.priority, msg.congestion_control()) {
+                return false;
+            }
+        }
         // If the queue is not QoS, it means that we only have one priority with index 0.
         let (idx, priority) = if self.stage_in.len() > 1 {
             (msg.channel.priority as usize, msg.channel.priority)
@@ -583,10 +833,66 @@ impl TransmissionPipelineProducer {
             (0, Priority::default())
         };
         // Lock the channel. We are the only one that will be writing on it.
-        let mut queue = zlock!(self.stage_in[idx]);
+        let mut queue = match self.lock_stage_in(idx) {
+            Some(queue) => queue,
+            None => return false,
+        };
         queue.push_zenoh_message(&mut msg, priority)
     }
 
+    /// Pushes messages sharing the same priority, without any other message of that priority being
+    /// pushed in between. The messages are serialized in the same batch as long as they fit in.
+    pub(crate) fn push_zenoh_messages(&self, msgs: Vec<ZenohMessage>) -> bool {
+        let (idx, priority) = match msgs.first() {
+            Some(msg) if self.stage_in.len() > 1 => {
+                (msg.channel.priority as usize, msg.channel.priority)
+            }
+            Some(_) => (0, Priority::default()),
+            None => return true,
+        };
+        // Lock the channel for the whole batch.
+        let mut queue = match self.lock_stage_in(idx) {
+            Some(queue) => queue,
+            None => return false,
+        };
+        let mut res = true;
+        for mut msg in msgs {
+            if let Some(watermarks) = &self.watermarks {
+                if watermarks.drops(msg.channel.priority, msg.congestion_control()) {
+                    res = false;
+                    continue;
+                }
+            }
+            res &= queue.push_zenoh_message(&mut msg, priority);
+        }
+        res
+    }
+
+    // Locks the queue of index `idx`. Another push may hold the lock while blocked on the full
+    // queue, the wait for the lock thus gives up at the push deadline of the current thread too.
+    fn lock_stage_in(&self, idx: usize) -> Option<MutexGuard<'_, StageIn>> {
+        let deadline = match PUSH_DEADLINE.with(Cell::get) {
+            Some(deadline) => deadline,
+            None => return Some(zlock!(self.stage_in[idx])),
+        };
+        let mut backoff = Duration::from_nanos(TSLOT as u64);
+        loop {
+            match self.stage_in[idx].try_lock() {
+                Ok(queue) => return Some(queue),
+                Err(TryLockError::Poisoned(_)) => return Some(zlock!(self.stage_in[idx])),
+                Err(TryLockError::WouldBlock) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        PUSH_EXPIRED.with(|e| e.set(true));
+                        return None;
+                    }
+                    thread::sleep(backoff.min(deadline - now));
+                    backoff = (backoff * 2).min(Duration::from_millis(1));
+                }
+            }
+        }
+    }
+
     #[inline]
     pub(crate) fn push_transport_message(&self, msg: TransportMessage, priority: Priority) -> bool {
         // If the queue is not QoS, it means that we only have one priority with index 0.
@@ -600,6 +906,12 @@ impl TransmissionPipelineProducer {
         queue.push_transport_message(msg)
     }
 
+    /// The number of complete batches waiting to be pulled.
+    #[inline]
+    pub(crate) fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn disable(&self) {
         self.active.store(false, Ordering::Relaxed);
 
@@ -680,6 +992,49 @@ impl TransmissionPipelineConsumer {
     }
 }
 
+/// A transmission pipeline without link, whose batches are pulled and given back by a background
+/// task, to benchmark the serialization and the batching of the messages.
+#[cfg(feature = "test")]
+#[doc(hidden)]
+pub struct BenchPipeline {
+    producer: TransmissionPipelineProducer,
+}
+
+#[cfg(feature = "test")]
+impl BenchPipeline {
+    /// Creates a pipeline with the given batch size, with a queue per priority if `qos` is set.
+    pub fn new(batch_size: u16, qos: bool) -> Self {
+        let num = if qos { Priority::NUM } else { 1 };
+        let conduits = (0..num)
+            .map(|_| TransportConduitTx::make(zenoh_protocol::defaults::SEQ_NUM_RES).unwrap())
+            .collect::<Vec<_>>();
+        let config = TransmissionPipelineConf {
+            is_streamed: true,
+            batch_size,
+            queue_size: [QueueSizeConf::MAX; Priority::NUM],
+            ..Default::default()
+        };
+        let (producer, mut consumer) = TransmissionPipeline::make(config, &conduits, None);
+        async_std::task::spawn(async move {
+            while let Some((batch, priority)) = consumer.pull().await {
+                consumer.refill(batch, priority);
+            }
+        });
+        BenchPipeline { producer }
+    }
+
+    pub fn push(&self, msg: ZenohMessage) -> bool {
+        self.producer.push_zenoh_message(msg)
+    }
+}
+
+#[cfg(feature = "test")]
+impl Drop for BenchPipeline {
+    fn drop(&mut self) {
+        self.producer.disable();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -709,9 +1064,14 @@ mod tests {
 
     const CONFIG: TransmissionPipelineConf = TransmissionPipelineConf {
         is_streamed: true,
+        preamble: false,
         batch_size: BATCH_SIZE,
+        max_message_size: usize::MAX,
         queue_size: [1; Priority::NUM],
         backoff: Duration::from_micros(1),
+        watermarks: None,
+        memory: None,
+        pacing: None,
     };
 
     #[test]
@@ -823,6 +1183,7 @@ mod tests {
                 let (producer, consumer) = TransmissionPipeline::make(
                     TransmissionPipelineConf::default(),
                     conduits.as_slice(),
+                    None,
                 );
 
                 let t_c = task::spawn(async move {
@@ -892,8 +1253,11 @@ mod tests {
         // Pipeline
         let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
         let conduits = vec![tct];
-        let (producer, mut consumer) =
-            TransmissionPipeline::make(TransmissionPipelineConf::default(), conduits.as_slice());
+        let (producer, mut consumer) = TransmissionPipeline::make(
+            TransmissionPipelineConf::default(),
+            conduits.as_slice(),
+            None,
+        );
 
         let counter = Arc::new(AtomicUsize::new(0));
 
@@ -938,13 +1302,280 @@ mod tests {
         });
     }
 
+    #[test]
+    fn tx_pipeline_push_deadline() {
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; (CONFIG.batch_size / 2) as usize]),
+            Channel {
+                priority: Priority::Control,
+                reliability: Reliability::Reliable,
+            },
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        // Nothing pulls from the pipeline, so that the pushes end up blocking
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let (producer, _consumer) = TransmissionPipeline::make(CONFIG, &[tct], None);
+        let deadline = Duration::from_millis(200);
+        let start = Instant::now();
+        let (pushed, expired) = with_push_deadline(start + deadline, || {
+            (0..=CONFIG.queue_size[0] + 1).all(|_| producer.push_zenoh_message(message.clone()))
+        });
+        assert!(!pushed);
+        assert!(expired);
+        assert!(start.elapsed() >= deadline);
+        assert!(start.elapsed() < TIMEOUT);
+
+        // The deadline also applies to the wait for the queue held by another push
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let (producer, _consumer) = TransmissionPipeline::make(CONFIG, &[tct], None);
+        let queue = zlock!(producer.stage_in[0]);
+        let start = Instant::now();
+        let (pushed, expired) = with_push_deadline(start + deadline, || {
+            producer.push_zenoh_message(message.clone())
+        });
+        drop(queue);
+        assert!(!pushed);
+        assert!(expired);
+        assert!(start.elapsed() >= deadline);
+        assert!(start.elapsed() < TIMEOUT);
+    }
+
+    #[test]
+    fn tx_pipeline_lazy_batches() {
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; 8]),
+            Channel {
+                priority: Priority::Data,
+                reliability: Reliability::Reliable,
+            },
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let memory = Arc::new(MemoryBudget::default());
+        let config = TransmissionPipelineConf {
+            queue_size: [4; Priority::NUM],
+            memory: Some(memory.clone()),
+            ..CONFIG
+        };
+        let conduits = (0..Priority::NUM)
+            .map(|_| TransportConduitTx::make(SEQ_NUM_RES).unwrap())
+            .collect::<Vec<_>>();
+        let (producer, consumer) = TransmissionPipeline::make(config, &conduits, None);
+        assert_eq!(memory.used(), 0);
+
+        // Only the batch of the used priority is allocated
+        assert!(producer.push_zenoh_message(message.clone()));
+        assert!(producer.push_zenoh_message(message));
+        assert_eq!(memory.used(), CONFIG.batch_size as usize);
+
+        drop(producer);
+        drop(consumer);
+        assert_eq!(memory.used(), 0);
+    }
+
+    #[test]
+    fn tx_pipeline_pool() {
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; 8]),
+            Channel {
+                priority: Priority::Data,
+                reliability: Reliability::Reliable,
+            },
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let pool = Arc::new(BufferPool::new(4 * CONFIG.batch_size as usize));
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let (producer, mut consumer) =
+            TransmissionPipeline::make(CONFIG, &[tct], Some(pool.clone()));
+
+        // The sent batches are given back to the pool and taken from it again
+        for i in 0..4 {
+            assert!(producer.push_zenoh_message(message.clone()));
+            let (batch, priority) = task::block_on(consumer.pull().timeout(TIMEOUT))
+                .unwrap()
+                .unwrap();
+            consumer.refill(batch, priority);
+            assert_eq!(pool.stats().size, CONFIG.batch_size as usize);
+            assert_eq!(pool.stats().misses, 1);
+            assert_eq!(pool.stats().hits, i);
+        }
+
+        drop(producer);
+        drop(consumer);
+        assert_eq!(pool.stats().size, CONFIG.batch_size as usize);
+    }
+
+    #[test]
+    fn tx_pipeline_pacing() {
+        // Every message fills its own batch
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; (CONFIG.batch_size / 2) as usize + 1]),
+            Channel {
+                priority: Priority::Data,
+                reliability: Reliability::Reliable,
+            },
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        // A single batch per interval
+        const INTERVAL: Duration = Duration::from_millis(50);
+        let mut bytes = [None; Priority::NUM];
+        bytes[Priority::Data as usize] = Some(1);
+        let config = TransmissionPipelineConf {
+            queue_size: [4; Priority::NUM],
+            pacing: Some(QueuePacing {
+                interval: INTERVAL,
+                bytes,
+            }),
+            ..CONFIG
+        };
+        let start = Instant::now();
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let (producer, mut consumer) = TransmissionPipeline::make(config, &[tct], None);
+        for _ in 0..3 {
+            assert!(producer.push_zenoh_message(message.clone()));
+        }
+
+        // The batches are spread over the intervals, none of them is lost
+        task::block_on(async {
+            for _ in 0..3 {
+                let (batch, priority) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+                consumer.refill(batch, priority);
+            }
+        });
+        assert!(start.elapsed() >= 2 * INTERVAL);
+    }
+
+    #[test]
+    fn tx_pipeline_max_message_size() {
+        let message = |len: usize| {
+            ZenohMessage::make_data(
+                "test".into(),
+                ZBuf::from(vec![0_u8; len]),
+                Channel {
+                    priority: Priority::Data,
+                    reliability: Reliability::Reliable,
+                },
+                CongestionControl::Block,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+
+        let config = TransmissionPipelineConf {
+            max_message_size: 2 * BATCH_SIZE as usize,
+            queue_size: [4; Priority::NUM],
+            ..CONFIG
+        };
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let (producer, _consumer) = TransmissionPipeline::make(config, &[tct], None);
+
+        // A message larger than the maximum size is refused without being fragmented
+        assert!(!producer.push_zenoh_message(message(4 * BATCH_SIZE as usize)));
+        assert_eq!(producer.queued(), 0);
+        // The smaller ones are still fragmented
+        assert!(producer.push_zenoh_message(message(BATCH_SIZE as usize)));
+        assert!(producer.queued() > 0);
+    }
+
+    #[test]
+    fn tx_pipeline_congestion_drops() {
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; (CONFIG.batch_size / 2) as usize]),
+            Channel {
+                priority: Priority::Data,
+                reliability: Reliability::BestEffort,
+            },
+            CongestionControl::Drop,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        // Nothing pulls from the pipeline, so that the pushes end up dropping their message
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let (producer, _consumer) = TransmissionPipeline::make(CONFIG, &[tct], None);
+        let (pushed, dropped) = count_congestion_drops(|| {
+            (0..10)
+                .filter(|_| producer.push_zenoh_message(message.clone()))
+                .count()
+        });
+        assert!(dropped > 0);
+        assert_eq!(pushed + dropped, 10);
+
+        // The drops of a nested call are not counted again by the outer call
+        let ((_, inner), outer) = count_congestion_drops(|| {
+            count_congestion_drops(|| producer.push_zenoh_message(message.clone()))
+        });
+        assert_eq!(inner, 1);
+        assert_eq!(outer, 0);
+    }
+
+    #[test]
+    fn tx_pipeline_batch_partial_drops() {
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; (CONFIG.batch_size / 2) as usize]),
+            Channel {
+                priority: Priority::Data,
+                reliability: Reliability::BestEffort,
+            },
+            CongestionControl::Drop,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        // Nothing pulls from the pipeline, so that the batch doesn't fit in the queue
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let (producer, mut consumer) = TransmissionPipeline::make(CONFIG, &[tct], None);
+        let (pushed, dropped) =
+            count_congestion_drops(|| producer.push_zenoh_messages(vec![message; 10]));
+        assert!(!pushed);
+
+        // The messages preceding the congestion are sent nonetheless
+        assert!(dropped > 0 && dropped < 10);
+        task::block_on(async {
+            let (batch, _) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+            assert!(!batch.is_empty());
+        });
+    }
+
     #[test]
     #[ignore]
     fn tx_pipeline_thr() {
         // Queue
         let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
         let conduits = vec![tct];
-        let (producer, mut consumer) = TransmissionPipeline::make(CONFIG, conduits.as_slice());
+        let (producer, mut consumer) =
+            TransmissionPipeline::make(CONFIG, conduits.as_slice(), None);
         let count = Arc::new(AtomicUsize::new(0));
         let size = Arc::new(AtomicUsize::new(0));
 
@@ -0,0 +1,137 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use zenoh_core::zlock;
+
+/// The statistics of a [`BufferPool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferPoolStats {
+    /// The maximum amount of memory kept by the pool.
+    pub capacity: usize,
+    /// The amount of memory currently kept by the pool.
+    pub size: usize,
+    /// The number of buffers taken from the pool.
+    pub hits: usize,
+    /// The number of buffers allocated because the pool had none of the requested size.
+    pub misses: usize,
+}
+
+#[derive(Default)]
+struct BufferPoolInner {
+    size: usize,
+    // The buffers are kept by length, since the batches and rx buffers of a link have a fixed size
+    buffers: HashMap<usize, Vec<Box<[u8]>>>,
+}
+
+/// A pool of buffers shared by all the links of a transport manager.
+///
+/// The links take their tx batches and rx buffers from the pool when started and give them back
+/// when closed, so that the memory of the closed links is reused by the new ones instead of
+/// being freed and allocated again. The tx batches are also given back to the pool once sent,
+/// as long as it has room for them, and taken from it again when the next batch is needed.
+pub(crate) struct BufferPool {
+    capacity: usize,
+    inner: Mutex<BufferPoolInner>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl BufferPool {
+    pub(crate) fn new(capacity: usize) -> Self {
+        BufferPool {
+            capacity,
+            inner: Mutex::new(BufferPoolInner::default()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Takes a buffer of `len` bytes from the pool, allocating it if the pool has none.
+    pub(crate) fn take(&self, len: usize) -> Box<[u8]> {
+        let buffer = {
+            let mut guard = zlock!(self.inner);
+            let buffer = guard.buffers.get_mut(&len).and_then(|b| b.pop());
+            if buffer.is_some() {
+                guard.size -= len;
+            }
+            buffer
+        };
+        match buffer {
+            Some(buffer) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                buffer
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                zenoh_buffers::vec::uninit(len).into_boxed_slice()
+            }
+        }
+    }
+
+    /// Returns `true` if a buffer of `len` bytes given back to the pool would be kept.
+    pub(crate) fn has_room(&self, len: usize) -> bool {
+        zlock!(self.inner).size + len <= self.capacity
+    }
+
+    /// Gives a buffer back to the pool, which frees it if it would exceed its capacity.
+    pub(crate) fn give(&self, buffer: Box<[u8]>) {
+        let len = buffer.len();
+        let mut guard = zlock!(self.inner);
+        if guard.size + len <= self.capacity {
+            guard.size += len;
+            guard.buffers.entry(len).or_default().push(buffer);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> BufferPoolStats {
+        BufferPoolStats {
+            capacity: self.capacity,
+            size: zlock!(self.inner).size,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_pool() {
+        let pool = BufferPool::new(3 * 1024);
+
+        let a = pool.take(1024);
+        let b = pool.take(1024);
+        assert_eq!(a.len(), 1024);
+        assert_eq!(pool.stats().misses, 2);
+
+        // The buffers are only reused for the same length
+        pool.give(a);
+        pool.give(b);
+        assert_eq!(pool.stats().size, 2048);
+        assert_eq!(pool.take(2048).len(), 2048);
+        assert_eq!(pool.stats().misses, 3);
+        assert_eq!(pool.take(1024).len(), 1024);
+        assert_eq!(pool.stats().hits, 1);
+        assert_eq!(pool.stats().size, 1024);
+
+        // The buffers exceeding the capacity are freed
+        assert!(!pool.has_room(4096));
+        pool.give(pool.take(4096));
+        assert_eq!(pool.stats().size, 1024);
+    }
+}
@@ -66,3 +66,272 @@ macro_rules! stats_struct {
     }
 }
 pub(crate) use stats_struct;
+
+use serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use zenoh_protocol::core::Priority;
+
+/// The number of messages and payload bytes of a kind of zenoh message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MessageStats {
+    pub msgs: usize,
+    pub payload_bytes: usize,
+}
+
+impl AddAssign for MessageStats {
+    fn add_assign(&mut self, other: Self) {
+        self.msgs += other.msgs;
+        self.payload_bytes += other.payload_bytes;
+    }
+}
+
+/// A snapshot of the messages exchanged in one direction of a transport.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DirectionStats {
+    /// The number of transport messages (i.e. frames, keep-alives, ...).
+    pub transport_msgs: usize,
+    /// The number of bytes written to or read from the links.
+    pub bytes: usize,
+    /// The number of zenoh messages.
+    pub zenoh_msgs: usize,
+    pub data: MessageStats,
+    pub data_reply: MessageStats,
+    pub pull_msgs: usize,
+    pub query_msgs: usize,
+    pub declare_msgs: usize,
+    pub linkstate_msgs: usize,
+    pub unit_msgs: usize,
+    pub unit_reply_msgs: usize,
+    /// On transmission, the number of zenoh messages dropped because of congestion or of the
+    /// lack of links. On reception, the number of frames dropped because their sequence number
+    /// was already received, e.g. frames retransmitted over another link.
+    pub dropped: usize,
+}
+
+impl AddAssign for DirectionStats {
+    fn add_assign(&mut self, other: Self) {
+        self.transport_msgs += other.transport_msgs;
+        self.bytes += other.bytes;
+        self.zenoh_msgs += other.zenoh_msgs;
+        self.data += other.data;
+        self.data_reply += other.data_reply;
+        self.pull_msgs += other.pull_msgs;
+        self.query_msgs += other.query_msgs;
+        self.declare_msgs += other.declare_msgs;
+        self.linkstate_msgs += other.linkstate_msgs;
+        self.unit_msgs += other.unit_msgs;
+        self.unit_reply_msgs += other.unit_reply_msgs;
+        self.dropped += other.dropped;
+    }
+}
+
+/// A snapshot of the zenoh messages exchanged on a given [`Priority`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PriorityStats {
+    pub tx_msgs: usize,
+    pub tx_dropped: usize,
+    pub rx_msgs: usize,
+    /// The received messages dropped because the queue of the callback workers was full.
+    pub rx_dropped: usize,
+}
+
+impl AddAssign for PriorityStats {
+    fn add_assign(&mut self, other: Self) {
+        self.tx_msgs += other.tx_msgs;
+        self.tx_dropped += other.tx_dropped;
+        self.rx_msgs += other.rx_msgs;
+        self.rx_dropped += other.rx_dropped;
+    }
+}
+
+/// A snapshot of the protocol anomalies of the remote peers of a transport.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AnomalyStats {
+    /// The frames received with a sequence number out of the expected window.
+    pub invalid_sn: usize,
+    /// The fragments that could not be reassembled.
+    pub defrag_errors: usize,
+    /// The frames received on a priority without a conduit.
+    pub unknown_conduit: usize,
+    /// The Close messages received with an unexpected peer id.
+    pub invalid_close: usize,
+    /// The transport messages not expected on an established transport.
+    pub unexpected_msgs: usize,
+    /// The batches that could not be decoded.
+    pub decode_errors: usize,
+    /// The times the decoding of a link was resynchronized after an error.
+    pub resyncs: usize,
+    /// The messages received from unauthorized peers.
+    pub unauthorized: usize,
+}
+
+impl AddAssign for AnomalyStats {
+    fn add_assign(&mut self, other: Self) {
+        self.invalid_sn += other.invalid_sn;
+        self.defrag_errors += other.defrag_errors;
+        self.unknown_conduit += other.unknown_conduit;
+        self.invalid_close += other.invalid_close;
+        self.unexpected_msgs += other.unexpected_msgs;
+        self.decode_errors += other.decode_errors;
+        self.resyncs += other.resyncs;
+        self.unauthorized += other.unauthorized;
+    }
+}
+
+/// A typed snapshot of the statistics of a transport, or of the sum of several transports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TransportStats {
+    pub tx: DirectionStats,
+    pub rx: DirectionStats,
+    pub anomalies: AnomalyStats,
+    /// The statistics of each priority, indexed by `Priority as usize`.
+    pub priorities: [PriorityStats; Priority::NUM],
+}
+
+impl TransportStats {
+    pub fn priority(&self, priority: Priority) -> &PriorityStats {
+        &self.priorities[priority as usize]
+    }
+}
+
+impl AddAssign for TransportStats {
+    fn add_assign(&mut self, other: Self) {
+        self.tx += other.tx;
+        self.rx += other.rx;
+        self.anomalies += other.anomalies;
+        for (p, o) in self.priorities.iter_mut().zip(other.priorities) {
+            *p += o;
+        }
+    }
+}
+
+/// Builds a [`TransportStats`] from the snapshots of the counters of a transport, mapping
+/// each [`AnomalyStats`] field listed after the priorities to the counter that tracks it.
+macro_rules! transport_stats {
+    ($stats:expr, $priorities:expr $(, $anomaly:ident: $counter:ident)* $(,)?) => {{
+        let s = $stats;
+        $crate::common::stats::TransportStats {
+            tx: $crate::common::stats::DirectionStats {
+                transport_msgs: s.tx_t_msgs,
+                bytes: s.tx_bytes,
+                zenoh_msgs: s.tx_z_msgs,
+                data: $crate::common::stats::MessageStats {
+                    msgs: s.tx_z_data_msgs,
+                    payload_bytes: s.tx_z_data_payload_bytes,
+                },
+                data_reply: $crate::common::stats::MessageStats {
+                    msgs: s.tx_z_data_reply_msgs,
+                    payload_bytes: s.tx_z_data_reply_payload_bytes,
+                },
+                pull_msgs: s.tx_z_pull_msgs,
+                query_msgs: s.tx_z_query_msgs,
+                declare_msgs: s.tx_z_declare_msgs,
+                linkstate_msgs: s.tx_z_linkstate_msgs,
+                unit_msgs: s.tx_z_unit_msgs,
+                unit_reply_msgs: s.tx_z_unit_reply_msgs,
+                dropped: s.tx_z_dropped,
+            },
+            rx: $crate::common::stats::DirectionStats {
+                transport_msgs: s.rx_t_msgs,
+                bytes: s.rx_bytes,
+                zenoh_msgs: s.rx_z_msgs,
+                data: $crate::common::stats::MessageStats {
+                    msgs: s.rx_z_data_msgs,
+                    payload_bytes: s.rx_z_data_payload_bytes,
+                },
+                data_reply: $crate::common::stats::MessageStats {
+                    msgs: s.rx_z_data_reply_msgs,
+                    payload_bytes: s.rx_z_data_reply_payload_bytes,
+                },
+                pull_msgs: s.rx_z_pull_msgs,
+                query_msgs: s.rx_z_query_msgs,
+                declare_msgs: s.rx_z_declare_msgs,
+                linkstate_msgs: s.rx_z_linkstate_msgs,
+                unit_msgs: s.rx_z_unit_msgs,
+                unit_reply_msgs: s.rx_z_unit_reply_msgs,
+                dropped: s.rx_t_dropped,
+            },
+            anomalies: $crate::common::stats::AnomalyStats {
+                $($anomaly: s.$counter,)*
+                ..Default::default()
+            },
+            priorities: $priorities,
+        }
+    }};
+}
+pub(crate) use transport_stats;
+
+/// The per-priority counters of a transport.
+#[derive(Default)]
+pub(crate) struct PriorityStatsAtomic {
+    tx_msgs: [AtomicUsize; Priority::NUM],
+    tx_dropped: [AtomicUsize; Priority::NUM],
+    rx_msgs: [AtomicUsize; Priority::NUM],
+    rx_dropped: [AtomicUsize; Priority::NUM],
+}
+
+impl PriorityStatsAtomic {
+    pub(crate) fn snapshot(&self) -> [PriorityStats; Priority::NUM] {
+        let mut stats = [PriorityStats::default(); Priority::NUM];
+        for (i, s) in stats.iter_mut().enumerate() {
+            s.tx_msgs = self.tx_msgs[i].load(Ordering::Relaxed);
+            s.tx_dropped = self.tx_dropped[i].load(Ordering::Relaxed);
+            s.rx_msgs = self.rx_msgs[i].load(Ordering::Relaxed);
+            s.rx_dropped = self.rx_dropped[i].load(Ordering::Relaxed);
+        }
+        stats
+    }
+
+    pub(crate) fn inc_tx_msgs(&self, priority: Priority) {
+        self.tx_msgs[priority as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_tx_dropped(&self, priority: Priority) {
+        self.tx_dropped[priority as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_rx_msgs(&self, priority: Priority) {
+        self.rx_msgs[priority as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_rx_dropped(&self, priority: Priority) {
+        self.rx_dropped[priority as usize].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transport_stats_sum() {
+        let priorities = PriorityStatsAtomic::default();
+        priorities.inc_tx_msgs(Priority::Data);
+        priorities.inc_tx_dropped(Priority::Data);
+        priorities.inc_rx_msgs(Priority::Control);
+        priorities.inc_rx_dropped(Priority::Control);
+
+        let mut stats = TransportStats {
+            priorities: priorities.snapshot(),
+            ..Default::default()
+        };
+        stats.tx.data.msgs = 1;
+        stats.rx.dropped = 2;
+        stats.anomalies.resyncs = 3;
+        let mut sum = stats;
+        sum += stats;
+
+        assert_eq!(sum.tx.data.msgs, 2);
+        assert_eq!(sum.rx.dropped, 4);
+        assert_eq!(sum.anomalies.resyncs, 6);
+        assert_eq!(sum.priority(Priority::Data).tx_msgs, 2);
+        assert_eq!(sum.priority(Priority::Data).tx_dropped, 2);
+        assert_eq!(sum.priority(Priority::Control).rx_msgs, 2);
+        assert_eq!(sum.priority(Priority::Control).rx_dropped, 2);
+        assert_eq!(
+            sum.priority(Priority::Background),
+            &PriorityStats::default()
+        );
+    }
+}
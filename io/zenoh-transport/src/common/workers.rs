@@ -0,0 +1,256 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use flume::r#async::SendFut;
+use futures::FutureExt;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::task::{Context, Poll};
+use zenoh_protocol::core::Priority;
+use zenoh_result::ZResult;
+use zenoh_util::sched::ThreadSchedule;
+
+// The number of callbacks queued per worker and priority before the rx tasks wait for it,
+// or drop the messages that can be dropped
+const QUEUE_SIZE: usize = 256;
+
+pub(crate) type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// The outcome of [`CallbackWorkers::dispatch`].
+pub(crate) enum Dispatch {
+    Queued,
+    Dropped,
+    /// The queue is full and the job waits for the worker, ahead of the jobs dispatched after it.
+    Waiting(SendFut<'static, Job>),
+}
+
+/// A pool of threads running the callbacks of the received messages, so that a slow callback
+/// does not block the rx task of the link.
+///
+/// The callbacks of a given key (e.g. the zid of the remote peer) always run on the same worker,
+/// preserving the order of the messages received from that peer on a given priority.
+/// Each worker has a bounded queue per priority and runs the callbacks of the highest priorities
+/// first, so that an overloaded worker delays the low priority messages before the others.
+pub(crate) struct CallbackWorkers {
+    // The queues of each worker, indexed by `Priority as usize`
+    senders: Vec<Vec<flume::Sender<Job>>>,
+}
+
+impl CallbackWorkers {
+    pub(crate) fn new(workers: usize, schedule: &ThreadSchedule) -> Self {
+        let senders = (0..workers)
+            .map(|i| {
+                let (senders, receivers): (Vec<_>, Vec<_>) = (0..Priority::NUM)
+                    .map(|_| flume::bounded::<Job>(QUEUE_SIZE))
+                    .unzip();
+                let schedule = schedule.clone();
+                // The worker stops once the transport manager, hence the senders, are dropped
+                std::thread::Builder::new()
+                    .name(format!("zenoh-rx-cb-{i}"))
+                    .spawn(move || {
+                        if let Err(e) = schedule.apply() {
+                            log::error!("Unable to schedule the rx callback worker: {}", e);
+                        }
+                        while let Some(job) = next_job(&receivers) {
+                            job();
+                        }
+                    })
+                    .expect("Unable to spawn rx callback worker");
+                senders
+            })
+            .collect();
+        CallbackWorkers { senders }
+    }
+
+    /// Queues `job` on the worker of `key` without blocking.
+    ///
+    /// When the queue of `priority` is full, a `droppable` job is dropped, otherwise it takes
+    /// its turn among the jobs waiting for the worker and the caller has to await it, applying
+    /// the same backpressure on the link as an inline callback.
+    pub(crate) fn dispatch<K: Hash>(
+        &self,
+        key: &K,
+        priority: Priority,
+        droppable: bool,
+        job: Job,
+    ) -> Dispatch {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let index = hasher.finish() as usize % self.senders.len();
+        let sender = &self.senders[index][priority as usize];
+        if droppable {
+            match sender.try_send(job) {
+                Err(flume::TrySendError::Full(_)) => Dispatch::Dropped,
+                _ => Dispatch::Queued,
+            }
+        } else {
+            // Polling the send once registers it in the queue of the waiting senders, that the
+            // worker serves in order, and the waker is replaced by the one of the awaiting task
+            let mut send = sender.clone().into_send_async(job);
+            let waker = futures::task::noop_waker_ref();
+            match send.poll_unpin(&mut Context::from_waker(waker)) {
+                Poll::Ready(_) => Dispatch::Queued,
+                Poll::Pending => Dispatch::Waiting(send),
+            }
+        }
+    }
+}
+
+type InlineCallback = Box<dyn FnOnce() -> ZResult<()> + Send + 'static>;
+
+/// The callbacks of the messages handled by an rx task, that it runs or waits for once it has
+/// released the conduits of the messages.
+#[derive(Default)]
+pub(crate) struct RxCallbacks {
+    waiting: Vec<SendFut<'static, Job>>,
+    inline: Vec<InlineCallback>,
+}
+
+impl RxCallbacks {
+    pub(crate) fn wait(&mut self, send: SendFut<'static, Job>) {
+        self.waiting.push(send);
+    }
+
+    pub(crate) fn inline(&mut self, callback: InlineCallback) {
+        self.inline.push(callback);
+    }
+
+    /// Waits for the workers to accept the queued callbacks, then runs the inline ones.
+    pub(crate) async fn run(&mut self) -> ZResult<()> {
+        for send in self.waiting.drain(..) {
+            let _ = send.await;
+        }
+        for callback in self.inline.drain(..) {
+            callback()?;
+        }
+        Ok(())
+    }
+}
+
+// Returns the next job by decreasing priority, waiting for one if all the queues are empty
+fn next_job(receivers: &[flume::Receiver<Job>]) -> Option<Job> {
+    if let Some(job) = receivers.iter().find_map(|r| r.try_recv().ok()) {
+        return Some(job);
+    }
+    receivers
+        .iter()
+        .fold(flume::Selector::new(), |s, r| s.recv(r, |res| res.ok()))
+        .wait()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn callback_workers() {
+        let workers = CallbackWorkers::new(4, &ThreadSchedule::default());
+        let received = Arc::new(Mutex::new(vec![]));
+        for i in 0..100 {
+            let received = received.clone();
+            workers.dispatch(
+                &"peer",
+                Priority::Data,
+                false,
+                Box::new(move || received.lock().unwrap().push(i)),
+            );
+        }
+        let (sender, receiver) = flume::bounded(1);
+        workers.dispatch(
+            &"peer",
+            Priority::Data,
+            false,
+            Box::new(move || {
+                let _ = sender.send(());
+            }),
+        );
+        receiver.recv().unwrap();
+        // The callbacks of a same key and priority run in order
+        assert_eq!(*received.lock().unwrap(), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn callback_workers_drop() {
+        let workers = CallbackWorkers::new(1, &ThreadSchedule::default());
+        // Block the worker until all the jobs are queued
+        let (unblock, blocked) = flume::bounded::<()>(1);
+        workers.dispatch(
+            &"peer",
+            Priority::Control,
+            false,
+            Box::new(move || {
+                let _ = blocked.recv();
+            }),
+        );
+        let queued = (0..2 * QUEUE_SIZE)
+            .filter(|_| {
+                matches!(
+                    workers.dispatch(&"peer", Priority::Data, true, Box::new(|| {})),
+                    Dispatch::Queued
+                )
+            })
+            .count();
+        // The worker may have taken a job off the queue before blocking
+        assert!(queued == QUEUE_SIZE || queued == QUEUE_SIZE + 1);
+        // The other priorities have their own queue
+        assert!(matches!(
+            workers.dispatch(&"peer", Priority::RealTime, true, Box::new(|| {})),
+            Dispatch::Queued
+        ));
+        unblock.send(()).unwrap();
+    }
+
+    #[test]
+    fn callback_workers_wait() {
+        let workers = CallbackWorkers::new(1, &ThreadSchedule::default());
+        let (unblock, blocked) = flume::bounded::<()>(1);
+        workers.dispatch(
+            &"peer",
+            Priority::Control,
+            false,
+            Box::new(move || {
+                let _ = blocked.recv();
+            }),
+        );
+        // The jobs that do not fit in the queue wait for the worker without blocking the caller
+        let received = Arc::new(Mutex::new(vec![]));
+        let mut callbacks = RxCallbacks::default();
+        for i in 0..2 * QUEUE_SIZE {
+            let received = received.clone();
+            let job = Box::new(move || received.lock().unwrap().push(i));
+            if let Dispatch::Waiting(send) = workers.dispatch(&"peer", Priority::Data, false, job) {
+                callbacks.wait(send);
+            }
+        }
+        assert!(!callbacks.waiting.is_empty());
+        unblock.send(()).unwrap();
+        async_std::task::block_on(callbacks.run()).unwrap();
+
+        let (sender, receiver) = flume::bounded(1);
+        workers.dispatch(
+            &"peer",
+            Priority::Data,
+            false,
+            Box::new(move || {
+                let _ = sender.send(());
+            }),
+        );
+        receiver.recv().unwrap();
+        // The waiting jobs run in the order of their dispatch
+        assert_eq!(
+            *received.lock().unwrap(),
+            (0..2 * QUEUE_SIZE).collect::<Vec<_>>()
+        );
+    }
+}
@@ -17,23 +17,37 @@
 //! This crate is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
+pub mod capture;
 mod common;
 mod manager;
+pub mod memory;
 mod multicast;
+pub mod pacing;
 mod primitives;
 #[cfg(feature = "shared-memory")]
 mod shm;
+pub mod slow_consumer;
 pub mod unicast;
-
+pub mod watermark;
+
+#[cfg(feature = "test")]
+pub use common::pipeline::BenchPipeline;
+pub use common::pipeline::{count_congestion_drops, with_push_deadline};
+pub use common::pool::BufferPoolStats;
+#[cfg(feature = "stats")]
+pub use common::stats::{
+    AnomalyStats, DirectionStats, MessageStats, PriorityStats, TransportStats,
+};
 pub use manager::*;
 pub use multicast::*;
 pub use primitives::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::sync::Arc;
+use std::time::Duration;
 pub use unicast::*;
-use zenoh_link::Link;
-use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_link::{Link, LinkHandshakeError};
+use zenoh_protocol::core::{EndPoint, Locator, WhatAmI, ZenohId};
 use zenoh_protocol::zenoh::ZenohMessage;
 use zenoh_result::ZResult;
 
@@ -51,6 +65,47 @@ pub trait TransportEventHandler: Send + Sync {
         &self,
         transport: TransportMulticast,
     ) -> ZResult<Arc<dyn TransportMulticastEventHandler>>;
+
+    /// Called when an outgoing connection attempt failed.
+    fn connect_failed(&self, _failure: &ConnectFailure) {}
+}
+
+/// The cause of the failure of an outgoing connection attempt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectFailureReason {
+    /// The remote end couldn't be reached, e.g. no node is listening on the endpoint.
+    Unreachable,
+    /// The remote end was reached but the security handshake of the link (e.g. TLS) failed.
+    LinkHandshake,
+    /// The link was opened but the session establishment failed, e.g. the remote node refused
+    /// or failed to authenticate the session.
+    Establishment,
+    /// The attempt didn't complete in time.
+    Timeout,
+}
+
+impl ConnectFailureReason {
+    /// Returns the reason of the failure of an attempt returning `e`.
+    pub fn of(e: &zenoh_result::Error) -> Self {
+        if e.is::<LinkHandshakeError>() {
+            ConnectFailureReason::LinkHandshake
+        } else if e.is::<unicast::establishment::EstablishmentError>() {
+            ConnectFailureReason::Establishment
+        } else {
+            ConnectFailureReason::Unreachable
+        }
+    }
+}
+
+/// A failed outgoing connection attempt.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectFailure {
+    pub endpoint: EndPoint,
+    pub reason: ConnectFailureReason,
+    pub error: String,
+    /// The delay before the next attempt, if the connection is retried.
+    pub retry_in: Option<Duration>,
 }
 
 #[derive(Default)]
@@ -119,6 +174,9 @@ pub trait TransportPeerEventHandler: Send + Sync {
     fn closing(&self);
     fn closed(&self);
     fn as_any(&self) -> &dyn Any;
+    /// Called before [`closing`](Self::closing) when the remote peer closed the transport
+    /// suggesting a locator to reconnect to.
+    fn migrate(&self, _locator: Locator) {}
 }
 
 // Define an empty TransportCallback for the listener transport
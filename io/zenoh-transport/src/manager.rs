@@ -11,14 +11,22 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::common::pool::{BufferPool, BufferPoolStats};
+#[cfg(feature = "stats")]
+use super::common::stats::TransportStats;
+use super::common::workers::CallbackWorkers;
+use super::memory::MemoryBudget;
 use super::multicast::manager::{
     TransportManagerBuilderMulticast, TransportManagerConfigMulticast,
     TransportManagerStateMulticast,
 };
+use super::pacing::QueuePacing;
+use super::slow_consumer::SlowConsumerEvents;
 use super::unicast::manager::{
     TransportManagerBuilderUnicast, TransportManagerConfigUnicast, TransportManagerStateUnicast,
 };
 use super::unicast::TransportUnicast;
+use super::watermark::{QueueWatermarks, WatermarkEvents};
 use super::TransportEventHandler;
 use async_std::sync::Mutex as AsyncMutex;
 use rand::{RngCore, SeedableRng};
@@ -28,7 +36,10 @@ use std::sync::Arc;
 use std::sync::RwLock;
 use std::time::Duration;
 use zenoh_cfg_properties::{config::*, Properties};
-use zenoh_config::{Config, QueueConf, QueueSizeConf};
+use zenoh_config::{Config, LinkPoolConf, LinkRxConf, LinkTxConf, QueueConf, QueueSizeConf};
+use zenoh_core::executor::{self, JoinHandle};
+#[cfg(feature = "stats")]
+use zenoh_core::zlock;
 use zenoh_core::zparse;
 use zenoh_crypto::{BlockCipher, PseudoRng};
 use zenoh_link::NewLinkChannelSender;
@@ -39,6 +50,7 @@ use zenoh_protocol::{
 use zenoh_result::{bail, ZResult};
 #[cfg(feature = "shared-memory")]
 use zenoh_shm::SharedMemoryReader;
+use zenoh_util::sched::ThreadSchedule;
 
 /// # Examples
 /// ```
@@ -97,15 +109,23 @@ pub struct TransportManagerConfig {
     pub whatami: WhatAmI,
     pub sn_resolution: ZInt,
     pub batch_size: u16,
+    pub max_message_size: usize,
     pub queue_size: [usize; Priority::NUM],
     pub queue_backoff: Duration,
+    pub queue_watermarks: Option<QueueWatermarks>,
+    pub queue_pacing: Option<QueuePacing>,
+    pub memory: Arc<MemoryBudget>,
     pub defrag_buff_size: usize,
     pub link_rx_buffer_size: usize,
+    pub buffer_pool_size: usize,
+    pub rx_callback_workers: usize,
     pub unicast: TransportManagerConfigUnicast,
     pub multicast: TransportManagerConfigMulticast,
     pub endpoint: HashMap<String, Properties>,
     pub handler: Arc<dyn TransportEventHandler>,
     pub tx_threads: usize,
+    pub tx_schedule: ThreadSchedule,
+    pub rx_callback_schedule: ThreadSchedule,
     pub protocols: Vec<String>,
 }
 
@@ -125,15 +145,24 @@ pub struct TransportManagerBuilder {
     whatami: WhatAmI,
     sn_resolution: ZInt,
     batch_size: u16,
+    max_message_size: usize,
     queue_size: QueueSizeConf,
     queue_backoff: Duration,
+    queue_watermarks: Option<QueueWatermarks>,
+    queue_pacing: Option<QueuePacing>,
+    memory: Arc<MemoryBudget>,
     defrag_buff_size: usize,
     link_rx_buffer_size: usize,
+    buffer_pool_size: usize,
+    rx_callback_workers: usize,
     unicast: TransportManagerBuilderUnicast,
     multicast: TransportManagerBuilderMulticast,
     endpoint: HashMap<String, Properties>,
     tx_threads: usize,
+    tx_schedule: ThreadSchedule,
+    rx_callback_schedule: ThreadSchedule,
     protocols: Option<Vec<String>>,
+    denied_protocols: Vec<String>,
 }
 
 impl TransportManagerBuilder {
@@ -157,6 +186,11 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
     pub fn queue_size(mut self, queue_size: QueueSizeConf) -> Self {
         self.queue_size = queue_size;
         self
@@ -167,6 +201,21 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn queue_watermarks(mut self, queue_watermarks: Option<QueueWatermarks>) -> Self {
+        self.queue_watermarks = queue_watermarks;
+        self
+    }
+
+    pub fn queue_pacing(mut self, queue_pacing: Option<QueuePacing>) -> Self {
+        self.queue_pacing = queue_pacing;
+        self
+    }
+
+    pub fn memory_budget(mut self, memory: Arc<MemoryBudget>) -> Self {
+        self.memory = memory;
+        self
+    }
+
     pub fn defrag_buff_size(mut self, defrag_buff_size: usize) -> Self {
         self.defrag_buff_size = defrag_buff_size;
         self
@@ -177,6 +226,16 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn buffer_pool_size(mut self, buffer_pool_size: usize) -> Self {
+        self.buffer_pool_size = buffer_pool_size;
+        self
+    }
+
+    pub fn rx_callback_workers(mut self, rx_callback_workers: usize) -> Self {
+        self.rx_callback_workers = rx_callback_workers;
+        self
+    }
+
     pub fn endpoint(mut self, endpoint: HashMap<String, Properties>) -> Self {
         self.endpoint = endpoint;
         self
@@ -197,11 +256,26 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn tx_schedule(mut self, schedule: ThreadSchedule) -> Self {
+        self.tx_schedule = schedule;
+        self
+    }
+
+    pub fn rx_callback_schedule(mut self, schedule: ThreadSchedule) -> Self {
+        self.rx_callback_schedule = schedule;
+        self
+    }
+
     pub fn protocols(mut self, protocols: Option<Vec<String>>) -> Self {
         self.protocols = protocols;
         self
     }
 
+    pub fn denied_protocols(mut self, protocols: Vec<String>) -> Self {
+        self.denied_protocols = protocols;
+        self
+    }
+
     pub async fn from_config(mut self, config: &Config) -> ZResult<TransportManagerBuilder> {
         self = self.zid(*config.id());
         if let Some(v) = config.mode() {
@@ -217,11 +291,65 @@ impl TransportManagerBuilder {
                 .unwrap(),
         );
         self = self.batch_size(config.transport().link().tx().batch_size().unwrap());
+        self = self.max_message_size(config.transport().link().tx().max_message_size().unwrap());
         self = self.defrag_buff_size(config.transport().link().rx().max_message_size().unwrap());
         self = self.link_rx_buffer_size(config.transport().link().rx().buffer_size().unwrap());
+        self = self.buffer_pool_size(config.transport().link().pool().size().unwrap());
+        self = self.rx_callback_workers(config.transport().link().rx().callback_workers().unwrap());
         self = self.queue_size(config.transport().link().tx().queue().size().clone());
+        let watermarks = config.transport().link().tx().queue().watermarks();
+        if let Some(high) = *watermarks.high() {
+            let low = watermarks.low().unwrap_or(high / 2);
+            if low >= high {
+                bail!(
+                    "Invalid tx queue watermarks: the low watermark {} must be lower than the high one {}",
+                    low,
+                    high
+                );
+            }
+            self = self.queue_watermarks(Some(QueueWatermarks {
+                high,
+                low,
+                drop_low_priority: watermarks.drop_low_priority().unwrap_or(false),
+            }));
+        }
+        let pacing = config.transport().link().tx().queue().pacing();
+        let b = pacing.bytes();
+        let mut bytes = [None; Priority::NUM];
+        bytes[Priority::Control as usize] = *b.control();
+        bytes[Priority::RealTime as usize] = *b.real_time();
+        bytes[Priority::InteractiveHigh as usize] = *b.interactive_high();
+        bytes[Priority::InteractiveLow as usize] = *b.interactive_low();
+        bytes[Priority::DataHigh as usize] = *b.data_high();
+        bytes[Priority::Data as usize] = *b.data();
+        bytes[Priority::DataLow as usize] = *b.data_low();
+        bytes[Priority::Background as usize] = *b.background();
+        if bytes.iter().any(Option::is_some) {
+            let interval = pacing.interval().unwrap_or(1_000);
+            if interval == 0 || bytes.contains(&Some(0)) {
+                bail!("Invalid tx queue pacing: the interval and the bytes must be greater than 0");
+            }
+            self = self.queue_pacing(Some(QueuePacing {
+                interval: Duration::from_micros(interval),
+                bytes,
+            }));
+        }
+        self = self.memory_budget(Arc::new(MemoryBudget::from_config(config)?));
         self = self.tx_threads(config.transport().link().tx().threads().unwrap());
+        let tx = config.transport().link().tx();
+        self = self.tx_schedule(ThreadSchedule::new(tx.affinity().clone(), *tx.priority())?);
+        let rx = config.transport().link().rx();
+        self =
+            self.rx_callback_schedule(ThreadSchedule::new(rx.affinity().clone(), *rx.priority())?);
         self = self.protocols(config.transport().link().protocols().clone());
+        self = self.denied_protocols(
+            config
+                .transport()
+                .link()
+                .denied_protocols()
+                .clone()
+                .unwrap_or_default(),
+        );
 
         let (c, errors) = zenoh_link::LinkConfigurator::default()
             .configurations(config)
@@ -269,21 +397,34 @@ impl TransportManagerBuilder {
             whatami: self.whatami,
             sn_resolution: self.sn_resolution,
             batch_size: self.batch_size,
+            max_message_size: self.max_message_size,
             queue_size,
             queue_backoff: self.queue_backoff,
+            queue_watermarks: self.queue_watermarks,
+            queue_pacing: self.queue_pacing,
+            memory: self.memory,
             defrag_buff_size: self.defrag_buff_size,
             link_rx_buffer_size: self.link_rx_buffer_size,
+            buffer_pool_size: self.buffer_pool_size,
+            rx_callback_workers: self.rx_callback_workers,
             unicast: unicast.config,
             multicast: multicast.config,
             endpoint: self.endpoint,
             handler,
             tx_threads: self.tx_threads,
-            protocols: self.protocols.unwrap_or_else(|| {
-                zenoh_link::PROTOCOLS
-                    .iter()
-                    .map(|x| x.to_string())
-                    .collect()
-            }),
+            tx_schedule: self.tx_schedule,
+            rx_callback_schedule: self.rx_callback_schedule,
+            protocols: self
+                .protocols
+                .unwrap_or_else(|| {
+                    zenoh_link::PROTOCOLS
+                        .iter()
+                        .map(|x| x.to_string())
+                        .collect()
+                })
+                .into_iter()
+                .filter(|p| !self.denied_protocols.contains(p))
+                .collect(),
         };
 
         let state = TransportManagerState {
@@ -307,35 +448,61 @@ impl Default for TransportManagerBuilder {
             whatami: ZN_MODE_DEFAULT.parse().unwrap(),
             sn_resolution: SEQ_NUM_RES,
             batch_size: BATCH_SIZE,
+            max_message_size: LinkTxConf::default().max_message_size().unwrap(),
             queue_size: queue.size,
             queue_backoff: Duration::from_nanos(backoff),
+            queue_watermarks: None,
+            queue_pacing: None,
+            memory: Arc::new(MemoryBudget::default()),
             defrag_buff_size: zparse!(ZN_DEFRAG_BUFF_SIZE_DEFAULT).unwrap(),
             link_rx_buffer_size: zparse!(ZN_LINK_RX_BUFF_SIZE_DEFAULT).unwrap(),
+            buffer_pool_size: LinkPoolConf::default().size().unwrap(),
+            rx_callback_workers: LinkRxConf::default().callback_workers().unwrap(),
             endpoint: HashMap::new(),
             unicast: TransportManagerBuilderUnicast::default(),
             multicast: TransportManagerBuilderMulticast::default(),
             tx_threads: 1,
+            tx_schedule: ThreadSchedule::default(),
+            rx_callback_schedule: ThreadSchedule::default(),
             protocols: None,
+            denied_protocols: vec![],
         }
     }
 }
 
 #[derive(Clone)]
 pub(crate) struct TransportExecutor {
-    executor: Arc<async_executor::Executor<'static>>,
+    // The dedicated threads are used along with the global executor, or to apply the schedule of
+    // the tx threads which cannot be applied to the threads of the process executor
+    executor: Option<Arc<async_executor::Executor<'static>>>,
     sender: async_std::channel::Sender<()>,
 }
 
 impl TransportExecutor {
-    fn new(num_threads: usize) -> Self {
+    fn new(num_threads: usize, schedule: &ThreadSchedule) -> Self {
         let (sender, receiver) = async_std::channel::bounded(1);
+        if !executor::is_global() && schedule.is_default() {
+            return Self {
+                executor: None,
+                sender,
+            };
+        }
         let executor = Arc::new(async_executor::Executor::new());
         for _ in 0..num_threads {
             let exec = executor.clone();
             let recv = receiver.clone();
-            std::thread::spawn(move || async_std::task::block_on(exec.run(recv.recv())));
+            let schedule = schedule.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = schedule.apply() {
+                    log::error!("Unable to schedule the tx thread: {}", e);
+                }
+                async_std::task::block_on(exec.run(recv.recv()))
+            });
+        }
+        Self {
+            executor: Some(executor),
+            sender,
         }
-        Self { executor, sender }
     }
 
     async fn stop(&self) {
@@ -345,8 +512,11 @@ impl TransportExecutor {
     pub(crate) fn spawn<T: Send + 'static>(
         &self,
         future: impl core::future::Future<Output = T> + Send + 'static,
-    ) -> async_executor::Task<T> {
-        self.executor.spawn(future)
+    ) -> JoinHandle<T> {
+        match self.executor.as_ref() {
+            Some(exec) => executor::spawn_on(&**exec, future),
+            None => executor::spawn(future),
+        }
     }
 }
 
@@ -361,6 +531,13 @@ pub struct TransportManager {
     pub(crate) locator_inspector: zenoh_link::LocatorInspector,
     pub(crate) new_unicast_link_sender: NewLinkChannelSender,
     pub(crate) tx_executor: TransportExecutor,
+    pub(crate) buffer_pool: Arc<BufferPool>,
+    pub(crate) rx_workers: Option<Arc<CallbackWorkers>>,
+    pub(crate) slow_consumers: Arc<SlowConsumerEvents>,
+    pub(crate) watermarks: Arc<WatermarkEvents>,
+    // The statistics of the transports removed from the manager
+    #[cfg(feature = "stats")]
+    pub(crate) closed_stats: Arc<std::sync::Mutex<TransportStats>>,
 }
 
 impl TransportManager {
@@ -375,6 +552,15 @@ impl TransportManager {
         let (new_unicast_link_sender, new_unicast_link_receiver) = flume::unbounded();
 
         let tx_threads = params.config.tx_threads;
+        let buffer_pool = Arc::new(BufferPool::new(params.config.buffer_pool_size));
+        // The schedule can only be applied to the callbacks run by the workers, one per core by default
+        let schedule = &params.config.rx_callback_schedule;
+        let rx_callback_workers = match params.config.rx_callback_workers {
+            0 if !schedule.is_default() => schedule.affinity.len().max(1),
+            workers => workers,
+        };
+        let rx_workers = (rx_callback_workers > 0)
+            .then(|| Arc::new(CallbackWorkers::new(rx_callback_workers, schedule)));
         let this = TransportManager {
             config: Arc::new(params.config),
             state: Arc::new(params.state),
@@ -384,11 +570,17 @@ impl TransportManager {
             shmr: Arc::new(RwLock::new(SharedMemoryReader::new())),
             locator_inspector: Default::default(),
             new_unicast_link_sender,
-            tx_executor: TransportExecutor::new(tx_threads),
+            tx_executor: TransportExecutor::new(tx_threads, &params.config.tx_schedule),
+            buffer_pool,
+            rx_workers,
+            slow_consumers: Arc::new(SlowConsumerEvents::default()),
+            watermarks: Arc::new(WatermarkEvents::default()),
+            #[cfg(feature = "stats")]
+            closed_stats: Arc::new(std::sync::Mutex::new(TransportStats::default())),
         };
 
         // @TODO: this should be moved into the unicast module
-        async_std::task::spawn({
+        executor::spawn({
             let this = this.clone();
             async move {
                 while let Ok(link) = new_unicast_link_receiver.recv_async().await {
@@ -408,6 +600,39 @@ impl TransportManager {
         self.config.zid
     }
 
+    /// The statistics of the pool of tx batches and rx buffers shared by all the links.
+    pub fn get_buffer_pool_stats(&self) -> BufferPoolStats {
+        self.buffer_pool.stats()
+    }
+
+    /// The slow consumer events of the transports of this manager, shared with the sessions using it.
+    pub fn slow_consumers(&self) -> &Arc<SlowConsumerEvents> {
+        &self.slow_consumers
+    }
+
+    /// The watermark events of the tx queues of the links of this manager.
+    pub fn watermarks(&self) -> &Arc<WatermarkEvents> {
+        &self.watermarks
+    }
+
+    /// A snapshot of the statistics summed over all the transports of this manager, including the
+    /// closed ones: the counters never decrease when a transport is closed.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> TransportStats {
+        // The transports are accounted in `closed_stats` while still locked, never twice nor missed
+        let unicast = zlock!(self.state.unicast.transports);
+        let multicast = zlock!(self.state.multicast.transports);
+        let mut stats = *zlock!(self.closed_stats);
+        for s in unicast
+            .values()
+            .map(|t| t.transport_stats())
+            .chain(multicast.values().map(|t| t.transport_stats()))
+        {
+            stats += s;
+        }
+        stats
+    }
+
     pub async fn close(&self) {
         log::trace!("TransportManager::clear())");
         self.close_unicast().await;
@@ -420,12 +645,7 @@ impl TransportManager {
     /*************************************/
     pub async fn add_listener(&self, endpoint: EndPoint) -> ZResult<Locator> {
         let p = endpoint.protocol();
-        if !self
-            .config
-            .protocols
-            .iter()
-            .any(|x| x.as_str() == p.as_str())
-        {
+        if !self.is_protocol_allowed(p.as_str()) {
             bail!(
                 "Unsupported protocol: {}. Supported protocols are: {:?}",
                 p,
@@ -468,6 +688,12 @@ impl TransportManager {
         // @TODO: multicast
     }
 
+    /// Returns `true` if the link `protocol` may be used for listening and opening transports,
+    /// according to the `transport/link/protocols` configuration.
+    pub fn is_protocol_allowed(&self, protocol: &str) -> bool {
+        self.config.protocols.iter().any(|p| p == protocol)
+    }
+
     /*************************************/
     /*             TRANSPORT             */
     /*************************************/
@@ -483,12 +709,7 @@ impl TransportManager {
 
     pub async fn open_transport(&self, endpoint: EndPoint) -> ZResult<TransportUnicast> {
         let p = endpoint.protocol();
-        if !self
-            .config
-            .protocols
-            .iter()
-            .any(|x| x.as_str() == p.as_str())
-        {
+        if !self.is_protocol_allowed(p.as_str()) {
             bail!(
                 "Unsupported protocol: {}. Supported protocols are: {:?}",
                 p,
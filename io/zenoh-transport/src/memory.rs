@@ -0,0 +1,328 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The memory budget of a runtime: the accounting of the memory used by its defragmentation
+//! buffers, tx queues and routing tables, and the policy applied when approaching its limit.
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use zenoh_config::Config;
+use zenoh_core::zlock;
+use zenoh_result::{bail, ZResult};
+
+/// The subsystems whose memory is accounted in a [`MemoryBudget`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemorySubsystem {
+    /// The buffers of the fragmented messages being reassembled.
+    Defrag = 0,
+    /// The batches of the tx queues of the links.
+    TxQueues = 1,
+    /// The resources and declarations of the routing tables.
+    Routing = 2,
+}
+
+impl MemorySubsystem {
+    pub const NUM: usize = 3;
+}
+
+/// The actions taken while the memory used is above the threshold of the budget.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryPolicy {
+    /// The new sessions are rejected.
+    pub reject_sessions: bool,
+    /// The best effort messages are dropped instead of being sent.
+    pub drop_best_effort: bool,
+    /// The declarations of subscribers and queryables of the remote nodes are deferred until the
+    /// memory used gets back below the threshold.
+    pub shed_declarations: bool,
+}
+
+/// The memory used, in bytes, by each subsystem of a [`MemoryBudget`].
+#[derive(Clone, Debug, Serialize)]
+pub struct MemoryUsage {
+    pub limit: Option<usize>,
+    pub threshold: Option<usize>,
+    pub used: usize,
+    pub defrag: usize,
+    pub tx_queues: usize,
+    pub routing: usize,
+    pub policy: MemoryPolicy,
+}
+
+/// The memory budget of a runtime.
+///
+/// The memory is accounted per subsystem, the reservations above the limit are refused, and the
+/// [`MemoryPolicy`] applies while the memory used is above the threshold.
+#[derive(Debug)]
+pub struct MemoryBudget {
+    limit: Option<usize>,
+    threshold: usize,
+    policy: MemoryPolicy,
+    used: [AtomicUsize; MemorySubsystem::NUM],
+    total: AtomicUsize,
+    near_limit: AtomicBool,
+    recoveries: Mutex<Vec<flume::Sender<()>>>,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        MemoryBudget::new(None, 100, MemoryPolicy::default())
+    }
+}
+
+impl MemoryBudget {
+    /// Creates a budget of `limit` bytes, unlimited if `None`, whose policy applies from
+    /// `threshold` percent of the limit.
+    pub fn new(limit: Option<usize>, threshold: u8, policy: MemoryPolicy) -> Self {
+        MemoryBudget {
+            limit,
+            threshold: limit.map_or(usize::MAX, |l| l / 100 * threshold as usize),
+            policy,
+            used: Default::default(),
+            total: AtomicUsize::new(0),
+            near_limit: AtomicBool::new(false),
+            recoveries: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> ZResult<Self> {
+        let c = config.memory();
+        let threshold = c.threshold().unwrap_or(90);
+        if threshold > 100 {
+            bail!(
+                "Invalid memory threshold {}%: must be at most 100%",
+                threshold
+            );
+        }
+        let policy = MemoryPolicy {
+            reject_sessions: c.policy().reject_sessions().unwrap_or(true),
+            drop_best_effort: c.policy().drop_best_effort().unwrap_or(true),
+            shed_declarations: c.policy().shed_declarations().unwrap_or(false),
+        };
+        Ok(MemoryBudget::new(*c.limit(), threshold, policy))
+    }
+
+    /// The memory used by all the subsystems.
+    pub fn used(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Accounts `bytes` used by `subsystem`, even if it exceeds the limit.
+    pub fn reserve(&self, subsystem: MemorySubsystem, bytes: usize) {
+        self.total.fetch_add(bytes, Ordering::Relaxed);
+        self.used[subsystem as usize].fetch_add(bytes, Ordering::Relaxed);
+        self.update();
+    }
+
+    /// Accounts `bytes` used by `subsystem` unless it exceeds the limit.
+    /// Returns `false` if the reservation is refused.
+    pub fn try_reserve(&self, subsystem: MemorySubsystem, bytes: usize) -> bool {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        // The limit is checked and the memory reserved at once, the concurrent reservations
+        // can't exceed it together
+        if self
+            .total
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(bytes).filter(|used| *used <= limit)
+            })
+            .is_err()
+        {
+            return false;
+        }
+        self.used[subsystem as usize].fetch_add(bytes, Ordering::Relaxed);
+        self.update();
+        true
+    }
+
+    /// Releases `bytes` previously reserved by `subsystem`.
+    pub fn release(&self, subsystem: MemorySubsystem, bytes: usize) {
+        self.used[subsystem as usize].fetch_sub(bytes, Ordering::Relaxed);
+        self.total.fetch_sub(bytes, Ordering::Relaxed);
+        self.update();
+    }
+
+    /// Returns a channel notified each time the memory used gets back below the threshold.
+    pub fn recoveries(&self) -> flume::Receiver<()> {
+        let (sender, receiver) = flume::bounded(1);
+        zlock!(self.recoveries).push(sender);
+        receiver
+    }
+
+    // Logs the crossings of the threshold
+    fn update(&self) {
+        if self.limit.is_none() {
+            return;
+        }
+        let used = self.used();
+        let near_limit = used >= self.threshold;
+        if self.near_limit.swap(near_limit, Ordering::Relaxed) != near_limit {
+            if near_limit {
+                log::warn!(
+                    "Memory used ({} bytes) above the threshold of the budget ({} bytes), applying {:?}",
+                    used,
+                    self.threshold,
+                    self.policy
+                );
+            } else {
+                log::info!("Memory used ({} bytes) back below the threshold", used);
+                // A pending notification is enough, the receivers gone are forgotten
+                zlock!(self.recoveries).retain(|sender| {
+                    !matches!(
+                        sender.try_send(()),
+                        Err(flume::TrySendError::Disconnected(_))
+                    )
+                });
+            }
+        }
+    }
+
+    /// Returns `true` if the memory used is above the threshold.
+    #[inline]
+    pub fn is_near_limit(&self) -> bool {
+        self.near_limit.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn rejects_sessions(&self) -> bool {
+        self.policy.reject_sessions && self.is_near_limit()
+    }
+
+    #[inline]
+    pub fn drops_best_effort(&self) -> bool {
+        self.policy.drop_best_effort && self.is_near_limit()
+    }
+
+    #[inline]
+    pub fn sheds_declarations(&self) -> bool {
+        self.policy.shed_declarations && self.is_near_limit()
+    }
+
+    pub fn usage(&self) -> MemoryUsage {
+        let used = |s: MemorySubsystem| self.used[s as usize].load(Ordering::Relaxed);
+        MemoryUsage {
+            limit: self.limit,
+            threshold: self.limit.map(|_| self.threshold),
+            used: self.used(),
+            defrag: used(MemorySubsystem::Defrag),
+            tx_queues: used(MemorySubsystem::TxQueues),
+            routing: used(MemorySubsystem::Routing),
+            policy: self.policy,
+        }
+    }
+}
+
+/// Memory reserved in a [`MemoryBudget`], released when dropped.
+#[derive(Debug)]
+pub struct MemoryReservation {
+    budget: Arc<MemoryBudget>,
+    subsystem: MemorySubsystem,
+    bytes: usize,
+}
+
+impl MemoryReservation {
+    /// Reserves `bytes` used by `subsystem`, even if it exceeds the limit.
+    pub fn new(budget: Arc<MemoryBudget>, subsystem: MemorySubsystem, bytes: usize) -> Self {
+        budget.reserve(subsystem, bytes);
+        MemoryReservation {
+            budget,
+            subsystem,
+            bytes,
+        }
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.budget.release(self.subsystem, self.bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_budget() {
+        let policy = MemoryPolicy {
+            reject_sessions: true,
+            drop_best_effort: false,
+            shed_declarations: true,
+        };
+        let budget = MemoryBudget::new(Some(1_000), 80, policy);
+        assert!(budget.try_reserve(MemorySubsystem::TxQueues, 600));
+        assert!(!budget.is_near_limit());
+        assert!(budget.try_reserve(MemorySubsystem::Defrag, 200));
+        assert!(budget.rejects_sessions());
+        assert!(!budget.drops_best_effort());
+        assert!(budget.sheds_declarations());
+        assert!(!budget.try_reserve(MemorySubsystem::Routing, 201));
+        budget.reserve(MemorySubsystem::Routing, 201);
+        assert_eq!(budget.used(), 1_001);
+
+        let usage = budget.usage();
+        assert_eq!(usage.defrag, 200);
+        assert_eq!(usage.tx_queues, 600);
+        assert_eq!(usage.routing, 201);
+        assert_eq!(usage.threshold, Some(800));
+
+        budget.release(MemorySubsystem::Routing, 201);
+        budget.release(MemorySubsystem::Defrag, 200);
+        assert!(!budget.is_near_limit());
+
+        let budget = Arc::new(budget);
+        let reservation = MemoryReservation::new(budget.clone(), MemorySubsystem::Routing, 300);
+        assert!(budget.is_near_limit());
+        drop(reservation);
+        assert_eq!(budget.used(), 600);
+        assert!(!budget.is_near_limit());
+
+        let recoveries = budget.recoveries();
+        let reservation = MemoryReservation::new(budget.clone(), MemorySubsystem::Routing, 300);
+        assert!(recoveries.try_recv().is_err());
+        drop(reservation);
+        assert!(recoveries.try_recv().is_ok());
+
+        let unlimited = MemoryBudget::default();
+        assert!(unlimited.try_reserve(MemorySubsystem::Routing, usize::MAX / 2));
+        assert!(!unlimited.is_near_limit());
+    }
+
+    #[test]
+    fn memory_budget_concurrent_reservations() {
+        const THREADS: usize = 8;
+        const RESERVATIONS: usize = 1_000;
+
+        let budget = Arc::new(MemoryBudget::new(
+            Some(RESERVATIONS),
+            100,
+            MemoryPolicy::default(),
+        ));
+        let threads = (0..THREADS)
+            .map(|_| {
+                let budget = budget.clone();
+                std::thread::spawn(move || {
+                    (0..RESERVATIONS)
+                        .filter(|_| budget.try_reserve(MemorySubsystem::Defrag, 1))
+                        .count()
+                })
+            })
+            .collect::<Vec<_>>();
+        let reserved: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+
+        // The concurrent reservations never exceed the limit together
+        assert_eq!(reserved, RESERVATIONS);
+        assert_eq!(budget.used(), RESERVATIONS);
+        assert_eq!(budget.usage().defrag, RESERVATIONS);
+    }
+}
@@ -15,11 +15,69 @@ use crate::multicast::{TransportMulticast, TransportMulticastConfig, TransportMu
 use crate::TransportManager;
 use rand::Rng;
 use std::sync::Arc;
+use std::time::Duration;
 use zenoh_core::{zasynclock, zlock};
+use zenoh_crypto::hmac;
 use zenoh_link::LinkMulticast;
-use zenoh_protocol::core::{ConduitSn, ConduitSnList, Priority};
+use zenoh_protocol::{
+    core::{ConduitSn, ConduitSnList, Priority, WhatAmI, ZInt, ZenohId},
+    transport::Join,
+};
 use zenoh_result::ZResult;
 
+// The parameters of the sender of a Join message, which its token signs
+fn join_params(
+    version: u8,
+    whatami: WhatAmI,
+    zid: &ZenohId,
+    lease: Duration,
+    sn_resolution: ZInt,
+) -> Vec<u8> {
+    let wai: ZInt = whatami.into();
+    let mut params = vec![version];
+    params.extend_from_slice(&wai.to_le_bytes());
+    params.extend_from_slice(zid.as_slice());
+    params.extend_from_slice(&(lease.as_millis() as u64).to_le_bytes());
+    params.extend_from_slice(&sn_resolution.to_le_bytes());
+    params
+}
+
+/// Computes the token presented in the Join messages sent with the given parameters, signing
+/// them with the group key.
+pub(crate) fn join_token(
+    key: &[u8],
+    version: u8,
+    whatami: WhatAmI,
+    zid: &ZenohId,
+    lease: Duration,
+    sn_resolution: ZInt,
+) -> ZResult<Vec<u8>> {
+    hmac::sign(
+        key,
+        &join_params(version, whatami, zid, lease, sn_resolution),
+    )
+}
+
+/// Returns `true` if the Join message presents a token signed with the group key.
+///
+/// As the token only depends on the parameters of its sender, it can be replayed by anyone
+/// having received it: it is not an authentication of the sender.
+pub(crate) fn is_join_authorized(key: &[u8], join: &Join) -> bool {
+    match join.token.as_ref() {
+        Some(token) => {
+            let params = join_params(
+                join.version,
+                join.whatami,
+                &join.zid,
+                join.lease,
+                join.sn_resolution,
+            );
+            hmac::verify(key, &params, token).unwrap_or(false)
+        }
+        None => false,
+    }
+}
+
 pub(crate) async fn open_link(
     manager: &TransportManager,
     link: LinkMulticast,
@@ -82,3 +140,33 @@ pub(crate) async fn open_link(
 
     Ok(transport)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_authorization() {
+        let zid = ZenohId::rand();
+        let lease = Duration::from_secs(10);
+        let token = join_token(b"key", 1, WhatAmI::Peer, &zid, lease, 1 << 28).unwrap();
+        let mut join = Join {
+            version: 1,
+            whatami: WhatAmI::Peer,
+            zid,
+            lease,
+            sn_resolution: 1 << 28,
+            next_sns: ConduitSnList::Plain(ConduitSn::default()),
+            token: Some(token),
+        };
+        assert!(is_join_authorized(b"key", &join));
+        assert!(!is_join_authorized(b"other key", &join));
+
+        join.lease = Duration::from_secs(20);
+        assert!(!is_join_authorized(b"key", &join));
+
+        join.lease = lease;
+        join.token = None;
+        assert!(!is_join_authorized(b"key", &join));
+    }
+}
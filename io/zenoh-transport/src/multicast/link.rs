@@ -11,22 +11,25 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::common::{conduit::TransportConduitTx, pipeline::TransmissionPipeline};
+use super::common::{
+    conduit::TransportConduitTx, pipeline::TransmissionPipeline, workers::RxCallbacks,
+};
 use super::transport::TransportMulticastInner;
 #[cfg(feature = "stats")]
 use super::TransportMulticastStatsAtomic;
+use crate::capture::{self, Direction};
 use crate::common::batch::WBatch;
 use crate::common::pipeline::{
     TransmissionPipelineConf, TransmissionPipelineConsumer, TransmissionPipelineProducer,
 };
 use async_std::prelude::FutureExt;
 use async_std::task;
-use async_std::task::JoinHandle;
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use zenoh_buffers::reader::{HasReader, Reader};
 use zenoh_codec::{RCodec, Zenoh060};
+use zenoh_core::executor::{self, JoinHandle};
 use zenoh_core::zlock;
 use zenoh_link::{LinkMulticast, Locator};
 use zenoh_protocol::{
@@ -36,6 +39,7 @@ use zenoh_protocol::{
 use zenoh_result::{bail, zerror, ZResult};
 use zenoh_sync::RecyclingObjectPool;
 use zenoh_sync::Signal;
+use zenoh_util::watchdog::{self, TaskProbe};
 
 pub(super) struct TransportLinkMulticastConfig {
     pub(super) version: u8,
@@ -46,6 +50,7 @@ pub(super) struct TransportLinkMulticastConfig {
     pub(super) join_interval: Duration,
     pub(super) sn_resolution: ZInt,
     pub(super) batch_size: u16,
+    pub(super) join_token: Option<Vec<u8>>,
 }
 
 #[derive(Clone)]
@@ -95,23 +100,41 @@ impl TransportLinkMulticast {
         if self.handle_tx.is_none() {
             let tpc = TransmissionPipelineConf {
                 is_streamed: false,
+                preamble: false,
                 batch_size: config.batch_size.min(self.link.get_mtu()),
+                max_message_size: self.transport.manager.config.max_message_size,
                 queue_size: self.transport.manager.config.queue_size,
                 backoff: self.transport.manager.config.queue_backoff,
+                watermarks: None,
+                memory: Some(self.transport.manager.config.memory.clone()),
+                pacing: self.transport.manager.config.queue_pacing,
             };
             // The pipeline
-            let (producer, consumer) = TransmissionPipeline::make(tpc, &conduit_tx);
+            let (producer, consumer) = TransmissionPipeline::make(
+                tpc,
+                &conduit_tx,
+                Some(self.transport.manager.buffer_pool.clone()),
+            );
+            let probe = watchdog::register(
+                format!("tx {}", self.link),
+                Some(Box::new({
+                    let producer = producer.clone();
+                    move || format!("queued batches: {}", producer.queued())
+                })),
+                Some(restart(&self.link)),
+            );
             self.pipeline = Some(producer);
 
             // Spawn the TX task
             let c_link = self.link.clone();
             let c_transport = self.transport.clone();
-            let handle = task::spawn(async move {
+            let handle = self.transport.manager.tx_executor.spawn(async move {
                 let res = tx_task(
                     consumer,
                     c_link.clone(),
                     config,
                     initial_sns,
+                    probe,
                     #[cfg(feature = "stats")]
                     c_transport.stats.clone(),
                 )
@@ -120,7 +143,7 @@ impl TransportLinkMulticast {
                     log::debug!("{}", e);
                     // Spawn a task to avoid a deadlock waiting for this same task
                     // to finish in the close() joining its handle
-                    task::spawn(async move { c_transport.delete().await });
+                    executor::spawn(async move { c_transport.delete().await });
                 }
             });
             self.handle_tx = Some(Arc::new(handle));
@@ -140,14 +163,17 @@ impl TransportLinkMulticast {
             let c_transport = self.transport.clone();
             let c_signal = self.signal_rx.clone();
             let c_rx_buffer_size = self.transport.manager.config.link_rx_buffer_size;
+            let probe =
+                watchdog::register(format!("rx {}", self.link), None, Some(restart(&self.link)));
 
-            let handle = task::spawn(async move {
+            let handle = executor::spawn(async move {
                 // Start the consume task
                 let res = rx_task(
                     c_link.clone(),
                     c_transport.clone(),
                     c_signal.clone(),
                     c_rx_buffer_size,
+                    probe,
                 )
                 .await;
                 c_signal.trigger();
@@ -155,7 +181,7 @@ impl TransportLinkMulticast {
                     log::debug!("{}", e);
                     // Spawn a task to avoid a deadlock waiting for this same task
                     // to finish in the close() joining its handle
-                    task::spawn(async move { c_transport.delete().await });
+                    executor::spawn(async move { c_transport.delete().await });
                 }
             });
             self.handle_rx = Some(Arc::new(handle));
@@ -189,11 +215,26 @@ impl TransportLinkMulticast {
 /*************************************/
 /*              TASKS                */
 /*************************************/
+// Closing the link makes its pending reads and writes fail, so that its tasks stop and the
+// transport is deleted
+fn restart(link: &LinkMulticast) -> watchdog::Restart {
+    let link = link.clone();
+    Box::new(move || {
+        let link = link.clone();
+        executor::spawn(async move {
+            if let Err(e) = link.close().await {
+                log::debug!("{}: error closing stalled link: {}", link, e);
+            }
+        });
+    })
+}
+
 async fn tx_task(
     mut pipeline: TransmissionPipelineConsumer,
     link: LinkMulticast,
     config: TransportLinkMulticastConfig,
     mut last_sns: Vec<ConduitSn>,
+    probe: TaskProbe,
     #[cfg(feature = "stats")] stats: Arc<TransportMulticastStatsAtomic>,
 ) -> ZResult<()> {
     enum Action {
@@ -226,14 +267,17 @@ async fn tx_task(
     let keep_alive = config.join_interval / config.keep_alive as u32;
     let mut last_join = Instant::now().checked_sub(config.join_interval).unwrap();
     loop {
-        match pull(&mut pipeline, keep_alive)
+        probe.idle();
+        let action = pull(&mut pipeline, keep_alive)
             .race(join(last_join, config.join_interval))
-            .await
-        {
+            .await;
+        probe.progress();
+        match action {
             Action::Pull((batch, priority)) => {
                 // Send the buffer on the link
                 let bytes = batch.as_bytes();
                 link.write_all(bytes).await?;
+                capture::capture(Direction::Tx, &link, batch.payload());
                 // Keep track of next SNs
                 if let Some(sn) = batch.latest_sn.reliable {
                     last_sns[priority].reliable = sn;
@@ -272,11 +316,13 @@ async fn tx_task(
                     config.lease,
                     config.sn_resolution,
                     next_sns,
+                    config.join_token.clone(),
                     attachment,
                 );
 
                 #[allow(unused_variables)] // Used when stats feature is enabled
                 let n = link.write_transport_message(&message).await?;
+                capture::capture_message(Direction::Tx, &link, &message);
                 #[cfg(feature = "stats")]
                 {
                     stats.inc_tx_t_msgs(1);
@@ -292,6 +338,7 @@ async fn tx_task(
 
                 #[allow(unused_variables)] // Used when stats feature is enabled
                 let n = link.write_transport_message(&message).await?;
+                capture::capture_message(Direction::Tx, &link, &message);
                 #[cfg(feature = "stats")]
                 {
                     stats.inc_tx_t_msgs(1);
@@ -312,6 +359,7 @@ async fn tx_task(
                                 config.join_interval.as_millis()
                             )
                         })??;
+                    capture::capture(Direction::Tx, &link, b.payload());
 
                     #[cfg(feature = "stats")]
                     {
@@ -332,6 +380,7 @@ async fn rx_task(
     transport: TransportMulticastInner,
     signal: Signal,
     rx_buffer_size: usize,
+    probe: TaskProbe,
 ) -> ZResult<()> {
     enum Action {
         Read((usize, Locator)),
@@ -362,7 +411,9 @@ async fn rx_task(
         // Retrieve one buffer
         let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
         // Async read from the underlying link
+        probe.idle();
         let action = read(&link, &mut buffer).race(stop(signal.clone())).await?;
+        probe.progress();
         match action {
             Action::Read((n, loc)) => {
                 if n == 0 {
@@ -372,9 +423,11 @@ async fn rx_task(
 
                 #[cfg(feature = "stats")]
                 transport.stats.inc_rx_bytes(n);
+                capture::capture(Direction::Rx, &link, &buffer[..n]);
 
                 // Deserialize all the messages from the current ZBuf
                 let mut reader = buffer[0..n].reader();
+                let mut callbacks = RxCallbacks::default();
                 while reader.can_read() {
                     let msg: TransportMessage = codec
                         .read(&mut reader)
@@ -383,7 +436,8 @@ async fn rx_task(
                     #[cfg(feature = "stats")]
                     transport.stats.inc_rx_t_msgs(1);
 
-                    transport.receive_message(msg, &loc)?
+                    transport.receive_message(msg, &loc, &mut callbacks)?;
+                    callbacks.run().await?;
                 }
             }
             Action::Stop => break,
@@ -15,12 +15,16 @@ use crate::multicast::transport::TransportMulticastInner;
 use crate::multicast::TransportMulticast;
 use crate::TransportManager;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use zenoh_config::{Config, ZN_LINK_KEEP_ALIVE_DEFAULT, ZN_LINK_LEASE_DEFAULT};
 use zenoh_core::{zlock, zparse};
 use zenoh_link::*;
-use zenoh_protocol::{core::endpoint::Protocol, transport::tmsg};
+use zenoh_protocol::{
+    core::{endpoint::Protocol, ZenohId},
+    transport::tmsg,
+};
 use zenoh_result::{bail, zerror, ZResult};
 
 pub struct TransportManagerConfigMulticast {
@@ -29,6 +33,8 @@ pub struct TransportManagerConfigMulticast {
     pub join_interval: Duration,
     pub max_sessions: usize,
     pub is_qos: bool,
+    pub join_key: Option<Vec<u8>>,
+    pub ignore: Vec<ZenohId>,
 }
 
 pub struct TransportManagerBuilderMulticast {
@@ -37,6 +43,8 @@ pub struct TransportManagerBuilderMulticast {
     join_interval: Duration,
     max_sessions: usize,
     is_qos: bool,
+    join_key: Option<Vec<u8>>,
+    ignore: Vec<ZenohId>,
 }
 
 pub struct TransportManagerStateMulticast {
@@ -77,6 +85,22 @@ impl TransportManagerBuilderMulticast {
         self
     }
 
+    /// Sets the group key signing the Join messages, the peers whose Join messages are not
+    /// signed with the same key are ignored.
+    ///
+    /// This only keeps the misconfigured peers out of the group: a signed Join can be replayed
+    /// and the other messages are not signed, so the key doesn't authenticate the peers.
+    pub fn join_key(mut self, join_key: Option<Vec<u8>>) -> Self {
+        self.join_key = join_key;
+        self
+    }
+
+    /// Sets the peers whose Join messages, and thus traffic, are ignored.
+    pub fn ignore(mut self, ignore: Vec<ZenohId>) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
     pub async fn from_config(
         mut self,
         config: &Config,
@@ -90,6 +114,31 @@ impl TransportManagerBuilderMulticast {
         ));
         self = self.max_sessions(config.transport().multicast().max_sessions().unwrap());
         self = self.qos(*config.transport().qos().enabled());
+        let join_key = match config.transport().multicast().join_key() {
+            Some(key) => {
+                // The exec providers are blocking commands
+                let secrets = config.transport().auth().secrets().clone();
+                let key = key.clone();
+                let key = async_std::task::spawn_blocking(move || secrets.resolve(&key)).await?;
+                if key.is_empty() {
+                    bail!("Invalid multicast join key: empty key");
+                }
+                Some(key.into_bytes())
+            }
+            None => None,
+        };
+        self = self.join_key(join_key);
+        let ignore = config
+            .transport()
+            .multicast()
+            .ignore()
+            .iter()
+            .map(|zid| {
+                ZenohId::from_str(zid)
+                    .map_err(|e| zerror!("Invalid multicast ignored peer `{}`: {}", zid, e))
+            })
+            .collect::<Result<Vec<ZenohId>, _>>()?;
+        self = self.ignore(ignore);
 
         Ok(self)
     }
@@ -101,6 +150,8 @@ impl TransportManagerBuilderMulticast {
             join_interval: self.join_interval,
             max_sessions: self.max_sessions,
             is_qos: self.is_qos,
+            join_key: self.join_key,
+            ignore: self.ignore,
         };
 
         let state = TransportManagerStateMulticast {
@@ -122,6 +173,8 @@ impl Default for TransportManagerBuilderMulticast {
             join_interval: Duration::from_millis(0),
             max_sessions: 0,
             is_qos: false,
+            join_key: None,
+            ignore: vec![],
         };
         async_std::task::block_on(tmb.from_config(&Config::default())).unwrap()
     }
@@ -137,10 +190,17 @@ impl TransportManager {
 
         zlock!(self.state.multicast.protocols).clear();
 
-        let mut tm_guard = zlock!(self.state.multicast.transports)
-            .drain()
-            .map(|(_, v)| v)
-            .collect::<Vec<Arc<TransportMulticastInner>>>();
+        let mut tm_guard = {
+            let mut guard = zlock!(self.state.multicast.transports);
+            #[cfg(feature = "stats")]
+            for tm in guard.values() {
+                *zlock!(self.closed_stats) += tm.transport_stats();
+            }
+            guard
+                .drain()
+                .map(|(_, v)| v)
+                .collect::<Vec<Arc<TransportMulticastInner>>>()
+        };
         for tm in tm_guard.drain(..) {
             let _ = tm.close(tmsg::close_reason::GENERIC).await;
         }
@@ -217,6 +277,10 @@ impl TransportManager {
     pub(super) fn del_transport_multicast(&self, locator: &Locator) -> ZResult<()> {
         let mut guard = zlock!(self.state.multicast.transports);
         let res = guard.remove(locator);
+        #[cfg(feature = "stats")]
+        if let Some(transport) = res.as_ref() {
+            *zlock!(self.closed_stats) += transport.transport_stats();
+        }
 
         let proto = locator.protocol();
         if !guard.iter().any(|(l, _)| l.protocol() == proto) {
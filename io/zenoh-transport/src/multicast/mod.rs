@@ -20,17 +20,22 @@ pub(crate) mod tx;
 
 use super::common;
 #[cfg(feature = "stats")]
-use super::common::stats::stats_struct;
+use super::common::stats::{stats_struct, TransportStats};
 use crate::{TransportMulticastEventHandler, TransportPeer};
 pub use manager::*;
 use std::{
     fmt,
     sync::{Arc, Weak},
+    time::Duration,
 };
 use transport::{TransportMulticastConfig, TransportMulticastInner};
 use zenoh_core::zread;
-use zenoh_link::Link;
-use zenoh_protocol::{core::ZInt, transport::tmsg, zenoh::ZenohMessage};
+use zenoh_link::{Link, Locator};
+use zenoh_protocol::{
+    core::{ConduitSnList, WhatAmI, ZInt, ZenohId},
+    transport::tmsg,
+    zenoh::ZenohMessage,
+};
 use zenoh_result::{zerror, ZResult};
 
 /*************************************/
@@ -59,6 +64,8 @@ stats_struct! {
         pub tx_z_unit_reply_msgs,
         pub tx_bytes,
         pub rx_t_msgs,
+        pub rx_t_dropped,
+        pub rx_t_unauthorized,
         pub rx_z_msgs,
         pub rx_z_data_msgs,
         pub rx_z_data_payload_bytes,
@@ -74,6 +81,45 @@ stats_struct! {
     }
 }
 
+/*************************************/
+/*              PEERS                */
+/*************************************/
+/// The state of a peer discovered on a multicast transport.
+#[derive(Clone, Debug)]
+pub struct TransportMulticastPeerInfo {
+    pub zid: ZenohId,
+    pub whatami: WhatAmI,
+    pub locator: Locator,
+    /// The lease advertised by the peer, which is evicted when no message is received from it
+    /// for a whole lease.
+    pub lease: Duration,
+    /// The time elapsed since the peer joined.
+    pub joined: Duration,
+    /// The time elapsed since the last message received from the peer.
+    pub last_seen: Duration,
+    /// The next sequence numbers expected from the peer.
+    pub next_sns: ConduitSnList,
+}
+
+/// An event on the peers of a multicast transport.
+#[derive(Clone, Debug)]
+pub enum TransportMulticastPeerEvent {
+    /// A peer joined the transport.
+    Joined {
+        zid: ZenohId,
+        whatami: WhatAmI,
+        locator: Locator,
+    },
+    /// A peer left the transport, `reason` being a [`close_reason`](tmsg::close_reason):
+    /// `EXPIRED` if its lease expired, `GENERIC` if it has been evicted, otherwise the reason
+    /// of the Close message it sent.
+    Left {
+        zid: ZenohId,
+        locator: Locator,
+        reason: u8,
+    },
+}
+
 /*************************************/
 /*       TRANSPORT MULTICAST         */
 /*************************************/
@@ -124,6 +170,41 @@ impl TransportMulticast {
         Ok(transport.get_peers())
     }
 
+    /// Returns the state of the peers discovered on this transport.
+    #[inline(always)]
+    pub fn get_peers_info(&self) -> ZResult<Vec<TransportMulticastPeerInfo>> {
+        let transport = self.get_transport()?;
+        Ok(transport.get_peers_info())
+    }
+
+    /// Evicts the peer `zid` from this transport, it joins again with its next Join message.
+    /// Returns `false` if the peer is not known.
+    pub fn evict_peer(&self, zid: &ZenohId) -> ZResult<bool> {
+        let transport = self.get_transport()?;
+        transport.evict_peer(zid, false)
+    }
+
+    /// Evicts the peer `zid` from this transport and ignores its Join messages, and thus its
+    /// traffic, until [`unignore_peer`](TransportMulticast::unignore_peer) is called.
+    /// Returns `false` if the peer is not known.
+    pub fn ignore_peer(&self, zid: &ZenohId) -> ZResult<bool> {
+        let transport = self.get_transport()?;
+        transport.evict_peer(zid, true)
+    }
+
+    /// Accepts again the Join messages of the peer `zid`.
+    /// Returns `false` if the peer was not ignored.
+    pub fn unignore_peer(&self, zid: &ZenohId) -> ZResult<bool> {
+        let transport = self.get_transport()?;
+        Ok(transport.unignore_peer(zid))
+    }
+
+    /// Returns a receiver of the events on the peers joining and leaving this transport.
+    pub fn peer_events(&self) -> ZResult<flume::Receiver<TransportMulticastPeerEvent>> {
+        let transport = self.get_transport()?;
+        Ok(transport.subscribe_peer_events())
+    }
+
     #[inline(always)]
     pub async fn close(&self) -> ZResult<()> {
         // Return Ok if the transport has already been closed
@@ -149,6 +230,12 @@ impl TransportMulticast {
     pub fn get_stats(&self) -> ZResult<TransportMulticastStats> {
         Ok(self.get_transport()?.stats.snapshot())
     }
+
+    /// Returns a typed snapshot of the statistics of this transport.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> ZResult<TransportStats> {
+        Ok(self.get_transport()?.transport_stats())
+    }
 }
 
 impl From<&Arc<TransportMulticastInner>> for TransportMulticast {
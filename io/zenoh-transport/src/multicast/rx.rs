@@ -12,13 +12,15 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::common::conduit::TransportChannelRx;
+use super::common::workers::{Dispatch, RxCallbacks};
+use super::establishment::is_join_authorized;
 use super::transport::{TransportMulticastInner, TransportMulticastPeer};
 use std::sync::MutexGuard;
 use zenoh_core::{zlock, zread};
 #[cfg(feature = "stats")]
 use zenoh_protocol::zenoh::ZenohBody;
 use zenoh_protocol::{
-    core::{Locator, Priority, Reliability, ZInt},
+    core::{Channel, Locator, Priority, Reliability, ZInt},
     transport::{Frame, FramePayload, Join, TransportBody, TransportMessage},
     zenoh::ZenohMessage,
 };
@@ -34,11 +36,23 @@ impl TransportMulticastInner {
         #[allow(unused_mut)] // shared-memory feature requires mut
         mut msg: ZenohMessage,
         peer: &TransportMulticastPeer,
+        callbacks: &mut RxCallbacks,
     ) -> ZResult<()> {
+        let span = tracing::trace_span!(
+            "rx",
+            zid = %peer.zid,
+            trace = tracing::field::Empty
+        );
+        if let Some(tc) = msg.trace_context() {
+            span.record("trace", tracing::field::display(tc));
+        }
+        let _enter = span.enter();
+
         #[cfg(feature = "stats")]
         {
             use zenoh_buffers::SplitBuffer;
             self.stats.inc_rx_z_msgs(1);
+            self.priority_stats.inc_rx_msgs(msg.channel.priority);
             match &msg.body {
                 ZenohBody::Data(data) => match data.reply_context {
                     Some(_) => {
@@ -59,6 +73,8 @@ impl TransportMulticastInner {
                 ZenohBody::Query(_) => self.stats.inc_rx_z_query_msgs(1),
                 ZenohBody::Declare(_) => self.stats.inc_rx_z_declare_msgs(1),
                 ZenohBody::LinkStateList(_) => self.stats.inc_rx_z_linkstate_msgs(1),
+                // Only accounted in the zenoh messages
+                ZenohBody::CongestionDrop(_) => {}
             }
         }
 
@@ -67,24 +83,62 @@ impl TransportMulticastInner {
             let _ = crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.shmr)?;
         }
 
-        peer.handler.handle_message(msg)
+        match self.manager.rx_workers.as_ref() {
+            Some(workers) => {
+                let zid = peer.zid;
+                let priority = msg.channel.priority;
+                let droppable = msg.is_droppable();
+                let handler = peer.handler.clone();
+                let span = span.clone();
+                let job = Box::new(move || {
+                    let _enter = span.enter();
+                    if let Err(e) = handler.handle_message(msg) {
+                        tracing::debug!(%zid, "Error handling message: {}", e);
+                    }
+                });
+                match workers.dispatch(&zid, priority, droppable, job) {
+                    Dispatch::Queued => {}
+                    Dispatch::Waiting(send) => callbacks.wait(send),
+                    Dispatch::Dropped => {
+                        tracing::trace!(%zid, ?priority, "Callback queue full, message dropped");
+                        #[cfg(feature = "stats")]
+                        self.priority_stats.inc_rx_dropped(priority);
+                    }
+                }
+            }
+            None => {
+                let handler = peer.handler.clone();
+                let span = span.clone();
+                callbacks.inline(Box::new(move || {
+                    let _enter = span.enter();
+                    handler.handle_message(msg)
+                }));
+            }
+        }
+        Ok(())
     }
 
     fn handle_frame(
         &self,
+        channel: Channel,
         sn: ZInt,
         payload: FramePayload,
         mut guard: MutexGuard<'_, TransportChannelRx>,
         peer: &TransportMulticastPeer,
+        callbacks: &mut RxCallbacks,
     ) -> ZResult<()> {
         let precedes = guard.sn.precedes(sn)?;
         if !precedes {
-            log::debug!(
-                "Transport: {}. Frame with invalid SN dropped: {}. Expected: {}.",
-                self.manager.config.zid,
+            tracing::debug!(
+                zid = %peer.zid,
+                link = %peer.locator,
+                conduit = ?channel,
                 sn,
-                guard.sn.get()
+                expected = guard.sn.get(),
+                "Frame with invalid SN dropped."
             );
+            #[cfg(feature = "stats")]
+            self.stats.inc_rx_t_dropped(1);
             // Drop the fragments if needed
             if !guard.defrag.is_empty() {
                 guard.defrag.clear();
@@ -111,14 +165,16 @@ impl TransportMulticastInner {
                             self.locator
                         )
                     })?;
-                    self.trigger_callback(msg, peer)
+                    self.trigger_callback(msg, peer, callbacks)
                 } else {
                     Ok(())
                 }
             }
-            FramePayload::Messages { mut messages } => {
-                for msg in messages.drain(..) {
-                    self.trigger_callback(msg, peer)?;
+            FramePayload::Messages { messages } => {
+                // The callbacks are queued while holding the conduit, to keep the order of the
+                // messages, but run after releasing it, which a slow callback would otherwise hold
+                for msg in messages {
+                    self.trigger_callback(msg, peer, callbacks)?;
                 }
                 Ok(())
             }
@@ -138,65 +194,92 @@ impl TransportMulticastInner {
             || join.lease != peer.lease
             || join.is_qos() != peer.is_qos()
         {
-            let e = format!(
+            tracing::debug!(
+                zid = %peer.zid,
+                link = %peer.locator,
+                "Ignoring Join. Inconsistent parameters."
+            );
+            bail!(
                 "Ingoring Join on {} of peer: {}. Inconsistent parameters. Version",
-                peer.locator, peer.zid,
+                peer.locator,
+                peer.zid,
             );
-            log::debug!("{}", e);
-            bail!("{}", e);
         }
 
         Ok(())
     }
 
     pub(super) fn handle_join_from_unknown(&self, join: Join, locator: &Locator) -> ZResult<()> {
+        if self.is_ignored(&join.zid) {
+            tracing::trace!(zid = %join.zid, link = %locator, "Ignoring Join. Peer ignored.");
+            return Ok(());
+        }
+
         if zread!(self.peers).len() >= self.manager.config.multicast.max_sessions {
-            log::debug!(
-                "Ingoring Join on {} from peer: {}. Max sessions reached: {}.",
-                locator,
-                join.zid,
-                self.manager.config.multicast.max_sessions,
+            tracing::debug!(
+                zid = %join.zid,
+                link = %locator,
+                max_sessions = self.manager.config.multicast.max_sessions,
+                "Ignoring Join. Max sessions reached."
             );
             return Ok(());
         }
 
         if join.version != self.manager.config.version {
-            log::debug!(
-                "Ingoring Join on {} from peer: {}. Unsupported version: {}. Expected: {}.",
-                locator,
-                join.zid,
-                join.version,
-                self.manager.config.version,
+            tracing::debug!(
+                zid = %join.zid,
+                link = %locator,
+                version = join.version,
+                expected = self.manager.config.version,
+                "Ignoring Join. Unsupported version."
             );
             return Ok(());
         }
 
         if join.sn_resolution > self.manager.config.sn_resolution {
-            log::debug!(
-                "Ingoring Join on {} from peer: {}. Unsupported SN resolution: {}. Expected: <= {}.",
-                locator,
-                join.zid,
-                join.sn_resolution,
-                self.manager.config.sn_resolution,
+            tracing::debug!(
+                zid = %join.zid,
+                link = %locator,
+                sn_resolution = join.sn_resolution,
+                expected = self.manager.config.sn_resolution,
+                "Ignoring Join. Unsupported SN resolution."
             );
             return Ok(());
         }
 
         if !self.manager.config.multicast.is_qos && join.is_qos() {
-            log::debug!(
-                "Ingoring Join on {} from peer: {}. QoS is not supported.",
-                locator,
-                join.zid,
+            tracing::debug!(
+                zid = %join.zid,
+                link = %locator,
+                "Ignoring Join. QoS is not supported."
             );
             return Ok(());
         }
 
+        if let Some(key) = self.manager.config.multicast.join_key.as_ref() {
+            if !is_join_authorized(key, &join) {
+                tracing::debug!(
+                    zid = %join.zid,
+                    link = %locator,
+                    "Ignoring Join. Unauthorized."
+                );
+                #[cfg(feature = "stats")]
+                self.stats.inc_rx_t_unauthorized(1);
+                return Ok(());
+            }
+        }
+
         let _ = self.new_peer(locator, join);
 
         Ok(())
     }
 
-    pub(super) fn receive_message(&self, msg: TransportMessage, locator: &Locator) -> ZResult<()> {
+    pub(super) fn receive_message(
+        &self,
+        msg: TransportMessage,
+        locator: &Locator,
+        callbacks: &mut RxCallbacks,
+    ) -> ZResult<()> {
         // Process the received message
         let r_guard = zread!(self.peers);
         match r_guard.get(locator) {
@@ -227,7 +310,7 @@ impl TransportMulticastInner {
                             Reliability::Reliable => zlock!(c.reliable),
                             Reliability::BestEffort => zlock!(c.best_effort),
                         };
-                        self.handle_frame(sn, payload, guard, peer)
+                        self.handle_frame(channel, sn, payload, guard, peer, callbacks)
                     }
                     TransportBody::Join(join) => self.handle_join_from_peer(join, peer),
                     TransportBody::Close(close) => {
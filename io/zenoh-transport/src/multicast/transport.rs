@@ -11,26 +11,31 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::common::conduit::{TransportConduitRx, TransportConduitTx};
+use super::common::conduit::{TransportChannelRx, TransportConduitRx, TransportConduitTx};
+#[cfg(feature = "stats")]
+use super::common::stats::{transport_stats, PriorityStatsAtomic, TransportStats};
+use super::establishment::join_token;
 use super::link::{TransportLinkMulticast, TransportLinkMulticastConfig};
 #[cfg(feature = "stats")]
 use super::TransportMulticastStatsAtomic;
+use super::{TransportMulticastPeerEvent, TransportMulticastPeerInfo};
 use crate::{
     TransportManager, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
 };
 use async_trait::async_trait;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    convert::TryInto,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, RwLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
-use zenoh_core::{zread, zwrite};
+use zenoh_core::{zlock, zread, zwrite};
 use zenoh_link::{Link, LinkMulticast, Locator};
 use zenoh_protocol::{
-    core::{ConduitSnList, Priority, WhatAmI, ZInt, ZenohId},
+    core::{ConduitSn, ConduitSnList, Priority, WhatAmI, ZInt, ZenohId},
     transport::{tmsg, Join, TransportMessage},
     zenoh::ZenohMessage,
 };
@@ -52,16 +57,55 @@ pub(super) struct TransportMulticastPeer {
     pub(super) handle: TimedHandle,
     pub(super) conduit_rx: Box<[TransportConduitRx]>,
     pub(super) handler: Arc<dyn TransportPeerEventHandler>,
+    pub(super) joined: Instant,
+    // The milliseconds elapsed between the join and the last message received from the peer
+    pub(super) last_seen: AtomicU64,
 }
 
 impl TransportMulticastPeer {
     pub(super) fn active(&self) {
         self.whatchdog.store(true, Ordering::Release);
+        self.last_seen
+            .store(self.joined.elapsed().as_millis() as u64, Ordering::Relaxed);
     }
 
     pub(super) fn is_qos(&self) -> bool {
         self.conduit_rx.len() == Priority::NUM
     }
+
+    fn info(&self) -> TransportMulticastPeerInfo {
+        fn next_sn(channel: &Mutex<TransportChannelRx>) -> ZInt {
+            let guard = zlock!(channel);
+            (guard.sn.get() + 1) % guard.sn.resolution()
+        }
+
+        let next_sns = self
+            .conduit_rx
+            .iter()
+            .map(|c| ConduitSn {
+                reliable: next_sn(&c.reliable),
+                best_effort: next_sn(&c.best_effort),
+            })
+            .collect::<Vec<ConduitSn>>();
+        let next_sns = if next_sns.len() == Priority::NUM {
+            let tmp: [ConduitSn; Priority::NUM] = next_sns.try_into().unwrap();
+            ConduitSnList::QoS(tmp.into())
+        } else {
+            ConduitSnList::Plain(next_sns[0])
+        };
+        let elapsed = self.joined.elapsed();
+        let last_seen = Duration::from_millis(self.last_seen.load(Ordering::Relaxed));
+
+        TransportMulticastPeerInfo {
+            zid: self.zid,
+            whatami: self.whatami,
+            locator: self.locator.clone(),
+            lease: self.lease,
+            joined: elapsed,
+            last_seen: elapsed.saturating_sub(last_seen),
+            next_sns,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -99,9 +143,15 @@ pub(crate) struct TransportMulticastInner {
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportMulticastEventHandler>>>>,
     // The timer for peer leases
     pub(super) timer: Arc<Timer>,
+    // The peers whose Join messages are ignored
+    pub(super) ignored: Arc<RwLock<HashSet<ZenohId>>>,
+    // The listeners of the peer events
+    pub(super) peer_events: Arc<Mutex<Vec<flume::Sender<TransportMulticastPeerEvent>>>>,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportMulticastStatsAtomic>,
+    #[cfg(feature = "stats")]
+    pub(super) priority_stats: Arc<PriorityStatsAtomic>,
 }
 
 pub(crate) struct TransportMulticastConfig {
@@ -137,8 +187,21 @@ impl TransportMulticastInner {
             link: Arc::new(RwLock::new(None)),
             callback: Arc::new(RwLock::new(None)),
             timer: Arc::new(Timer::new(false)),
+            ignored: Arc::new(RwLock::new(
+                config
+                    .manager
+                    .config
+                    .multicast
+                    .ignore
+                    .iter()
+                    .copied()
+                    .collect(),
+            )),
+            peer_events: Arc::new(Mutex::new(vec![])),
             #[cfg(feature = "stats")]
             stats: Arc::new(TransportMulticastStatsAtomic::default()),
+            #[cfg(feature = "stats")]
+            priority_stats: Arc::new(PriorityStatsAtomic::default()),
         };
 
         let mut w_guard = zwrite!(ti.link);
@@ -148,6 +211,15 @@ impl TransportMulticastInner {
         Ok(ti)
     }
 
+    #[cfg(feature = "stats")]
+    pub(crate) fn transport_stats(&self) -> TransportStats {
+        transport_stats!(
+            self.stats.snapshot(),
+            self.priority_stats.snapshot(),
+            unauthorized: rx_t_unauthorized,
+        )
+    }
+
     pub(super) fn set_callback(&self, callback: Arc<dyn TransportMulticastEventHandler>) {
         let mut guard = zwrite!(self.callback);
         *guard = Some(callback);
@@ -262,6 +334,17 @@ impl TransportMulticastInner {
         match guard.as_mut() {
             Some(l) => {
                 assert!(!self.conduit_tx.is_empty());
+                let join_token = match self.manager.config.multicast.join_key.as_ref() {
+                    Some(key) => Some(join_token(
+                        key,
+                        self.manager.config.version,
+                        self.manager.config.whatami,
+                        &self.manager.config.zid,
+                        self.manager.config.multicast.lease,
+                        self.manager.config.sn_resolution,
+                    )?),
+                    None => None,
+                };
                 let config = TransportLinkMulticastConfig {
                     version: self.manager.config.version,
                     zid: self.manager.config.zid,
@@ -271,6 +354,7 @@ impl TransportMulticastInner {
                     join_interval: self.manager.config.multicast.join_interval,
                     sn_resolution: self.manager.config.sn_resolution,
                     batch_size,
+                    join_token,
                 };
                 l.start_tx(config, self.conduit_tx.clone());
                 Ok(())
@@ -361,6 +445,7 @@ impl TransportMulticastInner {
                 let tcr = TransportConduitRx::make(
                     join.sn_resolution,
                     self.manager.config.defrag_buff_size,
+                    &self.manager.config.memory,
                 )?;
                 tcr.sync(sn)?;
                 vec![tcr]
@@ -371,6 +456,7 @@ impl TransportMulticastInner {
                     let tcr = TransportConduitRx::make(
                         join.sn_resolution,
                         self.manager.config.defrag_buff_size,
+                        &self.manager.config.memory,
                     )?;
                     tcr.sync(*sn)?;
                     tcrs.push(tcr);
@@ -402,10 +488,17 @@ impl TransportMulticastInner {
             handle,
             conduit_rx,
             handler,
+            joined: Instant::now(),
+            last_seen: AtomicU64::new(0),
         };
         {
             zwrite!(self.peers).insert(locator.clone(), peer);
         }
+        self.notify_peer_event(TransportMulticastPeerEvent::Joined {
+            zid: join.zid,
+            whatami: join.whatami,
+            locator: locator.clone(),
+        });
 
         // Add the event to the timer
         self.timer.add(event);
@@ -440,10 +533,60 @@ impl TransportMulticastInner {
             peer.handler.closing();
             drop(guard);
             peer.handler.closed();
+            self.notify_peer_event(TransportMulticastPeerEvent::Left {
+                zid: peer.zid,
+                locator: locator.clone(),
+                reason,
+            });
         }
         Ok(())
     }
 
+    /// Removes the peer `zid`, which joins again with its next Join message unless `ignore`.
+    /// Returns `false` if the peer was not found.
+    pub(super) fn evict_peer(&self, zid: &ZenohId, ignore: bool) -> ZResult<bool> {
+        if ignore {
+            zwrite!(self.ignored).insert(*zid);
+        }
+        let locator = zread!(self.peers)
+            .values()
+            .find(|p| &p.zid == zid)
+            .map(|p| p.locator.clone());
+        match locator {
+            Some(locator) => {
+                self.del_peer(&locator, tmsg::close_reason::GENERIC)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub(super) fn is_ignored(&self, zid: &ZenohId) -> bool {
+        zread!(self.ignored).contains(zid)
+    }
+
+    pub(super) fn unignore_peer(&self, zid: &ZenohId) -> bool {
+        zwrite!(self.ignored).remove(zid)
+    }
+
+    pub(super) fn subscribe_peer_events(&self) -> flume::Receiver<TransportMulticastPeerEvent> {
+        let (tx, rx) = flume::unbounded();
+        zlock!(self.peer_events).push(tx);
+        rx
+    }
+
+    fn notify_peer_event(&self, event: TransportMulticastPeerEvent) {
+        // The listeners which have been dropped are removed
+        zlock!(self.peer_events).retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    pub(super) fn get_peers_info(&self) -> Vec<TransportMulticastPeerInfo> {
+        zread!(self.peers)
+            .values()
+            .map(TransportMulticastPeer::info)
+            .collect()
+    }
+
     pub(super) fn get_peers(&self) -> Vec<TransportPeer> {
         zread!(self.peers)
             .values()
@@ -75,15 +75,22 @@ impl TransportMulticastInner {
             ZenohBody::Query(_) => self.stats.inc_tx_z_query_msgs(1),
             ZenohBody::Declare(_) => self.stats.inc_tx_z_declare_msgs(1),
             ZenohBody::LinkStateList(_) => self.stats.inc_tx_z_linkstate_msgs(1),
+            // Only accounted in the zenoh messages
+            ZenohBody::CongestionDrop(_) => {}
         }
 
+        #[cfg(feature = "stats")]
+        let priority = msg.channel.priority;
+
         let res = self.schedule_on_link(msg);
 
         #[cfg(feature = "stats")]
         if res {
             self.stats.inc_tx_z_msgs(1);
+            self.priority_stats.inc_tx_msgs(priority);
         } else {
             self.stats.inc_tx_z_dropped(1);
+            self.priority_stats.inc_tx_dropped(priority);
         }
 
         res
@@ -0,0 +1,95 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! The pacing of the tx queues of the links: the batches of a paced priority are spread over
+//! time, at most a given number of bytes being sent per interval.
+//!
+//! Unlike rate limiting, nothing is dropped: the batches exceeding the budget of an interval wait
+//! in the queue for the next one, smoothing out the microbursts of bursty publishers that would
+//! otherwise overflow the buffers of the switches on the path.
+use std::time::{Duration, Instant};
+use zenoh_protocol::core::Priority;
+
+/// The pacing of a tx queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueuePacing {
+    /// The pacing interval.
+    pub interval: Duration,
+    /// The maximum number of bytes of each priority sent per interval, unpaced if `None`.
+    pub bytes: [Option<usize>; Priority::NUM],
+}
+
+/// Tracks the bytes sent by a paced priority queue in the current interval.
+#[derive(Debug)]
+pub(crate) struct Pacer {
+    bytes: usize,
+    interval: Duration,
+    start: Instant,
+    sent: usize,
+}
+
+impl Pacer {
+    pub(crate) fn new(bytes: usize, interval: Duration) -> Self {
+        Self {
+            bytes,
+            interval,
+            start: Instant::now(),
+            sent: 0,
+        }
+    }
+
+    /// Returns how long to wait before sending, if the budget of the current interval is spent.
+    ///
+    /// A batch is always sent when some budget is left, so that batches larger than the budget
+    /// are still sent, one per interval.
+    pub(crate) fn delay(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed >= self.interval {
+            self.start = now;
+            self.sent = 0;
+            return None;
+        }
+        (self.sent >= self.bytes).then(|| self.interval - elapsed)
+    }
+
+    pub(crate) fn account(&mut self, len: usize) {
+        self.sent += len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pacer() {
+        let interval = Duration::from_millis(10);
+        let mut pacer = Pacer::new(100, interval);
+        let start = pacer.start;
+
+        // The budget of the first interval
+        assert_eq!(pacer.delay(start), None);
+        pacer.account(60);
+        assert_eq!(pacer.delay(start), None);
+        pacer.account(60);
+        assert_eq!(
+            pacer.delay(start + Duration::from_millis(4)),
+            Some(Duration::from_millis(6))
+        );
+
+        // A new interval resets the budget
+        assert_eq!(pacer.delay(start + interval), None);
+        pacer.account(1_000);
+        assert_eq!(pacer.delay(start + interval), Some(interval));
+    }
+}
@@ -14,9 +14,11 @@
 use super::Primitives;
 use crate::TransportPeerEventHandler;
 use std::any::Any;
+use std::time::Duration;
 use zenoh_link::Link;
 use zenoh_protocol::zenoh::{
-    Data, Declaration, Declare, LinkStateList, Pull, Query, Unit, ZenohBody, ZenohMessage,
+    CongestionDrop, Data, Declaration, Declare, LinkStateList, Pull, Query, Unit, ZenohBody,
+    ZenohMessage,
 };
 use zenoh_result::{bail, ZResult};
 
@@ -75,6 +77,7 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                 payload,
                 congestion_control,
                 reply_context,
+                ..
             }) => match reply_context {
                 None => {
                     self.primitives.send_data(
@@ -88,8 +91,14 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                 }
                 Some(rep) => match rep.replier {
                     Some(replier) => {
-                        self.primitives
-                            .send_reply_data(rep.qid, replier.id, key, data_info, payload);
+                        self.primitives.send_reply_data(
+                            rep.qid,
+                            replier.id,
+                            key,
+                            data_info,
+                            payload,
+                            msg.channel.priority,
+                        );
                     }
                     None => {
                         bail!("ReplyData with no replier_id")
@@ -100,7 +109,8 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
             ZenohBody::Unit(Unit { reply_context, .. }) => {
                 if let Some(rep) = reply_context {
                     if rep.is_final() {
-                        self.primitives.send_reply_final(rep.qid);
+                        self.primitives
+                            .send_reply_final(rep.qid, msg.channel.priority);
                     }
                 }
             }
@@ -111,8 +121,9 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                 qid,
                 target,
                 consolidation,
+                timeout,
+                destinations,
                 body,
-                ..
             }) => {
                 self.primitives.send_query(
                     &key,
@@ -120,7 +131,10 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                     qid,
                     target.unwrap_or_default(),
                     consolidation,
+                    timeout.map(Duration::from_millis),
+                    destinations.as_deref(),
                     body,
+                    msg.channel.priority,
                     msg.routing_context,
                 );
             }
@@ -136,6 +150,10 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
             }
 
             ZenohBody::LinkStateList(LinkStateList { .. }) => {}
+
+            ZenohBody::CongestionDrop(CongestionDrop { key_expr, dropped }) => {
+                self.primitives.send_congestion_drop(&key_expr, dropped);
+            }
         }
 
         Ok(())
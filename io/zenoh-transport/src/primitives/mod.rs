@@ -16,15 +16,25 @@ mod mux;
 
 pub use demux::*;
 pub use mux::*;
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
 use zenoh_protocol::{
     core::{
-        Channel, CongestionControl, ConsolidationMode, QueryTarget, QueryableInfo, SubInfo,
-        WireExpr, ZInt, ZenohId,
+        Channel, CongestionControl, ConsolidationMode, Priority, QueryTarget, QueryableInfo,
+        SubInfo, WireExpr, ZInt, ZenohId,
     },
     zenoh::{DataInfo, QueryBody, RoutingContext},
 };
 
+/// A data message of a batch sent with [`Primitives::send_data_batch`].
+#[derive(Debug, Clone)]
+pub struct BatchedData {
+    pub key_expr: WireExpr<'static>,
+    pub payload: ZBuf,
+    pub data_info: Option<DataInfo>,
+    pub routing_context: Option<RoutingContext>,
+}
+
 pub trait Primitives: Send + Sync {
     fn decl_resource(&self, expr_id: ZInt, key_expr: &WireExpr);
     fn forget_resource(&self, expr_id: ZInt);
@@ -58,6 +68,29 @@ pub trait Primitives: Send + Sync {
         routing_context: Option<RoutingContext>,
     );
 
+    /// Sends data messages sharing the same channel, without any other message being sent on
+    /// that channel in between where supported.
+    fn send_data_batch(
+        &self,
+        batch: Vec<BatchedData>,
+        channel: Channel,
+        congestion_control: CongestionControl,
+    ) {
+        for data in batch {
+            self.send_data(
+                &data.key_expr,
+                data.payload,
+                channel,
+                congestion_control,
+                data.data_info,
+                data.routing_context,
+            );
+        }
+    }
+
+    /// Sends a query on the conduit of the given `priority`, whose replies are expected to be
+    /// sent on the same conduit. The `timeout` is the time left before the query expires at
+    /// its origin, if known.
     #[allow(clippy::too_many_arguments)]
     fn send_query(
         &self,
@@ -66,7 +99,10 @@ pub trait Primitives: Send + Sync {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationMode,
+        timeout: Option<Duration>,
+        destinations: Option<&[ZenohId]>,
         body: Option<QueryBody>,
+        priority: Priority,
         routing_context: Option<RoutingContext>,
     );
 
@@ -77,9 +113,10 @@ pub trait Primitives: Send + Sync {
         key_expr: WireExpr,
         info: Option<DataInfo>,
         payload: ZBuf,
+        priority: Priority,
     );
 
-    fn send_reply_final(&self, qid: ZInt);
+    fn send_reply_final(&self, qid: ZInt, priority: Priority);
 
     fn send_pull(
         &self,
@@ -90,6 +127,10 @@ pub trait Primitives: Send + Sync {
     );
 
     fn send_close(&self);
+
+    /// Notifies that `dropped` best-effort data messages on `key_expr` received from this face
+    /// have been dropped because of congestion.
+    fn send_congestion_drop(&self, _key_expr: &str, _dropped: ZInt) {}
 }
 
 #[derive(Default)]
@@ -143,7 +184,10 @@ impl Primitives for DummyPrimitives {
         _qid: ZInt,
         _target: QueryTarget,
         _consolidation: ConsolidationMode,
+        _timeout: Option<Duration>,
+        _destinations: Option<&[ZenohId]>,
         _body: Option<QueryBody>,
+        _priority: Priority,
         _routing_context: Option<RoutingContext>,
     ) {
     }
@@ -154,9 +198,10 @@ impl Primitives for DummyPrimitives {
         _key_expr: WireExpr,
         _info: Option<DataInfo>,
         _payload: ZBuf,
+        _priority: Priority,
     ) {
     }
-    fn send_reply_final(&self, _qid: ZInt) {}
+    fn send_reply_final(&self, _qid: ZInt, _priority: Priority) {}
     fn send_pull(
         &self,
         _is_final: bool,
@@ -12,27 +12,186 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::super::TransportUnicast;
-use super::Primitives;
+use super::{BatchedData, Primitives};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
+use zenoh_core::{executor, zlock};
 use zenoh_protocol::{
     core::{
-        Channel, CongestionControl, ConsolidationMode, QueryTarget, QueryableInfo, SubInfo,
-        WireExpr, ZInt, ZenohId,
+        Channel, CongestionControl, ConsolidationMode, Priority, QueryTarget, QueryableInfo,
+        SubInfo, WireExpr, ZInt, ZenohId,
     },
+    transport::tmsg,
     zenoh::{
         zmsg, DataInfo, Declaration, ForgetPublisher, ForgetQueryable, ForgetResource,
         ForgetSubscriber, Publisher, QueryBody, Queryable, ReplierInfo, ReplyContext, Resource,
-        RoutingContext, Subscriber, ZenohMessage,
+        RoutingContext, Subscriber, ZenohBody, ZenohMessage,
     },
 };
 
+// The maximum number of declarations sent in a single declare message
+const MAX_COALESCED_DECLARATIONS: usize = 256;
+// The time during which the declarations are coalesced before being sent
+const COALESCING_DELAY: Duration = Duration::from_millis(1);
+
+// The query timeout in milliseconds, rounded up so that a remaining time under 1 ms is not sent as 0
+fn timeout_millis(timeout: Duration) -> ZInt {
+    let millis = timeout.as_millis() as ZInt;
+    if timeout.subsec_nanos() % 1_000_000 == 0 {
+        millis
+    } else {
+        millis + 1
+    }
+}
+
+#[derive(Default)]
+struct PendingDeclarations {
+    routing_context: Option<RoutingContext>,
+    declarations: Vec<Declaration>,
+}
+
+impl PendingDeclarations {
+    fn take(&mut self) -> Option<(ZenohMessage, usize)> {
+        if self.declarations.is_empty() {
+            return None;
+        }
+        let declarations = std::mem::take(&mut self.declarations);
+        let count = declarations.len();
+        let msg = ZenohMessage::make_declare(declarations, self.routing_context.take(), None);
+        Some((msg, count))
+    }
+}
+
+#[derive(Default)]
+struct Declarations {
+    pending: Mutex<PendingDeclarations>,
+    // The number of declarations not sent yet, pending or being sent, so that the other
+    // messages do not take the locks when there are none
+    unsent: AtomicUsize,
+    // Taken before releasing `pending` and held while sending, so that the declare messages
+    // are sent in order without holding `pending`
+    sending: Mutex<()>,
+}
+
+impl Declarations {
+    fn flush(&self, handler: &TransportUnicast, egress: &Option<EgressFilter>) {
+        if self.unsent.load(Ordering::Acquire) > 0 {
+            self.send(zlock!(self.pending), handler, egress);
+        }
+    }
+
+    // Sends the pending declarations, after the ones being sent by the other threads
+    fn send(
+        &self,
+        mut pending: MutexGuard<'_, PendingDeclarations>,
+        handler: &TransportUnicast,
+        egress: &Option<EgressFilter>,
+    ) {
+        let taken = pending.take();
+        let _sending = zlock!(self.sending);
+        drop(pending);
+        if let Some((msg, count)) = taken {
+            send(handler, egress, msg);
+            self.unsent.fetch_sub(count, Ordering::Release);
+        }
+    }
+}
+
+/// A filter applied on the messages before they are sent, returning the message to send,
+/// possibly modified, or `None` to drop it.
+pub type EgressFilter = Arc<dyn Fn(ZenohMessage) -> Option<ZenohMessage> + Send + Sync>;
+
+fn send(handler: &TransportUnicast, egress: &Option<EgressFilter>, msg: ZenohMessage) {
+    let msg = match egress {
+        Some(filter) => match filter(msg) {
+            Some(msg) => msg,
+            None => return,
+        },
+        None => msg,
+    };
+    let _ = handler.handle_message(msg);
+}
+
 pub struct Mux {
     handler: TransportUnicast,
+    // The declarations are coalesced during `COALESCING_DELAY` or until another message is sent,
+    // so that a burst of declarations (e.g. the subscribers and queryables declared at startup)
+    // is sent in a few declare messages
+    declarations: Arc<Declarations>,
+    egress: Option<EgressFilter>,
 }
 
 impl Mux {
     pub fn new(handler: TransportUnicast) -> Mux {
-        Mux { handler }
+        Mux {
+            handler,
+            declarations: Arc::new(Declarations::default()),
+            egress: None,
+        }
+    }
+
+    /// Applies `egress` on all the messages sent by this mux.
+    pub fn with_egress(mut self, egress: EgressFilter) -> Mux {
+        self.egress = Some(egress);
+        self
+    }
+
+    fn declare(&self, declaration: Declaration, routing_context: Option<RoutingContext>) {
+        let mut pending = zlock!(self.declarations.pending);
+        while !pending.declarations.is_empty()
+            && (pending.routing_context != routing_context
+                || pending.declarations.len() >= MAX_COALESCED_DECLARATIONS)
+        {
+            self.declarations.send(pending, &self.handler, &self.egress);
+            pending = zlock!(self.declarations.pending);
+        }
+        if pending.declarations.is_empty() {
+            pending.routing_context = routing_context;
+            let declarations = self.declarations.clone();
+            let handler = self.handler.clone();
+            let egress = self.egress.clone();
+            executor::spawn(async move {
+                async_std::task::sleep(COALESCING_DELAY).await;
+                declarations.flush(&handler, &egress);
+            });
+        }
+        pending.declarations.push(declaration);
+        self.declarations.unsent.fetch_add(1, Ordering::Release);
+    }
+
+    // The other messages are sent after the pending declarations, which they may refer to
+    fn flush_declarations(&self) {
+        self.declarations.flush(&self.handler, &self.egress);
+    }
+
+    fn send(&self, msg: ZenohMessage) {
+        send(&self.handler, &self.egress, msg)
+    }
+
+    // Adapts the Data to the extensions agreed with the peer: the trace contexts are only sent
+    // to the peers understanding them, which fail to decode them otherwise, and the Data without
+    // DataInfo nor payload are encoded as Units to the peers that agreed on it
+    fn with_extensions(&self, mut msg: ZenohMessage) -> ZenohMessage {
+        if let ZenohBody::Data(data) = &mut msg.body {
+            if let Some(info) = data.data_info.as_mut() {
+                if info.trace_context.is_some()
+                    && !self
+                        .handler
+                        .has_extension(tmsg::init_options::TRACE_CONTEXT)
+                        .unwrap_or(false)
+                {
+                    info.trace_context = None;
+                }
+            }
+            data.unit = data.is_unit()
+                && self
+                    .handler
+                    .has_extension(tmsg::init_options::UNIT_DATA)
+                    .unwrap_or(false);
+        }
+        msg
     }
 }
 
@@ -42,18 +201,12 @@ impl Primitives for Mux {
             expr_id,
             key: key_expr.to_owned(),
         });
-        let decls = vec![d];
-        let _ = self
-            .handler
-            .handle_message(ZenohMessage::make_declare(decls, None, None));
+        self.declare(d, None);
     }
 
     fn forget_resource(&self, expr_id: ZInt) {
         let d = Declaration::ForgetResource(ForgetResource { expr_id });
-        let decls = vec![d];
-        let _ = self
-            .handler
-            .handle_message(ZenohMessage::make_declare(decls, None, None));
+        self.declare(d, None);
     }
 
     fn decl_subscriber(
@@ -66,40 +219,28 @@ impl Primitives for Mux {
             key: key_expr.to_owned(),
             info: sub_info.clone(),
         });
-        let decls = vec![d];
-        let _ =
-            self.handler
-                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+        self.declare(d, routing_context);
     }
 
     fn forget_subscriber(&self, key_expr: &WireExpr, routing_context: Option<RoutingContext>) {
         let d = Declaration::ForgetSubscriber(ForgetSubscriber {
             key: key_expr.to_owned(),
         });
-        let decls = vec![d];
-        let _ =
-            self.handler
-                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+        self.declare(d, routing_context);
     }
 
     fn decl_publisher(&self, key_expr: &WireExpr, routing_context: Option<RoutingContext>) {
         let d = Declaration::Publisher(Publisher {
             key: key_expr.to_owned(),
         });
-        let decls = vec![d];
-        let _ =
-            self.handler
-                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+        self.declare(d, routing_context);
     }
 
     fn forget_publisher(&self, key_expr: &WireExpr, routing_context: Option<RoutingContext>) {
         let d = Declaration::ForgetPublisher(ForgetPublisher {
             key: key_expr.to_owned(),
         });
-        let decls = vec![d];
-        let _ =
-            self.handler
-                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+        self.declare(d, routing_context);
     }
 
     fn decl_queryable(
@@ -112,20 +253,14 @@ impl Primitives for Mux {
             key: key_expr.to_owned(),
             info: qabl_info.clone(),
         });
-        let decls = vec![d];
-        let _ =
-            self.handler
-                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+        self.declare(d, routing_context);
     }
 
     fn forget_queryable(&self, key_expr: &WireExpr, routing_context: Option<RoutingContext>) {
         let d = Declaration::ForgetQueryable(ForgetQueryable {
             key: key_expr.to_owned(),
         });
-        let decls = vec![d];
-        let _ =
-            self.handler
-                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+        self.declare(d, routing_context);
     }
 
     fn send_data(
@@ -137,7 +272,8 @@ impl Primitives for Mux {
         data_info: Option<DataInfo>,
         routing_context: Option<RoutingContext>,
     ) {
-        let _ = self.handler.handle_message(ZenohMessage::make_data(
+        self.flush_declarations();
+        self.send(self.with_extensions(ZenohMessage::make_data(
             key_expr.to_owned(),
             payload,
             channel,
@@ -146,7 +282,36 @@ impl Primitives for Mux {
             routing_context,
             None,
             None,
-        ));
+        )));
+    }
+
+    fn send_data_batch(
+        &self,
+        batch: Vec<BatchedData>,
+        channel: Channel,
+        congestion_control: CongestionControl,
+    ) {
+        self.flush_declarations();
+        let messages = batch
+            .into_iter()
+            .map(|data| {
+                self.with_extensions(ZenohMessage::make_data(
+                    data.key_expr,
+                    data.payload,
+                    channel,
+                    congestion_control,
+                    data.data_info,
+                    data.routing_context,
+                    None,
+                    None,
+                ))
+            })
+            .filter_map(|msg| match &self.egress {
+                Some(filter) => filter(msg),
+                None => Some(msg),
+            })
+            .collect();
+        let _ = self.handler.schedule_batch(messages);
     }
 
     fn send_query(
@@ -156,7 +321,10 @@ impl Primitives for Mux {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationMode,
+        timeout: Option<Duration>,
+        destinations: Option<&[ZenohId]>,
         body: Option<QueryBody>,
+        priority: Priority,
         routing_context: Option<RoutingContext>,
     ) {
         let target_opt = if target == QueryTarget::default() {
@@ -164,16 +332,34 @@ impl Primitives for Mux {
         } else {
             Some(target)
         };
-        let _ = self.handler.handle_message(ZenohMessage::make_query(
+        // The peers that did not agree on the query timeout extension fail to decode it
+        let timeout = timeout.filter(|_| {
+            self.handler
+                .has_extension(tmsg::init_options::QUERY_TIMEOUT)
+                .unwrap_or(false)
+        });
+        // The queries are forwarded to the peers not understanding the destinations without them,
+        // their replies being filtered out by the querying session
+        let destinations = destinations.filter(|_| {
+            self.handler
+                .has_extension(tmsg::init_options::QUERY_DESTINATIONS)
+                .unwrap_or(false)
+        });
+        self.flush_declarations();
+        let mut msg = ZenohMessage::make_query(
             key_expr.to_owned(),
             parameters.to_owned(),
             qid,
             target_opt,
             consolidation,
+            timeout.map(timeout_millis),
+            destinations.map(|d| d.to_vec()),
             body,
             routing_context,
             None,
-        ));
+        );
+        msg.channel.priority = priority;
+        self.send(msg);
     }
 
     fn send_reply_data(
@@ -183,22 +369,31 @@ impl Primitives for Mux {
         key_expr: WireExpr,
         data_info: Option<DataInfo>,
         payload: ZBuf,
+        priority: Priority,
     ) {
-        let _ = self.handler.handle_message(ZenohMessage::make_data(
+        self.flush_declarations();
+        self.send(self.with_extensions(ZenohMessage::make_data(
             key_expr.to_owned(),
             payload,
-            zmsg::default_channel::REPLY,
+            Channel {
+                priority,
+                ..zmsg::default_channel::REPLY
+            },
             zmsg::default_congestion_control::REPLY,
             data_info,
             None,
             Some(ReplyContext::new(qid, Some(ReplierInfo { id: replier_id }))),
             None,
-        ));
+        )));
     }
 
-    fn send_reply_final(&self, qid: ZInt) {
-        let _ = self.handler.handle_message(ZenohMessage::make_unit(
-            zmsg::default_channel::REPLY,
+    fn send_reply_final(&self, qid: ZInt, priority: Priority) {
+        self.flush_declarations();
+        self.send(ZenohMessage::make_unit(
+            Channel {
+                priority,
+                ..zmsg::default_channel::REPLY
+            },
             zmsg::default_congestion_control::REPLY,
             Some(ReplyContext::new(qid, None)),
             None,
@@ -212,7 +407,8 @@ impl Primitives for Mux {
         pull_id: ZInt,
         max_samples: &Option<ZInt>,
     ) {
-        let _ = self.handler.handle_message(ZenohMessage::make_pull(
+        self.flush_declarations();
+        self.send(ZenohMessage::make_pull(
             is_final,
             key_expr.to_owned(),
             pull_id,
@@ -222,6 +418,22 @@ impl Primitives for Mux {
     }
 
     fn send_close(&self) {
+        self.flush_declarations();
         // self.handler.closing().await;
     }
+
+    fn send_congestion_drop(&self, key_expr: &str, dropped: ZInt) {
+        // The peers that did not agree on the congestion drop extension fail to decode it
+        if self
+            .handler
+            .has_extension(tmsg::init_options::CONGESTION_DROP)
+            .unwrap_or(false)
+        {
+            self.send(ZenohMessage::make_congestion_drop(
+                key_expr.to_string(),
+                dropped,
+                None,
+            ));
+        }
+    }
 }
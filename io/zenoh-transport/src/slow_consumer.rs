@@ -0,0 +1,269 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Detection of the slow consumers: the peers whose tx queue and the subscribers whose delivery
+//! stay saturated longer than a threshold.
+//!
+//! Each slow consumer is reported once with a [`SlowConsumerEvent`], logged as a warning and
+//! dispatched to the subscribers of the [`SlowConsumerEvents`] of its transport manager, until it
+//! keeps up again.
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_core::zlock;
+
+// The maximum number of distinct key expressions reported for a saturation
+const MAX_KEY_EXPRS: usize = 8;
+
+/// An event related to a slow consumer.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SlowConsumerEvent {
+    /// The tx queue of a peer has been saturated for `duration_ms`, `dropped` messages have been
+    /// dropped meanwhile.
+    SlowPeer {
+        zid: String,
+        key_exprs: Vec<String>,
+        duration_ms: u64,
+        dropped: usize,
+    },
+    /// The delivery to a subscriber has been blocked for `duration_ms`.
+    SlowSubscriber {
+        id: usize,
+        key_expr: String,
+        duration_ms: u64,
+    },
+    /// A peer previously reported slow keeps up again.
+    PeerRecovered { zid: String, dropped: usize },
+    /// A subscriber previously reported slow keeps up again.
+    SubscriberRecovered { id: usize, key_expr: String },
+}
+
+/// The slow consumer events of a [`TransportManager`](crate::TransportManager), i.e. of its
+/// transports and of the subscribers of the sessions using it.
+#[derive(Default)]
+pub struct SlowConsumerEvents {
+    subscribers: Mutex<Vec<flume::Sender<SlowConsumerEvent>>>,
+}
+
+impl SlowConsumerEvents {
+    /// Subscribes to the slow consumer events.
+    pub fn subscribe(&self) -> flume::Receiver<SlowConsumerEvent> {
+        let (tx, rx) = flume::unbounded();
+        zlock!(self.subscribers).push(tx);
+        rx
+    }
+
+    /// Logs `event` and notifies it to all the subscribers.
+    pub fn notify(&self, event: SlowConsumerEvent) {
+        log_event(&event);
+        zlock!(self.subscribers).retain(|s| s.send(event.clone()).is_ok());
+    }
+}
+
+fn log_event(event: &SlowConsumerEvent) {
+    match event {
+        SlowConsumerEvent::SlowPeer {
+            zid,
+            key_exprs,
+            duration_ms,
+            dropped,
+        } => tracing::warn!(
+            %zid,
+            ?key_exprs,
+            duration_ms,
+            dropped,
+            "Slow consumer: tx queue saturated"
+        ),
+        SlowConsumerEvent::SlowSubscriber {
+            id,
+            key_expr,
+            duration_ms,
+        } => tracing::warn!(
+            id,
+            %key_expr,
+            duration_ms,
+            "Slow consumer: subscriber delivery blocked"
+        ),
+        SlowConsumerEvent::PeerRecovered { zid, dropped } => {
+            tracing::info!(%zid, dropped, "Slow consumer recovered")
+        }
+        SlowConsumerEvent::SubscriberRecovered { id, key_expr } => {
+            tracing::info!(id, %key_expr, "Slow consumer recovered")
+        }
+    }
+}
+
+/// A saturation lasting longer than the threshold, reported once.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Saturation {
+    pub duration: Duration,
+    pub dropped: usize,
+    pub key_exprs: Vec<String>,
+}
+
+#[derive(Default)]
+struct SaturationState {
+    since: Option<Instant>,
+    dropped: usize,
+    key_exprs: Vec<String>,
+    reported: bool,
+}
+
+/// Tracks the saturation of a consumer.
+///
+/// A consumer is saturated from the first message it couldn't take without waiting, until it
+/// takes one without waiting again.
+pub struct SaturationDetector {
+    threshold: Duration,
+    saturated: AtomicBool,
+    reported: AtomicBool,
+    state: Mutex<SaturationState>,
+}
+
+impl SaturationDetector {
+    /// Creates a detector reporting the saturations longer than `threshold`, disabled if zero.
+    pub fn new(threshold: Duration) -> Self {
+        SaturationDetector {
+            threshold,
+            saturated: AtomicBool::new(false),
+            reported: AtomicBool::new(false),
+            state: Mutex::new(SaturationState::default()),
+        }
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > Duration::ZERO
+    }
+
+    #[inline]
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Returns `true` if the ongoing saturation has been reported.
+    #[inline]
+    pub fn is_reported(&self) -> bool {
+        self.reported.load(Ordering::Acquire)
+    }
+
+    /// Records a message that couldn't be taken without waiting since `since`, and that has been
+    /// dropped if `dropped`. Returns the saturation the first time it exceeds the threshold.
+    pub fn saturated(
+        &self,
+        since: Instant,
+        key_expr: Option<String>,
+        dropped: bool,
+    ) -> Option<Saturation> {
+        let mut state = zlock!(self.state);
+        self.saturated.store(true, Ordering::Release);
+        let since = *state.since.get_or_insert(since);
+        if dropped {
+            state.dropped += 1;
+        }
+        if let Some(key_expr) = key_expr {
+            if state.key_exprs.len() < MAX_KEY_EXPRS && !state.key_exprs.contains(&key_expr) {
+                state.key_exprs.push(key_expr);
+            }
+        }
+        let duration = since.elapsed();
+        if state.reported || duration < self.threshold {
+            return None;
+        }
+        state.reported = true;
+        self.reported.store(true, Ordering::Release);
+        Some(Saturation {
+            duration,
+            dropped: state.dropped,
+            key_exprs: state.key_exprs.clone(),
+        })
+    }
+
+    /// Records a message taken without waiting. Returns the number of messages dropped during the
+    /// saturation if it had been reported.
+    #[inline]
+    pub fn cleared(&self) -> Option<usize> {
+        if !self.saturated.load(Ordering::Acquire) {
+            return None;
+        }
+        let mut state = zlock!(self.state);
+        self.saturated.store(false, Ordering::Release);
+        self.reported.store(false, Ordering::Release);
+        let state = std::mem::take(&mut *state);
+        state.reported.then_some(state.dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saturation_detector() {
+        let detector = SaturationDetector::new(Duration::from_millis(50));
+        assert!(detector.is_enabled());
+        assert_eq!(detector.cleared(), None);
+
+        // A short saturation is not reported
+        assert_eq!(detector.saturated(Instant::now(), None, true), None);
+        assert_eq!(detector.cleared(), None);
+
+        // A long saturation is reported once
+        assert_eq!(
+            detector.saturated(Instant::now(), Some("a".into()), true),
+            None
+        );
+        std::thread::sleep(Duration::from_millis(60));
+        let saturation = detector
+            .saturated(Instant::now(), Some("b".into()), true)
+            .unwrap();
+        assert!(saturation.duration >= Duration::from_millis(50));
+        assert_eq!(saturation.dropped, 2);
+        assert_eq!(saturation.key_exprs, vec!["a".to_string(), "b".to_string()]);
+        assert!(detector.is_reported());
+        assert_eq!(
+            detector.saturated(Instant::now(), Some("a".into()), true),
+            None
+        );
+        assert_eq!(detector.cleared(), Some(3));
+        assert!(!detector.is_reported());
+        assert_eq!(detector.cleared(), None);
+
+        assert!(!SaturationDetector::new(Duration::ZERO).is_enabled());
+    }
+
+    #[test]
+    fn slow_consumer_events() {
+        let events = SlowConsumerEvents::default();
+        let other = SlowConsumerEvents::default();
+        let rx = events.subscribe();
+        let other_rx = other.subscribe();
+        drop(events.subscribe());
+
+        events.notify(SlowConsumerEvent::PeerRecovered {
+            zid: "a1".into(),
+            dropped: 1,
+        });
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(SlowConsumerEvent::PeerRecovered { dropped: 1, .. })
+        ));
+        // The events of a manager are not notified to the subscribers of another one
+        assert!(other_rx.try_recv().is_err());
+        // And the dropped subscribers are forgotten
+        assert_eq!(zlock!(events.subscribers).len(), 1);
+    }
+}
@@ -14,7 +14,8 @@
 use super::{init_syn, AResult};
 use crate::{
     unicast::establishment::{
-        authenticator::AuthenticatedPeerLink, Cookie, EstablishmentProperties, Zenoh060Cookie,
+        authenticator::AuthenticatedPeerLink, replay, Cookie, EstablishmentProperties,
+        HandshakeError, HandshakeFailure, Zenoh060Cookie,
     },
     TransportManager,
 };
@@ -49,6 +50,10 @@ pub(super) async fn send(
     // Build the fields for the InitAck message
     let whatami = manager.config.whatami;
     let azid = manager.config.zid;
+    // The preamble is only used if both sides enable it
+    let is_preamble = input.is_preamble && manager.config.unicast.preamble;
+    // Only the extensions supported by both sides are used on the transport
+    let extensions = input.extensions & manager.config.unicast.extensions;
     let sn_resolution = if agreed_sn_resolution == input.sn_resolution {
         None
     } else {
@@ -61,7 +66,10 @@ pub(super) async fn send(
         zid: input.zid,
         sn_resolution: agreed_sn_resolution,
         is_qos: input.is_qos,
+        is_preamble,
+        extensions,
         nonce: zasynclock!(manager.prng).gen_range(0..agreed_sn_resolution),
+        timestamp: replay::now_millis(),
         properties: EstablishmentProperties::new(),
     };
 
@@ -79,7 +87,13 @@ pub(super) async fn send(
                     .map(|x| x.value),
             )
             .await
-            .map_err(|e| (e, Some(tmsg::close_reason::INVALID)))?;
+            .map_err(|e| {
+                super::audit_auth_failure(auth_link, cookie.zid, pa, &e);
+                (
+                    HandshakeError(HandshakeFailure::AuthFailed, e).into(),
+                    Some(tmsg::close_reason::INVALID),
+                )
+            })?;
         // Add attachment property if available
         if let Some(att) = att.take() {
             ps_attachment
@@ -133,6 +147,8 @@ pub(super) async fn send(
         azid,
         sn_resolution,
         input.is_qos,
+        is_preamble,
+        extensions,
         cookie,
         attachment,
     );
@@ -13,7 +13,9 @@ use std::convert::TryFrom;
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::super::{AuthenticatedPeerLink, EstablishmentProperties};
+use super::super::{
+    AuthenticatedPeerLink, EstablishmentProperties, HandshakeError, HandshakeFailure,
+};
 use super::AResult;
 use crate::TransportManager;
 use zenoh_link::LinkUnicast;
@@ -33,6 +35,8 @@ pub(super) struct Output {
     pub(super) zid: ZenohId,
     pub(super) sn_resolution: ZInt,
     pub(super) is_qos: bool,
+    pub(super) is_preamble: bool,
+    pub(super) extensions: ZInt,
     pub(super) init_syn_properties: EstablishmentProperties,
 }
 pub(super) async fn recv(
@@ -90,9 +94,25 @@ pub(super) async fn recv(
             link,
             init_syn.zid
         );
+        let e = HandshakeError(HandshakeFailure::IncompatibleVersion, e.into());
         return Err((e.into(), Some(tmsg::close_reason::INVALID)));
     }
 
+    // Reject the handshake before any further work if the transports or links limits are reached
+    if let Some(reason) = manager.reject_inbound_unicast(&init_syn.zid) {
+        let e = zerror!(
+            "Rejecting InitSyn on {} from peer {}: {} limit reached",
+            link,
+            init_syn.zid,
+            tmsg::close_reason_to_str(reason)
+        );
+        let cause = match reason {
+            tmsg::close_reason::MAX_SESSIONS => HandshakeFailure::MaxSessions,
+            _ => HandshakeFailure::MaxLinks,
+        };
+        return Err((HandshakeError(cause, e.into()).into(), Some(reason)));
+    }
+
     // Validate the InitSyn with the peer authenticators
     let init_syn_properties: EstablishmentProperties = match msg.attachment.take() {
         Some(att) => EstablishmentProperties::try_from(&att)
@@ -105,6 +125,8 @@ pub(super) async fn recv(
         zid: init_syn.zid,
         sn_resolution: init_syn.sn_resolution,
         is_qos: init_syn.is_qos,
+        is_preamble: init_syn.is_preamble,
+        extensions: init_syn.extensions,
         init_syn_properties,
     };
     Ok(output)
@@ -16,18 +16,33 @@ mod init_syn;
 mod open_ack;
 mod open_syn;
 
-use crate::unicast::establishment::authenticator::AuthenticatedPeerLink;
+use crate::unicast::establishment::authenticator::{AuthenticatedPeerLink, PeerAuthenticator};
 use crate::unicast::establishment::{
-    close_link, transport_finalize, transport_init, InputFinalize,
+    close_link, transport_finalize, transport_init, HandshakeError, HandshakeFailure, InputFinalize,
 };
 use crate::TransportManager;
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
-use zenoh_protocol::transport::tmsg;
+use zenoh_protocol::{core::ZenohId, transport::tmsg};
 use zenoh_result::ZResult;
+use zenoh_util::audit::{self, AuditEvent};
 
 pub(super) type AError = (zenoh_result::Error, Option<u8>);
 pub(super) type AResult<T> = Result<T, AError>;
 
+fn audit_auth_failure(
+    auth_link: &AuthenticatedPeerLink,
+    zid: ZenohId,
+    pa: &PeerAuthenticator,
+    e: &zenoh_result::Error,
+) {
+    audit::record(AuditEvent::AuthenticationFailed {
+        zid: Some(zid),
+        link: auth_link.to_string(),
+        method: pa.id().name(),
+        reason: e.to_string(),
+    });
+}
+
 pub(crate) async fn accept_link(
     link: &LinkUnicast,
     manager: &TransportManager,
@@ -39,7 +54,11 @@ pub(crate) async fn accept_link(
             match $s {
                 Ok(output) => output,
                 Err((e, reason)) => {
-                    log::error!("{}", e);
+                    match reason {
+                        Some(tmsg::close_reason::MAX_SESSIONS)
+                        | Some(tmsg::close_reason::MAX_LINKS) => log::debug!("{}", e),
+                        _ => log::error!("{}", e),
+                    }
                     close_link(link, manager, auth_link, reason).await;
                     return Err(e);
                 }
@@ -59,6 +78,7 @@ pub(crate) async fn accept_link(
         sn_resolution: output.cookie.sn_resolution,
         is_shm: output.is_shm,
         is_qos: output.cookie.is_qos,
+        extensions: output.cookie.extensions,
     };
     let transport = step!(transport_init(manager, input)
         .await
@@ -90,8 +110,18 @@ pub(crate) async fn accept_link(
     step!(step!(transport
         .get_inner()
         .map_err(|e| (e, Some(tmsg::close_reason::INVALID))))
-    .add_link(link.clone(), LinkUnicastDirection::Inbound)
-    .map_err(|e| (e, Some(tmsg::close_reason::MAX_LINKS))));
+    .add_link(
+        link.clone(),
+        LinkUnicastDirection::Inbound,
+        output.cookie.is_preamble,
+    )
+    .map_err(|e| {
+        manager.count_rejection_unicast(tmsg::close_reason::MAX_LINKS);
+        (
+            HandshakeError(HandshakeFailure::MaxLinks, e).into(),
+            Some(tmsg::close_reason::MAX_LINKS),
+        )
+    }));
 
     // Sync the RX sequence number
     let _ = step!(transport
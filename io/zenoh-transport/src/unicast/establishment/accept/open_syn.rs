@@ -12,15 +12,15 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::super::{
-    authenticator::AuthenticatedPeerLink,
-    {Cookie, EstablishmentProperties},
+    authenticator::AuthenticatedPeerLink, replay, Cookie, EstablishmentProperties, HandshakeError,
+    HandshakeFailure,
 };
 use super::AResult;
 use crate::{unicast::establishment::cookie::Zenoh060Cookie, TransportManager};
 use std::{convert::TryFrom, time::Duration};
 use zenoh_buffers::reader::HasReader;
 use zenoh_codec::{RCodec, Zenoh060};
-use zenoh_core::{zasynclock, zasyncread};
+use zenoh_core::{zasynclock, zasyncread, zlock};
 use zenoh_crypto::hmac;
 use zenoh_link::LinkUnicast;
 use zenoh_protocol::{
@@ -93,6 +93,7 @@ pub(super) async fn recv(
     // Verify that the cookie is the one we sent
     if input.cookie_hash != hmac::digest(&encrypted) {
         let e = zerror!("Rejecting OpenSyn on: {}. Unkwown cookie.", link);
+        let e = HandshakeError(HandshakeFailure::InvalidCookie, e.into());
         return Err((e.into(), Some(tmsg::close_reason::INVALID)));
     }
 
@@ -104,12 +105,26 @@ pub(super) async fn recv(
         codec: Zenoh060::default(),
     };
     let mut cookie: Cookie = codec.read(&mut reader).map_err(|_| {
+        let e = zerror!("Decoding cookie failed");
         (
-            zerror!("Decoding cookie failed").into(),
+            HandshakeError(HandshakeFailure::InvalidCookie, e.into()).into(),
             Some(tmsg::close_reason::INVALID),
         )
     })?;
 
+    // Handshakes on connectionless links may be captured and replayed
+    if !link.is_streamed() {
+        zlock!(manager.state.unicast.replay_cache)
+            .check(&cookie, replay::now_millis())
+            .map_err(|e| {
+                log::warn!("Rejecting OpenSyn on: {}. {}", link, e);
+                (
+                    HandshakeError(HandshakeFailure::InvalidCookie, e).into(),
+                    Some(tmsg::close_reason::INVALID),
+                )
+            })?;
+    }
+
     // Validate with the peer authenticators
     let mut open_syn_properties: EstablishmentProperties = match msg.attachment.take() {
         Some(att) => EstablishmentProperties::try_from(&att)
@@ -145,7 +160,13 @@ pub(super) async fn recv(
             }
         }
 
-        let mut att = att.map_err(|e| (e, Some(tmsg::close_reason::INVALID)))?;
+        let mut att = att.map_err(|e| {
+            super::audit_auth_failure(auth_link, cookie.zid, pa, &e);
+            (
+                HandshakeError(HandshakeFailure::AuthFailed, e).into(),
+                Some(tmsg::close_reason::INVALID),
+            )
+        })?;
         if let Some(att) = att.take() {
             ps_attachment
                 .insert(Property {
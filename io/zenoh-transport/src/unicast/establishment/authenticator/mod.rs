@@ -11,6 +11,8 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+#[cfg(feature = "auth_psk")]
+mod psk;
 #[cfg(feature = "auth_pubkey")]
 mod pubkey;
 #[cfg(feature = "shared-memory")]
@@ -20,6 +22,8 @@ mod userpassword;
 
 use crate::unicast::establishment::Cookie;
 use async_trait::async_trait;
+#[cfg(feature = "auth_psk")]
+pub use psk::*;
 #[cfg(feature = "auth_pubkey")]
 pub use pubkey::*;
 #[cfg(feature = "shared-memory")]
@@ -32,10 +36,31 @@ use std::sync::Arc;
 #[cfg(feature = "auth_usrpwd")]
 pub use userpassword::*;
 use zenoh_config::Config;
+#[cfg(any(feature = "auth_psk", feature = "auth_pubkey", feature = "auth_usrpwd"))]
+use zenoh_config::SecretsConf;
 use zenoh_link::{Link, Locator};
 use zenoh_protocol::core::{ZInt, ZenohId};
 use zenoh_result::ZResult;
 
+/*************************************/
+/*             SECRETS               */
+/*************************************/
+/// Resolves the secret `value` on a blocking thread: the exec providers are blocking commands.
+#[cfg(any(feature = "auth_psk", feature = "auth_pubkey", feature = "auth_usrpwd"))]
+pub(crate) async fn resolve_secret(secrets: &SecretsConf, value: &str) -> ZResult<String> {
+    let secrets = secrets.clone();
+    let value = value.to_string();
+    async_std::task::spawn_blocking(move || secrets.resolve(&value)).await
+}
+
+/// Reads the secret file at `path` on a blocking thread, after checking its permissions.
+#[cfg(any(feature = "auth_psk", feature = "auth_usrpwd"))]
+pub(crate) async fn read_secret_file(secrets: &SecretsConf, path: &str) -> ZResult<String> {
+    let secrets = secrets.clone();
+    let path = path.to_string();
+    async_std::task::spawn_blocking(move || secrets.read_file(&path)).await
+}
+
 /*************************************/
 /*              LINK                 */
 /*************************************/
@@ -127,6 +152,19 @@ pub enum PeerAuthenticatorId {
     Shm = 1,
     UserPassword = 2,
     PublicKey = 3,
+    PreSharedKey = 4,
+}
+
+impl PeerAuthenticatorId {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            PeerAuthenticatorId::Reserved => "reserved",
+            PeerAuthenticatorId::Shm => "shm",
+            PeerAuthenticatorId::UserPassword => "usrpwd",
+            PeerAuthenticatorId::PublicKey => "pubkey",
+            PeerAuthenticatorId::PreSharedKey => "psk",
+        }
+    }
 }
 
 impl From<PeerAuthenticatorId> for ZInt {
@@ -159,6 +197,14 @@ impl PeerAuthenticator {
             }
         }
 
+        #[cfg(feature = "auth_psk")]
+        {
+            let mut res = PreSharedKeyAuthenticator::from_config(_config).await?;
+            if let Some(pa) = res.take() {
+                pas.insert(pa.into());
+            }
+        }
+
         #[cfg(feature = "shared-memory")]
         {
             let mut res = SharedMemoryAuthenticator::from_config(_config).await?;
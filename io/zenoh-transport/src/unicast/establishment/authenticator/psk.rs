@@ -0,0 +1,560 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{
+    read_secret_file, resolve_secret, AuthenticatedPeerLink, PeerAuthenticator,
+    PeerAuthenticatorId, PeerAuthenticatorTrait,
+};
+use super::{Locator, ZInt, ZenohId};
+use crate::unicast::establishment::Cookie;
+use async_std::sync::{Mutex, RwLock};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zenoh_buffers::{
+    reader::{DidntRead, HasReader, Reader},
+    writer::{DidntWrite, HasWriter, Writer},
+};
+use zenoh_cfg_properties::Properties;
+use zenoh_codec::{RCodec, WCodec, Zenoh060};
+use zenoh_config::Config;
+use zenoh_core::{zasynclock, zasyncread, zasyncwrite};
+use zenoh_crypto::hmac;
+use zenoh_result::{bail, zerror, ZResult};
+
+const PSK_VERSION: ZInt = 1;
+
+/// # Attachment decorator
+///
+/// ```text
+/// The Attachment can decorate any message (i.e., TransportMessage and ZenohMessage) and it allows to
+/// append to the message any additional information. Since the information contained in the
+/// Attachement is relevant only to the layer that provided them (e.g., Transport, Zenoh, User) it
+/// is the duty of that layer to serialize and de-serialize the attachment whenever deemed necessary.
+///
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// | ENC |  ATTCH  |
+/// +-+-+-+---------+
+/// ~   Attachment  ~
+/// +---------------+
+///
+/// ENC values:
+/// - 0x00 => Zenoh Properties
+/// ```
+
+/*************************************/
+/*             InitSyn               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |0 0 0|  ATTCH  |
+/// +-+-+-+---------+
+/// ~    version    ~
+/// +---------------+
+struct InitSynProperty {
+    version: ZInt,
+}
+
+impl<W> WCodec<&InitSynProperty, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &InitSynProperty) -> Self::Output {
+        self.write(&mut *writer, x.version)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<InitSynProperty, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<InitSynProperty, Self::Error> {
+        let version: ZInt = self.read(&mut *reader)?;
+        Ok(InitSynProperty { version })
+    }
+}
+
+/*************************************/
+/*             InitAck               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |0 0 0|  ATTCH  |
+/// +-+-+-+---------+
+/// ~     nonce     ~
+/// +---------------+
+struct InitAckProperty {
+    nonce: ZInt,
+}
+
+impl<W> WCodec<&InitAckProperty, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &InitAckProperty) -> Self::Output {
+        self.write(&mut *writer, x.nonce)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<InitAckProperty, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<InitAckProperty, Self::Error> {
+        let nonce: ZInt = self.read(&mut *reader)?;
+        Ok(InitAckProperty { nonce })
+    }
+}
+
+/*************************************/
+/*             OpenSyn               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |0 0 0|  ATTCH  |
+/// +-+-+-+---------+
+/// ~    key id     ~
+/// +---------------+
+/// ~     hmac      ~
+/// +---------------+
+struct OpenSynProperty {
+    key_id: Vec<u8>,
+    hmac: Vec<u8>,
+}
+
+impl<W> WCodec<&OpenSynProperty, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &OpenSynProperty) -> Self::Output {
+        self.write(&mut *writer, x.key_id.as_slice())?;
+        self.write(&mut *writer, x.hmac.as_slice())?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<OpenSynProperty, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<OpenSynProperty, Self::Error> {
+        let key_id: Vec<u8> = self.read(&mut *reader)?;
+        let hmac: Vec<u8> = self.read(&mut *reader)?;
+        Ok(OpenSynProperty { key_id, hmac })
+    }
+}
+
+/*************************************/
+/*          Authenticator            */
+/*************************************/
+struct Credentials {
+    key_id: Vec<u8>,
+    key: Vec<u8>,
+}
+
+struct Authenticated {
+    key_id: Vec<u8>,
+    links: HashSet<(Locator, Locator)>,
+}
+
+#[derive(Default)]
+struct Failures {
+    count: usize,
+    locked_until: Option<Instant>,
+}
+
+/// A pre-shared-key authenticator.
+///
+/// The opening peer proves the knowledge of the key associated to its key-id by returning
+/// the HMAC of the challenge sent by the accepting peer. Key-ids accumulating `max_failures`
+/// consecutive failed attempts are rejected during `lockout_duration`.
+pub struct PreSharedKeyAuthenticator {
+    lookup: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    credentials: Option<Credentials>,
+    max_failures: usize,
+    lockout_duration: Duration,
+    failures: Mutex<HashMap<Vec<u8>, Failures>>,
+    authenticated: Mutex<HashMap<ZenohId, Authenticated>>,
+}
+
+impl PreSharedKeyAuthenticator {
+    pub fn new(
+        lookup: HashMap<Vec<u8>, Vec<u8>>,
+        mut credentials: Option<(Vec<u8>, Vec<u8>)>,
+    ) -> PreSharedKeyAuthenticator {
+        let credentials = credentials.take().map(|cr| Credentials {
+            key_id: cr.0,
+            key: cr.1,
+        });
+        PreSharedKeyAuthenticator {
+            lookup: RwLock::new(lookup),
+            credentials,
+            max_failures: 0,
+            lockout_duration: Duration::ZERO,
+            failures: Mutex::new(HashMap::new()),
+            authenticated: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Locks out a key-id during `duration` after `max_failures` consecutive failed
+    /// attempts. A `max_failures` of 0 disables the lockout.
+    pub fn lockout(mut self, max_failures: usize, duration: Duration) -> Self {
+        self.max_failures = max_failures;
+        self.lockout_duration = duration;
+        self
+    }
+
+    pub async fn add_key(&self, key_id: Vec<u8>, key: Vec<u8>) -> ZResult<()> {
+        let mut guard = zasyncwrite!(self.lookup);
+        guard.insert(key_id, key);
+        Ok(())
+    }
+
+    pub async fn del_key(&self, key_id: &[u8]) -> ZResult<()> {
+        zasyncwrite!(self.lookup).remove(key_id);
+        zasynclock!(self.failures).remove(key_id);
+        Ok(())
+    }
+
+    /// Clears the failure counter of a key-id, lifting its lockout if any.
+    pub async fn unlock(&self, key_id: &[u8]) {
+        zasynclock!(self.failures).remove(key_id);
+    }
+
+    fn decode_key(key: &str) -> ZResult<Vec<u8>> {
+        let key = hex::decode(key.trim()).map_err(|e| zerror!("Invalid PSK key: {}", e))?;
+        if key.is_empty() {
+            bail!("Invalid PSK key: empty key");
+        }
+        Ok(key)
+    }
+
+    pub async fn from_config(config: &Config) -> ZResult<Option<PreSharedKeyAuthenticator>> {
+        let c = config.transport().auth().psk();
+        let secrets = config.transport().auth().secrets();
+
+        let mut lookup: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        for k in c.keys() {
+            lookup.insert(
+                k.id.clone().into(),
+                Self::decode_key(&resolve_secret(secrets, &k.key).await?)?,
+            );
+        }
+        if let Some(file) = c.keys_file() {
+            let content = read_secret_file(secrets, file)
+                .await
+                .map_err(|e| zerror!("Invalid PSK keys file: {}", e))?;
+            // Populate the key-id dictionary
+            let mut ps = Properties::from(content);
+            for (key_id, key) in ps.drain() {
+                lookup.insert(key_id.into(), Self::decode_key(&key)?);
+            }
+        }
+        if !lookup.is_empty() {
+            log::debug!("PSK dictionary has been configured");
+        }
+
+        let mut credentials: Option<(Vec<u8>, Vec<u8>)> = None;
+        if let Some(key_id) = c.key_id() {
+            if let Some(key) = c.key() {
+                log::debug!("PSK key-id and key have been configured");
+                credentials = Some((
+                    key_id.to_string().into(),
+                    Self::decode_key(&resolve_secret(secrets, key).await?)?,
+                ));
+            }
+        }
+
+        if !lookup.is_empty() || credentials.is_some() {
+            log::debug!("PSK authentication is enabled");
+            let max_failures = c.max_failures().unwrap_or(0);
+            let duration = Duration::from_millis(c.lockout_duration().unwrap_or(0));
+            Ok(Some(
+                PreSharedKeyAuthenticator::new(lookup, credentials).lockout(max_failures, duration),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Verifies the HMAC of a challenge, keeping track of the failed attempts of each key-id.
+    async fn verify(
+        &self,
+        link: &AuthenticatedPeerLink,
+        key_id: &[u8],
+        challenge: &[u8],
+        hmac: &[u8],
+    ) -> ZResult<()> {
+        let now = Instant::now();
+        let mut failures = zasynclock!(self.failures);
+        if let Some(f) = failures.get_mut(key_id) {
+            match f.locked_until {
+                Some(until) if now < until => {
+                    bail!("Rejected OpenSyn with locked out PSK on link: {}", link)
+                }
+                Some(_) => *f = Failures::default(),
+                None => {}
+            }
+        }
+
+        let key = zasyncread!(self.lookup).get(key_id).cloned();
+        // Unknown key-ids are verified against a dummy key so that they are not
+        // distinguishable from known ones by timing.
+        let valid = hmac::verify(key.as_deref().unwrap_or(&[0]), challenge, hmac)?;
+        if key.is_none() {
+            bail!("Received OpenSyn with invalid PSK on link: {}", link)
+        }
+        if valid {
+            failures.remove(key_id);
+            return Ok(());
+        }
+
+        let f = failures.entry(key_id.to_vec()).or_default();
+        f.count += 1;
+        if self.max_failures > 0 && f.count >= self.max_failures {
+            log::warn!(
+                "PSK key-id {} locked out for {:?} after {} failed attempts",
+                String::from_utf8_lossy(key_id),
+                self.lockout_duration,
+                f.count
+            );
+            f.locked_until = Some(now + self.lockout_duration);
+        }
+        bail!("Received OpenSyn with invalid PSK on link: {}", link)
+    }
+}
+
+#[async_trait]
+impl PeerAuthenticatorTrait for PreSharedKeyAuthenticator {
+    fn id(&self) -> PeerAuthenticatorId {
+        PeerAuthenticatorId::PreSharedKey
+    }
+
+    async fn close(&self) {
+        // No cleanup needed
+    }
+
+    async fn get_init_syn_properties(
+        &self,
+        link: &AuthenticatedPeerLink,
+        _peer_id: &ZenohId,
+    ) -> ZResult<Option<Vec<u8>>> {
+        // If credentials are not configured, don't initiate the PSK authentication
+        if self.credentials.is_none() {
+            return Ok(None);
+        }
+
+        let init_syn_property = InitSynProperty {
+            version: PSK_VERSION,
+        };
+        let mut wbuf = vec![];
+        let codec = Zenoh060::default();
+        let mut writer = wbuf.writer();
+        codec
+            .write(&mut writer, &init_syn_property)
+            .map_err(|_| zerror!("Error in encoding InitSyn for PSK on link: {}", link))?;
+        let attachment = wbuf;
+
+        Ok(Some(attachment))
+    }
+
+    async fn handle_init_syn(
+        &self,
+        link: &AuthenticatedPeerLink,
+        cookie: &Cookie,
+        property: Option<Vec<u8>>,
+    ) -> ZResult<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+        let p = property
+            .ok_or_else(|| zerror!("Received InitSyn with no PSK attachment on link: {}", link))?;
+
+        let codec = Zenoh060::default();
+
+        let mut reader = p.reader();
+        let init_syn_property: InitSynProperty = codec.read(&mut reader).map_err(|_| {
+            zerror!(
+                "Received InitSyn with invalid PSK attachment on link: {}",
+                link
+            )
+        })?;
+        if init_syn_property.version > PSK_VERSION {
+            bail!("Rejected InitSyn with invalid attachment on link: {}", link)
+        }
+
+        // Create the InitAck attachment
+        let init_ack_property = InitAckProperty {
+            nonce: cookie.nonce,
+        };
+        let mut wbuf = vec![];
+        let mut writer = wbuf.writer();
+        codec
+            .write(&mut writer, &init_ack_property)
+            .map_err(|_| zerror!("Error in encoding InitAck for PSK on link: {}", link))?;
+        let attachment = wbuf;
+
+        Ok((Some(attachment), None))
+    }
+
+    async fn handle_init_ack(
+        &self,
+        link: &AuthenticatedPeerLink,
+        _peer_id: &ZenohId,
+        _sn_resolution: ZInt,
+        property: Option<Vec<u8>>,
+    ) -> ZResult<Option<Vec<u8>>> {
+        // If credentials are not configured, don't continue the PSK authentication
+        let credentials = match self.credentials.as_ref() {
+            Some(cr) => cr,
+            None => return Ok(None),
+        };
+
+        let p = property
+            .ok_or_else(|| zerror!("Received InitAck with no PSK attachment on link: {}", link))?;
+
+        let codec = Zenoh060::default();
+
+        let mut reader = p.reader();
+        let init_ack_property: InitAckProperty = codec.read(&mut reader).map_err(|_| {
+            zerror!(
+                "Received InitAck with invalid PSK attachment on link: {}",
+                link
+            )
+        })?;
+
+        // Create the HMAC of the nonce received as a challenge using the pre-shared key
+        let challenge = init_ack_property.nonce.to_le_bytes();
+        let hmac = hmac::sign(&credentials.key, &challenge)?;
+        // Create the OpenSyn attachment
+        let open_syn_property = OpenSynProperty {
+            key_id: credentials.key_id.clone(),
+            hmac,
+        };
+        // Encode the OpenSyn attachment
+        let mut wbuf = vec![];
+        let mut writer = wbuf.writer();
+        codec
+            .write(&mut writer, &open_syn_property)
+            .map_err(|_| zerror!("Error in encoding OpenSyn for PSK on link: {}", link))?;
+        let attachment = wbuf;
+
+        Ok(Some(attachment))
+    }
+
+    async fn handle_open_syn(
+        &self,
+        link: &AuthenticatedPeerLink,
+        cookie: &Cookie,
+        property: (Option<Vec<u8>>, Option<Vec<u8>>),
+    ) -> ZResult<Option<Vec<u8>>> {
+        let (attachment, _cookie) = property;
+        let a = attachment
+            .ok_or_else(|| zerror!("Received OpenSyn with no PSK attachment on link: {}", link))?;
+
+        let codec = Zenoh060::default();
+
+        let mut reader = a.reader();
+        let open_syn_property: OpenSynProperty = codec.read(&mut reader).map_err(|_| {
+            zerror!(
+                "Received OpenSyn with invalid PSK attachment on link: {}",
+                link
+            )
+        })?;
+
+        let challenge = cookie.nonce.to_le_bytes();
+        self.verify(
+            link,
+            &open_syn_property.key_id,
+            &challenge,
+            &open_syn_property.hmac,
+        )
+        .await?;
+
+        // Check PID validity
+        let mut guard = zasynclock!(self.authenticated);
+        match guard.get_mut(&cookie.zid) {
+            Some(auth) => {
+                if open_syn_property.key_id != auth.key_id {
+                    bail!("Received OpenSyn with invalid PSK on link: {}", link)
+                }
+                auth.links.insert((link.src.clone(), link.dst.clone()));
+            }
+            None => {
+                let mut links = HashSet::new();
+                links.insert((link.src.clone(), link.dst.clone()));
+                let auth = Authenticated {
+                    key_id: open_syn_property.key_id,
+                    links,
+                };
+                guard.insert(cookie.zid, auth);
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn handle_open_ack(
+        &self,
+        _link: &AuthenticatedPeerLink,
+        _property: Option<Vec<u8>>,
+    ) -> ZResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    async fn handle_link_err(&self, link: &AuthenticatedPeerLink) {
+        // Need to check if it authenticated and remove it if this is the last link
+        let mut guard = zasynclock!(self.authenticated);
+        let mut to_del: Option<ZenohId> = None;
+        for (peer_id, auth) in guard.iter_mut() {
+            auth.links.remove(&(link.src.clone(), link.dst.clone()));
+            if auth.links.is_empty() {
+                to_del = Some(*peer_id);
+                break;
+            }
+        }
+        if let Some(peer_id) = to_del.take() {
+            guard.remove(&peer_id);
+        }
+    }
+
+    async fn handle_close(&self, peer_id: &ZenohId) {
+        zasynclock!(self.authenticated).remove(peer_id);
+    }
+}
+
+//noinspection ALL
+impl From<Arc<PreSharedKeyAuthenticator>> for PeerAuthenticator {
+    fn from(v: Arc<PreSharedKeyAuthenticator>) -> PeerAuthenticator {
+        PeerAuthenticator(v)
+    }
+}
+
+impl From<PreSharedKeyAuthenticator> for PeerAuthenticator {
+    fn from(v: PreSharedKeyAuthenticator) -> PeerAuthenticator {
+        Self::from(Arc::new(v))
+    }
+}
@@ -12,7 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::{
-    AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorTrait,
+    resolve_secret, AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId,
+    PeerAuthenticatorTrait,
 };
 use crate::unicast::establishment::Cookie;
 use async_std::sync::Mutex;
@@ -331,6 +332,7 @@ impl PubKeyAuthenticator {
 
     pub async fn from_config(config: &Config) -> ZResult<Option<PubKeyAuthenticator>> {
         let c = config.transport().auth().pubkey();
+        let secrets = config.transport().auth().secrets();
 
         // @TODO: support PubKey keys import
 
@@ -339,7 +341,8 @@ impl PubKeyAuthenticator {
             (Some(public), Some(private)) => {
                 let pub_key = RsaPublicKey::from_pkcs1_pem(public)
                     .map_err(|e| zerror!("Rsa Public Key: {}", e))?;
-                let pri_key = RsaPrivateKey::from_pkcs1_pem(private)
+                let private = resolve_secret(secrets, private).await?;
+                let pri_key = RsaPrivateKey::from_pkcs1_pem(&private)
                     .map_err(|e| zerror!("Rsa Private Key: {}", e))?;
                 return Ok(Some(Self::new(pub_key, pri_key)));
             }
@@ -359,6 +362,7 @@ impl PubKeyAuthenticator {
                 let pub_key = RsaPublicKey::read_pkcs1_pem_file(path)
                     .map_err(|e| zerror!("Rsa Public Key: {}", e))?;
                 let path = Path::new(private);
+                secrets.check_permissions(path)?;
                 let pri_key = RsaPrivateKey::read_pkcs1_pem_file(path)
                     .map_err(|e| zerror!("Rsa Private Key: {}", e))?;
                 return Ok(Some(Self::new(pub_key, pri_key)));
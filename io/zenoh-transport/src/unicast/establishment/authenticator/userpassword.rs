@@ -12,11 +12,11 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::{
-    AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorTrait,
+    read_secret_file, resolve_secret, AuthenticatedPeerLink, PeerAuthenticator,
+    PeerAuthenticatorId, PeerAuthenticatorTrait,
 };
 use super::{Locator, ZInt, ZenohId};
 use crate::unicast::establishment::Cookie;
-use async_std::fs;
 use async_std::sync::{Mutex, RwLock};
 use async_trait::async_trait;
 use std::collections::{HashMap, HashSet};
@@ -218,10 +218,11 @@ impl UserPasswordAuthenticator {
 
     pub async fn from_config(config: &Config) -> ZResult<Option<UserPasswordAuthenticator>> {
         let c = config.transport().auth().usrpwd();
+        let secrets = config.transport().auth().secrets();
 
         let mut lookup: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
         if let Some(dict) = c.dictionary_file() {
-            let content = fs::read_to_string(dict)
+            let content = read_secret_file(secrets, dict)
                 .await
                 .map_err(|e| zerror!("Invalid user-password dictionary file: {}", e))?;
             // Populate the user-password dictionary
@@ -236,7 +237,10 @@ impl UserPasswordAuthenticator {
         if let Some(user) = c.user() {
             if let Some(password) = c.password() {
                 log::debug!("User and password have been configured");
-                credentials = Some((user.to_string().into(), password.to_string().into()));
+                credentials = Some((
+                    resolve_secret(secrets, user).await?.into(),
+                    resolve_secret(secrets, password).await?.into(),
+                ));
             }
         }
 
@@ -26,7 +26,11 @@ pub struct Cookie {
     pub zid: ZenohId,
     pub sn_resolution: ZInt,
     pub is_qos: bool,
+    pub is_preamble: bool,
+    pub extensions: ZInt,
     pub nonce: ZInt,
+    /// Creation time of the cookie in milliseconds since the UNIX epoch.
+    pub timestamp: ZInt,
     pub properties: EstablishmentProperties,
 }
 
@@ -43,7 +47,11 @@ where
         self.write(&mut *writer, x.sn_resolution)?;
         let is_qos = u8::from(x.is_qos);
         self.write(&mut *writer, is_qos)?;
+        let is_preamble = u8::from(x.is_preamble);
+        self.write(&mut *writer, is_preamble)?;
+        self.write(&mut *writer, x.extensions)?;
         self.write(&mut *writer, x.nonce)?;
+        self.write(&mut *writer, x.timestamp)?;
         self.write(&mut *writer, x.properties.as_slice())?;
 
         Ok(())
@@ -63,7 +71,11 @@ where
         let sn_resolution: ZInt = self.read(&mut *reader)?;
         let is_qos: u8 = self.read(&mut *reader)?;
         let is_qos = is_qos == 1;
+        let is_preamble: u8 = self.read(&mut *reader)?;
+        let is_preamble = is_preamble == 1;
+        let extensions: ZInt = self.read(&mut *reader)?;
         let nonce: ZInt = self.read(&mut *reader)?;
+        let timestamp: ZInt = self.read(&mut *reader)?;
         let mut ps: Vec<Property> = self.read(&mut *reader)?;
         let mut properties = EstablishmentProperties::new();
         for p in ps.drain(..) {
@@ -75,7 +87,10 @@ where
             zid,
             sn_resolution,
             is_qos,
+            is_preamble,
+            extensions,
             nonce,
+            timestamp,
             properties,
         };
 
@@ -137,7 +152,10 @@ impl Cookie {
             zid: ZenohId::default(),
             sn_resolution: rng.gen(),
             is_qos: rng.gen_bool(0.5),
+            is_preamble: rng.gen_bool(0.5),
+            extensions: rng.gen(),
             nonce: rng.gen(),
+            timestamp: rng.gen(),
             properties: EstablishmentProperties::rand(),
         }
     }
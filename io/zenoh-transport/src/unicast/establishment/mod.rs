@@ -16,6 +16,7 @@ pub mod authenticator;
 pub(super) mod cookie;
 pub(crate) mod open;
 pub(super) mod properties;
+pub(crate) mod replay;
 
 use super::super::TransportManager;
 use super::{TransportConfigUnicast, TransportPeer, TransportUnicast};
@@ -32,6 +33,57 @@ use zenoh_protocol::{
 };
 use zenoh_result::ZResult;
 
+/// The error of an opened link on which the session establishment failed.
+#[derive(Debug)]
+pub(crate) struct EstablishmentError(pub(crate) zenoh_result::Error);
+
+impl std::error::Error for EstablishmentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl std::fmt::Display for EstablishmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The cause of a failed handshake on an inbound link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HandshakeFailure {
+    AuthFailed,
+    IncompatibleVersion,
+    MaxSessions,
+    MaxLinks,
+    InvalidCookie,
+    Timeout,
+    Other,
+}
+
+impl HandshakeFailure {
+    pub(crate) fn of(e: &zenoh_result::Error) -> HandshakeFailure {
+        e.downcast_ref::<HandshakeError>()
+            .map_or(HandshakeFailure::Other, |e| e.0)
+    }
+}
+
+/// The error of a handshake step, along with the cause counted in the handshake stats.
+#[derive(Debug)]
+pub(crate) struct HandshakeError(pub(crate) HandshakeFailure, pub(crate) zenoh_result::Error);
+
+impl std::error::Error for HandshakeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.1.source()
+    }
+}
+
+impl std::fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.1.fmt(f)
+    }
+}
+
 pub(super) async fn close_link(
     link: &LinkUnicast,
     manager: &TransportManager,
@@ -65,6 +117,7 @@ pub(super) struct InputInit {
     pub(super) sn_resolution: ZInt,
     pub(super) is_shm: bool,
     pub(super) is_qos: bool,
+    pub(super) extensions: ZInt,
 }
 async fn transport_init(
     manager: &TransportManager,
@@ -79,6 +132,7 @@ async fn transport_init(
         sn_resolution: input.sn_resolution,
         is_shm: input.is_shm,
         is_qos: input.is_qos,
+        extensions: input.extensions,
         initial_sn_tx,
     };
 
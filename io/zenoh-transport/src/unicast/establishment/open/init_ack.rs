@@ -38,6 +38,8 @@ pub(super) struct Output {
     pub(super) whatami: WhatAmI,
     pub(super) sn_resolution: ZInt,
     pub(super) is_qos: bool,
+    pub(super) is_preamble: bool,
+    pub(super) extensions: ZInt,
     pub(super) is_shm: bool,
     pub(super) cookie: ZSlice,
     pub(super) open_syn_attachment: Option<Attachment>,
@@ -175,6 +177,10 @@ pub(super) async fn recv(
         whatami: init_ack.whatami,
         sn_resolution,
         is_qos: init_ack.is_qos,
+        // The responder only agrees on the preamble if it has been proposed
+        is_preamble: init_ack.is_preamble && manager.config.unicast.preamble,
+        // Likewise for the extensions
+        extensions: init_ack.extensions & manager.config.unicast.extensions,
         is_shm,
         cookie: init_ack.cookie,
         open_syn_attachment,
@@ -66,6 +66,8 @@ pub(super) async fn send(
         manager.config.zid,
         manager.config.sn_resolution,
         manager.config.unicast.is_qos,
+        manager.config.unicast.preamble,
+        manager.config.unicast.extensions,
         init_syn_attachment,
     );
     let _ = link
@@ -61,12 +61,14 @@ pub(crate) async fn open_link(
     }
 
     let zid = output.zid;
+    let is_preamble = output.is_preamble;
     let input = InputInit {
         zid,
         whatami: output.whatami,
         sn_resolution: output.sn_resolution,
         is_shm: output.is_shm,
         is_qos: output.is_qos,
+        extensions: output.extensions,
     };
     let transport = step!(super::transport_init(manager, input).await);
 
@@ -106,7 +108,7 @@ pub(crate) async fn open_link(
     step!(step!(transport
         .get_inner()
         .map_err(|e| (e, Some(tmsg::close_reason::INVALID))))
-    .add_link(link.clone(), LinkUnicastDirection::Outbound)
+    .add_link(link.clone(), LinkUnicastDirection::Outbound, is_preamble,)
     .map_err(|e| (e, Some(tmsg::close_reason::MAX_LINKS))));
 
     // Sync the RX sequence number
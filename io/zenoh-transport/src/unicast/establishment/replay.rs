@@ -0,0 +1,114 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::cookie::Cookie;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zenoh_protocol::core::{ZInt, ZenohId};
+use zenoh_result::{bail, ZResult};
+
+/// Returns the current time in milliseconds since the UNIX epoch, as stored in the cookies.
+pub(crate) fn now_millis() -> ZInt {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as ZInt
+}
+
+/// Keeps track of the cookies accepted on connectionless links within the acceptance window,
+/// so that a captured OpenSyn cannot be replayed to create a ghost session.
+pub(crate) struct ReplayCache {
+    window: ZInt,
+    capacity: usize,
+    // Accepted cookies in acceptance order
+    accepted: VecDeque<(ZInt, ZenohId, ZInt)>,
+    index: HashSet<(ZenohId, ZInt)>,
+}
+
+impl ReplayCache {
+    pub(crate) fn new(window: Duration, capacity: usize) -> Self {
+        ReplayCache {
+            window: window.as_millis() as ZInt,
+            capacity,
+            accepted: VecDeque::with_capacity(capacity),
+            index: HashSet::with_capacity(capacity),
+        }
+    }
+
+    /// Validates the timestamp and nonce of `cookie` at time `now` and records it if accepted.
+    pub(crate) fn check(&mut self, cookie: &Cookie, now: ZInt) -> ZResult<()> {
+        if cookie.timestamp > now.saturating_add(self.window)
+            || now.saturating_sub(cookie.timestamp) > self.window
+        {
+            bail!(
+                "Cookie of {} is outside of the acceptance window ({} ms)",
+                cookie.zid,
+                self.window
+            );
+        }
+
+        // Evict the cookies that can no longer be accepted
+        while let Some((timestamp, zid, nonce)) = self.accepted.front() {
+            if now.saturating_sub(*timestamp) <= self.window {
+                break;
+            }
+            self.index.remove(&(*zid, *nonce));
+            self.accepted.pop_front();
+        }
+
+        if self.index.contains(&(cookie.zid, cookie.nonce)) {
+            bail!("Cookie of {} has already been used", cookie.zid);
+        }
+        // Evicting a cookie still in the window would allow it to be replayed
+        if self.accepted.len() >= self.capacity {
+            bail!(
+                "Replay cache is full ({} cookies), rejecting cookie of {}",
+                self.capacity,
+                cookie.zid
+            );
+        }
+        self.accepted
+            .push_back((cookie.timestamp, cookie.zid, cookie.nonce));
+        self.index.insert((cookie.zid, cookie.nonce));
+        Ok(())
+    }
+}
+
+mod tests {
+    #[test]
+    fn replay_cache() {
+        use super::*;
+
+        let mut cookie = Cookie::rand();
+        cookie.timestamp = 1_000;
+        cookie.nonce = 0;
+        let mut cache = ReplayCache::new(Duration::from_millis(100), 2);
+
+        // Fresh cookies are accepted only once
+        assert!(cache.check(&cookie, 1_050).is_ok());
+        assert!(cache.check(&cookie, 1_060).is_err());
+
+        // Stale and future cookies are rejected
+        assert!(cache.check(&cookie, 1_200).is_err());
+        cookie.timestamp = 2_000;
+        assert!(cache.check(&cookie, 1_800).is_err());
+
+        // Expired cookies are evicted from the cache
+        cookie.timestamp = 1_050;
+        cookie.nonce += 1;
+        assert!(cache.check(&cookie, 1_100).is_ok());
+        cookie.nonce += 1;
+        assert!(cache.check(&cookie, 1_100).is_err());
+        assert!(cache.check(&cookie, 1_120).is_ok());
+    }
+}
@@ -12,26 +12,32 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::common::conduit::TransportConduitTx;
+use super::common::workers::RxCallbacks;
+use super::rtt::{self, LinkRtt};
 use super::transport::TransportUnicastInner;
+use super::LinkRxLimits;
 #[cfg(feature = "stats")]
 use super::TransportUnicastStatsAtomic;
+use crate::capture::{self, Direction};
 use crate::common::pipeline::{
     TransmissionPipeline, TransmissionPipelineConf, TransmissionPipelineConsumer,
     TransmissionPipelineProducer,
 };
+use crate::watermark::WatermarkDetector;
 use crate::TransportExecutor;
 use async_std::prelude::FutureExt;
-use async_std::task;
-use async_std::task::JoinHandle;
+use std::fmt;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use zenoh_buffers::reader::{HasReader, Reader};
 use zenoh_buffers::ZSlice;
-use zenoh_codec::{RCodec, Zenoh060};
+use zenoh_codec::{find_preamble, RCodec, Zenoh060};
+use zenoh_core::executor::{self, JoinHandle};
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
-use zenoh_protocol::transport::TransportMessage;
-use zenoh_result::{bail, zerror, ZResult};
+use zenoh_protocol::transport::{tmsg, Preamble, TransportMessage};
+use zenoh_result::{bail, zerror, ZError, ZResult};
 use zenoh_sync::{RecyclingObjectPool, Signal};
+use zenoh_util::watchdog::{self, TaskProbe};
 
 #[derive(Clone)]
 pub(super) struct TransportLinkUnicast {
@@ -39,12 +45,16 @@ pub(super) struct TransportLinkUnicast {
     pub(super) direction: LinkUnicastDirection,
     // The underlying link
     pub(super) link: LinkUnicast,
+    // The batches are preceded by a preamble, as agreed at establishment on a streamed link
+    pub(super) preamble: bool,
     // The transmission pipeline
     pub(super) pipeline: Option<TransmissionPipelineProducer>,
+    // The round-trip time measured with the pings
+    pub(super) rtt: Arc<LinkRtt>,
     // The transport this link is associated to
     transport: TransportUnicastInner,
     // The signals to stop TX/RX tasks
-    handle_tx: Option<Arc<async_executor::Task<()>>>,
+    handle_tx: Option<Arc<JoinHandle<()>>>,
     signal_rx: Signal,
     handle_rx: Option<Arc<JoinHandle<()>>>,
 }
@@ -54,12 +64,15 @@ impl TransportLinkUnicast {
         transport: TransportUnicastInner,
         link: LinkUnicast,
         direction: LinkUnicastDirection,
+        preamble: bool,
     ) -> TransportLinkUnicast {
         TransportLinkUnicast {
             direction,
             transport,
+            preamble: preamble && link.is_streamed(),
             link,
             pipeline: None,
+            rtt: Arc::new(LinkRtt::default()),
             handle_tx: None,
             signal_rx: Signal::new(),
             handle_rx: None,
@@ -76,33 +89,78 @@ impl TransportLinkUnicast {
         conduit_tx: &[TransportConduitTx],
     ) {
         if self.handle_tx.is_none() {
+            let batch_size = batch_size.min(self.link.get_mtu());
             let config = TransmissionPipelineConf {
                 is_streamed: self.link.is_streamed(),
-                batch_size: batch_size.min(self.link.get_mtu()),
+                preamble: self.preamble,
+                batch_size,
+                max_message_size: self.transport.config.manager.config.max_message_size,
                 queue_size: self.transport.config.manager.config.queue_size,
                 backoff: self.transport.config.manager.config.queue_backoff,
+                watermarks: self.transport.config.manager.config.queue_watermarks.map(
+                    |watermarks| {
+                        Arc::new(WatermarkDetector::new(
+                            watermarks,
+                            self.transport.config.manager.watermarks.clone(),
+                            self.transport.config.zid.to_string(),
+                            self.link.to_string(),
+                        ))
+                    },
+                ),
+                memory: Some(self.transport.config.manager.config.memory.clone()),
+                pacing: self.transport.config.manager.config.queue_pacing,
             };
             // The pipeline
-            let (producer, consumer) = TransmissionPipeline::make(config, conduit_tx);
+            let (producer, consumer) = TransmissionPipeline::make(
+                config,
+                conduit_tx,
+                Some(self.transport.config.manager.buffer_pool.clone()),
+            );
+            let probe = watchdog::register(
+                format!("tx {}", self.link),
+                Some(Box::new({
+                    let producer = producer.clone();
+                    move || format!("queued batches: {}", producer.queued())
+                })),
+                Some(restart(&self.link)),
+            );
             self.pipeline = Some(producer);
 
             // Spawn the TX task
             let c_link = self.link.clone();
             let c_transport = self.transport.clone();
+            let c_preamble = self.preamble;
+            let ping_interval = self.transport.config.manager.config.unicast.ping_interval;
+            let adaptive_keep_alive = self
+                .transport
+                .config
+                .manager
+                .config
+                .unicast
+                .adaptive_keep_alive;
             let handle = executor.spawn(async move {
                 let res = tx_task(
                     consumer,
                     c_link.clone(),
+                    c_preamble,
                     keep_alive,
+                    adaptive_keep_alive,
+                    ping_interval,
+                    probe,
                     #[cfg(feature = "stats")]
                     c_transport.stats.clone(),
                 )
                 .await;
                 if let Err(e) = res {
-                    log::debug!("{}", e);
+                    tracing::debug!(
+                        zid = %c_transport.config.zid,
+                        link = %c_link,
+                        "{}",
+                        e
+                    );
                     // Spawn a task to avoid a deadlock waiting for this same task
                     // to finish in the close() joining its handle
-                    task::spawn(async move { c_transport.del_link(&c_link).await });
+                    executor::spawn(async move { c_transport.del_link(&c_link).await });
                 }
             });
             self.handle_tx = Some(Arc::new(handle));
@@ -120,25 +178,43 @@ impl TransportLinkUnicast {
             // Spawn the RX task
             let c_link = self.link.clone();
             let c_transport = self.transport.clone();
+            let c_preamble = self.preamble;
             let c_signal = self.signal_rx.clone();
             let c_rx_buffer_size = self.transport.config.manager.config.link_rx_buffer_size;
+            let probe =
+                watchdog::register(format!("rx {}", self.link), None, Some(restart(&self.link)));
 
-            let handle = task::spawn(async move {
+            let handle = executor::spawn(async move {
                 // Start the consume task
                 let res = rx_task(
                     c_link.clone(),
+                    c_preamble,
                     c_transport.clone(),
                     lease,
                     c_signal.clone(),
                     c_rx_buffer_size,
+                    probe,
                 )
                 .await;
                 c_signal.trigger();
                 if let Err(e) = res {
-                    log::debug!("{}", e);
                     // Spawn a task to avoid a deadlock waiting for this same task
                     // to finish in the close() joining its handle
-                    task::spawn(async move { c_transport.del_link(&c_link).await });
+                    if e.is::<RxLimitExceeded>() {
+                        // The whole transport is closed, or the peer would keep going on another link
+                        log::warn!("Closing transport with {}: {}", c_transport.config.zid, e);
+                        executor::spawn(async move {
+                            c_transport.close(tmsg::close_reason::QUOTA_EXCEEDED).await
+                        });
+                    } else {
+                        tracing::debug!(
+                            zid = %c_transport.config.zid,
+                            link = %c_link,
+                            "{}",
+                            e
+                        );
+                        executor::spawn(async move { c_transport.del_link(&c_link).await });
+                    }
                 }
             });
             self.handle_rx = Some(Arc::new(handle));
@@ -169,47 +245,115 @@ impl TransportLinkUnicast {
     }
 }
 
+// Closing the link makes its pending reads and writes fail, so that its tasks stop and the link
+// is removed from the transport, to be re-established if needed
+fn restart(link: &LinkUnicast) -> watchdog::Restart {
+    let link = link.clone();
+    Box::new(move || {
+        let link = link.clone();
+        executor::spawn(async move {
+            if let Err(e) = link.close().await {
+                log::debug!("{}: error closing stalled link: {}", link, e);
+            }
+        });
+    })
+}
+
 /*************************************/
 /*              TASKS                */
 /*************************************/
 async fn tx_task(
     mut pipeline: TransmissionPipelineConsumer,
     link: LinkUnicast,
+    preamble: bool,
     keep_alive: Duration,
+    adaptive_keep_alive: bool,
+    ping_interval: Duration,
+    probe: TaskProbe,
     #[cfg(feature = "stats")] stats: Arc<TransportUnicastStatsAtomic>,
 ) -> ZResult<()> {
+    // The pings are sent regardless of the traffic, the timeout is shortened accordingly.
+    // The keep-alives are postponed by any message sent if adaptive, the remote side renewing
+    // the lease of the link on any message received.
+    let mut next_ping = (ping_interval > Duration::ZERO).then(|| Instant::now() + ping_interval);
+    let mut next_keep_alive = Instant::now() + keep_alive;
+    // The messages sent outside of the batches are preceded by the preamble as well
+    let preamble = preamble.then(|| Preamble::new().to_bytes());
     loop {
-        match pipeline.pull().timeout(keep_alive).await {
-            Ok(res) => match res {
-                Some((batch, priority)) => {
-                    // Send the buffer on the link
-                    let bytes = batch.as_bytes();
-                    link.write_all(bytes).await?;
+        let now = Instant::now();
+        let timeout = match next_ping {
+            Some(next_ping) => next_keep_alive
+                .min(next_ping)
+                .saturating_duration_since(now),
+            None => next_keep_alive.saturating_duration_since(now),
+        };
+        probe.idle();
+        let pulled = pipeline.pull().timeout(timeout).await;
+        probe.progress();
+        match pulled {
+            Ok(Some((batch, priority))) => {
+                // Send the buffer on the link
+                let bytes = batch.as_bytes();
+                link.write_all(bytes).await?;
+                capture::capture(Direction::Tx, &link, batch.payload());
 
-                    #[cfg(feature = "stats")]
-                    {
-                        stats.inc_tx_t_msgs(batch.stats.t_msgs);
-                        stats.inc_tx_bytes(bytes.len());
-                    }
-
-                    // Reinsert the batch into the queue
-                    pipeline.refill(batch, priority);
-                }
-                None => break,
-            },
-            Err(_) => {
-                let zid = None;
-                let attachment = None;
-                let message = TransportMessage::make_keep_alive(zid, attachment);
-
-                #[allow(unused_variables)] // Used when stats feature is enabled
-                let n = link.write_transport_message(&message).await?;
                 #[cfg(feature = "stats")]
                 {
-                    stats.inc_tx_t_msgs(1);
-                    stats.inc_tx_bytes(n);
+                    stats.inc_tx_t_msgs(batch.stats.t_msgs);
+                    stats.inc_tx_bytes(bytes.len());
+                }
+
+                // Reinsert the batch into the queue
+                pipeline.refill(batch, priority);
+
+                if adaptive_keep_alive {
+                    next_keep_alive = Instant::now() + keep_alive;
                 }
             }
+            Ok(None) => break,
+            // Nothing to send before the timeout
+            Err(_) => {}
+        }
+
+        if next_ping.map_or(false, |next_ping| next_ping <= Instant::now()) {
+            let attachment = None;
+            let message = TransportMessage::make_ping(rtt::ping_hash(), attachment);
+
+            if let Some(preamble) = preamble.as_ref() {
+                link.write_all(preamble).await?;
+            }
+            #[allow(unused_variables)] // Used when stats feature is enabled
+            let n = link.write_transport_message(&message).await?;
+            capture::capture_message(Direction::Tx, &link, &message);
+            #[cfg(feature = "stats")]
+            {
+                stats.inc_tx_t_msgs(1);
+                stats.inc_tx_bytes(n);
+            }
+            next_ping = Some(Instant::now() + ping_interval);
+            // A ping also keeps the link alive
+            if adaptive_keep_alive {
+                next_keep_alive = Instant::now() + keep_alive;
+            }
+        }
+
+        if next_keep_alive <= Instant::now() {
+            let zid = None;
+            let attachment = None;
+            let message = TransportMessage::make_keep_alive(zid, attachment);
+
+            if let Some(preamble) = preamble.as_ref() {
+                link.write_all(preamble).await?;
+            }
+            #[allow(unused_variables)] // Used when stats feature is enabled
+            let n = link.write_transport_message(&message).await?;
+            capture::capture_message(Direction::Tx, &link, &message);
+            #[cfg(feature = "stats")]
+            {
+                stats.inc_tx_t_msgs(1);
+                stats.inc_tx_bytes(n);
+            }
+            next_keep_alive = Instant::now() + keep_alive;
         }
     }
 
@@ -220,6 +364,7 @@ async fn tx_task(
             .timeout(keep_alive)
             .await
             .map_err(|_| zerror!("{}: flush failed after {} ms", link, keep_alive.as_millis()))??;
+        capture::capture(Direction::Tx, &link, b.payload());
 
         #[cfg(feature = "stats")]
         {
@@ -231,25 +376,193 @@ async fn tx_task(
     Ok(())
 }
 
+/// The error of an rx task whose link exceeded the ingress limits.
+#[derive(Debug)]
+pub(super) struct RxLimitExceeded(pub(super) ZError);
+
+impl std::error::Error for RxLimitExceeded {}
+
+impl fmt::Display for RxLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Checks the frames received on a link against the ingress limits, before they are decoded
+struct RxLimiter {
+    limits: LinkRxLimits,
+    window: Instant,
+    frames: u32,
+}
+
+impl RxLimiter {
+    fn new(limits: LinkRxLimits) -> Self {
+        Self {
+            limits,
+            window: Instant::now(),
+            frames: 0,
+        }
+    }
+
+    fn check(&mut self, link: &LinkUnicast, size: usize) -> ZResult<()> {
+        if let Some(max) = self.limits.max_frame_size {
+            if size > max {
+                let e = zerror!(
+                    "{}: received a frame of {} bytes, exceeding the limit of {} bytes",
+                    link,
+                    size,
+                    max
+                );
+                return Err(RxLimitExceeded(e).into());
+            }
+        }
+        if let Some(max) = self.limits.max_batch_rate {
+            let now = Instant::now();
+            if now.duration_since(self.window) >= Duration::from_secs(1) {
+                self.window = now;
+                self.frames = 0;
+            }
+            self.frames += 1;
+            if self.frames > max {
+                let e = zerror!("{}: received more than {} frames per second", link, max);
+                return Err(RxLimitExceeded(e).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+// Counts the consecutive batches failing to decode on a link, up to the configured maximum.
+// On a reliable link, the messages skipped with a batch are lost for good: the reception resumes
+// with the next frame, whose SN is accepted past the gap.
+struct RxDecodeErrors {
+    max: usize,
+    consecutive: usize,
+}
+
+impl RxDecodeErrors {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            consecutive: 0,
+        }
+    }
+
+    fn failed(&mut self, link: &LinkUnicast, transport: &TransportUnicastInner) -> ZResult<()> {
+        self.consecutive += 1;
+        if self.consecutive > self.max {
+            bail!(
+                "{}: {} consecutive batches failed to decode",
+                link,
+                self.consecutive
+            );
+        }
+        log::warn!(
+            "{}: skipping the remainder of a batch that failed to decode ({}/{})",
+            link,
+            self.consecutive,
+            self.max
+        );
+        // The fragments being reassembled may have been skipped with the batch
+        transport.clear_defrag();
+        Ok(())
+    }
+
+    fn succeeded(&mut self) {
+        self.consecutive = 0;
+    }
+}
+
+// Deserializes and handles the messages of a batch, returns false if the batch failed to decode.
+// The messages preceding the decoding error are handled nonetheless.
+async fn rx_batch(
+    transport: &TransportUnicastInner,
+    link: &LinkUnicast,
+    mut zslice: ZSlice,
+) -> ZResult<bool> {
+    let codec = Zenoh060::default();
+    let mut reader = zslice.reader();
+    let mut callbacks = RxCallbacks::default();
+    while reader.can_read() {
+        let msg: TransportMessage = match codec.read(&mut reader) {
+            Ok(msg) => msg,
+            Err(_) => {
+                #[cfg(feature = "stats")]
+                transport.stats.inc_rx_t_decode_errors(1);
+                return Ok(false);
+            }
+        };
+
+        #[cfg(feature = "stats")]
+        {
+            transport.stats.inc_rx_t_msgs(1);
+        }
+
+        transport.receive_message(msg, link, &mut callbacks)?;
+        callbacks.run().await?;
+    }
+    Ok(true)
+}
+
+// The number of bytes skipped at most when looking for the next preamble, i.e. a whole batch
+const RESYNC_MAX_SKIPPED: usize = Preamble::LEN + 2 + u16::MAX as usize;
+
+// Skips the bytes received until `window` holds a preamble, returns the number of bytes skipped
+async fn resync(link: &LinkUnicast, window: &mut [u8; Preamble::LEN]) -> ZResult<usize> {
+    let mut skipped = 0;
+    while find_preamble(&window[..], Preamble::VERSION) != Some(0) {
+        if skipped >= RESYNC_MAX_SKIPPED {
+            bail!(
+                "{}: no preamble found in the last {} bytes received",
+                link,
+                skipped
+            );
+        }
+        window.copy_within(1.., 0);
+        link.read_exact(&mut window[Preamble::LEN - 1..]).await?;
+        skipped += 1;
+    }
+    Ok(skipped)
+}
+
+// The pool of rx buffers of a link
+type RxPool = RecyclingObjectPool<Box<[u8]>, Box<dyn Fn() -> Box<[u8]> + Send + Sync>>;
+
 async fn rx_task_stream(
     link: LinkUnicast,
+    preamble: bool,
     transport: TransportUnicastInner,
     lease: Duration,
     signal: Signal,
-    rx_buffer_size: usize,
+    pool: &RxPool,
+    probe: TaskProbe,
 ) -> ZResult<()> {
     enum Action {
-        Read(usize),
+        // The length of the batch read, and the number of bytes skipped before its preamble
+        Read(usize, usize),
         Stop,
     }
 
-    async fn read(link: &LinkUnicast, buffer: &mut [u8]) -> ZResult<Action> {
+    async fn read(
+        link: &LinkUnicast,
+        preamble: bool,
+        buffer: &mut [u8],
+        limiter: &mut RxLimiter,
+    ) -> ZResult<Action> {
+        let mut skipped = 0;
+        if preamble {
+            let mut window = [0_u8; Preamble::LEN];
+            link.read_exact(&mut window).await?;
+            skipped = resync(link, &mut window).await?;
+        }
         // 16 bits for reading the batch length
         let mut length = [0_u8, 0_u8];
         link.read_exact(&mut length).await?;
         let n = u16::from_le_bytes(length) as usize;
+        // Check the limits before reading the frame itself
+        limiter.check(link, n)?;
         link.read_exact(&mut buffer[0..n]).await?;
-        Ok(Action::Read(n))
+        Ok(Action::Read(n, skipped))
     }
 
     async fn stop(signal: Signal) -> ZResult<Action> {
@@ -257,45 +570,46 @@ async fn rx_task_stream(
         Ok(Action::Stop)
     }
 
-    let codec = Zenoh060::default();
+    let mut limiter = RxLimiter::new(transport.config.manager.config.unicast.rx_limits);
+    let mut decode_errors =
+        RxDecodeErrors::new(transport.config.manager.config.unicast.max_decode_errors);
 
-    // The pool of buffers
-    let mtu = link.get_mtu() as usize;
-    let mut n = rx_buffer_size / mtu;
-    if rx_buffer_size % mtu != 0 {
-        n += 1;
-    }
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
     while !signal.is_triggered() {
         // Retrieve one buffer
         let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
         // Async read from the underlying link
-        let action = read(&link, &mut buffer)
+        probe.idle();
+        // Any frame received renews the lease, be it a keep-alive or not
+        let action = read(&link, preamble, &mut buffer, &mut limiter)
             .race(stop(signal.clone()))
             .timeout(lease)
             .await
             .map_err(|_| zerror!("{}: expired after {} milliseconds", link, lease.as_millis()))??;
+        probe.progress();
         match action {
-            Action::Read(n) => {
+            Action::Read(n, skipped) => {
                 #[cfg(feature = "stats")]
                 {
-                    transport.stats.inc_rx_bytes(2 + n); // Account for the batch len encoding (16 bits)
+                    // Account for the batch len encoding (16 bits) and the preamble
+                    let header = if preamble { Preamble::LEN + 2 } else { 2 };
+                    transport.stats.inc_rx_bytes(skipped + header + n);
                 }
-
-                // Deserialize all the messages from the current ZBuf
-                let mut zslice = ZSlice::make(Arc::new(buffer), 0, n).unwrap();
-                let mut reader = zslice.reader();
-                while reader.can_read() {
-                    let msg: TransportMessage = codec
-                        .read(&mut reader)
-                        .map_err(|_| zerror!("{}: decoding error", link))?;
-
+                if skipped > 0 {
+                    // The previous batch did not end where its length said, e.g. a corrupted length
+                    log::debug!("{}: resynchronized after {} bytes", link, skipped);
                     #[cfg(feature = "stats")]
-                    {
-                        transport.stats.inc_rx_t_msgs(1);
-                    }
-
-                    transport.receive_message(msg, &link)?
+                    transport.stats.inc_rx_t_resyncs(1);
+                    decode_errors.failed(&link, &transport)?;
+                }
+                capture::capture(Direction::Rx, &link, &buffer[..n]);
+
+                // Deserialize all the messages from the current ZBuf, the next batch starts
+                // after its length regardless of a decoding error
+                let zslice = ZSlice::make(Arc::new(buffer), 0, n).unwrap();
+                if rx_batch(&transport, &link, zslice).await? {
+                    decode_errors.succeeded();
+                } else {
+                    decode_errors.failed(&link, &transport)?;
                 }
             }
             Action::Stop => break,
@@ -309,7 +623,8 @@ async fn rx_task_dgram(
     transport: TransportUnicastInner,
     lease: Duration,
     signal: Signal,
-    rx_buffer_size: usize,
+    pool: &RxPool,
+    probe: TaskProbe,
 ) -> ZResult<()> {
     enum Action {
         Read(usize),
@@ -326,50 +641,41 @@ async fn rx_task_dgram(
         Ok(Action::Stop)
     }
 
-    let codec = Zenoh060::default();
+    let mut limiter = RxLimiter::new(transport.config.manager.config.unicast.rx_limits);
+    let mut decode_errors =
+        RxDecodeErrors::new(transport.config.manager.config.unicast.max_decode_errors);
 
-    // The pool of buffers
-    let mtu = link.get_mtu() as usize;
-    let mut n = rx_buffer_size / mtu;
-    if rx_buffer_size % mtu != 0 {
-        n += 1;
-    }
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
     while !signal.is_triggered() {
         // Retrieve one buffer
         let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
         // Async read from the underlying link
+        probe.idle();
         let action = read(&link, &mut buffer)
             .race(stop(signal.clone()))
             .timeout(lease)
             .await
             .map_err(|_| zerror!("{}: expired after {} milliseconds", link, lease.as_millis()))??;
+        probe.progress();
         match action {
             Action::Read(n) => {
                 if n == 0 {
                     // Reading 0 bytes means error
                     bail!("{}: zero bytes reading", link)
                 }
+                limiter.check(&link, n)?;
 
                 #[cfg(feature = "stats")]
                 {
                     transport.stats.inc_rx_bytes(n);
                 }
-
-                // Deserialize all the messages from the current ZBuf
-                let mut zslice = ZSlice::make(Arc::new(buffer), 0, n).unwrap();
-                let mut reader = zslice.reader();
-                while reader.can_read() {
-                    let msg: TransportMessage = codec
-                        .read(&mut reader)
-                        .map_err(|_| zerror!("{}: decoding error", link))?;
-
-                    #[cfg(feature = "stats")]
-                    {
-                        transport.stats.inc_rx_t_msgs(1);
-                    }
-
-                    transport.receive_message(msg, &link)?
+                capture::capture(Direction::Rx, &link, &buffer[..n]);
+
+                // Deserialize all the messages from the current ZBuf, each datagram being a batch
+                let zslice = ZSlice::make(Arc::new(buffer), 0, n).unwrap();
+                if rx_batch(&transport, &link, zslice).await? {
+                    decode_errors.succeeded();
+                } else {
+                    decode_errors.failed(&link, &transport)?;
                 }
             }
             Action::Stop => break,
@@ -380,14 +686,162 @@ async fn rx_task_dgram(
 
 async fn rx_task(
     link: LinkUnicast,
+    preamble: bool,
     transport: TransportUnicastInner,
     lease: Duration,
     signal: Signal,
     rx_buffer_size: usize,
+    probe: TaskProbe,
 ) -> ZResult<()> {
-    if link.is_streamed() {
-        rx_task_stream(link, transport, lease, signal, rx_buffer_size).await
+    // The pool of buffers, taken from the pool shared by all the links and given back to it
+    let shared = transport.config.manager.buffer_pool.clone();
+    let mtu = link.get_mtu() as usize;
+    let mut n = rx_buffer_size / mtu;
+    if rx_buffer_size % mtu != 0 {
+        n += 1;
+    }
+    let alloc: Box<dyn Fn() -> Box<[u8]> + Send + Sync> = {
+        let shared = shared.clone();
+        Box::new(move || shared.take(mtu))
+    };
+    let pool: RxPool = RecyclingObjectPool::new(n, alloc);
+
+    let res = if link.is_streamed() {
+        rx_task_stream(link, preamble, transport, lease, signal, &pool, probe).await
     } else {
-        rx_task_dgram(link, transport, lease, signal, rx_buffer_size).await
+        rx_task_dgram(link, transport, lease, signal, &pool, probe).await
+    };
+    for buffer in pool.drain() {
+        shared.give(buffer);
+    }
+    res
+}
+
+#[cfg(all(test, feature = "transport_tcp", feature = "transport_udp"))]
+mod tests {
+    use super::*;
+    use crate::{DummyTransportEventHandler, TransportManager, TransportUnicast};
+    use async_std::task;
+    use std::convert::TryFrom;
+    use zenoh_core::{zasync_executor_init, zlock};
+    use zenoh_protocol::core::{EndPoint, WhatAmI, ZenohId};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(10);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    // Opens a transport from a client to a router listening on `endpoint`
+    async fn open(endpoint: &EndPoint) -> (TransportManager, TransportManager, TransportUnicast) {
+        let client_id = ZenohId::try_from([1]).unwrap();
+        let router_id = ZenohId::try_from([2]).unwrap();
+        let manager = |zid, whatami| {
+            TransportManager::builder()
+                .zid(zid)
+                .whatami(whatami)
+                .unicast(
+                    TransportManager::config_unicast()
+                        .preamble(false)
+                        .max_decode_errors(1),
+                )
+                .build(Arc::new(DummyTransportEventHandler::default()))
+                .unwrap()
+        };
+        let router_manager = manager(router_id, WhatAmI::Router);
+        let client_manager = manager(client_id, WhatAmI::Client);
+        ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+        ztimeout!(client_manager.open_transport(endpoint.clone())).unwrap();
+        let router_transport = ztimeout!(async {
+            loop {
+                match router_manager.get_transport_unicast(&client_id) {
+                    Some(transport) => break transport,
+                    None => task::sleep(SLEEP).await,
+                }
+            }
+        });
+        (router_manager, client_manager, router_transport)
+    }
+
+    // Writes a batch failing to decode on the link of the client to the router
+    async fn inject_corrupt_batch(client_manager: &TransportManager) {
+        let transport = client_manager.get_transports_unicast().pop().unwrap();
+        let link = transport.get_inner().unwrap().get_links().pop().unwrap();
+        let batch = [0xff_u8; 8];
+        if link.is_streamed() {
+            let mut bytes = (batch.len() as u16).to_le_bytes().to_vec();
+            bytes.extend_from_slice(&batch);
+            link.write_all(&bytes).await.unwrap();
+        } else {
+            link.write_all(&batch).await.unwrap();
+        }
+    }
+
+    async fn close(router_manager: TransportManager, client_manager: TransportManager) {
+        ztimeout!(client_manager.close());
+        ztimeout!(router_manager.close());
+    }
+
+    #[test]
+    fn rx_decode_error_reliable() {
+        task::block_on(async {
+            zasync_executor_init!();
+            let endpoint: EndPoint = "tcp/127.0.0.1:19030".parse().unwrap();
+            let (router_manager, client_manager, router_transport) = open(&endpoint).await;
+
+            // The reliable link skips the batch and resumes with the next one
+            inject_corrupt_batch(&client_manager).await;
+            task::sleep(100 * SLEEP).await;
+            #[cfg(feature = "stats")]
+            assert_eq!(router_transport.get_stats().unwrap().rx_t_decode_errors, 1);
+            assert_eq!(router_manager.get_transports_unicast().len(), 1);
+            assert!(router_transport.get_zid().is_ok());
+
+            // It is closed once more consecutive batches than configured failed to decode
+            inject_corrupt_batch(&client_manager).await;
+            inject_corrupt_batch(&client_manager).await;
+            ztimeout!(async {
+                while !router_manager.get_transports_unicast().is_empty() {
+                    task::sleep(SLEEP).await;
+                }
+            });
+
+            close(router_manager, client_manager).await;
+        });
+    }
+
+    #[test]
+    fn rx_decode_error_best_effort() {
+        task::block_on(async {
+            zasync_executor_init!();
+            let endpoint: EndPoint = "udp/127.0.0.1:19031".parse().unwrap();
+            let (router_manager, client_manager, router_transport) = open(&endpoint).await;
+
+            // A fragment is being reassembled when the batch fails to decode
+            let inner = router_transport.get_inner().unwrap();
+            {
+                let mut guard = zlock!(inner.conduit_rx[0].best_effort);
+                let sn = guard.sn.get();
+                guard.defrag.sync(sn).unwrap();
+                guard.defrag.push(sn, vec![0_u8; 8].into()).unwrap();
+            }
+
+            // The best-effort link skips the batch and drops the fragments it may have held
+            inject_corrupt_batch(&client_manager).await;
+            ztimeout!(async {
+                while !zlock!(inner.conduit_rx[0].best_effort).defrag.is_empty() {
+                    task::sleep(SLEEP).await;
+                }
+            });
+            #[cfg(feature = "stats")]
+            assert_eq!(router_transport.get_stats().unwrap().rx_t_decode_errors, 1);
+            assert_eq!(router_manager.get_transports_unicast().len(), 1);
+            assert!(router_transport.get_zid().is_ok());
+
+            close(router_manager, client_manager).await;
+        });
     }
 }
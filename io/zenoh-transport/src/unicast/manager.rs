@@ -12,23 +12,25 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::unicast::{
-    establishment::authenticator::*,
+    establishment::{
+        authenticator::*, replay::ReplayCache, EstablishmentError, HandshakeError, HandshakeFailure,
+    },
     transport::{TransportUnicastConfig, TransportUnicastInner},
     TransportConfigUnicast, TransportUnicast,
 };
 use crate::TransportManager;
 use async_std::prelude::FutureExt;
 use async_std::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
-use async_std::task;
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use zenoh_cfg_properties::config::*;
-use zenoh_config::Config;
-use zenoh_core::{zasynclock, zasyncread, zasyncwrite, zlock, zparse};
+use zenoh_config::{unwrap_or_default, Config};
+use zenoh_core::{executor, zasynclock, zasyncread, zasyncwrite, zlock, zparse, zread};
 use zenoh_link::*;
 use zenoh_protocol::{
-    core::{endpoint::Protocol, ZenohId},
+    core::{endpoint::Protocol, ZInt, ZenohId},
     transport::tmsg,
 };
 use zenoh_result::{bail, zerror, ZResult};
@@ -39,15 +41,119 @@ use zenoh_result::{bail, zerror, ZResult};
 pub struct TransportManagerConfigUnicast {
     pub lease: Duration,
     pub keep_alive: usize,
+    pub adaptive_keep_alive: bool,
+    pub preamble: bool,
     pub accept_timeout: Duration,
     pub accept_pending: usize,
     pub max_sessions: usize,
     pub max_links: usize,
+    pub replay_window: Duration,
+    pub replay_cache_size: usize,
+    pub ping_interval: Duration,
+    pub rtt_scheduling: bool,
+    pub slow_consumer_threshold: Duration,
+    pub rx_limits: LinkRxLimits,
+    pub max_decode_errors: usize,
     pub is_qos: bool,
+    pub extensions: ZInt,
     #[cfg(feature = "shared-memory")]
     pub is_shm: bool,
 }
 
+/// The ingress limits of each link, a peer exceeding them has its transport closed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LinkRxLimits {
+    /// The maximum size in bytes of a received frame.
+    pub max_frame_size: Option<usize>,
+    /// The maximum size in bytes of a message reassembled from fragments.
+    pub max_message_size: Option<usize>,
+    /// The maximum number of frames received per second.
+    pub max_batch_rate: Option<u32>,
+}
+
+/// The number of inbound links rejected because a limit of the transport manager was reached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransportRejectionStats {
+    /// The links rejected because `max_sessions` transports were already open.
+    pub max_sessions: usize,
+    /// The links rejected because their transport already had `max_links` inbound links.
+    pub max_links: usize,
+}
+
+/// The outcomes of the handshakes on inbound links.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransportHandshakeStats {
+    /// The handshakes started, i.e. the inbound links not closed straight away by `accept_pending`.
+    pub attempts: usize,
+    /// The handshakes that added the link to a transport.
+    pub successes: usize,
+    /// The handshakes failed because a link or peer authenticator rejected the remote node.
+    pub auth_failed: usize,
+    /// The handshakes failed because the remote node runs another protocol version.
+    pub incompatible_version: usize,
+    /// The handshakes failed because `max_sessions` transports were already open.
+    pub max_sessions: usize,
+    /// The handshakes failed because their transport already had `max_links` inbound links.
+    pub max_links: usize,
+    /// The handshakes failed because the cookie sent back by the remote node was unknown,
+    /// undecodable or replayed.
+    pub invalid_cookie: usize,
+    /// The handshakes not completed within `accept_timeout`.
+    pub timeout: usize,
+    /// The handshakes failed for any other reason, e.g. a malformed message.
+    pub other: usize,
+}
+
+#[derive(Default)]
+pub(super) struct TransportHandshakeStatsAtomic {
+    attempts: AtomicUsize,
+    successes: AtomicUsize,
+    auth_failed: AtomicUsize,
+    incompatible_version: AtomicUsize,
+    max_sessions: AtomicUsize,
+    max_links: AtomicUsize,
+    invalid_cookie: AtomicUsize,
+    timeout: AtomicUsize,
+    other: AtomicUsize,
+}
+
+impl TransportHandshakeStatsAtomic {
+    fn attempt(&self) {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn success(&self) {
+        self.successes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn failure(&self, cause: HandshakeFailure) {
+        match cause {
+            HandshakeFailure::AuthFailed => &self.auth_failed,
+            HandshakeFailure::IncompatibleVersion => &self.incompatible_version,
+            HandshakeFailure::MaxSessions => &self.max_sessions,
+            HandshakeFailure::MaxLinks => &self.max_links,
+            HandshakeFailure::InvalidCookie => &self.invalid_cookie,
+            HandshakeFailure::Timeout => &self.timeout,
+            HandshakeFailure::Other => &self.other,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> TransportHandshakeStats {
+        TransportHandshakeStats {
+            attempts: self.attempts.load(Ordering::Relaxed),
+            successes: self.successes.load(Ordering::Relaxed),
+            auth_failed: self.auth_failed.load(Ordering::Relaxed),
+            incompatible_version: self.incompatible_version.load(Ordering::Relaxed),
+            max_sessions: self.max_sessions.load(Ordering::Relaxed),
+            max_links: self.max_links.load(Ordering::Relaxed),
+            invalid_cookie: self.invalid_cookie.load(Ordering::Relaxed),
+            timeout: self.timeout.load(Ordering::Relaxed),
+            other: self.other.load(Ordering::Relaxed),
+        }
+    }
+}
+
 pub struct TransportManagerStateUnicast {
     // Incoming uninitialized transports
     pub(super) incoming: Arc<AsyncMutex<usize>>,
@@ -59,6 +165,13 @@ pub struct TransportManagerStateUnicast {
     pub(super) protocols: Arc<Mutex<HashMap<String, LinkManagerUnicast>>>,
     // Established transports
     pub(super) transports: Arc<Mutex<HashMap<ZenohId, Arc<TransportUnicastInner>>>>,
+    // Cookies accepted on connectionless links
+    pub(super) replay_cache: Arc<Mutex<ReplayCache>>,
+    // Inbound links rejected because of the max_sessions and max_links limits
+    pub(super) rejected_sessions: Arc<AtomicUsize>,
+    pub(super) rejected_links: Arc<AtomicUsize>,
+    // Outcomes of the handshakes on inbound links
+    pub(super) handshakes: Arc<TransportHandshakeStatsAtomic>,
 }
 
 pub struct TransportManagerParamsUnicast {
@@ -74,11 +187,21 @@ pub struct TransportManagerBuilderUnicast {
     //       target interval.
     pub(super) lease: Duration,
     pub(super) keep_alive: usize,
+    pub(super) adaptive_keep_alive: bool,
+    pub(super) preamble: bool,
     pub(super) accept_timeout: Duration,
     pub(super) accept_pending: usize,
     pub(super) max_sessions: usize,
     pub(super) max_links: usize,
+    pub(super) replay_window: Duration,
+    pub(super) replay_cache_size: usize,
+    pub(super) ping_interval: Duration,
+    pub(super) rtt_scheduling: bool,
+    pub(super) slow_consumer_threshold: Duration,
+    pub(super) rx_limits: LinkRxLimits,
+    pub(super) max_decode_errors: usize,
     pub(super) is_qos: bool,
+    pub(super) extensions: ZInt,
     #[cfg(feature = "shared-memory")]
     pub(super) is_shm: bool,
     pub(super) peer_authenticator: HashSet<PeerAuthenticator>,
@@ -96,6 +219,16 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    pub fn adaptive_keep_alive(mut self, adaptive_keep_alive: bool) -> Self {
+        self.adaptive_keep_alive = adaptive_keep_alive;
+        self
+    }
+
+    pub fn preamble(mut self, preamble: bool) -> Self {
+        self.preamble = preamble;
+        self
+    }
+
     pub fn accept_timeout(mut self, accept_timeout: Duration) -> Self {
         self.accept_timeout = accept_timeout;
         self
@@ -116,6 +249,46 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    pub fn replay_window(mut self, replay_window: Duration) -> Self {
+        self.replay_window = replay_window;
+        self
+    }
+
+    pub fn replay_cache_size(mut self, replay_cache_size: usize) -> Self {
+        self.replay_cache_size = replay_cache_size;
+        self
+    }
+
+    /// Sets the interval at which each link is pinged to measure its round-trip time.
+    /// A zero interval disables the pings.
+    pub fn ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.ping_interval = ping_interval;
+        self
+    }
+
+    pub fn rtt_scheduling(mut self, rtt_scheduling: bool) -> Self {
+        self.rtt_scheduling = rtt_scheduling;
+        self
+    }
+
+    /// Sets the duration above which a peer whose tx queue stays saturated is reported.
+    /// A zero threshold disables the detection.
+    pub fn slow_consumer_threshold(mut self, slow_consumer_threshold: Duration) -> Self {
+        self.slow_consumer_threshold = slow_consumer_threshold;
+        self
+    }
+
+    pub fn rx_limits(mut self, rx_limits: LinkRxLimits) -> Self {
+        self.rx_limits = rx_limits;
+        self
+    }
+
+    /// Sets the number of consecutive batches failing to decode on a link before it is closed.
+    pub fn max_decode_errors(mut self, max_decode_errors: usize) -> Self {
+        self.max_decode_errors = max_decode_errors;
+        self
+    }
+
     pub fn peer_authenticator(mut self, peer_authenticator: HashSet<PeerAuthenticator>) -> Self {
         self.peer_authenticator = peer_authenticator;
         self
@@ -131,6 +304,12 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    /// The zenoh message extensions announced in INIT, see `tmsg::init_options::EXTENSIONS`.
+    pub fn extensions(mut self, extensions: ZInt) -> Self {
+        self.extensions = extensions & tmsg::init_options::EXTENSIONS;
+        self
+    }
+
     #[cfg(feature = "shared-memory")]
     pub fn shm(mut self, is_shm: bool) -> Self {
         self.is_shm = is_shm;
@@ -142,12 +321,39 @@ impl TransportManagerBuilderUnicast {
             config.transport().link().tx().lease().unwrap(),
         ));
         self = self.keep_alive(config.transport().link().tx().keep_alive().unwrap());
+        self = self.adaptive_keep_alive(
+            config
+                .transport()
+                .link()
+                .tx()
+                .adaptive_keep_alive()
+                .unwrap(),
+        );
+        self = self.preamble(config.transport().link().tx().preamble().unwrap());
         self = self.accept_timeout(Duration::from_millis(
             config.transport().unicast().accept_timeout().unwrap(),
         ));
         self = self.accept_pending(config.transport().unicast().accept_pending().unwrap());
         self = self.max_sessions(config.transport().unicast().max_sessions().unwrap());
         self = self.max_links(config.transport().unicast().max_links().unwrap());
+        self = self.replay_window(Duration::from_millis(
+            config.transport().unicast().replay_window().unwrap(),
+        ));
+        self = self.replay_cache_size(config.transport().unicast().replay_cache_size().unwrap());
+        self = self.ping_interval(Duration::from_millis(
+            config.transport().unicast().ping_interval().unwrap(),
+        ));
+        self = self.rtt_scheduling(config.transport().unicast().rtt_scheduling().unwrap());
+        self = self.slow_consumer_threshold(Duration::from_millis(unwrap_or_default!(config
+            .slow_consumer()
+            .threshold())));
+        let limits = config.transport().link().rx().limits();
+        self = self.rx_limits(LinkRxLimits {
+            max_frame_size: *limits.max_frame_size(),
+            max_message_size: *limits.max_message_size(),
+            max_batch_rate: *limits.max_batch_rate(),
+        });
+        self = self.max_decode_errors(config.transport().link().rx().max_decode_errors().unwrap());
         self = self.qos(*config.transport().qos().enabled());
 
         #[cfg(feature = "shared-memory")]
@@ -167,11 +373,21 @@ impl TransportManagerBuilderUnicast {
         let config = TransportManagerConfigUnicast {
             lease: self.lease,
             keep_alive: self.keep_alive,
+            adaptive_keep_alive: self.adaptive_keep_alive,
+            preamble: self.preamble,
             accept_timeout: self.accept_timeout,
             accept_pending: self.accept_pending,
             max_sessions: self.max_sessions,
             max_links: self.max_links,
+            replay_window: self.replay_window,
+            replay_cache_size: self.replay_cache_size,
+            ping_interval: self.ping_interval,
+            rtt_scheduling: self.rtt_scheduling,
+            slow_consumer_threshold: self.slow_consumer_threshold,
+            rx_limits: self.rx_limits,
+            max_decode_errors: self.max_decode_errors,
             is_qos: self.is_qos,
+            extensions: self.extensions,
             #[cfg(feature = "shared-memory")]
             is_shm: self.is_shm,
         };
@@ -204,6 +420,13 @@ impl TransportManagerBuilderUnicast {
             transports: Arc::new(Mutex::new(HashMap::new())),
             link_authenticator: Arc::new(AsyncRwLock::new(self.link_authenticator)),
             peer_authenticator: Arc::new(AsyncRwLock::new(self.peer_authenticator)),
+            replay_cache: Arc::new(Mutex::new(ReplayCache::new(
+                self.replay_window,
+                self.replay_cache_size,
+            ))),
+            rejected_sessions: Arc::new(AtomicUsize::new(0)),
+            rejected_links: Arc::new(AtomicUsize::new(0)),
+            handshakes: Arc::new(TransportHandshakeStatsAtomic::default()),
         };
 
         let params = TransportManagerParamsUnicast { config, state };
@@ -217,11 +440,21 @@ impl Default for TransportManagerBuilderUnicast {
         Self {
             lease: Duration::from_millis(zparse!(ZN_LINK_LEASE_DEFAULT).unwrap()),
             keep_alive: zparse!(ZN_LINK_KEEP_ALIVE_DEFAULT).unwrap(),
+            adaptive_keep_alive: zparse!(ZN_ADAPTIVE_KEEP_ALIVE_DEFAULT).unwrap(),
+            preamble: zparse!(ZN_LINK_PREAMBLE_DEFAULT).unwrap(),
             accept_timeout: Duration::from_millis(zparse!(ZN_OPEN_TIMEOUT_DEFAULT).unwrap()),
             accept_pending: zparse!(ZN_OPEN_INCOMING_PENDING_DEFAULT).unwrap(),
             max_sessions: zparse!(ZN_MAX_SESSIONS_UNICAST_DEFAULT).unwrap(),
             max_links: zparse!(ZN_MAX_LINKS_DEFAULT).unwrap(),
+            replay_window: Duration::from_millis(zparse!(ZN_REPLAY_WINDOW_DEFAULT).unwrap()),
+            replay_cache_size: zparse!(ZN_REPLAY_CACHE_SIZE_DEFAULT).unwrap(),
+            ping_interval: Duration::from_millis(zparse!(ZN_PING_INTERVAL_DEFAULT).unwrap()),
+            rtt_scheduling: zparse!(ZN_RTT_SCHEDULING_DEFAULT).unwrap(),
+            slow_consumer_threshold: Duration::ZERO,
+            rx_limits: LinkRxLimits::default(),
+            max_decode_errors: zparse!(ZN_LINK_RX_MAX_DECODE_ERRORS_DEFAULT).unwrap(),
             is_qos: zparse!(ZN_QOS_DEFAULT).unwrap(),
+            extensions: tmsg::init_options::EXTENSIONS,
             #[cfg(feature = "shared-memory")]
             is_shm: zparse!(ZN_SHM_DEFAULT).unwrap(),
             peer_authenticator: HashSet::new(),
@@ -263,10 +496,17 @@ impl TransportManager {
             }
         }
 
-        let mut tu_guard = zlock!(self.state.unicast.transports)
-            .drain()
-            .map(|(_, v)| v)
-            .collect::<Vec<Arc<TransportUnicastInner>>>();
+        let mut tu_guard = {
+            let mut guard = zlock!(self.state.unicast.transports);
+            #[cfg(feature = "stats")]
+            for tu in guard.values() {
+                *zlock!(self.closed_stats) += tu.transport_stats();
+            }
+            guard
+                .drain()
+                .map(|(_, v)| v)
+                .collect::<Vec<Arc<TransportUnicastInner>>>()
+        };
         for tu in tu_guard.drain(..) {
             let _ = tu.close(tmsg::close_reason::GENERIC).await;
         }
@@ -349,6 +589,53 @@ impl TransportManager {
     /*************************************/
     /*             TRANSPORT             */
     /*************************************/
+    /// Returns the reason for which a new inbound link with `peer` must be rejected, if opening it
+    /// would exceed the `max_sessions` or `max_links` limits, and counts the rejection.
+    pub(super) fn reject_inbound_unicast(&self, peer: &ZenohId) -> Option<u8> {
+        let guard = zlock!(self.state.unicast.transports);
+        match guard.get(peer) {
+            Some(transport) => {
+                let links = zread!(transport.links)
+                    .iter()
+                    .filter(|l| l.direction == LinkUnicastDirection::Inbound)
+                    .count();
+                if links >= self.config.unicast.max_links {
+                    self.count_rejection_unicast(tmsg::close_reason::MAX_LINKS);
+                    return Some(tmsg::close_reason::MAX_LINKS);
+                }
+            }
+            None => {
+                if guard.len() >= self.config.unicast.max_sessions {
+                    self.count_rejection_unicast(tmsg::close_reason::MAX_SESSIONS);
+                    return Some(tmsg::close_reason::MAX_SESSIONS);
+                }
+            }
+        }
+        None
+    }
+
+    pub(super) fn count_rejection_unicast(&self, reason: u8) {
+        match reason {
+            tmsg::close_reason::MAX_SESSIONS => &self.state.unicast.rejected_sessions,
+            tmsg::close_reason::MAX_LINKS => &self.state.unicast.rejected_links,
+            _ => return,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The number of inbound links rejected because of the `max_sessions` and `max_links` limits.
+    pub fn get_rejection_stats_unicast(&self) -> TransportRejectionStats {
+        TransportRejectionStats {
+            max_sessions: self.state.unicast.rejected_sessions.load(Ordering::Relaxed),
+            max_links: self.state.unicast.rejected_links.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The outcomes of the handshakes on inbound links.
+    pub fn get_handshake_stats_unicast(&self) -> TransportHandshakeStats {
+        self.state.unicast.handshakes.snapshot()
+    }
+
     pub(super) fn init_transport_unicast(
         &self,
         config: TransportConfigUnicast,
@@ -403,6 +690,17 @@ impl TransportManager {
                     return Err(e.into());
                 }
 
+                if transport.config.extensions != config.extensions {
+                    let e = zerror!(
+                        "Transport with peer {} already exist. Invalid extensions: {:#x}. Execpted: {:#x}.",
+                        config.peer,
+                        config.extensions,
+                        transport.config.extensions
+                    );
+                    log::trace!("{}", e);
+                    return Err(e.into());
+                }
+
                 Ok(transport.into())
             }
             None => {
@@ -414,6 +712,17 @@ impl TransportManager {
                         config.peer
                     );
                     log::trace!("{}", e);
+                    return Err(HandshakeError(HandshakeFailure::MaxSessions, e.into()).into());
+                }
+
+                // Then verify that the memory budget allows a new transport
+                if self.config.memory.rejects_sessions() {
+                    let e = zerror!(
+                        "Memory budget exhausted ({} bytes used). Denying new transport with peer: {}",
+                        self.config.memory.used(),
+                        config.peer
+                    );
+                    log::trace!("{}", e);
                     return Err(e.into());
                 }
 
@@ -426,6 +735,7 @@ impl TransportManager {
                     initial_sn_tx: config.initial_sn_tx,
                     is_shm: config.is_shm,
                     is_qos: config.is_qos,
+                    extensions: config.extensions,
                 };
                 let a_t = Arc::new(TransportUnicastInner::make(stc)?);
 
@@ -478,7 +788,9 @@ impl TransportManager {
             dst: link.get_src().to_owned(),
             peer_id: None,
         };
-        super::establishment::open::open_link(&link, self, &mut auth_link).await
+        super::establishment::open::open_link(&link, self, &mut auth_link)
+            .await
+            .map_err(|e| EstablishmentError(e).into())
     }
 
     pub fn get_transport_unicast(&self, peer: &ZenohId) -> Option<TransportUnicast> {
@@ -495,13 +807,19 @@ impl TransportManager {
     }
 
     pub(super) async fn del_transport_unicast(&self, peer: &ZenohId) -> ZResult<()> {
-        let _ = zlock!(self.state.unicast.transports)
-            .remove(peer)
-            .ok_or_else(|| {
+        {
+            let mut guard = zlock!(self.state.unicast.transports);
+            let res = guard.remove(peer);
+            #[cfg(feature = "stats")]
+            if let Some(transport) = res.as_ref() {
+                *zlock!(self.closed_stats) += transport.transport_stats();
+            }
+            res.ok_or_else(|| {
                 let e = zerror!("Can not delete the transport of peer: {}", peer);
                 log::trace!("{}", e);
                 e
             })?;
+        }
 
         for pa in zasyncread!(self.state.unicast.peer_authenticator).iter() {
             pa.handle_close(peer).await;
@@ -527,6 +845,7 @@ impl TransportManager {
         log::trace!("New link waiting... {}", link);
         *guard += 1;
         drop(guard);
+        self.state.unicast.handshakes.attempt();
 
         let mut peer_id: Option<ZenohId> = None;
         let peer_link = Link::from(&link);
@@ -539,6 +858,10 @@ impl TransportManager {
                         if let Some(zid2) = zid.as_ref() {
                             if zid1 != zid2 {
                                 log::debug!("Ambigous PeerID identification for link: {}", link);
+                                self.state
+                                    .unicast
+                                    .handshakes
+                                    .failure(HandshakeFailure::AuthFailed);
                                 let _ = link.close().await;
                                 let mut guard = zasynclock!(self.state.unicast.incoming);
                                 *guard -= 1;
@@ -551,6 +874,10 @@ impl TransportManager {
                 }
                 Err(e) => {
                     log::debug!("{}", e);
+                    self.state
+                        .unicast
+                        .handshakes
+                        .failure(HandshakeFailure::AuthFailed);
                     let mut guard = zasynclock!(self.state.unicast.incoming);
                     *guard -= 1;
                     return;
@@ -560,20 +887,26 @@ impl TransportManager {
 
         // Spawn a task to accept the link
         let c_manager = self.clone();
-        task::spawn(async move {
+        executor::spawn(async move {
             let mut auth_link = AuthenticatedPeerLink {
                 src: link.get_src().to_owned(),
                 dst: link.get_dst().to_owned(),
                 peer_id,
             };
 
-            if let Err(e) =
-                super::establishment::accept::accept_link(&link, &c_manager, &mut auth_link)
-                    .timeout(c_manager.config.unicast.accept_timeout)
-                    .await
-            {
-                log::debug!("{}", e);
-                let _ = link.close().await;
+            let res = super::establishment::accept::accept_link(&link, &c_manager, &mut auth_link)
+                .timeout(c_manager.config.unicast.accept_timeout)
+                .await;
+            let handshakes = &c_manager.state.unicast.handshakes;
+            match res {
+                Ok(Ok(())) => handshakes.success(),
+                // The link has already been closed by the failed handshake step
+                Ok(Err(e)) => handshakes.failure(HandshakeFailure::of(&e)),
+                Err(e) => {
+                    log::debug!("{}", e);
+                    handshakes.failure(HandshakeFailure::Timeout);
+                    let _ = link.close().await;
+                }
             }
             let mut guard = zasynclock!(c_manager.state.unicast.incoming);
             *guard -= 1;
@@ -14,21 +14,23 @@
 pub mod establishment;
 pub(crate) mod link;
 pub(crate) mod manager;
+pub(crate) mod rtt;
 pub(crate) mod rx;
 pub(crate) mod transport;
 pub(crate) mod tx;
 
 use super::common;
 #[cfg(feature = "stats")]
-use super::common::stats::stats_struct;
+use super::common::stats::{stats_struct, transport_stats, PriorityStats, TransportStats};
 use super::{TransportPeer, TransportPeerEventHandler};
 pub use manager::*;
+pub use rtt::RttStats;
 use std::fmt;
 use std::sync::{Arc, Weak};
 use transport::TransportUnicastInner;
 use zenoh_link::Link;
 use zenoh_protocol::{
-    core::{WhatAmI, ZInt, ZenohId},
+    core::{Locator, WhatAmI, ZInt, ZenohId},
     transport::tmsg,
     zenoh::ZenohMessage,
 };
@@ -48,6 +50,7 @@ stats_struct! {
         pub tx_t_msgs,
         pub tx_z_msgs,
         pub tx_z_dropped,
+        pub tx_z_slow_consumer_dropped,
         pub tx_z_data_msgs,
         pub tx_z_data_payload_bytes,
         pub tx_z_data_reply_msgs,
@@ -60,6 +63,15 @@ stats_struct! {
         pub tx_z_unit_reply_msgs,
         pub tx_bytes,
         pub rx_t_msgs,
+        pub rx_t_dropped,
+        // Protocol anomalies of the remote peer
+        pub rx_t_invalid_sn,
+        pub rx_t_defrag_errors,
+        pub rx_t_unknown_conduit,
+        pub rx_t_invalid_close,
+        pub rx_t_unexpected_msgs,
+        pub rx_t_decode_errors,
+        pub rx_t_resyncs,
         pub rx_z_msgs,
         pub rx_z_data_msgs,
         pub rx_z_data_payload_bytes,
@@ -86,6 +98,7 @@ pub(crate) struct TransportConfigUnicast {
     pub(crate) initial_sn_tx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    pub(crate) extensions: ZInt,
 }
 
 /// [`TransportUnicast`] is the transport handler returned
@@ -131,6 +144,13 @@ impl TransportUnicast {
         Ok(transport.is_qos())
     }
 
+    /// Whether the zenoh message extension has been agreed with the peer during INIT.
+    #[inline(always)]
+    pub fn has_extension(&self, extension: ZInt) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.has_extension(extension))
+    }
+
     #[inline(always)]
     pub fn get_callback(&self) -> ZResult<Option<Arc<dyn TransportPeerEventHandler>>> {
         let transport = self.get_inner()?;
@@ -163,6 +183,16 @@ impl TransportUnicast {
             .collect())
     }
 
+    /// Returns the links along with their round-trip time, if measured.
+    pub fn get_links_rtt(&self) -> ZResult<Vec<(Link, Option<RttStats>)>> {
+        let transport = self.get_inner()?;
+        Ok(transport
+            .get_links_rtt()
+            .into_iter()
+            .map(|(l, rtt)| (l.into(), rtt))
+            .collect())
+    }
+
     #[inline(always)]
     pub fn schedule(&self, message: ZenohMessage) -> ZResult<()> {
         let transport = self.get_inner()?;
@@ -170,6 +200,15 @@ impl TransportUnicast {
         Ok(())
     }
 
+    /// Schedules messages sharing the same channel, so that no other message is sent on that
+    /// channel in between.
+    #[inline(always)]
+    pub fn schedule_batch(&self, messages: Vec<ZenohMessage>) -> ZResult<()> {
+        let transport = self.get_inner()?;
+        transport.schedule_batch(messages);
+        Ok(())
+    }
+
     #[inline(always)]
     pub async fn close_link(&self, link: &Link) -> ZResult<()> {
         let transport = self.get_inner()?;
@@ -193,6 +232,28 @@ impl TransportUnicast {
         }
     }
 
+    /// Closes the transport, notifying the remote peer with the given
+    /// [`close_reason`](tmsg::close_reason).
+    #[inline(always)]
+    pub async fn close_with_reason(&self, reason: u8) -> ZResult<()> {
+        // Return Ok if the transport has already been closed
+        match self.get_inner() {
+            Ok(transport) => transport.close(reason).await,
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Closes the transport with the [`MIGRATE`](tmsg::close_reason::MIGRATE) reason,
+    /// suggesting the remote peer to reconnect to the given locator.
+    #[inline(always)]
+    pub async fn migrate(&self, locator: Locator) -> ZResult<()> {
+        // Return Ok if the transport has already been closed
+        match self.get_inner() {
+            Ok(transport) => transport.migrate(locator).await,
+            Err(_) => Ok(()),
+        }
+    }
+
     #[inline(always)]
     pub fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
         self.schedule(message)
@@ -202,6 +263,30 @@ impl TransportUnicast {
     pub fn get_stats(&self) -> ZResult<TransportUnicastStats> {
         Ok(self.get_inner()?.stats.snapshot())
     }
+
+    /// Returns a typed snapshot of the statistics of this transport.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> ZResult<TransportStats> {
+        Ok(self.get_inner()?.transport_stats())
+    }
+}
+
+#[cfg(feature = "stats")]
+pub(crate) fn typed_stats(
+    stats: TransportUnicastStats,
+    priorities: [PriorityStats; zenoh_protocol::core::Priority::NUM],
+) -> TransportStats {
+    transport_stats!(
+        stats,
+        priorities,
+        invalid_sn: rx_t_invalid_sn,
+        defrag_errors: rx_t_defrag_errors,
+        unknown_conduit: rx_t_unknown_conduit,
+        invalid_close: rx_t_invalid_close,
+        unexpected_msgs: rx_t_unexpected_msgs,
+        decode_errors: rx_t_decode_errors,
+        resyncs: rx_t_resyncs,
+    )
 }
 
 impl From<&Arc<TransportUnicastInner>> for TransportUnicast {
@@ -236,3 +321,37 @@ impl fmt::Debug for TransportUnicast {
         }
     }
 }
+
+#[cfg(all(test, feature = "stats"))]
+mod tests {
+    use super::*;
+    use crate::common::stats::AnomalyStats;
+
+    #[test]
+    fn transport_unicast_anomalies() {
+        let stats = TransportUnicastStatsAtomic::default();
+        stats.inc_rx_t_invalid_sn(1);
+        stats.inc_rx_t_defrag_errors(2);
+        stats.inc_rx_t_unknown_conduit(3);
+        stats.inc_rx_t_invalid_close(4);
+        stats.inc_rx_t_unexpected_msgs(5);
+        stats.inc_rx_t_decode_errors(6);
+        stats.inc_rx_t_resyncs(7);
+
+        let stats = typed_stats(stats.snapshot(), Default::default());
+        assert_eq!(
+            stats.anomalies,
+            AnomalyStats {
+                invalid_sn: 1,
+                defrag_errors: 2,
+                unknown_conduit: 3,
+                invalid_close: 4,
+                unexpected_msgs: 5,
+                decode_errors: 6,
+                resyncs: 7,
+                unauthorized: 0,
+            }
+        );
+        assert_eq!(stats.rx, Default::default());
+    }
+}
@@ -0,0 +1,138 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_core::zlock;
+use zenoh_protocol::core::ZInt;
+
+// The number of most recent samples the statistics are computed on
+const RTT_WINDOW: usize = 128;
+
+lazy_static! {
+    // The origin of the hashes carried by the pings
+    static ref EPOCH: Instant = Instant::now();
+}
+
+/// Returns the hash of a ping sent now, i.e. the microseconds elapsed since [`EPOCH`].
+pub(crate) fn ping_hash() -> ZInt {
+    EPOCH.elapsed().as_micros() as ZInt
+}
+
+/// The round-trip time of a link, measured over its most recent pings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RttStats {
+    /// The number of samples the statistics are computed on.
+    pub samples: usize,
+    pub min: Duration,
+    pub avg: Duration,
+    pub p99: Duration,
+    /// The mean absolute difference between consecutive samples.
+    pub jitter: Duration,
+}
+
+#[derive(Default)]
+pub(crate) struct LinkRtt {
+    samples: Mutex<VecDeque<Duration>>,
+}
+
+impl LinkRtt {
+    /// Records the pong answering the ping with `hash`, ignoring it if the hash is invalid.
+    pub(crate) fn pong(&self, hash: ZInt) {
+        let now = ping_hash();
+        if hash <= now {
+            self.record(Duration::from_micros(now - hash));
+        }
+    }
+
+    fn record(&self, rtt: Duration) {
+        let mut samples = zlock!(self.samples);
+        if samples.len() == RTT_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(rtt);
+    }
+
+    pub(crate) fn stats(&self) -> Option<RttStats> {
+        let samples = zlock!(self.samples);
+        if samples.is_empty() {
+            return None;
+        }
+        let n = samples.len();
+        let mut sorted = samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort();
+        let jitter = samples
+            .iter()
+            .zip(samples.iter().skip(1))
+            .map(|(a, b)| if a > b { *a - *b } else { *b - *a })
+            .sum::<Duration>()
+            / (n.max(2) - 1) as u32;
+        Some(RttStats {
+            samples: n,
+            min: sorted[0],
+            avg: sorted.iter().sum::<Duration>() / n as u32,
+            p99: sorted[(n * 99 - 1) / 100],
+            jitter,
+        })
+    }
+
+    /// The average round-trip time, used to weight the scheduling among the links.
+    pub(crate) fn avg(&self) -> Option<Duration> {
+        let samples = zlock!(self.samples);
+        if samples.is_empty() {
+            return None;
+        }
+        Some(samples.iter().sum::<Duration>() / samples.len() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_rtt_stats() {
+        let rtt = LinkRtt::default();
+        assert!(rtt.stats().is_none());
+
+        rtt.record(Duration::from_millis(10));
+        let stats = rtt.stats().unwrap();
+        assert_eq!(stats.samples, 1);
+        assert_eq!(stats.p99, Duration::from_millis(10));
+        assert_eq!(stats.jitter, Duration::ZERO);
+
+        for ms in [30, 20, 40] {
+            rtt.record(Duration::from_millis(ms));
+        }
+        let stats = rtt.stats().unwrap();
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.min, Duration::from_millis(10));
+        assert_eq!(stats.avg, Duration::from_millis(25));
+        assert_eq!(stats.p99, Duration::from_millis(40));
+        // |30-10| + |20-30| + |40-20| = 50 over 3 differences
+        assert_eq!(stats.jitter, Duration::from_millis(50) / 3);
+        assert_eq!(rtt.avg(), Some(Duration::from_millis(25)));
+
+        for _ in 0..RTT_WINDOW {
+            rtt.record(Duration::from_millis(1));
+        }
+        assert_eq!(rtt.stats().unwrap().samples, RTT_WINDOW);
+        assert_eq!(rtt.stats().unwrap().p99, Duration::from_millis(1));
+
+        // A hash in the future is ignored
+        rtt.pong(ping_hash() + 1_000_000);
+        assert_eq!(rtt.avg(), Some(Duration::from_millis(1)));
+    }
+}
@@ -12,18 +12,21 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::common::conduit::TransportChannelRx;
+use super::common::workers::{Dispatch, RxCallbacks};
+use super::link::RxLimitExceeded;
 use super::transport::TransportUnicastInner;
-use async_std::task;
 use std::sync::MutexGuard;
 #[cfg(feature = "stats")]
 use zenoh_buffers::SplitBuffer;
-use zenoh_core::{zlock, zread};
+use zenoh_core::{executor, zlock, zread};
 use zenoh_link::LinkUnicast;
 #[cfg(feature = "stats")]
 use zenoh_protocol::zenoh::ZenohBody;
 use zenoh_protocol::{
-    core::{Priority, Reliability, ZInt, ZenohId},
-    transport::{tmsg, Close, Frame, FramePayload, KeepAlive, TransportBody, TransportMessage},
+    core::{Channel, Locator, Priority, Reliability, ZInt, ZenohId},
+    transport::{
+        tmsg, Close, Frame, FramePayload, KeepAlive, Ping, Pong, TransportBody, TransportMessage,
+    },
     zenoh::ZenohMessage,
 };
 use zenoh_result::{bail, zerror, ZResult};
@@ -36,10 +39,22 @@ impl TransportUnicastInner {
         &self,
         #[allow(unused_mut)] // shared-memory feature requires mut
         mut msg: ZenohMessage,
+        callbacks: &mut RxCallbacks,
     ) -> ZResult<()> {
+        let span = tracing::trace_span!(
+            "rx",
+            zid = %self.config.zid,
+            trace = tracing::field::Empty
+        );
+        if let Some(tc) = msg.trace_context() {
+            span.record("trace", tracing::field::display(tc));
+        }
+        let _enter = span.enter();
+
         #[cfg(feature = "stats")]
         {
             self.stats.inc_rx_z_msgs(1);
+            self.priority_stats.inc_rx_msgs(msg.channel.priority);
             match &msg.body {
                 ZenohBody::Data(data) => match data.reply_context {
                     Some(_) => {
@@ -60,20 +75,52 @@ impl TransportUnicastInner {
                 ZenohBody::Query(_) => self.stats.inc_rx_z_query_msgs(1),
                 ZenohBody::Declare(_) => self.stats.inc_rx_z_declare_msgs(1),
                 ZenohBody::LinkStateList(_) => self.stats.inc_rx_z_linkstate_msgs(1),
+                // Only accounted in the zenoh messages
+                ZenohBody::CongestionDrop(_) => {}
             }
         }
 
         let callback = zread!(self.callback).clone();
-        if let Some(callback) = callback.as_ref() {
+        if let Some(callback) = callback {
             #[cfg(feature = "shared-memory")]
             {
                 crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.config.manager.shmr)?;
             }
-            callback.handle_message(msg)
+            match self.config.manager.rx_workers.as_ref() {
+                Some(workers) => {
+                    let zid = self.config.zid;
+                    let priority = msg.channel.priority;
+                    let droppable = msg.is_droppable();
+                    let span = span.clone();
+                    let job = Box::new(move || {
+                        let _enter = span.enter();
+                        if let Err(e) = callback.handle_message(msg) {
+                            tracing::debug!(%zid, "Error handling message: {}", e);
+                        }
+                    });
+                    match workers.dispatch(&zid, priority, droppable, job) {
+                        Dispatch::Queued => {}
+                        Dispatch::Waiting(send) => callbacks.wait(send),
+                        Dispatch::Dropped => {
+                            tracing::trace!(%zid, ?priority, "Callback queue full, message dropped");
+                            #[cfg(feature = "stats")]
+                            self.priority_stats.inc_rx_dropped(priority);
+                        }
+                    }
+                }
+                None => {
+                    let span = span.clone();
+                    callbacks.inline(Box::new(move || {
+                        let _enter = span.enter();
+                        callback.handle_message(msg)
+                    }));
+                }
+            }
+            Ok(())
         } else {
-            log::debug!(
-                "Transport: {}. No callback available, dropping message: {}",
-                self.config.zid,
+            tracing::debug!(
+                zid = %self.config.zid,
+                "No callback available, dropping message: {}",
                 msg
             );
             Ok(())
@@ -86,20 +133,31 @@ impl TransportUnicastInner {
         zid: Option<ZenohId>,
         reason: u8,
         link_only: bool,
+        locator: Option<Locator>,
     ) -> ZResult<()> {
         // Check if the PID is correct when provided
         if let Some(zid) = zid {
             if zid != self.config.zid {
-                log::debug!(
-                    "Received an invalid Close on link {} from peer {} with reason: {}. Ignoring.",
-                    link,
-                    zid,
-                    tmsg::close_reason_to_str(reason),
+                tracing::debug!(
+                    %zid,
+                    %link,
+                    reason = tmsg::close_reason_to_str(reason),
+                    "Received an invalid Close. Ignoring."
                 );
+                #[cfg(feature = "stats")]
+                self.stats.inc_rx_t_invalid_close(1);
                 return Ok(());
             }
         }
 
+        // Let the upper layer know where to reconnect before the transport goes away
+        if let Some(locator) = locator.filter(|_| reason == tmsg::close_reason::MIGRATE) {
+            tracing::debug!(zid = %self.config.zid, %link, %locator, "Received a migration hint");
+            if let Some(callback) = self.get_callback() {
+                callback.migrate(locator);
+            }
+        }
+
         // Stop now rx and tx tasks before doing the proper cleanup
         let _ = self.stop_rx(link);
         let _ = self.stop_tx(link);
@@ -109,7 +167,7 @@ impl TransportUnicastInner {
         let c_link = link.clone();
         // Spawn a task to avoid a deadlock waiting for this same task
         // to finish in the link close() joining the rx handle
-        task::spawn(async move {
+        executor::spawn(async move {
             if link_only {
                 let _ = c_transport.del_link(&c_link).await;
             } else {
@@ -122,18 +180,26 @@ impl TransportUnicastInner {
 
     fn handle_frame(
         &self,
+        channel: Channel,
         sn: ZInt,
         payload: FramePayload,
         mut guard: MutexGuard<'_, TransportChannelRx>,
+        callbacks: &mut RxCallbacks,
     ) -> ZResult<()> {
         let precedes = guard.sn.precedes(sn)?;
         if !precedes {
-            log::debug!(
-                "Transport: {}. Frame with invalid SN dropped: {}. Expected: {}.",
-                self.config.zid,
+            tracing::debug!(
+                zid = %self.config.zid,
+                conduit = ?channel,
                 sn,
-                guard.sn.get()
+                expected = guard.sn.get(),
+                "Frame with invalid SN dropped."
             );
+            #[cfg(feature = "stats")]
+            {
+                self.stats.inc_rx_t_dropped(1);
+                self.stats.inc_rx_t_invalid_sn(1);
+            }
             // Drop the fragments if needed
             if !guard.defrag.is_empty() {
                 guard.defrag.clear();
@@ -150,27 +216,88 @@ impl TransportUnicastInner {
                 if guard.defrag.is_empty() {
                     let _ = guard.defrag.sync(sn);
                 }
-                guard.defrag.push(sn, buffer)?;
+                if let Some(max) = self
+                    .config
+                    .manager
+                    .config
+                    .unicast
+                    .rx_limits
+                    .max_message_size
+                {
+                    let len = guard.defrag.len() + buffer.len();
+                    if len > max {
+                        guard.defrag.clear();
+                        let e = zerror!(
+                            "Transport: {}. Fragmented message of at least {} bytes exceeds the limit of {} bytes.",
+                            self.config.zid,
+                            len,
+                            max
+                        );
+                        return Err(RxLimitExceeded(e).into());
+                    }
+                }
+                if let Err(e) = guard.defrag.push(sn, buffer) {
+                    #[cfg(feature = "stats")]
+                    self.stats.inc_rx_t_defrag_errors(1);
+                    return Err(e);
+                }
                 if is_final {
                     // When shared-memory feature is disabled, msg does not need to be mutable
                     let msg = guard.defrag.defragment().ok_or_else(|| {
+                        #[cfg(feature = "stats")]
+                        self.stats.inc_rx_t_defrag_errors(1);
                         zerror!("Transport: {}. Defragmentation error.", self.config.zid)
                     })?;
-                    self.trigger_callback(msg)
+                    self.trigger_callback(msg, callbacks)
                 } else {
                     Ok(())
                 }
             }
-            FramePayload::Messages { mut messages } => {
-                for msg in messages.drain(..) {
-                    self.trigger_callback(msg)?;
+            FramePayload::Messages { messages } => {
+                // The callbacks are queued while holding the conduit, for the messages of the
+                // other links of the transport to be handled after them, but run without it
+                for msg in messages {
+                    self.trigger_callback(msg, callbacks)?;
                 }
                 Ok(())
             }
         }
     }
 
-    pub(super) fn receive_message(&self, msg: TransportMessage, link: &LinkUnicast) -> ZResult<()> {
+    fn handle_ping(&self, link: &LinkUnicast, hash: ZInt) {
+        // Answer on the same link, for the pong to measure its round-trip time
+        let guard = zread!(self.links);
+        if let Some(pl) = guard
+            .iter()
+            .find(|tl| &tl.link == link)
+            .and_then(|tl| tl.pipeline.as_ref())
+        {
+            let attachment = None;
+            let message = TransportMessage::make_pong(hash, attachment);
+            pl.push_transport_message(message, Priority::Control);
+        }
+    }
+
+    fn handle_pong(&self, link: &LinkUnicast, hash: ZInt) {
+        if let Some(tl) = zread!(self.links).iter().find(|tl| &tl.link == link) {
+            tl.rtt.pong(hash);
+        }
+    }
+
+    /// Drops the fragments being reassembled, e.g. when a batch has been skipped.
+    pub(super) fn clear_defrag(&self) {
+        for c in self.conduit_rx.iter() {
+            zlock!(c.reliable).defrag.clear();
+            zlock!(c.best_effort).defrag.clear();
+        }
+    }
+
+    pub(super) fn receive_message(
+        &self,
+        msg: TransportMessage,
+        link: &LinkUnicast,
+        callbacks: &mut RxCallbacks,
+    ) -> ZResult<()> {
         log::trace!("Received: {:?}", msg);
         // Process the received message
         match msg.body {
@@ -184,6 +311,8 @@ impl TransportUnicastInner {
                 } else if channel.priority == Priority::default() {
                     &self.conduit_rx[0]
                 } else {
+                    #[cfg(feature = "stats")]
+                    self.stats.inc_rx_t_unknown_conduit(1);
                     bail!(
                         "Transport: {}. Unknown conduit: {:?}.",
                         self.config.zid,
@@ -192,9 +321,11 @@ impl TransportUnicastInner {
                 };
 
                 match channel.reliability {
-                    Reliability::Reliable => self.handle_frame(sn, payload, zlock!(c.reliable)),
+                    Reliability::Reliable => {
+                        self.handle_frame(channel, sn, payload, zlock!(c.reliable), callbacks)
+                    }
                     Reliability::BestEffort => {
-                        self.handle_frame(sn, payload, zlock!(c.best_effort))
+                        self.handle_frame(channel, sn, payload, zlock!(c.best_effort), callbacks)
                     }
                 }
             }
@@ -202,14 +333,26 @@ impl TransportUnicastInner {
                 zid,
                 reason,
                 link_only,
-            }) => self.handle_close(link, zid, reason, link_only),
+                locator,
+            }) => self.handle_close(link, zid, reason, link_only, locator),
             TransportBody::KeepAlive(KeepAlive { .. }) => Ok(()),
+            TransportBody::Ping(Ping { hash }) => {
+                self.handle_ping(link, hash);
+                Ok(())
+            }
+            TransportBody::Pong(Pong { hash }) => {
+                self.handle_pong(link, hash);
+                Ok(())
+            }
             _ => {
-                log::debug!(
-                    "Transport: {}. Message handling not implemented: {:?}",
-                    self.config.zid,
+                tracing::debug!(
+                    zid = %self.config.zid,
+                    %link,
+                    "Message handling not implemented: {:?}",
                     msg
                 );
+                #[cfg(feature = "stats")]
+                self.stats.inc_rx_t_unexpected_msgs(1);
                 Ok(())
             }
         }
@@ -13,16 +13,20 @@
 //
 use super::super::{TransportExecutor, TransportManager, TransportPeerEventHandler};
 use super::common::conduit::{TransportConduitRx, TransportConduitTx};
+#[cfg(feature = "stats")]
+use super::common::stats::{PriorityStatsAtomic, TransportStats};
 use super::link::TransportLinkUnicast;
+use super::rtt::RttStats;
 #[cfg(feature = "stats")]
 use super::TransportUnicastStatsAtomic;
+use crate::slow_consumer::SaturationDetector;
 use async_std::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use zenoh_core::{zasynclock, zread, zwrite};
 use zenoh_link::{Link, LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::{
-    core::{ConduitSn, Priority, WhatAmI, ZInt, ZenohId},
+    core::{ConduitSn, Locator, Priority, WhatAmI, ZInt, ZenohId},
     transport::TransportMessage,
     zenoh::ZenohMessage,
 };
@@ -58,6 +62,7 @@ pub(crate) struct TransportUnicastConfig {
     pub(crate) initial_sn_tx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    pub(crate) extensions: ZInt,
 }
 
 #[derive(Clone)]
@@ -74,9 +79,13 @@ pub(crate) struct TransportUnicastInner {
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Mutex for notification
     pub(super) alive: Arc<AsyncMutex<bool>>,
+    // The saturation of the tx queue, to detect whether the peer is a slow consumer
+    pub(super) slow_consumer: Arc<SaturationDetector>,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportUnicastStatsAtomic>,
+    #[cfg(feature = "stats")]
+    pub(super) priority_stats: Arc<PriorityStatsAtomic>,
 }
 
 impl TransportUnicastInner {
@@ -93,6 +102,7 @@ impl TransportUnicastInner {
             conduit_rx.push(TransportConduitRx::make(
                 config.sn_resolution,
                 config.manager.config.defrag_buff_size,
+                &config.manager.config.memory,
             )?);
         }
 
@@ -104,6 +114,9 @@ impl TransportUnicastInner {
             c.sync(initial_sn)?;
         }
 
+        let slow_consumer = Arc::new(SaturationDetector::new(
+            config.manager.config.unicast.slow_consumer_threshold,
+        ));
         let t = TransportUnicastInner {
             config,
             conduit_tx: conduit_tx.into_boxed_slice().into(),
@@ -111,13 +124,21 @@ impl TransportUnicastInner {
             links: Arc::new(RwLock::new(vec![].into_boxed_slice())),
             callback: Arc::new(RwLock::new(None)),
             alive: Arc::new(AsyncMutex::new(false)),
+            slow_consumer,
             #[cfg(feature = "stats")]
             stats: Arc::new(TransportUnicastStatsAtomic::default()),
+            #[cfg(feature = "stats")]
+            priority_stats: Arc::new(PriorityStatsAtomic::default()),
         };
 
         Ok(t)
     }
 
+    #[cfg(feature = "stats")]
+    pub(crate) fn transport_stats(&self) -> TransportStats {
+        super::typed_stats(self.stats.snapshot(), self.priority_stats.snapshot())
+    }
+
     pub(super) fn set_callback(&self, callback: Arc<dyn TransportPeerEventHandler>) {
         let mut guard = zwrite!(self.callback);
         *guard = Some(callback);
@@ -157,10 +178,10 @@ impl TransportUnicastInner {
     /*           TERMINATION             */
     /*************************************/
     pub(super) async fn delete(&self) -> ZResult<()> {
-        log::debug!(
-            "[{}] Closing transport with peer: {}",
-            self.config.manager.config.zid,
-            self.config.zid
+        tracing::debug!(
+            local_zid = %self.config.manager.config.zid,
+            zid = %self.config.zid,
+            "Closing transport with peer."
         );
         // Mark the transport as no longer alive and keep the lock
         // to avoid concurrent new_transport and closing/closed notifications
@@ -206,6 +227,7 @@ impl TransportUnicastInner {
         &self,
         link: LinkUnicast,
         direction: LinkUnicastDirection,
+        preamble: bool,
     ) -> ZResult<()> {
         // Add the link to the channel
         let mut guard = zwrite!(self.links);
@@ -228,7 +250,7 @@ impl TransportUnicastInner {
         }
 
         // Create a channel link from a link
-        let link = TransportLinkUnicast::new(self.clone(), link, direction);
+        let link = TransportLinkUnicast::new(self.clone(), link, direction, preamble);
 
         let mut links = Vec::with_capacity(guard.len() + 1);
         links.extend_from_slice(&guard);
@@ -382,6 +404,10 @@ impl TransportUnicastInner {
         self.config.is_qos
     }
 
+    pub(crate) fn has_extension(&self, extension: ZInt) -> bool {
+        self.config.extensions & extension == extension
+    }
+
     pub(crate) fn get_callback(&self) -> Option<Arc<dyn TransportPeerEventHandler>> {
         zread!(self.callback).clone()
     }
@@ -435,6 +461,31 @@ impl TransportUnicastInner {
         self.delete().await
     }
 
+    pub(crate) async fn migrate(&self, locator: Locator) -> ZResult<()> {
+        log::trace!(
+            "Migrating transport with peer: {} to {}",
+            self.config.zid,
+            locator
+        );
+
+        let mut pipelines = zread!(self.links)
+            .iter()
+            .filter_map(|sl| sl.pipeline.clone())
+            .collect::<Vec<_>>();
+        for p in pipelines.drain(..) {
+            // Same as close, with the locator the peer is suggested to reconnect to
+            let peer_id = Some(self.config.manager.zid());
+            let link_only = true;
+            let attachment = None; // No attachment here
+            let msg =
+                TransportMessage::make_migrate(peer_id, locator.clone(), link_only, attachment);
+
+            p.push_transport_message(msg, Priority::Background);
+        }
+        // Terminate and clean up the transport
+        self.delete().await
+    }
+
     /*************************************/
     /*        SCHEDULE AND SEND TX       */
     /*************************************/
@@ -456,7 +507,35 @@ impl TransportUnicastInner {
         self.schedule_first_fit(message)
     }
 
+    /// Schedule Zenoh messages sharing the same channel on the transmission queue, contiguously
+    pub(crate) fn schedule_batch(
+        &self,
+        #[allow(unused_mut)] mut messages: Vec<ZenohMessage>,
+    ) -> bool {
+        #[cfg(feature = "shared-memory")]
+        for message in messages.iter_mut() {
+            let res = if self.config.is_shm {
+                crate::shm::map_zmsg_to_shminfo(message)
+            } else {
+                crate::shm::map_zmsg_to_shmbuf(message, &self.config.manager.shmr)
+            };
+            if let Err(e) = res {
+                log::trace!("Failed SHM conversion: {}", e);
+                return false;
+            }
+        }
+
+        self.schedule_batch_first_fit(messages)
+    }
+
     pub(crate) fn get_links(&self) -> Vec<LinkUnicast> {
         zread!(self.links).iter().map(|l| l.link.clone()).collect()
     }
+
+    pub(crate) fn get_links_rtt(&self) -> Vec<(LinkUnicast, Option<RttStats>)> {
+        zread!(self.links)
+            .iter()
+            .map(|l| (l.link.clone(), l.rtt.stats()))
+            .collect()
+    }
 }
@@ -11,47 +11,114 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::link::TransportLinkUnicast;
 use super::transport::TransportUnicastInner;
+use crate::common::pipeline::TransmissionPipelineProducer;
+use crate::slow_consumer::SlowConsumerEvent;
+use std::time::{Duration, Instant};
 #[cfg(feature = "stats")]
 use zenoh_buffers::SplitBuffer;
 use zenoh_core::zread;
-#[cfg(feature = "stats")]
-use zenoh_protocol::zenoh::ZenohBody;
-use zenoh_protocol::zenoh::ZenohMessage;
+use zenoh_protocol::zenoh::{ZenohBody, ZenohMessage};
 
 impl TransportUnicastInner {
+    // Returns the first link, or the one with the lowest average round-trip time if enabled
+    fn select_link<'a>(
+        &self,
+        mut links: impl Iterator<Item = &'a TransportLinkUnicast>,
+    ) -> Option<&'a TransportLinkUnicast> {
+        if self.config.manager.config.unicast.rtt_scheduling {
+            links.min_by_key(|tl| tl.rtt.avg().unwrap_or(Duration::MAX))
+        } else {
+            links.next()
+        }
+    }
+
+    // Pushes on the pipeline, tracking the saturation of the tx queue if the slow consumers are detected
+    fn push_on_pipeline(&self, pl: &TransmissionPipelineProducer, msg: ZenohMessage) -> bool {
+        if !self.slow_consumer.is_enabled() {
+            return pl.push_zenoh_message(msg);
+        }
+
+        let key_expr = match &msg.body {
+            ZenohBody::Data(data) => Some(data.key.clone()),
+            _ => None,
+        };
+        let start = Instant::now();
+        let res = pl.push_zenoh_message(msg);
+        if res && start.elapsed() < self.slow_consumer.threshold() {
+            if let Some(dropped) = self.slow_consumer.cleared() {
+                self.config
+                    .manager
+                    .slow_consumers
+                    .notify(SlowConsumerEvent::PeerRecovered {
+                        zid: self.config.zid.to_string(),
+                        dropped,
+                    });
+            }
+            return res;
+        }
+
+        // The message has been dropped or the push blocked for too long
+        if let Some(saturation) =
+            self.slow_consumer
+                .saturated(start, key_expr.map(|k| k.to_string()), !res)
+        {
+            self.config
+                .manager
+                .slow_consumers
+                .notify(SlowConsumerEvent::SlowPeer {
+                    zid: self.config.zid.to_string(),
+                    key_exprs: saturation.key_exprs,
+                    duration_ms: saturation.duration.as_millis() as u64,
+                    dropped: saturation.dropped,
+                });
+        }
+        #[cfg(feature = "stats")]
+        if !res && self.slow_consumer.is_reported() {
+            self.stats.inc_tx_z_slow_consumer_dropped(1);
+        }
+        res
+    }
+
     fn schedule_on_link(&self, msg: ZenohMessage) -> bool {
         macro_rules! zpush {
-            ($guard:expr, $pipeline:expr, $msg:expr) => {
+            ($transport:expr, $guard:expr, $pipeline:expr, $msg:expr) => {
                 // Drop the guard before the push_zenoh_message since
                 // the link could be congested and this operation could
                 // block for fairly long time
                 let pl = $pipeline.clone();
                 drop($guard);
                 log::trace!("Scheduled: {:?}", $msg);
-                return pl.push_zenoh_message($msg);
+                return $transport.push_on_pipeline(&pl, $msg);
             };
         }
 
+        // The best effort messages are dropped while the memory budget is exhausted
+        if !msg.is_reliable() && self.config.manager.config.memory.drops_best_effort() {
+            log::trace!("Dropped (memory budget exhausted): {:?}", msg);
+            return false;
+        }
+
         let guard = zread!(self.links);
         // First try to find the best match between msg and link reliability
-        if let Some(pl) = guard
-            .iter()
-            .filter_map(|tl| {
-                if msg.is_reliable() == tl.link.is_reliable() {
-                    tl.pipeline.as_ref()
-                } else {
-                    None
-                }
-            })
-            .next()
+        if let Some(pl) = self
+            .select_link(
+                guard.iter().filter(|tl| {
+                    msg.is_reliable() == tl.link.is_reliable() && tl.pipeline.is_some()
+                }),
+            )
+            .and_then(|tl| tl.pipeline.as_ref())
         {
-            zpush!(guard, pl, msg);
+            zpush!(self, guard, pl, msg);
         }
 
         // No best match found, take the first available link
-        if let Some(pl) = guard.iter().filter_map(|tl| tl.pipeline.as_ref()).next() {
-            zpush!(guard, pl, msg);
+        if let Some(pl) = self
+            .select_link(guard.iter().filter(|tl| tl.pipeline.is_some()))
+            .and_then(|tl| tl.pipeline.as_ref())
+        {
+            zpush!(self, guard, pl, msg);
         }
 
         // No Link found
@@ -87,15 +154,79 @@ impl TransportUnicastInner {
             ZenohBody::Query(_) => self.stats.inc_tx_z_query_msgs(1),
             ZenohBody::Declare(_) => self.stats.inc_tx_z_declare_msgs(1),
             ZenohBody::LinkStateList(_) => self.stats.inc_tx_z_linkstate_msgs(1),
+            // Only accounted in the zenoh messages
+            ZenohBody::CongestionDrop(_) => {}
         }
 
+        #[cfg(feature = "stats")]
+        let priority = msg.channel.priority;
+
         let res = self.schedule_on_link(msg);
 
         #[cfg(feature = "stats")]
         if res {
             self.stats.inc_tx_z_msgs(1);
+            self.priority_stats.inc_tx_msgs(priority);
         } else {
             self.stats.inc_tx_z_dropped(1);
+            self.priority_stats.inc_tx_dropped(priority);
+        }
+
+        res
+    }
+
+    // Pushes the messages on the pipeline of a single link, so that they are sent contiguously
+    pub(super) fn schedule_batch_first_fit(&self, msgs: Vec<ZenohMessage>) -> bool {
+        let reliable = match msgs.first() {
+            Some(msg) => msg.is_reliable(),
+            None => return true,
+        };
+        let guard = zread!(self.links);
+        let pl = self
+            .select_link(
+                guard
+                    .iter()
+                    .filter(|tl| reliable == tl.link.is_reliable() && tl.pipeline.is_some()),
+            )
+            .or_else(|| self.select_link(guard.iter().filter(|tl| tl.pipeline.is_some())))
+            .and_then(|tl| tl.pipeline.clone());
+        drop(guard);
+
+        #[cfg(feature = "stats")]
+        let (count, priority) = (msgs.len(), msgs[0].channel.priority);
+        #[cfg(feature = "stats")]
+        for msg in msgs.iter() {
+            if let ZenohBody::Data(data) = &msg.body {
+                self.stats.inc_tx_z_data_msgs(1);
+                self.stats.inc_tx_z_data_payload_bytes(data.payload.len());
+            }
+        }
+
+        let res = match pl {
+            Some(pl) => {
+                log::trace!("Scheduled batch of {} messages", msgs.len());
+                pl.push_zenoh_messages(msgs)
+            }
+            None => {
+                log::trace!(
+                    "Batch of {} messages dropped because the transport has no links",
+                    msgs.len()
+                );
+                false
+            }
+        };
+
+        #[cfg(feature = "stats")]
+        if res {
+            self.stats.inc_tx_z_msgs(count);
+            for _ in 0..count {
+                self.priority_stats.inc_tx_msgs(priority);
+            }
+        } else {
+            self.stats.inc_tx_z_dropped(count);
+            for _ in 0..count {
+                self.priority_stats.inc_tx_dropped(priority);
+            }
         }
 
         res
@@ -0,0 +1,216 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The watermarks of the tx queues of the links: the early warnings given before the messages
+//! are dropped or blocked because a tx queue is full.
+//!
+//! A tx queue crossing its high watermark is reported once with a [`WatermarkEvent`], logged and
+//! dispatched to the subscribers of the [`WatermarkEvents`] of its transport manager, until it drains down to its low watermark.
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use zenoh_core::zlock;
+use zenoh_protocol::core::{CongestionControl, Priority};
+
+/// An event related to the watermarks of a tx queue.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WatermarkEvent {
+    /// The tx queue of `link` to `zid` reached its high watermark with `queued` batches.
+    High {
+        zid: String,
+        link: String,
+        queued: usize,
+    },
+    /// The tx queue of `link` to `zid` drained down to its low watermark, `dropped` low priority
+    /// messages have been dropped meanwhile.
+    Low {
+        zid: String,
+        link: String,
+        queued: usize,
+        dropped: usize,
+    },
+}
+
+/// The watermark events of the tx queues of the links of a
+/// [`TransportManager`](crate::TransportManager).
+#[derive(Default)]
+pub struct WatermarkEvents {
+    subscribers: Mutex<Vec<flume::Sender<WatermarkEvent>>>,
+}
+
+impl WatermarkEvents {
+    /// Subscribes to the watermark events.
+    pub fn subscribe(&self) -> flume::Receiver<WatermarkEvent> {
+        let (tx, rx) = flume::unbounded();
+        zlock!(self.subscribers).push(tx);
+        rx
+    }
+
+    /// Logs `event` and notifies it to all the subscribers.
+    pub fn notify(&self, event: WatermarkEvent) {
+        match &event {
+            WatermarkEvent::High { zid, link, queued } => {
+                tracing::warn!(%zid, %link, queued, "Tx queue above its high watermark")
+            }
+            WatermarkEvent::Low {
+                zid,
+                link,
+                queued,
+                dropped,
+            } => tracing::info!(
+                %zid,
+                %link,
+                queued,
+                dropped,
+                "Tx queue back to its low watermark"
+            ),
+        }
+        zlock!(self.subscribers).retain(|s| s.send(event.clone()).is_ok());
+    }
+}
+
+/// The watermarks of a tx queue, in number of batches queued for all the priorities.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueWatermarks {
+    pub high: usize,
+    pub low: usize,
+    /// Whether the [`Priority::DataLow`] and [`Priority::Background`] messages with the
+    /// [`CongestionControl::Drop`] congestion control are dropped while the queue is above its
+    /// high watermark.
+    pub drop_low_priority: bool,
+}
+
+/// Tracks the watermarks of the tx queue of a link.
+#[derive(Debug)]
+pub(crate) struct WatermarkDetector {
+    watermarks: QueueWatermarks,
+    events: Arc<WatermarkEvents>,
+    zid: String,
+    link: String,
+    above: AtomicBool,
+    dropped: AtomicUsize,
+}
+
+impl WatermarkDetector {
+    pub(crate) fn new(
+        watermarks: QueueWatermarks,
+        events: Arc<WatermarkEvents>,
+        zid: String,
+        link: String,
+    ) -> Self {
+        WatermarkDetector {
+            watermarks,
+            events,
+            zid,
+            link,
+            above: AtomicBool::new(false),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// Records a batch queued, `queued` batches being queued now.
+    #[inline]
+    pub(crate) fn enqueued(&self, queued: usize) {
+        if queued >= self.watermarks.high && !self.above.swap(true, Ordering::AcqRel) {
+            self.events.notify(WatermarkEvent::High {
+                zid: self.zid.clone(),
+                link: self.link.clone(),
+                queued,
+            });
+        }
+    }
+
+    /// Records a batch dequeued, `queued` batches being queued now.
+    #[inline]
+    pub(crate) fn dequeued(&self, queued: usize) {
+        if queued <= self.watermarks.low && self.above.swap(false, Ordering::AcqRel) {
+            self.events.notify(WatermarkEvent::Low {
+                zid: self.zid.clone(),
+                link: self.link.clone(),
+                queued,
+                dropped: self.dropped.swap(0, Ordering::Relaxed),
+            });
+        }
+    }
+
+    /// Returns `true` if a message with `priority` and `congestion_control` is to be dropped,
+    /// counting it. The messages with the [`CongestionControl::Block`] congestion control are
+    /// never dropped.
+    #[inline]
+    pub(crate) fn drops(&self, priority: Priority, congestion_control: CongestionControl) -> bool {
+        let drops = self.watermarks.drop_low_priority
+            && congestion_control == CongestionControl::Drop
+            && matches!(priority, Priority::DataLow | Priority::Background)
+            && self.above.load(Ordering::Acquire);
+        if drops {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        drops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watermark_detector() {
+        let events = Arc::new(WatermarkEvents::default());
+        let received = events.subscribe();
+        let detector = WatermarkDetector::new(
+            QueueWatermarks {
+                high: 4,
+                low: 1,
+                drop_low_priority: true,
+            },
+            events,
+            "a1".to_string(),
+            "tcp/127.0.0.1:7447".to_string(),
+        );
+        let drops = |priority| detector.drops(priority, CongestionControl::Drop);
+
+        for queued in 1..4 {
+            detector.enqueued(queued);
+        }
+        assert!(!drops(Priority::Background));
+        detector.enqueued(4);
+        detector.enqueued(5);
+        assert!(drops(Priority::Background));
+        assert!(drops(Priority::DataLow));
+        assert!(!drops(Priority::Data));
+        assert!(!detector.drops(Priority::Background, CongestionControl::Block));
+
+        detector.dequeued(3);
+        assert!(drops(Priority::Background));
+        detector.dequeued(1);
+        assert!(!drops(Priority::Background));
+        detector.dequeued(0);
+
+        let received: Vec<_> = received.try_iter().collect();
+        assert_eq!(received.len(), 2);
+        assert!(matches!(
+            received[0],
+            WatermarkEvent::High { queued: 4, .. }
+        ));
+        assert!(matches!(
+            received[1],
+            WatermarkEvent::Low {
+                queued: 1,
+                dropped: 3,
+                ..
+            }
+        ));
+    }
+}
@@ -33,7 +33,8 @@ mod tests {
     use zenoh_result::ZResult;
     use zenoh_transport::{
         TransportEventHandler, TransportManager, TransportMulticast,
-        TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+        TransportMulticastEventHandler, TransportMulticastPeerEvent, TransportPeer,
+        TransportPeerEventHandler, TransportUnicast,
     };
 
     const TIMEOUT: Duration = Duration::from_secs(60);
@@ -275,9 +276,42 @@ mod tests {
         task::sleep(SLEEP).await;
     }
 
+    async fn test_peers(peer01: &TransportMulticastPeer, peer02: &TransportMulticastPeer) {
+        let peer01_id = peer01.manager.zid();
+
+        let info = peer02.transport.get_peers_info().unwrap();
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].zid, peer01_id);
+        assert!(info[0].last_seen <= info[0].lease);
+
+        // An ignored peer does not join again
+        let events = peer02.transport.peer_events().unwrap();
+        assert!(peer02.transport.ignore_peer(&peer01_id).unwrap());
+        assert!(peer02.transport.get_peers().unwrap().is_empty());
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            TransportMulticastPeerEvent::Left { zid, .. } if zid == peer01_id
+        ));
+        task::sleep(3 * SLEEP).await;
+        assert!(peer02.transport.get_peers().unwrap().is_empty());
+
+        // The peer joins again with its next Join message once not ignored anymore
+        assert!(peer02.transport.unignore_peer(&peer01_id).unwrap());
+        ztimeout!(async {
+            while peer02.transport.get_peers().unwrap().is_empty() {
+                task::sleep(SLEEP_COUNT).await;
+            }
+        });
+        assert!(matches!(
+            ztimeout!(events.recv_async()).unwrap(),
+            TransportMulticastPeerEvent::Joined { zid, .. } if zid == peer01_id
+        ));
+    }
+
     async fn run_single(endpoint: &EndPoint, channel: Channel, msg_size: usize) {
         let (peer01, peer02) = open_transport(endpoint).await;
         test_transport(&peer01, &peer02, channel, msg_size).await;
+        test_peers(&peer01, &peer02).await;
 
         #[cfg(feature = "stats")]
         {
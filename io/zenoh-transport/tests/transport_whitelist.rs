@@ -95,6 +95,27 @@ async fn run(endpoints: &[EndPoint]) {
         assert!(res.is_err());
     }
 
+    // Create the router transport manager
+    println!(">>> Transport Whitelist [1b1]");
+    let router_manager = TransportManager::builder()
+        .zid(router_id)
+        .denied_protocols(Vec::from_iter(
+            endpoints.iter().map(|e| e.protocol().to_string()),
+        ))
+        .build(Arc::new(SHRouter))
+        .unwrap();
+
+    // Create the listener on the router
+    for e in endpoints.iter() {
+        println!("Listener endpoint: {e}");
+        let res = ztimeout!(router_manager.add_listener(e.clone()));
+        assert!(res.is_err());
+
+        println!("Open endpoint: {e}");
+        let res = ztimeout!(router_manager.open_transport(e.clone()));
+        assert!(res.is_err());
+    }
+
     // Create the router transport manager
     println!(">>> Transport Whitelist [2a1]");
     let unicast = TransportManager::config_unicast().max_links(usize::MAX);
@@ -14,7 +14,7 @@
 use async_std::{prelude::FutureExt, task};
 #[cfg(feature = "auth_pubkey")]
 use rsa::{BigUint, RsaPrivateKey, RsaPublicKey};
-#[cfg(feature = "auth_usrpwd")]
+#[cfg(any(feature = "auth_usrpwd", feature = "auth_psk"))]
 use std::collections::HashMap;
 use std::{any::Any, collections::HashSet, iter::FromIterator, sync::Arc, time::Duration};
 use zenoh_core::zasync_executor_init;
@@ -24,6 +24,8 @@ use zenoh_protocol::{
     zenoh::ZenohMessage,
 };
 use zenoh_result::ZResult;
+#[cfg(feature = "auth_psk")]
+use zenoh_transport::unicast::establishment::authenticator::PreSharedKeyAuthenticator;
 #[cfg(feature = "auth_pubkey")]
 use zenoh_transport::unicast::establishment::authenticator::PubKeyAuthenticator;
 #[cfg(feature = "shared-memory")]
@@ -694,6 +696,145 @@ async fn authenticator_user_password(endpoint: &EndPoint) {
     task::sleep(SLEEP).await;
 }
 
+#[cfg(feature = "auth_psk")]
+async fn authenticator_pre_shared_key(endpoint: &EndPoint) {
+    use zenoh_transport::TransportManager;
+
+    /* [CLIENT] */
+    let client01_id = ZenohId::try_from([2]).unwrap();
+    let key_id01 = "sensor01".to_string();
+    let key01 = vec![0x01; 32];
+
+    let client02_id = ZenohId::try_from([3]).unwrap();
+    let key_id02 = "sensor02".to_string();
+    let key02 = vec![0x02; 32];
+
+    /* [ROUTER] */
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_handler = Arc::new(SHRouterAuthenticator::new());
+    // Create the router transport manager. Only sensor01 is known with its correct key
+    // while sensor02 is registered with a wrong key.
+    let mut lookup: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    lookup.insert(key_id01.clone().into(), key01.clone());
+    lookup.insert(key_id02.clone().into(), vec![0xff; 32]);
+
+    let peer_auth_router = Arc::new(
+        PreSharedKeyAuthenticator::new(lookup, None).lockout(2, Duration::from_secs(3600)),
+    );
+    let unicast = TransportManager::config_unicast()
+        .peer_authenticator(HashSet::from_iter(vec![peer_auth_router.clone().into()]));
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .unicast(unicast)
+        .build(router_handler.clone())
+        .unwrap();
+
+    // Create the transport transport manager for the first client
+    let peer_auth_client01 =
+        PreSharedKeyAuthenticator::new(HashMap::new(), Some((key_id01.into(), key01)));
+    let unicast = TransportManager::config_unicast()
+        .peer_authenticator(HashSet::from_iter(vec![peer_auth_client01.into()]));
+    let client01_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client01_id)
+        .unicast(unicast)
+        .build(Arc::new(SHClientAuthenticator::default()))
+        .unwrap();
+
+    // Create the transport transport manager for the second client
+    let peer_auth_client02 = PreSharedKeyAuthenticator::new(
+        HashMap::new(),
+        Some((key_id02.clone().into(), key02.clone())),
+    );
+    let unicast = TransportManager::config_unicast()
+        .peer_authenticator(HashSet::from_iter(vec![peer_auth_client02.into()]));
+    let client02_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client02_id)
+        .unicast(unicast)
+        .build(Arc::new(SHClientAuthenticator::default()))
+        .unwrap();
+
+    /* [1] */
+    println!("\nTransport Authenticator PreSharedKey [1a1]");
+    // Add the locator on the router
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Authenticator PreSharedKey [1a1]: {res:?}");
+    assert!(res.is_ok());
+
+    /* [2] */
+    // Open a first transport from the client to the router
+    // -> This should be accepted
+    println!("Transport Authenticator PreSharedKey [2a1]");
+    let res = ztimeout!(client01_manager.open_transport(endpoint.clone()));
+    println!("Transport Authenticator PreSharedKey [2a1]: {res:?}");
+    assert!(res.is_ok());
+    let c_ses1 = res.unwrap();
+
+    /* [3] */
+    // Open transports with a wrong key until the key-id gets locked out
+    // -> These should be rejected
+    for i in 0..2 {
+        println!("Transport Authenticator PreSharedKey [3a{i}]");
+        let res = ztimeout!(client02_manager.open_transport(endpoint.clone()));
+        println!("Transport Authenticator PreSharedKey [3a{i}]: {res:?}");
+        assert!(res.is_err());
+    }
+
+    /* [4] */
+    // Fix the key of client02 on the router
+    // -> This should still be rejected since the key-id is locked out
+    let res = ztimeout!(peer_auth_router.add_key(key_id02.clone().into(), key02));
+    assert!(res.is_ok());
+    println!("Transport Authenticator PreSharedKey [4a1]");
+    let res = ztimeout!(client02_manager.open_transport(endpoint.clone()));
+    println!("Transport Authenticator PreSharedKey [4a1]: {res:?}");
+    assert!(res.is_err());
+
+    /* [5] */
+    // Lift the lockout
+    // -> This should be accepted
+    ztimeout!(peer_auth_router.unlock(key_id02.as_bytes()));
+    println!("Transport Authenticator PreSharedKey [5a1]");
+    let res = ztimeout!(client02_manager.open_transport(endpoint.clone()));
+    println!("Transport Authenticator PreSharedKey [5a1]: {res:?}");
+    assert!(res.is_ok());
+    let c_ses2 = res.unwrap();
+
+    /* [6] */
+    println!("Transport Authenticator PreSharedKey [6a1]");
+    let res = ztimeout!(c_ses1.close());
+    println!("Transport Authenticator PreSharedKey [6a1]: {res:?}");
+    assert!(res.is_ok());
+    println!("Transport Authenticator PreSharedKey [6a2]");
+    let res = ztimeout!(c_ses2.close());
+    println!("Transport Authenticator PreSharedKey [6a2]: {res:?}");
+    assert!(res.is_ok());
+
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    /* [7] */
+    // Perform clean up of the open locators
+    println!("Transport Authenticator PreSharedKey [7a1]");
+    let res = ztimeout!(router_manager.del_listener(endpoint));
+    println!("Transport Authenticator PreSharedKey [7a1]: {res:?}");
+    assert!(res.is_ok());
+
+    ztimeout!(async {
+        while !router_manager.get_listeners().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
 #[cfg(feature = "shared-memory")]
 async fn authenticator_shared_memory(endpoint: &EndPoint) {
     use zenoh_transport::TransportManager;
@@ -780,6 +921,8 @@ async fn run(endpoint: &EndPoint) {
     authenticator_multilink(endpoint).await;
     #[cfg(feature = "auth_usrpwd")]
     authenticator_user_password(endpoint).await;
+    #[cfg(feature = "auth_psk")]
+    authenticator_pre_shared_key(endpoint).await;
     #[cfg(feature = "shared-memory")]
     authenticator_shared_memory(endpoint).await;
 }
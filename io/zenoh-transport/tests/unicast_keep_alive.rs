@@ -0,0 +1,131 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::{EndPoint, WhatAmI, ZenohId};
+use zenoh_transport::{DummyTransportEventHandler, TransportManager};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const SLEEP_COUNT: Duration = Duration::from_millis(10);
+
+const LEASE: Duration = Duration::from_secs(1);
+const KEEP_ALIVE: usize = 4;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn run(endpoint: &EndPoint, adaptive_keep_alive: bool) {
+    // Define client and router IDs
+    let client_id = ZenohId::try_from([1]).unwrap();
+    let router_id = ZenohId::try_from([2]).unwrap();
+
+    // The pings are disabled, the keep-alives being the only messages exchanged
+    let unicast = || {
+        TransportManager::config_unicast()
+            .lease(LEASE)
+            .keep_alive(KEEP_ALIVE)
+            .adaptive_keep_alive(adaptive_keep_alive)
+            .ping_interval(Duration::ZERO)
+    };
+
+    // Create the router transport manager
+    let router_manager = TransportManager::builder()
+        .zid(router_id)
+        .whatami(WhatAmI::Router)
+        .unicast(unicast())
+        .build(Arc::new(DummyTransportEventHandler::default()))
+        .unwrap();
+
+    // Create the client transport manager
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .unicast(unicast())
+        .build(Arc::new(DummyTransportEventHandler::default()))
+        .unwrap();
+
+    // Create the listener on the router
+    println!("Add locator: {endpoint}");
+    let _ = ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+
+    println!("Opening transport with {endpoint}");
+    let _ = ztimeout!(client_manager.open_transport(endpoint.clone())).unwrap();
+    let client_transport = client_manager.get_transport(&router_id).unwrap();
+    let router_transport = ztimeout!(async {
+        loop {
+            match router_manager.get_transport(&client_id) {
+                Some(transport) => break transport,
+                None => task::sleep(SLEEP_COUNT).await,
+            }
+        }
+    });
+
+    // Leave the link idle for several leases
+    task::sleep(3 * LEASE).await;
+
+    // The keep-alives have kept the transport open on both sides
+    assert!(client_transport.get_zid().is_ok());
+    assert!(router_transport.get_zid().is_ok());
+    assert_eq!(client_manager.get_transports().len(), 1);
+    assert_eq!(router_manager.get_transports().len(), 1);
+    #[cfg(feature = "stats")]
+    {
+        // At least a keep-alive per lease has been sent and received on each side
+        let client_stats = client_transport.get_stats().unwrap();
+        let router_stats = router_transport.get_stats().unwrap();
+        println!("Client: {client_stats:?}");
+        println!("Router: {router_stats:?}");
+        assert!(client_stats.tx_t_msgs >= 3);
+        assert!(router_stats.rx_t_msgs >= 3);
+        assert!(router_stats.tx_t_msgs >= 3);
+        assert!(client_stats.rx_t_msgs >= 3);
+    }
+
+    println!("Closing transport with {endpoint}");
+    ztimeout!(client_transport.close()).unwrap();
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    println!("Del locator: {endpoint}");
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_keep_alive_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 19020).parse().unwrap();
+    task::block_on(async {
+        run(&endpoint, true).await;
+        run(&endpoint, false).await;
+    });
+}
@@ -0,0 +1,176 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::{
+    any::Any,
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use zenoh_core::{zasync_executor_init, zlock};
+use zenoh_link::{EndPoint, Link, Locator};
+use zenoh_protocol::{
+    core::{WhatAmI, ZenohId},
+    zenoh::ZenohMessage,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportPeerEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router
+#[derive(Default)]
+struct SHRouterMigrate;
+
+impl TransportEventHandler for SHRouterMigrate {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler::default()))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClientMigrate {
+    migration: Arc<Mutex<Option<Locator>>>,
+}
+
+impl TransportEventHandler for SHClientMigrate {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCClientMigrate {
+            migration: self.migration.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the client
+struct SCClientMigrate {
+    migration: Arc<Mutex<Option<Locator>>>,
+}
+
+impl TransportPeerEventHandler for SCClientMigrate {
+    fn handle_message(&self, _message: ZenohMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn migrate(&self, locator: Locator) {
+        *zlock!(self.migration) = Some(locator);
+    }
+}
+
+async fn migrate_transport(endpoint: &EndPoint, target: &Locator) {
+    /* [ROUTER] */
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .build(Arc::new(SHRouterMigrate::default()))
+        .unwrap();
+
+    /* [CLIENT] */
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_handler = Arc::new(SHClientMigrate::default());
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .build(client_handler.clone())
+        .unwrap();
+
+    // Open a transport from the client to the router
+    let _ = ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+    let _ = ztimeout!(client_manager.open_transport(endpoint.clone())).unwrap();
+
+    // Wait for the router to see the client
+    let transport = ztimeout!(async {
+        loop {
+            if let Some(transport) = router_manager.get_transport_unicast(&client_id) {
+                break transport;
+            }
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    // Migrate the client to the target locator
+    ztimeout!(transport.migrate(target.clone())).unwrap();
+
+    // The client should receive the hint and close its transport
+    ztimeout!(async {
+        while zlock!(client_handler.migration).is_none() {
+            task::sleep(SLEEP).await;
+        }
+    });
+    assert_eq!(zlock!(client_handler.migration).as_ref(), Some(target));
+    ztimeout!(async {
+        while client_manager.get_transport_unicast(&router_id).is_some() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    // Stop the managers
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn migrate_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 13100).parse().unwrap();
+    let target: Locator = format!("tcp/127.0.0.1:{}", 13101).parse().unwrap();
+    task::block_on(migrate_transport(&endpoint, &target));
+}
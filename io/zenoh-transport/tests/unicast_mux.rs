@@ -0,0 +1,482 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::any::Any;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh_buffers::{SplitBuffer, ZBuf};
+use zenoh_core::{zasync_executor_init, zlock};
+use zenoh_link::Link;
+use zenoh_protocol::{
+    core::{
+        Channel, CongestionControl, ConsolidationMode, EndPoint, Priority, QueryTarget,
+        QueryableInfo, SubInfo, WhatAmI, WireExpr, ZInt, ZenohId,
+    },
+    transport::tmsg,
+    zenoh::{DataInfo, Declaration, TraceContext, ZenohBody, ZenohMessage},
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportEventHandler, Mux, Primitives, TransportEventHandler, TransportManager,
+    TransportMulticast, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+    TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const SLEEP_COUNT: Duration = Duration::from_millis(10);
+
+const DECL_COUNT: usize = 1_000;
+
+const PRIORITY_ALL: [Priority; 8] = [
+    Priority::Control,
+    Priority::RealTime,
+    Priority::InteractiveHigh,
+    Priority::InteractiveLow,
+    Priority::DataHigh,
+    Priority::Data,
+    Priority::DataLow,
+    Priority::Background,
+];
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router, keeping the received messages
+#[derive(Default)]
+struct SHRouter {
+    messages: Arc<Mutex<Vec<ZenohMessage>>>,
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCRouter {
+            messages: self.messages.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the router
+struct SCRouter {
+    messages: Arc<Mutex<Vec<ZenohMessage>>>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
+        zlock!(self.messages).push(message);
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+async fn open_transport(
+    endpoint: &EndPoint,
+    router_extensions: ZInt,
+) -> (
+    TransportManager,
+    Arc<SHRouter>,
+    TransportManager,
+    TransportUnicast,
+) {
+    // Define client and router IDs
+    let client_id = ZenohId::try_from([1]).unwrap();
+    let router_id = ZenohId::try_from([2]).unwrap();
+
+    // Create the router transport manager
+    let router_handler = Arc::new(SHRouter::default());
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .unicast(TransportManager::config_unicast().extensions(router_extensions))
+        .build(router_handler.clone())
+        .unwrap();
+
+    // Create the client transport manager
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .build(Arc::new(DummyTransportEventHandler::default()))
+        .unwrap();
+
+    // Create the listener on the router
+    println!("Add locator: {endpoint}");
+    let _ = ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+
+    // Open transport -> This should be accepted
+    println!("Opening transport with {endpoint}");
+    let _ = ztimeout!(client_manager.open_transport(endpoint.clone())).unwrap();
+    let client_transport = client_manager.get_transport(&router_id).unwrap();
+
+    (
+        router_manager,
+        router_handler,
+        client_manager,
+        client_transport,
+    )
+}
+
+async fn close_transport(
+    router_manager: TransportManager,
+    client_manager: TransportManager,
+    client_transport: TransportUnicast,
+    endpoint: &EndPoint,
+) {
+    println!("Closing transport with {endpoint}");
+    ztimeout!(client_transport.close()).unwrap();
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    println!("Del locator: {endpoint}");
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+async fn test_declarations(endpoint: &EndPoint) {
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoint, tmsg::init_options::EXTENSIONS).await;
+
+    // Declare a burst of subscribers and queryables through the mux
+    let mux = Mux::new(client_transport.clone());
+    for i in 0..DECL_COUNT / 2 {
+        let key = format!("test/mux/sub/{i}");
+        mux.decl_subscriber(&WireExpr::from(key.as_str()), &SubInfo::default(), None);
+        let key = format!("test/mux/qabl/{i}");
+        mux.decl_queryable(
+            &WireExpr::from(key.as_str()),
+            &QueryableInfo::default(),
+            None,
+        );
+    }
+
+    // Wait for all the declarations to be received
+    let count = |messages: &[ZenohMessage]| -> usize {
+        messages
+            .iter()
+            .map(|m| match &m.body {
+                ZenohBody::Declare(d) => d.declarations.len(),
+                _ => 0,
+            })
+            .sum()
+    };
+    ztimeout!(async {
+        while count(&zlock!(router_handler.messages)) < DECL_COUNT {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+
+    let messages = zlock!(router_handler.messages).clone();
+    assert_eq!(count(&messages), DECL_COUNT);
+    println!(
+        "{} declarations received in {} messages",
+        DECL_COUNT,
+        messages.len()
+    );
+    assert!(messages.len() < DECL_COUNT);
+    // The declarations are received in the order of their declaration
+    let keys: Vec<String> = messages
+        .iter()
+        .flat_map(|m| match &m.body {
+            ZenohBody::Declare(d) => d.declarations.clone(),
+            _ => vec![],
+        })
+        .map(|d| match d {
+            Declaration::Subscriber(s) => s.key.to_string(),
+            Declaration::Queryable(q) => q.key.to_string(),
+            _ => panic!("Unexpected declaration: {d:?}"),
+        })
+        .collect();
+    let expected: Vec<String> = (0..DECL_COUNT / 2)
+        .flat_map(|i| [format!("test/mux/sub/{i}"), format!("test/mux/qabl/{i}")])
+        .collect();
+    assert_eq!(keys, expected);
+
+    close_transport(router_manager, client_manager, client_transport, endpoint).await;
+}
+
+async fn test_query_priorities(endpoint: &EndPoint) {
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoint, tmsg::init_options::EXTENSIONS).await;
+
+    // Send a query and its replies on each priority, the id of the query being its priority
+    let mux = Mux::new(client_transport.clone());
+    let zid = ZenohId::try_from([1]).unwrap();
+    for priority in PRIORITY_ALL {
+        let qid = priority as ZInt;
+        mux.send_query(
+            &"test/mux/query".into(),
+            "",
+            qid,
+            QueryTarget::default(),
+            ConsolidationMode::None,
+            None,
+            None,
+            None,
+            priority,
+            None,
+        );
+        mux.send_reply_data(
+            qid,
+            zid,
+            "test/mux/query".into(),
+            None,
+            vec![0_u8; 8].into(),
+            priority,
+        );
+        mux.send_reply_final(qid, priority);
+    }
+
+    ztimeout!(async {
+        while zlock!(router_handler.messages).len() < 3 * PRIORITY_ALL.len() {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+
+    // Each message is received on the conduit of the priority of its query
+    let messages = zlock!(router_handler.messages).clone();
+    assert_eq!(messages.len(), 3 * PRIORITY_ALL.len());
+    for m in messages.iter() {
+        let qid = match &m.body {
+            ZenohBody::Query(q) => q.qid,
+            ZenohBody::Data(d) => d.reply_context.as_ref().unwrap().qid,
+            ZenohBody::Unit(u) => u.reply_context.as_ref().unwrap().qid,
+            _ => panic!("Unexpected message: {m:?}"),
+        };
+        assert_eq!(m.channel.priority as ZInt, qid);
+    }
+
+    close_transport(router_manager, client_manager, client_transport, endpoint).await;
+}
+
+async fn test_query_timeout(endpoint: &EndPoint, router_extensions: ZInt) {
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoint, router_extensions).await;
+
+    // Send a query with a timeout not counting whole milliseconds
+    let mux = Mux::new(client_transport.clone());
+    mux.send_query(
+        &"test/mux/query".into(),
+        "",
+        0,
+        QueryTarget::default(),
+        ConsolidationMode::None,
+        Some(Duration::from_micros(1_500)),
+        None,
+        None,
+        Priority::default(),
+        None,
+    );
+
+    ztimeout!(async {
+        while zlock!(router_handler.messages).is_empty() {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+
+    // The timeout is rounded up, and only sent to the peers agreeing on the extension
+    let messages = zlock!(router_handler.messages).clone();
+    assert_eq!(messages.len(), 1);
+    let timeout = match &messages[0].body {
+        ZenohBody::Query(q) => q.timeout,
+        _ => panic!("Unexpected message: {:?}", messages[0]),
+    };
+    if router_extensions & tmsg::init_options::QUERY_TIMEOUT != 0 {
+        assert_eq!(timeout, Some(2));
+    } else {
+        assert_eq!(timeout, None);
+    }
+
+    close_transport(router_manager, client_manager, client_transport, endpoint).await;
+}
+
+async fn test_unit_data(endpoint: &EndPoint, router_extensions: ZInt) {
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoint, router_extensions).await;
+
+    // Send a data without info nor payload
+    let mux = Mux::new(client_transport.clone());
+    mux.send_data(
+        &"test/mux/unit".into(),
+        ZBuf::default(),
+        Channel::default(),
+        CongestionControl::default(),
+        None,
+        None,
+    );
+
+    ztimeout!(async {
+        while zlock!(router_handler.messages).is_empty() {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+
+    // The data is only encoded as a Unit to the peers agreeing on the extension
+    let messages = zlock!(router_handler.messages).clone();
+    assert_eq!(messages.len(), 1);
+    let data = match &messages[0].body {
+        ZenohBody::Data(d) => d,
+        _ => panic!("Unexpected message: {:?}", messages[0]),
+    };
+    assert_eq!(data.key.suffix, "test/mux/unit");
+    assert!(data.payload.is_empty());
+    assert_eq!(
+        data.unit,
+        router_extensions & tmsg::init_options::UNIT_DATA != 0
+    );
+
+    close_transport(router_manager, client_manager, client_transport, endpoint).await;
+}
+
+async fn test_trace_context(endpoint: &EndPoint, router_extensions: ZInt) {
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoint, router_extensions).await;
+
+    // Send a data carrying a trace context
+    let trace_context = TraceContext {
+        trace_id: [1; 16],
+        span_id: [2; 8],
+        flags: TraceContext::SAMPLED,
+    };
+    let mux = Mux::new(client_transport.clone());
+    mux.send_data(
+        &"test/mux/trace".into(),
+        ZBuf::from(vec![0_u8; 8]),
+        Channel::default(),
+        CongestionControl::default(),
+        Some(DataInfo {
+            trace_context: Some(trace_context),
+            ..Default::default()
+        }),
+        None,
+    );
+
+    ztimeout!(async {
+        while zlock!(router_handler.messages).is_empty() {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+
+    // The trace context is only sent to the peers agreeing on the extension
+    let messages = zlock!(router_handler.messages).clone();
+    assert_eq!(messages.len(), 1);
+    if router_extensions & tmsg::init_options::TRACE_CONTEXT != 0 {
+        assert_eq!(messages[0].trace_context(), Some(&trace_context));
+    } else {
+        assert_eq!(messages[0].trace_context(), None);
+    }
+
+    close_transport(router_manager, client_manager, client_transport, endpoint).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_mux_declarations_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 19000).parse().unwrap();
+    task::block_on(test_declarations(&endpoint));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_mux_query_priorities_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 19010).parse().unwrap();
+    task::block_on(test_query_priorities(&endpoint));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_mux_query_timeout_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 19030).parse().unwrap();
+    task::block_on(test_query_timeout(
+        &endpoint,
+        tmsg::init_options::EXTENSIONS,
+    ));
+    // A router not supporting the extension, like the older versions
+    task::block_on(test_query_timeout(&endpoint, 0));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_mux_unit_data_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 19040).parse().unwrap();
+    task::block_on(test_unit_data(&endpoint, tmsg::init_options::EXTENSIONS));
+    // A router not supporting the extension, like the older versions
+    task::block_on(test_unit_data(&endpoint, 0));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_mux_trace_context_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 19050).parse().unwrap();
+    task::block_on(test_trace_context(
+        &endpoint,
+        tmsg::init_options::EXTENSIONS,
+    ));
+    // A router not supporting the extension, like the older versions
+    task::block_on(test_trace_context(&endpoint, 0));
+}
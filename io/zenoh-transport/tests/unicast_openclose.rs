@@ -239,6 +239,16 @@ async fn openclose_transport(endpoint: &EndPoint) {
         let links = s.get_links().unwrap();
         assert_eq!(links.len(), links_num);
     });
+    println!("Transport Open Close [3e1]");
+    let stats = router_manager.get_rejection_stats_unicast();
+    println!("Transport Open Close [3e2]: {stats:?}");
+    assert_eq!(stats.max_links, 1);
+    assert_eq!(stats.max_sessions, 0);
+    let stats = router_manager.get_handshake_stats_unicast();
+    println!("Transport Open Close [3e3]: {stats:?}");
+    assert_eq!(stats.attempts, 3);
+    assert_eq!(stats.successes, 2);
+    assert_eq!(stats.max_links, 1);
 
     /* [4] */
     // Close the open transport on the client
@@ -325,6 +335,18 @@ async fn openclose_transport(endpoint: &EndPoint) {
         let links = s.get_links().unwrap();
         assert_eq!(links.len(), links_num);
     });
+    println!("Transport Open Close [6d1]");
+    let stats = router_manager.get_rejection_stats_unicast();
+    println!("Transport Open Close [6d2]: {stats:?}");
+    assert_eq!(stats.max_links, 1);
+    assert_eq!(stats.max_sessions, 1);
+    let stats = router_manager.get_handshake_stats_unicast();
+    println!("Transport Open Close [6d3]: {stats:?}");
+    assert_eq!(stats.attempts, 5);
+    assert_eq!(stats.successes, 3);
+    assert_eq!(stats.max_links, 1);
+    assert_eq!(stats.max_sessions, 1);
+    assert_eq!(stats.other, 0);
 
     /* [7] */
     // Close the open transport on the client
@@ -0,0 +1,133 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+use zenoh_buffers::ZBuf;
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::{
+    core::{Channel, CongestionControl, EndPoint, WhatAmI, ZenohId},
+    zenoh::ZenohMessage,
+};
+use zenoh_transport::{DummyTransportEventHandler, LinkRxLimits, TransportManager};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn run(endpoint: &EndPoint, limits: LinkRxLimits, msg_size: usize) {
+    // Define client and router IDs
+    let client_id = ZenohId::try_from([1]).unwrap();
+    let router_id = ZenohId::try_from([2]).unwrap();
+
+    // Create the router transport manager
+    let unicast = TransportManager::config_unicast().rx_limits(limits);
+    let router_manager = TransportManager::builder()
+        .zid(router_id)
+        .whatami(WhatAmI::Router)
+        .unicast(unicast)
+        .build(Arc::new(DummyTransportEventHandler::default()))
+        .unwrap();
+
+    // Create the client transport manager
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .build(Arc::new(DummyTransportEventHandler::default()))
+        .unwrap();
+
+    // Create the listener on the router
+    println!("Add locator: {endpoint}");
+    let _ = ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+
+    // Open transport -> This should be accepted, the limits only apply once established
+    println!("Opening transport with {endpoint}");
+    let _ = ztimeout!(client_manager.open_transport(endpoint.clone())).unwrap();
+    let client_transport = client_manager.get_transport(&router_id).unwrap();
+
+    // Create the message to send, this would trigger the transport closure
+    let message = ZenohMessage::make_data(
+        "test".into(),
+        ZBuf::from(vec![0_u8; msg_size]),
+        Channel::default(),
+        CongestionControl::Block,
+        None,
+        None,
+        None,
+        None,
+    );
+    println!("Sending message of {msg_size} bytes with limits {limits:?}");
+    client_transport.schedule(message).unwrap();
+
+    // Wait that the transport has been closed on both sides
+    ztimeout!(async {
+        while client_transport.get_zid().is_ok() {
+            task::sleep(SLEEP).await;
+        }
+    });
+    ztimeout!(async {
+        while !router_manager.get_transports_unicast().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    // Stop the locators on the manager
+    println!("Del locator: {endpoint}");
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+    ztimeout!(async {
+        while !router_manager.get_listeners().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    task::sleep(SLEEP).await;
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_rx_limits_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 18000).parse().unwrap();
+    // Run
+    task::block_on(async {
+        // A single frame larger than the limit
+        let limits = LinkRxLimits {
+            max_frame_size: Some(1_024),
+            ..Default::default()
+        };
+        run(&endpoint, limits, 4_096).await;
+
+        // A fragmented message larger than the limit
+        let limits = LinkRxLimits {
+            max_message_size: Some(100_000),
+            ..Default::default()
+        };
+        run(&endpoint, limits, 131_072).await;
+    });
+}
@@ -50,6 +50,10 @@ pub struct StorageConfig {
     pub garbage_collection_config: GarbageCollectionConfig,
     // Note: ReplicaConfig is optional. Alignment will be performed only if it is a replica
     pub replica_config: Option<ReplicaConfig>,
+    // Note: HistoryPolicy is optional. If absent, the storage keeps what its backend keeps
+    pub history: Option<HistoryPolicy>,
+    // If true, the keys missing from the storage are fetched from the volume's external source
+    pub read_through: bool,
 }
 // Note: All parameters should be same for replicas, else will result on huge overhead
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,6 +61,9 @@ pub struct ReplicaConfig {
     pub publication_interval: Duration,
     pub propagation_delay: Duration,
     pub delta: Duration,
+    pub sub_intervals: usize,
+    pub hot: Option<usize>,
+    pub warm: Option<usize>,
 }
 
 impl Default for ReplicaConfig {
@@ -73,10 +80,31 @@ impl Default for ReplicaConfig {
             // Higher the frequency of updates, lower the delta should be chosen
             // To be efficient, delta should be the time containing no more than 100,000 samples
             delta: Duration::from_millis(1000),
+            // This is the number of subintervals each interval is divided into in the digest.
+            // Higher values allow finer grained alignment at the cost of larger digests
+            sub_intervals: 10,
+            // These are the number of intervals in the hot and warm eras of the digest.
+            // The intervals of the hot era are exchanged with their subintervals, the ones of the warm
+            // era with their checksums only, and the older ones are aggregated in the cold era.
+            // When not set, they are computed from the publication interval and delta
+            hot: None,
+            warm: None,
         }
     }
 }
 
+// The values a storage keeps for each key, enforced on insertion and on garbage collection
+// Note: All the replicas of a storage should be configured with the same policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryPolicy {
+    // Keep only the latest value of each key
+    Latest,
+    // Keep the given number of most recent values of each key
+    Count(usize),
+    // Keep the values received within the given duration, the older ones being removed
+    Window(Duration),
+}
+
 // The configuration for periodic garbage collection of metadata in storage manager
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GarbageCollectionConfig {
@@ -454,10 +482,59 @@ impl StorageConfig {
                         bail!("Invalid type for field `delta` in `replica_config` of storage `{}`. Only integer values are accepted.", plugin_name)
                     }
                 }
+                if let Some(n) = s.get("sub_intervals") {
+                    match n.to_string().parse::<usize>() {
+                        Ok(n) if n > 0 => replica_config.sub_intervals = n,
+                        _ => bail!("Invalid type for field `sub_intervals` in `replica_config` of storage `{}`. Only strictly positive integer values are accepted.", plugin_name)
+                    }
+                }
+                if let Some(n) = s.get("hot") {
+                    match n.to_string().parse::<usize>() {
+                        Ok(n) if n > 0 => replica_config.hot = Some(n),
+                        _ => bail!("Invalid type for field `hot` in `replica_config` of storage `{}`. Only strictly positive integer values are accepted.", plugin_name)
+                    }
+                }
+                if let Some(n) = s.get("warm") {
+                    match n.to_string().parse::<usize>() {
+                        Ok(n) if n > 0 => replica_config.warm = Some(n),
+                        _ => bail!("Invalid type for field `warm` in `replica_config` of storage `{}`. Only strictly positive integer values are accepted.", plugin_name)
+                    }
+                }
+                if replica_config.delta.as_millis() < replica_config.sub_intervals as u128 {
+                    bail!("Invalid `replica_config` of storage `{}`: `delta` must be at least `sub_intervals` milliseconds.", plugin_name)
+                }
                 Some(replica_config)
             }
             None => None,
         };
+        let history = match config.get("history") {
+            Some(Value::String(s)) if s == "latest" => Some(HistoryPolicy::Latest),
+            Some(Value::Object(h)) if h.len() == 1 => {
+                if let Some(count) = h.get("count") {
+                    match count.to_string().parse::<usize>() {
+                        Ok(count) if count > 0 => Some(HistoryPolicy::Count(count)),
+                        _ => bail!("Invalid type for field `count` in `history` of storage `{}`. Only strictly positive integer values are accepted.", storage_name)
+                    }
+                } else if let Some(window) = h.get("window") {
+                    match window.to_string().parse::<u64>() {
+                        Ok(window) if window > 0 => Some(HistoryPolicy::Window(Duration::from_secs(window))),
+                        _ => bail!("Invalid type for field `window` in `history` of storage `{}`. Only strictly positive integer values are accepted.", storage_name)
+                    }
+                } else {
+                    bail!("Invalid `history` of storage `{}`. Accepted values: \"latest\", {{ count: <number of values> }} or {{ window: <duration in seconds> }}", storage_name)
+                }
+            }
+            None => None,
+            _ => bail!("Invalid `history` of storage `{}`. Accepted values: \"latest\", {{ count: <number of values> }} or {{ window: <duration in seconds> }}", storage_name)
+        };
+        let read_through = match config.get("read_through") {
+            Some(Value::Bool(b)) => *b,
+            None => false,
+            _ => bail!(
+                "Invalid type for field `read_through` of storage `{}`. Only booleans are accepted.",
+                storage_name
+            ),
+        };
         Ok(StorageConfig {
             name: storage_name.into(),
             key_expr,
@@ -467,6 +544,8 @@ impl StorageConfig {
             volume_cfg,
             garbage_collection_config,
             replica_config,
+            history,
+            read_through,
         })
     }
 }
@@ -502,3 +581,49 @@ impl PrivacyTransparentGet<serde_json::Value> for serde_json::Map<String, serde_
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn storage_config(history: Value) -> ZResult<StorageConfig> {
+        let config = json!({ "key_expr": "test/**", "volume": "memory", "history": history });
+        StorageConfig::try_from("storage-manager", "test", &config)
+    }
+
+    #[test]
+    fn history_config() {
+        assert_eq!(
+            storage_config(json!("latest")).unwrap().history,
+            Some(HistoryPolicy::Latest)
+        );
+        assert_eq!(
+            storage_config(json!({ "count": 3 })).unwrap().history,
+            Some(HistoryPolicy::Count(3))
+        );
+        assert_eq!(
+            storage_config(json!({ "window": 60 })).unwrap().history,
+            Some(HistoryPolicy::Window(Duration::from_secs(60)))
+        );
+        assert!(storage_config(json!({ "count": 0 })).is_err());
+        assert!(storage_config(json!({ "count": 3, "window": 60 })).is_err());
+        assert!(storage_config(json!("all")).is_err());
+    }
+
+    #[test]
+    fn plugin_history_config() {
+        // The storage-manager validates its whole configuration, including the history policies,
+        // when it is updated
+        let config = |history: Value| json!({ "storages": { "test": { "key_expr": "test/**", "volume": "memory", "history": history } } });
+        let plugin = PluginConfig::try_from(("storage-manager", &config(json!({ "count": 3 }))));
+        assert_eq!(
+            plugin.unwrap().storages[0].history,
+            Some(HistoryPolicy::Count(3))
+        );
+        assert!(PluginConfig::try_from(("storage-manager", &config(json!("all")))).is_err());
+        assert!(
+            PluginConfig::try_from(("storage-manager", &config(json!({ "size": 3 })))).is_err()
+        );
+    }
+}
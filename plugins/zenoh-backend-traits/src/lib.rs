@@ -19,6 +19,7 @@
 //! This crate provides the traits to be implemented by a zenoh backend library:
 //!  - [`Volume`]
 //!  - [`Storage`]
+//!  - [`ReadThrough`], optionally, to serve storages configured with `read_through: true`
 //!
 //! Such library must also declare a `create_volume()` operation
 //! with the `#[no_mangle]` attribute as an entrypoint to be called for the Backend creation.
@@ -136,9 +137,10 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use zenoh::prelude::{KeyExpr, OwnedKeyExpr, Sample, Selector};
 use zenoh::queryable::ReplyBuilder;
-use zenoh::time::Timestamp;
+use zenoh::time::{Timestamp, NTP64};
 use zenoh::value::Value;
 pub use zenoh::Result as ZResult;
+use zenoh_result::bail;
 
 pub mod config;
 use config::{StorageConfig, VolumeConfig};
@@ -213,6 +215,23 @@ pub trait Volume: Send + Sync {
     /// Returns an interceptor that will be called before sending any reply
     /// to a query from a storage created by this backend. `None` can be returned for no interception point.
     fn outgoing_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>;
+
+    /// Returns the external source from which the storages created by this backend and configured
+    /// with `read_through: true` fetch their missing keys. By default, read-through is not supported.
+    fn read_through(&self) -> Option<Arc<dyn ReadThrough>> {
+        None
+    }
+}
+
+/// Trait to be implemented to fetch the keys missing from a storage from an external system.
+///
+/// The fetched values are then cached in the storage, subject to its history policy.
+#[async_trait]
+pub trait ReadThrough: Send + Sync {
+    /// Function called on a query for a single key the storage doesn't hold.
+    /// A key can be `None` if it matches the `strip_prefix` exactly.
+    /// Returns `Ok(None)` if the key isn't present in the external system either.
+    async fn fetch(&self, key: Option<OwnedKeyExpr>) -> ZResult<Option<Value>>;
 }
 
 /// Trait to be implemented by a Storage.
@@ -257,6 +276,14 @@ pub trait Storage: Send + Sync {
     /// The latest Timestamp corresponding to each key is either the timestamp of the delete or put whichever is the latest.
     /// Remember to fetch the entry corresponding to the `None` key
     async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>>;
+
+    /// Function called to enforce the history policy configured for this storage, if any.
+    /// It must remove the values associated with the key whose timestamp is older than `time`, keeping the newer ones.
+    /// A key can be `None` if it matches the `strip_prefix` exactly.
+    /// This is only called on storages whose volume has the [`History::All`] capability.
+    async fn trim(&mut self, _key: Option<OwnedKeyExpr>, _time: NTP64) -> ZResult<()> {
+        bail!("This storage doesn't support trimming the history of its keys")
+    }
 }
 
 /// A wrapper around the [`zenoh::queryable::Query`] allowing to call the
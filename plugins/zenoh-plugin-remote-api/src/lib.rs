@@ -0,0 +1,508 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+use async_std::task;
+use futures_util::future::{self, Either};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::Message;
+use zenoh::plugins::{Plugin, RunningPluginTrait, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod config;
+pub use config::Config;
+mod protocol;
+use protocol::string_to_payload;
+pub use protocol::{ClientMessage, Format, ServerMessage};
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+
+zenoh_plugin_trait::declare_plugin!(RemoteApiPlugin);
+pub struct RemoteApiPlugin {}
+
+impl ZenohPlugin for RemoteApiPlugin {}
+
+impl Plugin for RemoteApiPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "remote_api";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("Remote API plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let clients = Arc::new(AtomicUsize::new(0));
+        let (stop_tx, stop_rx) = flume::bounded(1);
+        task::spawn(run(runtime.clone(), conf.clone(), clients.clone(), stop_rx));
+        Ok(Box::new(RunningPlugin {
+            conf,
+            clients,
+            _stop: stop_tx,
+        }))
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    clients: Arc<AtomicUsize>,
+    // Stops the server and disconnects its clients once dropped along with the plugin
+    _stop: flume::Sender<()>,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-remote-api doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        let mut key = String::from(plugin_status_key);
+        with_extended_string(&mut key, &["/version"], |key| {
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(
+                    key.clone(),
+                    GIT_VERSION.into(),
+                ))
+            }
+        });
+        with_extended_string(&mut key, &["/port"], |port_key| {
+            if keyexpr::new(port_key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(
+                    port_key.clone(),
+                    (&self.conf).into(),
+                ))
+            }
+        });
+        with_extended_string(&mut key, &["/clients"], |clients_key| {
+            if keyexpr::new(clients_key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(
+                    clients_key.clone(),
+                    self.clients.load(Ordering::Relaxed).into(),
+                ))
+            }
+        });
+        Ok(responses)
+    }
+}
+
+fn with_extended_string<R, F: FnMut(&mut String) -> R>(
+    prefix: &mut String,
+    suffixes: &[&str],
+    mut closure: F,
+) -> R {
+    let prefix_len = prefix.len();
+    for suffix in suffixes {
+        prefix.push_str(suffix);
+    }
+    let result = closure(prefix);
+    prefix.truncate(prefix_len);
+    result
+}
+
+// Awaits `future`, unless the plugin is stopped first
+async fn until_stopped<F: Future>(future: F, stop: &flume::Receiver<()>) -> Option<F::Output> {
+    match future::select(Box::pin(future), stop.recv_async()).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+fn encode(format: Format, message: &ServerMessage) -> Option<Message> {
+    format
+        .encode(message)
+        .map_err(|e| log::error!("Unable to encode message for remote API client: {}", e))
+        .ok()
+}
+
+// Sends a message to the client, waiting for room in its queue
+async fn send(tx: &flume::Sender<Message>, format: Format, message: &ServerMessage) {
+    if let Some(message) = encode(format, message) {
+        // The client is gone if the channel is closed
+        let _ = tx.send_async(message).await;
+    }
+}
+
+// Sends a sample to the client from a subscriber's callback, dropping it if the client
+// doesn't keep up with the publications and its queue is full
+fn send_sample(tx: &flume::Sender<Message>, format: Format, id: u64, sample: &Sample) {
+    if let Some(message) = encode(format, &ServerMessage::sample(id, sample)) {
+        if let Err(flume::TrySendError::Full(_)) = tx.try_send(message) {
+            log::debug!(
+                "Remote API client queue full: dropping sample for subscriber {}",
+                id
+            );
+        }
+    }
+}
+
+async fn handle_request(
+    session: &Arc<Session>,
+    request: ClientMessage,
+    format: Format,
+    tx: &flume::Sender<Message>,
+    subscribers: &mut HashMap<u64, Subscriber<'static, ()>>,
+) -> ZResult<()> {
+    match request {
+        ClientMessage::DeclareSubscriber { id, key_expr } => {
+            if subscribers.contains_key(&id) {
+                bail!("Subscriber {} is already declared", id)
+            }
+            let tx = tx.clone();
+            let subscriber = session
+                .declare_subscriber(key_expr)
+                .callback(move |sample| send_sample(&tx, format, id, &sample))
+                .res()
+                .await?;
+            subscribers.insert(id, subscriber);
+        }
+        ClientMessage::UndeclareSubscriber { id } => match subscribers.remove(&id) {
+            Some(subscriber) => subscriber.undeclare().res().await?,
+            None => bail!("Subscriber {} is not declared", id),
+        },
+        ClientMessage::Put {
+            key_expr,
+            value,
+            base64,
+            encoding,
+        } => {
+            let mut put = session.put(key_expr, string_to_payload(value, base64)?);
+            if let Some(encoding) = encoding {
+                put = put.encoding(encoding);
+            }
+            put.res().await?;
+        }
+        ClientMessage::Delete { key_expr } => session.delete(key_expr).res().await?,
+        ClientMessage::Get {
+            id,
+            selector,
+            value,
+            base64,
+        } => {
+            let mut get = session.get(selector);
+            if let Some(value) = value {
+                get = get.with_value(string_to_payload(value, base64)?);
+            }
+            let replies = get.res().await?;
+            let tx = tx.clone();
+            task::spawn(async move {
+                while let Ok(reply) = replies.recv_async().await {
+                    send(&tx, format, &ServerMessage::reply(id, &reply.sample)).await;
+                }
+                send(&tx, format, &ServerMessage::ReplyFinal { id }).await;
+            });
+        }
+    }
+    Ok(())
+}
+
+async fn handle_client(
+    session: Arc<Session>,
+    stream: TcpStream,
+    addr: SocketAddr,
+    queue_size: usize,
+    stop: flume::Receiver<()>,
+) {
+    let ws = match until_stopped(accept_async(stream), &stop).await {
+        Some(Ok(ws)) => ws,
+        None => return,
+        Some(Err(e)) => {
+            log::debug!("WebSocket handshake with {} failed: {}", addr, e);
+            return;
+        }
+    };
+    log::debug!("Remote API client connected from {}", addr);
+    let (mut ws_tx, mut ws_rx) = ws.split();
+
+    // The messages to the client are sent from the subscribers' callbacks and the queries' tasks.
+    // The queue is bounded, so that a slow client can't make the plugin buffer without limit.
+    let (tx, rx) = flume::bounded::<Message>(queue_size);
+    task::spawn(async move {
+        while let Ok(message) = rx.recv_async().await {
+            if let Err(e) = ws_tx.send(message).await {
+                log::debug!("Error sending to remote API client {}: {}", addr, e);
+                break;
+            }
+        }
+    });
+
+    // The subscribers are undeclared when dropped, along with the connection
+    let mut subscribers = HashMap::new();
+    while let Some(Some(message)) = until_stopped(ws_rx.next(), &stop).await {
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                log::debug!("Error receiving from remote API client {}: {}", addr, e);
+                break;
+            }
+        };
+        if message.is_close() {
+            break;
+        }
+        let (request, format) = match Format::decode(&message) {
+            Some(Ok(decoded)) => decoded,
+            Some(Err(e)) => {
+                let format = if message.is_binary() {
+                    Format::Cbor
+                } else {
+                    Format::Json
+                };
+                let error = ServerMessage::Error {
+                    id: None,
+                    message: e.to_string(),
+                };
+                send(&tx, format, &error).await;
+                continue;
+            }
+            // Ping and pong messages are handled by the WebSocket layer
+            None => continue,
+        };
+        log::trace!("Remote API request from {}: {:?}", addr, request);
+        let id = request.id();
+        if let Err(e) = handle_request(&session, request, format, &tx, &mut subscribers).await {
+            let error = ServerMessage::Error {
+                id,
+                message: e.to_string(),
+            };
+            send(&tx, format, &error).await;
+        }
+    }
+    log::debug!("Remote API client {} disconnected", addr);
+}
+
+pub async fn run(
+    runtime: Runtime,
+    conf: Config,
+    clients: Arc<AtomicUsize>,
+    stop: flume::Receiver<()>,
+) {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let session = match zenoh::init(runtime).res().await {
+        Ok(session) => Arc::new(session),
+        Err(e) => {
+            log::error!("Unable to open session for remote API: {}", e);
+            return;
+        }
+    };
+    let listener = match TcpListener::bind(&conf.ws_port).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!(
+                "Unable to start WebSocket server for remote API on {}: {}",
+                conf.ws_port,
+                e
+            );
+            return;
+        }
+    };
+
+    // The sender is never used, the server stops once it is dropped along with the plugin
+    while let Some(accepted) = until_stopped(listener.accept(), &stop).await {
+        match accepted {
+            Ok((stream, addr)) => {
+                let session = session.clone();
+                let clients = clients.clone();
+                let stop = stop.clone();
+                let queue_size = conf.queue_size;
+                task::spawn(async move {
+                    clients.fetch_add(1, Ordering::Relaxed);
+                    handle_client(session, stream, addr, queue_size, stop).await;
+                    clients.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            Err(e) => log::warn!("Error accepting remote API connection: {}", e),
+        }
+    }
+    log::debug!("Remote API server on {} stopped", conf.ws_port);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+    const WS_PORT: &str = "127.0.0.1:18765";
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    async fn connect() -> Client {
+        async_std::future::timeout(TIMEOUT, async {
+            loop {
+                match connect_async(format!("ws://{WS_PORT}")).await {
+                    Ok((client, _)) => break client,
+                    Err(_) => task::sleep(SLEEP).await,
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn request(client: &mut Client, request: &str) {
+        client.send(Message::Text(request.into())).await.unwrap();
+    }
+
+    async fn response(client: &mut Client) -> ServerMessage {
+        let message = async_std::future::timeout(TIMEOUT, client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn remote_api_queue_full() {
+        task::block_on(async {
+            let (tx, rx) = flume::bounded(1);
+            let sample = Sample::new(KeyExpr::try_from("test/remote_api").unwrap(), "value");
+            // The samples are dropped once the queue is full, without blocking the callbacks
+            send_sample(&tx, Format::Json, 1, &sample);
+            send_sample(&tx, Format::Json, 1, &sample);
+            assert_eq!(rx.len(), 1);
+
+            // The other messages wait for room in the queue
+            let final_sent = send(&tx, Format::Json, &ServerMessage::ReplyFinal { id: 2 });
+            let received = async {
+                task::sleep(SLEEP).await;
+                (
+                    rx.recv_async().await.unwrap(),
+                    rx.recv_async().await.unwrap(),
+                )
+            };
+            let (_, (_, last)) = future::join(final_sent, received).await;
+            assert!(matches!(
+                serde_json::from_str::<ServerMessage>(last.to_text().unwrap()).unwrap(),
+                ServerMessage::ReplyFinal { id: 2 }
+            ));
+        });
+    }
+
+    #[test]
+    fn remote_api() {
+        task::block_on(async {
+            let mut config = zenoh::config::peer();
+            config.scouting.multicast.set_enabled(Some(false)).unwrap();
+            config
+                .insert_json5(
+                    "plugins/remote_api",
+                    &format!(r#"{{ ws_port: "{WS_PORT}" }}"#),
+                )
+                .unwrap();
+            let session = zenoh::open(config).res().await.unwrap();
+            let plugin =
+                RemoteApiPlugin::start(RemoteApiPlugin::STATIC_NAME, session.runtime()).unwrap();
+            let mut client = connect().await;
+
+            // The requests are handled in order, the error acknowledging the subscriber declaration
+            request(
+                &mut client,
+                r#"{ "type": "declare_subscriber", "id": 1, "key_expr": "test/remote_api" }"#,
+            )
+            .await;
+            request(
+                &mut client,
+                r#"{ "type": "undeclare_subscriber", "id": 2 }"#,
+            )
+            .await;
+            assert!(matches!(
+                response(&mut client).await,
+                ServerMessage::Error { id: Some(2), .. }
+            ));
+
+            session.put("test/remote_api", "value").res().await.unwrap();
+            match response(&mut client).await {
+                ServerMessage::Sample {
+                    id,
+                    key_expr,
+                    value,
+                    ..
+                } => {
+                    assert_eq!(id, 1);
+                    assert_eq!(key_expr, "test/remote_api");
+                    assert_eq!(value, "value");
+                }
+                message => panic!("Unexpected message {message:?}"),
+            }
+
+            // Stopping the plugin disconnects its clients and stops its server
+            drop(plugin);
+            let next = async_std::future::timeout(TIMEOUT, client.next())
+                .await
+                .unwrap();
+            assert!(matches!(
+                next,
+                None | Some(Err(_)) | Some(Ok(Message::Close(_)))
+            ));
+            async_std::future::timeout(TIMEOUT, async {
+                while connect_async(format!("ws://{WS_PORT}")).await.is_ok() {
+                    task::sleep(SLEEP).await;
+                }
+            })
+            .await
+            .unwrap();
+
+            session.close().res().await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,243 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The messages exchanged with the remote clients.
+//!
+//! Each WebSocket message carries exactly one message, serialized in JSON in text frames or in
+//! CBOR in binary frames. The messages sent by the server in response to a request (samples of a
+//! subscriber, replies to a query, errors) use the format of that request.
+//!
+//! Payloads are carried as strings: UTF-8 payloads as is, other payloads encoded in base64 with
+//! the `base64` flag set.
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::Message;
+use zenoh::prelude::r#async::*;
+use zenoh_result::{zerror, ZResult};
+
+/// A message sent by a remote client.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ClientMessage {
+    /// Declares a subscriber, whose samples will be sent with the same `id`.
+    DeclareSubscriber {
+        id: u64,
+        key_expr: String,
+    },
+    UndeclareSubscriber {
+        id: u64,
+    },
+    Put {
+        key_expr: String,
+        value: String,
+        #[serde(default)]
+        base64: bool,
+        #[serde(default)]
+        encoding: Option<String>,
+    },
+    Delete {
+        key_expr: String,
+    },
+    /// Issues a query, whose replies will be sent with the same `id`.
+    Get {
+        id: u64,
+        selector: String,
+        #[serde(default)]
+        value: Option<String>,
+        #[serde(default)]
+        base64: bool,
+    },
+}
+
+impl ClientMessage {
+    /// The identifier of the subscriber or query this message refers to, if any.
+    pub fn id(&self) -> Option<u64> {
+        match self {
+            ClientMessage::DeclareSubscriber { id, .. }
+            | ClientMessage::UndeclareSubscriber { id }
+            | ClientMessage::Get { id, .. } => Some(*id),
+            ClientMessage::Put { .. } | ClientMessage::Delete { .. } => None,
+        }
+    }
+}
+
+/// A message sent to a remote client.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Sample {
+        id: u64,
+        key_expr: String,
+        kind: String,
+        value: String,
+        base64: bool,
+        encoding: String,
+        timestamp: Option<String>,
+    },
+    Reply {
+        id: u64,
+        key_expr: String,
+        value: String,
+        base64: bool,
+        encoding: String,
+        timestamp: Option<String>,
+    },
+    ReplyError {
+        id: u64,
+        value: String,
+        base64: bool,
+        encoding: String,
+    },
+    ReplyFinal {
+        id: u64,
+    },
+    Error {
+        id: Option<u64>,
+        message: String,
+    },
+}
+
+/// The serialization format of the messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Cbor,
+}
+
+impl Format {
+    pub fn decode(message: &Message) -> Option<ZResult<(ClientMessage, Format)>> {
+        match message {
+            Message::Text(text) => Some(
+                serde_json::from_str(text)
+                    .map(|m| (m, Format::Json))
+                    .map_err(|e| zerror!("Invalid JSON message: {}", e).into()),
+            ),
+            Message::Binary(bytes) => Some(
+                ciborium::de::from_reader(bytes.as_slice())
+                    .map(|m| (m, Format::Cbor))
+                    .map_err(|e| zerror!("Invalid CBOR message: {}", e).into()),
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, message: &ServerMessage) -> ZResult<Message> {
+        match self {
+            Format::Json => Ok(Message::Text(serde_json::to_string(message)?)),
+            Format::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::ser::into_writer(message, &mut bytes)
+                    .map_err(|e| zerror!("Unable to encode CBOR message: {}", e))?;
+                Ok(Message::Binary(bytes))
+            }
+        }
+    }
+}
+
+/// Returns the payload of `value` as a string, and whether it is encoded in base64.
+pub fn payload_to_string(value: &Value) -> (String, bool) {
+    let payload = value.payload.contiguous();
+    match std::str::from_utf8(&payload) {
+        Ok(s) => (s.to_string(), false),
+        Err(_) => (b64_std_engine.encode(&payload), true),
+    }
+}
+
+pub fn string_to_payload(value: String, base64: bool) -> ZResult<Vec<u8>> {
+    if base64 {
+        b64_std_engine
+            .decode(value)
+            .map_err(|e| zerror!("Invalid base64 payload: {}", e).into())
+    } else {
+        Ok(value.into_bytes())
+    }
+}
+
+impl ServerMessage {
+    pub fn sample(id: u64, sample: &Sample) -> Self {
+        let (value, base64) = payload_to_string(&sample.value);
+        ServerMessage::Sample {
+            id,
+            key_expr: sample.key_expr.to_string(),
+            kind: sample.kind.to_string(),
+            value,
+            base64,
+            encoding: sample.value.encoding.to_string(),
+            timestamp: sample.timestamp.as_ref().map(|ts| ts.to_string()),
+        }
+    }
+
+    pub fn reply(id: u64, reply: &Result<Sample, Value>) -> Self {
+        match reply {
+            Ok(sample) => {
+                let (value, base64) = payload_to_string(&sample.value);
+                ServerMessage::Reply {
+                    id,
+                    key_expr: sample.key_expr.to_string(),
+                    value,
+                    base64,
+                    encoding: sample.value.encoding.to_string(),
+                    timestamp: sample.timestamp.as_ref().map(|ts| ts.to_string()),
+                }
+            }
+            Err(err) => {
+                let (value, base64) = payload_to_string(err);
+                ServerMessage::ReplyError {
+                    id,
+                    value,
+                    base64,
+                    encoding: err.encoding.to_string(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn client_messages_codec() {
+        use super::*;
+
+        let put = ClientMessage::Put {
+            key_expr: "demo/example".into(),
+            value: "AAEC".into(),
+            base64: true,
+            encoding: None,
+        };
+        let json = Message::Text(
+            r#"{"type":"put","key_expr":"demo/example","value":"AAEC","base64":true}"#.into(),
+        );
+        let (decoded, format) = Format::decode(&json).unwrap().unwrap();
+        assert_eq!(decoded, put);
+        assert_eq!(format, Format::Json);
+        assert_eq!(
+            string_to_payload("AAEC".into(), true).unwrap(),
+            vec![0, 1, 2]
+        );
+
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&put, &mut bytes).unwrap();
+        let (decoded, format) = Format::decode(&Message::Binary(bytes)).unwrap().unwrap();
+        assert_eq!(decoded, put);
+        assert_eq!(format, Format::Cbor);
+
+        assert!(
+            Format::decode(&Message::Text(r#"{"type":"unknown"}"#.into()))
+                .unwrap()
+                .is_err()
+        );
+        assert!(Format::decode(&Message::Ping(vec![])).is_none());
+    }
+}
@@ -293,15 +293,30 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
                         async_std::task::current().id()
                     );
                     let sender = &sender;
-                    let sub = req
-                        .state()
-                        .0
-                        .declare_subscriber(&key_expr)
-                        .res()
-                        .await
-                        .unwrap();
+                    let sub = match req.state().0.declare_subscriber(&key_expr).res().await {
+                        Ok(sub) => sub,
+                        Err(e) => {
+                            log::error!(
+                                "Unable to subscribe to {} for SSE stream: {} (task {})",
+                                key_expr,
+                                e,
+                                async_std::task::current().id()
+                            );
+                            return;
+                        }
+                    };
                     loop {
-                        let sample = sub.recv_async().await.unwrap();
+                        let sample = match sub.recv_async().await {
+                            Ok(sample) => sample,
+                            Err(e) => {
+                                log::debug!(
+                                    "SSE subscriber closed ({})! Terminate (task {})",
+                                    e,
+                                    async_std::task::current().id()
+                                );
+                                break;
+                            }
+                        };
                         match sender
                             .send(&sample.kind.to_string(), sample_to_json(sample), None)
                             .timeout(std::time::Duration::new(10, 0))
@@ -17,14 +17,15 @@ use std::sync::Arc;
 use zenoh::prelude::r#async::*;
 use zenoh::Session;
 use zenoh_backend_traits::config::StorageConfig;
-use zenoh_backend_traits::Capability;
-use zenoh_result::ZResult;
+use zenoh_backend_traits::{Capability, ReadThrough};
+use zenoh_result::{zerror, ZResult};
 
 pub struct StoreIntercept {
     pub storage: Box<dyn zenoh_backend_traits::Storage>,
     pub capability: Capability,
     pub in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     pub out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
+    pub read_through: Option<Arc<dyn ReadThrough>>,
 }
 
 pub(crate) async fn create_and_start_storage(
@@ -37,12 +38,24 @@ pub(crate) async fn create_and_start_storage(
 ) -> ZResult<Sender<StorageMessage>> {
     log::trace!("Create storage {}", &admin_key);
     let capability = backend.get_capability();
+    let read_through = if config.read_through {
+        Some(backend.read_through().ok_or_else(|| {
+            zerror!(
+                "Storage `{}` is configured with `read_through`, but volume `{}` doesn't support it",
+                config.name,
+                config.volume_id
+            )
+        })?)
+    } else {
+        None
+    };
     let storage = backend.create_storage(config.clone()).await?;
     let store_intercept = StoreIntercept {
         storage,
         capability,
         in_interceptor,
         out_interceptor,
+        read_through,
     };
 
     start_storage(store_intercept, config, admin_key, zenoh).await
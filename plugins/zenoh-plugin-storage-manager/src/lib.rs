@@ -38,7 +38,7 @@ use zenoh_backend_traits::CreateVolume;
 use zenoh_backend_traits::CREATE_VOLUME_FN_NAME;
 use zenoh_backend_traits::{config::*, Volume};
 use zenoh_core::zlock;
-use zenoh_result::{bail, ZResult};
+use zenoh_result::{bail, zerror, ZResult};
 use zenoh_util::LibLoader;
 
 mod backends_mgt;
@@ -390,6 +390,28 @@ impl RunningPluginTrait for StorageRuntime {
         });
         Ok(responses)
     }
+
+    fn adminspace_action(&self, action: &str, _payload: &[u8]) -> ZResult<()> {
+        let storage = match action.strip_prefix("storages/") {
+            Some(storage) => storage.strip_suffix("/flush"),
+            None => None,
+        };
+        let storage = match storage {
+            Some(storage) => storage,
+            None => bail!("Unsupported storage manager action `{}`", action),
+        };
+        let guard = zlock!(self.0);
+        match guard
+            .storages
+            .values()
+            .find_map(|storages| storages.get(storage))
+        {
+            Some(handle) => handle
+                .try_send(StorageMessage::Flush)
+                .map_err(|e| zerror!("Unable to flush storage `{}`: {}", storage, e).into()),
+            None => bail!("Storage `{}` not found", storage),
+        }
+    }
 }
 
 const BACKEND_LIB_PREFIX: &str = "zenoh_backend_";
@@ -50,7 +50,6 @@ const CONTENTS: &str = "contents";
 pub const EPOCH_START: SystemTime = SystemTime::UNIX_EPOCH;
 
 pub const ALIGN_PREFIX: &str = "@-digest";
-pub const SUBINTERVAL_CHUNKS: usize = 10;
 
 // A replica consists of a storage service and services required for anti-entropy
 // To perform anti-entropy, we need a `Digest` that contains the state of the datastore
@@ -314,13 +313,7 @@ impl Replica {
             return false;
         }
         // TODO: test this part
-        if config.delta != self.replica_config.delta
-            || config.hot
-                != Replica::get_hot_interval_number(
-                    self.replica_config.publication_interval,
-                    self.replica_config.delta,
-                )
-        {
+        if config != Replica::get_digest_config(&self.replica_config) {
             log::error!("[DIGEST_SUB] Mismatching digest configs, cannot be aligned");
             return false;
         }
@@ -335,11 +328,22 @@ impl Replica {
             .unwrap()
     }
 
-    pub fn get_hot_interval_number(publication_interval: Duration, delta: Duration) -> usize {
-        ((publication_interval.as_nanos() / delta.as_nanos()) as usize) + 1
+    pub fn get_digest_config(replica_config: &ReplicaConfig) -> DigestConfig {
+        let hot = replica_config.hot.unwrap_or_else(|| {
+            Replica::get_hot_interval_number(
+                replica_config.publication_interval,
+                replica_config.delta,
+            )
+        });
+        DigestConfig {
+            delta: replica_config.delta,
+            sub_intervals: replica_config.sub_intervals,
+            hot,
+            warm: replica_config.warm.unwrap_or(hot * 5),
+        }
     }
 
-    pub fn get_warm_interval_number(publication_interval: Duration, delta: Duration) -> usize {
-        Replica::get_hot_interval_number(publication_interval, delta) * 5
+    pub fn get_hot_interval_number(publication_interval: Duration, delta: Duration) -> usize {
+        ((publication_interval.as_nanos() / delta.as_nanos()) as usize) + 1
     }
 }
@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::{Digest, DigestConfig, LogEntry};
+use super::{Digest, LogEntry};
 use async_std::sync::Arc;
 use async_std::sync::RwLock;
 use async_std::task::sleep;
@@ -71,18 +71,7 @@ impl Snapshotter {
                 last_interval: RwLock::new(last_interval),
                 digest: Arc::new(RwLock::new(Digest::create_digest(
                     last_snapshot_time,
-                    DigestConfig {
-                        delta: replica_config.delta,
-                        sub_intervals: super::SUBINTERVAL_CHUNKS,
-                        hot: super::Replica::get_hot_interval_number(
-                            replica_config.publication_interval,
-                            replica_config.delta,
-                        ),
-                        warm: super::Replica::get_warm_interval_number(
-                            replica_config.publication_interval,
-                            replica_config.delta,
-                        ),
-                    },
+                    super::Replica::get_digest_config(replica_config),
                     Vec::new(),
                     last_interval,
                 ))),
@@ -205,18 +194,7 @@ impl Snapshotter {
         }
         let digest = Digest::create_digest(
             now,
-            super::DigestConfig {
-                delta: self.replica_config.delta,
-                sub_intervals: super::SUBINTERVAL_CHUNKS,
-                hot: super::Replica::get_hot_interval_number(
-                    self.replica_config.publication_interval,
-                    self.replica_config.delta,
-                ),
-                warm: super::Replica::get_warm_interval_number(
-                    self.replica_config.publication_interval,
-                    self.replica_config.delta,
-                ),
-            },
+            super::Replica::get_digest_config(&self.replica_config),
             log,
             *latest_interval,
         );
@@ -18,15 +18,19 @@ use async_std::sync::{Mutex, RwLock};
 use async_trait::async_trait;
 use flume::{Receiver, Sender};
 use futures::select;
-use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::{self, FromStr};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zenoh::buffers::ZBuf;
 use zenoh::prelude::r#async::*;
+use zenoh::selector::TimeRange;
 use zenoh::time::{Timestamp, NTP64};
 use zenoh::{Result as ZResult, Session};
-use zenoh_backend_traits::config::{GarbageCollectionConfig, StorageConfig};
-use zenoh_backend_traits::{Capability, History, Persistence, StorageInsertionResult, StoredData};
+use zenoh_backend_traits::config::{GarbageCollectionConfig, HistoryPolicy, StorageConfig};
+use zenoh_backend_traits::{
+    Capability, History, Persistence, ReadThrough, StorageInsertionResult, StoredData,
+};
 use zenoh_keyexpr::key_expr::OwnedKeyExpr;
 use zenoh_keyexpr::keyexpr_tree::impls::KeyedSetProvider;
 use zenoh_keyexpr::keyexpr_tree::IKeyExprTreeMut;
@@ -57,12 +61,16 @@ pub struct StorageService {
     complete: bool,
     name: String,
     strip_prefix: Option<OwnedKeyExpr>,
-    storage: Mutex<Box<dyn zenoh_backend_traits::Storage>>,
+    storage: Arc<Mutex<Box<dyn zenoh_backend_traits::Storage>>>,
     capability: Capability,
+    history: Option<HistoryPolicy>,
+    // Timestamps of the values kept for each key, only maintained to enforce HistoryPolicy::Count
+    history_index: Mutex<HashMap<Option<OwnedKeyExpr>, VecDeque<Timestamp>>>,
     tombstones: Arc<RwLock<KeBoxTree<Timestamp, NonWild, KeyedSetProvider>>>,
     wildcard_updates: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
+    read_through: Option<Arc<dyn ReadThrough>>,
     replication: Option<ReplicationService>,
 }
 
@@ -82,12 +90,15 @@ impl StorageService {
             complete: config.complete,
             name: name.to_string(),
             strip_prefix: config.strip_prefix,
-            storage: Mutex::new(store_intercept.storage),
+            storage: Arc::new(Mutex::new(store_intercept.storage)),
             capability: store_intercept.capability,
+            history: config.history,
+            history_index: Mutex::new(HashMap::new()),
             tombstones: Arc::new(RwLock::new(KeBoxTree::new())),
             wildcard_updates: Arc::new(RwLock::new(KeBoxTree::new())),
             in_interceptor: store_intercept.in_interceptor,
             out_interceptor: store_intercept.out_interceptor,
+            read_through: store_intercept.read_through,
             replication,
         };
         if storage_service
@@ -131,8 +142,9 @@ impl StorageService {
 
         // start periodic GC event
         let t = Timer::default();
+        let period = gc_config.period;
         let gc = TimedEvent::periodic(
-            gc_config.period,
+            period,
             GarbageCollectionEvent {
                 config: gc_config,
                 tombstones: self.tombstones.clone(),
@@ -141,6 +153,20 @@ impl StorageService {
         );
         t.add_async(gc).await;
 
+        // expired values are removed along with the metadata
+        if let Some(HistoryPolicy::Window(window)) = self.history {
+            let expiration = TimedEvent::periodic(
+                period,
+                ExpirationEvent {
+                    name: self.name.clone(),
+                    window,
+                    trim: self.capability.history == History::All,
+                    storage: self.storage.clone(),
+                },
+            );
+            t.add_async(expiration).await;
+        }
+
         // subscribe on key_expr
         let storage_sub = match self.session.declare_subscriber(&self.key_expr).res().await {
             Ok(storage_sub) => storage_sub,
@@ -207,6 +233,7 @@ impl StorageService {
                                 log::trace!("Dropping storage {}", self.name);
                                 return
                             },
+                            Ok(StorageMessage::Flush) => self.flush().await,
                             Ok(StorageMessage::GetStatus(tx)) => {
                                 let storage = self.storage.lock().await;
                                 std::mem::drop(tx.send(storage.get_admin_status()).await);
@@ -245,6 +272,7 @@ impl StorageService {
                                 log::trace!("Dropping storage {}", self.name);
                                 return
                             },
+                            Ok(StorageMessage::Flush) => self.flush().await,
                             Ok(StorageMessage::GetStatus(tx)) => {
                                 let storage = self.storage.lock().await;
                                 std::mem::drop(tx.send(storage.get_admin_status()).await);
@@ -260,6 +288,15 @@ impl StorageService {
         }
     }
 
+    // Deletes all the data held by the storage, as a deletion published on its key expression would
+    async fn flush(&self) {
+        log::debug!("Flushing storage {}", self.name);
+        let mut sample = Sample::new(KeyExpr::from(self.key_expr.clone()), Value::empty());
+        sample.kind = SampleKind::Delete;
+        sample.ensure_timestamp();
+        self.process_sample(sample).await;
+    }
+
     // The storage should only simply save the key, sample pair while put and retrieve the same during get
     // the trimming during PUT and GET should be handled by the plugin
     async fn process_sample(&self, sample: Sample) {
@@ -329,19 +366,45 @@ impl StorageService {
                         return;
                     }
                 };
+                if sample.kind == SampleKind::Put
+                    && self.is_expired(sample_to_store.timestamp.as_ref().unwrap())
+                {
+                    log::trace!(
+                        "Sample `{}` is older than the history window of storage {}",
+                        sample_to_store,
+                        self.name
+                    );
+                    continue;
+                }
                 let mut storage = self.storage.lock().await;
                 let result = if sample.kind == SampleKind::Put {
-                    storage
+                    let result = storage
                         .put(
-                            stripped_key,
+                            stripped_key.clone(),
                             sample_to_store.value.clone(),
                             sample_to_store.timestamp.unwrap(),
                         )
-                        .await
+                        .await;
+                    // An outdated value was not stored, the history of the key is unchanged
+                    if matches!(
+                        result,
+                        Ok(StorageInsertionResult::Inserted | StorageInsertionResult::Replaced)
+                    ) {
+                        self.enforce_history(
+                            &mut storage,
+                            stripped_key,
+                            sample_to_store.timestamp.unwrap(),
+                        )
+                        .await;
+                    }
+                    result
                 } else if sample.kind == SampleKind::Delete {
                     // register a tombstone
                     self.mark_tombstone(&k, sample_to_store.timestamp.unwrap())
                         .await;
+                    if matches!(self.history, Some(HistoryPolicy::Count(_))) {
+                        self.history_index.lock().await.remove(&stripped_key);
+                    }
                     storage
                         .delete(stripped_key, sample_to_store.timestamp.unwrap())
                         .await
@@ -370,6 +433,78 @@ impl StorageService {
         }
     }
 
+    fn is_expired(&self, timestamp: &Timestamp) -> bool {
+        match self.history {
+            Some(HistoryPolicy::Window(window)) => timestamp.get_time() < &window_start(window),
+            _ => false,
+        }
+    }
+
+    // Removes the values of `key` that are no longer to be kept once a value with `timestamp` was inserted
+    async fn enforce_history(
+        &self,
+        storage: &mut Box<dyn zenoh_backend_traits::Storage>,
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) {
+        // Backends without history support already keep the latest value only
+        if self.capability.history != History::All {
+            return;
+        }
+        let time = match self.history {
+            None => return,
+            Some(HistoryPolicy::Latest) => *timestamp.get_time(),
+            Some(HistoryPolicy::Window(window)) => window_start(window),
+            Some(HistoryPolicy::Count(count)) => {
+                let mut history_index = self.history_index.lock().await;
+                let timestamps = match history_index.entry(key.clone()) {
+                    Entry::Occupied(entry) => {
+                        let timestamps = entry.into_mut();
+                        let position = timestamps.partition_point(|ts| ts < &timestamp);
+                        if timestamps.get(position) != Some(&timestamp) {
+                            timestamps.insert(position, timestamp);
+                        }
+                        timestamps
+                    }
+                    // The key is not indexed yet, e.g. its values were stored before a restart:
+                    // its index is rebuilt from the values already stored, including the new one
+                    Entry::Vacant(entry) => {
+                        let mut stored: Vec<Timestamp> = match storage.get(key.clone(), "").await {
+                            Ok(stored) => stored.into_iter().map(|s| s.timestamp).collect(),
+                            Err(e) => {
+                                log::warn!(
+                                    "Unable to enforce the history policy of storage {}: {}",
+                                    self.name,
+                                    e
+                                );
+                                return;
+                            }
+                        };
+                        if !stored.contains(&timestamp) {
+                            stored.push(timestamp);
+                        }
+                        stored.sort();
+                        entry.insert(VecDeque::from(stored))
+                    }
+                };
+                if timestamps.len() <= count {
+                    return;
+                }
+                while timestamps.len() > count {
+                    timestamps.pop_front();
+                }
+                *timestamps.front().unwrap().get_time()
+            }
+        };
+        if let Err(e) = storage.trim(key, time).await {
+            log::warn!(
+                "Unable to enforce the history policy of storage {}: {}",
+                self.name,
+                e
+            );
+        }
+    }
+
     async fn mark_tombstone(&self, key_expr: &OwnedKeyExpr, timestamp: Timestamp) {
         // @TODO: change into a better store that does incremental writes
         let mut tombstones = self.tombstones.write().await;
@@ -500,6 +635,27 @@ impl StorageService {
             }
         };
         log::trace!("[STORAGE] Processing query on key_expr: {}", q.key_expr());
+        // The `_time` parameter is applied to the data returned by the backends, whether they support it or not
+        let time_range = match q.selector().time_range() {
+            Ok(time_range) => time_range.map(TimeRange::resolve),
+            Err(e) => {
+                let err_message = format!("Storage {} received an invalid query: {}", self.name, e);
+                log::warn!("{}", err_message);
+                if let Err(e) = q.reply(Err(err_message.into())).res().await {
+                    log::warn!(
+                        "Storage {} raised an error replying a query: {}",
+                        self.name,
+                        e
+                    )
+                }
+                return;
+            }
+        };
+        let in_time_range = |data: &StoredData| {
+            time_range.as_ref().map_or(true, |range| {
+                range.contains(data.timestamp.get_time().to_system_time())
+            })
+        };
         if q.key_expr().is_wild() {
             // resolve key expr into individual keys
             let matching_keys = self.get_matching_keys(q.key_expr()).await;
@@ -515,7 +671,7 @@ impl StorageService {
                 };
                 match storage.get(stripped_key, q.parameters()).await {
                     Ok(stored_data) => {
-                        for entry in stored_data {
+                        for entry in stored_data.into_iter().filter(in_time_range) {
                             let sample = Sample::new(key.clone(), entry.value)
                                 .with_timestamp(entry.timestamp);
                             // apply outgoing interceptor on results
@@ -547,7 +703,17 @@ impl StorageService {
                 }
             };
             let mut storage = self.storage.lock().await;
-            match storage.get(stripped_key, q.parameters()).await {
+            let result = storage.get(stripped_key.clone(), q.parameters()).await;
+            drop(storage);
+            // if key is not available, try to fetch it from the external source
+            let result = match result {
+                Ok(stored_data) if !stored_data.is_empty() || self.read_through.is_none() => {
+                    Ok(stored_data)
+                }
+                Err(e) if self.read_through.is_none() => Err(e),
+                _ => self.read_through(q.key_expr(), stripped_key).await,
+            };
+            match result {
                 Ok(stored_data) => {
                     // if key is not available, return Error
                     if stored_data.is_empty() {
@@ -561,7 +727,7 @@ impl StorageService {
                         }
                         return;
                     }
-                    for entry in stored_data {
+                    for entry in stored_data.into_iter().filter(in_time_range) {
                         let sample = Sample::new(q.key_expr().clone(), entry.value)
                             .with_timestamp(entry.timestamp);
                         // apply outgoing interceptor on results
@@ -595,6 +761,36 @@ impl StorageService {
         }
     }
 
+    // Fetches a key missing from the storage from the external source, caching the result in the storage
+    async fn read_through(
+        &self,
+        key_expr: &KeyExpr<'static>,
+        stripped_key: Option<OwnedKeyExpr>,
+    ) -> ZResult<Vec<StoredData>> {
+        let source = match &self.read_through {
+            Some(source) => source,
+            None => return Ok(Vec::new()),
+        };
+        match source.fetch(stripped_key).await? {
+            Some(value) => {
+                log::trace!(
+                    "Fetched missing key `{}` for storage {}",
+                    key_expr,
+                    self.name
+                );
+                let mut sample = Sample::new(key_expr.clone(), value);
+                sample.ensure_timestamp();
+                let stored_data = StoredData {
+                    value: sample.value.clone(),
+                    timestamp: sample.timestamp.unwrap(),
+                };
+                self.process_sample(sample).await;
+                Ok(vec![stored_data])
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
     async fn get_matching_keys(&self, key_expr: &KeyExpr<'_>) -> Vec<OwnedKeyExpr> {
         let mut result = Vec::new();
         // @TODO: if cache exists, use that to get the list
@@ -719,6 +915,52 @@ fn construct_update(data: String) -> Update {
     Update { kind, data }
 }
 
+// The oldest time of the values to be kept with a history window of `window`
+fn window_start(window: Duration) -> NTP64 {
+    NTP64::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap()) - NTP64::from(window)
+}
+
+// Periodic event removing the values older than the history window
+struct ExpirationEvent {
+    name: String,
+    window: Duration,
+    trim: bool,
+    storage: Arc<Mutex<Box<dyn zenoh_backend_traits::Storage>>>,
+}
+
+#[async_trait]
+impl Timed for ExpirationEvent {
+    async fn run(&mut self) {
+        log::trace!("Start removing expired values of storage {}", self.name);
+        let time_limit = window_start(self.window);
+        let mut storage = self.storage.lock().await;
+        let entries = match storage.get_all_entries().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::error!("Unable to list the entries of storage {}: {}", self.name, e);
+                return;
+            }
+        };
+        for (key, ts) in entries {
+            let result = if ts.get_time() < &time_limit {
+                storage.delete(key, ts).await.map(|_| ())
+            } else if self.trim {
+                storage.trim(key, time_limit).await
+            } else {
+                Ok(())
+            };
+            if let Err(e) = result {
+                log::warn!(
+                    "Unable to remove expired values of storage {}: {}",
+                    self.name,
+                    e
+                );
+            }
+        }
+        log::trace!("End removing expired values of storage {}", self.name);
+    }
+}
+
 // Periodic event cleaning-up data info for old metadata
 struct GarbageCollectionEvent {
     config: GarbageCollectionConfig,
@@ -763,3 +1005,260 @@ impl Timed for GarbageCollectionEvent {
         log::trace!("End garbage collection of obsolete data-infos");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh::time::new_reception_timestamp;
+    use zenoh_backend_traits::Storage;
+
+    // A storage keeping all the values of each key in memory
+    #[derive(Clone, Default)]
+    struct HistoryStorage(Arc<std::sync::Mutex<Vec<(Option<OwnedKeyExpr>, Value, Timestamp)>>>);
+
+    impl HistoryStorage {
+        fn timestamps(&self, key: &str) -> Vec<Timestamp> {
+            let key = Some(OwnedKeyExpr::from_str(key).unwrap());
+            let mut timestamps = self
+                .0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(k, _, _)| k == &key)
+                .map(|(_, _, ts)| *ts)
+                .collect::<Vec<_>>();
+            timestamps.sort();
+            timestamps
+        }
+    }
+
+    #[async_trait]
+    impl Storage for HistoryStorage {
+        fn get_admin_status(&self) -> serde_json::Value {
+            serde_json::Value::Null
+        }
+
+        async fn put(
+            &mut self,
+            key: Option<OwnedKeyExpr>,
+            value: Value,
+            timestamp: Timestamp,
+        ) -> ZResult<StorageInsertionResult> {
+            self.0.lock().unwrap().push((key, value, timestamp));
+            Ok(StorageInsertionResult::Inserted)
+        }
+
+        async fn delete(
+            &mut self,
+            key: Option<OwnedKeyExpr>,
+            _timestamp: Timestamp,
+        ) -> ZResult<StorageInsertionResult> {
+            self.0.lock().unwrap().retain(|(k, _, _)| k != &key);
+            Ok(StorageInsertionResult::Deleted)
+        }
+
+        async fn get(
+            &mut self,
+            key: Option<OwnedKeyExpr>,
+            _parameters: &str,
+        ) -> ZResult<Vec<StoredData>> {
+            Ok(self
+                .0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(k, _, _)| k == &key)
+                .map(|(_, value, timestamp)| StoredData {
+                    value: value.clone(),
+                    timestamp: *timestamp,
+                })
+                .collect())
+        }
+
+        async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+            let mut entries: HashMap<Option<OwnedKeyExpr>, Timestamp> = HashMap::new();
+            for (key, _, timestamp) in self.0.lock().unwrap().iter() {
+                let latest = entries.entry(key.clone()).or_insert(*timestamp);
+                if *latest < *timestamp {
+                    *latest = *timestamp;
+                }
+            }
+            Ok(entries.into_iter().collect())
+        }
+
+        async fn trim(&mut self, key: Option<OwnedKeyExpr>, time: NTP64) -> ZResult<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .retain(|(k, _, ts)| k != &key || ts.get_time() >= &time);
+            Ok(())
+        }
+    }
+
+    async fn storage_service(
+        storage: HistoryStorage,
+        history: Option<HistoryPolicy>,
+    ) -> StorageService {
+        let mut config = zenoh::config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let storage: Box<dyn Storage> = Box::new(storage);
+        StorageService {
+            session: zenoh::open(config).res().await.unwrap().into_arc(),
+            key_expr: OwnedKeyExpr::from_str("test/storage/**").unwrap(),
+            complete: false,
+            name: "test".into(),
+            strip_prefix: Some(OwnedKeyExpr::from_str("test/storage").unwrap()),
+            storage: Arc::new(Mutex::new(storage)),
+            capability: Capability {
+                persistence: Persistence::Volatile,
+                history: History::All,
+                read_cost: 0,
+            },
+            history,
+            history_index: Mutex::new(HashMap::new()),
+            tombstones: Arc::new(RwLock::new(KeBoxTree::new())),
+            wildcard_updates: Arc::new(RwLock::new(KeBoxTree::new())),
+            in_interceptor: None,
+            out_interceptor: None,
+            read_through: None,
+            replication: None,
+        }
+    }
+
+    fn sample(key_expr: &str, timestamp: Timestamp) -> Sample {
+        Sample::new(KeyExpr::try_from(key_expr).unwrap().into_owned(), "value")
+            .with_timestamp(timestamp)
+    }
+
+    #[test]
+    fn history_count() {
+        async_std::task::block_on(async {
+            let storage = HistoryStorage::default();
+            let service = storage_service(storage.clone(), Some(HistoryPolicy::Count(2))).await;
+            let t1 = Timestamp::from_str("2022-12-21T15:00:01.000000000Z/01").unwrap();
+            let t2 = Timestamp::from_str("2022-12-21T15:00:02.000000000Z/01").unwrap();
+            let t3 = Timestamp::from_str("2022-12-21T15:00:03.000000000Z/01").unwrap();
+
+            // The values are kept in their timestamps order, whatever their reception order
+            for ts in [t1, t3, t2] {
+                service.process_sample(sample("test/storage/a", ts)).await;
+            }
+            service.process_sample(sample("test/storage/b", t1)).await;
+            assert_eq!(storage.timestamps("a"), vec![t2, t3]);
+            assert_eq!(storage.timestamps("b"), vec![t1]);
+
+            // A deleted key starts its history anew
+            let t4 = Timestamp::from_str("2022-12-21T15:00:04.000000000Z/01").unwrap();
+            let t5 = Timestamp::from_str("2022-12-21T15:00:05.000000000Z/01").unwrap();
+            let mut delete = sample("test/storage/a", t4);
+            delete.kind = SampleKind::Delete;
+            service.process_sample(delete).await;
+            service.process_sample(sample("test/storage/a", t5)).await;
+            assert_eq!(storage.timestamps("a"), vec![t5]);
+        });
+    }
+
+    #[test]
+    fn history_count_stored() {
+        async_std::task::block_on(async {
+            let storage = HistoryStorage::default();
+            let t1 = Timestamp::from_str("2022-12-21T15:00:01.000000000Z/01").unwrap();
+            let t2 = Timestamp::from_str("2022-12-21T15:00:02.000000000Z/01").unwrap();
+            let t3 = Timestamp::from_str("2022-12-21T15:00:03.000000000Z/01").unwrap();
+            // Values stored before the storage (re)started
+            for ts in [t1, t2] {
+                let key = Some(OwnedKeyExpr::from_str("a").unwrap());
+                storage.0.lock().unwrap().push((key, "value".into(), ts));
+            }
+
+            let service = storage_service(storage.clone(), Some(HistoryPolicy::Count(2))).await;
+            service.process_sample(sample("test/storage/a", t3)).await;
+            assert_eq!(storage.timestamps("a"), vec![t2, t3]);
+        });
+    }
+
+    #[test]
+    fn history_window() {
+        async_std::task::block_on(async {
+            let storage = HistoryStorage::default();
+            let window = Duration::from_secs(3600);
+            let service =
+                storage_service(storage.clone(), Some(HistoryPolicy::Window(window))).await;
+            let old = Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap();
+
+            // The values older than the window aren't inserted
+            service.process_sample(sample("test/storage/a", old)).await;
+            assert!(storage.timestamps("a").is_empty());
+            let recent = new_reception_timestamp();
+            service
+                .process_sample(sample("test/storage/a", recent))
+                .await;
+            assert_eq!(storage.timestamps("a"), vec![recent]);
+
+            // And are removed by the periodic expiration once they are out of the window
+            let key = Some(OwnedKeyExpr::from_str("a").unwrap());
+            storage.0.lock().unwrap().push((key, "value".into(), old));
+            let key = Some(OwnedKeyExpr::from_str("b").unwrap());
+            storage.0.lock().unwrap().push((key, "value".into(), old));
+            let mut expiration = ExpirationEvent {
+                name: "test".into(),
+                window,
+                trim: true,
+                storage: service.storage.clone(),
+            };
+            expiration.run().await;
+            assert_eq!(storage.timestamps("a"), vec![recent]);
+            assert!(storage.timestamps("b").is_empty());
+        });
+    }
+
+    // An external source holding the `fetched` key and failing on the `error` one
+    struct Source(std::sync::atomic::AtomicUsize);
+
+    #[async_trait]
+    impl ReadThrough for Source {
+        async fn fetch(&self, key: Option<OwnedKeyExpr>) -> ZResult<Option<Value>> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            match key.as_ref().map(|k| k.as_str()) {
+                Some("fetched") => Ok(Some("external".into())),
+                Some("error") => bail!("Unreachable external source"),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    #[test]
+    fn read_through() {
+        async_std::task::block_on(async {
+            let storage = HistoryStorage::default();
+            let mut service = storage_service(storage.clone(), None).await;
+            let source = Arc::new(Source(std::sync::atomic::AtomicUsize::new(0)));
+            service.read_through = Some(source.clone());
+            let key = |k: &str| {
+                (
+                    KeyExpr::try_from(format!("test/storage/{k}")).unwrap(),
+                    Some(OwnedKeyExpr::from_str(k).unwrap()),
+                )
+            };
+
+            // A key fetched from the external source is cached in the storage
+            let (key_expr, stripped_key) = key("fetched");
+            let fetched = service.read_through(&key_expr, stripped_key).await.unwrap();
+            assert_eq!(fetched.len(), 1);
+            assert_eq!(fetched[0].value.to_string(), "external");
+            assert_eq!(storage.timestamps("fetched"), vec![fetched[0].timestamp]);
+
+            // A key missing from the external source isn't cached
+            let (key_expr, stripped_key) = key("missing");
+            let fetched = service.read_through(&key_expr, stripped_key).await.unwrap();
+            assert!(fetched.is_empty());
+            assert!(storage.timestamps("missing").is_empty());
+
+            // The errors of the external source are reported
+            let (key_expr, stripped_key) = key("error");
+            assert!(service.read_through(&key_expr, stripped_key).await.is_err());
+            assert!(storage.timestamps("error").is_empty());
+            assert_eq!(source.0.load(std::sync::atomic::Ordering::SeqCst), 3);
+        });
+    }
+}
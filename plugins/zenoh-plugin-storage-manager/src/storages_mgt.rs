@@ -20,6 +20,7 @@ pub use super::replica::{Replica, StorageService};
 
 pub enum StorageMessage {
     Stop,
+    Flush,
     GetStatus(async_std::channel::Sender<serde_json::Value>),
 }
 
@@ -26,6 +26,56 @@ pub struct PluginsManager<StartArgs, RunningPlugin> {
     loader: Option<LibLoader>,
     plugin_starters: Vec<Box<dyn PluginStarter<StartArgs, RunningPlugin> + Send + Sync>>,
     running_plugins: HashMap<String, (String, RunningPlugin)>,
+    failed_plugins: HashMap<String, PluginFailure>,
+}
+
+/// The last failure of a plugin to start.
+#[derive(Debug, Clone)]
+pub struct PluginFailure {
+    /// The path the plugin was loaded from.
+    pub path: String,
+    /// The reason why the plugin failed to start.
+    pub error: String,
+    /// The number of consecutive failed attempts at starting the plugin.
+    pub attempts: usize,
+}
+
+impl PluginFailure {
+    fn record(
+        failed_plugins: &mut HashMap<String, PluginFailure>,
+        name: &str,
+        path: &str,
+        error: String,
+    ) {
+        let failure = failed_plugins
+            .entry(name.into())
+            .or_insert_with(|| PluginFailure {
+                path: path.into(),
+                error: String::new(),
+                attempts: 0,
+            });
+        failure.path = path.into();
+        failure.error = error;
+        failure.attempts += 1;
+    }
+}
+
+/// Starts the plugin, turning a panic during its start into an error.
+fn start_guarded<StartArgs, RunningPlugin>(
+    starter: &(dyn PluginStarter<StartArgs, RunningPlugin> + Send + Sync),
+    args: &StartArgs,
+) -> ZResult<RunningPlugin> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| starter.start(args))) {
+        Ok(result) => result,
+        Err(e) => match e
+            .downcast_ref::<String>()
+            .map(|s| s.as_str())
+            .or_else(|| e.downcast_ref::<&str>().copied())
+        {
+            Some(e) => bail!("Plugin {} panicked while starting: {}", starter.name(), e),
+            None => bail!("Plugin {} panicked while starting", starter.name()),
+        },
+    }
 }
 
 impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, RunningPlugin> {
@@ -35,6 +85,7 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
             loader: Some(loader),
             plugin_starters: Vec::new(),
             running_plugins: HashMap::new(),
+            failed_plugins: HashMap::new(),
         }
     }
     /// Constructs a new plugin manager with dynamic library loading enabled.
@@ -43,6 +94,7 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
             loader: None,
             plugin_starters: Vec::new(),
             running_plugins: HashMap::new(),
+            failed_plugins: HashMap::new(),
         }
     }
 
@@ -52,16 +104,73 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
     >(
         mut self,
     ) -> Self {
-        let plugin_starter: StaticPlugin<P> = StaticPlugin::new();
-        self.plugin_starters.push(Box::new(plugin_starter));
+        self.add_static_plugin::<P>();
         self
     }
 
+    /// Adds a statically linked plugin to the manager, unless a plugin by the same name was added already.
+    pub fn add_static_plugin<
+        P: Plugin<StartArgs = StartArgs, RunningPlugin = RunningPlugin> + Send + Sync,
+    >(
+        &mut self,
+    ) {
+        if !self
+            .plugin_starters
+            .iter()
+            .any(|s| s.name() == P::STATIC_NAME)
+        {
+            let plugin_starter: StaticPlugin<P> = StaticPlugin::new();
+            self.plugin_starters.push(Box::new(plugin_starter));
+        }
+    }
+
+    /// Moves the plugins of `other` to this manager, along with its library loader if this manager has none.
+    ///
+    /// The plugins of `other` named like a plugin of this manager are dropped, stopping them if they were running,
+    /// unless only the one of `other` is running: it then replaces the one of this manager.
+    pub fn extend(&mut self, other: Self) {
+        let PluginsManager {
+            loader,
+            plugin_starters,
+            mut running_plugins,
+            mut failed_plugins,
+        } = other;
+        if self.loader.is_none() {
+            self.loader = loader;
+        }
+        for starter in plugin_starters {
+            let name = starter.name().to_string();
+            // The running plugin is dropped before the library of its starter
+            let running = running_plugins.remove(&name);
+            let failure = failed_plugins.remove(&name);
+            if let Some(i) = self.plugin_starters.iter().position(|s| s.name() == name) {
+                if running.is_none() || self.running_plugins.contains_key(&name) {
+                    log::warn!(
+                        "Plugin `{}` (from {}) is already registered, dropping it",
+                        name,
+                        starter.path()
+                    );
+                    drop(running);
+                    continue;
+                }
+                self.plugin_starters.remove(i);
+                self.failed_plugins.remove(&name);
+            }
+            if let Some(running) = running {
+                self.running_plugins.insert(name.clone(), running);
+            }
+            if let Some(failure) = failure {
+                self.failed_plugins.insert(name, failure);
+            }
+            self.plugin_starters.push(starter);
+        }
+    }
+
     /// Starts `plugin`.
     ///
     /// `Ok(true)` => plugin was successfully started  
     /// `Ok(false)` => plugin was running already, nothing happened  
-    /// `Err(e)` => starting the plugin failed due to `e`
+    /// `Err(e)` => starting the plugin failed due to `e`, which is then reported by [`Self::failed_plugins`]
     pub fn start(
         &mut self,
         plugin: &str,
@@ -69,16 +178,30 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
     ) -> ZResult<Option<(&str, &RunningPlugin)>> {
         match self.running_plugins.entry(plugin.into()) {
             Entry::Occupied(_) => Ok(None),
-            Entry::Vacant(e) => {
-                match self.plugin_starters.iter().find(|p| p.name() == plugin) {
-                    Some(s) => {
-                        let path = s.path();
-                        let (_, plugin) = e.insert((path.into(), s.start(args).map_err(|e| zerror!(e => "Failed to load plugin {} (from {})", plugin, path))?));
-                        Ok(Some((path, &*plugin)))
+            Entry::Vacant(e) => match self.plugin_starters.iter().find(|p| p.name() == plugin) {
+                Some(s) => {
+                    let path = s.path();
+                    match start_guarded(&**s, args) {
+                        Ok(running) => {
+                            self.failed_plugins.remove(plugin);
+                            let (_, running) = e.insert((path.into(), running));
+                            Ok(Some((path, &*running)))
+                        }
+                        Err(err) => {
+                            let err =
+                                zerror!(err => "Failed to load plugin {} (from {})", plugin, path);
+                            PluginFailure::record(
+                                &mut self.failed_plugins,
+                                plugin,
+                                path,
+                                err.to_string(),
+                            );
+                            Err(err.into())
+                        }
                     }
-                    None => bail!("Plugin starter for `{}` not found", plugin),
                 }
-            }
+                None => bail!("Plugin starter for `{}` not found", plugin),
+            },
         }
     }
 
@@ -94,6 +217,7 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
         let PluginsManager {
             plugin_starters,
             running_plugins,
+            failed_plugins,
             ..
         } = self;
         let compat = crate::Compatibility::new().unwrap();
@@ -117,14 +241,20 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
                             Some(Err(e)) => Err(zerror!(e => "Plugin {} (from {}) compatibility couldn't be recovered. This likely means it's very broken.", name, path)),
                             None => Ok(()),
                         };
-                        if let Err(e) = compatible {
-                            Err(e.into())
-                        } else {
-                            match p.start(args) {
-                                Ok(p) => Ok(Some(unsafe {
+                        let started = match compatible {
+                            Err(e) => Err(e.into()),
+                            Ok(()) => start_guarded(&**p, args),
+                        };
+                        match started {
+                            Ok(p) => {
+                                failed_plugins.remove(name);
+                                Ok(Some(unsafe {
                                     std::mem::transmute(&e.insert((path.into(), p)).1)
-                                })),
-                                Err(e) => Err(e),
+                                }))
+                            }
+                            Err(e) => {
+                                PluginFailure::record(failed_plugins, name, path, e.to_string());
+                                Err(e)
                             }
                         }
                     }
@@ -136,6 +266,7 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
     /// Stops `plugin`, returning `true` if it was indeed running.
     pub fn stop(&mut self, plugin: &str) -> bool {
         let result = self.running_plugins.remove(plugin).is_some();
+        self.failed_plugins.remove(plugin);
         self.plugin_starters
             .retain(|p| p.name() != plugin || !p.deletable());
         result
@@ -162,6 +293,23 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
             .iter()
             .map(|(s, (path, p))| (s.as_str(), (path.as_str(), p)))
     }
+    /// Stops `plugin` after it crashed or exited while running, reporting `error` in [`Self::failed_plugins`].
+    ///
+    /// Unlike [`Self::stop`], the plugin remains loaded so that it may be started again.
+    /// Returns `true` if it was indeed running.
+    pub fn fail(&mut self, plugin: &str, error: String) -> bool {
+        match self.running_plugins.remove(plugin) {
+            Some((path, _)) => {
+                PluginFailure::record(&mut self.failed_plugins, plugin, &path, error);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Returns an iterator over each plugin which failed to start and isn't running, where the keys are their name.
+    pub fn failed_plugins(&self) -> impl Iterator<Item = (&str, &PluginFailure)> {
+        self.failed_plugins.iter().map(|(s, f)| (s.as_str(), f))
+    }
     /// Returns the handle of the requested running plugin if available.
     pub fn plugin(&self, name: &str) -> Option<&RunningPlugin> {
         self.running_plugins.get(name).map(|p| &p.1)
@@ -0,0 +1,83 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use criterion::{criterion_group, BenchmarkId, Criterion, Throughput};
+use zenoh_bench::PAYLOAD_SIZES;
+use zenoh_buffers::ZBuf;
+use zenoh_protocol::{
+    core::{Channel, CongestionControl, Priority, Reliability},
+    defaults::BATCH_SIZE,
+    zenoh::ZenohMessage,
+};
+use zenoh_transport::BenchPipeline;
+
+// The priorities of the successive messages, cycled through
+const PRIORITY_MIXES: [(&str, &[Priority]); 2] = [
+    ("data", &[Priority::Data]),
+    (
+        "mixed",
+        &[Priority::RealTime, Priority::Data, Priority::Background],
+    ),
+];
+
+// The messages are pushed in the transmission pipeline of the transport, serializing and batching
+// them per priority, while its batches are pulled and given back in the background as by a link
+fn bench_batching(c: &mut Criterion) {
+    let pipeline = BenchPipeline::new(BATCH_SIZE, true);
+
+    for (mix, priorities) in PRIORITY_MIXES {
+        let mut group = c.benchmark_group(format!("batching/{mix}"));
+        // Larger payloads are fragmented and never batched
+        for size in PAYLOAD_SIZES
+            .into_iter()
+            .filter(|s| *s < BATCH_SIZE as usize)
+        {
+            let messages = priorities
+                .iter()
+                .map(|priority| {
+                    ZenohMessage::make_data(
+                        1.into(),
+                        ZBuf::from(vec![0u8; size]),
+                        Channel {
+                            priority: *priority,
+                            reliability: Reliability::Reliable,
+                        },
+                        CongestionControl::Block,
+                        None,
+                        None,
+                        None,
+                        None,
+                    )
+                })
+                .collect::<Vec<_>>();
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(
+                BenchmarkId::from_parameter(size),
+                &messages,
+                |b, messages| {
+                    let mut next = messages.iter().cycle();
+                    b.iter(|| pipeline.push(next.next().unwrap().clone()))
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_batching);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    zenoh_bench::report();
+}
@@ -0,0 +1,73 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use criterion::{criterion_group, BenchmarkId, Criterion, Throughput};
+use zenoh_bench::PAYLOAD_SIZES;
+use zenoh_buffers::{reader::HasReader, writer::HasWriter, ZBuf, ZSlice};
+use zenoh_codec::{RCodec, WCodec, Zenoh060};
+use zenoh_protocol::{core::CongestionControl, zenoh::Data};
+
+fn data(size: usize) -> Data {
+    Data {
+        key: 1.into(),
+        data_info: None,
+        payload: ZBuf::from(vec![0u8; size]),
+        congestion_control: CongestionControl::default(),
+        reply_context: None,
+        unit: false,
+    }
+}
+
+fn bench_codec(c: &mut Criterion) {
+    let codec = Zenoh060::default();
+
+    let mut group = c.benchmark_group("codec/encode");
+    for size in PAYLOAD_SIZES {
+        let data = data(size);
+        let mut buff = Vec::with_capacity(size + 64);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| {
+                buff.clear();
+                let mut writer = buff.writer();
+                codec.write(&mut writer, data).unwrap();
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("codec/decode");
+    for size in PAYLOAD_SIZES {
+        let mut buff = vec![];
+        let mut writer = buff.writer();
+        codec.write(&mut writer, &data(size)).unwrap();
+        let zslice = ZSlice::from(buff);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &zslice, |b, zslice| {
+            b.iter(|| {
+                let mut zslice = zslice.clone();
+                let mut reader = zslice.reader();
+                let _: Data = codec.read(&mut reader).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_codec);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    zenoh_bench::report();
+}
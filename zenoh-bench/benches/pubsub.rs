@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use criterion::{criterion_group, BenchmarkId, Criterion, Throughput};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use zenoh::prelude::sync::*;
+use zenoh_bench::PAYLOAD_SIZES;
+
+const ENDPOINT: &str = "tcp/127.0.0.1:17460";
+const KEY_EXPR: &str = "bench/pubsub";
+const SLEEP: Duration = Duration::from_secs(1);
+
+// The priorities of the successive publications, cycled through
+const PRIORITY_MIXES: [(&str, &[Priority]); 2] = [
+    ("data", &[Priority::Data]),
+    (
+        "mixed",
+        &[Priority::RealTime, Priority::Data, Priority::Background],
+    ),
+];
+
+fn open_peers() -> (Session, Session) {
+    let mut config = config::peer();
+    config.listen.endpoints = vec![ENDPOINT.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer01 = zenoh::open(config).res_sync().unwrap();
+
+    let mut config = config::peer();
+    config.connect.endpoints = vec![ENDPOINT.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer02 = zenoh::open(config).res_sync().unwrap();
+
+    (peer01, peer02)
+}
+
+fn bench_pubsub(c: &mut Criterion) {
+    let (peer01, peer02) = open_peers();
+
+    let received = Arc::new(AtomicUsize::new(0));
+    let c_received = received.clone();
+    let _sub = peer01
+        .declare_subscriber(KEY_EXPR)
+        .callback(move |_| {
+            c_received.fetch_add(1, Ordering::Release);
+        })
+        .res_sync()
+        .unwrap();
+
+    for (mix, priorities) in PRIORITY_MIXES {
+        let publishers = priorities
+            .iter()
+            .map(|priority| {
+                peer02
+                    .declare_publisher(KEY_EXPR)
+                    .priority(*priority)
+                    .congestion_control(CongestionControl::Block)
+                    .res_sync()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        // Wait for the declarations to propagate
+        thread::sleep(SLEEP);
+
+        let mut group = c.benchmark_group(format!("pubsub/{mix}"));
+        for size in PAYLOAD_SIZES {
+            let value = Value::from(vec![0u8; size]);
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &value, |b, value| {
+                b.iter_custom(|iters| {
+                    received.store(0, Ordering::Release);
+                    let start = Instant::now();
+                    for (_, publisher) in (0..iters).zip(publishers.iter().cycle()) {
+                        publisher.put(value.clone()).res_sync().unwrap();
+                    }
+                    while received.load(Ordering::Acquire) < iters as usize {
+                        thread::yield_now();
+                    }
+                    start.elapsed()
+                })
+            });
+        }
+        group.finish();
+    }
+
+    peer02.close().res_sync().unwrap();
+    peer01.close().res_sync().unwrap();
+}
+
+criterion_group!(benches, bench_pubsub);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    zenoh_bench::report();
+}
@@ -0,0 +1,83 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use criterion::{criterion_group, BenchmarkId, Criterion, Throughput};
+use std::thread;
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh_bench::PAYLOAD_SIZES;
+
+const ENDPOINT: &str = "tcp/127.0.0.1:17461";
+const KEY_EXPR: &str = "bench/query";
+const SLEEP: Duration = Duration::from_secs(1);
+
+fn open_peers() -> (Session, Session) {
+    let mut config = config::peer();
+    config.listen.endpoints = vec![ENDPOINT.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer01 = zenoh::open(config).res_sync().unwrap();
+
+    let mut config = config::peer();
+    config.connect.endpoints = vec![ENDPOINT.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer02 = zenoh::open(config).res_sync().unwrap();
+
+    (peer01, peer02)
+}
+
+fn bench_query(c: &mut Criterion) {
+    let (peer01, peer02) = open_peers();
+
+    let mut group = c.benchmark_group("query");
+    for size in PAYLOAD_SIZES {
+        // The queryable replies with a payload of the benchmarked size
+        let value = Value::from(vec![0u8; size]);
+        let queryable = peer01
+            .declare_queryable(KEY_EXPR)
+            .callback(move |query| {
+                let sample = Sample::new(KeyExpr::try_from(KEY_EXPR).unwrap(), value.clone());
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_sync()
+            .unwrap();
+        // Wait for the declaration to propagate
+        thread::sleep(SLEEP);
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| {
+                let replies = peer02.get(KEY_EXPR).res_sync().unwrap();
+                let mut count = 0;
+                while let Ok(reply) = replies.recv() {
+                    reply.sample.unwrap();
+                    count += 1;
+                }
+                assert_eq!(count, 1);
+            })
+        });
+
+        queryable.undeclare().res_sync().unwrap();
+    }
+    group.finish();
+
+    peer02.close().res_sync().unwrap();
+    peer01.close().res_sync().unwrap();
+}
+
+criterion_group!(benches, bench_query);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    zenoh_bench::report();
+}
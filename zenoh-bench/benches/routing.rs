@@ -0,0 +1,96 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use criterion::{criterion_group, BenchmarkId, Criterion};
+use std::thread;
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+
+const ENDPOINT: &str = "tcp/127.0.0.1:17462";
+const SLEEP: Duration = Duration::from_secs(1);
+// The numbers of subscriptions declared in the routing tables of the publishing peer
+const SUBSCRIPTIONS: [usize; 3] = [1_000, 10_000, 100_000];
+// The number of distinct key expressions the publications cycle through
+const KEYS: usize = 100;
+
+fn open_peers() -> (Session, Session) {
+    let mut config = config::peer();
+    config.listen.endpoints = vec![ENDPOINT.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer01 = zenoh::open(config).res_sync().unwrap();
+
+    let mut config = config::peer();
+    config.connect.endpoints = vec![ENDPOINT.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer02 = zenoh::open(config).res_sync().unwrap();
+
+    (peer01, peer02)
+}
+
+// Publications on key expressions for which the publishing peer has no resource, matching
+// none of the many subscriptions of the other peer: their cost is the one of routing them.
+//
+// The routing tables only visit the children of a resource whose chunk is the same as the one
+// routed or wild: the `routing` group, whose subscriptions are not wild, is expected to stay flat
+// with the number of subscriptions, while the `routing_wild` group, whose subscriptions are all
+// wild and visited, grows with it.
+fn bench_routing_group(c: &mut Criterion, name: &str, subscription: fn(usize) -> String) {
+    let (peer01, peer02) = open_peers();
+
+    let keys = (0..KEYS)
+        .map(|i| KeyExpr::try_from(format!("bench/{name}/{i}/undeclared")).unwrap())
+        .collect::<Vec<_>>();
+    let mut subscribers = vec![];
+    let mut group = c.benchmark_group(name);
+    for count in SUBSCRIPTIONS {
+        for i in subscribers.len()..count {
+            let subscriber = peer01
+                .declare_subscriber(format!("bench/{name}/{}", subscription(i)))
+                .callback(|_| {})
+                .res_sync()
+                .unwrap();
+            subscribers.push(subscriber);
+        }
+        // Wait for the declarations to propagate
+        thread::sleep(SLEEP * (1 + count / 10_000) as u32);
+
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            let mut keys = keys.iter().cycle();
+            b.iter(|| {
+                peer02
+                    .put(keys.next().unwrap(), vec![0u8; 8])
+                    .res_sync()
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+
+    drop(subscribers);
+    peer02.close().res_sync().unwrap();
+    peer01.close().res_sync().unwrap();
+}
+
+fn bench_routing(c: &mut Criterion) {
+    bench_routing_group(c, "routing", |i| i.to_string());
+    // The wild chunks never match the publications, ending with a distinct chunk
+    bench_routing_group(c, "routing_wild", |i| format!("{i}$*/none"));
+}
+
+criterion_group!(benches, bench_routing);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    zenoh_bench::report();
+}
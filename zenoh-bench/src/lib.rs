@@ -0,0 +1,98 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Benchmarks of zenoh: codec, tx batching, loopback publications, query round-trips and routing.
+//!
+//! Run them with `cargo bench -p zenoh-bench`. With the `json` feature, a summary of all the
+//! results is written once a benchmark has run, to the path given by the `ZENOH_BENCH_JSON`
+//! environment variable or to `target/zenoh-bench.json`.
+use std::path::PathBuf;
+
+/// The payload sizes of the benchmarks, in bytes.
+pub const PAYLOAD_SIZES: [usize; 4] = [8, 1_024, 65_536, 1_048_576];
+
+fn target_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target"))
+}
+
+/// The directory where criterion writes the results.
+pub fn criterion_dir() -> PathBuf {
+    std::env::var_os("CRITERION_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| target_dir().join("criterion"))
+}
+
+/// Writes the summary of the results in JSON if the `json` feature is enabled.
+pub fn report() {
+    #[cfg(feature = "json")]
+    {
+        let output = std::env::var_os("ZENOH_BENCH_JSON")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| target_dir().join("zenoh-bench.json"));
+        match json::dump(&criterion_dir(), &output) {
+            Ok(()) => println!("Results summary written to {}", output.display()),
+            Err(e) => eprintln!(
+                "Unable to write results summary to {}: {}",
+                output.display(),
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use serde_json::{json, Value};
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    fn read(path: &Path) -> io::Result<Value> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    // Each benchmark has its latest results in a `new` directory
+    fn collect(dir: &Path, results: &mut Vec<Value>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_dir() || path.ends_with("report") {
+                continue;
+            }
+            let new = path.join("new");
+            if new.join("benchmark.json").is_file() && new.join("estimates.json").is_file() {
+                let benchmark = read(&new.join("benchmark.json"))?;
+                let estimates = read(&new.join("estimates.json"))?;
+                results.push(json!({
+                    "id": benchmark["full_id"],
+                    "throughput": benchmark["throughput"],
+                    "mean_ns": estimates["mean"]["point_estimate"],
+                    "median_ns": estimates["median"]["point_estimate"],
+                    "std_dev_ns": estimates["std_dev"]["point_estimate"],
+                }));
+            } else {
+                collect(&path, results)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn dump(criterion: &Path, output: &Path) -> io::Result<()> {
+        let mut results = vec![];
+        collect(criterion, &mut results)?;
+        results.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+        fs::write(output, serde_json::to_vec_pretty(&results)?)
+    }
+}
@@ -28,7 +28,7 @@ use zenoh_protocol::{
     core::{Encoding, KnownEncoding, SampleKind, WireExpr},
     zenoh::{DataInfo, ZenohMessage},
 };
-use zenoh_transport::{TransportEventHandler, TransportPeerEventHandler};
+use zenoh_transport::{ConnectFailure, TransportEventHandler, TransportPeerEventHandler};
 
 macro_rules! ke_for_sure {
     ($val:expr) => {
@@ -41,6 +41,7 @@ lazy_static::lazy_static!(
     static ref KE_PREFIX: &'static keyexpr = ke_for_sure!("@/session");
     static ref KE_TRANSPORT_UNICAST: &'static keyexpr = ke_for_sure!("transport/unicast");
     static ref KE_LINK: &'static keyexpr = ke_for_sure!("link");
+    static ref KE_CONNECT_FAILURE: &'static keyexpr = ke_for_sure!("connect/failure");
 );
 
 pub(crate) fn init(session: &Session) {
@@ -52,6 +53,7 @@ pub(crate) fn init(session: &Session) {
         let _admin_qabl = session.declare_queryable_inner(
             &admin_key,
             true,
+            false,
             Locality::SessionLocal,
             Arc::new({
                 let session = session.clone();
@@ -147,6 +149,22 @@ impl TransportEventHandler for Handler {
     ) -> ZResult<Arc<dyn zenoh_transport::TransportMulticastEventHandler>> {
         bail!("unimplemented")
     }
+
+    fn connect_failed(&self, failure: &ConnectFailure) {
+        if let Ok(own_zid) = keyexpr::new(&self.session.zid().to_string()) {
+            let expr = WireExpr::from(&(*KE_PREFIX / own_zid / *KE_CONNECT_FAILURE)).to_owned();
+            let info = DataInfo {
+                encoding: Some(Encoding::Exact(KnownEncoding::AppJson)),
+                ..Default::default()
+            };
+            self.session.handle_data(
+                true,
+                &expr,
+                Some(info),
+                serde_json::to_vec(failure).unwrap().into(),
+            );
+        }
+    }
 }
 
 pub(crate) struct PeerHandler {
@@ -0,0 +1,158 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! A blocking API, for the applications without an async runtime (e.g. threaded codebases or
+//! FFI layers).
+//!
+//! Every operation of a [`SyncSession`] blocks the calling thread until it completes. The
+//! options of the operations are available through the builders of the underlying [`Session`],
+//! which a [`SyncSession`] dereferences to, resolved with [`res_sync`](SyncResolve::res_sync).
+//!
+//! # Examples
+//! ```no_run
+//! use zenoh::prelude::sync::*;
+//!
+//! let session = SyncSession::open(config::peer()).unwrap();
+//! let subscriber = session.declare_subscriber("key/expression").unwrap();
+//! session.put("key/expression", "value").unwrap();
+//! while let Ok(sample) = subscriber.recv() {
+//!     println!("Received: {}", sample);
+//! }
+//! ```
+use crate::prelude::{KeyExpr, Selector, Value};
+use crate::publication::Publisher;
+use crate::query::Reply;
+use crate::queryable::{Query, Queryable};
+use crate::sample::Sample;
+use crate::subscriber::Subscriber;
+use crate::Session;
+use std::convert::TryInto;
+use std::ops::Deref;
+use zenoh_core::SyncResolve;
+use zenoh_result::ZResult;
+
+/// A zenoh [`Session`] whose operations block until they complete.
+#[derive(Debug)]
+pub struct SyncSession(Session);
+
+impl SyncSession {
+    /// Opens a session, blocking until it is opened.
+    pub fn open<TryIntoConfig>(config: TryIntoConfig) -> ZResult<SyncSession>
+    where
+        TryIntoConfig: TryInto<crate::config::Config> + Send + 'static,
+        <TryIntoConfig as TryInto<crate::config::Config>>::Error: std::fmt::Debug,
+    {
+        crate::open(config).res_sync().map(SyncSession)
+    }
+
+    /// Returns the underlying [`Session`].
+    pub fn into_session(self) -> Session {
+        self.0
+    }
+
+    /// Closes the session, blocking until it is closed.
+    pub fn close(self) -> ZResult<()> {
+        self.0.close().res_sync()
+    }
+
+    /// Puts a value, blocking until it is sent.
+    pub fn put<'a, 'b: 'a, TryIntoKeyExpr, IntoValue>(
+        &'a self,
+        key_expr: TryIntoKeyExpr,
+        value: IntoValue,
+    ) -> ZResult<()>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        self.0.put(key_expr, value).res_sync()
+    }
+
+    /// Deletes a key expression, blocking until the deletion is sent.
+    pub fn delete<'a, 'b: 'a, TryIntoKeyExpr>(&'a self, key_expr: TryIntoKeyExpr) -> ZResult<()>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.0.delete(key_expr).res_sync()
+    }
+
+    /// Queries a selector, returning the channel receiving the replies.
+    ///
+    /// The channel is disconnected once all the replies have been received, or on timeout.
+    pub fn get<'a, 'b: 'a, IntoSelector>(
+        &'a self,
+        selector: IntoSelector,
+    ) -> ZResult<flume::Receiver<Reply>>
+    where
+        IntoSelector: TryInto<Selector<'b>>,
+        <IntoSelector as TryInto<Selector<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.0.get(selector).res_sync()
+    }
+
+    /// Declares a subscriber, whose samples are received with the blocking methods of the
+    /// returned channel (e.g. `recv`).
+    pub fn declare_subscriber<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        key_expr: TryIntoKeyExpr,
+    ) -> ZResult<Subscriber<'a, flume::Receiver<Sample>>>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.0.declare_subscriber(key_expr).res_sync()
+    }
+
+    /// Declares a queryable, whose queries are received with the blocking methods of the
+    /// returned channel (e.g. `recv`) and answered with
+    /// [`reply`](Query::reply)`(..).res_sync()`.
+    pub fn declare_queryable<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        key_expr: TryIntoKeyExpr,
+    ) -> ZResult<Queryable<'a, flume::Receiver<Query>>>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.0.declare_queryable(key_expr).res_sync()
+    }
+
+    /// Declares a publisher, whose publications are sent with
+    /// [`put`](Publisher::put)`(..).res_sync()`.
+    pub fn declare_publisher<'a, 'b: 'a, TryIntoKeyExpr>(
+        &'a self,
+        key_expr: TryIntoKeyExpr,
+    ) -> ZResult<Publisher<'a>>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.0.declare_publisher(key_expr).res_sync()
+    }
+}
+
+impl From<Session> for SyncSession {
+    fn from(session: Session) -> Self {
+        SyncSession(session)
+    }
+}
+
+impl Deref for SyncSession {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        &self.0
+    }
+}
@@ -14,6 +14,9 @@
 
 //! Callback handler trait.
 use crate::API_DATA_RECEPTION_CHANNEL_SIZE;
+use async_trait::async_trait;
+use flume::RecvTimeoutError;
+use std::time::Instant;
 
 /// An alias for `Arc<T>`.
 pub type Dyn<T> = std::sync::Arc<T>;
@@ -80,6 +83,36 @@ impl<T: Send + Sync + 'static> IntoCallbackReceiverPair<'static, T>
     }
 }
 
+/// Asynchronous receptions with a deadline.
+///
+/// This is the asynchronous counterpart of [`flume::Receiver::recv_deadline`], e.g. to wait
+/// for the next [`Reply`](crate::query::Reply) of a [`get`](crate::Session::get) without
+/// blocking past a given instant.
+#[async_trait]
+pub trait RecvDeadlineAsync<T> {
+    /// Waits for a value until `deadline`.
+    ///
+    /// Returns [`RecvTimeoutError::Disconnected`] once all the senders are dropped and the
+    /// channel is empty, e.g. when all the replies to a query have been received.
+    async fn recv_deadline_async(&self, deadline: Instant) -> Result<T, RecvTimeoutError>;
+}
+
+#[async_trait]
+impl<T: Send> RecvDeadlineAsync<T> for flume::Receiver<T> {
+    async fn recv_deadline_async(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match async_std::future::timeout(timeout, self.recv_async()).await {
+            Ok(Ok(t)) => Ok(t),
+            Ok(Err(_)) => Err(RecvTimeoutError::Disconnected),
+            // A value may have been sent concurrently with the expiration of the deadline
+            Err(_) => self.try_recv().map_err(|e| match e {
+                flume::TryRecvError::Empty => RecvTimeoutError::Timeout,
+                flume::TryRecvError::Disconnected => RecvTimeoutError::Disconnected,
+            }),
+        }
+    }
+}
+
 /// A function that can transform a [`FnMut`]`(T)` to
 /// a [`Fn`]`(T)` with the help of a [`Mutex`](std::sync::Mutex).
 pub fn locked<T>(fnmut: impl FnMut(T)) -> impl Fn(T) {
@@ -15,8 +15,12 @@
 //! Tools to access information about the current zenoh [`Session`](crate::Session).
 use crate::SessionRef;
 use std::future::Ready;
-use zenoh_config::{WhatAmI, ZenohId};
+use zenoh_config::{Locator, WhatAmI, ZenohId};
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+#[cfg(feature = "stats")]
+pub use zenoh_transport::{
+    AnomalyStats, DirectionStats, MessageStats, PriorityStats, TransportStats,
+};
 
 /// A builder retuned by [`SessionInfo::zid()`](SessionInfo::zid) that allows
 /// to access the [`ZenohId`] of the current zenoh [`Session`](crate::Session).
@@ -148,6 +152,180 @@ impl<'a> AsyncResolve for PeersZidBuilder<'a> {
     }
 }
 
+/// A builder retuned by [`SessionInfo::mode()`](SessionInfo::mode) that allows
+/// to access the mode ([`WhatAmI`]) of the current zenoh [`Session`](crate::Session).
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let mode = session.info().mode().res().await;
+/// # })
+/// ```
+pub struct ModeBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for ModeBuilder<'a> {
+    type To = WhatAmI;
+}
+
+impl<'a> SyncResolve for ModeBuilder<'a> {
+    fn res_sync(self) -> Self::To {
+        self.session.runtime.whatami
+    }
+}
+
+impl<'a> AsyncResolve for ModeBuilder<'a> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A zenoh router or peer the current zenoh [`Session`](crate::Session) is connected to.
+#[derive(Clone, Debug)]
+pub struct RemoteInfo {
+    pub zid: ZenohId,
+    pub whatami: WhatAmI,
+    /// The locators of the remote ends of the links the session is connected with.
+    pub locators: Vec<Locator>,
+}
+
+fn remotes(session: &SessionRef<'_>, whatami: WhatAmI) -> Vec<RemoteInfo> {
+    session
+        .runtime
+        .manager()
+        .get_transports()
+        .into_iter()
+        .filter_map(|s| {
+            let remote = RemoteInfo {
+                zid: s.get_zid().ok()?,
+                whatami: s.get_whatami().ok()?,
+                locators: s.get_links().ok()?.into_iter().map(|l| l.dst).collect(),
+            };
+            (remote.whatami == whatami).then_some(remote)
+        })
+        .collect()
+}
+
+/// A builder retuned by [`SessionInfo::routers()`](SessionInfo::routers) that allows
+/// to access the [`RemoteInfo`] of the zenoh routers this process is currently connected to.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// for router in session.info().routers().res().await {
+///     println!("{} at {:?}", router.zid, router.locators);
+/// }
+/// # })
+/// ```
+pub struct RoutersBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for RoutersBuilder<'a> {
+    type To = Box<dyn Iterator<Item = RemoteInfo> + Send + Sync>;
+}
+
+impl<'a> SyncResolve for RoutersBuilder<'a> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        Box::new(remotes(&self.session, WhatAmI::Router).into_iter())
+    }
+}
+
+impl<'a> AsyncResolve for RoutersBuilder<'a> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A builder retuned by [`SessionInfo::peers()`](SessionInfo::peers) that allows
+/// to access the [`RemoteInfo`] of the zenoh peers this process is currently connected to.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// for peer in session.info().peers().res().await {
+///     println!("{} at {:?}", peer.zid, peer.locators);
+/// }
+/// # })
+/// ```
+pub struct PeersBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for PeersBuilder<'a> {
+    type To = Box<dyn Iterator<Item = RemoteInfo> + Send + Sync>;
+}
+
+impl<'a> SyncResolve for PeersBuilder<'a> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        Box::new(remotes(&self.session, WhatAmI::Peer).into_iter())
+    }
+}
+
+impl<'a> AsyncResolve for PeersBuilder<'a> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A builder retuned by [`SessionInfo::listeners()`](SessionInfo::listeners) that allows
+/// to access the [`Locator`]s the current zenoh [`Session`](crate::Session) is listening on.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let mut listeners = session.info().listeners().res().await;
+/// while let Some(listener) = listeners.next() {}
+/// # })
+/// ```
+pub struct ListenersBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for ListenersBuilder<'a> {
+    type To = Box<dyn Iterator<Item = Locator> + Send + Sync>;
+}
+
+impl<'a> SyncResolve for ListenersBuilder<'a> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        Box::new(
+            self.session
+                .runtime
+                .manager()
+                .get_listeners()
+                .into_iter()
+                .map(|endpoint| endpoint.to_locator()),
+        )
+    }
+}
+
+impl<'a> AsyncResolve for ListenersBuilder<'a> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
 /// Struct returned by [`Session::info()`](crate::Session::info) which allows
 /// to access informations about the current zenoh [`Session`](crate::Session).
 ///
@@ -219,4 +397,78 @@ impl SessionInfo<'_> {
             session: self.session.clone(),
         }
     }
+
+    /// Return the mode ([`WhatAmI`]) of the current zenoh [`Session`](crate::Session).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let mode = session.info().mode().res().await;
+    /// # })
+    /// ```
+    pub fn mode(&self) -> ModeBuilder<'_> {
+        ModeBuilder {
+            session: self.session.clone(),
+        }
+    }
+
+    /// Return the [`RemoteInfo`] of the zenoh routers this process is currently connected to.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let mut routers = session.info().routers().res().await;
+    /// while let Some(router) = routers.next() {}
+    /// # })
+    /// ```
+    pub fn routers(&self) -> RoutersBuilder<'_> {
+        RoutersBuilder {
+            session: self.session.clone(),
+        }
+    }
+
+    /// Return the [`RemoteInfo`] of the zenoh peers this process is currently connected to.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let mut peers = session.info().peers().res().await;
+    /// while let Some(peer) = peers.next() {}
+    /// # })
+    /// ```
+    pub fn peers(&self) -> PeersBuilder<'_> {
+        PeersBuilder {
+            session: self.session.clone(),
+        }
+    }
+
+    /// Return the [`Locator`]s the current zenoh [`Session`](crate::Session) is listening on.
+    ///
+    /// The locators carry the address the listeners are actually bound to, e.g. the port
+    /// assigned by the system to the endpoints listening on port `0`.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let mut listeners = session.info().listeners().res().await;
+    /// while let Some(listener) = listeners.next() {}
+    /// # })
+    /// ```
+    pub fn listeners(&self) -> ListenersBuilder<'_> {
+        ListenersBuilder {
+            session: self.session.clone(),
+        }
+    }
 }
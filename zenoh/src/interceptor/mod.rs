@@ -0,0 +1,284 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Data-plane interceptors.
+//!
+//! An [`Interceptor`] inspects the messages exchanged with a remote node, and may modify or drop
+//! them: ingress interceptors are applied on the messages received from the node before they are
+//! routed, egress interceptors on the messages sent to the node once routed.
+//!
+//! Interceptors are configured in the `routing/interceptors` section of the configuration, and
+//! created for each session by the [`InterceptorFactory`] registered under their name, e.g. by a
+//! plugin with [`register_factory`]. They are applied in the order of the configuration.
+//!
+//! The [`KeyExprRewriter`] is built-in, registered under the name `keyexpr_rewrite`.
+use crate::config::{Config, InterceptorConf};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use zenoh_link::Link;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_result::{zerror, ZResult};
+use zenoh_transport::EgressFilter;
+
+mod rewrite;
+pub use crate::config::InterceptorFlow;
+pub use rewrite::KeyExprRewriter;
+pub use zenoh_protocol::zenoh::ZenohMessage;
+
+/// The remote node of the session an interceptor is created for.
+#[derive(Clone, Debug)]
+pub struct InterceptorContext {
+    pub zid: ZenohId,
+    pub whatami: WhatAmI,
+}
+
+/// A hook applied on the messages exchanged with a remote node.
+pub trait Interceptor: Send + Sync {
+    /// Returns the message to forward, possibly modified, or `None` to drop it.
+    fn intercept(&self, flow: InterceptorFlow, msg: ZenohMessage) -> Option<ZenohMessage>;
+}
+
+/// Creates the [`Interceptor`] of each session from its configuration.
+pub trait InterceptorFactory: Send + Sync {
+    fn make(
+        &self,
+        context: &InterceptorContext,
+        config: &serde_json::Value,
+    ) -> ZResult<Arc<dyn Interceptor>>;
+}
+
+lazy_static::lazy_static! {
+    static ref FACTORIES: RwLock<HashMap<String, Arc<dyn InterceptorFactory>>> = {
+        let mut factories: HashMap<String, Arc<dyn InterceptorFactory>> = HashMap::new();
+        factories.insert(KeyExprRewriter::NAME.to_string(), Arc::new(rewrite::Factory));
+        RwLock::new(factories)
+    };
+}
+
+/// Registers `factory` under `name`, replacing the factory previously registered under it.
+///
+/// Only the sessions opened afterwards are intercepted.
+pub fn register_factory(name: &str, factory: Arc<dyn InterceptorFactory>) {
+    zwrite!(FACTORIES).insert(name.to_string(), factory);
+}
+
+/// Returns `true` if one of `links` has a local address on one of `interfaces`.
+fn on_interfaces(links: &[Link], interfaces: &[String]) -> bool {
+    let addrs: Vec<_> = interfaces
+        .iter()
+        .filter_map(|name| zenoh_util::net::get_unicast_addresses_of_interface(name).ok())
+        .flatten()
+        .collect();
+    links.iter().any(|link| {
+        let src = link.src.address();
+        src.as_str()
+            .parse::<SocketAddr>()
+            .map_or(false, |src| addrs.contains(&src.ip()))
+    })
+}
+
+fn apply(
+    interceptors: &[Arc<dyn Interceptor>],
+    flow: InterceptorFlow,
+    msg: ZenohMessage,
+) -> Option<ZenohMessage> {
+    interceptors
+        .iter()
+        .try_fold(msg, |msg, interceptor| interceptor.intercept(flow, msg))
+}
+
+/// The interceptors configured on a runtime.
+#[derive(Default)]
+pub(crate) struct Interceptors {
+    confs: Vec<InterceptorConf>,
+}
+
+impl Interceptors {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        Interceptors {
+            confs: config.routing().interceptors().clone(),
+        }
+    }
+
+    /// Creates the interceptors of a session with the remote node `context` connected with `links`.
+    ///
+    /// Fails if the factory of one of the applicable interceptors isn't registered or fails.
+    pub(crate) fn chain(
+        &self,
+        context: &InterceptorContext,
+        links: &[Link],
+    ) -> ZResult<InterceptorChain> {
+        let mut chain = InterceptorChain::default();
+        for conf in &self.confs {
+            if let Some(zids) = &conf.zids {
+                if !zids.contains(&context.zid) {
+                    continue;
+                }
+            }
+            if let Some(interfaces) = &conf.interfaces {
+                if !on_interfaces(links, interfaces) {
+                    continue;
+                }
+            }
+            let factory = zread!(FACTORIES)
+                .get(&conf.name)
+                .cloned()
+                .ok_or_else(|| zerror!("Interceptor `{}`: factory not registered", conf.name))?;
+            let interceptor = factory
+                .make(context, &conf.config)
+                .map_err(|e| zerror!("Interceptor `{}`: {}", conf.name, e))?;
+            let applies = |flow| conf.flows.as_ref().map_or(true, |f| f.contains(&flow));
+            if applies(InterceptorFlow::Ingress) {
+                chain.ingress.push(interceptor.clone());
+            }
+            if applies(InterceptorFlow::Egress) {
+                chain.egress.push(interceptor);
+            }
+        }
+        Ok(chain)
+    }
+}
+
+/// The interceptors of a session, in configuration order.
+#[derive(Default)]
+pub(crate) struct InterceptorChain {
+    ingress: Vec<Arc<dyn Interceptor>>,
+    egress: Vec<Arc<dyn Interceptor>>,
+}
+
+impl InterceptorChain {
+    #[inline]
+    pub(crate) fn ingress(&self, msg: ZenohMessage) -> Option<ZenohMessage> {
+        apply(&self.ingress, InterceptorFlow::Ingress, msg)
+    }
+
+    /// Returns the filter applying the egress interceptors, if any.
+    pub(crate) fn egress_filter(&self) -> Option<EgressFilter> {
+        if self.egress.is_empty() {
+            return None;
+        }
+        let egress = self.egress.clone();
+        Some(Arc::new(move |msg| {
+            apply(&egress, InterceptorFlow::Egress, msg)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counter(Arc<AtomicUsize>);
+
+    impl Interceptor for Counter {
+        fn intercept(&self, _flow: InterceptorFlow, msg: ZenohMessage) -> Option<ZenohMessage> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Some(msg)
+        }
+    }
+
+    struct Dropper;
+
+    impl Interceptor for Dropper {
+        fn intercept(&self, _flow: InterceptorFlow, _msg: ZenohMessage) -> Option<ZenohMessage> {
+            None
+        }
+    }
+
+    struct Factory<F>(F);
+
+    impl<F: Fn() -> Arc<dyn Interceptor> + Send + Sync> InterceptorFactory for Factory<F> {
+        fn make(
+            &self,
+            _context: &InterceptorContext,
+            _config: &serde_json::Value,
+        ) -> ZResult<Arc<dyn Interceptor>> {
+            Ok((self.0)())
+        }
+    }
+
+    fn conf(name: &str, flows: Option<Vec<InterceptorFlow>>) -> InterceptorConf {
+        InterceptorConf {
+            name: name.to_string(),
+            flows,
+            interfaces: None,
+            zids: None,
+            config: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn interceptors_chain() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let c_count = count.clone();
+        register_factory(
+            "test_count",
+            Arc::new(Factory(move || -> Arc<dyn Interceptor> {
+                Arc::new(Counter(c_count.clone()))
+            })),
+        );
+        register_factory(
+            "test_drop",
+            Arc::new(Factory(|| -> Arc<dyn Interceptor> { Arc::new(Dropper) })),
+        );
+        let context = InterceptorContext {
+            zid: ZenohId::try_from([1]).unwrap(),
+            whatami: WhatAmI::Peer,
+        };
+        let msg = || ZenohMessage::make_declare(vec![], None, None);
+
+        // The interceptors are applied in order until one drops the message
+        let interceptors = Interceptors {
+            confs: vec![
+                conf("test_count", None),
+                conf("test_drop", Some(vec![InterceptorFlow::Ingress])),
+                conf("test_count", None),
+            ],
+        };
+        let chain = interceptors.chain(&context, &[]).unwrap();
+        assert!(chain.ingress(msg()).is_none());
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        let egress = chain.egress_filter().unwrap();
+        assert!(egress(msg()).is_some());
+        assert_eq!(count.load(Ordering::Relaxed), 3);
+
+        // Interceptors scoped to interfaces none of the links is on are skipped
+        let mut scoped = conf("test_drop", None);
+        scoped.interfaces = Some(vec!["no-such-interface".to_string()]);
+        let interceptors = Interceptors {
+            confs: vec![scoped],
+        };
+        let chain = interceptors.chain(&context, &[]).unwrap();
+        assert!(chain.ingress(msg()).is_some());
+        assert!(chain.egress_filter().is_none());
+
+        // Interceptors scoped to other remote nodes are skipped
+        let mut scoped = conf("test_drop", None);
+        scoped.zids = Some(vec![ZenohId::try_from([2]).unwrap()]);
+        let interceptors = Interceptors {
+            confs: vec![scoped],
+        };
+        let chain = interceptors.chain(&context, &[]).unwrap();
+        assert!(chain.ingress(msg()).is_some());
+
+        // Sessions are refused if a factory is missing
+        let interceptors = Interceptors {
+            confs: vec![conf("test_missing", None)],
+        };
+        assert!(interceptors.chain(&context, &[]).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The built-in interceptor rewriting the prefix of the key expressions.
+use super::{Interceptor, InterceptorContext, InterceptorFactory, InterceptorFlow};
+use crate::prelude::OwnedKeyExpr;
+use serde::Deserialize;
+use std::sync::Arc;
+use zenoh_protocol::core::WireExpr;
+use zenoh_protocol::zenoh::{Declaration, ZenohBody, ZenohMessage};
+use zenoh_result::{bail, zerror, ZResult};
+
+/// An [`Interceptor`] replacing the `local` prefix of the key expressions by the `remote` one
+/// in the messages sent, and the `remote` prefix by the `local` one in the messages received.
+///
+/// A missing prefix stands for the root, e.g. with a `local` prefix `site-a` and no `remote`
+/// prefix, `site-a/x` is sent as `x` and `x` is received as `site-a/x`. The rewriting in one
+/// direction is the inverse of the other: the messages and declarations whose key expression is
+/// outside of the prefix, or would become empty, are dropped rather than left untouched, as they
+/// would otherwise come back under another key expression.
+///
+/// The key expressions of the data, queries, pulls, declarations and congestion drop notifications
+/// are rewritten, those using a declared resource as scope being rewritten through the declaration
+/// of that resource: the prefix must thus not be split between a declared resource and a suffix.
+pub struct KeyExprRewriter {
+    local: Option<OwnedKeyExpr>,
+    remote: Option<OwnedKeyExpr>,
+}
+
+impl KeyExprRewriter {
+    /// The name under which the factory of this interceptor is registered.
+    pub const NAME: &'static str = "keyexpr_rewrite";
+
+    pub fn new(local: Option<OwnedKeyExpr>, remote: Option<OwnedKeyExpr>) -> ZResult<Self> {
+        if local.is_none() && remote.is_none() {
+            bail!("At least one of `local` and `remote` must be provided");
+        }
+        for prefix in local.iter().chain(remote.iter()) {
+            if prefix.contains('*') || prefix.contains('$') {
+                bail!("Prefix `{}` must not contain wildcards", prefix);
+            }
+        }
+        Ok(KeyExprRewriter { local, remote })
+    }
+
+    /// Rewrites `key`, returns `false` if it is outside of `from` and must be dropped.
+    fn rewrite(key: &mut WireExpr<'static>, from: Option<&str>, to: Option<&str>) -> bool {
+        if key.scope != 0 {
+            return true;
+        }
+        match replace_prefix(&key.suffix, from, to) {
+            Some(suffix) => {
+                key.suffix = suffix.into();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Replaces the prefix `from` of `key` by `to`, or returns `None` if `key` is outside of `from`
+/// or would become empty.
+fn replace_prefix(key: &str, from: Option<&str>, to: Option<&str>) -> Option<String> {
+    let rest = match from {
+        Some(from) => match key.strip_prefix(from)? {
+            "" => None,
+            rest => Some(rest.strip_prefix('/')?),
+        },
+        None => Some(key),
+    };
+    match (to, rest) {
+        (Some(to), Some(rest)) => Some(format!("{to}/{rest}")),
+        (Some(to), None) => Some(to.to_string()),
+        (None, Some(rest)) => Some(rest.to_string()),
+        (None, None) => None,
+    }
+}
+
+impl Interceptor for KeyExprRewriter {
+    fn intercept(&self, flow: InterceptorFlow, mut msg: ZenohMessage) -> Option<ZenohMessage> {
+        let local = self.local.as_ref().map(|k| k.as_str());
+        let remote = self.remote.as_ref().map(|k| k.as_str());
+        let (from, to) = match flow {
+            InterceptorFlow::Ingress => (remote, local),
+            InterceptorFlow::Egress => (local, remote),
+        };
+        let keep = match &mut msg.body {
+            ZenohBody::Data(data) => Self::rewrite(&mut data.key, from, to),
+            ZenohBody::Query(query) => Self::rewrite(&mut query.key, from, to),
+            ZenohBody::Pull(pull) => Self::rewrite(&mut pull.key, from, to),
+            ZenohBody::Declare(declare) => {
+                declare.declarations.retain_mut(|declaration| {
+                    let key = match declaration {
+                        Declaration::Resource(d) => &mut d.key,
+                        Declaration::Publisher(d) => &mut d.key,
+                        Declaration::ForgetPublisher(d) => &mut d.key,
+                        Declaration::Subscriber(d) => &mut d.key,
+                        Declaration::ForgetSubscriber(d) => &mut d.key,
+                        Declaration::Queryable(d) => &mut d.key,
+                        Declaration::ForgetQueryable(d) => &mut d.key,
+                        Declaration::ForgetResource(_) => return true,
+                    };
+                    Self::rewrite(key, from, to)
+                });
+                !declare.declarations.is_empty()
+            }
+            ZenohBody::CongestionDrop(drop) => match replace_prefix(&drop.key_expr, from, to) {
+                Some(key_expr) => {
+                    drop.key_expr = key_expr;
+                    true
+                }
+                None => false,
+            },
+            ZenohBody::Unit(_) | ZenohBody::LinkStateList(_) => true,
+        };
+        keep.then_some(msg)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RewriteConf {
+    #[serde(default)]
+    local: Option<OwnedKeyExpr>,
+    #[serde(default)]
+    remote: Option<OwnedKeyExpr>,
+}
+
+pub(super) struct Factory;
+
+impl InterceptorFactory for Factory {
+    fn make(
+        &self,
+        _context: &InterceptorContext,
+        config: &serde_json::Value,
+    ) -> ZResult<Arc<dyn Interceptor>> {
+        let conf: RewriteConf = serde_json::from_value(config.clone())
+            .map_err(|e| zerror!("Invalid configuration: {}", e))?;
+        Ok(Arc::new(KeyExprRewriter::new(conf.local, conf.remote)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use zenoh_protocol::core::SubInfo;
+    use zenoh_protocol::zenoh::Subscriber;
+
+    fn data(key: WireExpr<'static>) -> ZenohMessage {
+        ZenohMessage::make_data(
+            key,
+            vec![].into(),
+            Default::default(),
+            Default::default(),
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn key(msg: &ZenohMessage) -> WireExpr<'static> {
+        match &msg.body {
+            ZenohBody::Data(data) => data.key.clone(),
+            ZenohBody::Declare(declare) => match &declare.declarations[0] {
+                Declaration::Subscriber(sub) => sub.key.clone(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn keyexpr_rewrite() {
+        let site = OwnedKeyExpr::try_from("site-a").unwrap();
+        let rewriter = KeyExprRewriter::new(Some(site), None).unwrap();
+        let egress = |k: &str| {
+            rewriter
+                .intercept(InterceptorFlow::Egress, data(k.to_string().into()))
+                .map(|msg| key(&msg).suffix.to_string())
+        };
+        let ingress = |k: &str| {
+            rewriter
+                .intercept(InterceptorFlow::Ingress, data(k.to_string().into()))
+                .map(|msg| key(&msg).suffix.to_string())
+        };
+        assert_eq!(egress("site-a/demo/x").as_deref(), Some("demo/x"));
+        // Only whole chunks are stripped, the key expressions outside of the prefix or becoming
+        // empty are dropped
+        assert_eq!(egress("site-ab/demo"), None);
+        assert_eq!(egress("site-a"), None);
+        assert_eq!(egress("other/x"), None);
+        // Scoped key expressions are rewritten through the declaration of their scope
+        let scoped = WireExpr {
+            scope: 1,
+            suffix: "site-a/x".into(),
+        };
+        assert_eq!(
+            key(&rewriter
+                .intercept(InterceptorFlow::Egress, data(scoped.clone()))
+                .unwrap()),
+            scoped
+        );
+        // Each direction is the inverse of the other
+        for k in ["site-a/demo/x", "site-a/site-a", "x", "other/x", "site-ab"] {
+            if let Some(sent) = egress(k) {
+                assert_eq!(ingress(&sent).as_deref(), Some(k));
+            }
+            if let Some(received) = ingress(k) {
+                assert_eq!(egress(&received).as_deref(), Some(k));
+            }
+        }
+
+        let subscriber = ZenohMessage::make_declare(
+            vec![Declaration::Subscriber(Subscriber {
+                key: "demo/**".into(),
+                info: SubInfo::default(),
+            })],
+            None,
+            None,
+        );
+        let msg = rewriter
+            .intercept(InterceptorFlow::Ingress, subscriber)
+            .unwrap();
+        assert_eq!(key(&msg).suffix, "site-a/demo/**");
+
+        let backbone = OwnedKeyExpr::try_from("backbone").unwrap();
+        let site = OwnedKeyExpr::try_from("site-a").unwrap();
+        let rewriter = KeyExprRewriter::new(Some(site), Some(backbone)).unwrap();
+        let msg = rewriter
+            .intercept(InterceptorFlow::Ingress, data("backbone/x".into()))
+            .unwrap();
+        assert_eq!(key(&msg).suffix, "site-a/x");
+        let msg = rewriter
+            .intercept(InterceptorFlow::Egress, data("site-a".into()))
+            .unwrap();
+        assert_eq!(key(&msg).suffix, "backbone");
+        assert!(rewriter
+            .intercept(InterceptorFlow::Ingress, data("other/x".into()))
+            .is_none());
+
+        assert!(KeyExprRewriter::new(None, None).is_err());
+        assert!(KeyExprRewriter::new(Some(OwnedKeyExpr::try_from("a/*").unwrap()), None).is_err());
+    }
+}
@@ -20,6 +20,9 @@ use std::{
     str::FromStr,
 };
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+/// Maps indexed by key expressions, such as [`KeBoxTree`](keyexpr_tree::KeBoxTree), whose values can be
+/// retrieved by the key expressions intersecting or including their keys.
+pub use zenoh_keyexpr::keyexpr_tree;
 pub use zenoh_protocol::core::key_expr::*;
 use zenoh_protocol::core::{key_expr::canon::Canonizable, WireExpr};
 use zenoh_result::ZResult;
@@ -88,7 +88,7 @@ use prelude::*;
 use scouting::ScoutBuilder;
 use std::future::Ready;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
-pub use zenoh_macros::{kedefine, keformat, kewrite};
+pub use zenoh_macros::{ke, kedefine, keformat, kewrite};
 use zenoh_result::{zerror, ZResult};
 
 /// A zenoh error.
@@ -109,8 +109,12 @@ pub use net::runtime;
 pub mod selector;
 #[deprecated = "This module is now a separate crate. Use the crate directly for shorter compile-times"]
 pub use zenoh_config as config;
+/// The executor on which the tasks of zenoh are spawned, to initialize before opening a session.
+pub use zenoh_core::executor;
+pub mod blocking;
 pub mod handlers;
 pub mod info;
+pub mod interceptor;
 pub mod liveliness;
 pub mod plugins;
 pub mod prelude;
@@ -119,6 +123,7 @@ pub mod query;
 pub mod queryable;
 pub mod sample;
 pub mod subscriber;
+pub mod transform;
 pub mod value;
 #[cfg(feature = "shared-memory")]
 pub use zenoh_shm as shm;
@@ -130,9 +135,33 @@ pub use zenoh_buffers as buffers;
 /// Time related types and functions.
 pub mod time {
     use std::convert::TryFrom;
+    use std::sync::RwLock;
+    use zenoh_core::{zread, zwrite};
 
     pub use zenoh_protocol::core::{Timestamp, TimestampId, NTP64};
 
+    /// A clock source, returning the current time as an [`NTP64`] relative to the UNIX epoch.
+    pub type Clock = fn() -> NTP64;
+
+    static CLOCK: RwLock<Clock> = RwLock::new(system_clock);
+
+    /// The default [`Clock`], reading the system time.
+    pub fn system_clock() -> NTP64 {
+        uhlc::system_time_clock()
+    }
+
+    /// Sets the [`Clock`] of the HLC generating the timestamps of the runtimes opened afterwards,
+    /// e.g. to rely on a PTP-disciplined clock rather than on the system time.
+    ///
+    /// The HLC of a runtime is created only if timestamping is enabled in its configuration.
+    pub fn set_clock(clock: Clock) {
+        *zwrite!(CLOCK) = clock;
+    }
+
+    pub(crate) fn clock() -> Clock {
+        *zread!(CLOCK)
+    }
+
     /// Generates a reception [`Timestamp`] with id=0x01.  
     /// This operation should be called if a timestamp is required for an incoming [`zenoh::Sample`](crate::Sample)
     /// that doesn't contain any timestamp.
@@ -294,10 +323,21 @@ where
     }
 }
 
-/// Initialize a Session with an existing Runtime.
-/// This operation is used by the plugins to share the same Runtime as the router.
-#[doc(hidden)]
-#[zenoh_macros::unstable]
+/// Initialize a zenoh [`Session`] on an existing [`Runtime`](runtime::Runtime).
+///
+/// The sessions initialized on a same runtime share its transports and scouting, e.g. an application
+/// session and the session of an embedded bridge, or the router and its plugins.
+/// Closing such a session doesn't close the runtime, which remains open until [`Runtime::close`](runtime::Runtime::close).
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let bridge_session = zenoh::init(session.runtime().clone()).res().await.unwrap();
+/// # })
+/// ```
 pub fn init(runtime: Runtime) -> InitBuilder {
     InitBuilder {
         runtime,
@@ -307,15 +347,13 @@ pub fn init(runtime: Runtime) -> InitBuilder {
 }
 
 /// A builder returned by [`init`] and used to initialize a Session with an existing Runtime.
-#[doc(hidden)]
-#[zenoh_macros::unstable]
+#[must_use = "Resolvables do nothing unless you resolve them using the `res` method from either `SyncResolve` or `AsyncResolve`"]
 pub struct InitBuilder {
     runtime: Runtime,
     aggregated_subscribers: Vec<OwnedKeyExpr>,
     aggregated_publishers: Vec<OwnedKeyExpr>,
 }
 
-#[zenoh_macros::unstable]
 impl InitBuilder {
     #[inline]
     pub fn aggregated_subscribers(mut self, exprs: Vec<OwnedKeyExpr>) -> Self {
@@ -330,24 +368,23 @@ impl InitBuilder {
     }
 }
 
-#[zenoh_macros::unstable]
 impl Resolvable for InitBuilder {
     type To = ZResult<Session>;
 }
 
-#[zenoh_macros::unstable]
 impl SyncResolve for InitBuilder {
     fn res_sync(self) -> <Self as Resolvable>::To {
+        let transformers = transform::Transformers::from_config(&self.runtime.config.lock())?;
         Ok(Session::init(
             self.runtime,
             self.aggregated_subscribers,
             self.aggregated_publishers,
+            transformers,
         )
         .res_sync())
     }
 }
 
-#[zenoh_macros::unstable]
 impl AsyncResolve for InitBuilder {
     type Future = Ready<Self::To>;
 
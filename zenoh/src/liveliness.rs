@@ -544,6 +544,7 @@ where
                 &Some(KeyExpr::from(*KE_PREFIX_LIVELINESS)),
                 Locality::default(),
                 callback,
+                None,
                 &SubInfo::default(),
             )
             .map(|sub_state| Subscriber {
@@ -744,6 +745,8 @@ where
                 QueryTarget::default(),
                 QueryConsolidation::default(),
                 Locality::default(),
+                None,
+                Priority::default(),
                 self.timeout,
                 None,
                 callback,
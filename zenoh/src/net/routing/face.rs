@@ -14,16 +14,18 @@
 use super::router::*;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
 use zenoh_protocol::{
     core::{
-        Channel, CongestionControl, ConsolidationMode, QueryTarget, QueryableInfo, SubInfo,
-        WhatAmI, WireExpr, ZInt, ZenohId,
+        Channel, CongestionControl, ConsolidationMode, Priority, QueryTarget, QueryableInfo,
+        SubInfo, WhatAmI, WireExpr, ZInt, ZenohId,
     },
     zenoh::{DataInfo, QueryBody, RoutingContext},
 };
-use zenoh_transport::Primitives;
+use zenoh_transport::{BatchedData, Primitives};
 
 pub struct FaceState {
     pub(super) id: usize,
@@ -31,6 +33,10 @@ pub struct FaceState {
     pub(super) whatami: WhatAmI,
     pub(super) primitives: Arc<dyn Primitives + Send + Sync>,
     pub(super) link_id: usize,
+    /// Whether shared memory buffers can be sent by reference to this face, i.e. it is a local
+    /// face or its transport negotiated shared memory in the handshake.
+    #[cfg_attr(not(feature = "shared-memory"), allow(dead_code))]
+    pub(super) shm: bool,
     pub(super) local_mappings: HashMap<ZInt, Arc<Resource>>,
     pub(super) remote_mappings: HashMap<ZInt, Arc<Resource>>,
     pub(super) local_subs: HashSet<Arc<Resource>>,
@@ -39,6 +45,11 @@ pub struct FaceState {
     pub(super) remote_qabls: HashSet<Arc<Resource>>,
     pub(super) next_qid: ZInt,
     pub(super) pending_queries: HashMap<ZInt, Arc<Query>>,
+    /// Number of queries issued by this face still waiting for their final reply.
+    pub(super) queries_in_flight: AtomicUsize,
+    /// The data messages received from this face, or routed from it, and dropped because of
+    /// congestion, by key expression, not notified to it yet.
+    pub(super) congestion_drops: Arc<Mutex<HashMap<String, ZInt>>>,
 }
 
 impl FaceState {
@@ -48,6 +59,7 @@ impl FaceState {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        shm: bool,
     ) -> Arc<FaceState> {
         Arc::new(FaceState {
             id,
@@ -55,6 +67,7 @@ impl FaceState {
             whatami,
             primitives,
             link_id,
+            shm,
             local_mappings: HashMap::new(),
             remote_mappings: HashMap::new(),
             local_subs: HashSet::new(),
@@ -63,6 +76,8 @@ impl FaceState {
             remote_qabls: HashSet::new(),
             next_qid: 0,
             pending_queries: HashMap::new(),
+            queries_in_flight: AtomicUsize::new(0),
+            congestion_drops: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -152,6 +167,58 @@ impl FaceState {
     }
 }
 
+/// A subscriber or queryable of a remote node ignored while the memory used was above the
+/// threshold of the budget, declared again once it gets back below.
+pub(crate) struct ShedDeclaration {
+    face: Weak<FaceState>,
+    key_expr: WireExpr<'static>,
+    info: ShedInfo,
+    routing_context: Option<RoutingContext>,
+}
+
+enum ShedInfo {
+    Subscriber(SubInfo),
+    Queryable(QueryableInfo),
+}
+
+impl ShedDeclaration {
+    fn is(&self, face: &FaceState, key_expr: &WireExpr, subscriber: bool) -> bool {
+        self.face.upgrade().map_or(false, |f| f.id == face.id)
+            && self.key_expr == *key_expr
+            && matches!(self.info, ShedInfo::Subscriber(_)) == subscriber
+    }
+}
+
+/// Declares again the declarations shed by the faces still open.
+pub(crate) fn redeclare_shed_declarations(tables: &Arc<TablesLock>) {
+    let shed = std::mem::take(&mut *zlock!(zread!(tables.tables).shed_declarations));
+    for declaration in shed {
+        let state = match declaration.face.upgrade() {
+            Some(state) if zread!(tables.tables).faces.contains_key(&state.id) => state,
+            _ => continue,
+        };
+        log::debug!(
+            "Memory budget recovered: declare {} of {}",
+            declaration.key_expr,
+            state.zid
+        );
+        let face = Face {
+            tables: tables.clone(),
+            state,
+        };
+        match &declaration.info {
+            ShedInfo::Subscriber(sub_info) => {
+                face.decl_subscriber(&declaration.key_expr, sub_info, declaration.routing_context)
+            }
+            ShedInfo::Queryable(qabl_info) => face.decl_queryable(
+                &declaration.key_expr,
+                qabl_info,
+                declaration.routing_context,
+            ),
+        }
+    }
+}
+
 impl fmt::Display for FaceState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Face{{{}, {}}}", self.id, self.zid)
@@ -185,6 +252,29 @@ impl Primitives for Face {
     ) {
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
         let rtables = zread!(self.tables.tables);
+        if self.state.zid != rtables.zid && rtables.memory.sheds_declarations() {
+            log::warn!(
+                "Memory budget exhausted: ignore subscriber {} declared by {} until it recovers",
+                key_expr,
+                self.state.zid
+            );
+            zlock!(rtables.shed_declarations).push(ShedDeclaration {
+                face: Arc::downgrade(&self.state),
+                key_expr: key_expr.to_owned(),
+                info: ShedInfo::Subscriber(sub_info.clone()),
+                routing_context,
+            });
+            return;
+        }
+        // The latched samples are delivered by the router or peer nearest to the subscriber: the
+        // one its client subscription is declared to
+        let latched = match (rtables.whatami, self.state.whatami) {
+            (WhatAmI::Client, _) | (WhatAmI::Router, WhatAmI::Router) => false,
+            (WhatAmI::Router, WhatAmI::Peer)
+            | (WhatAmI::Peer, WhatAmI::Router)
+            | (WhatAmI::Peer, WhatAmI::Peer) => !rtables.full_net(WhatAmI::Peer),
+            _ => true,
+        };
         match (rtables.whatami, self.state.whatami) {
             (WhatAmI::Router, WhatAmI::Router) => {
                 if let Some(router) = self.state.get_router(&rtables, routing_context) {
@@ -232,12 +322,19 @@ impl Primitives for Face {
                 );
             }
         }
+        let samples = if latched {
+            latched_samples(&self.tables.tables, &self.state, key_expr)
+        } else {
+            vec![]
+        };
         drop(ctrl_lock);
+        send_latched_samples(&self.state, samples);
     }
 
     fn forget_subscriber(&self, key_expr: &WireExpr, routing_context: Option<RoutingContext>) {
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
         let rtables = zread!(self.tables.tables);
+        zlock!(rtables.shed_declarations).retain(|d| !d.is(&self.state, key_expr, true));
         match (rtables.whatami, self.state.whatami) {
             (WhatAmI::Router, WhatAmI::Router) => {
                 if let Some(router) = self.state.get_router(&rtables, routing_context) {
@@ -291,6 +388,20 @@ impl Primitives for Face {
     ) {
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
         let rtables = zread!(self.tables.tables);
+        if self.state.zid != rtables.zid && rtables.memory.sheds_declarations() {
+            log::warn!(
+                "Memory budget exhausted: ignore queryable {} declared by {} until it recovers",
+                key_expr,
+                self.state.zid
+            );
+            zlock!(rtables.shed_declarations).push(ShedDeclaration {
+                face: Arc::downgrade(&self.state),
+                key_expr: key_expr.to_owned(),
+                info: ShedInfo::Queryable(qabl_info.clone()),
+                routing_context,
+            });
+            return;
+        }
         match (rtables.whatami, self.state.whatami) {
             (WhatAmI::Router, WhatAmI::Router) => {
                 if let Some(router) = self.state.get_router(&rtables, routing_context) {
@@ -342,6 +453,7 @@ impl Primitives for Face {
     fn forget_queryable(&self, key_expr: &WireExpr, routing_context: Option<RoutingContext>) {
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
         let rtables = zread!(self.tables.tables);
+        zlock!(rtables.shed_declarations).retain(|d| !d.is(&self.state, key_expr, false));
         match (rtables.whatami, self.state.whatami) {
             (WhatAmI::Router, WhatAmI::Router) => {
                 if let Some(router) = self.state.get_router(&rtables, routing_context) {
@@ -402,6 +514,21 @@ impl Primitives for Face {
         );
     }
 
+    fn send_data_batch(
+        &self,
+        batch: Vec<BatchedData>,
+        channel: Channel,
+        congestion_control: CongestionControl,
+    ) {
+        full_reentrant_route_data_batch(
+            &self.tables.tables,
+            &self.state,
+            batch,
+            channel,
+            congestion_control,
+        );
+    }
+
     fn send_query(
         &self,
         key_expr: &WireExpr,
@@ -409,7 +536,10 @@ impl Primitives for Face {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationMode,
+        timeout: Option<Duration>,
+        destinations: Option<&[ZenohId]>,
         body: Option<QueryBody>,
+        priority: Priority,
         routing_context: Option<RoutingContext>,
     ) {
         route_query(
@@ -420,11 +550,15 @@ impl Primitives for Face {
             qid,
             target,
             consolidation,
+            timeout,
+            destinations,
             body,
+            priority,
             routing_context,
         );
     }
 
+    // The replies are routed with the priority of the query they answer
     fn send_reply_data(
         &self,
         qid: ZInt,
@@ -432,6 +566,7 @@ impl Primitives for Face {
         key_expr: WireExpr,
         info: Option<DataInfo>,
         payload: ZBuf,
+        _priority: Priority,
     ) {
         route_send_reply_data(
             &self.tables,
@@ -444,7 +579,7 @@ impl Primitives for Face {
         );
     }
 
-    fn send_reply_final(&self, qid: ZInt) {
+    fn send_reply_final(&self, qid: ZInt, _priority: Priority) {
         route_send_reply_final(&self.tables, &mut self.state.clone(), qid);
     }
 
@@ -468,6 +603,10 @@ impl Primitives for Face {
     fn send_close(&self) {
         super::router::close_face(&self.tables, &Arc::downgrade(&self.state));
     }
+
+    fn send_congestion_drop(&self, key_expr: &str, dropped: ZInt) {
+        route_congestion_drop(&self.tables.tables, &self.state, key_expr, dropped);
+    }
 }
 
 impl fmt::Display for Face {
@@ -0,0 +1,68 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use uhlc::Timestamp;
+use zenoh_core::zlock;
+
+// The number of the most recently routed data remembered
+const MAX_FORWARDED: usize = 4096;
+
+/// The timestamps of the data most recently routed by a "peer_to_peer" peer forwarding between
+/// its peers, or along a static route. The same data reaching the node again, through another
+/// peer or a cycle of nodes, is identified by its timestamp and dropped.
+#[derive(Default)]
+pub(crate) struct ForwardedData {
+    seen: Mutex<(HashSet<Timestamp>, VecDeque<Timestamp>)>,
+}
+
+impl ForwardedData {
+    /// Records the data with `timestamp` as routed, returns `false` if it already was.
+    pub(crate) fn insert(&self, timestamp: &Timestamp) -> bool {
+        let mut guard = zlock!(self.seen);
+        let (seen, order) = &mut *guard;
+        if !seen.insert(*timestamp) {
+            return false;
+        }
+        order.push_back(*timestamp);
+        if order.len() > MAX_FORWARDED {
+            if let Some(oldest) = order.pop_front() {
+                seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uhlc::HLC;
+
+    #[test]
+    fn forwarded_data() {
+        let hlc = HLC::default();
+        let forwarded = ForwardedData::default();
+        let first = hlc.new_timestamp();
+        assert!(forwarded.insert(&first));
+        assert!(!forwarded.insert(&first));
+        assert!(forwarded.insert(&hlc.new_timestamp()));
+
+        // Only the most recent timestamps are remembered
+        for _ in 0..MAX_FORWARDED {
+            forwarded.insert(&hlc.new_timestamp());
+        }
+        assert!(forwarded.insert(&first));
+    }
+}
@@ -0,0 +1,172 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+use std::sync::Mutex;
+use zenoh_buffers::ZBuf;
+use zenoh_core::zlock;
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::core::{Channel, SampleKind};
+use zenoh_protocol::zenoh::DataInfo;
+
+/// The most recent latched sample published on a key expression.
+#[derive(Clone)]
+pub(crate) struct LatchedSample {
+    pub(crate) payload: ZBuf,
+    pub(crate) info: DataInfo,
+    pub(crate) channel: Channel,
+}
+
+/// The most recent latched samples routed by this node, delivered to the newly matching
+/// subscribers of its clients.
+pub(crate) struct LatchedSamples {
+    samples: Mutex<HashMap<OwnedKeyExpr, LatchedSample>>,
+    max_samples: usize,
+}
+
+impl Default for LatchedSamples {
+    fn default() -> Self {
+        LatchedSamples::new(zenoh_config::defaults::routing::latched::max_samples)
+    }
+}
+
+impl LatchedSamples {
+    /// Creates the latched samples keeping the sample of at most `max_samples` key expressions.
+    pub(crate) fn new(max_samples: usize) -> Self {
+        LatchedSamples {
+            samples: Mutex::new(HashMap::new()),
+            max_samples,
+        }
+    }
+
+    /// Keeps the latched sample published on `key_expr`, replacing the previous one.
+    /// A latched delete forgets the sample of the key expression. Once `max_samples` key
+    /// expressions are kept, the samples published on other key expressions are not kept.
+    pub(crate) fn latch(&self, key_expr: &str, payload: &ZBuf, info: &DataInfo, channel: Channel) {
+        let key_expr = match keyexpr::new(key_expr) {
+            Ok(ke) if !ke.is_wild() => ke,
+            _ => {
+                log::debug!(
+                    "Ignore latched sample on non concrete key expr {}",
+                    key_expr
+                );
+                return;
+            }
+        };
+        let mut samples = zlock!(self.samples);
+        if info.kind == SampleKind::Delete {
+            samples.remove(key_expr);
+        } else if samples.len() >= self.max_samples && !samples.contains_key(key_expr) {
+            log::warn!(
+                "Do not keep latched sample on {}: the {} latched samples limit is reached",
+                key_expr,
+                self.max_samples
+            );
+        } else {
+            samples.insert(
+                key_expr.into(),
+                LatchedSample {
+                    payload: payload.clone(),
+                    info: info.clone(),
+                    channel,
+                },
+            );
+        }
+    }
+
+    /// Returns the latched samples whose key expression intersects with `key_expr`.
+    pub(crate) fn matching(&self, key_expr: &keyexpr) -> Vec<(OwnedKeyExpr, LatchedSample)> {
+        zlock!(self.samples)
+            .iter()
+            .filter(|(ke, _)| ke.intersects(key_expr))
+            .map(|(ke, sample)| (ke.clone(), sample.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn latched_samples() {
+        use super::LatchedSamples;
+        use zenoh_buffers::ZBuf;
+        use zenoh_protocol::core::{key_expr::keyexpr, Channel, SampleKind};
+        use zenoh_protocol::zenoh::DataInfo;
+
+        let latched = LatchedSamples::default();
+        let put = DataInfo {
+            latched: true,
+            ..Default::default()
+        };
+        latched.latch("demo/a", &ZBuf::default(), &put, Channel::default());
+        latched.latch("demo/b", &ZBuf::default(), &put, Channel::default());
+        latched.latch("demo/*", &ZBuf::default(), &put, Channel::default());
+        latched.latch("other/a", &ZBuf::default(), &put, Channel::default());
+
+        let matching = latched.matching(keyexpr::new("demo/**").unwrap());
+        assert_eq!(matching.len(), 2);
+        let matching = latched.matching(keyexpr::new("demo/a").unwrap());
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].0.as_str(), "demo/a");
+
+        let delete = DataInfo {
+            kind: SampleKind::Delete,
+            latched: true,
+            ..Default::default()
+        };
+        latched.latch("demo/a", &ZBuf::default(), &delete, Channel::default());
+        assert!(latched.matching(keyexpr::new("demo/a").unwrap()).is_empty());
+        assert_eq!(latched.matching(keyexpr::new("**").unwrap()).len(), 2);
+    }
+
+    #[test]
+    fn latched_samples_limit() {
+        use super::LatchedSamples;
+        use zenoh_buffers::ZBuf;
+        use zenoh_protocol::core::{key_expr::keyexpr, Channel, SampleKind};
+        use zenoh_protocol::zenoh::DataInfo;
+
+        let latched = LatchedSamples::new(2);
+        let put = DataInfo {
+            latched: true,
+            ..Default::default()
+        };
+        latched.latch("demo/a", &ZBuf::default(), &put, Channel::default());
+        latched.latch("demo/b", &ZBuf::default(), &put, Channel::default());
+        latched.latch("demo/c", &ZBuf::default(), &put, Channel::default());
+        let all = keyexpr::new("demo/**").unwrap();
+        assert_eq!(latched.matching(all).len(), 2);
+        assert!(latched.matching(keyexpr::new("demo/c").unwrap()).is_empty());
+
+        // The samples of the kept key expressions are still replaced
+        let put = DataInfo {
+            latched: true,
+            encoding: Some(zenoh_protocol::core::Encoding::TEXT_PLAIN),
+            ..Default::default()
+        };
+        latched.latch("demo/a", &ZBuf::default(), &put, Channel::default());
+        let matching = latched.matching(keyexpr::new("demo/a").unwrap());
+        assert_eq!(matching[0].1.info.encoding, put.encoding);
+
+        // A delete frees room for another key expression
+        let delete = DataInfo {
+            kind: SampleKind::Delete,
+            latched: true,
+            ..Default::default()
+        };
+        latched.latch("demo/b", &ZBuf::default(), &delete, Channel::default());
+        latched.latch("demo/c", &ZBuf::default(), &put, Channel::default());
+        assert_eq!(latched.matching(keyexpr::new("demo/c").unwrap()).len(), 1);
+        assert_eq!(latched.matching(all).len(), 2);
+    }
+}
@@ -18,11 +18,16 @@
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
 pub mod face;
+pub(crate) mod forwarded;
+pub(crate) mod latched;
 pub mod network;
 pub mod pubsub;
 pub mod queries;
+pub(crate) mod quotas;
+pub(crate) mod static_routes;
 pub mod resource;
 pub mod router;
+pub(crate) mod traffic;
 
 use super::runtime;
 
@@ -12,17 +12,19 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::face::FaceState;
+use super::latched::LatchedSample;
 use super::network::Network;
 use super::resource::{DataRoutes, Direction, PullCaches, Resource, Route, SessionContext};
 use super::router::{RoutingExpr, Tables, TablesLock};
 use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::sync::RwLock;
 use std::sync::{Arc, RwLockReadGuard};
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
-use zenoh_core::zread;
+use zenoh_core::{executor, zlock, zread};
 use zenoh_protocol::core::key_expr::keyexpr;
 use zenoh_protocol::{
     core::{
@@ -32,6 +34,7 @@ use zenoh_protocol::{
     zenoh::{DataInfo, RoutingContext},
 };
 use zenoh_sync::get_mut_unchecked;
+use zenoh_transport::{count_congestion_drops, BatchedData};
 
 #[inline]
 fn send_sourced_subscription_to_net_childs(
@@ -89,7 +92,9 @@ fn propagate_simple_subscription_to(
                 if full_peer_net {
                     dst_face.whatami == WhatAmI::Client
                 } else {
-                    src_face.whatami == WhatAmI::Client || dst_face.whatami == WhatAmI::Client
+                    src_face.whatami == WhatAmI::Client
+                        || dst_face.whatami == WhatAmI::Client
+                        || tables.forwards_between(src_face.zid, dst_face.zid)
                 }
             }
             _ => src_face.whatami == WhatAmI::Client || dst_face.whatami == WhatAmI::Client,
@@ -165,6 +170,22 @@ fn propagate_sourced_subscription(
     }
 }
 
+// A node not subscribed yet, or subscribed best effort and now reliably, registers and
+// propagates its subscription
+#[inline]
+fn requests_more_reliability(
+    subs: &HashMap<ZenohId, Reliability>,
+    node: &ZenohId,
+    sub_info: &SubInfo,
+) -> bool {
+    match subs.get(node) {
+        Some(reliability) => {
+            *reliability == Reliability::BestEffort && sub_info.reliability == Reliability::Reliable
+        }
+        None => true,
+    }
+}
+
 fn register_router_subscription(
     tables: &mut Tables,
     face: &mut Arc<FaceState>,
@@ -172,7 +193,7 @@ fn register_router_subscription(
     sub_info: &SubInfo,
     router: ZenohId,
 ) {
-    if !res.context().router_subs.contains(&router) {
+    if requests_more_reliability(&res.context().router_subs, &router, sub_info) {
         // Register router subscription
         {
             log::debug!(
@@ -183,7 +204,7 @@ fn register_router_subscription(
             get_mut_unchecked(res)
                 .context_mut()
                 .router_subs
-                .insert(router);
+                .insert(router, sub_info.reliability);
             tables.router_subs.insert(res.clone());
         }
 
@@ -259,11 +280,14 @@ fn register_peer_subscription(
     sub_info: &SubInfo,
     peer: ZenohId,
 ) {
-    if !res.context().peer_subs.contains(&peer) {
+    if requests_more_reliability(&res.context().peer_subs, &peer, sub_info) {
         // Register peer subscription
         {
             log::debug!("Register peer subscription {} (peer: {})", res.expr(), peer);
-            get_mut_unchecked(res).context_mut().peer_subs.insert(peer);
+            get_mut_unchecked(res)
+                .context_mut()
+                .peer_subs
+                .insert(peer, sub_info.reliability);
             tables.peer_subs.insert(res.clone());
         }
 
@@ -463,7 +487,7 @@ fn remote_router_subs(tables: &Tables, res: &Arc<Resource>) -> bool {
         && res
             .context()
             .router_subs
-            .iter()
+            .keys()
             .any(|peer| peer != &tables.zid)
 }
 
@@ -473,7 +497,7 @@ fn remote_peer_subs(tables: &Tables, res: &Arc<Resource>) -> bool {
         && res
             .context()
             .peer_subs
-            .iter()
+            .keys()
             .any(|peer| peer != &tables.zid)
 }
 
@@ -534,7 +558,7 @@ fn propagate_forget_simple_subscription(tables: &mut Tables, res: &Arc<Resource>
 fn propagate_forget_simple_subscription_to_peers(tables: &mut Tables, res: &Arc<Resource>) {
     if !tables.full_net(WhatAmI::Peer)
         && res.context().router_subs.len() == 1
-        && res.context().router_subs.contains(&tables.zid)
+        && res.context().router_subs.contains_key(&tables.zid)
     {
         for mut face in tables
             .faces
@@ -606,7 +630,7 @@ fn unregister_router_subscription(tables: &mut Tables, res: &mut Arc<Resource>,
     get_mut_unchecked(res)
         .context_mut()
         .router_subs
-        .retain(|sub| sub != router);
+        .remove(router);
 
     if res.context().router_subs.is_empty() {
         tables.router_subs.retain(|sub| !Arc::ptr_eq(sub, res));
@@ -626,7 +650,7 @@ fn undeclare_router_subscription(
     res: &mut Arc<Resource>,
     router: &ZenohId,
 ) {
-    if res.context().router_subs.contains(router) {
+    if res.context().router_subs.contains_key(router) {
         unregister_router_subscription(tables, res, router);
         propagate_forget_sourced_subscription(tables, res, face, router, WhatAmI::Router);
     }
@@ -672,10 +696,7 @@ fn unregister_peer_subscription(tables: &mut Tables, res: &mut Arc<Resource>, pe
         res.expr(),
         peer
     );
-    get_mut_unchecked(res)
-        .context_mut()
-        .peer_subs
-        .retain(|sub| sub != peer);
+    get_mut_unchecked(res).context_mut().peer_subs.remove(peer);
 
     if res.context().peer_subs.is_empty() {
         tables.peer_subs.retain(|sub| !Arc::ptr_eq(sub, res));
@@ -692,7 +713,7 @@ fn undeclare_peer_subscription(
     res: &mut Arc<Resource>,
     peer: &ZenohId,
 ) {
-    if res.context().peer_subs.contains(peer) {
+    if res.context().peer_subs.contains_key(peer) {
         unregister_peer_subscription(tables, res, peer);
         propagate_forget_sourced_subscription(tables, res, face, peer, WhatAmI::Peer);
     }
@@ -841,7 +862,7 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
             } else if face.whatami == WhatAmI::Peer && !tables.full_net(WhatAmI::Peer) {
                 for sub in &tables.router_subs {
                     if sub.context.is_some()
-                        && (sub.context().router_subs.iter().any(|r| *r != tables.zid)
+                        && (sub.context().router_subs.keys().any(|r| *r != tables.zid)
                             || sub.session_ctxs.values().any(|s| {
                                 s.subs.is_some()
                                     && (s.face.whatami == WhatAmI::Client
@@ -913,7 +934,7 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
             for mut res in tables
                 .router_subs
                 .iter()
-                .filter(|res| res.context().router_subs.contains(node))
+                .filter(|res| res.context().router_subs.contains_key(node))
                 .cloned()
                 .collect::<Vec<Arc<Resource>>>()
             {
@@ -932,7 +953,7 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
             for mut res in tables
                 .peer_subs
                 .iter()
-                .filter(|res| res.context().peer_subs.contains(node))
+                .filter(|res| res.context().peer_subs.contains_key(node))
                 .cloned()
                 .collect::<Vec<Arc<Resource>>>()
             {
@@ -983,10 +1004,10 @@ pub(crate) fn pubsub_tree_change(
                         WhatAmI::Router => &res.context().router_subs,
                         _ => &res.context().peer_subs,
                     };
-                    for sub in subs {
+                    for (sub, reliability) in subs {
                         if *sub == tree_id {
                             let sub_info = SubInfo {
-                                reliability: Reliability::Reliable, // @TODO
+                                reliability: *reliability,
                                 mode: SubMode::Push,
                             };
                             send_sourced_subscription_to_net_childs(
@@ -1077,28 +1098,41 @@ fn insert_faces_for_subs(
     tables: &Tables,
     net: &Network,
     source: usize,
-    subs: &HashSet<ZenohId>,
+    subs: &HashMap<ZenohId, Reliability>,
 ) {
     if net.trees.len() > source {
-        for sub in subs {
+        for (sub, reliability) in subs {
             if let Some(sub_idx) = net.get_idx(sub) {
                 if net.trees[source].directions.len() > sub_idx.index() {
                     if let Some(direction) = net.trees[source].directions[sub_idx.index()] {
                         if net.graph.contains_node(direction) {
                             if let Some(face) = tables.get_face(&net.graph[direction].zid) {
-                                route.entry(face.id).or_insert_with(|| {
-                                    let key_expr =
-                                        Resource::get_best_key(expr.prefix, expr.suffix, face.id);
-                                    (
-                                        face.clone(),
-                                        key_expr.to_owned(),
-                                        if source != 0 {
-                                            Some(RoutingContext::new(source as ZInt))
-                                        } else {
-                                            None
-                                        },
-                                    )
-                                });
+                                route
+                                    .entry(face.id)
+                                    .and_modify(|(_, r)| {
+                                        if *reliability == Reliability::Reliable {
+                                            *r = Reliability::Reliable
+                                        }
+                                    })
+                                    .or_insert_with(|| {
+                                        let key_expr = Resource::get_best_key(
+                                            expr.prefix,
+                                            expr.suffix,
+                                            face.id,
+                                        );
+                                        (
+                                            (
+                                                face.clone(),
+                                                key_expr.to_owned(),
+                                                if source != 0 {
+                                                    Some(RoutingContext::new(source as ZInt))
+                                                } else {
+                                                    None
+                                                },
+                                            ),
+                                            *reliability,
+                                        )
+                                    });
                             }
                         }
                     }
@@ -1205,13 +1239,25 @@ fn compute_data_route(
                         _ => {
                             source_type == WhatAmI::Client
                                 || context.face.whatami == WhatAmI::Client
+                                || tables.forwards_between_peers()
                         }
                     } && subinfo.mode == SubMode::Push
                     {
-                        route.entry(*sid).or_insert_with(|| {
-                            let key_expr = Resource::get_best_key(expr.prefix, expr.suffix, *sid);
-                            (context.face.clone(), key_expr.to_owned(), None)
-                        });
+                        route
+                            .entry(*sid)
+                            .and_modify(|(_, reliability)| {
+                                if subinfo.reliability == Reliability::Reliable {
+                                    *reliability = Reliability::Reliable
+                                }
+                            })
+                            .or_insert_with(|| {
+                                let key_expr =
+                                    Resource::get_best_key(expr.prefix, expr.suffix, *sid);
+                                (
+                                    (context.face.clone(), key_expr.to_owned(), None),
+                                    subinfo.reliability,
+                                )
+                            });
                     }
                 }
             }
@@ -1254,6 +1300,7 @@ pub(super) fn compute_data_routes_(tables: &Tables, res: &Arc<Resource>) -> Data
         peers_data_routes: vec![],
         peer_data_route: None,
         client_data_route: None,
+        static_routes: None,
     };
     let mut expr = RoutingExpr::new(res, "");
     if tables.whatami == WhatAmI::Router {
@@ -1306,6 +1353,9 @@ pub(super) fn compute_data_routes_(tables: &Tables, res: &Arc<Resource>) -> Data
             Some(compute_data_route(tables, &mut expr, None, WhatAmI::Client));
     }
     routes.matching_pulls = Some(compute_matching_pulls(tables, &mut expr));
+    if tables.static_routes.is_enabled() && res.static_routes().is_none() {
+        routes.static_routes = Some(tables.static_routes.matching(&res.expr()));
+    }
     routes
 }
 
@@ -1366,6 +1416,9 @@ pub(crate) fn compute_data_routes(tables: &mut Tables, res: &mut Arc<Resource>)
                 Some(compute_data_route(tables, &mut expr, None, WhatAmI::Client));
         }
         res_mut.context_mut().matching_pulls = compute_matching_pulls(tables, &mut expr);
+        if tables.static_routes.is_enabled() && res.static_routes().is_none() {
+            res_mut.context_mut().static_routes = Some(tables.static_routes.matching(&res.expr()));
+        }
     }
 }
 
@@ -1578,11 +1631,83 @@ fn should_route(
             && (src_face.whatami != WhatAmI::Peer
                 || outface.whatami != WhatAmI::Peer
                 || tables.full_net(WhatAmI::Peer)
-                || tables.failover_brokering(src_face.zid, outface.zid));
+                || tables.failover_brokering(src_face.zid, outface.zid)
+                || tables.forwards_between(src_face.zid, outface.zid));
     }
     false
 }
 
+/// The payload of a routed data message.
+///
+/// The shared memory buffers of the payload are sent by reference to the faces supporting
+/// shared memory, while they are copied once in a network buffer shared by the other faces.
+struct RoutedPayload {
+    payload: ZBuf,
+    #[cfg(feature = "shared-memory")]
+    shm: bool,
+    #[cfg(feature = "shared-memory")]
+    net: Option<ZBuf>,
+}
+
+impl RoutedPayload {
+    fn new(payload: ZBuf) -> Self {
+        RoutedPayload {
+            #[cfg(feature = "shared-memory")]
+            shm: payload
+                .zslices()
+                .any(|s| s.buf.as_any().is::<zenoh_shm::SharedMemoryBuf>()),
+            #[cfg(feature = "shared-memory")]
+            net: None,
+            payload,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.payload.len()
+    }
+
+    /// The payload to send to `outface`.
+    #[cfg_attr(not(feature = "shared-memory"), allow(unused_variables))]
+    fn for_face(&mut self, outface: &FaceState) -> ZBuf {
+        #[cfg(feature = "shared-memory")]
+        if self.shm && !outface.shm {
+            let payload = &self.payload;
+            return self
+                .net
+                .get_or_insert_with(|| {
+                    use zenoh_buffers::SplitBuffer;
+                    ZBuf::from(payload.contiguous().into_owned())
+                })
+                .clone();
+        }
+        self.payload.clone()
+    }
+
+    /// The payload to send to `outface`, the last face it is sent to, moved rather than cloned.
+    #[cfg_attr(not(feature = "shared-memory"), allow(unused_variables))]
+    fn into_face(self, outface: &FaceState) -> ZBuf {
+        #[cfg(feature = "shared-memory")]
+        if self.shm && !outface.shm {
+            let mut routed = self;
+            return routed.for_face(outface);
+        }
+        self.payload
+    }
+}
+
+// The data is only sent reliably if both the publisher and the subscriptions requested it
+#[inline]
+fn out_channel(channel: Channel, reliability: Reliability) -> Channel {
+    match reliability {
+        Reliability::Reliable => channel,
+        Reliability::BestEffort => Channel {
+            priority: channel.priority,
+            reliability: Reliability::BestEffort,
+        },
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn full_reentrant_route_data(
     tables_ref: &RwLock<Tables>,
@@ -1594,6 +1719,248 @@ pub fn full_reentrant_route_data(
     payload: ZBuf,
     routing_context: Option<RoutingContext>,
 ) {
+    let (_, dropped) = count_congestion_drops(|| {
+        route_data(
+            tables_ref,
+            face,
+            expr,
+            channel,
+            info,
+            payload,
+            routing_context,
+            &mut |outface, key_expr, payload, channel, info, context| {
+                outface.primitives.send_data(
+                    key_expr,
+                    payload,
+                    channel,
+                    congestion_control,
+                    info,
+                    context,
+                )
+            },
+        )
+    });
+    if dropped > 0 {
+        notify_congestion_drops(tables_ref, face, expr, dropped);
+    }
+}
+
+/// Returns the latched samples matching the subscription declared by `face` on `expr`.
+pub(crate) fn latched_samples(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    expr: &WireExpr,
+) -> Vec<(OwnedKeyExpr, LatchedSample)> {
+    let tables = zread!(tables_ref);
+    let key_expr = match tables.get_mapping(face, &expr.scope) {
+        Some(prefix) => prefix.expr() + expr.suffix.as_ref(),
+        None => return vec![],
+    };
+    match keyexpr::new(key_expr.as_str()) {
+        Ok(key_expr) => tables.latched.matching(key_expr),
+        Err(_) => vec![],
+    }
+}
+
+/// Sends the latched `samples` to `face`. They are dropped rather than blocking if its tx queue
+/// is full, the caller must not hold the control lock of the tables.
+pub(crate) fn send_latched_samples(face: &FaceState, samples: Vec<(OwnedKeyExpr, LatchedSample)>) {
+    for (key_expr, sample) in samples {
+        log::trace!("Send latched sample {} to {}", key_expr, face);
+        face.primitives.send_data(
+            &key_expr.as_str().into(),
+            sample.payload,
+            sample.channel,
+            CongestionControl::Drop,
+            Some(sample.info),
+            None,
+        );
+    }
+}
+
+/// Notifies `face`, if enabled and at most once per configured interval, of the data messages
+/// on `expr` received from it and dropped because of congestion.
+fn notify_congestion_drops(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    expr: &WireExpr,
+    dropped: usize,
+) {
+    let (interval, key_expr) = {
+        let tables = zread!(tables_ref);
+        let interval = match tables.congestion_drop_interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        match tables.get_mapping(face, &expr.scope) {
+            Some(prefix) => (interval, prefix.expr() + expr.suffix.as_ref()),
+            None => return,
+        }
+    };
+    queue_congestion_drops(face, key_expr, dropped as ZInt, interval);
+}
+
+// Adds the drops to the ones notified to `face` at the end of the current interval
+fn queue_congestion_drops(face: &FaceState, key_expr: String, dropped: ZInt, interval: Duration) {
+    let mut pending = zlock!(face.congestion_drops);
+    if pending.is_empty() {
+        let congestion_drops = face.congestion_drops.clone();
+        let primitives = face.primitives.clone();
+        executor::spawn(async move {
+            async_std::task::sleep(interval).await;
+            let drops = std::mem::take(&mut *zlock!(congestion_drops));
+            for (key_expr, dropped) in drops {
+                primitives.send_congestion_drop(&key_expr, dropped);
+            }
+        });
+    }
+    *pending.entry(key_expr).or_default() += dropped;
+}
+
+/// Relays the notification, received from `face`, of the data messages on `key_expr` dropped
+/// because of congestion to the faces whose data on `key_expr` is routed to `face`, i.e. one hop
+/// further toward the publishers. The local sessions are notified at once, the other faces at
+/// most once per configured interval.
+pub(crate) fn route_congestion_drop(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    key_expr: &str,
+    dropped: ZInt,
+) {
+    let key_expr = match keyexpr::new(key_expr) {
+        Ok(key_expr) => key_expr,
+        Err(e) => {
+            log::debug!(
+                "Ignore congestion drop notification for {} from {}: {}",
+                key_expr,
+                face,
+                e
+            );
+            return;
+        }
+    };
+    let (interval, local, remote) = {
+        let tables = zread!(tables_ref);
+        let res = Resource::get_resource(&tables.root_res, key_expr);
+        let (mut local, mut remote) = (vec![], vec![]);
+        for src in tables.faces.values().filter(|src| src.id != face.id) {
+            let mut expr = RoutingExpr::new(&tables.root_res, key_expr);
+            if get_data_route(&tables, src, &res, &mut expr, None).contains_key(&face.id) {
+                if src.zid == tables.zid {
+                    local.push(src.clone());
+                } else {
+                    remote.push(src.clone());
+                }
+            }
+        }
+        let interval = tables.congestion_drop_interval.unwrap_or_else(|| {
+            Duration::from_millis(zenoh_config::defaults::routing::congestion_drop::interval)
+        });
+        (interval, local, remote)
+    };
+    for src in local {
+        src.primitives.send_congestion_drop(key_expr, dropped);
+    }
+    for src in remote {
+        queue_congestion_drops(&src, key_expr.to_string(), dropped, interval);
+    }
+}
+
+/// Routes the data messages of a batch, the messages routed to a same face being sent to it in a batch.
+pub fn full_reentrant_route_data_batch(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    batch: Vec<BatchedData>,
+    channel: Channel,
+    congestion_control: CongestionControl,
+) {
+    // The batches per outgoing face and channel
+    let mut out_batches: Vec<(Arc<FaceState>, Channel, Vec<BatchedData>)> = vec![];
+    for data in batch {
+        route_data(
+            tables_ref,
+            face,
+            &data.key_expr,
+            channel,
+            data.data_info,
+            data.payload,
+            data.routing_context,
+            &mut |outface, key_expr, payload, channel, data_info, routing_context| {
+                let data = BatchedData {
+                    key_expr: key_expr.to_owned(),
+                    payload,
+                    data_info,
+                    routing_context,
+                };
+                match out_batches
+                    .iter_mut()
+                    .find(|(f, c, _)| f.id == outface.id && *c == channel)
+                {
+                    Some((_, _, batch)) => batch.push(data),
+                    None => out_batches.push((outface.clone(), channel, vec![data])),
+                }
+            },
+        );
+    }
+    for (outface, channel, batch) in out_batches {
+        outface
+            .primitives
+            .send_data_batch(batch, channel, congestion_control);
+    }
+}
+
+/// Returns the route of the data matching a static route forwarded to its connected `next_hop`:
+/// the local sessions of the learned `route` and the next hop, unless the data comes from it.
+fn static_data_route(
+    tables: &Tables,
+    face: &FaceState,
+    route: &Route,
+    next_hop: &Arc<FaceState>,
+    expr: &mut RoutingExpr,
+    channel: Channel,
+) -> Arc<Route> {
+    let mut static_route: Route = route
+        .iter()
+        .filter(|(_, ((outface, _, _), _))| outface.zid == tables.zid && outface.id != face.id)
+        .map(|(sid, direction)| (*sid, direction.clone()))
+        .collect();
+    if next_hop.id != face.id {
+        let key_expr = Resource::get_best_key(expr.prefix, expr.suffix, next_hop.id);
+        static_route.insert(
+            next_hop.id,
+            (
+                (next_hop.clone(), key_expr.to_owned(), None),
+                channel.reliability,
+            ),
+        );
+    }
+    Arc::new(static_route)
+}
+
+type SendData<'a> = dyn FnMut(&Arc<FaceState>, &WireExpr, ZBuf, Channel, Option<DataInfo>, Option<RoutingContext>)
+    + 'a;
+
+#[allow(clippy::too_many_arguments)]
+fn route_data(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    expr: &WireExpr,
+    channel: Channel,
+    info: Option<DataInfo>,
+    payload: ZBuf,
+    routing_context: Option<RoutingContext>,
+    send: &mut SendData,
+) {
+    let span = tracing::trace_span!(
+        "route",
+        from = %face.zid,
+        trace = tracing::field::Empty
+    );
+    if let Some(tc) = info.as_ref().and_then(|i| i.trace_context.as_ref()) {
+        span.record("trace", tracing::field::display(tc));
+    }
+    let _enter = span.enter();
+
     let tables = zread!(tables_ref);
     match tables.get_mapping(face, &expr.scope).cloned() {
         Some(prefix) => {
@@ -1604,6 +1971,32 @@ pub fn full_reentrant_route_data(
             );
             let mut expr = RoutingExpr::new(&prefix, expr.suffix.as_ref());
 
+            if let Some(info) = info.as_ref().filter(|info| info.latched) {
+                tables
+                    .latched
+                    .latch(expr.full_expr(), &payload, info, channel);
+            }
+
+            let accounted = tables.accounting.is_enabled();
+            let accounting = if accounted {
+                let counters = tables.accounting.matching(expr.full_expr());
+                for c in &counters {
+                    c.rx(payload.len());
+                }
+                counters
+            } else {
+                vec![]
+            };
+            let mut payload = RoutedPayload::new(payload);
+            let payload_len = payload.len();
+            let account_tx = |msgs: usize| {
+                if accounted {
+                    for c in &accounting {
+                        c.tx(msgs, payload_len);
+                    }
+                }
+            };
+
             if tables.whatami != WhatAmI::Router
                 || face.whatami != WhatAmI::Peer
                 || tables.peers_net.is_none()
@@ -1611,22 +2004,53 @@ pub fn full_reentrant_route_data(
                     == *tables.elect_router(expr.full_expr(), tables.get_router_links(face.zid))
             {
                 let res = Resource::get_resource(&prefix, expr.suffix);
-                let route = get_data_route(&tables, face, &res, &mut expr, routing_context);
+                let mut route = get_data_route(&tables, face, &res, &mut expr, routing_context);
+                let static_hop = if tables.static_routes.is_enabled() {
+                    match res.as_ref().and_then(|res| res.static_routes()) {
+                        Some(matching) => tables.static_routes.next_hop(matching),
+                        None => tables
+                            .static_routes
+                            .next_hop(&tables.static_routes.matching(expr.full_expr())),
+                    }
+                } else {
+                    None
+                };
+                if let Some(next_hop) = &static_hop {
+                    route = static_data_route(&tables, face, &route, next_hop, &mut expr, channel);
+                }
                 let matching_pulls = get_matching_pulls(&tables, &res, &mut expr);
 
                 if !(route.is_empty() && matching_pulls.is_empty()) {
                     let data_info =
                         treat_timestamp!(&tables.hlc, info, tables.drop_future_timestamp);
 
+                    // A forwarding peer, or a node forwarding the data to the next hop of a static
+                    // route, routes the data only once: the same data reaching it again through
+                    // another peer or through a cycle of nodes is dropped
+                    if tables.forwards_between_peers() || static_hop.is_some() {
+                        if let Some(ts) = data_info.as_ref().and_then(|i| i.timestamp.as_ref()) {
+                            if !tables.forwarded.insert(ts) {
+                                log::trace!(
+                                    "Drop data {} from {} already forwarded",
+                                    expr.full_expr(),
+                                    face
+                                );
+                                return;
+                            }
+                        }
+                    }
+
                     if route.len() == 1 && matching_pulls.len() == 0 {
-                        let (outface, key_expr, context) = route.values().next().unwrap();
+                        let ((outface, key_expr, context), reliability) =
+                            route.values().next().unwrap();
                         if should_route(&tables, face, outface, &mut expr) {
                             drop(tables);
-                            outface.primitives.send_data(
+                            account_tx(1);
+                            send(
+                                outface,
                                 key_expr,
-                                payload,
-                                channel, // @TODO: Need to check the active subscriptions to determine the right reliability value
-                                congestion_control,
+                                payload.into_face(outface),
+                                out_channel(channel, *reliability),
                                 data_info,
                                 *context,
                             )
@@ -1634,42 +2058,81 @@ pub fn full_reentrant_route_data(
                     } else {
                         if !matching_pulls.is_empty() {
                             let lock = zlock!(tables.pull_caches_lock);
-                            cache_data!(matching_pulls, expr, payload, data_info);
+                            cache_data!(matching_pulls, expr, payload.payload, data_info);
                             drop(lock);
                         }
 
-                        if tables.whatami == WhatAmI::Router {
+                        if tables.whatami == WhatAmI::Router
+                            || tables.forwards_between_peers()
+                            || static_hop.is_some()
+                        {
                             let route = route
                                 .values()
-                                .filter(|(outface, _key_expr, _context)| {
+                                .filter(|((outface, _key_expr, _context), _reliability)| {
                                     should_route(&tables, face, outface, &mut expr)
                                 })
                                 .cloned()
-                                .collect::<Vec<Direction>>();
+                                .collect::<Vec<(Direction, Reliability)>>();
 
                             drop(tables);
-                            for (outface, key_expr, context) in route {
-                                outface.primitives.send_data(
-                                    &key_expr,
-                                    payload.clone(),
-                                    channel, // @TODO: Need to check the active subscriptions to determine the right reliability value
-                                    congestion_control,
-                                    data_info.clone(),
-                                    context,
-                                )
+                            let mut remaining = route.len();
+                            account_tx(remaining);
+                            // The payload is cloned for all the faces but the last one
+                            for ((outface, key_expr, context), reliability) in route {
+                                remaining -= 1;
+                                if remaining > 0 {
+                                    send(
+                                        &outface,
+                                        &key_expr,
+                                        payload.for_face(&outface),
+                                        out_channel(channel, reliability),
+                                        data_info.clone(),
+                                        context,
+                                    )
+                                } else {
+                                    send(
+                                        &outface,
+                                        &key_expr,
+                                        payload.into_face(&outface),
+                                        out_channel(channel, reliability),
+                                        data_info,
+                                        context,
+                                    );
+                                    break;
+                                }
                             }
                         } else {
                             drop(tables);
-                            for (outface, key_expr, context) in route.values() {
-                                if face.id != outface.id {
-                                    outface.primitives.send_data(
+                            let mut remaining = route
+                                .values()
+                                .filter(|((outface, _, _), _)| face.id != outface.id)
+                                .count();
+                            account_tx(remaining);
+                            // The payload is cloned for all the faces but the last one
+                            for ((outface, key_expr, context), reliability) in route.values() {
+                                if face.id == outface.id {
+                                    continue;
+                                }
+                                remaining -= 1;
+                                if remaining > 0 {
+                                    send(
+                                        outface,
                                         key_expr,
-                                        payload.clone(),
-                                        channel, // @TODO: Need to check the active subscriptions to determine the right reliability value
-                                        congestion_control,
+                                        payload.for_face(outface),
+                                        out_channel(channel, *reliability),
                                         data_info.clone(),
                                         *context,
                                     )
+                                } else {
+                                    send(
+                                        outface,
+                                        key_expr,
+                                        payload.into_face(outface),
+                                        out_channel(channel, *reliability),
+                                        data_info,
+                                        *context,
+                                    );
+                                    break;
                                 }
                             }
                         }
@@ -23,7 +23,9 @@ use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, RwLockReadGuard, Weak};
+use std::time::{Duration, Instant};
 use zenoh_buffers::ZBuf;
 use zenoh_protocol::core::key_expr::keyexpr;
 use zenoh_protocol::{
@@ -32,16 +34,37 @@ use zenoh_protocol::{
             include::{Includer, DEFAULT_INCLUDER},
             OwnedKeyExpr,
         },
-        ConsolidationMode, QueryTarget, QueryableInfo, WhatAmI, WireExpr, ZInt, ZenohId,
+        ConsolidationMode, Priority, QueryTarget, QueryableInfo, WhatAmI, WireExpr, ZInt, ZenohId,
     },
     zenoh::{DataInfo, QueryBody, RoutingContext},
 };
 use zenoh_sync::get_mut_unchecked;
-use zenoh_util::Timed;
+use zenoh_util::{Timed, TimedEvent};
 
 pub(crate) struct Query {
     src_face: Arc<FaceState>,
     src_qid: ZInt,
+    // The priority of the query, inherited by its replies
+    priority: Priority,
+}
+
+impl Query {
+    fn new(src_face: Arc<FaceState>, src_qid: ZInt, priority: Priority) -> Self {
+        src_face.queries_in_flight.fetch_add(1, Ordering::Relaxed);
+        Query {
+            src_face,
+            src_qid,
+            priority,
+        }
+    }
+}
+
+impl Drop for Query {
+    fn drop(&mut self) {
+        self.src_face
+            .queries_in_flight
+            .fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 #[cfg(feature = "complete_n")]
@@ -49,6 +72,7 @@ pub(crate) struct Query {
 fn merge_qabl_infos(mut this: QueryableInfo, info: &QueryableInfo) -> QueryableInfo {
     this.complete += info.complete;
     this.distance = std::cmp::min(this.distance, info.distance);
+    this.load_balanced = this.load_balanced && info.load_balanced;
     this
 }
 
@@ -57,6 +81,7 @@ fn merge_qabl_infos(mut this: QueryableInfo, info: &QueryableInfo) -> QueryableI
 fn merge_qabl_infos(mut this: QueryableInfo, info: &QueryableInfo) -> QueryableInfo {
     this.complete = ZInt::from(this.complete != 0 || info.complete != 0);
     this.distance = std::cmp::min(this.distance, info.distance);
+    this.load_balanced = this.load_balanced && info.load_balanced;
     this
 }
 
@@ -92,6 +117,7 @@ fn local_router_qabl_info(tables: &Tables, res: &Arc<Resource>) -> QueryableInfo
         .unwrap_or(QueryableInfo {
             complete: 0,
             distance: 0,
+            load_balanced: false,
         })
 }
 
@@ -128,6 +154,7 @@ fn local_peer_qabl_info(tables: &Tables, res: &Arc<Resource>) -> QueryableInfo {
         .unwrap_or(QueryableInfo {
             complete: 0,
             distance: 0,
+            load_balanced: false,
         })
 }
 
@@ -168,7 +195,9 @@ fn local_qabl_info(tables: &Tables, res: &Arc<Resource>, face: &Arc<FaceState>)
     res.session_ctxs
         .values()
         .fold(info, |accu, ctx| {
-            if ctx.face.id != face.id && ctx.face.whatami != WhatAmI::Peer
+            if ctx.face.id != face.id
+                && (ctx.face.whatami != WhatAmI::Peer
+                    || tables.forwards_between(ctx.face.zid, face.zid))
                 || face.whatami != WhatAmI::Peer
                 || tables.failover_brokering(ctx.face.zid, face.zid)
             {
@@ -187,6 +216,7 @@ fn local_qabl_info(tables: &Tables, res: &Arc<Resource>, face: &Arc<FaceState>)
         .unwrap_or(QueryableInfo {
             complete: 0,
             distance: 0,
+            load_balanced: false,
         })
 }
 
@@ -255,6 +285,7 @@ fn propagate_simple_queryable(
                         src_face.is_none()
                             || src_face.as_ref().unwrap().whatami == WhatAmI::Client
                             || dst_face.whatami == WhatAmI::Client
+                            || tables.forwards_between(src_face.as_ref().unwrap().zid, dst_face.zid)
                     }
                 }
                 _ => {
@@ -1252,6 +1283,7 @@ fn insert_target_for_qabls(
                                         ),
                                         complete: if complete { qabl_info.complete } else { 0 },
                                         distance: net.distances[qabl_idx.index()],
+                                        load_balanced: qabl_info.load_balanced,
                                     });
                                 }
                             }
@@ -1363,7 +1395,11 @@ fn compute_query_route(
             for (sid, context) in &mres.session_ctxs {
                 if match tables.whatami {
                     WhatAmI::Router => context.face.whatami != WhatAmI::Router,
-                    _ => source_type == WhatAmI::Client || context.face.whatami == WhatAmI::Client,
+                    _ => {
+                        source_type == WhatAmI::Client
+                            || context.face.whatami == WhatAmI::Client
+                            || tables.forwards_between_peers()
+                    }
                 } {
                     let key_expr = Resource::get_best_key(expr.prefix, expr.suffix, *sid);
                     if let Some(qabl_info) = context.qabl.as_ref() {
@@ -1371,6 +1407,7 @@ fn compute_query_route(
                             direction: (context.face.clone(), key_expr.to_owned(), None),
                             complete: if complete { qabl_info.complete } else { 0 },
                             distance: 0.5,
+                            load_balanced: qabl_info.load_balanced,
                         });
                     }
                 }
@@ -1571,11 +1608,36 @@ fn should_route(
             && (src_face.whatami != WhatAmI::Peer
                 || outface.whatami != WhatAmI::Peer
                 || tables.full_net(WhatAmI::Peer)
-                || tables.failover_brokering(src_face.zid, outface.zid));
+                || tables.failover_brokering(src_face.zid, outface.zid)
+                || tables.forwards_between(src_face.zid, outface.zid));
     }
     false
 }
 
+/// Returns the load balanced queryable a best matching query from `src_face` is routed to, if
+/// any: the one reached through the face with the fewest pending queries, in round robin among
+/// equals. The other queryables are then ignored.
+fn balance_query<'a>(
+    tables: &Tables,
+    qabls: &'a QueryTargetQablSet,
+    src_face: &FaceState,
+    expr: &mut RoutingExpr,
+) -> Option<&'a QueryTargetQabl> {
+    let candidates = qabls
+        .iter()
+        .filter(|qabl| {
+            qabl.load_balanced && should_route(tables, src_face, &qabl.direction.0, expr)
+        })
+        .collect::<Vec<_>>();
+    if candidates.is_empty() {
+        return None;
+    }
+    let start = tables.query_balancing.fetch_add(1, Ordering::Relaxed);
+    (0..candidates.len())
+        .map(|i| candidates[(start + i) % candidates.len()])
+        .min_by_key(|qabl| qabl.direction.0.pending_queries.len())
+}
+
 #[inline]
 fn compute_final_route(
     tables: &Tables,
@@ -1685,10 +1747,11 @@ fn compute_final_route(
             route
         }
         QueryTarget::BestMatching => {
-            if let Some(qabl) = qabls
-                .iter()
-                .find(|qabl| qabl.direction.0.id != src_face.id && qabl.complete > 0)
-            {
+            if let Some(qabl) = balance_query(tables, qabls, src_face, expr).or_else(|| {
+                qabls
+                    .iter()
+                    .find(|qabl| qabl.direction.0.id != src_face.id && qabl.complete > 0)
+            }) {
                 let mut route = HashMap::new();
                 #[cfg(feature = "complete_n")]
                 {
@@ -1710,6 +1773,39 @@ fn compute_final_route(
     }
 }
 
+/// Returns, for each of the `destinations` of a query, the zid of the neighbour it is reached
+/// through: itself if directly connected, or the next hop towards it in the link state.
+/// `None` if the destination is unknown to the link state, e.g. a client of another router.
+fn destination_hops(tables: &Tables, destinations: &[ZenohId]) -> Vec<Option<ZenohId>> {
+    destinations
+        .iter()
+        .map(|zid| {
+            if tables.faces.values().any(|face| face.zid == *zid) {
+                return Some(*zid);
+            }
+            [tables.routers_net.as_ref(), tables.peers_net.as_ref()]
+                .into_iter()
+                .flatten()
+                .filter(|net| net.full_linkstate)
+                .find_map(|net| {
+                    let idx = net.get_idx(zid)?;
+                    let direction = (*net.trees[net.idx.index()].directions.get(idx.index())?)?;
+                    Some(net.graph[direction].zid)
+                })
+        })
+        .collect()
+}
+
+/// Whether a query whose destinations are reached through `hops` may be routed to `outface`.
+/// The destinations unknown to the link state may be reached through any router or peer.
+#[inline]
+fn leads_to(outface: &FaceState, hops: &[Option<ZenohId>]) -> bool {
+    hops.iter().any(|hop| match hop {
+        Some(zid) => *zid == outface.zid,
+        None => outface.whatami != WhatAmI::Client,
+    })
+}
+
 #[inline]
 fn compute_local_replies(
     tables: &Tables,
@@ -1762,14 +1858,14 @@ struct QueryCleanup {
 impl Timed for QueryCleanup {
     async fn run(&mut self) {
         if let Some(mut face) = self.face.upgrade() {
-            let tables_lock = zwrite!(self.tables.tables);
+            let queries_lock = zwrite!(self.tables.queries_lock);
             if let Some(query) = get_mut_unchecked(&mut face)
                 .pending_queries
                 .remove(&self.qid)
             {
-                drop(tables_lock);
-                log::warn!(
-                    "Didn't receive final reply {}:{} from {}: Timeout!",
+                drop(queries_lock);
+                log::debug!(
+                    "Didn't receive final reply {}:{} from {} before the query expired: abandon it",
                     query.src_face,
                     self.qid,
                     face
@@ -1878,9 +1974,19 @@ pub fn route_query(
     qid: ZInt,
     target: QueryTarget,
     consolidation: ConsolidationMode,
+    timeout: Option<Duration>,
+    destinations: Option<&[ZenohId]>,
     body: Option<QueryBody>,
+    priority: Priority,
     routing_context: Option<RoutingContext>,
 ) {
+    // The query has already expired at its origin, nobody waits for its replies
+    if timeout == Some(Duration::ZERO) {
+        log::debug!("Drop expired query {}:{}", face, qid);
+        face.primitives.clone().send_reply_final(qid, priority);
+        return;
+    }
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
     let rtables = zread!(tables_ref.tables);
     match rtables.get_mapping(face, &expr.scope) {
         Some(prefix) => {
@@ -1902,16 +2008,35 @@ pub fn route_query(
             {
                 let res = Resource::get_resource(&prefix, expr.suffix);
                 let route = get_query_route(&rtables, face, &res, &mut expr, routing_context);
+                let route = match destinations {
+                    Some(destinations) => {
+                        let hops = destination_hops(&rtables, destinations);
+                        Arc::new(
+                            route
+                                .iter()
+                                .filter(|qabl| leads_to(&qabl.direction.0, &hops))
+                                .cloned()
+                                .collect(),
+                        )
+                    }
+                    None => route,
+                };
 
-                let query = Arc::new(Query {
-                    src_face: face.clone(),
-                    src_qid: qid,
-                });
+                let query = Arc::new(Query::new(face.clone(), qid, priority));
 
                 let queries_lock = zwrite!(tables_ref.queries_lock);
                 let route = compute_final_route(&rtables, &route, face, &mut expr, &target, query);
                 let local_replies = compute_local_replies(&rtables, &prefix, expr.suffix, face);
                 let zid = rtables.zid;
+                let timer = rtables.timer.clone();
+                rtables.query_fanout.record(&target, route.len());
+                log::debug!(
+                    "Route query {}:{} with target {:?} to {} destinations",
+                    face,
+                    qid,
+                    target,
+                    route.len()
+                );
 
                 drop(queries_lock);
                 drop(rtables);
@@ -1919,7 +2044,7 @@ pub fn route_query(
                 for (expr, payload) in local_replies {
                     face.primitives
                         .clone()
-                        .send_reply_data(qid, zid, expr, None, payload);
+                        .send_reply_data(qid, zid, expr, None, payload, priority);
                 }
 
                 if route.is_empty() {
@@ -1928,21 +2053,26 @@ pub fn route_query(
                         face,
                         qid
                     );
-                    face.primitives.clone().send_reply_final(qid)
+                    face.primitives.clone().send_reply_final(qid, priority)
                 } else {
-                    // let timer = tables.timer.clone();
-                    // let timeout = tables.queries_default_timeout;
+                    // The pending queries are abandoned when they expire at their origin
+                    let cleanup = |outface: &Arc<FaceState>, qid: ZInt| {
+                        if let Some(deadline) = deadline {
+                            timer.add(TimedEvent::once(
+                                deadline,
+                                QueryCleanup {
+                                    tables: tables_ref.clone(),
+                                    face: Arc::downgrade(outface),
+                                    qid,
+                                },
+                            ));
+                        }
+                    };
+                    let timeout = || deadline.map(|d| d.saturating_duration_since(Instant::now()));
                     #[cfg(feature = "complete_n")]
                     {
                         for ((outface, key_expr, context), qid, t) in route.values() {
-                            // timer.add(TimedEvent::once(
-                            //     Instant::now() + timout,
-                            //     QueryCleanup {
-                            //         tables: tables_ref.clone(),
-                            //         face: Arc::downgrade(&outface),
-                            //         *qid,
-                            //     },
-                            // ));
+                            cleanup(outface, *qid);
                             log::trace!("Propagate query {}:{} to {}", face, qid, outface);
                             outface.primitives.send_query(
                                 key_expr,
@@ -1950,7 +2080,10 @@ pub fn route_query(
                                 *qid,
                                 *t,
                                 consolidation,
+                                timeout(),
+                                destinations,
                                 body.clone(),
+                                priority,
                                 *context,
                             );
                         }
@@ -1959,14 +2092,7 @@ pub fn route_query(
                     #[cfg(not(feature = "complete_n"))]
                     {
                         for ((outface, key_expr, context), qid) in route.values() {
-                            // timer.add(TimedEvent::once(
-                            //     Instant::now() + timeout,
-                            //     QueryCleanup {
-                            //         tables: tables_ref.clone(),
-                            //         face: Arc::downgrade(&outface),
-                            //         *qid,
-                            //     },
-                            // ));
+                            cleanup(outface, *qid);
                             log::trace!("Propagate query {}:{} to {}", face, qid, outface);
                             outface.primitives.send_query(
                                 key_expr,
@@ -1974,7 +2100,10 @@ pub fn route_query(
                                 *qid,
                                 target,
                                 consolidation,
+                                timeout(),
+                                destinations,
                                 body.clone(),
+                                priority,
                                 *context,
                             );
                         }
@@ -1983,7 +2112,7 @@ pub fn route_query(
             } else {
                 log::debug!("Send final reply {}:{} (not master)", face, qid);
                 drop(rtables);
-                face.primitives.clone().send_reply_final(qid)
+                face.primitives.clone().send_reply_final(qid, priority)
             }
         }
         None => {
@@ -1992,7 +2121,7 @@ pub fn route_query(
                 expr.scope
             );
             drop(rtables);
-            face.primitives.clone().send_reply_final(qid)
+            face.primitives.clone().send_reply_final(qid, priority)
         }
     }
 }
@@ -2017,6 +2146,7 @@ pub(crate) fn route_send_reply_data(
                 key_expr,
                 info,
                 payload,
+                query.priority,
             );
         }
         None => log::warn!(
@@ -2069,6 +2199,6 @@ pub(crate) fn finalize_pending_query(query: Arc<Query>) {
             .src_face
             .primitives
             .clone()
-            .send_reply_final(query.src_qid);
+            .send_reply_final(query.src_qid, query.priority);
     }
 }
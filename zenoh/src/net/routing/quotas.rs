@@ -0,0 +1,241 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::Face;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_config::{Config, QuotaRule};
+use zenoh_core::{zlock, zread};
+use zenoh_protocol::core::ZenohId;
+use zenoh_protocol::zenoh::{Data, Declaration, Declare, ZenohBody, ZenohMessage};
+
+const BANDWIDTH_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct QuotaLimits {
+    max_subscribers: Option<usize>,
+    max_queryables: Option<usize>,
+    max_pending_queries: Option<usize>,
+    max_bytes_per_sec: Option<usize>,
+}
+
+impl QuotaLimits {
+    fn overridden_by(self, rule: &QuotaRule) -> Self {
+        QuotaLimits {
+            max_subscribers: rule.max_subscribers.or(self.max_subscribers),
+            max_queryables: rule.max_queryables.or(self.max_queryables),
+            max_pending_queries: rule.max_pending_queries.or(self.max_pending_queries),
+            max_bytes_per_sec: rule.max_bytes_per_sec.or(self.max_bytes_per_sec),
+        }
+    }
+
+    fn is_unlimited(&self) -> bool {
+        *self == QuotaLimits::default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum QuotaViolation {
+    Subscribers,
+    Queryables,
+    PendingQueries,
+    Bandwidth,
+}
+
+impl fmt::Display for QuotaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaViolation::Subscribers => write!(f, "max_subscribers"),
+            QuotaViolation::Queryables => write!(f, "max_queryables"),
+            QuotaViolation::PendingQueries => write!(f, "max_pending_queries"),
+            QuotaViolation::Bandwidth => write!(f, "max_bytes_per_sec"),
+        }
+    }
+}
+
+/// The quotas configured on a router or peer, see [`QuotasConf`](zenoh_config::QuotasConf).
+///
+/// The quotas are looked up by the zenoh id declared by the remote node during the handshake,
+/// which is not authenticated: they guard against misbehaving nodes, not malicious ones.
+#[derive(Default)]
+pub(crate) struct Quotas {
+    default: QuotaLimits,
+    identities: HashMap<ZenohId, QuotaLimits>,
+    subscribers_violations: AtomicUsize,
+    queryables_violations: AtomicUsize,
+    pending_queries_violations: AtomicUsize,
+    bandwidth_violations: AtomicUsize,
+}
+
+impl Quotas {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let c = config.transport().quotas();
+        let default = QuotaLimits {
+            max_subscribers: *c.max_subscribers(),
+            max_queryables: *c.max_queryables(),
+            max_pending_queries: *c.max_pending_queries(),
+            max_bytes_per_sec: *c.max_bytes_per_sec(),
+        };
+        let mut identities = HashMap::new();
+        for rule in c.identities() {
+            for zid in &rule.zids {
+                identities.insert(*zid, default.overridden_by(rule));
+            }
+        }
+        Quotas {
+            default,
+            identities,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the enforcer of the quotas applying to the remote node `zid`, if any.
+    pub(crate) fn enforcer(&self, zid: &ZenohId) -> Option<QuotaEnforcer> {
+        let limits = self.identities.get(zid).copied().unwrap_or(self.default);
+        (!limits.is_unlimited()).then(|| QuotaEnforcer {
+            limits,
+            window: Mutex::new((Instant::now(), 0)),
+        })
+    }
+
+    pub(crate) fn violated(&self, violation: QuotaViolation) {
+        let counter = match violation {
+            QuotaViolation::Subscribers => &self.subscribers_violations,
+            QuotaViolation::Queryables => &self.queryables_violations,
+            QuotaViolation::PendingQueries => &self.pending_queries_violations,
+            QuotaViolation::Bandwidth => &self.bandwidth_violations,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn violations_json(&self) -> serde_json::Value {
+        json!({
+            "max_subscribers": self.subscribers_violations.load(Ordering::Relaxed),
+            "max_queryables": self.queryables_violations.load(Ordering::Relaxed),
+            "max_pending_queries": self.pending_queries_violations.load(Ordering::Relaxed),
+            "max_bytes_per_sec": self.bandwidth_violations.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// Enforces the quotas of a single transport.
+pub(crate) struct QuotaEnforcer {
+    limits: QuotaLimits,
+    window: Mutex<(Instant, usize)>,
+}
+
+impl QuotaEnforcer {
+    /// Checks the quotas that must be enforced before a message is handled.
+    pub(crate) fn check_message(
+        &self,
+        face: &Face,
+        msg: &ZenohMessage,
+    ) -> Result<(), QuotaViolation> {
+        match &msg.body {
+            ZenohBody::Data(Data {
+                payload,
+                reply_context: None,
+                ..
+            }) => {
+                if let Some(max) = self.limits.max_bytes_per_sec {
+                    let now = Instant::now();
+                    let mut window = zlock!(self.window);
+                    if now.duration_since(window.0) >= BANDWIDTH_WINDOW {
+                        *window = (now, 0);
+                    }
+                    window.1 += payload.len();
+                    if window.1 > max {
+                        return Err(QuotaViolation::Bandwidth);
+                    }
+                }
+            }
+            ZenohBody::Query(_) => {
+                if let Some(max) = self.limits.max_pending_queries {
+                    if face.state.queries_in_flight.load(Ordering::Relaxed) >= max {
+                        return Err(QuotaViolation::PendingQueries);
+                    }
+                }
+            }
+            ZenohBody::Declare(Declare { declarations }) => {
+                // Declaration quotas are checked before the message is handled so that
+                // declarations exceeding them are never propagated. Redeclarations of
+                // an already declared subscriber or queryable count as new ones.
+                let (subs, qabls) =
+                    declarations
+                        .iter()
+                        .fold((0, 0), |(subs, qabls), decl| match decl {
+                            Declaration::Subscriber(_) => (subs + 1, qabls),
+                            Declaration::Queryable(_) => (subs, qabls + 1),
+                            _ => (subs, qabls),
+                        });
+                let _tables = zread!(face.tables.tables);
+                if let Some(max) = self.limits.max_subscribers {
+                    if subs > 0 && face.state.remote_subs.len() + subs > max {
+                        return Err(QuotaViolation::Subscribers);
+                    }
+                }
+                if let Some(max) = self.limits.max_queryables {
+                    if qabls > 0 && face.state.remote_qabls.len() + qabls > max {
+                        return Err(QuotaViolation::Queryables);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+mod tests {
+    #[test]
+    fn quotas_config() {
+        use super::Quotas;
+        use std::convert::TryFrom;
+        use zenoh_config::{Config, ValidatedMap};
+        use zenoh_protocol::core::ZenohId;
+
+        let mut config = Config::default();
+        config
+            .insert_json5(
+                "transport/quotas",
+                r#"{
+                    max_subscribers: 10,
+                    identities: [{ zids: ["a1"], max_subscribers: 100, max_bytes_per_sec: 1024 }],
+                }"#,
+            )
+            .unwrap();
+        let quotas = Quotas::from_config(&config);
+
+        let limits = quotas
+            .enforcer(&ZenohId::try_from([2]).unwrap())
+            .unwrap()
+            .limits;
+        assert_eq!(limits.max_subscribers, Some(10));
+        assert_eq!(limits.max_bytes_per_sec, None);
+
+        let limits = quotas
+            .enforcer(&ZenohId::try_from([0xa1]).unwrap())
+            .unwrap()
+            .limits;
+        assert_eq!(limits.max_subscribers, Some(100));
+        assert_eq!(limits.max_bytes_per_sec, Some(1024));
+
+        // No enforcer when no quota is configured
+        let quotas = Quotas::from_config(&Config::default());
+        assert!(quotas.enforcer(&ZenohId::try_from([2]).unwrap()).is_none());
+    }
+}
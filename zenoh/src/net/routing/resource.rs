@@ -19,21 +19,25 @@ use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Weak};
 use zenoh_buffers::ZBuf;
 use zenoh_protocol::{
-    core::{key_expr::keyexpr, QueryableInfo, SubInfo, WireExpr, ZInt, ZenohId},
+    core::{key_expr::keyexpr, QueryableInfo, Reliability, SubInfo, WireExpr, ZInt, ZenohId},
     zenoh::{DataInfo, RoutingContext},
 };
 use zenoh_sync::get_mut_unchecked;
+use zenoh_transport::memory::{MemoryBudget, MemoryReservation, MemorySubsystem};
 
 pub(super) type Direction = (Arc<FaceState>, WireExpr<'static>, Option<RoutingContext>);
-pub(super) type Route = HashMap<usize, Direction>;
+// The reliability is the one requested by the subscriptions reached through the direction
+pub(super) type Route = HashMap<usize, (Direction, Reliability)>;
 #[cfg(feature = "complete_n")]
 pub(super) type QueryRoute = HashMap<usize, (Direction, ZInt, zenoh_protocol::core::QueryTarget)>;
 #[cfg(not(feature = "complete_n"))]
 pub(super) type QueryRoute = HashMap<usize, (Direction, ZInt)>;
+#[derive(Clone)]
 pub(super) struct QueryTargetQabl {
     pub(super) direction: Direction,
     pub(super) complete: ZInt,
     pub(super) distance: f64,
+    pub(super) load_balanced: bool,
 }
 pub(super) type QueryTargetQablSet = Vec<QueryTargetQabl>;
 pub(super) type PullCaches = Vec<Arc<SessionContext>>;
@@ -53,6 +57,7 @@ pub(super) struct DataRoutes {
     pub(super) peers_data_routes: Vec<Arc<Route>>,
     pub(super) peer_data_route: Option<Arc<Route>>,
     pub(super) client_data_route: Option<Arc<Route>>,
+    pub(super) static_routes: Option<Vec<usize>>,
 }
 
 pub(super) struct QueryRoutes {
@@ -63,8 +68,9 @@ pub(super) struct QueryRoutes {
 }
 
 pub(super) struct ResourceContext {
-    pub(super) router_subs: HashSet<ZenohId>,
-    pub(super) peer_subs: HashSet<ZenohId>,
+    // The reliability requested by the subscriptions of each router and peer
+    pub(super) router_subs: HashMap<ZenohId, Reliability>,
+    pub(super) peer_subs: HashMap<ZenohId, Reliability>,
     pub(super) router_qabls: HashMap<ZenohId, QueryableInfo>,
     pub(super) peer_qabls: HashMap<ZenohId, QueryableInfo>,
     pub(super) matches: Vec<Weak<Resource>>,
@@ -74,6 +80,8 @@ pub(super) struct ResourceContext {
     pub(super) peers_data_routes: Vec<Arc<Route>>,
     pub(super) peer_data_route: Option<Arc<Route>>,
     pub(super) client_data_route: Option<Arc<Route>>,
+    // The indexes of the static routes including the resource, once resolved
+    pub(super) static_routes: Option<Vec<usize>>,
     pub(super) valid_query_routes: bool,
     pub(super) routers_query_routes: Vec<Arc<QueryTargetQablSet>>,
     pub(super) peers_query_routes: Vec<Arc<QueryTargetQablSet>>,
@@ -84,8 +92,8 @@ pub(super) struct ResourceContext {
 impl ResourceContext {
     fn new() -> ResourceContext {
         ResourceContext {
-            router_subs: HashSet::new(),
-            peer_subs: HashSet::new(),
+            router_subs: HashMap::new(),
+            peer_subs: HashMap::new(),
             router_qabls: HashMap::new(),
             peer_qabls: HashMap::new(),
             matches: Vec::new(),
@@ -95,6 +103,7 @@ impl ResourceContext {
             peers_data_routes: Vec::new(),
             peer_data_route: None,
             client_data_route: None,
+            static_routes: None,
             valid_query_routes: false,
             routers_query_routes: Vec::new(),
             peers_query_routes: Vec::new(),
@@ -112,6 +121,9 @@ impl ResourceContext {
         self.peers_data_routes = data_routes.peers_data_routes;
         self.peer_data_route = data_routes.peer_data_route;
         self.client_data_route = data_routes.client_data_route;
+        if let Some(static_routes) = data_routes.static_routes {
+            self.static_routes = Some(static_routes);
+        }
     }
 
     pub(super) fn update_query_routes(&mut self, query_routes: QueryRoutes) {
@@ -128,8 +140,12 @@ pub struct Resource {
     pub(super) suffix: String,
     pub(super) nonwild_prefix: Option<(Arc<Resource>, String)>,
     pub(super) childs: HashMap<String, Arc<Resource>>,
+    // The keys of the childs whose chunk is wild, the only ones that may match a chunk they differ from
+    pub(super) wild_childs: HashSet<String>,
     pub(super) context: Option<ResourceContext>,
     pub(super) session_ctxs: HashMap<usize, Arc<SessionContext>>,
+    // The memory of the resource accounted in the memory budget
+    _memory: Option<MemoryReservation>,
 }
 
 impl PartialEq for Resource {
@@ -146,7 +162,12 @@ impl Hash for Resource {
 }
 
 impl Resource {
-    fn new(parent: &Arc<Resource>, suffix: &str, context: Option<ResourceContext>) -> Resource {
+    fn new(
+        parent: &Arc<Resource>,
+        suffix: &str,
+        context: Option<ResourceContext>,
+        memory: &Arc<MemoryBudget>,
+    ) -> Resource {
         let nonwild_prefix = match &parent.nonwild_prefix {
             None => {
                 if suffix.contains('*') {
@@ -163,8 +184,14 @@ impl Resource {
             suffix: String::from(suffix),
             nonwild_prefix,
             childs: HashMap::new(),
+            wild_childs: HashSet::new(),
             context,
             session_ctxs: HashMap::new(),
+            _memory: Some(MemoryReservation::new(
+                memory.clone(),
+                MemorySubsystem::Routing,
+                std::mem::size_of::<Resource>() + suffix.len(),
+            )),
         }
     }
 
@@ -243,6 +270,14 @@ impl Resource {
         }
     }
 
+    /// The indexes of the static routes including this resource, once resolved with its routes.
+    #[inline(always)]
+    pub(super) fn static_routes(&self) -> Option<&[usize]> {
+        self.context
+            .as_ref()
+            .and_then(|ctx| ctx.static_routes.as_deref())
+    }
+
     #[inline(always)]
     pub fn client_data_route(&self) -> Option<Arc<Route>> {
         match &self.context {
@@ -321,8 +356,10 @@ impl Resource {
             suffix: String::from(""),
             nonwild_prefix: None,
             childs: HashMap::new(),
+            wild_childs: HashSet::new(),
             context: None,
             session_ctxs: HashMap::new(),
+            _memory: None,
         })
     }
 
@@ -345,7 +382,9 @@ impl Resource {
                     }
                 }
                 {
-                    get_mut_unchecked(parent).childs.remove(&res.suffix);
+                    let parent = get_mut_unchecked(parent);
+                    parent.childs.remove(&res.suffix);
+                    parent.wild_childs.remove(&res.suffix);
                 }
                 Resource::clean(parent);
             }
@@ -361,8 +400,20 @@ impl Resource {
         result
     }
 
+    fn is_wild_chunk(chunk: &str) -> bool {
+        chunk.contains('*') || chunk.trim_start_matches('/').is_empty()
+    }
+
+    fn insert_child(from: &mut Arc<Resource>, chunk: &str, child: Arc<Resource>) {
+        let from = get_mut_unchecked(from);
+        if Resource::is_wild_chunk(chunk) {
+            from.wild_childs.insert(String::from(chunk));
+        }
+        from.childs.insert(String::from(chunk), child);
+    }
+
     pub fn make_resource(
-        _tables: &mut Tables,
+        tables: &mut Tables,
         from: &mut Arc<Resource>,
         suffix: &str,
     ) -> Arc<Resource> {
@@ -376,23 +427,21 @@ impl Resource {
             };
 
             match get_mut_unchecked(from).childs.get_mut(chunk) {
-                Some(res) => Resource::make_resource(_tables, res, rest),
+                Some(res) => Resource::make_resource(tables, res, rest),
                 None => {
-                    let mut new = Arc::new(Resource::new(from, chunk, None));
+                    let mut new = Arc::new(Resource::new(from, chunk, None, &tables.memory));
                     if log::log_enabled!(log::Level::Debug) && rest.is_empty() {
                         log::debug!("Register resource {}", new.expr());
                     }
-                    let res = Resource::make_resource(_tables, &mut new, rest);
-                    get_mut_unchecked(from)
-                        .childs
-                        .insert(String::from(chunk), new);
+                    let res = Resource::make_resource(tables, &mut new, rest);
+                    Resource::insert_child(from, chunk, new);
                     res
                 }
             }
         } else {
             match from.parent.clone() {
                 Some(mut parent) => {
-                    Resource::make_resource(_tables, &mut parent, &[&from.suffix, suffix].concat())
+                    Resource::make_resource(tables, &mut parent, &[&from.suffix, suffix].concat())
                 }
                 None => {
                     let (chunk, rest) = match suffix[1..].find('/') {
@@ -401,16 +450,15 @@ impl Resource {
                     };
 
                     match get_mut_unchecked(from).childs.get_mut(chunk) {
-                        Some(res) => Resource::make_resource(_tables, res, rest),
+                        Some(res) => Resource::make_resource(tables, res, rest),
                         None => {
-                            let mut new = Arc::new(Resource::new(from, chunk, None));
+                            let mut new =
+                                Arc::new(Resource::new(from, chunk, None, &tables.memory));
                             if log::log_enabled!(log::Level::Debug) && rest.is_empty() {
                                 log::debug!("Register resource {}", new.expr());
                             }
-                            let res = Resource::make_resource(_tables, &mut new, rest);
-                            get_mut_unchecked(from)
-                                .childs
-                                .insert(String::from(chunk), new);
+                            let res = Resource::make_resource(tables, &mut new, rest);
+                            Resource::insert_child(from, chunk, new);
                             res
                         }
                     }
@@ -545,6 +593,34 @@ impl Resource {
         get_best_key_(prefix, suffix, sid, true)
     }
 
+    // Calls `f` on the childs of `from` whose chunk may intersect the first chunk of `key_expr`:
+    // unless that chunk is wild, those are the child with the very same chunk and the wild childs.
+    fn for_each_candidate_child(
+        from: &Arc<Resource>,
+        key_expr: &keyexpr,
+        mut f: impl FnMut(&Arc<Resource>),
+    ) {
+        let (chunk, _) = Resource::fst_chunk(key_expr);
+        if Resource::is_wild_chunk(chunk) || from.suffix == "/" {
+            for child in from.childs.values() {
+                f(child)
+            }
+        } else {
+            let same = match from.parent {
+                Some(_) => from.childs.get(&["/", chunk.as_str()].concat()),
+                None => from.childs.get(chunk.as_str()),
+            };
+            if let Some(child) = same {
+                f(child)
+            }
+            for wild in &from.wild_childs {
+                if let Some(child) = from.childs.get(wild) {
+                    f(child)
+                }
+            }
+        }
+    }
+
     pub fn get_matches(tables: &Tables, key_expr: &keyexpr) -> Vec<Weak<Resource>> {
         fn recursive_push(from: &Arc<Resource>, matches: &mut Vec<Weak<Resource>>) {
             if from.context.is_some() {
@@ -560,9 +636,9 @@ impl Resource {
             matches: &mut Vec<Weak<Resource>>,
         ) {
             if from.parent.is_none() || from.suffix == "/" {
-                for child in from.childs.values() {
-                    get_matches_from(key_expr, child, matches);
-                }
+                Resource::for_each_candidate_child(from, key_expr, |child| {
+                    get_matches_from(key_expr, child, matches)
+                });
                 return;
             }
             let suffix: &keyexpr = from
@@ -582,9 +658,9 @@ impl Resource {
                                 matches.push(Arc::downgrade(from));
                             }
                             if suffix.as_bytes() == b"**" {
-                                for child in from.childs.values() {
+                                Resource::for_each_candidate_child(from, key_expr, |child| {
                                     get_matches_from(key_expr, child, matches)
-                                }
+                                });
                             }
                             if let Some(child) =
                                 from.childs.get("/**").or_else(|| from.childs.get("**"))
@@ -599,13 +675,13 @@ impl Resource {
                     Some(rest) => {
                         let recheck_keyexpr_one_level_lower =
                             chunk.as_bytes() == b"**" || suffix.as_bytes() == b"**";
-                        for child in from.childs.values() {
-                            get_matches_from(rest, child, matches);
-                            if recheck_keyexpr_one_level_lower {
-                                get_matches_from(key_expr, child, matches)
-                            }
-                        }
+                        Resource::for_each_candidate_child(from, rest, |child| {
+                            get_matches_from(rest, child, matches)
+                        });
                         if recheck_keyexpr_one_level_lower {
+                            Resource::for_each_candidate_child(from, key_expr, |child| {
+                                get_matches_from(key_expr, child, matches)
+                            });
                             get_matches_from(rest, from, matches)
                         }
                     }
@@ -614,19 +690,8 @@ impl Resource {
         }
         let mut matches = Vec::new();
         get_matches_from(key_expr, &tables.root_res, &mut matches);
-        let mut i = 0;
-        while i < matches.len() {
-            let current = matches[i].as_ptr();
-            let mut j = i + 1;
-            while j < matches.len() {
-                if std::ptr::eq(current, matches[j].as_ptr()) {
-                    matches.swap_remove(j);
-                } else {
-                    j += 1
-                }
-            }
-            i += 1
-        }
+        let mut visited = HashSet::with_capacity(matches.len());
+        matches.retain(|m| visited.insert(m.as_ptr()));
         matches
     }
 
@@ -11,32 +11,43 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::face::{Face, FaceState};
+use super::face::{redeclare_shed_declarations, Face, FaceState, ShedDeclaration};
+use super::forwarded::ForwardedData;
+use super::latched::LatchedSamples;
 use super::network::{shared_nodes, Network};
 pub use super::pubsub::*;
 pub use super::queries::*;
+use super::quotas::{QuotaEnforcer, QuotaViolation, Quotas};
 pub use super::resource::*;
 use super::runtime::Runtime;
-use async_std::task::JoinHandle;
+use super::static_routes::StaticRoutes;
+use super::traffic::{QueryFanout, TrafficAccounting};
+use crate::interceptor::{InterceptorChain, InterceptorContext, Interceptors};
 use std::any::Any;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hasher;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 use std::sync::{Mutex, RwLock};
 use std::time::Duration;
 use uhlc::HLC;
 use zenoh_config::whatami::WhatAmIMatcher;
+use zenoh_core::executor::{self, JoinHandle};
 use zenoh_link::Link;
 use zenoh_protocol::{
     core::{WhatAmI, ZInt, ZenohId},
+    transport::tmsg,
     zenoh::{ZenohBody, ZenohMessage},
 };
+use zenoh_transport::memory::MemoryBudget;
 use zenoh_transport::{DeMux, Mux, Primitives, TransportPeerEventHandler, TransportUnicast};
 // use zenoh_collections::Timer;
 use zenoh_core::zconfigurable;
 use zenoh_result::ZResult;
 use zenoh_sync::get_mut_unchecked;
+use zenoh_util::audit::{self, AuditEvent};
+use zenoh_util::Timer;
 
 zconfigurable! {
     static ref TREES_COMPUTATION_DELAY: u64 = 100;
@@ -75,11 +86,14 @@ pub struct Tables {
     pub(crate) hlc: Option<Arc<HLC>>,
     pub(crate) drop_future_timestamp: bool,
     pub(crate) router_peers_failover_brokering: bool,
-    // pub(crate) timer: Timer,
+    pub(crate) peer_forwarding: bool,
+    pub(crate) forwarded: ForwardedData,
+    pub(crate) timer: Timer,
     // pub(crate) queries_default_timeout: Duration,
     pub(crate) root_res: Arc<Resource>,
     pub(crate) faces: HashMap<usize, Arc<FaceState>>,
     pub(crate) pull_caches_lock: Mutex<()>,
+    pub(crate) latched: LatchedSamples,
     pub(crate) router_subs: HashSet<Arc<Resource>>,
     pub(crate) peer_subs: HashSet<Arc<Resource>>,
     pub(crate) router_qabls: HashSet<Arc<Resource>>,
@@ -89,6 +103,16 @@ pub struct Tables {
     pub(crate) shared_nodes: Vec<ZenohId>,
     pub(crate) routers_trees_task: Option<JoinHandle<()>>,
     pub(crate) peers_trees_task: Option<JoinHandle<()>>,
+    pub(crate) accounting: TrafficAccounting,
+    pub(crate) query_fanout: QueryFanout,
+    pub(crate) static_routes: StaticRoutes,
+    pub(crate) memory: Arc<MemoryBudget>,
+    /// The declarations ignored under memory pressure, declared again once the memory recovers.
+    pub(crate) shed_declarations: Mutex<Vec<ShedDeclaration>>,
+    /// The minimum interval between two congestion drop notifications to a same face, if enabled.
+    pub(crate) congestion_drop_interval: Option<Duration>,
+    // The round robin counter of the load balanced queries
+    pub(crate) query_balancing: AtomicUsize,
 }
 
 impl Tables {
@@ -107,11 +131,14 @@ impl Tables {
             hlc,
             drop_future_timestamp,
             router_peers_failover_brokering,
-            // timer: Timer::new(true),
+            peer_forwarding: false,
+            forwarded: ForwardedData::default(),
+            timer: Timer::new(false),
             // queries_default_timeout,
             root_res: Resource::root(),
             faces: HashMap::new(),
             pull_caches_lock: Mutex::new(()),
+            latched: LatchedSamples::default(),
             router_subs: HashSet::new(),
             peer_subs: HashSet::new(),
             router_qabls: HashSet::new(),
@@ -121,6 +148,13 @@ impl Tables {
             shared_nodes: vec![],
             routers_trees_task: None,
             peers_trees_task: None,
+            accounting: TrafficAccounting::default(),
+            query_fanout: QueryFanout::default(),
+            static_routes: StaticRoutes::default(),
+            memory: Arc::new(MemoryBudget::default()),
+            shed_declarations: Mutex::new(vec![]),
+            congestion_drop_interval: None,
+            query_balancing: AtomicUsize::new(0),
         }
     }
 
@@ -232,6 +266,25 @@ impl Tables {
         !source_links.is_empty() && !source_links.contains(&dest)
     }
 
+    /// Returns `true` if this is a "peer_to_peer" peer forwarding the declarations and data
+    /// between all its faces, as a router would.
+    #[inline]
+    pub(crate) fn forwards_between_peers(&self) -> bool {
+        self.whatami == WhatAmI::Peer && self.peer_forwarding && !self.full_net(WhatAmI::Peer)
+    }
+
+    /// Returns `true` if this is a forwarding peer and `peer1` is not known, through gossip, to be
+    /// connected to `peer2`: the peers connected to each other are never forwarded between.
+    #[inline]
+    pub(crate) fn forwards_between(&self, peer1: ZenohId, peer2: ZenohId) -> bool {
+        self.forwards_between_peers()
+            && self
+                .peers_net
+                .as_ref()
+                .map(|net| !net.get_links(peer1).contains(&peer2))
+                .unwrap_or(true)
+    }
+
     #[inline]
     pub(crate) fn failover_brokering(&self, peer1: ZenohId, peer2: ZenohId) -> bool {
         self.router_peers_failover_brokering
@@ -248,16 +301,20 @@ impl Tables {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        shm: bool,
     ) -> Weak<FaceState> {
         let fid = self.face_counter;
         self.face_counter += 1;
         let mut newface = self
             .faces
             .entry(fid)
-            .or_insert_with(|| FaceState::new(fid, zid, whatami, primitives.clone(), link_id))
+            .or_insert_with(|| FaceState::new(fid, zid, whatami, primitives.clone(), link_id, shm))
             .clone();
         log::debug!("New {}", newface);
 
+        if self.static_routes.is_enabled() {
+            self.static_routes.connect(&newface);
+        }
         pubsub_new_face(self, &mut newface);
         queries_new_face(self, &mut newface);
 
@@ -270,7 +327,8 @@ impl Tables {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
     ) -> Weak<FaceState> {
-        self.open_net_face(zid, whatami, primitives, 0)
+        // Local faces share the memory of the process
+        self.open_net_face(zid, whatami, primitives, 0, true)
     }
 
     fn compute_routes(&mut self, res: &mut Arc<Resource>) {
@@ -301,7 +359,7 @@ impl Tables {
         if (net_type == WhatAmI::Router && self.routers_trees_task.is_none())
             || (net_type == WhatAmI::Peer && self.peers_trees_task.is_none())
         {
-            let task = Some(async_std::task::spawn(async move {
+            let task = Some(executor::spawn(async move {
                 async_std::task::sleep(std::time::Duration::from_millis(*TREES_COMPUTATION_DELAY))
                     .await;
                 let mut tables = zwrite!(tables_ref.tables);
@@ -417,6 +475,9 @@ pub fn close_face(tables: &TablesLock, face: &Weak<FaceState>) {
                     .update_query_routes(query_routes);
                 Resource::clean(&mut res);
             }
+            if wtables.static_routes.is_enabled() {
+                wtables.static_routes.disconnect(face);
+            }
             wtables.faces.remove(&face.id);
             drop(wtables);
             drop(ctrl_lock);
@@ -434,6 +495,8 @@ pub struct TablesLock {
 pub struct Router {
     whatami: WhatAmI,
     pub tables: Arc<TablesLock>,
+    pub(crate) quotas: Arc<Quotas>,
+    interceptors: Arc<Interceptors>,
 }
 
 impl Router {
@@ -459,9 +522,62 @@ impl Router {
                 ctrl_lock: Mutex::new(()),
                 queries_lock: RwLock::new(()),
             }),
+            quotas: Arc::new(Quotas::default()),
+            interceptors: Arc::new(Interceptors::default()),
         }
     }
 
+    pub(crate) fn with_quotas(mut self, quotas: Quotas) -> Self {
+        self.quotas = Arc::new(quotas);
+        self
+    }
+
+    pub(crate) fn with_interceptors(mut self, interceptors: Interceptors) -> Self {
+        self.interceptors = Arc::new(interceptors);
+        self
+    }
+
+    pub(crate) fn with_traffic_accounting(self, accounting: TrafficAccounting) -> Self {
+        zwrite!(self.tables.tables).accounting = accounting;
+        self
+    }
+
+    pub(crate) fn with_static_routes(self, static_routes: StaticRoutes) -> Self {
+        zwrite!(self.tables.tables).static_routes = static_routes;
+        self
+    }
+
+    pub(crate) fn with_memory_budget(self, memory: Arc<MemoryBudget>) -> Self {
+        let recoveries = memory.recoveries();
+        zwrite!(self.tables.tables).memory = memory;
+        // The task ends with the budget, kept alive by the tables
+        let tables = Arc::downgrade(&self.tables);
+        executor::spawn(async move {
+            while recoveries.recv_async().await.is_ok() {
+                match tables.upgrade() {
+                    Some(tables) => redeclare_shed_declarations(&tables),
+                    None => break,
+                }
+            }
+        });
+        self
+    }
+
+    pub(crate) fn with_peer_forwarding(self, forwarding: bool) -> Self {
+        zwrite!(self.tables.tables).peer_forwarding = forwarding;
+        self
+    }
+
+    pub(crate) fn with_congestion_drop_notification(self, interval: Option<Duration>) -> Self {
+        zwrite!(self.tables.tables).congestion_drop_interval = interval;
+        self
+    }
+
+    pub(crate) fn with_latched_samples(self, max_samples: usize) -> Self {
+        zwrite!(self.tables.tables).latched = LatchedSamples::new(max_samples);
+        self
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn init_link_state(
         &mut self,
@@ -528,9 +644,20 @@ impl Router {
         &self,
         transport: TransportUnicast,
     ) -> ZResult<Arc<LinkStateInterceptor>> {
+        let whatami = transport.get_whatami()?;
+        let context = InterceptorContext {
+            zid: transport.get_zid()?,
+            whatami,
+        };
+        let links = transport.get_links()?;
+        let interceptors = self.interceptors.chain(&context, &links)?;
+        let mux = match interceptors.egress_filter() {
+            Some(egress) => Mux::new(transport.clone()).with_egress(egress),
+            None => Mux::new(transport.clone()),
+        };
+
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
         let mut tables = zwrite!(self.tables.tables);
-        let whatami = transport.get_whatami()?;
 
         let link_id = match (self.whatami, whatami) {
             (WhatAmI::Router, WhatAmI::Router) => tables
@@ -557,17 +684,27 @@ impl Router {
             );
         }
 
+        if tables.static_routes.is_enabled() {
+            tables.static_routes.bind(&context.zid, &links);
+        }
+
+        let shm = transport.is_shm()?;
+        let quotas = self.quotas.enforcer(&transport.get_zid()?);
         let handler = Arc::new(LinkStateInterceptor::new(
             transport.clone(),
             self.tables.clone(),
+            self.quotas.clone(),
+            quotas,
+            interceptors,
             Face {
                 tables: self.tables.clone(),
                 state: tables
                     .open_net_face(
                         transport.get_zid().unwrap(),
                         whatami,
-                        Arc::new(Mux::new(transport)),
+                        Arc::new(mux),
                         link_id,
+                        shm,
                     )
                     .upgrade()
                     .unwrap(),
@@ -598,16 +735,69 @@ pub struct LinkStateInterceptor {
     pub(crate) tables: Arc<TablesLock>,
     pub(crate) face: Face,
     pub(crate) demux: DeMux<Face>,
+    quotas: Arc<Quotas>,
+    quota_enforcer: Option<QuotaEnforcer>,
+    quota_exceeded: AtomicBool,
+    interceptors: InterceptorChain,
 }
 
 impl LinkStateInterceptor {
-    fn new(transport: TransportUnicast, tables: Arc<TablesLock>, face: Face) -> Self {
+    fn new(
+        transport: TransportUnicast,
+        tables: Arc<TablesLock>,
+        quotas: Arc<Quotas>,
+        quota_enforcer: Option<QuotaEnforcer>,
+        interceptors: InterceptorChain,
+        face: Face,
+    ) -> Self {
         LinkStateInterceptor {
             transport,
             tables,
             face: face.clone(),
             demux: DeMux::new(face),
+            quotas,
+            quota_enforcer,
+            quota_exceeded: AtomicBool::new(false),
+            interceptors,
+        }
+    }
+
+    fn handle_quota_violation(&self, violation: QuotaViolation) {
+        // Only the first violation is reported, the transport is being closed
+        if self.quota_exceeded.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        log::warn!(
+            "Closing transport with {}: quota {} exceeded",
+            self.face.state.zid,
+            violation
+        );
+        self.quotas.violated(violation);
+        audit::record(AuditEvent::AccessDenied {
+            zid: self.face.state.zid,
+            reason: format!("quota {violation} exceeded"),
+        });
+        let transport = self.transport.clone();
+        executor::spawn(async move {
+            let _ = transport
+                .close_with_reason(tmsg::close_reason::QUOTA_EXCEEDED)
+                .await;
+        });
+    }
+
+    fn handle_message_with_quotas(
+        &self,
+        enforcer: &QuotaEnforcer,
+        msg: ZenohMessage,
+    ) -> ZResult<()> {
+        if self.quota_exceeded.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        if let Err(violation) = enforcer.check_message(&self.face, &msg) {
+            self.handle_quota_violation(violation);
+            return Ok(());
         }
+        self.demux.handle_message(msg)
     }
 }
 
@@ -698,7 +888,16 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
 
                 Ok(())
             }
-            _ => self.demux.handle_message(msg),
+            _ => {
+                let msg = match self.interceptors.ingress(msg) {
+                    Some(msg) => msg,
+                    None => return Ok(()),
+                };
+                match &self.quota_enforcer {
+                    Some(enforcer) => self.handle_message_with_quotas(enforcer, msg),
+                    None => self.demux.handle_message(msg),
+                }
+            }
         }
     }
 
@@ -0,0 +1,186 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use std::sync::Arc;
+use zenoh_config::{Config, EndPoint};
+use zenoh_link::Link;
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::core::{Locator, ZenohId};
+use zenoh_result::{bail, ZResult};
+
+/// The next hop of a static route.
+enum NextHop {
+    Zid(ZenohId),
+    /// The zenoh id of the node connected with the endpoint is known once its session is opened.
+    Endpoint(Locator, Option<ZenohId>),
+}
+
+impl NextHop {
+    fn zid(&self) -> Option<&ZenohId> {
+        match self {
+            NextHop::Zid(zid) => Some(zid),
+            NextHop::Endpoint(_, zid) => zid.as_ref(),
+        }
+    }
+}
+
+struct StaticRoute {
+    key_exprs: Vec<OwnedKeyExpr>,
+    next_hop: NextHop,
+    // The face of the next hop while it is connected
+    face: Option<Arc<FaceState>>,
+}
+
+/// The static routes configured on a router or peer, see
+/// [`RoutingConf`](zenoh_config::RoutingConf).
+///
+/// The data routed on a key expression included in the key expressions of a static route is
+/// only forwarded to its next hop, and to the local sessions, while the next hop is connected.
+/// The first matching static route whose next hop is connected applies. The static routes
+/// matching a resource are resolved once with its routes, see [`Resource::static_routes`].
+///
+/// [`Resource::static_routes`]: super::resource::Resource::static_routes
+#[derive(Default)]
+pub(crate) struct StaticRoutes {
+    routes: Vec<StaticRoute>,
+}
+
+impl StaticRoutes {
+    pub(crate) fn from_config(config: &Config) -> ZResult<Self> {
+        let mut routes = vec![];
+        for conf in config.routing().static_routes() {
+            let next_hop = match (&conf.zid, &conf.endpoint) {
+                (Some(zid), None) => NextHop::Zid(*zid),
+                (None, Some(endpoint)) => NextHop::Endpoint(endpoint.to_locator(), None),
+                _ => bail!(
+                    "Static route {:?}: exactly one of `zid` and `endpoint` must be set",
+                    conf.key_exprs
+                ),
+            };
+            routes.push(StaticRoute {
+                key_exprs: conf.key_exprs.clone(),
+                next_hop,
+                face: None,
+            });
+        }
+        Ok(StaticRoutes { routes })
+    }
+
+    #[inline]
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.routes.is_empty()
+    }
+
+    /// Binds the static routes whose next hop is given by an endpoint to the node `zid`
+    /// if it is connected with one of `links`.
+    pub(crate) fn bind(&mut self, zid: &ZenohId, links: &[Link]) {
+        for route in &mut self.routes {
+            if let NextHop::Endpoint(locator, bound) = &mut route.next_hop {
+                if links.iter().any(|link| link.dst == *locator) {
+                    log::debug!("Static route {:?} bound to {}", route.key_exprs, zid);
+                    *bound = Some(*zid);
+                }
+            }
+        }
+    }
+
+    /// Records the newly opened `face` as connecting the next hop of the static routes to it.
+    pub(crate) fn connect(&mut self, face: &Arc<FaceState>) {
+        for route in &mut self.routes {
+            if route.next_hop.zid() == Some(&face.zid) {
+                route.face = Some(face.clone());
+            }
+        }
+    }
+
+    /// Records the closed `face` as no longer connecting the next hop of the static routes.
+    pub(crate) fn disconnect(&mut self, face: &FaceState) {
+        for route in &mut self.routes {
+            if route.face.as_ref().map(|f| f.id) == Some(face.id) {
+                route.face = None;
+            }
+        }
+    }
+
+    /// Returns the indexes of the static routes including `key_expr`.
+    pub(crate) fn matching(&self, key_expr: &str) -> Vec<usize> {
+        match keyexpr::new(key_expr) {
+            Ok(key_expr) => self
+                .routes
+                .iter()
+                .enumerate()
+                .filter(|(_, route)| route.key_exprs.iter().any(|ke| ke.includes(key_expr)))
+                .map(|(idx, _)| idx)
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Returns the face of the first connected next hop of the `matching` static routes.
+    pub(crate) fn next_hop(&self, matching: &[usize]) -> Option<Arc<FaceState>> {
+        matching
+            .iter()
+            .find_map(|idx| self.routes.get(*idx)?.face.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn static_routes() {
+        use super::StaticRoutes;
+        use zenoh_config::{Config, ValidatedMap, ZenohId};
+        use zenoh_link::Link;
+
+        let routes = StaticRoutes::from_config(&Config::default()).unwrap();
+        assert!(!routes.is_enabled());
+
+        let mut config = Config::default();
+        config
+            .insert_json5(
+                "routing/static_routes",
+                r#"[{ key_exprs: ["demo/**"], zid: "a1", endpoint: "tcp/127.0.0.1:7447" }]"#,
+            )
+            .unwrap();
+        assert!(StaticRoutes::from_config(&config).is_err());
+
+        let mut config = Config::default();
+        config
+            .insert_json5(
+                "routing/static_routes",
+                r#"[{ key_exprs: ["demo/**"], endpoint: "tcp/127.0.0.1:7447" }]"#,
+            )
+            .unwrap();
+        let mut routes = StaticRoutes::from_config(&config).unwrap();
+        assert!(routes.is_enabled());
+        assert!(routes.routes[0].next_hop.zid().is_none());
+        assert_eq!(routes.matching("demo/a"), vec![0]);
+        assert!(routes.matching("other/a").is_empty());
+        assert!(routes.next_hop(&[0]).is_none());
+
+        let zid = ZenohId::rand();
+        let link = |dst: &str| Link {
+            src: "tcp/127.0.0.1:40000".parse().unwrap(),
+            dst: dst.parse().unwrap(),
+            group: None,
+            mtu: u16::MAX,
+            is_reliable: true,
+            is_streamed: true,
+        };
+        routes.bind(&zid, &[link("tcp/127.0.0.1:7448")]);
+        assert!(routes.routes[0].next_hop.zid().is_none());
+        routes.bind(&zid, &[link("tcp/127.0.0.1:7447")]);
+        assert_eq!(routes.routes[0].next_hop.zid(), Some(&zid));
+    }
+}
@@ -0,0 +1,227 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use zenoh_config::Config;
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::core::QueryTarget;
+
+/// The messages and bytes routed on the key expressions included in a configured key expression.
+#[derive(Default)]
+pub(crate) struct TrafficCounters {
+    rx_msgs: AtomicUsize,
+    rx_bytes: AtomicUsize,
+    tx_msgs: AtomicUsize,
+    tx_bytes: AtomicUsize,
+}
+
+impl TrafficCounters {
+    #[inline]
+    pub(crate) fn rx(&self, bytes: usize) {
+        self.rx_msgs.fetch_add(1, Ordering::Relaxed);
+        self.rx_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn tx(&self, msgs: usize, bytes: usize) {
+        self.tx_msgs.fetch_add(msgs, Ordering::Relaxed);
+        self.tx_bytes.fetch_add(msgs * bytes, Ordering::Relaxed);
+    }
+
+    fn json(&self) -> serde_json::Value {
+        json!({
+            "rx_msgs": self.rx_msgs.load(Ordering::Relaxed),
+            "rx_bytes": self.rx_bytes.load(Ordering::Relaxed),
+            "tx_msgs": self.tx_msgs.load(Ordering::Relaxed),
+            "tx_bytes": self.tx_bytes.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// The traffic accounting configured on a router or peer, see
+/// [`RoutingConf`](zenoh_config::RoutingConf).
+///
+/// A data message is received (rx) once from the face it comes from, including the local
+/// session, and transmitted (tx) once to each face it is routed to.
+#[derive(Default)]
+pub(crate) struct TrafficAccounting {
+    key_exprs: Vec<(OwnedKeyExpr, Arc<TrafficCounters>)>,
+}
+
+impl TrafficAccounting {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        TrafficAccounting {
+            key_exprs: config
+                .routing()
+                .accounting()
+                .iter()
+                .map(|key_expr| (key_expr.clone(), Arc::new(TrafficCounters::default())))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.key_exprs.is_empty()
+    }
+
+    /// Returns the counters of the configured key expressions including `key_expr`.
+    pub(crate) fn matching(&self, key_expr: &str) -> Vec<Arc<TrafficCounters>> {
+        match keyexpr::new(key_expr) {
+            Ok(key_expr) => self
+                .key_exprs
+                .iter()
+                .filter(|(configured, _)| configured.includes(key_expr))
+                .map(|(_, counters)| counters.clone())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    pub(crate) fn json(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.key_exprs
+                .iter()
+                .map(|(key_expr, counters)| (key_expr.to_string(), counters.json()))
+                .collect(),
+        )
+    }
+}
+
+/// The queries routed with a given target and the destinations they were routed to.
+#[derive(Default)]
+struct FanoutCounters {
+    queries: AtomicUsize,
+    destinations: AtomicUsize,
+    max_destinations: AtomicUsize,
+    last_destinations: AtomicUsize,
+}
+
+impl FanoutCounters {
+    fn record(&self, destinations: usize) {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        self.destinations.fetch_add(destinations, Ordering::Relaxed);
+        self.max_destinations
+            .fetch_max(destinations, Ordering::Relaxed);
+        self.last_destinations
+            .store(destinations, Ordering::Relaxed);
+    }
+
+    fn json(&self) -> serde_json::Value {
+        json!({
+            "queries": self.queries.load(Ordering::Relaxed),
+            "destinations": self.destinations.load(Ordering::Relaxed),
+            "max_destinations": self.max_destinations.load(Ordering::Relaxed),
+            "last_destinations": self.last_destinations.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// The fan-out of the queries routed by a router or peer, per query target.
+///
+/// A destination is a face the query is routed to: a local or remote session with matching
+/// queryables, or the next hop towards such sessions. The queries answered by this node only
+/// are routed to no destination.
+#[derive(Default)]
+pub(crate) struct QueryFanout {
+    best_matching: FanoutCounters,
+    all: FanoutCounters,
+    all_complete: FanoutCounters,
+    #[cfg(feature = "complete_n")]
+    complete: FanoutCounters,
+}
+
+impl QueryFanout {
+    #[inline]
+    pub(crate) fn record(&self, target: &QueryTarget, destinations: usize) {
+        let counters = match target {
+            QueryTarget::BestMatching => &self.best_matching,
+            QueryTarget::All => &self.all,
+            QueryTarget::AllComplete => &self.all_complete,
+            #[cfg(feature = "complete_n")]
+            QueryTarget::Complete(_) => &self.complete,
+        };
+        counters.record(destinations);
+    }
+
+    #[allow(unused_mut)]
+    pub(crate) fn json(&self) -> serde_json::Value {
+        let mut json = json!({
+            "best_matching": self.best_matching.json(),
+            "all": self.all.json(),
+            "all_complete": self.all_complete.json(),
+        });
+        #[cfg(feature = "complete_n")]
+        {
+            json["complete"] = self.complete.json();
+        }
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn query_fanout() {
+        use super::QueryFanout;
+        use zenoh_protocol::core::QueryTarget;
+
+        let fanout = QueryFanout::default();
+        fanout.record(&QueryTarget::All, 3);
+        fanout.record(&QueryTarget::All, 1);
+        fanout.record(&QueryTarget::BestMatching, 1);
+
+        let json = fanout.json();
+        assert_eq!(json["all"]["queries"], 2);
+        assert_eq!(json["all"]["destinations"], 4);
+        assert_eq!(json["all"]["max_destinations"], 3);
+        assert_eq!(json["all"]["last_destinations"], 1);
+        assert_eq!(json["best_matching"]["queries"], 1);
+        assert_eq!(json["all_complete"]["queries"], 0);
+    }
+
+    #[test]
+    fn traffic_accounting() {
+        use super::TrafficAccounting;
+        use zenoh_config::{Config, ValidatedMap};
+
+        let accounting = TrafficAccounting::from_config(&Config::default());
+        assert!(!accounting.is_enabled());
+
+        let mut config = Config::default();
+        config
+            .insert_json5("routing/accounting", r#"["demo/**", "demo/example/*"]"#)
+            .unwrap();
+        let accounting = TrafficAccounting::from_config(&config);
+        assert!(accounting.is_enabled());
+
+        for counters in accounting.matching("demo/example/a") {
+            counters.rx(10);
+            counters.tx(2, 10);
+        }
+        for counters in accounting.matching("demo/other") {
+            counters.rx(5);
+        }
+        assert!(accounting.matching("other/example").is_empty());
+
+        let json = accounting.json();
+        assert_eq!(json["demo/**"]["rx_msgs"], 2);
+        assert_eq!(json["demo/**"]["rx_bytes"], 15);
+        assert_eq!(json["demo/**"]["tx_msgs"], 2);
+        assert_eq!(json["demo/**"]["tx_bytes"], 20);
+        assert_eq!(json["demo/example/*"]["rx_msgs"], 1);
+        assert_eq!(json["demo/example/*"]["tx_bytes"], 20);
+    }
+}
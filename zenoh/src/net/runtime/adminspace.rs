@@ -26,22 +26,25 @@ use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use zenoh_buffers::{SplitBuffer, ZBuf};
-use zenoh_config::ValidatedMap;
-use zenoh_core::SyncResolve;
+use zenoh_config::{PluginRestartPolicy, ValidatedMap};
+use zenoh_core::{executor, SyncResolve};
 use zenoh_protocol::{
     core::{
         key_expr::OwnedKeyExpr, Channel, CongestionControl, ConsolidationMode, KnownEncoding,
-        QueryTarget, QueryableInfo, SampleKind, SubInfo, WireExpr, ZInt, ZenohId, EMPTY_EXPR_ID,
+        Priority, QueryTarget, QueryableInfo, SampleKind, SubInfo, WireExpr, ZInt, ZenohId,
+        EMPTY_EXPR_ID,
     },
     zenoh::{DataInfo, QueryBody, RoutingContext},
 };
 use zenoh_result::ZResult;
+use zenoh_transport::capture::{self, CaptureId, CaptureSink, CapturedBatch, FileCaptureSink};
 use zenoh_transport::{Primitives, TransportUnicast};
+use zenoh_util::audit::{self, AuditEvent};
 
 pub struct AdminContext {
     runtime: Runtime,
-    plugins_mgr: Mutex<plugins::PluginsManager>,
     zid_str: String,
     version: String,
 }
@@ -54,8 +57,31 @@ pub struct AdminSpace {
     mappings: Mutex<HashMap<ZInt, String>>,
     handlers: HashMap<OwnedKeyExpr, Handler>,
     context: Arc<AdminContext>,
+    capture: Mutex<Option<CaptureId>>,
 }
 
+const CAPTURE_QUEUE_SIZE: usize = 1024;
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CaptureRequest {
+    link: Option<String>,
+    file: Option<String>,
+}
+
+// Forwards the captured batches to the task publishing them, dropping them if it lags behind
+struct ChannelCaptureSink(flume::Sender<CapturedBatch>);
+
+impl CaptureSink for ChannelCaptureSink {
+    fn capture(&self, batch: &CapturedBatch) {
+        let _ = self.0.try_send(batch.clone());
+    }
+}
+
+const PLUGIN_RESTART_MAX_ATTEMPTS: usize = 5;
+const PLUGIN_SUPERVISION_INTERVAL: Duration = Duration::from_secs(1);
+const PLUGIN_RESTART_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Clone)]
 enum PluginDiff {
     Delete(String),
@@ -64,6 +90,8 @@ enum PluginDiff {
 
 impl AdminSpace {
     pub async fn start(runtime: &Runtime, plugins_mgr: plugins::PluginsManager, version: String) {
+        // The plugins loaded by zenohd join the static ones already registered on the runtime
+        zlock!(runtime.plugins).extend(plugins_mgr);
         let zid_str = runtime.zid.to_string();
         let root_key: OwnedKeyExpr = format!("@/router/{zid_str}").try_into().unwrap();
 
@@ -93,22 +121,39 @@ impl AdminSpace {
                 .unwrap(),
             Arc::new(queryables_data),
         );
+        handlers.insert(
+            format!("@/router/{zid_str}/transport/**")
+                .try_into()
+                .unwrap(),
+            Arc::new(transports_data),
+        );
         handlers.insert(
             format!("@/router/{zid_str}/status/plugins/**")
                 .try_into()
                 .unwrap(),
             Arc::new(plugins_status),
         );
-
-        let mut active_plugins = plugins_mgr
-            .running_plugins_info()
-            .into_iter()
-            .map(|(a, b)| (a.to_string(), b.to_string()))
-            .collect::<HashMap<_, _>>();
+        handlers.insert(
+            format!("@/router/{zid_str}/status/certificates")
+                .try_into()
+                .unwrap(),
+            Arc::new(certificates_status),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/status/traffic")
+                .try_into()
+                .unwrap(),
+            Arc::new(traffic_status),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/status/queries")
+                .try_into()
+                .unwrap(),
+            Arc::new(queries_status),
+        );
 
         let context = Arc::new(AdminContext {
             runtime: runtime.clone(),
-            plugins_mgr: Mutex::new(plugins_mgr),
             zid_str,
             version,
         });
@@ -118,10 +163,30 @@ impl AdminSpace {
             mappings: Mutex::new(HashMap::new()),
             handlers,
             context,
+            capture: Mutex::new(None),
+        });
+
+        let failed_plugins = zlock!(admin.context.runtime.plugins)
+            .failed_plugins()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        for name in failed_plugins {
+            if restart_requested(&admin.context, &name) {
+                spawn_plugin_restart(admin.context.clone(), name);
+            }
+        }
+        runtime.spawn({
+            let context = admin.context.clone();
+            async move {
+                loop {
+                    task::sleep(PLUGIN_SUPERVISION_INTERVAL).await;
+                    supervise_plugins(&context);
+                }
+            }
         });
 
         let cfg_rx = admin.context.runtime.config.subscribe();
-        task::spawn({
+        executor::spawn({
             let admin = admin.clone();
             async move {
                 while let Ok(change) = cfg_rx.recv_async().await {
@@ -129,7 +194,15 @@ impl AdminSpace {
                     if !change.starts_with("plugins") {
                         continue;
                     }
+                    // The plugins being added on the runtime are started first
+                    let _changes = zlock!(admin.context.runtime.plugins_changes);
 
+                    // Plugins may have been restarted since the last change
+                    let mut active_plugins = zlock!(admin.context.runtime.plugins)
+                        .running_plugins_info()
+                        .into_iter()
+                        .map(|(a, b)| (a.to_string(), b.to_string()))
+                        .collect::<HashMap<_, _>>();
                     let requested_plugins = {
                         let cfg_guard = admin.context.runtime.config.lock();
                         cfg_guard.plugins().load_requests().collect::<Vec<_>>()
@@ -154,7 +227,7 @@ impl AdminSpace {
                         }
                         diffs.push(PluginDiff::Start(request))
                     }
-                    let mut plugins_mgr = zlock!(admin.context.plugins_mgr);
+                    let mut plugins_mgr = zlock!(admin.context.runtime.plugins);
                     for diff in diffs {
                         match diff {
                             PluginDiff::Delete(plugin) => {
@@ -201,7 +274,16 @@ impl AdminSpace {
                                             Ok(None) => {
                                                 log::warn!("Plugin `{}` was already running", name)
                                             }
-                                            Err(e) => log::error!("{}", e),
+                                            Err(e) => {
+                                                log::error!("{}", e);
+                                                if plugin.restart == PluginRestartPolicy::OnFailure
+                                                {
+                                                    spawn_plugin_restart(
+                                                        admin.context.clone(),
+                                                        name.clone(),
+                                                    );
+                                                }
+                                            }
                                         }
                                     }
                                 }
@@ -221,6 +303,7 @@ impl AdminSpace {
             &QueryableInfo {
                 complete: 0,
                 distance: 0,
+                load_balanced: false,
             },
             None,
         );
@@ -230,6 +313,160 @@ impl AdminSpace {
             &SubInfo::default(),
             None,
         );
+
+        primitives.decl_subscriber(
+            &[&root_key, "/actions/**"].concat().into(),
+            &SubInfo::default(),
+            None,
+        );
+
+        // Forward the certificate events (reload, near expiry...) to the admin space
+        forward_events(
+            runtime,
+            primitives.clone(),
+            [&root_key, "/events/certificates"].concat(),
+            zenoh_link::certificates::subscribe(),
+        );
+        forward_events(
+            runtime,
+            primitives.clone(),
+            [&root_key, "/events/slow_consumers"].concat(),
+            runtime.manager().slow_consumers().subscribe(),
+        );
+        forward_events(
+            runtime,
+            primitives,
+            [&root_key, "/events/watermarks"].concat(),
+            runtime.manager().watermarks().subscribe(),
+        );
+    }
+
+    /// Runs an action written on `@/router/<zid>/actions/<action>`:
+    /// * `transports/<zid>/close` closes the unicast transport with `<zid>`.
+    /// * `plugins/<name>/<plugin_action>` is handed over to the running plugin `<name>`.
+    /// * `capture/start` starts capturing the batches exchanged on the links, see [`Self::start_capture`].
+    /// * `capture/stop` stops the capture started through the admin space.
+    fn run_action(&self, action: &str, payload: &[u8]) -> ZResult<()> {
+        if action == "capture/start" {
+            self.start_capture(payload)
+        } else if action == "capture/stop" {
+            match zlock!(self.capture).take() {
+                Some(id) => {
+                    capture::stop(id);
+                    Ok(())
+                }
+                None => bail!("No capture running"),
+            }
+        } else if let Some(zid) = action
+            .strip_prefix("transports/")
+            .and_then(|a| a.strip_suffix("/close"))
+        {
+            let zid: ZenohId = zid.parse()?;
+            let transport = self
+                .context
+                .runtime
+                .manager()
+                .get_transport_unicast(&zid)
+                .ok_or_else(|| zerror!("No transport opened with {}", zid))?;
+            executor::spawn(async move {
+                if let Err(e) = transport.close().await {
+                    log::warn!("Error closing transport with {}: {}", zid, e);
+                }
+            });
+            Ok(())
+        } else if let Some(plugin_action) = action.strip_prefix("plugins/") {
+            let (plugin, plugin_action) = plugin_action
+                .split_once('/')
+                .ok_or_else(|| zerror!("Missing plugin action in `{}`", action))?;
+            match zlock!(self.context.runtime.plugins).plugin(plugin) {
+                Some(running) => running.adminspace_action(plugin_action, payload),
+                None => bail!("Plugin `{}` is not running", plugin),
+            }
+        } else {
+            bail!("Unknown admin space action `{}`", action)
+        }
+    }
+
+    /// Starts a capture, replacing the one previously started through the admin space.
+    ///
+    /// The payload is an optional JSON object with the fields:
+    /// * `link`: only capture the links whose description contains this string;
+    /// * `file`: write the batches to this new capture file of the configured capture directory,
+    ///   instead of publishing their decoded messages on `@/router/<zid>/capture`.
+    fn start_capture(&self, payload: &[u8]) -> ZResult<()> {
+        let request: CaptureRequest = if payload.is_empty() {
+            CaptureRequest {
+                link: None,
+                file: None,
+            }
+        } else {
+            serde_json::from_slice(payload)
+                .map_err(|e| zerror!("Invalid capture request: {}", e))?
+        };
+        let sink: Arc<dyn CaptureSink> = match request.file {
+            Some(file) => {
+                let directory = self
+                    .context
+                    .runtime
+                    .config
+                    .lock()
+                    .adminspace
+                    .capture()
+                    .directory()
+                    .clone()
+                    .ok_or_else(|| {
+                        zerror!("Captures to a file are disabled: adminspace/capture/directory is not set")
+                    })?;
+                Arc::new(FileCaptureSink::new(directory, &file)?)
+            }
+            None => {
+                let primitives = zlock!(self.primitives)
+                    .clone()
+                    .ok_or_else(|| zerror!("Admin space is not started"))?;
+                let key: WireExpr = format!("@/router/{}/capture", self.zid).into();
+                let (tx, rx) = flume::bounded::<CapturedBatch>(CAPTURE_QUEUE_SIZE);
+                // The task ends when the sink, hence the sender, is dropped on stop
+                executor::spawn(async move {
+                    while let Ok(batch) = rx.recv_async().await {
+                        let time = batch
+                            .time
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_micros() as u64;
+                        let messages = match batch.messages() {
+                            Ok(messages) => json!(messages
+                                .iter()
+                                .map(|m| format!("{m:?}"))
+                                .collect::<Vec<_>>()),
+                            Err(e) => json!(e.to_string()),
+                        };
+                        let payload = json!({
+                            "time": time,
+                            "direction": batch.direction.to_string(),
+                            "link": batch.link,
+                            "messages": messages,
+                        });
+                        primitives.send_data(
+                            &key,
+                            payload.to_string().into_bytes().into(),
+                            Channel::default(),
+                            CongestionControl::default(),
+                            Some(DataInfo {
+                                encoding: Some(KnownEncoding::AppJson.into()),
+                                ..Default::default()
+                            }),
+                            None,
+                        );
+                    }
+                });
+                Arc::new(ChannelCaptureSink(tx))
+            }
+        };
+        let id = capture::start(request.link, sink);
+        if let Some(previous) = zlock!(self.capture).replace(id) {
+            capture::stop(previous);
+        }
+        Ok(())
     }
 
     pub fn key_expr_to_string<'a>(&self, key_expr: &'a WireExpr) -> ZResult<KeyExpr<'a>> {
@@ -316,6 +553,7 @@ impl Primitives for AdminSpace {
             data_info,
         );
 
+        let actions_prefix = format!("@/router/{}/actions/", &self.context.zid_str);
         {
             let conf = self.context.runtime.config.lock();
             if !conf.adminspace.permissions().write {
@@ -323,6 +561,15 @@ impl Primitives for AdminSpace {
                     "Received PUT on '{}' but adminspace.permissions.write=false in configuration",
                     key_expr
                 );
+                let result = Err("adminspace.permissions.write=false".to_string());
+                // The actions are recorded with the same key, whether they are run or denied
+                audit::record(match key_expr.as_str().strip_prefix(&actions_prefix) {
+                    Some(action) => AuditEvent::ActionRequested {
+                        key: action.to_string(),
+                        result,
+                    },
+                    None => AuditEvent::config_changed(key_expr.to_string(), None, result),
+                });
                 return;
             }
         }
@@ -341,9 +588,15 @@ impl Primitives for AdminSpace {
                     &self.context.zid_str,
                     key
                 );
-                if let Err(e) = self.context.runtime.config.remove(key) {
+                let result = self.context.runtime.config.remove(key);
+                if let Err(e) = &result {
                     log::error!("Error deleting conf value {} : {}", key_expr, e)
                 }
+                audit::record(AuditEvent::config_changed(
+                    key.to_string(),
+                    None,
+                    result.map_err(|e| e.to_string()),
+                ));
             } else {
                 match std::str::from_utf8(&payload.contiguous()) {
                     Ok(json) => {
@@ -353,12 +606,18 @@ impl Primitives for AdminSpace {
                             key,
                             json
                         );
-                        if let Err(e) = (&self.context.runtime.config).insert_json5(key, json) {
+                        let result = (&self.context.runtime.config).insert_json5(key, json);
+                        if let Err(e) = &result {
                             error!(
                                 "Error inserting conf value /@/router/{}/config/{} : {} - {}",
                                 &self.context.zid_str, key, json, e
                             );
                         }
+                        audit::record(AuditEvent::config_changed(
+                            key.to_string(),
+                            Some(json.to_string()),
+                            result.map_err(|e| e.to_string()),
+                        ));
                     }
                     Err(e) => error!(
                         "Received non utf8 conf value on /@/router/{}/config/{} : {}",
@@ -366,6 +625,20 @@ impl Primitives for AdminSpace {
                     ),
                 }
             }
+        } else if let Some(action) = key_expr.as_str().strip_prefix(&actions_prefix) {
+            log::trace!(
+                "Running action /@/router/{}/actions/{}",
+                &self.context.zid_str,
+                action
+            );
+            let result = self.run_action(action, &payload.contiguous());
+            if let Err(e) = &result {
+                error!("Error running action {} : {}", key_expr, e);
+            }
+            audit::record(AuditEvent::ActionRequested {
+                key: action.to_string(),
+                result: result.map_err(|e| e.to_string()),
+            });
         }
     }
 
@@ -376,7 +649,10 @@ impl Primitives for AdminSpace {
         qid: ZInt,
         target: QueryTarget,
         _consolidation: ConsolidationMode,
+        timeout: Option<Duration>,
+        _destinations: Option<&[ZenohId]>,
         body: Option<QueryBody>,
+        priority: Priority,
         _routing_context: Option<RoutingContext>,
     ) {
         trace!(
@@ -395,7 +671,7 @@ impl Primitives for AdminSpace {
                     "Received GET on '{}' but adminspace.permissions.read=false in configuration",
                     key_expr
                 );
-                primitives.send_reply_final(qid);
+                primitives.send_reply_final(qid, priority);
                 return;
             }
         }
@@ -404,7 +680,7 @@ impl Primitives for AdminSpace {
             Ok(key_expr) => key_expr.into_owned(),
             Err(e) => {
                 log::error!("Unknown KeyExpr: {}", e);
-                primitives.send_reply_final(qid);
+                primitives.send_reply_final(qid, priority);
                 return;
             }
         };
@@ -420,6 +696,9 @@ impl Primitives for AdminSpace {
                 }),
                 qid,
                 zid,
+                priority,
+                deadline: timeout.map(|timeout| Instant::now() + timeout),
+                transformers: None,
                 primitives,
             }),
         };
@@ -438,6 +717,7 @@ impl Primitives for AdminSpace {
         key_expr: WireExpr,
         info: Option<DataInfo>,
         payload: ZBuf,
+        _priority: Priority,
     ) {
         trace!(
             "recv ReplyData {:?} {:?} {:?} {:?} {:?}",
@@ -449,7 +729,7 @@ impl Primitives for AdminSpace {
         );
     }
 
-    fn send_reply_final(&self, qid: ZInt) {
+    fn send_reply_final(&self, qid: ZInt, _priority: Priority) {
         trace!("recv ReplyFinal {:?}", qid);
     }
 
@@ -474,6 +754,38 @@ impl Primitives for AdminSpace {
     }
 }
 
+// Publishes the events received on `events_rx` in JSON on `key` until the runtime is closed
+fn forward_events<T: serde::Serialize + Send + 'static>(
+    runtime: &Runtime,
+    primitives: Arc<Face>,
+    key: String,
+    events_rx: flume::Receiver<T>,
+) {
+    let key: WireExpr = key.into();
+    runtime.spawn(async move {
+        while let Ok(event) = events_rx.recv_async().await {
+            let payload = match serde_json::to_vec(&event) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    log::error!("Error serializing event for {}: {}", key, e);
+                    continue;
+                }
+            };
+            primitives.send_data(
+                &key,
+                payload.into(),
+                Channel::default(),
+                CongestionControl::default(),
+                Some(DataInfo {
+                    encoding: Some(KnownEncoding::AppJson.into()),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+    });
+}
+
 fn router_data(context: &AdminContext, query: Query) {
     let reply_key: OwnedKeyExpr = format!("@/router/{}", context.zid_str).try_into().unwrap();
 
@@ -481,7 +793,7 @@ fn router_data(context: &AdminContext, query: Query) {
 
     // plugins info
     let plugins: serde_json::Value = {
-        zlock!(context.plugins_mgr)
+        zlock!(context.runtime.plugins)
             .running_plugins_info()
             .into_iter()
             .map(|(k, v)| (k, json!({ "path": v })))
@@ -497,7 +809,6 @@ fn router_data(context: &AdminContext, query: Query) {
 
     // transports info
     let transport_to_json = |transport: &TransportUnicast| {
-        #[allow(unused_mut)]
         let mut json = json!({
             "peer": transport.get_zid().map_or_else(|_| "unknown".to_string(), |p| p.to_string()),
             "whatami": transport.get_whatami().map_or_else(|_| "unknown".to_string(), |p| p.to_string()),
@@ -506,6 +817,29 @@ fn router_data(context: &AdminContext, query: Query) {
                 |links| links.iter().map(|link| link.dst.to_string()).collect()
             ),
         });
+        // The round-trip times are only measured if `transport/unicast/ping_interval` is set
+        let rtt: Vec<serde_json::Value> = transport
+            .get_links_rtt()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(link, rtt)| {
+                rtt.map(|rtt| {
+                    json!({
+                        "link": link.dst.to_string(),
+                        "samples": rtt.samples,
+                        "min_us": rtt.min.as_micros() as u64,
+                        "avg_us": rtt.avg.as_micros() as u64,
+                        "p99_us": rtt.p99.as_micros() as u64,
+                        "jitter_us": rtt.jitter.as_micros() as u64,
+                    })
+                })
+            })
+            .collect();
+        if !rtt.is_empty() {
+            json.as_object_mut()
+                .unwrap()
+                .insert("rtt".to_string(), rtt.into());
+        }
         #[cfg(feature = "stats")]
         {
             let stats = crate::prelude::Parameters::decode(selector)
@@ -527,12 +861,35 @@ fn router_data(context: &AdminContext, query: Query) {
         .map(transport_to_json)
         .collect();
 
+    let pool = transport_mgr.get_buffer_pool_stats();
+    let handshakes = transport_mgr.get_handshake_stats_unicast();
     let json = json!({
         "zid": context.zid_str,
         "version": context.version,
         "locators": locators,
         "sessions": transports,
+        "buffer_pool": {
+            "capacity": pool.capacity,
+            "size": pool.size,
+            "hits": pool.hits,
+            "misses": pool.misses,
+        },
+        "handshakes": {
+            "attempts": handshakes.attempts,
+            "successes": handshakes.successes,
+            "failures": {
+                "auth_failed": handshakes.auth_failed,
+                "incompatible_version": handshakes.incompatible_version,
+                "max_sessions": handshakes.max_sessions,
+                "max_links": handshakes.max_links,
+                "invalid_cookie": handshakes.invalid_cookie,
+                "timeout": handshakes.timeout,
+                "other": handshakes.other,
+            },
+        },
         "plugins": plugins,
+        "quota_violations": context.runtime.router.quotas.violations_json(),
+        "memory": transport_mgr.config.memory.usage(),
     });
     log::trace!("AdminSpace router_data: {:?}", json);
     if let Err(e) = query
@@ -635,11 +992,183 @@ fn queryables_data(context: &AdminContext, query: Query) {
     }
 }
 
+fn transports_data(context: &AdminContext, query: Query) {
+    for transport in context.runtime.manager().get_transports() {
+        let zid = match transport.get_zid() {
+            Ok(zid) => zid,
+            // The transport has been closed meanwhile
+            Err(_) => continue,
+        };
+        let key =
+            KeyExpr::try_from(format!("@/router/{}/transport/{}", context.zid_str, zid)).unwrap();
+        if !query.key_expr().intersects(&key) {
+            continue;
+        }
+        // The round-trip times are only measured if `transport/unicast/ping_interval` is set
+        let links: Vec<serde_json::Value> = transport
+            .get_links_rtt()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(link, rtt)| {
+                json!({
+                    "src": link.src.to_string(),
+                    "dst": link.dst.to_string(),
+                    "mtu": link.mtu,
+                    "reliable": link.is_reliable,
+                    "streamed": link.is_streamed,
+                    "rtt": rtt.map(|rtt| json!({
+                        "samples": rtt.samples,
+                        "min_us": rtt.min.as_micros() as u64,
+                        "avg_us": rtt.avg.as_micros() as u64,
+                        "p99_us": rtt.p99.as_micros() as u64,
+                        "jitter_us": rtt.jitter.as_micros() as u64,
+                    })),
+                })
+            })
+            .collect();
+        #[allow(unused_mut)]
+        let mut json = json!({
+            "zid": zid.to_string(),
+            "whatami": transport.get_whatami().map_or_else(|_| "unknown".to_string(), |w| w.to_string()),
+            "features": {
+                "qos": transport.is_qos().unwrap_or(false),
+                "shm": transport.is_shm().unwrap_or(false),
+                // Compression isn't negotiated by this version of the protocol
+                "compression": false,
+            },
+            "sn_resolution": transport.get_sn_resolution().ok(),
+            "links": links,
+        });
+        #[cfg(feature = "stats")]
+        if let Ok(stats) = transport.stats() {
+            json.as_object_mut()
+                .unwrap()
+                .insert("stats".to_string(), json!(stats));
+        }
+        log::trace!("AdminSpace transports_data: {:?}", json);
+        if let Err(e) = query
+            .reply(Ok(Sample::new(
+                key,
+                Value::from(json.to_string().as_bytes().to_vec())
+                    .encoding(KnownEncoding::AppJson.into()),
+            )))
+            .res()
+        {
+            log::error!("Error sending AdminSpace reply: {:?}", e);
+        }
+    }
+}
+
+fn restart_requested(context: &AdminContext, plugin: &str) -> bool {
+    context
+        .runtime
+        .config
+        .lock()
+        .plugins()
+        .load_requests()
+        .any(|r| r.name == plugin && r.restart == PluginRestartPolicy::OnFailure)
+}
+
+/// Stops the running plugins which crashed or exited, then restarts them should their configuration request it.
+fn supervise_plugins(context: &Arc<AdminContext>) {
+    let exited = {
+        let mut plugins_mgr = zlock!(context.runtime.plugins);
+        let exited = plugins_mgr
+            .running_plugins()
+            .filter(|(_, (_, plugin))| {
+                // A plugin panicking when asked is as good as crashed
+                !std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| plugin.is_alive()))
+                    .unwrap_or(false)
+            })
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        for name in &exited {
+            plugins_mgr.fail(name, format!("Plugin {name} exited while running"));
+        }
+        exited
+    };
+    for name in exited {
+        if restart_requested(context, &name) {
+            log::error!("Plugin `{}` exited while running, restarting it", name);
+            spawn_plugin_restart(context.clone(), name);
+        } else {
+            log::error!("Plugin `{}` exited while running", name);
+        }
+    }
+}
+
+/// Retries starting `plugin` with an exponential backoff, as long as its configuration requests it.
+fn spawn_plugin_restart(context: Arc<AdminContext>, plugin: String) {
+    executor::spawn(async move {
+        let mut backoff = PLUGIN_RESTART_INITIAL_BACKOFF;
+        for attempt in 1..=PLUGIN_RESTART_MAX_ATTEMPTS {
+            task::sleep(backoff).await;
+            backoff *= 2;
+            if !restart_requested(&context, &plugin) {
+                return;
+            }
+            let mut plugins_mgr = zlock!(context.runtime.plugins);
+            match plugins_mgr.start(&plugin, &context.runtime) {
+                Ok(Some((path, running))) => {
+                    let mut cfg_guard = context.runtime.config.lock();
+                    cfg_guard.add_plugin_validator(&plugin, running.config_checker());
+                    log::info!(
+                        "Successfully restarted plugin `{}` from {} (attempt {})",
+                        plugin,
+                        path,
+                        attempt
+                    );
+                    return;
+                }
+                Ok(None) => return,
+                Err(e) => log::warn!(
+                    "Failed to restart plugin `{}` (attempt {}/{}): {}",
+                    plugin,
+                    attempt,
+                    PLUGIN_RESTART_MAX_ATTEMPTS,
+                    e
+                ),
+            }
+        }
+        log::error!(
+            "Giving up on restarting plugin `{}` after {} attempts",
+            plugin,
+            PLUGIN_RESTART_MAX_ATTEMPTS
+        );
+    });
+}
+
 fn plugins_status(context: &AdminContext, query: Query) {
     let selector = query.selector();
-    let guard = zlock!(context.plugins_mgr);
+    let guard = zlock!(context.runtime.plugins);
     let mut root_key = format!("@/router/{}/status/plugins/", &context.zid_str);
 
+    for (name, failure) in guard.failed_plugins() {
+        with_extended_string(&mut root_key, &[name, "/__failure__"], |failure_key| {
+            if let Ok(key_expr) = KeyExpr::try_from(failure_key.clone()) {
+                if query.key_expr().intersects(&key_expr) {
+                    let value = json!({
+                        "path": failure.path,
+                        "error": failure.error,
+                        "attempts": failure.attempts,
+                    });
+                    if let Err(e) = query
+                        .reply(Ok(Sample::new(
+                            key_expr,
+                            Value::from(value.to_string().as_bytes().to_vec())
+                                .encoding(KnownEncoding::AppJson.into()),
+                        )))
+                        .res()
+                    {
+                        log::error!("Error sending AdminSpace reply: {:?}", e);
+                    }
+                }
+            } else {
+                log::error!("Error: invalid plugin failure key {}", failure_key);
+            }
+        });
+    }
+
     for (name, (path, plugin)) in guard.running_plugins() {
         with_extended_string(&mut root_key, &[name], |plugin_key| {
             with_extended_string(plugin_key, &["/__path__"], |plugin_path_key| {
@@ -702,6 +1231,69 @@ fn plugins_status(context: &AdminContext, query: Query) {
     }
 }
 
+fn certificates_status(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/status/certificates", context.zid_str)
+        .try_into()
+        .unwrap();
+
+    let json = json!(zenoh_link::certificates::list());
+    log::trace!("AdminSpace certificates_status: {:?}", json);
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json.to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn traffic_status(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/status/traffic", context.zid_str)
+        .try_into()
+        .unwrap();
+
+    let json = zread!(context.runtime.router.tables.tables)
+        .accounting
+        .json();
+    log::trace!("AdminSpace traffic_status: {:?}", json);
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json.to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+/// Replies with the fan-out of the routed queries per target: the number of `queries`, their
+/// total and maximum number of `destinations`, and the number of destinations of the last one.
+fn queries_status(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/status/queries", context.zid_str)
+        .try_into()
+        .unwrap();
+
+    let json = zread!(context.runtime.router.tables.tables)
+        .query_fanout
+        .json();
+    log::trace!("AdminSpace queries_status: {:?}", json);
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json.to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
 fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix: &mut String,
     suffixes: &[&str],
@@ -0,0 +1,339 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Scouting through mDNS/DNS-SD, alongside the multicast scouting.
+//!
+//! The instances advertise their tcp and udp locators as `_zenoh._tcp.local.` and
+//! `_zenoh._udp.local.` services named after their zid, along with the `zid`, `whatami`,
+//! `locators` and Hello metadata TXT records. The services advertised by other tools without
+//! these records are considered as routers reachable on their addresses and port.
+use super::orchestrator::{hello_metadata, HelloFilter, Loop};
+use super::Runtime;
+use futures::prelude::*;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+use zenoh_link::Locator;
+use zenoh_protocol::{
+    core::{whatami::WhatAmIMatcher, WhatAmI, ZenohId},
+    scouting::{Hello, HelloMetadata},
+};
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The advertised and browsed service types, along with the protocol of their locators.
+const SERVICE_TYPES: [(&str, &str); 2] =
+    [("tcp", "_zenoh._tcp.local."), ("udp", "_zenoh._udp.local.")];
+
+/// A [`ServiceDaemon`] shut down once dropped.
+struct Daemon(ServiceDaemon);
+
+impl Daemon {
+    fn new() -> ZResult<Self> {
+        ServiceDaemon::new()
+            .map(Daemon)
+            .map_err(|e| zerror!("Unable to start mDNS daemon: {}", e).into())
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.shutdown() {
+            log::debug!("Unable to shut down mDNS daemon: {}", e);
+        }
+    }
+}
+
+/// Returns the services advertising the `locators` of the instance `zid`.
+fn services(
+    zid: &ZenohId,
+    whatami: WhatAmI,
+    locators: &[Locator],
+    metadata: Option<&HelloMetadata>,
+) -> Vec<ServiceInfo> {
+    let mut services = vec![];
+    for (protocol, ty) in SERVICE_TYPES {
+        let locators: Vec<&Locator> = locators
+            .iter()
+            .filter(|l| l.protocol().as_str() == protocol)
+            .collect();
+        let addrs: Vec<SocketAddr> = locators
+            .iter()
+            .filter_map(|l| l.address().as_str().parse().ok())
+            .collect();
+        let port = match addrs.first() {
+            Some(addr) => addr.port(),
+            None => continue,
+        };
+        let ips: Vec<IpAddr> = addrs.iter().map(SocketAddr::ip).collect();
+        let mut properties = HashMap::new();
+        properties.insert("zid".to_string(), zid.to_string());
+        properties.insert("whatami".to_string(), whatami.to_string());
+        properties.insert(
+            "locators".to_string(),
+            locators
+                .iter()
+                .map(|l| l.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        if let Some(metadata) = metadata {
+            if let Some(cluster) = &metadata.cluster {
+                properties.insert("cluster".to_string(), cluster.clone());
+            }
+            if let Some(region) = &metadata.region {
+                properties.insert("region".to_string(), region.clone());
+            }
+            if let Some(load) = metadata.load {
+                properties.insert("load".to_string(), load.to_string());
+            }
+            if !metadata.features.is_empty() {
+                properties.insert("features".to_string(), metadata.features.join(","));
+            }
+        }
+        match ServiceInfo::new(
+            ty,
+            &zid.to_string(),
+            &format!("{zid}.local."),
+            &ips[..],
+            port,
+            properties,
+        ) {
+            Ok(service) => services.push(service),
+            Err(e) => log::warn!("Unable to build mDNS service {} for {}: {}", ty, zid, e),
+        }
+    }
+    services
+}
+
+/// Returns the Hello equivalent to a service of type `protocol` resolved through mDNS.
+fn service_to_hello(protocol: &str, service: &ServiceInfo) -> Option<Hello> {
+    let property = |key: &str| service.get_property_val_str(key);
+    let zid = property("zid").and_then(|zid| ZenohId::from_str(zid).ok());
+    let whatami = match property("whatami") {
+        Some(whatami) => WhatAmI::from_str(whatami).ok()?,
+        None => WhatAmI::Router,
+    };
+    let locators: Vec<Locator> = match property("locators") {
+        Some(locators) => locators
+            .split(',')
+            .filter_map(|l| Locator::from_str(l).ok())
+            .collect(),
+        None => service
+            .get_addresses()
+            .iter()
+            .filter_map(|ip| {
+                let addr = SocketAddr::new(*ip, service.get_port());
+                Locator::from_str(&format!("{protocol}/{addr}")).ok()
+            })
+            .collect(),
+    };
+    let metadata = HelloMetadata {
+        cluster: property("cluster").map(str::to_string),
+        region: property("region").map(str::to_string),
+        load: property("load").and_then(|load| load.parse().ok()),
+        features: property("features")
+            .map(|f| f.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+    };
+    Some(Hello {
+        zid,
+        whatami,
+        locators,
+        metadata: (!metadata.is_empty()).then_some(metadata),
+    })
+}
+
+impl Runtime {
+    /// Browses the zenoh services through mDNS, calling `f` with the Hello of each resolved
+    /// instance matching `what` until it returns [`Loop::Break`].
+    async fn mdns_browse<Fut, F>(daemon: &Daemon, what: WhatAmIMatcher, f: F) -> ZResult<()>
+    where
+        F: Fn(Hello) -> Fut + std::marker::Send + std::marker::Sync + Clone,
+        Fut: Future<Output = Loop> + std::marker::Send,
+    {
+        let mut receivers = vec![];
+        for (protocol, ty) in SERVICE_TYPES {
+            match daemon.0.browse(ty) {
+                Ok(receiver) => receivers.push(receiver.into_stream().map(move |e| (protocol, e))),
+                Err(e) => bail!("Unable to browse mDNS services {}: {}", ty, e),
+            }
+        }
+        let mut events = futures::stream::select_all(receivers);
+        while let Some((protocol, event)) = events.next().await {
+            if let ServiceEvent::ServiceResolved(service) = event {
+                log::trace!("Resolved mDNS service {}", service.get_fullname());
+                match service_to_hello(protocol, &service) {
+                    Some(hello) if what.matches(hello.whatami) => {
+                        if let Loop::Break = f(hello).await {
+                            break;
+                        }
+                    }
+                    Some(_) => (),
+                    None => log::debug!(
+                        "Ignore mDNS service {} with invalid records",
+                        service.get_fullname()
+                    ),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Advertises this instance through mDNS if `advertise`, and connects to the browsed
+    /// instances matching `autoconnect`.
+    pub(crate) async fn start_mdns(
+        &self,
+        advertise: bool,
+        autoconnect: WhatAmIMatcher,
+    ) -> ZResult<()> {
+        let daemon = Daemon::new()?;
+        if advertise {
+            let metadata = hello_metadata(&self.config.lock());
+            for service in services(
+                &self.zid,
+                self.whatami,
+                &self.get_locators(),
+                metadata.as_ref(),
+            ) {
+                let name = service.get_fullname().to_string();
+                match daemon.0.register(service) {
+                    Ok(()) => log::info!("Advertising mDNS service {}", name),
+                    Err(e) => bail!("Unable to advertise mDNS service {}: {}", name, e),
+                }
+            }
+        }
+        if autoconnect.is_empty() {
+            // The daemon must stay alive for the services to remain advertised
+            self.spawn(async move {
+                let _daemon = daemon;
+                future::pending::<()>().await
+            });
+            return Ok(());
+        }
+        let this = self.clone();
+        self.spawn(async move {
+            let filter = &HelloFilter::from_config(&this.config.lock());
+            let this = &this;
+            let res = Runtime::mdns_browse(&daemon, autoconnect, move |hello| async move {
+                if !filter.matches(&hello) {
+                    log::debug!("Ignore mDNS scouted {:?} not matching {:?}", hello, filter);
+                } else if hello.locators.is_empty() {
+                    log::warn!("Resolved mDNS service with no locators: {:?}", hello);
+                } else {
+                    match &hello.zid {
+                        Some(zid) => {
+                            this.connect_scouted_peer(filter, zid, &hello.locators)
+                                .await
+                        }
+                        None => {
+                            if this
+                                .connect_scouted(filter, &hello.locators)
+                                .await
+                                .is_none()
+                            {
+                                log::warn!("Unable to connect to mDNS scouted {:?}", hello);
+                            }
+                        }
+                    }
+                }
+                Loop::Continue
+            })
+            .await;
+            if let Err(e) = res {
+                log::error!("{}", e);
+            }
+        });
+        Ok(())
+    }
+
+    /// Connects to the first instance matching `what` browsed through mDNS within `timeout`.
+    pub(crate) async fn mdns_connect_first(
+        &self,
+        what: WhatAmIMatcher,
+        timeout: Duration,
+    ) -> ZResult<()> {
+        let daemon = Daemon::new()?;
+        let filter = &HelloFilter::from_config(&self.config.lock());
+        let browse = Runtime::mdns_browse(&daemon, what, move |hello| async move {
+            log::info!("Found {:?} through mDNS", hello);
+            if filter.matches(&hello) {
+                if let Some(transport) = self.connect_scouted(filter, &hello.locators).await {
+                    log::debug!(
+                        "Successfully connected to mDNS scouted {:?} via {:?}",
+                        hello,
+                        transport
+                    );
+                    return Loop::Break;
+                }
+                log::warn!("Unable to connect to mDNS scouted {:?}", hello);
+            }
+            Loop::Continue
+        });
+        let timeout = async {
+            async_std::task::sleep(timeout).await;
+            bail!("timeout")
+        };
+        async_std::prelude::FutureExt::race(browse, timeout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mdns_services() {
+        let zid = ZenohId::rand();
+        let locators: Vec<Locator> = [
+            "tcp/192.168.1.2:7447",
+            "udp/192.168.1.2:7448",
+            "ws/192.168.1.2:8000",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+        let metadata = HelloMetadata {
+            cluster: Some("cluster-a".to_string()),
+            ..Default::default()
+        };
+        let services = services(&zid, WhatAmI::Peer, &locators, Some(&metadata));
+        assert_eq!(services.len(), 2);
+
+        let hello = service_to_hello("tcp", &services[0]).unwrap();
+        assert_eq!(hello.zid, Some(zid));
+        assert_eq!(hello.whatami, WhatAmI::Peer);
+        assert_eq!(hello.locators, locators[..1]);
+        assert_eq!(hello.metadata, Some(metadata));
+
+        // A service advertised by another tool
+        let service = ServiceInfo::new(
+            SERVICE_TYPES[0].1,
+            "router",
+            "router.local.",
+            "192.168.1.3",
+            7447,
+            HashMap::<String, String>::new(),
+        )
+        .unwrap();
+        let hello = service_to_hello("tcp", &service).unwrap();
+        assert_eq!(hello.zid, None);
+        assert_eq!(hello.whatami, WhatAmI::Router);
+        assert_eq!(
+            hello.locators,
+            vec!["tcp/192.168.1.3:7447".parse().unwrap()]
+        );
+    }
+}
@@ -12,21 +12,26 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-//! ⚠️ WARNING ⚠️
+//! The [`Runtime`] of zenoh, holding the transports, routing tables and scouting of a zenoh instance.
 //!
-//! This module is intended for Zenoh's internal use.
-//!
-//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//! A runtime may be shared by several sessions initialized with [`init`](crate::init).
+//! The rest of this module is intended for Zenoh's internal use.
 mod adminspace;
+#[cfg(feature = "mdns")]
+mod mdns;
 pub mod orchestrator;
 
 use super::routing;
 use super::routing::pubsub::full_reentrant_route_data;
+use super::routing::quotas::Quotas;
 use super::routing::router::{LinkStateInterceptor, Router};
-use crate::config::{unwrap_or_default, Config, ModeDependent, Notifier};
+use super::routing::static_routes::StaticRoutes;
+use super::routing::traffic::TrafficAccounting;
+use crate::config::{unwrap_or_default, Config, ModeDependent, Notifier, ValidatedMap};
+use crate::interceptor::Interceptors;
+use crate::plugins::sealed::PluginsManager;
 use crate::GIT_VERSION;
 pub use adminspace::AdminSpace;
-use async_std::task::JoinHandle;
 use futures::stream::StreamExt;
 use futures::Future;
 use std::any::Any;
@@ -35,17 +40,21 @@ use std::time::Duration;
 use stop_token::future::FutureExt;
 use stop_token::{StopSource, TimedOutError};
 use uhlc::{HLCBuilder, HLC};
+use zenoh_core::executor::{self, ExecutorMode, JoinHandle};
 use zenoh_link::{EndPoint, Link};
 use zenoh_protocol::{
     core::{whatami::WhatAmIMatcher, Locator, WhatAmI, ZenohId},
     zenoh::{ZenohBody, ZenohMessage},
 };
-use zenoh_result::{bail, ZResult};
+use zenoh_result::{bail, zerror, ZResult};
 use zenoh_sync::get_mut_unchecked;
+use zenoh_transport::memory::MemoryBudget;
 use zenoh_transport::{
     TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
     TransportPeer, TransportPeerEventHandler, TransportUnicast,
 };
+use zenoh_util::audit::{self, AuditEvent};
+use zenoh_util::watchdog::{self, WatchdogPolicy};
 
 pub struct RuntimeState {
     pub zid: ZenohId,
@@ -56,9 +65,16 @@ pub struct RuntimeState {
     pub transport_handlers: std::sync::RwLock<Vec<Arc<dyn TransportEventHandler>>>,
     pub(crate) locators: std::sync::RwLock<Vec<Locator>>,
     pub hlc: Option<Arc<HLC>>,
+    // The plugins running on the runtime, exposed in the admin space
+    pub(crate) plugins: std::sync::Mutex<PluginsManager>,
+    // Held while a plugin is added, for the admin space to apply the plugins configuration changes once it runs
+    pub(crate) plugins_changes: std::sync::Mutex<()>,
     pub(crate) stop_source: std::sync::RwLock<Option<StopSource>>,
 }
 
+/// A zenoh runtime, shared by the sessions and plugins initialized on it.
+///
+/// Cloning a runtime is cheap: the clones refer to the same transports and scouting.
 #[derive(Clone)]
 pub struct Runtime {
     state: Arc<RuntimeState>,
@@ -73,6 +89,7 @@ impl std::ops::Deref for Runtime {
 }
 
 impl Runtime {
+    /// Opens and starts a runtime: its listeners, connections to the configured endpoints and scouting.
     pub async fn new(config: Config) -> ZResult<Runtime> {
         let mut runtime = Runtime::init(config).await?;
         match runtime.start().await {
@@ -90,9 +107,38 @@ impl Runtime {
 
         log::info!("Using PID: {}", zid);
 
+        // The executor is shared by all the runtimes of the process
+        executor::get_or_init(|| {
+            let mode = ExecutorMode::from_config(
+                &unwrap_or_default!(config.executor().mode()),
+                unwrap_or_default!(config.executor().threads()),
+            )?;
+            log::debug!("Using executor: {:?}", mode);
+            Ok(mode)
+        })?;
+
+        // The watchdog is shared by all the runtimes of the process
+        let watchdog_threshold = unwrap_or_default!(config.watchdog().threshold());
+        if watchdog_threshold > 0 && !watchdog::is_enabled() {
+            let policy: WatchdogPolicy = unwrap_or_default!(config.watchdog().policy()).parse()?;
+            watchdog::start(Duration::from_millis(watchdog_threshold), policy)?;
+        }
+
         let whatami = unwrap_or_default!(config.mode());
-        let hlc = (*unwrap_or_default!(config.timestamping().enabled().get(whatami)))
-            .then(|| Arc::new(HLCBuilder::new().with_id(uhlc::ID::from(&zid)).build()));
+        let peer_forwarding = unwrap_or_default!(config.routing().peer().forwarding());
+        // A forwarding peer, or a node with static routes, identifies the data it already routed
+        // by their timestamp
+        let timestamping = *unwrap_or_default!(config.timestamping().enabled().get(whatami))
+            || (whatami == WhatAmI::Peer && peer_forwarding)
+            || !config.routing().static_routes().is_empty();
+        let hlc = timestamping.then(|| {
+            Arc::new(
+                HLCBuilder::new()
+                    .with_id(uhlc::ID::from(&zid))
+                    .with_clock(crate::time::clock())
+                    .build(),
+            )
+        });
         let drop_future_timestamp =
             unwrap_or_default!(config.timestamping().drop_future_timestamp());
 
@@ -109,17 +155,38 @@ impl Runtime {
             && unwrap_or_default!(config.routing().peer().mode()) == *"linkstate";
         let router_peers_failover_brokering =
             unwrap_or_default!(config.routing().router().peers_failover_brokering());
+        let congestion_drop_notify =
+            unwrap_or_default!(config.routing().congestion_drop().notify());
+        let congestion_drop_interval = congestion_drop_notify.then(|| {
+            Duration::from_millis(unwrap_or_default!(config
+                .routing()
+                .congestion_drop()
+                .interval()))
+        });
+        let latched_max_samples = unwrap_or_default!(config.routing().latched().max_samples());
         let queries_default_timeout =
             Duration::from_millis(unwrap_or_default!(config.queries_default_timeout()));
+        // The memory budget is shared by the transports and the routing tables
+        let memory = Arc::new(MemoryBudget::from_config(&config)?);
 
-        let router = Arc::new(Router::new(
-            zid,
-            whatami,
-            hlc.clone(),
-            drop_future_timestamp,
-            router_peers_failover_brokering,
-            queries_default_timeout,
-        ));
+        let router = Arc::new(
+            Router::new(
+                zid,
+                whatami,
+                hlc.clone(),
+                drop_future_timestamp,
+                router_peers_failover_brokering,
+                queries_default_timeout,
+            )
+            .with_quotas(Quotas::from_config(&config))
+            .with_interceptors(Interceptors::from_config(&config))
+            .with_traffic_accounting(TrafficAccounting::from_config(&config))
+            .with_static_routes(StaticRoutes::from_config(&config)?)
+            .with_memory_budget(memory.clone())
+            .with_peer_forwarding(peer_forwarding)
+            .with_congestion_drop_notification(congestion_drop_interval)
+            .with_latched_samples(latched_max_samples),
+        );
 
         let handler = Arc::new(RuntimeTransportEventHandler {
             runtime: std::sync::RwLock::new(None),
@@ -130,6 +197,7 @@ impl Runtime {
             .await?
             .whatami(whatami)
             .zid(zid)
+            .memory_budget(memory)
             .build(handler.clone())?;
 
         let config = Notifier::new(config);
@@ -144,6 +212,8 @@ impl Runtime {
                 transport_handlers: std::sync::RwLock::new(vec![]),
                 locators: std::sync::RwLock::new(vec![]),
                 hlc,
+                plugins: std::sync::Mutex::new(PluginsManager::static_plugins_only()),
+                plugins_changes: std::sync::Mutex::new(()),
                 stop_source: std::sync::RwLock::new(Some(StopSource::new())),
             }),
         };
@@ -158,6 +228,9 @@ impl Runtime {
             autoconnect,
         );
 
+        if config.lock().logging().filters().is_some() {
+            runtime.update_log_filters();
+        }
         let receiver = config.subscribe();
         runtime.spawn({
             let runtime2 = runtime.clone();
@@ -168,6 +241,8 @@ impl Runtime {
                         if let Err(e) = runtime2.update_peers().await {
                             log::error!("Error updating peers: {}", e);
                         }
+                    } else if event.starts_with("logging") {
+                        runtime2.update_log_filters();
                     }
                 }
             }
@@ -185,6 +260,62 @@ impl Runtime {
         zwrite!(self.state.transport_handlers).push(handler);
     }
 
+    /// Registers the statically linked plugin `P` on this runtime and starts it with `config`, which
+    /// becomes its configuration under `plugins/<name>`.
+    ///
+    /// This allows binaries embedding zenoh to run plugins, such as the REST or storage manager ones,
+    /// without zenohd loading them from dynamic libraries.
+    #[zenoh_macros::unstable]
+    pub fn add_plugin<P>(&self, config: serde_json::Value) -> ZResult<()>
+    where
+        P: crate::plugins::ZenohPlugin + Send + Sync,
+    {
+        let name = P::STATIC_NAME;
+        // The admin space applying the plugins configuration changes waits for the plugin to be started
+        let _changes = zlock!(self.plugins_changes);
+        if zlock!(self.plugins).plugin(name).is_some() {
+            bail!("Plugin `{}` is already running", name);
+        }
+        (&self.config)
+            .insert_json5(&format!("plugins/{name}"), &config.to_string())
+            .map_err(|e| zerror!("Invalid configuration of plugin `{}`: {}", name, e))?;
+        // The plugin is started outside the lock of the runtime plugins, that the admin space takes
+        // to reply to the queries the plugin may issue while starting
+        let mut plugin = PluginsManager::static_plugins_only().add_static::<P>();
+        let started = plugin.start(name, self).map(|_| ());
+        let mut plugins = zlock!(self.plugins);
+        if plugins.plugin(name).is_some() {
+            bail!("Plugin `{}` is already running", name);
+        }
+        // A plugin failing to start is kept, for its failure to be reported in the admin space
+        plugins.extend(plugin);
+        started?;
+        if let Some(running) = plugins.plugin(name) {
+            self.config
+                .lock()
+                .add_plugin_validator(name, running.config_checker());
+            log::info!("Successfully started static plugin `{}`", name);
+        }
+        Ok(())
+    }
+
+    /// Closes the unicast transports with the connected clients, suggesting them to reconnect
+    /// to the given locator (e.g., another router) before this runtime is drained.
+    ///
+    /// The clients reconnect and re-declare their entities on the new router while this one
+    /// keeps serving its other transports. A client only follows a locator among the endpoints
+    /// it is configured to connect to, and otherwise reconnects as after any other close.
+    pub async fn migrate_clients(&self, locator: Locator) -> ZResult<()> {
+        log::info!("Migrating clients to {}", locator);
+        for transport in self.manager().get_transports_unicast() {
+            if let Ok(WhatAmI::Client) = transport.get_whatami() {
+                transport.migrate(locator.clone()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes the runtime along with its transports, for all the sessions initialized on it.
     pub async fn close(&self) -> ZResult<()> {
         log::trace!("Runtime::close())");
         drop(self.stop_source.write().unwrap().take());
@@ -192,6 +323,15 @@ impl Runtime {
         Ok(())
     }
 
+    /// Applies the log filters of the configuration.
+    fn update_log_filters(&self) {
+        let filters = self.config.lock().logging().filters().clone();
+        match zenoh_util::log_filter::parse_filters(filters.as_deref().unwrap_or_default()) {
+            Ok(filters) => zenoh_util::log_filter::set_filters(filters),
+            Err(e) => log::error!("Error updating log filters: {}", e),
+        }
+    }
+
     pub fn new_timestamp(&self) -> Option<uhlc::Timestamp> {
         self.hlc.as_ref().map(|hlc| hlc.new_timestamp())
     }
@@ -209,7 +349,7 @@ impl Runtime {
             .read()
             .unwrap()
             .as_ref()
-            .map(|source| async_std::task::spawn(future.timeout_at(source.token())))
+            .map(|source| executor::spawn(future.timeout_at(source.token())))
     }
 }
 
@@ -225,6 +365,16 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
     ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
         match zread!(self.runtime).as_ref() {
             Some(runtime) => {
+                // The session is refused if its interceptors can't be created
+                let main_handler = runtime.router.new_transport_unicast(transport.clone())?;
+                audit::record(AuditEvent::SessionOpened {
+                    zid: peer.zid,
+                    whatami: peer.whatami,
+                    link: peer
+                        .links
+                        .first()
+                        .map(|l| format!("{} => {}", l.src, l.dst)),
+                });
                 let slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>> =
                     zread!(runtime.transport_handlers)
                         .iter()
@@ -235,7 +385,8 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
                 Ok(Arc::new(RuntimeSession {
                     runtime: runtime.clone(),
                     endpoint: std::sync::RwLock::new(None),
-                    main_handler: runtime.router.new_transport_unicast(transport).unwrap(),
+                    migration: std::sync::Mutex::new(None),
+                    main_handler,
                     slave_handlers,
                 }))
             }
@@ -255,6 +406,7 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
 pub(super) struct RuntimeSession {
     pub(super) runtime: Runtime,
     pub(super) endpoint: std::sync::RwLock<Option<EndPoint>>,
+    pub(super) migration: std::sync::Mutex<Option<EndPoint>>,
     pub(super) main_handler: Arc<LinkStateInterceptor>,
     pub(super) slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>>,
 }
@@ -307,6 +459,9 @@ impl TransportPeerEventHandler for RuntimeSession {
     }
 
     fn closed(&self) {
+        audit::record(AuditEvent::SessionClosed {
+            zid: self.main_handler.face.state.zid,
+        });
         self.main_handler.closed();
         for handler in &self.slave_handlers {
             handler.closed();
@@ -316,4 +471,32 @@ impl TransportPeerEventHandler for RuntimeSession {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn migrate(&self, locator: Locator) {
+        // Only the configured endpoints are followed: a router can't redirect the clients elsewhere
+        let endpoint = self
+            .runtime
+            .config
+            .lock()
+            .connect()
+            .endpoints()
+            .iter()
+            .find(|endpoint| endpoint.to_locator() == locator)
+            .cloned();
+        match endpoint {
+            Some(endpoint) => {
+                log::info!(
+                    "{} asked to migrate to {}",
+                    self.main_handler.face.state.zid,
+                    endpoint
+                );
+                *self.migration.lock().unwrap() = Some(endpoint);
+            }
+            None => log::warn!(
+                "{} asked to migrate to {} which is not a configured endpoint: ignored",
+                self.main_handler.face.state.zid,
+                locator
+            ),
+        }
+    }
 }
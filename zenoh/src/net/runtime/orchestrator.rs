@@ -16,24 +16,29 @@ use async_std::net::UdpSocket;
 use async_std::prelude::FutureExt;
 use futures::prelude::*;
 use socket2::{Domain, Socket, Type};
-use std::net::{IpAddr, Ipv6Addr, SocketAddr};
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use zenoh_buffers::reader::DidntRead;
 use zenoh_buffers::{reader::HasReader, writer::HasWriter};
 use zenoh_codec::{RCodec, WCodec, Zenoh060};
-use zenoh_config::{unwrap_or_default, EndPoint, ModeDependent};
+use zenoh_config::{unwrap_or_default, Config, EndPoint, ModeDependent, ScoutingUnicastConf};
 use zenoh_link::Locator;
 use zenoh_protocol::{
     core::{whatami::WhatAmIMatcher, WhatAmI, ZenohId},
-    scouting::{Hello, Scout, ScoutingBody, ScoutingMessage},
+    scouting::{Hello, HelloMetadata, Scout, ScoutingBody, ScoutingMessage},
 };
 use zenoh_result::{bail, zerror, ZResult};
-use zenoh_transport::TransportUnicast;
+use zenoh_transport::{ConnectFailure, ConnectFailureReason, TransportUnicast};
+use zenoh_util::net::IpSubnet;
+use zenoh_util::watchdog;
 
 const RCV_BUF_SIZE: usize = u16::MAX as usize;
 const SCOUT_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
 const SCOUT_MAX_PERIOD: Duration = Duration::from_millis(8_000);
 const SCOUT_PERIOD_INCREASE_FACTOR: u32 = 2;
+const HELLO_DEDUP_PERIOD: Duration = Duration::from_millis(500);
 const CONNECTION_TIMEOUT: Duration = Duration::from_millis(10_000);
 const CONNECTION_RETRY_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
 const CONNECTION_RETRY_MAX_PERIOD: Duration = Duration::from_millis(4_000);
@@ -46,6 +51,123 @@ pub enum Loop {
     Break,
 }
 
+/// Selects the scouted instances to connect to according to their zid, locators and Hello metadata.
+#[derive(Debug, Default)]
+pub(super) struct HelloFilter {
+    cluster: Option<String>,
+    region: Option<String>,
+    max_load: Option<u8>,
+    features: Vec<String>,
+    allow_zids: Vec<String>,
+    allow_subnets: Vec<IpSubnet>,
+    deny_zids: Vec<String>,
+    deny_subnets: Vec<IpSubnet>,
+}
+
+impl HelloFilter {
+    pub(super) fn from_config(config: &Config) -> Self {
+        // The zid patterns and subnets have been checked by the config validators
+        fn subnets(subnets: &[String]) -> Vec<IpSubnet> {
+            subnets.iter().filter_map(|s| s.parse().ok()).collect()
+        }
+        fn zids(zids: &[String]) -> Vec<String> {
+            zids.iter().map(|zid| zid.to_lowercase()).collect()
+        }
+
+        let filter = config.scouting().filter();
+        HelloFilter {
+            cluster: filter.cluster().clone(),
+            region: filter.region().clone(),
+            max_load: *filter.max_load(),
+            features: filter.features().clone().unwrap_or_default(),
+            allow_zids: zids(filter.allow().zids()),
+            allow_subnets: subnets(filter.allow().subnets()),
+            deny_zids: zids(filter.deny().zids()),
+            deny_subnets: subnets(filter.deny().subnets()),
+        }
+    }
+
+    pub(super) fn matches(&self, hello: &Hello) -> bool {
+        let zid_allowed = match &hello.zid {
+            Some(zid) => self.allows_zid(zid),
+            // The instances not advertising their zid can't match any pattern
+            None => self.allow_zids.is_empty(),
+        };
+        let subnets_filtered = !self.allow_subnets.is_empty() || !self.deny_subnets.is_empty();
+        let empty = HelloMetadata::default();
+        let metadata = hello.metadata.as_ref().unwrap_or(&empty);
+        zid_allowed
+            && (!subnets_filtered || !self.locators(&hello.locators).is_empty())
+            && (self.cluster.is_none() || self.cluster == metadata.cluster)
+            && (self.region.is_none() || self.region == metadata.region)
+            && match (self.max_load, metadata.load) {
+                (Some(max), Some(load)) => load <= max,
+                _ => true,
+            }
+            && self.features.iter().all(|f| metadata.features.contains(f))
+    }
+
+    /// Returns whether `zid` is allowed by the zid rules.
+    pub(super) fn allows_zid(&self, zid: &ZenohId) -> bool {
+        fn matches_zid(patterns: &[String], zid: &str) -> bool {
+            patterns
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => zid.starts_with(prefix),
+                    None => zid == pattern,
+                })
+        }
+
+        let zid = zid.to_string().to_lowercase();
+        (self.allow_zids.is_empty() || matches_zid(&self.allow_zids, &zid))
+            && !matches_zid(&self.deny_zids, &zid)
+    }
+
+    /// Returns the `locators` allowed by the subnet rules.
+    ///
+    /// The locators whose address isn't an IP address are only allowed if no `allow` subnet is set.
+    pub(super) fn locators(&self, locators: &[Locator]) -> Vec<Locator> {
+        locators
+            .iter()
+            .filter(
+                |locator| match locator.address().as_str().parse::<SocketAddr>() {
+                    Ok(addr) => {
+                        (self.allow_subnets.is_empty()
+                            || self.allow_subnets.iter().any(|s| s.contains(&addr.ip())))
+                            && !self.deny_subnets.iter().any(|s| s.contains(&addr.ip()))
+                    }
+                    Err(_) => self.allow_subnets.is_empty(),
+                },
+            )
+            .cloned()
+            .collect()
+    }
+}
+
+// The Hellos of an instance replying to a same scout on several groups or interfaces, e.g. on both
+// IPv4 and IPv6, are only handled once
+fn is_new_hello(seen: &Mutex<HashMap<ZenohId, Instant>>, hello: &Hello) -> bool {
+    let zid = match hello.zid {
+        Some(zid) => zid,
+        None => return true,
+    };
+    let now = Instant::now();
+    let mut seen = zlock!(seen);
+    seen.retain(|_, time| now.duration_since(*time) < HELLO_DEDUP_PERIOD);
+    seen.insert(zid, now).is_none()
+}
+
+pub(super) fn hello_metadata(config: &Config) -> Option<HelloMetadata> {
+    let hello = config.scouting().hello();
+    let metadata = HelloMetadata {
+        cluster: hello.cluster().clone(),
+        region: hello.region().clone(),
+        load: *hello.load(),
+        features: hello.features().clone().unwrap_or_default(),
+    };
+    (!metadata.is_empty()).then_some(metadata)
+}
+
 impl Runtime {
     pub(crate) async fn start(&mut self) -> ZResult<()> {
         match self.whatami {
@@ -56,38 +178,61 @@ impl Runtime {
     }
 
     async fn start_client(&self) -> ZResult<()> {
-        let (peers, scouting, addr, ifaces, timeout) = {
+        let (peers, mcast_addrs, ttl, gateways, mdns, ifaces, timeout) = {
             let guard = self.config.lock();
             (
                 guard.connect().endpoints().clone(),
-                unwrap_or_default!(guard.scouting().multicast().enabled()),
-                unwrap_or_default!(guard.scouting().multicast().address()),
+                Runtime::multicast_addresses(&guard)?,
+                unwrap_or_default!(guard.scouting().multicast().ttl()),
+                guard.scouting().unicast().gateways().clone(),
+                Runtime::mdns_enabled(&guard),
                 unwrap_or_default!(guard.scouting().multicast().interface()),
                 std::time::Duration::from_millis(unwrap_or_default!(guard.scouting().timeout())),
             )
         };
         match peers.len() {
             0 => {
-                if scouting {
-                    log::info!("Scouting for router ...");
-                    let ifaces = Runtime::get_interfaces(&ifaces);
-                    if ifaces.is_empty() {
-                        bail!("Unable to find multicast interface!")
-                    } else {
-                        let sockets: Vec<UdpSocket> = ifaces
-                            .into_iter()
-                            .filter_map(|iface| Runtime::bind_ucast_port(iface).ok())
-                            .collect();
-                        if sockets.is_empty() {
-                            bail!("Unable to bind UDP port to any multicast interface!")
-                        } else {
-                            self.connect_first(&sockets, WhatAmI::Router.into(), &addr, timeout)
-                                .await
-                        }
-                    }
-                } else {
+                if mcast_addrs.is_empty() && gateways.is_empty() && !mdns {
                     bail!("No peer specified and multicast scouting desactivated!")
                 }
+                let mut mcast_addrs = mcast_addrs;
+                let mut ifaces = Runtime::get_interfaces(&ifaces);
+                if ifaces.is_empty() {
+                    if !mcast_addrs.is_empty() {
+                        log::warn!("Unable to find multicast interface! Skip multicast scouting");
+                        mcast_addrs.clear();
+                    }
+                    // The gateways are scouted over unicast, from any interface
+                    ifaces.push(Ipv4Addr::UNSPECIFIED.into());
+                }
+                let targets = Runtime::scouting_targets(mcast_addrs, gateways);
+                if targets.is_empty() && !mdns {
+                    bail!("Unable to find multicast interface!")
+                }
+                let mut sockets = vec![];
+                if !targets.is_empty() {
+                    sockets = Runtime::bind_ucast_ports(&ifaces, &targets, ttl);
+                    if sockets.is_empty() {
+                        bail!("Unable to bind UDP port to any multicast interface!")
+                    }
+                }
+                log::info!("Scouting for router ...");
+                // The mDNS scouting runs concurrently with the multicast and unicast one
+                let mut scouts = vec![];
+                if !targets.is_empty() {
+                    scouts.push(
+                        self.connect_first(&sockets, WhatAmI::Router.into(), &targets, timeout)
+                            .boxed(),
+                    );
+                }
+                #[cfg(feature = "mdns")]
+                if mdns {
+                    scouts.push(
+                        self.mdns_connect_first(WhatAmI::Router.into(), timeout)
+                            .boxed(),
+                    );
+                }
+                futures::future::select_ok(scouts).await.map(|_| ())
             }
             _ => {
                 for locator in &peers {
@@ -98,8 +243,14 @@ impl Runtime {
                         .await
                     {
                         Ok(Ok(_)) => return Ok(()),
-                        Ok(Err(e)) => log::warn!("Unable to connect to {}! {}", locator, e),
-                        Err(e) => log::warn!("Unable to connect to {}! {}", locator, e),
+                        Ok(Err(e)) => {
+                            log::warn!("Unable to connect to {}! {}", locator, e);
+                            self.connect_failed(locator, ConnectFailureReason::of(&e), &e, None);
+                        }
+                        Err(e) => {
+                            log::warn!("Unable to connect to {}! {}", locator, e);
+                            self.connect_failed(locator, ConnectFailureReason::Timeout, &e, None);
+                        }
                     }
                 }
                 let e = zerror!("Unable to connect to any of {:?}! ", peers);
@@ -109,8 +260,9 @@ impl Runtime {
         }
     }
 
+    #[cfg_attr(not(feature = "mdns"), allow(unused_variables))]
     async fn start_peer(&self) -> ZResult<()> {
-        let (listeners, peers, scouting, listen, autoconnect, addr, ifaces, delay) = {
+        let (listeners, peers, mcast_addrs, ttl, listen, autoconnect, unicast, mdns, ifaces, delay) = {
             let guard = &self.config.lock();
             let listeners = if guard.listen().endpoints().is_empty() {
                 let endpoint: EndPoint = PEER_DEFAULT_LISTENER.parse().unwrap();
@@ -132,10 +284,17 @@ impl Runtime {
             (
                 listeners,
                 guard.connect().endpoints().clone(),
-                unwrap_or_default!(guard.scouting().multicast().enabled()),
+                Runtime::multicast_addresses(guard)?,
+                unwrap_or_default!(guard.scouting().multicast().ttl()),
                 *unwrap_or_default!(guard.scouting().multicast().listen().peer()),
                 *unwrap_or_default!(guard.scouting().multicast().autoconnect().peer()),
-                unwrap_or_default!(guard.scouting().multicast().address()),
+                guard.scouting().unicast().clone(),
+                Runtime::mdns_enabled(guard).then(|| {
+                    (
+                        *unwrap_or_default!(guard.scouting().mdns().advertise().peer()),
+                        *unwrap_or_default!(guard.scouting().mdns().autoconnect().peer()),
+                    )
+                }),
                 unwrap_or_default!(guard.scouting().multicast().interface()),
                 Duration::from_millis(unwrap_or_default!(guard.scouting().delay())),
             )
@@ -148,15 +307,21 @@ impl Runtime {
             self.spawn(async move { this.peer_connector(peer).await });
         }
 
-        if scouting {
-            self.start_scout(listen, autoconnect, addr, ifaces).await?;
+        if !mcast_addrs.is_empty() || !unicast.gateways.is_empty() || unicast.listen().is_some() {
+            self.start_scout(listen, autoconnect, mcast_addrs, ttl, unicast, ifaces)
+                .await?;
+        }
+        #[cfg(feature = "mdns")]
+        if let Some((advertise, autoconnect)) = mdns {
+            self.start_mdns(advertise, autoconnect).await?;
         }
         async_std::task::sleep(delay).await;
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "mdns"), allow(unused_variables))]
     async fn start_router(&self) -> ZResult<()> {
-        let (listeners, peers, scouting, listen, autoconnect, addr, ifaces) = {
+        let (listeners, peers, mcast_addrs, ttl, listen, autoconnect, unicast, mdns, ifaces) = {
             let guard = self.config.lock();
             let listeners = if guard.listen().endpoints().is_empty() {
                 let endpoint: EndPoint = ROUTER_DEFAULT_LISTENER.parse().unwrap();
@@ -178,10 +343,17 @@ impl Runtime {
             (
                 listeners,
                 guard.connect().endpoints().clone(),
-                unwrap_or_default!(guard.scouting().multicast().enabled()),
+                Runtime::multicast_addresses(&guard)?,
+                unwrap_or_default!(guard.scouting().multicast().ttl()),
                 *unwrap_or_default!(guard.scouting().multicast().listen().router()),
                 *unwrap_or_default!(guard.scouting().multicast().autoconnect().router()),
-                unwrap_or_default!(guard.scouting().multicast().address()),
+                guard.scouting().unicast().clone(),
+                Runtime::mdns_enabled(&guard).then(|| {
+                    (
+                        *unwrap_or_default!(guard.scouting().mdns().advertise().router()),
+                        *unwrap_or_default!(guard.scouting().mdns().autoconnect().router()),
+                    )
+                }),
                 unwrap_or_default!(guard.scouting().multicast().interface()),
             )
         };
@@ -193,56 +365,110 @@ impl Runtime {
             self.spawn(async move { this.peer_connector(peer).await });
         }
 
-        if scouting {
-            self.start_scout(listen, autoconnect, addr, ifaces).await?;
+        if !mcast_addrs.is_empty() || !unicast.gateways.is_empty() || unicast.listen().is_some() {
+            self.start_scout(listen, autoconnect, mcast_addrs, ttl, unicast, ifaces)
+                .await?;
+        }
+        #[cfg(feature = "mdns")]
+        if let Some((advertise, autoconnect)) = mdns {
+            self.start_mdns(advertise, autoconnect).await?;
         }
 
         Ok(())
     }
 
+    /// Starts the multicast scouting on the `mcast_addrs` groups, and the unicast scouting.
     async fn start_scout(
         &self,
         listen: bool,
         autoconnect: WhatAmIMatcher,
-        addr: SocketAddr,
+        mcast_addrs: Vec<SocketAddr>,
+        ttl: u32,
+        unicast: ScoutingUnicastConf,
         ifaces: String,
     ) -> ZResult<()> {
-        let ifaces = Runtime::get_interfaces(&ifaces);
-        let mcast_socket = Runtime::bind_mcast_port(&addr, &ifaces).await?;
-        if !ifaces.is_empty() {
-            let sockets: Vec<UdpSocket> = ifaces
-                .into_iter()
-                .filter_map(|iface| Runtime::bind_ucast_port(iface).ok())
-                .collect();
+        let mut mcast_addrs = mcast_addrs;
+        let mut ifaces = Runtime::get_interfaces(&ifaces);
+        if ifaces.is_empty() {
+            if !mcast_addrs.is_empty() {
+                log::warn!("Unable to find multicast interface! Skip multicast scouting");
+                mcast_addrs.clear();
+            }
+            // The gateways are scouted over unicast, from any interface
+            ifaces.push(Ipv4Addr::UNSPECIFIED.into());
+        }
+        let mut mcast_sockets = vec![];
+        for addr in mcast_addrs.iter() {
+            mcast_sockets.push(Runtime::bind_mcast_port(addr, &ifaces).await?);
+        }
+        // The replies to the unicast scouts are sent from the socket they are received on
+        let ucast_listener = match unicast.listen() {
+            Some(addr) => match UdpSocket::bind(*addr).await {
+                Ok(socket) => {
+                    log::info!("zenohd listening unicast scout messages on {}", addr);
+                    Some(socket)
+                }
+                Err(err) => bail!(err => "Unable to bind UDP port {}", addr),
+            },
+            None => None,
+        };
+        let targets = Runtime::scouting_targets(mcast_addrs, unicast.gateways);
+        let sockets = Runtime::bind_ucast_ports(&ifaces, &targets, ttl);
+        let this = self.clone();
+        self.spawn(async move {
+            let mut tasks = vec![];
             if !sockets.is_empty() {
-                let this = self.clone();
-                match (listen, autoconnect.is_empty()) {
-                    (true, false) => {
-                        self.spawn(async move {
-                            async_std::prelude::FutureExt::race(
-                                this.responder(&mcast_socket, &sockets),
-                                this.connect_all(&sockets, autoconnect, &addr),
-                            )
-                            .await;
-                        });
-                    }
-                    (true, true) => {
-                        self.spawn(async move {
-                            this.responder(&mcast_socket, &sockets).await;
-                        });
-                    }
-                    (false, false) => {
-                        self.spawn(
-                            async move { this.connect_all(&sockets, autoconnect, &addr).await },
-                        );
-                    }
-                    _ => {}
+                for socket in mcast_sockets.iter().filter(|_| listen) {
+                    tasks.push(this.responder(socket, &sockets).boxed());
+                }
+                if !autoconnect.is_empty() && !targets.is_empty() {
+                    tasks.push(this.connect_all(&sockets, autoconnect, &targets).boxed());
                 }
             }
-        }
+            if let Some(socket) = ucast_listener.as_ref() {
+                tasks.push(this.responder(socket, std::slice::from_ref(socket)).boxed());
+            }
+            if !tasks.is_empty() {
+                futures::future::select_all(tasks).await;
+            }
+        });
         Ok(())
     }
 
+    /// The addresses the scout messages are sent to: the multicast groups and the gateways.
+    pub(crate) fn scouting_targets(
+        mcast_addrs: Vec<SocketAddr>,
+        gateways: Vec<SocketAddr>,
+    ) -> Vec<SocketAddr> {
+        mcast_addrs.into_iter().chain(gateways).collect()
+    }
+
+    /// Whether the mDNS scouting is enabled, warning if zenoh has been built without it.
+    pub(crate) fn mdns_enabled(config: &Config) -> bool {
+        let enabled = unwrap_or_default!(config.scouting().mdns().enabled());
+        if enabled && !cfg!(feature = "mdns") {
+            log::warn!(
+                "mDNS scouting is enabled but zenoh has been built without the `mdns` feature"
+            );
+        }
+        enabled && cfg!(feature = "mdns")
+    }
+
+    /// The multicast groups to scout on, none if multicast scouting is disabled.
+    pub(crate) fn multicast_addresses(config: &Config) -> ZResult<Vec<SocketAddr>> {
+        if !unwrap_or_default!(config.scouting().multicast().enabled()) {
+            return Ok(vec![]);
+        }
+        let mut addrs = vec![unwrap_or_default!(config.scouting().multicast().address())];
+        if let Some(addr) = config.scouting().multicast().address_v6() {
+            if !addr.is_ipv6() {
+                bail!("Invalid IPv6 multicast scouting address {}", addr);
+            }
+            addrs.push(*addr);
+        }
+        Ok(addrs)
+    }
+
     pub(crate) async fn update_peers(&self) -> ZResult<()> {
         let peers = { self.config.lock().connect().endpoints().clone() };
         let tranports = self.manager().get_transports();
@@ -347,8 +573,33 @@ impl Runtime {
         }
     }
 
+    /// The indexes of the interfaces having the addresses `ifaces`, 0 standing for the default one.
+    fn get_interface_indexes(ifaces: &[IpAddr]) -> Vec<u32> {
+        let mut indexes = vec![];
+        for iface in ifaces {
+            let index = if iface.is_unspecified() {
+                0
+            } else {
+                match zenoh_util::net::get_index_of_interface(*iface) {
+                    Ok(index) => index,
+                    Err(err) => {
+                        log::warn!("Unable to find index of interface {}: {}", iface, err);
+                        continue;
+                    }
+                }
+            };
+            if !indexes.contains(&index) {
+                indexes.push(index);
+            }
+        }
+        if indexes.is_empty() {
+            indexes.push(0);
+        }
+        indexes
+    }
+
     pub async fn bind_mcast_port(sockaddr: &SocketAddr, ifaces: &[IpAddr]) -> ZResult<UdpSocket> {
-        let socket = match Socket::new(Domain::IPV4, Type::DGRAM, None) {
+        let socket = match Socket::new(Domain::for_address(*sockaddr), Type::DGRAM, None) {
             Ok(socket) => socket,
             Err(err) => {
                 log::error!("Unable to create datagram socket: {}", err);
@@ -366,9 +617,18 @@ impl Runtime {
             } // See UNIX Network Programmping p.212
             #[cfg(windows)]
             {
-                std::net::Ipv4Addr::UNSPECIFIED.into()
+                match sockaddr {
+                    SocketAddr::V4(_) => std::net::Ipv4Addr::UNSPECIFIED.into(),
+                    SocketAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+                }
             }
         };
+        if sockaddr.is_ipv6() {
+            if let Err(err) = socket.set_only_v6(true) {
+                log::error!("Unable to set IPV6_V6ONLY option: {}", err);
+                bail!(err => "Unable to set IPV6_V6ONLY option");
+            }
+        }
         match socket.bind(&SocketAddr::new(addr, sockaddr.port()).into()) {
             Ok(()) => log::debug!("UDP port bound to {}", sockaddr),
             Err(err) => {
@@ -378,20 +638,30 @@ impl Runtime {
         }
 
         match sockaddr.ip() {
-            IpAddr::V6(addr) => match socket.join_multicast_v6(&addr, 0) {
-                Ok(()) => log::debug!("Joined multicast group {} on interface 0", sockaddr.ip()),
-                Err(err) => {
-                    log::error!(
-                        "Unable to join multicast group {} on interface 0: {}",
-                        sockaddr.ip(),
-                        err
-                    );
-                    bail!(err =>
-                        "Unable to join multicast group {} on interface 0",
-                        sockaddr.ip()
-                    )
+            IpAddr::V6(addr) => {
+                for index in Runtime::get_interface_indexes(ifaces) {
+                    match socket.join_multicast_v6(&addr, index) {
+                        Ok(()) => log::debug!(
+                            "Joined multicast group {} on interface {}",
+                            sockaddr.ip(),
+                            index
+                        ),
+                        Err(err) => {
+                            log::error!(
+                                "Unable to join multicast group {} on interface {}: {}",
+                                sockaddr.ip(),
+                                index,
+                                err
+                            );
+                            bail!(err =>
+                                "Unable to join multicast group {} on interface {}",
+                                sockaddr.ip(),
+                                index
+                            )
+                        }
+                    }
                 }
-            },
+            }
             IpAddr::V4(addr) => {
                 for iface in ifaces {
                     if let IpAddr::V4(iface_addr) = iface {
@@ -422,6 +692,67 @@ impl Runtime {
         Ok(std::net::UdpSocket::from(socket).into())
     }
 
+    /// Binds the ports sending the scout messages to the `targets`: one per interface for the IPv4
+    /// targets, and one per interface index for the IPv6 targets.
+    pub fn bind_ucast_ports(ifaces: &[IpAddr], targets: &[SocketAddr], ttl: u32) -> Vec<UdpSocket> {
+        let mut sockets = vec![];
+        if targets.iter().any(SocketAddr::is_ipv4) {
+            for iface in ifaces {
+                if let Ok(socket) = Runtime::bind_ucast_port(*iface) {
+                    if let Err(err) = socket.set_multicast_ttl_v4(ttl) {
+                        log::warn!("Unable to set IP_MULTICAST_TTL option: {}", err);
+                    }
+                    sockets.push(socket);
+                }
+            }
+        }
+        if targets.iter().any(SocketAddr::is_ipv6) {
+            for index in Runtime::get_interface_indexes(ifaces) {
+                if let Ok(socket) = Runtime::bind_ucast_port_v6(index, ttl) {
+                    sockets.push(socket);
+                }
+            }
+        }
+        sockets
+    }
+
+    /// Binds an IPv6 port sending the multicast messages on the interface `index`.
+    pub fn bind_ucast_port_v6(index: u32, hops: u32) -> ZResult<UdpSocket> {
+        let socket = match Socket::new(Domain::IPV6, Type::DGRAM, None) {
+            Ok(socket) => socket,
+            Err(err) => {
+                log::warn!("Unable to create datagram socket: {}", err);
+                bail!(err=> "Unable to create datagram socket");
+            }
+        };
+        if let Err(err) = socket
+            .set_only_v6(true)
+            .and_then(|_| socket.set_multicast_if_v6(index))
+            .and_then(|_| socket.set_multicast_hops_v6(hops))
+        {
+            log::warn!(
+                "Unable to set IPv6 multicast options on interface {}: {}",
+                index,
+                err
+            );
+            bail!(err => "Unable to set IPv6 multicast options on interface {}", index);
+        }
+        let addr = SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0);
+        match socket.bind(&addr.into()) {
+            Ok(()) => log::debug!("UDP port bound to {} for interface {}", addr, index),
+            Err(err) => {
+                log::warn!(
+                    "Unable to bind udp port {} for interface {}: {}",
+                    addr,
+                    index,
+                    err
+                );
+                bail!(err => "Unable to bind udp port {} for interface {}", addr, index);
+            }
+        }
+        Ok(std::net::UdpSocket::from(socket).into())
+    }
+
     pub fn bind_ucast_port(addr: IpAddr) -> ZResult<UdpSocket> {
         let socket = match Socket::new(Domain::IPV4, Type::DGRAM, None) {
             Ok(socket) => socket,
@@ -479,6 +810,7 @@ impl Runtime {
                         e,
                         delay
                     );
+                    self.connect_failed(&peer, ConnectFailureReason::of(&e), &e, Some(delay));
                 }
                 Err(e) => {
                     log::debug!(
@@ -487,6 +819,7 @@ impl Runtime {
                         e,
                         delay
                     );
+                    self.connect_failed(&peer, ConnectFailureReason::Timeout, &e, Some(delay));
                 }
             }
             async_std::task::sleep(delay).await;
@@ -497,10 +830,12 @@ impl Runtime {
         }
     }
 
+    /// Sends scout messages to the `targets`, multicast or unicast, from the `sockets` and calls
+    /// `f` on each of the matching Hello messages received in reply.
     pub async fn scout<Fut, F>(
         sockets: &[UdpSocket],
         matcher: WhatAmIMatcher,
-        mcast_addr: &SocketAddr,
+        targets: &[SocketAddr],
         f: F,
     ) where
         F: Fn(Hello) -> Fut + std::marker::Send + std::marker::Sync + Clone,
@@ -516,24 +851,29 @@ impl Runtime {
             let codec = Zenoh060::default();
             codec.write(&mut writer, &scout).unwrap();
 
+            let probe = watchdog::register(format!("scout {targets:?}"), None, None);
             loop {
-                for socket in sockets {
+                probe.progress();
+                for (socket, target) in sockets.iter().flat_map(|socket| {
+                    let family = socket.local_addr().map(|addr| addr.is_ipv4());
+                    targets
+                        .iter()
+                        .filter(move |target| family.as_ref().ok() == Some(&target.is_ipv4()))
+                        .map(move |target| (socket, target))
+                }) {
                     log::trace!(
                         "Send {:?} to {} on interface {}",
                         scout.body,
-                        mcast_addr,
+                        target,
                         socket
                             .local_addr()
                             .map_or("unknown".to_string(), |addr| addr.ip().to_string())
                     );
-                    if let Err(err) = socket
-                        .send_to(wbuf.as_slice(), mcast_addr.to_string())
-                        .await
-                    {
+                    if let Err(err) = socket.send_to(wbuf.as_slice(), *target).await {
                         log::debug!(
                             "Unable to send {:?} to {} on interface {}: {}",
                             scout.body,
-                            mcast_addr,
+                            target,
                             socket
                                 .local_addr()
                                 .map_or("unknown".to_string(), |addr| addr.ip().to_string()),
@@ -541,18 +881,33 @@ impl Runtime {
                         );
                     }
                 }
+                probe.idle();
                 async_std::task::sleep(delay).await;
                 if delay * SCOUT_PERIOD_INCREASE_FACTOR <= SCOUT_MAX_PERIOD {
                     delay *= SCOUT_PERIOD_INCREASE_FACTOR;
                 }
             }
         };
+        let seen = &Mutex::new(HashMap::new());
         let recvs = futures::future::select_all(sockets.iter().map(move |socket| {
             let f = f.clone();
             async move {
                 let mut buf = vec![0; RCV_BUF_SIZE];
+                let probe = watchdog::register(
+                    format!(
+                        "scout receiver {}",
+                        socket
+                            .local_addr()
+                            .map_or("unknown".to_string(), |addr| addr.to_string())
+                    ),
+                    None,
+                    None,
+                );
                 loop {
-                    match socket.recv_from(&mut buf).await {
+                    probe.idle();
+                    let received = socket.recv_from(&mut buf).await;
+                    probe.progress();
+                    match received {
                         Ok((n, peer)) => {
                             let mut reader = buf.as_slice()[..n].reader();
                             let codec = Zenoh060::default();
@@ -560,12 +915,12 @@ impl Runtime {
                             if let Ok(msg) = res {
                                 log::trace!("Received {:?} from {}", msg.body, peer);
                                 if let ScoutingBody::Hello(hello) = &msg.body {
-                                    if matcher.matches(hello.whatami) {
-                                        if let Loop::Break = f(hello.clone()).await {
-                                            break;
-                                        }
-                                    } else {
+                                    if !matcher.matches(hello.whatami) {
                                         log::warn!("Received unexpected Hello: {:?}", msg.body);
+                                    } else if !is_new_hello(seen, hello) {
+                                        log::trace!("Ignore duplicate Hello from {}", peer);
+                                    } else if let Loop::Break = f(hello.clone()).await {
+                                        break;
                                     }
                                 }
                             } else {
@@ -585,28 +940,101 @@ impl Runtime {
         async_std::prelude::FutureExt::race(send, recvs).await;
     }
 
-    async fn connect(&self, locators: &[Locator]) -> Option<TransportUnicast> {
+    pub(super) async fn connect(&self, locators: &[Locator]) -> Option<TransportUnicast> {
         for locator in locators {
-            let endpoint = locator.clone().into();
+            if !self
+                .manager()
+                .is_protocol_allowed(locator.protocol().as_str())
+            {
+                log::trace!("Skip locator {} with a disallowed protocol", locator);
+                continue;
+            }
+            let endpoint: EndPoint = locator.clone().into();
             match self
                 .manager()
-                .open_transport(endpoint)
+                .open_transport(endpoint.clone())
                 .timeout(CONNECTION_TIMEOUT)
                 .await
             {
                 Ok(Ok(transport)) => return Some(transport),
-                Ok(Err(e)) => log::trace!("Unable to connect to {}! {}", locator, e),
-                Err(e) => log::trace!("Unable to connect to {}! {}", locator, e),
+                Ok(Err(e)) => {
+                    log::trace!("Unable to connect to {}! {}", locator, e);
+                    self.connect_failed(&endpoint, ConnectFailureReason::of(&e), &e, None);
+                }
+                Err(e) => {
+                    log::trace!("Unable to connect to {}! {}", locator, e);
+                    self.connect_failed(&endpoint, ConnectFailureReason::Timeout, &e, None);
+                }
             }
         }
         None
     }
 
+    /// Notifies the transport event handlers of a failed outgoing connection attempt.
+    fn connect_failed(
+        &self,
+        endpoint: &EndPoint,
+        reason: ConnectFailureReason,
+        error: &dyn std::fmt::Display,
+        retry_in: Option<Duration>,
+    ) {
+        let failure = ConnectFailure {
+            endpoint: endpoint.clone(),
+            reason,
+            error: error.to_string(),
+            retry_in,
+        };
+        for handler in zread!(self.transport_handlers).iter() {
+            handler.connect_failed(&failure);
+        }
+    }
+
+    /// Connects to a scouted instance through the `locators` allowed by `filter`.
+    ///
+    /// As the zid advertised in a Hello is not checked, the zid rules of `filter` are applied again
+    /// to the zid of the opened transport, which is closed if not allowed, unless it already existed.
+    pub(super) async fn connect_scouted(
+        &self,
+        filter: &HelloFilter,
+        locators: &[Locator],
+    ) -> Option<TransportUnicast> {
+        let existing: HashSet<ZenohId> = self
+            .manager()
+            .get_transports()
+            .iter()
+            .filter_map(|transport| transport.get_zid().ok())
+            .collect();
+        let transport = self.connect(&filter.locators(locators)).await?;
+        let zid = transport.get_zid().ok()?;
+        if filter.allows_zid(&zid) || existing.contains(&zid) {
+            Some(transport)
+        } else {
+            log::warn!(
+                "Close transport with scouted {} not matching {:?}",
+                zid,
+                filter
+            );
+            let _ = transport.close().await;
+            None
+        }
+    }
+
     pub async fn connect_peer(&self, zid: &ZenohId, locators: &[Locator]) {
+        self.connect_scouted_peer(&HelloFilter::default(), zid, locators)
+            .await
+    }
+
+    /// Connects to the scouted peer `zid` through [`connect_scouted`](Runtime::connect_scouted).
+    pub(super) async fn connect_scouted_peer(
+        &self,
+        filter: &HelloFilter,
+        zid: &ZenohId,
+        locators: &[Locator],
+    ) {
         if zid != &self.manager().zid() {
             if self.manager().get_transport(zid).is_none() {
                 log::debug!("Try to connect to peer {} via any of {:?}", zid, locators);
-                if let Some(transport) = self.connect(locators).await {
+                if let Some(transport) = self.connect_scouted(filter, locators).await {
                     log::debug!(
                         "Successfully connected to newly scouted peer {} via {:?}",
                         zid,
@@ -629,14 +1057,17 @@ impl Runtime {
         &self,
         sockets: &[UdpSocket],
         what: WhatAmIMatcher,
-        addr: &SocketAddr,
+        targets: &[SocketAddr],
         timeout: std::time::Duration,
     ) -> ZResult<()> {
+        let filter = &HelloFilter::from_config(&self.config.lock());
         let scout = async {
-            Runtime::scout(sockets, what, addr, move |hello| async move {
+            Runtime::scout(sockets, what, targets, move |hello| async move {
                 log::info!("Found {:?}", hello);
-                if !hello.locators.is_empty() {
-                    if let Some(transport) = self.connect(&hello.locators).await {
+                if !filter.matches(&hello) {
+                    log::debug!("Ignore scouted {:?} not matching {:?}", hello, filter);
+                } else if !hello.locators.is_empty() {
+                    if let Some(transport) = self.connect_scouted(filter, &hello.locators).await {
                         log::debug!(
                             "Successfully connected to newly scouted {:?} via {:?}",
                             hello,
@@ -664,13 +1095,19 @@ impl Runtime {
         &self,
         ucast_sockets: &[UdpSocket],
         what: WhatAmIMatcher,
-        addr: &SocketAddr,
+        targets: &[SocketAddr],
     ) {
-        Runtime::scout(ucast_sockets, what, addr, move |hello| async move {
+        let filter = &HelloFilter::from_config(&self.config.lock());
+        Runtime::scout(ucast_sockets, what, targets, move |hello| async move {
+            if !filter.matches(&hello) {
+                log::debug!("Ignore scouted {:?} not matching {:?}", hello, filter);
+                return Loop::Continue;
+            }
             match &hello.zid {
                 Some(zid) => {
                     if !hello.locators.is_empty() {
-                        self.connect_peer(zid, &hello.locators).await
+                        self.connect_scouted_peer(filter, zid, &hello.locators)
+                            .await
                     } else {
                         log::warn!("Received Hello with no locators: {:?}", hello);
                     }
@@ -684,7 +1121,7 @@ impl Runtime {
         .await
     }
 
-    async fn responder(&self, mcast_socket: &UdpSocket, ucast_sockets: &[UdpSocket]) {
+    async fn responder(&self, socket: &UdpSocket, ucast_sockets: &[UdpSocket]) {
         fn get_best_match<'a>(addr: &IpAddr, sockets: &'a [UdpSocket]) -> Option<&'a UdpSocket> {
             fn octets(addr: &IpAddr) -> Vec<u8> {
                 match addr {
@@ -702,7 +1139,10 @@ impl Runtime {
             }
             sockets
                 .iter()
-                .filter(|sock| sock.local_addr().is_ok())
+                .filter(|sock| {
+                    sock.local_addr()
+                        .map_or(false, |local| local.is_ipv4() == addr.is_ipv4())
+                })
                 .max_by(|sock1, sock2| {
                     matching_octets(addr, sock1).cmp(&matching_octets(addr, sock2))
                 })
@@ -714,8 +1154,11 @@ impl Runtime {
             .filter_map(|sock| sock.local_addr().ok())
             .collect();
         log::debug!("Waiting for UDP datagram...");
+        let probe = watchdog::register("scouting responder".to_string(), None, None);
         loop {
-            let (n, peer) = mcast_socket.recv_from(&mut buf).await.unwrap();
+            probe.idle();
+            let (n, peer) = socket.recv_from(&mut buf).await.unwrap();
+            probe.progress();
             if local_addrs.iter().any(|addr| *addr == peer) {
                 log::trace!("Ignore UDP datagram from own socket");
                 continue;
@@ -741,10 +1184,12 @@ impl Runtime {
                         } else {
                             None
                         };
+                        let metadata = hello_metadata(&self.config.lock());
                         let hello = ScoutingMessage::make_hello(
                             zid,
                             Some(self.whatami),
                             Some(self.get_locators()),
+                            metadata,
                             None,
                         );
                         let socket = get_best_match(&peer.ip(), ucast_sockets).unwrap();
@@ -777,7 +1222,21 @@ impl Runtime {
         match session.runtime.whatami {
             WhatAmI::Client => {
                 let runtime = session.runtime.clone();
+                let migration = session.migration.lock().unwrap().take();
                 session.runtime.spawn(async move {
+                    // Follow the migration hint of the closing router first, if any
+                    if let Some(endpoint) = migration {
+                        match runtime
+                            .manager()
+                            .open_transport(endpoint.clone())
+                            .timeout(CONNECTION_TIMEOUT)
+                            .await
+                        {
+                            Ok(Ok(_)) => return,
+                            Ok(Err(e)) => log::warn!("Unable to migrate to {}! {}", endpoint, e),
+                            Err(e) => log::warn!("Unable to migrate to {}! {}", endpoint, e),
+                        }
+                    }
                     let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
                     while runtime.start_client().await.is_err() {
                         async_std::task::sleep(delay).await;
@@ -803,3 +1262,138 @@ impl Runtime {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use zenoh_config::ValidatedMap;
+
+    #[test]
+    fn hello_filter() {
+        let mut config = Config::default();
+        config
+            .insert_json5("scouting/filter", r#"{cluster: "a", max_load: 50}"#)
+            .unwrap();
+        let filter = HelloFilter::from_config(&config);
+
+        let mut hello = Hello {
+            zid: None,
+            whatami: WhatAmI::Router,
+            locators: vec![],
+            metadata: None,
+        };
+        assert!(!filter.matches(&hello));
+        hello.metadata = Some(HelloMetadata {
+            cluster: Some("a".to_string()),
+            ..Default::default()
+        });
+        assert!(filter.matches(&hello));
+        hello.metadata.as_mut().unwrap().load = Some(80);
+        assert!(!filter.matches(&hello));
+        hello.metadata.as_mut().unwrap().cluster = Some("b".to_string());
+        hello.metadata.as_mut().unwrap().load = Some(20);
+        assert!(!filter.matches(&hello));
+
+        assert!(HelloFilter::default().matches(&hello));
+        assert_eq!(hello_metadata(&Config::default()), None);
+    }
+
+    #[test]
+    fn hello_allow_deny() {
+        let mut config = Config::default();
+        config
+            .insert_json5(
+                "scouting/filter/allow",
+                r#"{zids: ["a1*", "b2"], subnets: ["192.168.1.0/24", "fd00::/8"]}"#,
+            )
+            .unwrap();
+        config
+            .insert_json5(
+                "scouting/filter/deny",
+                r#"{zids: ["a1ff*"], subnets: ["192.168.1.128/25"]}"#,
+            )
+            .unwrap();
+        assert!(config
+            .insert_json5("scouting/filter/deny/subnets", r#"["192.168.1.0/33"]"#)
+            .is_err());
+        assert!(config
+            .insert_json5("scouting/filter/allow/zids", r#"["not-a-zid"]"#)
+            .is_err());
+        let filter = HelloFilter::from_config(&config);
+
+        let locators: Vec<Locator> = [
+            "tcp/192.168.1.2:7447",
+            "tcp/192.168.1.200:7447",
+            "tcp/[fd00::1]:7447",
+            "tcp/10.0.0.1:7447",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+        let mut hello = Hello {
+            zid: Some(ZenohId::from_str("a1b2").unwrap()),
+            whatami: WhatAmI::Peer,
+            locators: locators.clone(),
+            metadata: None,
+        };
+        assert!(filter.matches(&hello));
+        assert_eq!(
+            filter.locators(&hello.locators),
+            vec![locators[0].clone(), locators[2].clone()]
+        );
+
+        hello.zid = Some(ZenohId::from_str("a1ff").unwrap());
+        assert!(!filter.matches(&hello));
+        assert!(!filter.allows_zid(&ZenohId::from_str("a1ff").unwrap()));
+        assert!(filter.allows_zid(&ZenohId::from_str("A1B2").unwrap()));
+        hello.zid = Some(ZenohId::from_str("b2").unwrap());
+        assert!(filter.matches(&hello));
+        hello.zid = Some(ZenohId::from_str("b2b2").unwrap());
+        assert!(!filter.matches(&hello));
+        hello.zid = None;
+        assert!(!filter.matches(&hello));
+
+        hello.zid = Some(ZenohId::from_str("a1b2").unwrap());
+        hello.locators = locators[3..].to_vec();
+        assert!(!filter.matches(&hello));
+    }
+
+    #[test]
+    fn hello_dedup() {
+        let seen = Mutex::new(HashMap::new());
+        let mut hello = Hello {
+            zid: Some(ZenohId::rand()),
+            whatami: WhatAmI::Peer,
+            locators: vec![],
+            metadata: None,
+        };
+        assert!(is_new_hello(&seen, &hello));
+        assert!(!is_new_hello(&seen, &hello));
+        std::thread::sleep(HELLO_DEDUP_PERIOD);
+        assert!(is_new_hello(&seen, &hello));
+
+        hello.zid = None;
+        assert!(is_new_hello(&seen, &hello));
+        assert!(is_new_hello(&seen, &hello));
+    }
+
+    #[test]
+    fn multicast_addresses() {
+        let mut config = Config::default();
+        config
+            .insert_json5("scouting/multicast/address", r#""[ff02::224]:7446""#)
+            .unwrap();
+        config
+            .insert_json5("scouting/multicast/address_v6", r#""[ff02::225]:7446""#)
+            .unwrap();
+        let addrs = Runtime::multicast_addresses(&config).unwrap();
+        assert_eq!(addrs.len(), 2);
+        assert!(addrs.iter().all(SocketAddr::is_ipv6));
+
+        config
+            .insert_json5("scouting/multicast/address_v6", r#""224.0.0.225:7446""#)
+            .unwrap();
+        assert!(Runtime::multicast_addresses(&config).is_err());
+    }
+}
@@ -21,7 +21,7 @@ use zenoh_config::ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT;
 use zenoh_core::zlock;
 use zenoh_protocol::{
     core::{
-        key_expr::keyexpr, Channel, CongestionControl, ConsolidationMode, QueryTarget,
+        key_expr::keyexpr, Channel, CongestionControl, ConsolidationMode, Priority, QueryTarget,
         QueryableInfo, Reliability, SubInfo, SubMode, WhatAmI, WireExpr, ZInt, ZenohId,
         EMPTY_EXPR_ID,
     },
@@ -176,6 +176,51 @@ fn match_test() {
     }
 }
 
+#[test]
+fn match_siblings_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+    let primitives = Arc::new(DummyPrimitives::new());
+    let face = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives,
+    );
+    let mut face = face.upgrade().unwrap();
+    for i in 0..1000 {
+        register_expr(&tables, &mut face, i, &format!("a/{i}/b").as_str().into());
+    }
+    register_expr(&tables, &mut face, 1000, &"a/*/b".into());
+    register_expr(&tables, &mut face, 1001, &"a/1$*/b".into());
+    register_expr(&tables, &mut face, 1002, &"a/**".into());
+
+    let matches = |key_expr: &str| {
+        let tables = zread!(tables.tables);
+        let mut matches = Resource::get_matches(&tables, keyexpr::new(key_expr).unwrap())
+            .iter()
+            .map(|m| m.upgrade().unwrap().expr())
+            .collect::<Vec<_>>();
+        matches.sort();
+        matches
+    };
+    assert_eq!(matches("a/12/b"), ["a/**", "a/*/b", "a/1$*/b", "a/12/b"]);
+    assert_eq!(matches("a/2000/b"), ["a/**", "a/*/b"]);
+    assert_eq!(matches("a/*/b").len(), 1003);
+
+    register_expr(&tables, &mut face, 1003, &"a/2000/b".into());
+    assert_eq!(matches("a/2000/b"), ["a/**", "a/*/b", "a/2000/b"]);
+}
+
 #[test]
 fn clean_test() {
     let tables = TablesLock {
@@ -375,19 +420,33 @@ fn clean_test() {
 
 pub struct ClientPrimitives {
     data: std::sync::Mutex<Option<WireExpr<'static>>>,
+    payload: std::sync::Mutex<Option<ZBuf>>,
+    channel: std::sync::Mutex<Option<Channel>>,
+    query: std::sync::Mutex<Option<(ZInt, Priority)>>,
+    reply: std::sync::Mutex<Option<(ZInt, Priority)>>,
     mapping: std::sync::Mutex<std::collections::HashMap<ZInt, String>>,
+    subs: std::sync::Mutex<Vec<String>>,
+    congestion_drops: std::sync::Mutex<Vec<(String, ZInt)>>,
 }
 
 impl ClientPrimitives {
     pub fn new() -> ClientPrimitives {
         ClientPrimitives {
             data: std::sync::Mutex::new(None),
+            payload: std::sync::Mutex::new(None),
+            channel: std::sync::Mutex::new(None),
+            query: std::sync::Mutex::new(None),
+            reply: std::sync::Mutex::new(None),
             mapping: std::sync::Mutex::new(std::collections::HashMap::new()),
+            subs: std::sync::Mutex::new(vec![]),
+            congestion_drops: std::sync::Mutex::new(vec![]),
         }
     }
 
     pub fn clear_data(&self) {
         *self.data.lock().unwrap() = None;
+        *self.payload.lock().unwrap() = None;
+        *self.channel.lock().unwrap() = None;
     }
 }
 
@@ -418,6 +477,30 @@ impl ClientPrimitives {
             .map(|data| self.get_name(data))
     }
 
+    fn get_last_payload(&self) -> Option<ZBuf> {
+        self.payload.lock().unwrap().clone()
+    }
+
+    fn get_last_channel(&self) -> Option<Channel> {
+        *self.channel.lock().unwrap()
+    }
+
+    fn get_last_query(&self) -> Option<(ZInt, Priority)> {
+        *self.query.lock().unwrap()
+    }
+
+    fn get_last_reply(&self) -> Option<(ZInt, Priority)> {
+        *self.reply.lock().unwrap()
+    }
+
+    fn get_subs(&self) -> Vec<String> {
+        self.subs.lock().unwrap().clone()
+    }
+
+    fn get_congestion_drops(&self) -> Vec<(String, ZInt)> {
+        self.congestion_drops.lock().unwrap().clone()
+    }
+
     #[allow(dead_code)]
     fn get_last_key(&self) -> Option<WireExpr> {
         self.data.lock().unwrap().as_ref().cloned()
@@ -439,10 +522,12 @@ impl Primitives for ClientPrimitives {
 
     fn decl_subscriber(
         &self,
-        _key_expr: &WireExpr,
+        key_expr: &WireExpr,
         _sub_info: &SubInfo,
         _routing_context: Option<RoutingContext>,
     ) {
+        let name = self.get_name(key_expr);
+        zlock!(self.subs).push(name);
     }
     fn forget_subscriber(&self, _key_expr: &WireExpr, _routing_context: Option<RoutingContext>) {}
 
@@ -458,37 +543,47 @@ impl Primitives for ClientPrimitives {
     fn send_data(
         &self,
         key_expr: &WireExpr,
-        _payload: ZBuf,
-        _channel: Channel,
+        payload: ZBuf,
+        channel: Channel,
         _congestion_control: CongestionControl,
         _info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
     ) {
         *zlock!(self.data) = Some(key_expr.to_owned());
+        *zlock!(self.payload) = Some(payload);
+        *zlock!(self.channel) = Some(channel);
     }
 
     fn send_query(
         &self,
         _key_expr: &WireExpr,
         _parameters: &str,
-        _qid: ZInt,
+        qid: ZInt,
         _target: QueryTarget,
         _consolidation: ConsolidationMode,
+        _timeout: Option<Duration>,
+        _destinations: Option<&[ZenohId]>,
         _body: Option<QueryBody>,
+        priority: Priority,
         _routing_context: Option<RoutingContext>,
     ) {
+        *zlock!(self.query) = Some((qid, priority));
     }
 
     fn send_reply_data(
         &self,
-        _qid: ZInt,
+        qid: ZInt,
         _replier_id: ZenohId,
         _key_expr: WireExpr,
         _info: Option<DataInfo>,
         _payload: ZBuf,
+        priority: Priority,
     ) {
+        *zlock!(self.reply) = Some((qid, priority));
+    }
+    fn send_reply_final(&self, qid: ZInt, priority: Priority) {
+        *zlock!(self.reply) = Some((qid, priority));
     }
-    fn send_reply_final(&self, _qid: ZInt) {}
 
     fn send_pull(
         &self,
@@ -500,6 +595,10 @@ impl Primitives for ClientPrimitives {
     }
 
     fn send_close(&self) {}
+
+    fn send_congestion_drop(&self, key_expr: &str, dropped: ZInt) {
+        zlock!(self.congestion_drops).push((key_expr.to_string(), dropped));
+    }
 }
 
 #[test]
@@ -730,3 +829,768 @@ fn client_test() {
     // mapping strategy check
     // assert_eq!(primitives2.get_last_key().unwrap(), KeyExpr::IdWithSuffix(31, "/z2_pub1".to_string()));
 }
+
+#[test]
+fn reliability_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives0,
+    );
+
+    let mut subscribers = vec![];
+    for reliability in [Reliability::Reliable, Reliability::BestEffort] {
+        let primitives = Arc::new(ClientPrimitives::new());
+        let face = zwrite!(tables.tables).open_face(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            primitives.clone(),
+        );
+        declare_client_subscription(
+            &tables,
+            zread!(tables.tables),
+            &mut face.upgrade().unwrap(),
+            &"test/reliability/**".into(),
+            &SubInfo {
+                reliability,
+                mode: SubMode::Push,
+            },
+        );
+        subscribers.push((primitives, reliability));
+    }
+
+    for publisher_reliability in [Reliability::Reliable, Reliability::BestEffort] {
+        full_reentrant_route_data(
+            &tables.tables,
+            &face0.upgrade().unwrap(),
+            &"test/reliability/data".into(),
+            Channel {
+                priority: Default::default(),
+                reliability: publisher_reliability,
+            },
+            CongestionControl::default(),
+            None,
+            ZBuf::default(),
+            None,
+        );
+        for (primitives, sub_reliability) in &subscribers {
+            let expected = if publisher_reliability == Reliability::Reliable {
+                *sub_reliability
+            } else {
+                Reliability::BestEffort
+            };
+            assert_eq!(primitives.get_last_channel().unwrap().reliability, expected);
+            primitives.clear_data();
+        }
+    }
+}
+
+#[test]
+fn congestion_drop_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+    zwrite!(tables.tables).congestion_drop_interval = Some(Duration::from_millis(10));
+
+    // A local session and a remote node publishing, and a subscriber notifying drops
+    let local = Arc::new(ClientPrimitives::new());
+    zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        local.clone(),
+    );
+    let remote = Arc::new(ClientPrimitives::new());
+    zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        remote.clone(),
+    );
+    let subscriber = Arc::new(ClientPrimitives::new());
+    let face = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Client,
+        subscriber.clone(),
+    );
+    declare_client_subscription(
+        &tables,
+        zread!(tables.tables),
+        &mut face.upgrade().unwrap(),
+        &"test/drop/**".into(),
+        &SubInfo::default(),
+    );
+
+    // The notification goes to the faces whose data is routed to the subscriber, the local
+    // sessions at once and the other faces at the end of the interval
+    let face = face.upgrade().unwrap();
+    route_congestion_drop(&tables.tables, &face, "test/drop/data", 3);
+    assert_eq!(
+        local.get_congestion_drops(),
+        vec![("test/drop/data".into(), 3)]
+    );
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(
+        remote.get_congestion_drops(),
+        vec![("test/drop/data".into(), 3)]
+    );
+    assert!(subscriber.get_congestion_drops().is_empty());
+
+    // The notifications for data not routed to the subscriber or for invalid key expressions
+    // are ignored
+    route_congestion_drop(&tables.tables, &face, "test/other", 1);
+    route_congestion_drop(&tables.tables, &face, "test//drop", 1);
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(local.get_congestion_drops().len(), 1);
+    assert_eq!(remote.get_congestion_drops().len(), 1);
+}
+
+#[test]
+fn route_payload_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+    let mut config = zenoh_config::Config::default();
+    config
+        .routing
+        .set_accounting(vec!["test/payload/**".try_into().unwrap()])
+        .unwrap();
+    zwrite!(tables.tables).accounting =
+        crate::net::routing::traffic::TrafficAccounting::from_config(&config);
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives0,
+    );
+
+    let payload = ZBuf::from(vec![1u8, 2, 3, 4]);
+    let mut subscribers: Vec<Arc<ClientPrimitives>> = vec![];
+    // The payload is moved to the only face of the route, then to the last face of a longer route
+    for (count, tx_msgs) in [(1, 1), (3, 4)] {
+        while subscribers.len() < count {
+            let primitives = Arc::new(ClientPrimitives::new());
+            let face = zwrite!(tables.tables).open_face(
+                ZenohId::try_from([1]).unwrap(),
+                WhatAmI::Client,
+                primitives.clone(),
+            );
+            declare_client_subscription(
+                &tables,
+                zread!(tables.tables),
+                &mut face.upgrade().unwrap(),
+                &"test/payload/**".into(),
+                &SubInfo::default(),
+            );
+            subscribers.push(primitives);
+        }
+
+        full_reentrant_route_data(
+            &tables.tables,
+            &face0.upgrade().unwrap(),
+            &"test/payload/data".into(),
+            Channel::default(),
+            CongestionControl::default(),
+            None,
+            payload.clone(),
+            None,
+        );
+        for primitives in &subscribers {
+            assert_eq!(primitives.get_last_name().unwrap(), "test/payload/data");
+            assert_eq!(primitives.get_last_payload().unwrap(), payload);
+            primitives.clear_data();
+        }
+        let accounting = zread!(tables.tables).accounting.json();
+        assert_eq!(accounting["test/payload/**"]["tx_msgs"], tx_msgs);
+        assert_eq!(accounting["test/payload/**"]["tx_bytes"], 4 * tx_msgs);
+    }
+}
+
+#[test]
+fn peer_forwarding_test() {
+    for forwarding in [false, true] {
+        let tables = TablesLock {
+            tables: RwLock::new(Tables::new(
+                ZenohId::try_from([1]).unwrap(),
+                WhatAmI::Peer,
+                Some(Arc::new(HLC::default())),
+                false,
+                true,
+                Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+            )),
+            ctrl_lock: Mutex::new(()),
+            queries_lock: RwLock::new(()),
+        };
+        zwrite!(tables.tables).peer_forwarding = forwarding;
+
+        let primitives0 = Arc::new(ClientPrimitives::new());
+        let face0 = zwrite!(tables.tables).open_face(
+            ZenohId::try_from([2]).unwrap(),
+            WhatAmI::Peer,
+            primitives0.clone(),
+        );
+        let primitives1 = Arc::new(ClientPrimitives::new());
+        let face1 = zwrite!(tables.tables).open_face(
+            ZenohId::try_from([3]).unwrap(),
+            WhatAmI::Peer,
+            primitives1,
+        );
+        declare_client_subscription(
+            &tables,
+            zread!(tables.tables),
+            &mut face0.upgrade().unwrap(),
+            &"test/forwarding".into(),
+            &SubInfo {
+                reliability: Reliability::Reliable,
+                mode: SubMode::Push,
+            },
+        );
+
+        // The data of a peer only reaches the other peers through a forwarding peer
+        full_reentrant_route_data(
+            &tables.tables,
+            &face1.upgrade().unwrap(),
+            &"test/forwarding".into(),
+            Channel::default(),
+            CongestionControl::default(),
+            None,
+            ZBuf::default(),
+            None,
+        );
+        assert_eq!(primitives0.get_last_name().is_some(), forwarding);
+
+        // But never back to the peer it comes from
+        primitives0.clear_data();
+        full_reentrant_route_data(
+            &tables.tables,
+            &face0.upgrade().unwrap(),
+            &"test/forwarding".into(),
+            Channel::default(),
+            CongestionControl::default(),
+            None,
+            ZBuf::default(),
+            None,
+        );
+        assert!(primitives0.get_last_name().is_none());
+    }
+}
+
+#[test]
+fn query_priority_test() {
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        primitives0.clone(),
+    );
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut face0.upgrade().unwrap(),
+        &"test/priority".into(),
+        &QueryableInfo {
+            complete: 1,
+            distance: 0,
+            load_balanced: false,
+        },
+    );
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Client,
+        primitives1.clone(),
+    );
+
+    route_query(
+        &tables,
+        &face1.upgrade().unwrap(),
+        &"test/priority".into(),
+        "",
+        1,
+        QueryTarget::default(),
+        ConsolidationMode::None,
+        None,
+        None,
+        None,
+        Priority::InteractiveHigh,
+        None,
+    );
+    let (qid, priority) = primitives0.get_last_query().unwrap();
+    assert_eq!(priority, Priority::InteractiveHigh);
+
+    // The replies are sent back with the priority of the query they answer
+    route_send_reply_data(
+        &tables,
+        &mut face0.upgrade().unwrap(),
+        qid,
+        ZenohId::try_from([2]).unwrap(),
+        "test/priority".into(),
+        None,
+        ZBuf::default(),
+    );
+    assert_eq!(
+        primitives1.get_last_reply(),
+        Some((1, Priority::InteractiveHigh))
+    );
+    *zlock!(primitives1.reply) = None;
+    route_send_reply_final(&tables, &mut face0.upgrade().unwrap(), qid);
+    assert_eq!(
+        primitives1.get_last_reply(),
+        Some((1, Priority::InteractiveHigh))
+    );
+}
+
+#[test]
+fn query_timeout_test() {
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        primitives0.clone(),
+    );
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut face0.upgrade().unwrap(),
+        &"test/timeout".into(),
+        &QueryableInfo {
+            complete: 1,
+            distance: 0,
+            load_balanced: false,
+        },
+    );
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Client,
+        primitives1.clone(),
+    );
+    let query = |qid, timeout| {
+        route_query(
+            &tables,
+            &face1.upgrade().unwrap(),
+            &"test/timeout".into(),
+            "",
+            qid,
+            QueryTarget::default(),
+            ConsolidationMode::None,
+            Some(timeout),
+            None,
+            None,
+            Priority::default(),
+            None,
+        )
+    };
+
+    // An expired query is not routed and finalized at once
+    query(1, Duration::ZERO);
+    assert!(primitives0.get_last_query().is_none());
+    assert_eq!(primitives1.get_last_reply(), Some((1, Priority::default())));
+
+    // A pending query is abandoned when it expires
+    *zlock!(primitives1.reply) = None;
+    query(2, Duration::from_millis(100));
+    assert!(primitives0.get_last_query().is_some());
+    assert!(primitives1.get_last_reply().is_none());
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(primitives1.get_last_reply(), Some((2, Priority::default())));
+}
+
+#[test]
+fn query_load_balancing_test() {
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+
+    let qabl_info = QueryableInfo {
+        complete: 0,
+        distance: 0,
+        load_balanced: true,
+    };
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        primitives0.clone(),
+    );
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut face0.upgrade().unwrap(),
+        &"test/balanced".into(),
+        &qabl_info,
+    );
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Client,
+        primitives1.clone(),
+    );
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut face1.upgrade().unwrap(),
+        &"test/balanced".into(),
+        &qabl_info,
+    );
+    let primitives2 = Arc::new(ClientPrimitives::new());
+    let face2 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([4]).unwrap(),
+        WhatAmI::Client,
+        primitives2,
+    );
+
+    let query = |qid| {
+        *zlock!(primitives0.query) = None;
+        *zlock!(primitives1.query) = None;
+        route_query(
+            &tables,
+            &face2.upgrade().unwrap(),
+            &"test/balanced".into(),
+            "",
+            qid,
+            QueryTarget::BestMatching,
+            ConsolidationMode::None,
+            None,
+            None,
+            None,
+            Priority::default(),
+            None,
+        );
+        (
+            primitives0.get_last_query().is_some(),
+            primitives1.get_last_query().is_some(),
+        )
+    };
+
+    // Each query is routed to only one of the queryables
+    let (first0, first1) = query(1);
+    assert_ne!(first0, first1);
+    // The first one hasn't replied yet, so the next query goes to the other one
+    assert_eq!(query(2), (first1, first0));
+}
+
+#[test]
+fn query_destinations_test() {
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+
+    let qabl_info = QueryableInfo {
+        complete: 0,
+        distance: 0,
+        load_balanced: false,
+    };
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        primitives0.clone(),
+    );
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut face0.upgrade().unwrap(),
+        &"test/destinations".into(),
+        &qabl_info,
+    );
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Client,
+        primitives1.clone(),
+    );
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut face1.upgrade().unwrap(),
+        &"test/destinations".into(),
+        &qabl_info,
+    );
+    let primitives2 = Arc::new(ClientPrimitives::new());
+    let face2 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([4]).unwrap(),
+        WhatAmI::Client,
+        primitives2.clone(),
+    );
+
+    let query = |qid, destinations: &[ZenohId]| {
+        *zlock!(primitives0.query) = None;
+        *zlock!(primitives1.query) = None;
+        route_query(
+            &tables,
+            &face2.upgrade().unwrap(),
+            &"test/destinations".into(),
+            "",
+            qid,
+            QueryTarget::All,
+            ConsolidationMode::None,
+            None,
+            Some(destinations),
+            None,
+            Priority::default(),
+            None,
+        );
+        (
+            primitives0.get_last_query().is_some(),
+            primitives1.get_last_query().is_some(),
+        )
+    };
+
+    // Only the queryable of the destination is queried
+    assert_eq!(query(1, &[ZenohId::try_from([3]).unwrap()]), (false, true));
+    // A query without any reachable destination is finalized at once
+    *zlock!(primitives2.reply) = None;
+    assert_eq!(query(2, &[ZenohId::try_from([5]).unwrap()]), (false, false));
+    assert_eq!(primitives2.get_last_reply(), Some((2, Priority::default())));
+}
+
+#[test]
+fn quota_declarations_test() {
+    use crate::net::routing::face::Face;
+    use crate::net::routing::quotas::Quotas;
+    use zenoh_config::{Config, ValidatedMap};
+    use zenoh_protocol::zenoh::{Declaration, Subscriber, ZenohMessage};
+    use zenoh_transport::{DeMux, TransportPeerEventHandler};
+
+    let mut config = Config::default();
+    config
+        .insert_json5("transport/quotas", "{ max_subscribers: 1 }")
+        .unwrap();
+    let enforcer = Quotas::from_config(&config)
+        .enforcer(&ZenohId::try_from([2]).unwrap())
+        .unwrap();
+
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(ClientPrimitives::new()),
+    );
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let _face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Client,
+        primitives1.clone(),
+    );
+
+    let face = Face {
+        tables: tables.clone(),
+        state: face0.upgrade().unwrap(),
+    };
+    let demux = DeMux::new(face.clone());
+    for key in ["test/quota/a", "test/quota/b", "test/quota/c"] {
+        let msg = ZenohMessage::make_declare(
+            vec![Declaration::Subscriber(Subscriber {
+                key: key.into(),
+                info: SubInfo {
+                    reliability: Reliability::Reliable,
+                    mode: SubMode::Push,
+                },
+            })],
+            None,
+            None,
+        );
+        if enforcer.check_message(&face, &msg).is_ok() {
+            demux.handle_message(msg).unwrap();
+        }
+    }
+
+    // The declarations exceeding the quota are rejected before being propagated
+    assert_eq!(primitives1.get_subs(), vec!["test/quota/a".to_string()]);
+    assert_eq!(face.state.remote_subs.len(), 1);
+}
+
+#[test]
+fn static_routes_test() {
+    use crate::net::routing::static_routes::StaticRoutes;
+    use zenoh_config::{Config, ValidatedMap};
+
+    let hlc = Arc::new(HLC::default());
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Peer,
+            Some(hlc.clone()),
+            false,
+            true,
+            Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "routing/static_routes",
+            r#"[{ key_exprs: ["test/static/**"], zid: "03" }]"#,
+        )
+        .unwrap();
+    zwrite!(tables.tables).static_routes = StaticRoutes::from_config(&config).unwrap();
+
+    // A client subscribes, the static route next hop is a peer
+    let subscriber = Arc::new(ClientPrimitives::new());
+    let subscriber_face = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        subscriber.clone(),
+    );
+    let next_hop = Arc::new(ClientPrimitives::new());
+    let next_hop_face = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Peer,
+        next_hop.clone(),
+    );
+    let publisher_face = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([4]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(DummyPrimitives::new()),
+    );
+    let peer_face = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([5]).unwrap(),
+        WhatAmI::Peer,
+        Arc::new(DummyPrimitives::new()),
+    );
+    declare_client_subscription(
+        &tables,
+        zread!(tables.tables),
+        &mut subscriber_face.upgrade().unwrap(),
+        &"test/static/**".into(),
+        &SubInfo {
+            reliability: Reliability::Reliable,
+            mode: SubMode::Push,
+        },
+    );
+
+    let route = |face: &std::sync::Weak<crate::net::routing::face::FaceState>, info: &DataInfo| {
+        subscriber.clear_data();
+        next_hop.clear_data();
+        full_reentrant_route_data(
+            &tables.tables,
+            &face.upgrade().unwrap(),
+            &"test/static/a".into(),
+            Channel::default(),
+            CongestionControl::default(),
+            Some(info.clone()),
+            ZBuf::default(),
+            None,
+        );
+    };
+
+    // The data of the static route are only forwarded to its next hop
+    let info = DataInfo {
+        timestamp: Some(hlc.new_timestamp()),
+        ..Default::default()
+    };
+    route(&publisher_face, &info);
+    assert!(subscriber.get_last_name().is_none());
+    assert!(next_hop.get_last_name().is_some());
+
+    // The same data reaching the node again through a cycle are dropped
+    route(&publisher_face, &info);
+    assert!(next_hop.get_last_name().is_none());
+
+    // The routing restrictions still apply: a peer does not forward between peers
+    let info = DataInfo {
+        timestamp: Some(hlc.new_timestamp()),
+        ..Default::default()
+    };
+    route(&peer_face, &info);
+    assert!(next_hop.get_last_name().is_none());
+
+    // The learned routes apply once the next hop is disconnected
+    router::close_face(&tables, &next_hop_face);
+    let info = DataInfo {
+        timestamp: Some(hlc.new_timestamp()),
+        ..Default::default()
+    };
+    route(&publisher_face, &info);
+    assert!(subscriber.get_last_name().is_some());
+}
@@ -18,6 +18,7 @@ use crate::prelude::Selector;
 pub use crate::runtime::Runtime;
 pub use crate::Result as ZResult;
 use zenoh_core::zconfigurable;
+use zenoh_result::bail;
 
 zconfigurable! {
     pub static ref PLUGIN_PREFIX: String = "zenoh_plugin_".to_string();
@@ -65,6 +66,21 @@ pub trait RunningPluginTrait: Send + Sync + std::any::Any {
         selector: &'a Selector<'a>,
         plugin_status_key: &str,
     ) -> ZResult<Vec<Response>>;
+    /// Used to perform an action requested by a write on `@/router/<zid>/actions/plugins/<plugin_name>/<action>`.
+    ///
+    /// `action` is the part of the key following the plugin's name, and `payload` the written value.
+    /// Plugins that don't support any action may rely on the default implementation, which refuses them all.
+    fn adminspace_action(&self, action: &str, _payload: &[u8]) -> ZResult<()> {
+        bail!("Unsupported plugin action `{}`", action)
+    }
+    /// Used by `zenohd` to detect that your plugin crashed or exited while running, in which case it is
+    /// stopped, then restarted should its `__restart__` configuration request it.
+    ///
+    /// Plugins relying on background tasks should report whether these are still running.
+    /// The default implementation considers the plugin alive for as long as it is running.
+    fn is_alive(&self) -> bool {
+        true
+    }
 }
 
 /// The zenoh plugins manager. It handles the full lifetime of plugins, from loading to destruction.
@@ -31,7 +31,7 @@ pub(crate) mod common {
     pub(crate) type Id = usize;
 
     pub use crate::config::{self, Config, ValidatedMap};
-    pub use crate::handlers::IntoCallbackReceiverPair;
+    pub use crate::handlers::{IntoCallbackReceiverPair, RecvDeadlineAsync};
     pub use crate::selector::{Parameter, Parameters, Selector};
     pub use crate::session::{Session, SessionDeclarations};
 
@@ -62,6 +62,7 @@ pub(crate) mod common {
 /// Prelude to import when using Zenoh's sync API.
 pub mod sync {
     pub use super::common::*;
+    pub use crate::blocking::SyncSession;
     pub use zenoh_core::SyncResolve;
 }
 /// Prelude to import when using Zenoh's async API.
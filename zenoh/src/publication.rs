@@ -14,16 +14,26 @@
 
 //! Publishing primitives.
 
-use crate::net::transport::Primitives;
+use crate::net::transport::{BatchedData, Primitives};
 use crate::prelude::*;
 use crate::subscriber::Reliability;
 use crate::Encoding;
 use crate::SessionRef;
 use crate::Undeclarable;
+use std::fmt;
 use std::future::Ready;
-use zenoh_core::{zread, AsyncResolve, Resolvable, Resolve, SyncResolve};
-use zenoh_protocol::{core::Channel, zenoh::DataInfo};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_buffers::ZBuf;
+use zenoh_core::{zlock, zread, zwrite, AsyncResolve, Resolvable, Resolve, SyncResolve};
+use zenoh_protocol::{
+    core::Channel,
+    zenoh::{DataInfo, TraceContext},
+};
 use zenoh_result::ZResult;
+#[cfg(feature = "shared-memory")]
+use zenoh_shm::{SharedMemoryBuf, SharedMemoryProvider};
 
 /// The kind of congestion control.
 pub use zenoh_protocol::core::CongestionControl;
@@ -95,6 +105,13 @@ impl PutBuilder<'_, '_> {
         self
     }
 
+    /// Change the reliability of the channel on which the data is sent, [`Reliable`](Reliability::Reliable) by default.
+    #[inline]
+    pub fn reliability(mut self, reliability: Reliability) -> Self {
+        self.publisher = self.publisher.reliability(reliability);
+        self
+    }
+
     /// Restrict the matching subscribers that will receive the published data
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_macros::unstable]
@@ -104,12 +121,34 @@ impl PutBuilder<'_, '_> {
         self
     }
 
+    /// Mark the written data as latched, see [`PublisherBuilder::latched`].
+    #[inline]
+    pub fn latched(mut self, latched: bool) -> Self {
+        self.publisher = self.publisher.latched(latched);
+        self
+    }
+
     pub fn kind(mut self, kind: SampleKind) -> Self {
         self.kind = kind;
         self
     }
 }
 
+/// Starts a new distributed trace for a publication if its `publish` span is enabled,
+/// so that the spans of the routers and subscribers it crosses can be correlated.
+fn new_trace_context(span: &tracing::Span) -> Option<TraceContext> {
+    if span.is_disabled() {
+        return None;
+    }
+    let trace_context = TraceContext {
+        trace_id: rand::random(),
+        span_id: rand::random(),
+        flags: TraceContext::SAMPLED,
+    };
+    span.record("trace", tracing::field::display(&trace_context));
+    Some(trace_context)
+}
+
 impl Resolvable for PutBuilder<'_, '_> {
     type To = ZResult<()>;
 }
@@ -124,11 +163,18 @@ impl SyncResolve for PutBuilder<'_, '_> {
         } = self;
         let key_expr = publisher.key_expr?;
         log::trace!("write({:?}, [...])", &key_expr);
-        let primitives = zread!(publisher.session.state)
-            .primitives
-            .as_ref()
-            .unwrap()
-            .clone();
+        let span =
+            tracing::trace_span!("publish", key_expr = %key_expr, trace = tracing::field::Empty);
+        let _enter = span.enter();
+        let (primitives, payload) = {
+            let state = zread!(publisher.session.state);
+            let payload = if publisher.destination != Locality::SessionLocal {
+                Some(state.encode_payload(&key_expr, value.payload.clone())?)
+            } else {
+                None
+            };
+            (state.primitives.as_ref().unwrap().clone(), payload)
+        };
 
         let info = DataInfo {
             kind,
@@ -138,6 +184,8 @@ impl SyncResolve for PutBuilder<'_, '_> {
                 None
             },
             timestamp: publisher.session.runtime.new_timestamp(),
+            trace_context: new_trace_context(&span),
+            latched: publisher.latched,
             ..Default::default()
         };
         let data_info = if info != DataInfo::default() {
@@ -146,13 +194,13 @@ impl SyncResolve for PutBuilder<'_, '_> {
             None
         };
 
-        if publisher.destination != Locality::SessionLocal {
+        if let Some(payload) = payload {
             primitives.send_data(
                 &key_expr.to_wire(&publisher.session),
-                value.payload.clone(),
+                payload,
                 Channel {
                     priority: publisher.priority.into(),
-                    reliability: Reliability::Reliable, // @TODO: need to check subscriptions to determine the right reliability value
+                    reliability: publisher.reliability,
                 },
                 publisher.congestion_control,
                 data_info.clone(),
@@ -179,9 +227,138 @@ impl AsyncResolve for PutBuilder<'_, '_> {
     }
 }
 
+/// A builder for initializing a [`put_batch`](crate::Session::put_batch) operation.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+/// use zenoh::publication::CongestionControl;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// session
+///     .put_batch([("robot/pose", "1.0,2.0"), ("robot/covariance", "0.1,0.2")])
+///     .congestion_control(CongestionControl::Block)
+///     .res()
+///     .await
+///     .unwrap();
+/// # })
+/// ```
+#[must_use = "Resolvables do nothing unless you resolve them using the `res` method from either `SyncResolve` or `AsyncResolve`"]
+#[derive(Debug)]
+pub struct PutBatchBuilder<'a, 'b> {
+    pub(crate) session: &'a Session,
+    pub(crate) samples: ZResult<Vec<(KeyExpr<'b>, Value)>>,
+    pub(crate) congestion_control: CongestionControl,
+    pub(crate) priority: Priority,
+    pub(crate) reliability: Reliability,
+}
+
+impl PutBatchBuilder<'_, '_> {
+    /// Change the `congestion_control` to apply when routing the data.
+    #[inline]
+    pub fn congestion_control(mut self, congestion_control: CongestionControl) -> Self {
+        self.congestion_control = congestion_control;
+        self
+    }
+
+    /// Change the priority of the written data.
+    #[inline]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Change the reliability of the channel on which the data is sent, [`Reliable`](Reliability::Reliable) by default.
+    #[inline]
+    pub fn reliability(mut self, reliability: Reliability) -> Self {
+        self.reliability = reliability;
+        self
+    }
+}
+
+impl Resolvable for PutBatchBuilder<'_, '_> {
+    type To = ZResult<()>;
+}
+
+impl SyncResolve for PutBatchBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        send_batch(
+            self.session,
+            self.samples?,
+            Channel {
+                priority: self.priority.into(),
+                reliability: self.reliability,
+            },
+            self.congestion_control,
+            Locality::Any,
+        )
+    }
+}
+
+// Sends all the `samples` in one batch, and delivers them to the matching local subscribers
+fn send_batch(
+    session: &Session,
+    samples: Vec<(KeyExpr<'_>, Value)>,
+    channel: Channel,
+    congestion_control: CongestionControl,
+    destination: Locality,
+) -> ZResult<()> {
+    log::trace!("write_batch({} samples)", samples.len());
+    let mut batch = Vec::with_capacity(samples.len());
+    let mut local = Vec::with_capacity(samples.len());
+    let primitives = {
+        let state = zread!(session.state);
+        for (key_expr, value) in samples {
+            let info = DataInfo {
+                encoding: if value.encoding != Encoding::default() {
+                    Some(value.encoding)
+                } else {
+                    None
+                },
+                timestamp: session.runtime.new_timestamp(),
+                ..Default::default()
+            };
+            let data_info = if info != DataInfo::default() {
+                Some(info)
+            } else {
+                None
+            };
+            if destination != Locality::SessionLocal {
+                let payload = state.encode_payload(&key_expr, value.payload.clone())?;
+                batch.push(BatchedData {
+                    key_expr: key_expr.to_wire(session).to_owned(),
+                    payload,
+                    data_info: data_info.clone(),
+                    routing_context: None,
+                });
+            }
+            if destination != Locality::Remote {
+                local.push((key_expr, data_info, value.payload));
+            }
+        }
+        state.primitives.as_ref().unwrap().clone()
+    };
+
+    if !batch.is_empty() {
+        primitives.send_data_batch(batch, channel, congestion_control);
+    }
+    for (key_expr, data_info, payload) in local {
+        session.handle_data(true, &key_expr.to_wire(session), data_info, payload);
+    }
+    Ok(())
+}
+
+impl AsyncResolve for PutBatchBuilder<'_, '_> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
 use futures::Sink;
 use std::convert::TryFrom;
-use std::convert::TryInto;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use zenoh_result::Error;
@@ -221,7 +398,19 @@ pub struct Publisher<'a> {
     pub(crate) key_expr: KeyExpr<'a>,
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
+    pub(crate) reliability: Reliability,
     pub(crate) destination: Locality,
+    pub(crate) block_timeout: Option<Duration>,
+    #[cfg(feature = "shared-memory")]
+    pub(crate) shm: Option<SharedMemoryProvider>,
+    // The buffer reused by `write_from` once the previous publications have released it
+    pub(crate) buffer: Arc<Mutex<Arc<Vec<u8>>>>,
+    // Background publishers are not undeclared when dropped
+    pub(crate) background: bool,
+    pub(crate) latched: bool,
+    // The sequence number of the next publication, if the publications are sequenced
+    pub(crate) sequence: Option<AtomicU64>,
+    pub(crate) dropped_downstream: Arc<AtomicU64>,
 }
 
 impl<'a> Publisher<'a> {
@@ -229,6 +418,16 @@ impl<'a> Publisher<'a> {
         &self.key_expr
     }
 
+    /// The number of best-effort samples published on the key expression of this `Publisher`
+    /// that the infrastructure dropped because of congestion, since it was declared.
+    ///
+    /// The drops are only counted when the routers and peers on the way notify them, see the
+    /// `routing/congestion_drop` configuration, which they do at most once per configured
+    /// interval, so that the count lags behind the actual drops.
+    pub fn dropped_downstream(&self) -> u64 {
+        self.dropped_downstream.load(Ordering::Relaxed)
+    }
+
     /// Change the `congestion_control` to apply when routing the data.
     #[inline]
     pub fn congestion_control(mut self, congestion_control: CongestionControl) -> Self {
@@ -243,6 +442,15 @@ impl<'a> Publisher<'a> {
         self
     }
 
+    /// Change the reliability of the channel on which the data is sent, [`Reliable`](Reliability::Reliable) by default.
+    ///
+    /// The data is only delivered reliably to the subscribers that declared a reliable subscription.
+    #[inline]
+    pub fn reliability(mut self, reliability: Reliability) -> Self {
+        self.reliability = reliability;
+        self
+    }
+
     /// Restrict the matching subscribers that will receive the published data
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_macros::unstable]
@@ -252,6 +460,76 @@ impl<'a> Publisher<'a> {
         self
     }
 
+    /// Bound the time a publication with [`CongestionControl::Block`] may block waiting for
+    /// room in the transmission queues, e.g. when a downlink is dead but its transport hasn't
+    /// noticed yet.
+    ///
+    /// Past this duration the data is dropped on the congested links and the publication
+    /// returns a [`PublishTimeout`] error. There is no bound by default.
+    #[inline]
+    pub fn block_timeout(mut self, timeout: Duration) -> Self {
+        self.block_timeout = Some(timeout);
+        self
+    }
+
+    /// Allocate a shared memory buffer of at least `len` bytes from the
+    /// [`SharedMemoryProvider`] of this `Publisher`.
+    ///
+    /// Once filled, the buffer can be published without copy with [`put`](Publisher::put).
+    /// With [`WhenFull::Block`](zenoh_shm::WhenFull::Block), the allocation waits for enough memory
+    /// to be freed, asynchronously when resolved with `res_async`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh::shm::SharedMemoryProvider;
+    ///
+    /// let provider = SharedMemoryProvider::builder("example")
+    ///     .segment("data", 1024 * 1024)
+    ///     .build()
+    ///     .unwrap();
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let publisher = session
+    ///     .declare_publisher("key/expression")
+    ///     .shm_provider(provider)
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// let mut buf = publisher.alloc(4).res().await.unwrap();
+    /// unsafe { buf.as_mut_slice()[..4].copy_from_slice(b"data") };
+    /// publisher.put(buf).res().await.unwrap();
+    /// # })
+    /// ```
+    #[cfg(feature = "shared-memory")]
+    pub fn alloc(&self, len: usize) -> impl Resolve<ZResult<SharedMemoryBuf>> {
+        let provider = self.shm.clone().ok_or_else(|| {
+            zerror!(
+                "Publisher on {} has no shared memory provider",
+                self.key_expr
+            )
+        });
+        zenoh_core::ResolveFuture::new(async move { provider?.alloc_async(len).await })
+    }
+
+    // Runs `send`, giving up the pushes blocked for longer than the block timeout if any
+    fn send_with_block_timeout(&self, send: impl FnOnce() -> ZResult<()>) -> ZResult<()> {
+        match self.block_timeout {
+            Some(timeout) if self.congestion_control == CongestionControl::Block => {
+                let deadline = Instant::now() + timeout;
+                match crate::net::transport::with_push_deadline(deadline, send) {
+                    (_, true) => Err(PublishTimeout {
+                        key_expr: self.key_expr.to_string(),
+                        timeout,
+                    }
+                    .into()),
+                    (res, false) => res,
+                }
+            }
+            _ => send(),
+        }
+    }
+
     fn _write(&self, kind: SampleKind, value: Value) -> Publication {
         Publication {
             publisher: self,
@@ -315,6 +593,75 @@ impl<'a> Publisher<'a> {
         self._write(SampleKind::Delete, Value::empty())
     }
 
+    /// Put data copied from `payload` into a buffer owned by this `Publisher`.
+    ///
+    /// The buffer is reused as soon as the previous publication has been sent and isn't referenced
+    /// by any sample anymore, so that publishing samples of a fixed size doesn't allocate in the
+    /// steady state.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let publisher = session.declare_publisher("key/expression").res().await.unwrap();
+    /// let sample = [0u8; 64];
+    /// publisher.write_from(&sample).res().await.unwrap();
+    /// # })
+    /// ```
+    pub fn write_from(&self, payload: &[u8]) -> Publication {
+        if payload.is_empty() {
+            return self._write(SampleKind::Put, ZBuf::default().into());
+        }
+        let mut buffer = zlock!(self.buffer);
+        if Arc::get_mut(&mut buffer).is_none() {
+            *buffer = Arc::new(Vec::with_capacity(payload.len()));
+        }
+        let buf = Arc::get_mut(&mut buffer).unwrap(); // This unwrap is safe because the buffer was just replaced if shared
+        buf.clear();
+        buf.extend_from_slice(payload);
+        let payload = ZBuf::from(buffer.clone());
+        drop(buffer);
+        self._write(SampleKind::Put, payload.into())
+    }
+
+    /// Put data under the key expressions made of the key expression of this `Publisher`
+    /// joined with the given suffixes.
+    ///
+    /// All the data is sent in a single batch, serialized into as few frames as possible,
+    /// which is much cheaper than as many calls to [`put`](Publisher::put) when publishing
+    /// many small values at once.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let publisher = session.declare_publisher("map/tiles").res().await.unwrap();
+    /// publisher
+    ///     .put_iter((0..4).map(|i| (format!("{i}"), vec![0u8; 16])))
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn put_iter<I, S, IntoValue>(&self, samples: I) -> PublicationBatch
+    where
+        I: IntoIterator<Item = (S, IntoValue)>,
+        S: AsRef<str>,
+        IntoValue: Into<Value>,
+    {
+        PublicationBatch {
+            publisher: self,
+            samples: samples
+                .into_iter()
+                .map(|(suffix, value)| Ok((self.key_expr.join(&suffix)?, value.into())))
+                .collect(),
+        }
+    }
+
     /// Undeclares the [`Publisher`], informing the network that it needn't optimize publications for its key expression anymore.
     ///
     /// # Examples
@@ -381,7 +728,7 @@ impl AsyncResolve for PublisherUndeclaration<'_> {
 
 impl Drop for Publisher<'_> {
     fn drop(&mut self) {
-        if !self.key_expr.is_empty() {
+        if !self.key_expr.is_empty() && !self.background {
             let _ = self
                 .session
                 .undeclare_publication_intent(self.key_expr.clone())
@@ -410,11 +757,21 @@ impl SyncResolve for Publication<'_> {
             kind,
         } = self;
         log::trace!("write({:?}, [...])", publisher.key_expr);
-        let primitives = zread!(publisher.session.state)
-            .primitives
-            .as_ref()
-            .unwrap()
-            .clone();
+        let span = tracing::trace_span!(
+            "publish",
+            key_expr = %publisher.key_expr,
+            trace = tracing::field::Empty
+        );
+        let _enter = span.enter();
+        let (primitives, payload) = {
+            let state = zread!(publisher.session.state);
+            let payload = if publisher.destination != Locality::SessionLocal {
+                Some(state.encode_payload(&publisher.key_expr, value.payload.clone())?)
+            } else {
+                None
+            };
+            (state.primitives.as_ref().unwrap().clone(), payload)
+        };
 
         let info = DataInfo {
             kind,
@@ -424,6 +781,16 @@ impl SyncResolve for Publication<'_> {
                 None
             },
             timestamp: publisher.session.runtime.new_timestamp(),
+            source_id: publisher
+                .sequence
+                .as_ref()
+                .map(|_| publisher.session.runtime.zid),
+            source_sn: publisher
+                .sequence
+                .as_ref()
+                .map(|sn| sn.fetch_add(1, Ordering::Relaxed)),
+            trace_context: new_trace_context(&span),
+            latched: publisher.latched,
             ..Default::default()
         };
         let data_info = if info != DataInfo::default() {
@@ -432,19 +799,23 @@ impl SyncResolve for Publication<'_> {
             None
         };
 
-        if publisher.destination != Locality::SessionLocal {
-            primitives.send_data(
-                &publisher.key_expr.to_wire(&publisher.session),
-                value.payload.clone(),
-                Channel {
-                    priority: publisher.priority.into(),
-                    reliability: Reliability::Reliable, // @TODO: need to check subscriptions to determine the right reliability value
-                },
-                publisher.congestion_control,
-                data_info.clone(),
-                None,
-            );
-        }
+        let res = match payload {
+            Some(payload) => publisher.send_with_block_timeout(|| {
+                primitives.send_data(
+                    &publisher.key_expr.to_wire(&publisher.session),
+                    payload,
+                    Channel {
+                        priority: publisher.priority.into(),
+                        reliability: publisher.reliability,
+                    },
+                    publisher.congestion_control,
+                    data_info.clone(),
+                    None,
+                );
+                Ok(())
+            }),
+            None => Ok(()),
+        };
         if publisher.destination != Locality::Remote {
             publisher.session.handle_data(
                 true,
@@ -453,7 +824,7 @@ impl SyncResolve for Publication<'_> {
                 value.payload,
             );
         }
-        Ok(())
+        res
     }
 }
 
@@ -465,6 +836,43 @@ impl AsyncResolve for Publication<'_> {
     }
 }
 
+/// A [`Resolvable`] returned by [`Publisher::put_iter()`](Publisher::put_iter).
+pub struct PublicationBatch<'a> {
+    publisher: &'a Publisher<'a>,
+    samples: ZResult<Vec<(KeyExpr<'static>, Value)>>,
+}
+
+impl Resolvable for PublicationBatch<'_> {
+    type To = ZResult<()>;
+}
+
+impl SyncResolve for PublicationBatch<'_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let publisher = self.publisher;
+        let samples = self.samples?;
+        publisher.send_with_block_timeout(|| {
+            send_batch(
+                &publisher.session,
+                samples,
+                Channel {
+                    priority: publisher.priority.into(),
+                    reliability: publisher.reliability,
+                },
+                publisher.congestion_control,
+                publisher.destination,
+            )
+        })
+    }
+}
+
+impl AsyncResolve for PublicationBatch<'_> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
 impl<'a, IntoValue> Sink<IntoValue> for Publisher<'a>
 where
     IntoValue: Into<Value>,
@@ -515,7 +923,14 @@ pub struct PublisherBuilder<'a, 'b: 'a> {
     pub(crate) key_expr: ZResult<KeyExpr<'b>>,
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
+    pub(crate) reliability: Reliability,
     pub(crate) destination: Locality,
+    pub(crate) block_timeout: Option<Duration>,
+    #[cfg(feature = "shared-memory")]
+    pub(crate) shm: Option<SharedMemoryProvider>,
+    pub(crate) background: bool,
+    pub(crate) latched: bool,
+    pub(crate) sequenced: bool,
 }
 
 impl<'a, 'b> Clone for PublisherBuilder<'a, 'b> {
@@ -528,7 +943,14 @@ impl<'a, 'b> Clone for PublisherBuilder<'a, 'b> {
             },
             congestion_control: self.congestion_control,
             priority: self.priority,
+            reliability: self.reliability,
             destination: self.destination,
+            block_timeout: self.block_timeout,
+            #[cfg(feature = "shared-memory")]
+            shm: self.shm.clone(),
+            background: self.background,
+            latched: self.latched,
+            sequenced: self.sequenced,
         }
     }
 }
@@ -548,6 +970,15 @@ impl<'a, 'b> PublisherBuilder<'a, 'b> {
         self
     }
 
+    /// Change the reliability of the channel on which the data is sent, [`Reliable`](Reliability::Reliable) by default.
+    ///
+    /// The data is only delivered reliably to the subscribers that declared a reliable subscription.
+    #[inline]
+    pub fn reliability(mut self, reliability: Reliability) -> Self {
+        self.reliability = reliability;
+        self
+    }
+
     /// Restrict the matching subscribers that will receive the published data
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_macros::unstable]
@@ -556,6 +987,58 @@ impl<'a, 'b> PublisherBuilder<'a, 'b> {
         self.destination = destination;
         self
     }
+
+    /// Bound the time a publication with [`CongestionControl::Block`] may block waiting for
+    /// room in the transmission queues, e.g. when a downlink is dead but its transport hasn't
+    /// noticed yet.
+    ///
+    /// Past this duration the data is dropped on the congested links and the publication
+    /// returns a [`PublishTimeout`] error. There is no bound by default.
+    #[inline]
+    pub fn block_timeout(mut self, timeout: Duration) -> Self {
+        self.block_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the [`SharedMemoryProvider`] from which [`Publisher::alloc`] allocates buffers.
+    #[cfg(feature = "shared-memory")]
+    #[inline]
+    pub fn shm_provider(mut self, provider: SharedMemoryProvider) -> Self {
+        self.shm = Some(provider);
+        self
+    }
+
+    /// Keep the publication declared until the session is closed, even if the returned
+    /// [`Publisher`] and its clones are dropped.
+    ///
+    /// Such a publisher can still be undeclared explicitly with [`Publisher::undeclare`].
+    #[inline]
+    pub fn background(mut self) -> Self {
+        self.background = true;
+        self
+    }
+
+    /// Mark the published samples as latched: the routers keep the most recent latched sample
+    /// of each key expression and deliver it to the subscribers declared afterwards, without
+    /// requiring a storage.
+    ///
+    /// Deleting a key expression with a latched publisher forgets its latched sample. The latched
+    /// samples delivered to a new subscriber are dropped if its tx queue is full.
+    #[inline]
+    pub fn latched(mut self, latched: bool) -> Self {
+        self.latched = latched;
+        self
+    }
+
+    /// Number the published samples: they carry the id of the session and a sequence number
+    /// incremented by each publication of the [`Publisher`], from which the subscribers infer
+    /// the samples they missed, see [`SubscriberBuilder::gap_listener`](crate::subscriber::SubscriberBuilder::gap_listener).
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn sequenced(mut self, sequenced: bool) -> Self {
+        self.sequenced = sequenced;
+        self
+    }
 }
 
 impl<'a, 'b> Resolvable for PublisherBuilder<'a, 'b> {
@@ -564,44 +1047,29 @@ impl<'a, 'b> Resolvable for PublisherBuilder<'a, 'b> {
 
 impl<'a, 'b> SyncResolve for PublisherBuilder<'a, 'b> {
     fn res_sync(self) -> <Self as Resolvable>::To {
-        let mut key_expr = self.key_expr?;
-        if !key_expr.is_fully_optimized(&self.session) {
-            let session_id = self.session.id;
-            let expr_id = self.session.declare_prefix(key_expr.as_str()).res_sync();
-            let prefix_len = key_expr
-                .len()
-                .try_into()
-                .expect("How did you get a key expression with a length over 2^32!?");
-            key_expr = match key_expr.0 {
-                crate::key_expr::KeyExprInner::Borrowed(key_expr)
-                | crate::key_expr::KeyExprInner::BorrowedWire { key_expr, .. } => {
-                    KeyExpr(crate::key_expr::KeyExprInner::BorrowedWire {
-                        key_expr,
-                        expr_id,
-                        prefix_len,
-                        session_id,
-                    })
-                }
-                crate::key_expr::KeyExprInner::Owned(key_expr)
-                | crate::key_expr::KeyExprInner::Wire { key_expr, .. } => {
-                    KeyExpr(crate::key_expr::KeyExprInner::Wire {
-                        key_expr,
-                        expr_id,
-                        prefix_len,
-                        session_id,
-                    })
-                }
-            }
-        }
+        let key_expr = self.session.optimize_keyexpr(self.key_expr?);
         self.session
             .declare_publication_intent(key_expr.clone())
             .res_sync()?;
+        let dropped_downstream = Arc::new(AtomicU64::new(0));
+        zwrite!(self.session.state)
+            .dropped_downstream
+            .push((key_expr.clone().into(), Arc::downgrade(&dropped_downstream)));
         let publisher = Publisher {
             session: self.session,
             key_expr,
             congestion_control: self.congestion_control,
             priority: self.priority,
+            reliability: self.reliability,
             destination: self.destination,
+            block_timeout: self.block_timeout,
+            #[cfg(feature = "shared-memory")]
+            shm: self.shm,
+            buffer: Default::default(),
+            background: self.background,
+            latched: self.latched,
+            sequence: self.sequenced.then(|| AtomicU64::new(0)),
+            dropped_downstream,
         };
         log::trace!("publish({:?})", publisher.key_expr);
         Ok(publisher)
@@ -616,6 +1084,26 @@ impl<'a, 'b> AsyncResolve for PublisherBuilder<'a, 'b> {
     }
 }
 
+/// The error returned by a publication of a [`Publisher`] that blocked longer than its
+/// [`block_timeout`](PublisherBuilder::block_timeout).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishTimeout {
+    pub key_expr: String,
+    pub timeout: Duration,
+}
+
+impl std::error::Error for PublishTimeout {}
+
+impl fmt::Display for PublishTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Publication on {} blocked for more than {:?}",
+            self.key_expr, self.timeout
+        )
+    }
+}
+
 /// The Priority of zenoh messages.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 #[repr(u8)]
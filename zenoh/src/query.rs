@@ -16,11 +16,13 @@
 
 use crate::handlers::{locked, Callback, DefaultHandler};
 use crate::prelude::*;
-use crate::Session;
-use std::collections::HashMap;
+use crate::time::Timestamp;
+use crate::{Session, SessionRef};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::future::Ready;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+use zenoh_core::{zlock, AsyncResolve, Resolvable, SyncResolve};
 use zenoh_result::ZResult;
 
 /// The [`Queryable`](crate::queryable::Queryable)s that should be target of a [`get`](Session::get).
@@ -40,15 +42,24 @@ pub enum Mode<T> {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct QueryConsolidation {
     pub(crate) mode: Mode<ConsolidationMode>,
+    // Whether the replies are delivered sorted once the query is complete
+    pub(crate) sorted: bool,
 }
 
 impl QueryConsolidation {
     /// Automatic query consolidation strategy selection.
-    pub const AUTO: Self = Self { mode: Mode::Auto };
+    ///
+    /// Selects [`ConsolidationMode::None`] for selectors with a time range,
+    /// and [`ConsolidationMode::Latest`] otherwise.
+    pub const AUTO: Self = Self {
+        mode: Mode::Auto,
+        sorted: false,
+    };
 
     pub(crate) const fn from_mode(mode: ConsolidationMode) -> Self {
         Self {
             mode: Mode::Manual(mode),
+            sorted: false,
         }
     }
 
@@ -59,7 +70,10 @@ impl QueryConsolidation {
 }
 impl From<Mode<ConsolidationMode>> for QueryConsolidation {
     fn from(mode: Mode<ConsolidationMode>) -> Self {
-        Self { mode }
+        Self {
+            mode,
+            sorted: false,
+        }
     }
 }
 impl From<ConsolidationMode> for QueryConsolidation {
@@ -75,6 +89,11 @@ impl Default for QueryConsolidation {
 }
 
 /// Structs returned by a [`get`](Session::get).
+///
+/// The replies are delivered as they arrive, unless buffered by a
+/// [`ConsolidationMode::Latest`] consolidation. The receiver of the default
+/// handler is closed once all the queried nodes have sent their final marker,
+/// or the query timed out.
 #[non_exhaustive]
 #[derive(Clone, Debug)]
 pub struct Reply {
@@ -90,9 +109,85 @@ pub(crate) struct QueryState {
     pub(crate) scope: Option<KeyExpr<'static>>,
     pub(crate) reception_mode: ConsolidationMode,
     pub(crate) replies: Option<HashMap<OwnedKeyExpr, Reply>>,
+    // The replies held back to be delivered sorted once the query is complete, if requested.
+    // Only used without consolidation, the consolidated replies being held back in `replies`.
+    pub(crate) sorted: Option<Vec<Reply>>,
+    // The zids of the runtimes the query is restricted to, whose replies are the only ones delivered.
+    pub(crate) zids: Option<Vec<ZenohId>>,
     pub(crate) callback: Callback<'static, Reply>,
 }
 
+impl QueryState {
+    /// Takes the replies held back until the query is complete, in delivery order.
+    pub(crate) fn take_held_replies(&mut self) -> Vec<Reply> {
+        let consolidated = |replies: Option<HashMap<OwnedKeyExpr, Reply>>| -> Vec<Reply> {
+            replies
+                .map(|replies| replies.into_values().collect())
+                .unwrap_or_default()
+        };
+        match self.sorted.take() {
+            Some(mut replies) => {
+                if self.reception_mode != ConsolidationMode::None {
+                    replies = consolidated(self.replies.take());
+                }
+                replies.sort_by(|a, b| reply_order(a).cmp(&reply_order(b)));
+                replies
+            }
+            None if self.reception_mode == ConsolidationMode::Latest => {
+                consolidated(self.replies.take())
+            }
+            None => vec![],
+        }
+    }
+}
+
+fn reply_order(reply: &Reply) -> Option<(&str, Option<&Timestamp>)> {
+    reply
+        .sample
+        .as_ref()
+        .ok()
+        .map(|sample| (sample.key_expr.as_str(), sample.timestamp.as_ref()))
+}
+
+/// Wraps the callback of a query to drop the replies with the same key expression and timestamp
+/// as one of the `window` most recently delivered replies.
+fn deduplicate_replies(
+    callback: Callback<'static, Reply>,
+    window: usize,
+) -> Callback<'static, Reply> {
+    let delivered: Mutex<(
+        HashSet<(OwnedKeyExpr, Timestamp)>,
+        VecDeque<(OwnedKeyExpr, Timestamp)>,
+    )> = Mutex::new((HashSet::new(), VecDeque::new()));
+    Arc::new(move |reply: Reply| {
+        if let Ok(Sample {
+            key_expr,
+            timestamp: Some(timestamp),
+            ..
+        }) = &reply.sample
+        {
+            let id = (OwnedKeyExpr::from(key_expr.clone()), *timestamp);
+            let mut delivered = zlock!(delivered);
+            let (ids, order) = &mut *delivered;
+            if !ids.insert(id.clone()) {
+                log::trace!(
+                    "Drop duplicated reply on {} from {}",
+                    id.0,
+                    reply.replier_id
+                );
+                return;
+            }
+            order.push_back(id);
+            if order.len() > window {
+                if let Some(oldest) = order.pop_front() {
+                    ids.remove(&oldest);
+                }
+            }
+        }
+        callback(reply)
+    })
+}
+
 /// A builder for initializing a `query`.
 ///
 /// # Examples
@@ -122,9 +217,14 @@ pub struct GetBuilder<'a, 'b, Handler> {
     pub(crate) target: QueryTarget,
     pub(crate) consolidation: QueryConsolidation,
     pub(crate) destination: Locality,
+    pub(crate) priority: Priority,
     pub(crate) timeout: Duration,
     pub(crate) handler: Handler,
     pub(crate) value: Option<Value>,
+    // The number of most recent replies against which the replies are deduplicated, if enabled
+    pub(crate) deduplication: Option<usize>,
+    // The zids of the runtimes the query is restricted to, if any
+    pub(crate) zids: Option<Vec<ZenohId>>,
 }
 
 impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
@@ -156,8 +256,11 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             target,
             consolidation,
             destination,
+            priority,
             timeout,
             value,
+            deduplication,
+            zids,
             handler: _,
         } = self;
         GetBuilder {
@@ -167,8 +270,11 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             target,
             consolidation,
             destination,
+            priority,
             timeout,
             value,
+            deduplication,
+            zids,
             handler: callback,
         }
     }
@@ -235,8 +341,11 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             target,
             consolidation,
             destination,
+            priority,
             timeout,
             value,
+            deduplication,
+            zids,
             handler: _,
         } = self;
         GetBuilder {
@@ -246,14 +355,23 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             target,
             consolidation,
             destination,
+            priority,
             timeout,
             value,
+            deduplication,
+            zids,
             handler,
         }
     }
 }
 impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
     /// Change the target of the query.
+    ///
+    /// [`QueryTarget::BestMatching`] (the default) reaches the nearest complete queryables if any,
+    /// all the matching queryables otherwise. [`QueryTarget::All`] reaches all the matching
+    /// queryables and [`QueryTarget::AllComplete`] all the complete queryables including the
+    /// queried key expression. The fan-out of the routed queries per target is available in the
+    /// admin space under `@/router/<zid>/status/queries`.
     #[inline]
     pub fn target(mut self, target: QueryTarget) -> Self {
         self.target = target;
@@ -276,6 +394,16 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
         self
     }
 
+    /// Change the priority of the query.
+    ///
+    /// The query is sent on the conduit of this priority, and its replies are sent back on the
+    /// same conduit by the routers and the replying queryables.
+    #[inline]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     /// Set query timeout.
     #[inline]
     pub fn timeout(mut self, timeout: Duration) -> Self {
@@ -283,6 +411,76 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
         self
     }
 
+    /// Deliver the replies sorted by key expression, then by timestamp, once the query is
+    /// complete, rather than as they arrive.
+    ///
+    /// The replies are held back in the session until all the queried nodes have sent their
+    /// final marker or the query timed out, and consolidated according to the consolidation mode,
+    /// e.g. to read a range from a storage without buffering and sorting it on the application
+    /// side.
+    #[inline]
+    pub fn sorted(mut self, sorted: bool) -> Self {
+        self.consolidation.sorted = sorted;
+        self
+    }
+
+    /// Drop the replies with the same key expression and timestamp as one of the `window` most
+    /// recently delivered replies, e.g. the copies of a same reply received through several
+    /// routers of a redundant topology.
+    ///
+    /// The error replies and the replies without timestamp are always delivered.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh::query::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let replies = session
+    ///     .get("key/expression")
+    ///     .consolidation(ConsolidationMode::None)
+    ///     .deduplicate(1024)
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn deduplicate(mut self, window: usize) -> Self {
+        self.deduplication = Some(window);
+        self
+    }
+
+    /// Restrict the query to the queryables of the runtimes with the given zids, e.g. to query
+    /// the admin space or a service of a given node without a key expression prefix unique to it.
+    ///
+    /// The routers only forward the query towards these runtimes, and the replies of the other
+    /// ones, e.g. reached through routers not supporting this restriction, are dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let replies = session
+    ///     .get("key/expression")
+    ///     .destination_zids([session.zid()])
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn destination_zids<I>(mut self, zids: I) -> Self
+    where
+        I: IntoIterator<Item = ZenohId>,
+    {
+        self.zids = Some(zids.into_iter().collect());
+        self
+    }
+
     /// Set query value.
     #[inline]
     pub fn with_value<IntoValue>(mut self, value: IntoValue) -> Self
@@ -307,8 +505,11 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             target,
             consolidation,
             destination,
+            priority,
             timeout,
             value,
+            deduplication,
+            zids,
             handler,
         } = self;
         Self {
@@ -318,8 +519,11 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             target,
             consolidation,
             destination,
+            priority,
             timeout,
             value,
+            deduplication,
+            zids,
             handler,
         }
     }
@@ -358,6 +562,10 @@ where
 {
     fn res_sync(self) -> <Self as Resolvable>::To {
         let (callback, receiver) = self.handler.into_cb_receiver_pair();
+        let callback = match self.deduplication {
+            Some(window) => deduplicate_replies(callback, window),
+            None => callback,
+        };
 
         self.session
             .query(
@@ -366,6 +574,8 @@ where
                 self.target,
                 self.consolidation,
                 self.destination,
+                self.zids,
+                self.priority,
                 self.timeout,
                 self.value,
                 callback,
@@ -385,3 +595,159 @@ where
         std::future::ready(self.res_sync())
     }
 }
+
+/// A builder for initializing a [`Querier`].
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+/// use zenoh::query::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let querier = session
+///     .declare_querier("key/expression")
+///     .target(QueryTarget::All)
+///     .timeout(std::time::Duration::from_millis(500))
+///     .res()
+///     .await
+///     .unwrap();
+/// # })
+/// ```
+#[derive(Debug)]
+pub struct QuerierBuilder<'a, 'b> {
+    pub(crate) session: SessionRef<'a>,
+    pub(crate) key_expr: ZResult<KeyExpr<'b>>,
+    pub(crate) target: QueryTarget,
+    pub(crate) consolidation: QueryConsolidation,
+    pub(crate) destination: Locality,
+    pub(crate) priority: Priority,
+    pub(crate) timeout: Duration,
+}
+
+impl<'a, 'b> QuerierBuilder<'a, 'b> {
+    /// Change the default target of the queries.
+    #[inline]
+    pub fn target(mut self, target: QueryTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Change the default consolidation mode of the queries.
+    #[inline]
+    pub fn consolidation<QC: Into<QueryConsolidation>>(mut self, consolidation: QC) -> Self {
+        self.consolidation = consolidation.into();
+        self
+    }
+
+    /// Restrict the matching queryables that will receive the queries
+    /// to the ones that have the given [`Locality`](crate::prelude::Locality).
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn allowed_destination(mut self, destination: Locality) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    /// Change the default priority of the queries.
+    #[inline]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Change the default timeout of the queries.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl<'a, 'b> Resolvable for QuerierBuilder<'a, 'b> {
+    type To = ZResult<Querier<'a>>;
+}
+
+impl<'a, 'b> SyncResolve for QuerierBuilder<'a, 'b> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let key_expr = self.session.optimize_keyexpr(self.key_expr?).into_owned();
+        log::trace!("querier({:?})", key_expr);
+        Ok(Querier {
+            session: self.session,
+            key_expr,
+            target: self.target,
+            consolidation: self.consolidation,
+            destination: self.destination,
+            priority: self.priority,
+            timeout: self.timeout,
+        })
+    }
+}
+
+impl<'a, 'b> AsyncResolve for QuerierBuilder<'a, 'b> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A querier that issues the same query repeatedly.
+///
+/// The key expression of a querier is declared once, so that its queries are routed without
+/// resolving it again, and each query starts from the defaults of the querier
+/// (target, consolidation, priority, timeout), which can still be overridden on the returned
+/// [`GetBuilder`].
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let querier = session.declare_querier("key/expression").res().await.unwrap();
+/// for _ in 0..3 {
+///     let replies = querier.get().res().await.unwrap();
+///     while let Ok(reply) = replies.recv_async().await {
+///         println!(">> Received {:?}", reply.sample);
+///     }
+/// }
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct Querier<'a> {
+    pub(crate) session: SessionRef<'a>,
+    pub(crate) key_expr: KeyExpr<'static>,
+    pub(crate) target: QueryTarget,
+    pub(crate) consolidation: QueryConsolidation,
+    pub(crate) destination: Locality,
+    pub(crate) priority: Priority,
+    pub(crate) timeout: Duration,
+}
+
+impl<'a> Querier<'a> {
+    /// The key expression queried by this querier.
+    #[inline]
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    /// Query the key expression of this querier.
+    #[inline]
+    pub fn get(&self) -> GetBuilder<'_, 'static, DefaultHandler> {
+        GetBuilder {
+            session: &self.session,
+            selector: Ok(Selector::from(self.key_expr.clone())),
+            scope: Ok(None),
+            target: self.target,
+            consolidation: self.consolidation,
+            destination: self.destination,
+            priority: self.priority,
+            timeout: self.timeout,
+            value: None,
+            deduplication: None,
+            zids: None,
+            handler: DefaultHandler,
+        }
+    }
+}
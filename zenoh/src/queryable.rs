@@ -18,6 +18,7 @@ use crate::handlers::{locked, DefaultHandler};
 use crate::prelude::*;
 #[zenoh_macros::unstable]
 use crate::query::ReplyKeyExpr;
+use crate::transform::Transformers;
 use crate::SessionRef;
 use crate::Undeclarable;
 
@@ -25,6 +26,7 @@ use std::fmt;
 use std::future::Ready;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Instant;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
 use zenoh_protocol::core::WireExpr;
 use zenoh_result::ZResult;
@@ -40,12 +42,18 @@ pub(crate) struct QueryInner {
 
     pub(crate) qid: ZInt,
     pub(crate) zid: ZenohId,
+    /// The priority of this Query, inherited by its replies.
+    pub(crate) priority: zenoh_protocol::core::Priority,
+    /// The instant this Query expires at its origin, if known.
+    pub(crate) deadline: Option<Instant>,
+    /// The payload transformers of the session, applied on the replies.
+    pub(crate) transformers: Option<Arc<Transformers>>,
     pub(crate) primitives: Arc<dyn Primitives>,
 }
 
 impl Drop for QueryInner {
     fn drop(&mut self) {
-        self.primitives.send_reply_final(self.qid);
+        self.primitives.send_reply_final(self.qid, self.priority);
     }
 }
 
@@ -83,6 +91,23 @@ impl Query {
         self.inner.value.as_ref()
     }
 
+    /// The instant this Query expires at its origin, if it was propagated with the query.
+    ///
+    /// The querier doesn't wait for the replies sent after it.
+    #[inline(always)]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.inner.deadline
+    }
+
+    /// Returns `true` if this Query has expired at its origin, in which case the work to reply
+    /// to it can be abandoned: the replies are no longer sent.
+    #[inline(always)]
+    pub fn is_expired(&self) -> bool {
+        self.inner
+            .deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+
     /// Sends a reply to this Query.
     ///
     /// By default, queries only accept replies whose key expression intersects with the query's.
@@ -155,7 +180,15 @@ impl SyncResolve for ReplyBuilder<'_> {
                 {
                     bail!("Attempted to reply on `{}`, which does not intersect with query `{}`, despite query only allowing replies on matching key expressions", sample.key_expr, self.query.key_expr())
                 }
+                if self.query.is_expired() {
+                    log::debug!("Drop reply to expired query {}", self.query);
+                    return Ok(());
+                }
                 let (key_expr, payload, data_info) = sample.split();
+                let payload = match &self.query.inner.transformers {
+                    Some(transformers) => transformers.encode(&key_expr, payload)?,
+                    None => payload,
+                };
                 self.query.inner.primitives.send_reply_data(
                     self.query.inner.qid,
                     self.query.inner.zid,
@@ -165,6 +198,7 @@ impl SyncResolve for ReplyBuilder<'_> {
                     },
                     Some(data_info),
                     payload,
+                    self.query.inner.priority,
                 );
                 Ok(())
             }
@@ -185,6 +219,7 @@ pub(crate) struct QueryableState {
     pub(crate) id: Id,
     pub(crate) key_expr: WireExpr<'static>,
     pub(crate) complete: bool,
+    pub(crate) load_balanced: bool,
     pub(crate) origin: Locality,
     pub(crate) callback: Arc<dyn Fn(Query) + Send + Sync>,
 }
@@ -240,6 +275,11 @@ impl<'a> Undeclarable<(), QueryableUndeclaration<'a>> for CallbackQueryable<'a>
 
 /// A [`Resolvable`] returned when undeclaring a queryable.
 ///
+/// Once resolved, the queryable's callback won't be called anymore and the undeclaration has been
+/// handed to the network, so that a queryable can be declared again on the same key expression
+/// right away. It can also be awaited directly, which is equivalent to
+/// [`res_async`](AsyncResolve::res_async).
+///
 /// # Examples
 /// ```
 /// # async_std::task::block_on(async {
@@ -275,10 +315,21 @@ impl<'a> AsyncResolve for QueryableUndeclaration<'a> {
     }
 }
 
+impl std::future::IntoFuture for QueryableUndeclaration<'_> {
+    type Output = ZResult<()>;
+    type IntoFuture = Ready<Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.res_async()
+    }
+}
+
 impl Drop for CallbackQueryable<'_> {
     fn drop(&mut self) {
         if self.alive {
-            let _ = self.session.close_queryable(self.state.id);
+            if let Err(e) = self.session.close_queryable(self.state.id) {
+                log::warn!("Error undeclaring queryable on drop: {}", e);
+            }
         }
     }
 }
@@ -301,7 +352,9 @@ pub struct QueryableBuilder<'a, 'b, Handler> {
     pub(crate) session: SessionRef<'a>,
     pub(crate) key_expr: ZResult<KeyExpr<'b>>,
     pub(crate) complete: bool,
+    pub(crate) load_balanced: bool,
     pub(crate) origin: Locality,
+    pub(crate) background: bool,
     pub(crate) handler: Handler,
 }
 
@@ -331,14 +384,18 @@ impl<'a, 'b> QueryableBuilder<'a, 'b, DefaultHandler> {
             session,
             key_expr,
             complete,
+            load_balanced,
             origin,
+            background,
             handler: _,
         } = self;
         QueryableBuilder {
             session,
             key_expr,
             complete,
+            load_balanced,
             origin,
+            background,
             handler: callback,
         }
     }
@@ -402,14 +459,18 @@ impl<'a, 'b> QueryableBuilder<'a, 'b, DefaultHandler> {
             session,
             key_expr,
             complete,
+            load_balanced,
             origin,
+            background,
             handler: _,
         } = self;
         QueryableBuilder {
             session,
             key_expr,
             complete,
+            load_balanced,
             origin,
+            background,
             handler,
         }
     }
@@ -430,6 +491,50 @@ impl<'a, 'b, Handler> QueryableBuilder<'a, 'b, Handler> {
         self.complete = complete;
         self
     }
+
+    /// Make this queryable load balanced with the equivalent queryables of other sessions.
+    ///
+    /// The routers route each [`BestMatching`](crate::query::QueryTarget::BestMatching) query
+    /// matching several load balanced queryables to only one of them, the least busy one, rather
+    /// than to all of them, e.g. to serve requests with a pool of workers. A key expression is
+    /// only load balanced if all the queryables declared on it by a session are.
+    #[inline]
+    pub fn load_balanced(mut self, load_balanced: bool) -> Self {
+        self.load_balanced = load_balanced;
+        self
+    }
+}
+impl<'a, 'b, Handler> QueryableBuilder<'a, 'b, Handler>
+where
+    Handler: Fn(Query) + Send + Sync + 'static,
+{
+    /// Keep the queryable declared until the session is closed, even if the returned
+    /// [`Queryable`] is dropped.
+    ///
+    /// This is only available for queryables with a callback, which keep replying to the queries
+    /// without the application holding the queryable. Such a queryable can still be undeclared
+    /// explicitly with [`Queryable::undeclare`].
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session
+    ///     .declare_queryable("key/expression")
+    ///     .callback(|query| {println!(">> Handling query '{}'", query.selector());})
+    ///     .background()
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn background(mut self) -> Self {
+        self.background = true;
+        self
+    }
 }
 
 /// A queryable that provides data through a [`Handler`](crate::prelude::IntoCallbackReceiverPair).
@@ -439,7 +544,9 @@ impl<'a, 'b, Handler> QueryableBuilder<'a, 'b, Handler> {
 /// and the [`with`](QueryableBuilder::with) function
 /// of the resulting builder.
 ///
-/// Queryables are automatically undeclared when dropped.
+/// Queryables are automatically undeclared when dropped, before `drop` returns, unless declared
+/// in the [`background`](QueryableBuilder::background). Errors are only logged then: await
+/// [`undeclare`](Queryable::undeclare) to get them.
 ///
 /// # Examples
 /// ```no_run
@@ -471,7 +578,7 @@ pub struct Queryable<'a, Receiver> {
 
 impl<'a, Receiver> Queryable<'a, Receiver> {
     #[inline]
-    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+    pub fn undeclare(self) -> QueryableUndeclaration<'a> {
         Undeclarable::undeclare_inner(self, ())
     }
 }
@@ -510,6 +617,7 @@ where
             .declare_queryable_inner(
                 &self.key_expr?.to_wire(&session),
                 self.complete,
+                self.load_balanced,
                 self.origin,
                 callback,
             )
@@ -517,7 +625,8 @@ where
                 queryable: CallbackQueryable {
                     session,
                     state: qable_state,
-                    alive: true,
+                    // Background queryables are not undeclared when dropped
+                    alive: !self.background,
                 },
                 receiver,
             })
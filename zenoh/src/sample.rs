@@ -16,12 +16,13 @@
 use crate::buffers::ZBuf;
 #[zenoh_macros::unstable]
 use crate::prelude::ZenohId;
-use crate::prelude::{KeyExpr, SampleKind, Value};
+use crate::prelude::{keyexpr, Encoding, KeyExpr, SampleKind, Value};
 use crate::query::Reply;
 use crate::time::{new_reception_timestamp, Timestamp};
 #[zenoh_macros::unstable]
 use serde::Serialize;
 use std::convert::{TryFrom, TryInto};
+use zenoh_buffers::SplitBuffer;
 #[zenoh_macros::unstable]
 use zenoh_protocol::core::ZInt;
 use zenoh_protocol::zenoh::DataInfo;
@@ -200,16 +201,52 @@ impl Sample {
             source_sn: self.source_info.source_sn,
             #[cfg(not(feature = "unstable"))]
             source_sn: None,
+            trace_context: None,
+            latched: false,
         };
         (self.key_expr, self.value.payload, info)
     }
 
+    /// Gets the key expression of this Sample.
+    #[inline]
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    /// Gets the parts of the key expression of this Sample bound to each wildcard of `pattern`,
+    /// typically the key expression of the subscriber that received it.
+    ///
+    /// Returns `None` if `pattern` doesn't include the key expression of this Sample.
+    /// See [`keyexpr::captures`](crate::key_expr::keyexpr::captures) for the binding rules.
+    ///
+    /// # Examples
+    /// ```
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let sample = Sample::try_from("robot/robot1/pose", "0.0,1.0").unwrap();
+    /// let pattern = keyexpr::new("robot/*/pose").unwrap();
+    /// assert_eq!(sample.captures(pattern), Some(vec!["robot1"]));
+    /// ```
+    #[inline]
+    pub fn captures(&self, pattern: &keyexpr) -> Option<Vec<&str>> {
+        self.key_expr.captures(pattern)
+    }
+
     /// Gets the timestamp of this Sample.
     #[inline]
     pub fn get_timestamp(&self) -> Option<&Timestamp> {
         self.timestamp.as_ref()
     }
 
+    /// Gets the timestamp of this Sample.
+    ///
+    /// Timestamps generated by zenoh carry the id of the runtime that generated them, so that
+    /// the samples of different producers are totally ordered by their timestamps.
+    #[inline]
+    pub fn timestamp(&self) -> Option<&Timestamp> {
+        self.timestamp.as_ref()
+    }
+
     /// Sets the timestamp of this Sample.
     #[inline]
     pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
@@ -240,6 +277,34 @@ impl Sample {
     }
 }
 
+/// The metadata of a received [`Sample`], available before its payload is decoded.
+///
+/// It is given to the [`filter`](crate::subscriber::SubscriberBuilder::filter) of a subscriber
+/// to discard samples without paying the cost of decoding their payload.
+#[derive(Clone, Copy, Debug)]
+pub struct SampleMetadata<'a> {
+    /// The kind of the Sample.
+    pub kind: SampleKind,
+    /// The encoding of the Sample's payload.
+    pub encoding: &'a Encoding,
+    /// The [`Timestamp`] of the Sample.
+    pub timestamp: Option<&'a Timestamp>,
+    /// The length in bytes of the Sample's payload, as received.
+    pub len: usize,
+}
+
+impl<'a> SampleMetadata<'a> {
+    pub(crate) fn new(data_info: Option<&'a DataInfo>, payload: &ZBuf) -> Self {
+        const EMPTY: &Encoding = &Encoding::EMPTY;
+        SampleMetadata {
+            kind: data_info.map(|i| i.kind).unwrap_or_default(),
+            encoding: data_info.and_then(|i| i.encoding.as_ref()).unwrap_or(EMPTY),
+            timestamp: data_info.and_then(|i| i.timestamp.as_ref()),
+            len: payload.len(),
+        }
+    }
+}
+
 impl std::ops::Deref for Sample {
     type Target = Value;
 
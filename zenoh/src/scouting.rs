@@ -14,14 +14,13 @@
 use crate::handlers::{locked, Callback, DefaultHandler};
 use crate::net::runtime::{orchestrator::Loop, Runtime};
 
-use async_std::net::UdpSocket;
 use futures::StreamExt;
 use std::future::Ready;
 use std::{fmt, ops::Deref};
 use zenoh_config::{
     whatami::WhatAmIMatcher, ZN_MULTICAST_INTERFACE_DEFAULT, ZN_MULTICAST_IPV4_ADDRESS_DEFAULT,
 };
-use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+use zenoh_core::{executor, AsyncResolve, Resolvable, SyncResolve};
 use zenoh_result::ZResult;
 
 /// Constants and helpers for zenoh `whatami` flags.
@@ -30,6 +29,9 @@ pub use zenoh_protocol::core::WhatAmI;
 /// A zenoh Hello message.
 pub use zenoh_protocol::scouting::Hello;
 
+/// The metadata advertised in a zenoh [`Hello`] message.
+pub use zenoh_protocol::scouting::HelloMetadata;
+
 /// A builder for initializing a [`Scout`].
 ///
 /// # Examples
@@ -306,6 +308,15 @@ fn scout(
         }
     };
     let addr = config.scouting.multicast.address().unwrap_or(default_addr);
+    let mcast_addrs = std::iter::once(addr)
+        .chain(*config.scouting.multicast.address_v6())
+        .collect();
+    let ttl = config
+        .scouting
+        .multicast
+        .ttl()
+        .unwrap_or(zenoh_config::defaults::scouting::multicast::ttl);
+    let targets = Runtime::scouting_targets(mcast_addrs, config.scouting.unicast.gateways.clone());
     let ifaces = config
         .scouting
         .multicast
@@ -315,14 +326,11 @@ fn scout(
     let (stop_sender, stop_receiver) = flume::bounded::<()>(1);
     let ifaces = Runtime::get_interfaces(ifaces);
     if !ifaces.is_empty() {
-        let sockets: Vec<UdpSocket> = ifaces
-            .into_iter()
-            .filter_map(|iface| Runtime::bind_ucast_port(iface).ok())
-            .collect();
+        let sockets = Runtime::bind_ucast_ports(&ifaces, &targets, ttl);
         if !sockets.is_empty() {
-            async_std::task::spawn(async move {
+            executor::spawn(async move {
                 let mut stop_receiver = stop_receiver.stream();
-                let scout = Runtime::scout(&sockets, what, &addr, move |hello| {
+                let scout = Runtime::scout(&sockets, what, &targets, move |hello| {
                     let callback = callback.clone();
                     async move {
                         callback(hello);
@@ -58,6 +58,8 @@ use std::{
 /// Here are the currently standardized parameters for Zenoh (check the specification page for the exhaustive list):
 /// - `_time`: used to express interest in only values dated within a certain time range, values for
 ///   this parameter must be readable by the [Zenoh Time DSL](zenoh_util::time_range::TimeRange) for the value to be considered valid.
+/// - `_filter`: used to express interest in only the values matching a filter, whose syntax is left to the queryable
+///   (e.g. to the storage backends).
 /// - **`[unstable]`** `_anyke`: used in queries to express interest in replies coming from any key expression. By default, only replies
 ///   whose key expression match query's key expression are accepted. `_anyke` disables the query-reply key expression matching check.
 #[non_exhaustive]
@@ -70,6 +72,7 @@ pub struct Selector<'a> {
 }
 
 pub const TIME_RANGE_KEY: &str = "_time";
+pub const FILTER_KEY: &str = "_filter";
 impl<'a> Selector<'a> {
     /// Gets the parameters as a raw string.
     pub fn parameters(&self) -> &str {
@@ -164,16 +167,29 @@ impl<'a> Selector<'a> {
     }
 
     pub fn remove_time_range(&mut self) {
+        self.remove_parameter(TIME_RANGE_KEY)
+    }
+
+    /// Sets the filter of the values targeted by the selector.
+    pub fn with_filter(&mut self, filter: &str) {
+        self.remove_filter();
+        let selector = self.parameters_mut();
+        let mut encoder = form_urlencoded::Serializer::for_suffix(selector, 0);
+        encoder.append_pair(FILTER_KEY, filter).finish();
+    }
+
+    pub fn remove_filter(&mut self) {
+        self.remove_parameter(FILTER_KEY)
+    }
+
+    fn remove_parameter(&mut self, name: &str) {
         let selector = self.parameters_mut();
 
         let mut splice_start = 0;
         let mut splice_end = 0;
         for argument in selector.split('&') {
-            if argument.starts_with(TIME_RANGE_KEY)
-                && matches!(
-                    argument.as_bytes().get(TIME_RANGE_KEY.len()),
-                    None | Some(b'=')
-                )
+            if argument.starts_with(name)
+                && matches!(argument.as_bytes().get(name.len()), None | Some(b'='))
             {
                 splice_end = splice_start + argument.len();
                 break;
@@ -279,6 +295,23 @@ fn selector_accessors() {
         selector = selector.accept_any_keyexpr(false).unwrap();
         assert_eq!(selector.to_string(), without_any + "&other");
     }
+
+    let mut selector = Selector::try_from("hello/there?_filter=a&_time=[..]").unwrap();
+    assert_eq!(selector.filter().unwrap().as_deref(), Some("a"));
+    selector.with_filter("b&c");
+    assert_eq!(selector.filter().unwrap().as_deref(), Some("b&c"));
+    assert_eq!(selector.parameters(), "_time=[..]&_filter=b%26c");
+    assert_eq!(
+        selector.parameters_cowmap().unwrap().filter().unwrap(),
+        Some(&"b&c".into())
+    );
+    selector.remove_filter();
+    assert_eq!(selector.parameters(), "_time=[..]");
+    assert!(selector.filter().unwrap().is_none());
+    assert!(Selector::try_from("hello/there?_filter=a&_filter=b")
+        .unwrap()
+        .filter()
+        .is_err());
 }
 pub trait Parameter: Sized {
     type Name: AsRef<str> + Sized;
@@ -404,6 +437,17 @@ pub trait Parameters<'a> {
             None => None,
         })
     }
+
+    /// Extracts the standardized `_filter` argument from the selector parameters.
+    ///
+    /// The default implementation still causes a complete pass through the selector parameters to ensure that there are no duplicates of the `_filter` key.
+    fn filter(&'a self) -> ZResult<Option<ExtractedValue<'a, Self>>>
+    where
+        <Self::Decoder as Iterator>::Item: Parameter,
+    {
+        let [filter] = self.get_parameters([FILTER_KEY])?;
+        Ok(filter)
+    }
 }
 impl<'a> Parameters<'a> for Selector<'a> {
     type Decoder = <str as Parameters<'a>>::Decoder;
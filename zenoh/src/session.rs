@@ -28,8 +28,10 @@ use crate::prelude::{KeyExpr, Parameters};
 use crate::publication::*;
 use crate::query::*;
 use crate::queryable::*;
+use crate::sample::SampleMetadata;
 use crate::selector::TIME_RANGE_KEY;
 use crate::subscriber::*;
+use crate::transform::{PayloadTransformer, Transformers};
 use crate::Id;
 use crate::Priority;
 use crate::Sample;
@@ -44,14 +46,16 @@ use std::convert::TryInto;
 use std::fmt;
 use std::ops::Deref;
 use std::sync::atomic::{AtomicU16, AtomicU64, AtomicUsize, Ordering};
-use std::sync::Arc;
 use std::sync::RwLock;
-use std::time::Duration;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 use uhlc::HLC;
-use zenoh_buffers::ZBuf;
+use zenoh_buffers::{SplitBuffer, ZBuf};
 use zenoh_collections::SingleOrVec;
 use zenoh_config::unwrap_or_default;
-use zenoh_core::{zconfigurable, zread, Resolve, ResolveClosure, ResolveFuture, SyncResolve};
+use zenoh_core::{
+    executor, zconfigurable, zread, Resolve, ResolveClosure, ResolveFuture, SyncResolve,
+};
 use zenoh_protocol::{
     core::{
         key_expr::{keyexpr, OwnedKeyExpr},
@@ -81,6 +85,8 @@ pub(crate) struct SessionState {
     pub(crate) local_resources: HashMap<ExprId, Resource>,
     pub(crate) remote_resources: HashMap<ExprId, Resource>,
     pub(crate) publications: Vec<OwnedKeyExpr>,
+    // The congestion drop counters of the publishers, released when they are dropped
+    pub(crate) dropped_downstream: Vec<(OwnedKeyExpr, Weak<AtomicU64>)>,
     pub(crate) subscribers: HashMap<Id, Arc<SubscriberState>>,
     pub(crate) queryables: HashMap<Id, Arc<QueryableState>>,
     #[cfg(feature = "unstable")]
@@ -88,12 +94,16 @@ pub(crate) struct SessionState {
     pub(crate) queries: HashMap<ZInt, QueryState>,
     pub(crate) aggregated_subscribers: Vec<OwnedKeyExpr>,
     pub(crate) aggregated_publishers: Vec<OwnedKeyExpr>,
+    pub(crate) transformers: Arc<Transformers>,
+    pub(crate) max_message_size: usize,
 }
 
 impl SessionState {
     pub(crate) fn new(
         aggregated_subscribers: Vec<OwnedKeyExpr>,
         aggregated_publishers: Vec<OwnedKeyExpr>,
+        transformers: Transformers,
+        max_message_size: usize,
     ) -> SessionState {
         SessionState {
             primitives: None,
@@ -103,6 +113,7 @@ impl SessionState {
             local_resources: HashMap::new(),
             remote_resources: HashMap::new(),
             publications: Vec::new(),
+            dropped_downstream: Vec::new(),
             subscribers: HashMap::new(),
             queryables: HashMap::new(),
             #[cfg(feature = "unstable")]
@@ -110,6 +121,8 @@ impl SessionState {
             queries: HashMap::new(),
             aggregated_subscribers,
             aggregated_publishers,
+            transformers: Arc::new(transformers),
+            max_message_size,
         }
     }
 }
@@ -196,6 +209,40 @@ impl SessionState {
         }
     }
 
+    pub(crate) fn decode_payload(&self, key_expr: &keyexpr, payload: ZBuf) -> Option<ZBuf> {
+        if self.transformers.is_empty() {
+            return Some(payload);
+        }
+        match self.transformers.decode(key_expr, payload) {
+            Ok(payload) => Some(payload),
+            Err(e) => {
+                log::error!(
+                    "Unable to decode payload received for `{}`: {}",
+                    key_expr,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    pub(crate) fn encode_payload(&self, key_expr: &keyexpr, payload: ZBuf) -> ZResult<ZBuf> {
+        let payload = if self.transformers.is_empty() {
+            payload
+        } else {
+            self.transformers.encode(key_expr, payload)?
+        };
+        if payload.len() > self.max_message_size {
+            bail!(
+                "Payload of {} bytes on {} exceeds the maximum message size of {} bytes",
+                payload.len(),
+                key_expr,
+                self.max_message_size
+            );
+        }
+        Ok(payload)
+    }
+
     pub(crate) fn wireexpr_to_keyexpr<'a>(
         &'a self,
         key_expr: &'a WireExpr,
@@ -307,6 +354,8 @@ pub struct Session {
     pub(crate) state: Arc<RwLock<SessionState>>,
     pub(crate) id: u16,
     pub(crate) alive: bool,
+    // Whether the runtime is closed along with this session, i.e. it hasn't been initialized on an existing runtime
+    pub(crate) owns_runtime: bool,
 }
 
 static SESSION_ID_COUNTER: AtomicU16 = AtomicU16::new(0);
@@ -315,18 +364,30 @@ impl Session {
         runtime: Runtime,
         aggregated_subscribers: Vec<OwnedKeyExpr>,
         aggregated_publishers: Vec<OwnedKeyExpr>,
+        transformers: Transformers,
     ) -> impl Resolve<Session> {
         ResolveClosure::new(move || {
             let router = runtime.router.clone();
+            let max_message_size = runtime
+                .config
+                .lock()
+                .transport()
+                .link()
+                .tx()
+                .max_message_size()
+                .unwrap();
             let state = Arc::new(RwLock::new(SessionState::new(
                 aggregated_subscribers,
                 aggregated_publishers,
+                transformers,
+                max_message_size,
             )));
             let session = Session {
                 runtime: runtime.clone(),
                 state: state.clone(),
                 id: SESSION_ID_COUNTER.fetch_add(1, Ordering::SeqCst),
                 alive: true,
+                owns_runtime: false,
             };
 
             runtime.new_handler(Arc::new(admin::Handler::new(session.clone())));
@@ -413,6 +474,35 @@ impl Session {
         self.runtime.hlc.as_ref().map(Arc::as_ref)
     }
 
+    /// Register a [`PayloadTransformer`](crate::transform::PayloadTransformer) applied on
+    /// the payloads published on or received for the key expressions included in `key_expr`.
+    ///
+    /// Rules are evaluated in registration order, after the ones defined in the configuration,
+    /// and only the first matching rule is applied.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use std::sync::Arc;
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh::transform::AesGcmTransformer;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session.add_payload_transformer(
+    ///     "key/secret/**".try_into().unwrap(),
+    ///     Arc::new(AesGcmTransformer::new([0; AesGcmTransformer::KEY_SIZE])),
+    /// );
+    /// # })
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn add_payload_transformer(
+        &self,
+        key_expr: OwnedKeyExpr,
+        transformer: Arc<dyn PayloadTransformer>,
+    ) {
+        Arc::make_mut(&mut zwrite!(self.state).transformers).add(key_expr, transformer);
+    }
+
     /// Close the zenoh [`Session`](Session).
     ///
     /// Sessions are automatically closed when dropped, but you may want to use this function to handle errors or
@@ -430,7 +520,9 @@ impl Session {
     pub fn close(self) -> impl Resolve<ZResult<()>> {
         ResolveFuture::new(async move {
             trace!("close()");
-            self.runtime.close().await?;
+            if self.owns_runtime {
+                self.runtime.close().await?;
+            }
 
             let primitives = zwrite!(self.state).primitives.as_ref().unwrap().clone();
             primitives.send_close();
@@ -447,6 +539,22 @@ impl Session {
         Undeclarable::undeclare_inner(decl, self)
     }
 
+    /// Get the [`Runtime`](crate::runtime::Runtime) of the zenoh [`Session`](Session), to initialize
+    /// other sessions sharing its transports with [`zenoh::init`](crate::init).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let other_session = zenoh::init(session.runtime().clone()).res().await.unwrap();
+    /// # })
+    /// ```
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
     /// Get the current configuration of the zenoh [`Session`](Session).
     ///
     /// The returned configuration [`Notifier`](Notifier) can be used to read the current
@@ -495,6 +603,27 @@ impl Session {
         }
     }
 
+    /// Get a snapshot of the statistics summed over the transports opened by the zenoh
+    /// [`Session`](Session), including the closed ones: the counters never decrease.
+    ///
+    /// The statistics of a single transport can be obtained with
+    /// [`TransportUnicast::stats()`](zenoh_transport::TransportUnicast::stats).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let stats = session.stats();
+    /// println!("Sent {} data messages", stats.tx.data.msgs);
+    /// # })
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> crate::info::TransportStats {
+        self.runtime.manager().stats()
+    }
+
     /// Create a [`Subscriber`](Subscriber) for the given key expression.
     ///
     /// # Arguments
@@ -527,6 +656,9 @@ impl Session {
             reliability: Reliability::default(),
             mode: PushMode,
             origin: Locality::default(),
+            background: false,
+            filter: None,
+            gap_listener: None,
             handler: DefaultHandler,
         }
     }
@@ -565,7 +697,9 @@ impl Session {
             session: SessionRef::Borrow(self),
             key_expr: key_expr.try_into().map_err(Into::into),
             complete: false,
+            load_balanced: false,
             origin: Locality::default(),
+            background: false,
             handler: DefaultHandler,
         }
     }
@@ -602,7 +736,68 @@ impl Session {
             key_expr: key_expr.try_into().map_err(Into::into),
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
+            reliability: Reliability::Reliable,
+            destination: Locality::default(),
+            block_timeout: None,
+            #[cfg(feature = "shared-memory")]
+            shm: None,
+            background: false,
+            latched: false,
+            sequenced: false,
+        }
+    }
+
+    /// Create a [`Querier`](crate::query::Querier) for the given key expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_expr` - The key expression matching resources to query
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let querier = session.declare_querier("key/expression")
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// let replies = querier.get().res().await.unwrap();
+    /// # })
+    /// ```
+    pub fn declare_querier<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        key_expr: TryIntoKeyExpr,
+    ) -> QuerierBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.querier_builder(SessionRef::Borrow(self), key_expr)
+    }
+
+    fn querier_builder<'a, 'b, TryIntoKeyExpr>(
+        &self,
+        session: SessionRef<'a>,
+        key_expr: TryIntoKeyExpr,
+    ) -> QuerierBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        let timeout = {
+            let conf = self.runtime.config.lock();
+            Duration::from_millis(unwrap_or_default!(conf.queries_default_timeout()))
+        };
+        QuerierBuilder {
+            session,
+            key_expr: key_expr.try_into().map_err(Into::into),
+            target: QueryTarget::default(),
+            consolidation: QueryConsolidation::default(),
             destination: Locality::default(),
+            priority: Priority::default(),
+            timeout,
         }
     }
 
@@ -702,6 +897,55 @@ impl Session {
         }
     }
 
+    /// Put a batch of data.
+    ///
+    /// The data routed to a same remote node are enqueued contiguously on the same channel, without
+    /// any other message of that channel in between. This only holds for each hop: the routers
+    /// route the data of the batch one by one, and other traffic may be interleaved further on.
+    /// The batch is not atomic either: with [`CongestionControl::Drop`], the data not fitting in a
+    /// congested queue are dropped while the preceding ones are sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The key expressions and values to put
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session
+    ///     .put_batch([("robot/pose", "1.0,2.0"), ("robot/covariance", "0.1,0.2")])
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    pub fn put_batch<'a, 'b, I, TryIntoKeyExpr, IntoValue>(
+        &'a self,
+        samples: I,
+    ) -> PutBatchBuilder<'a, 'b>
+    where
+        I: IntoIterator<Item = (TryIntoKeyExpr, IntoValue)>,
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        PutBatchBuilder {
+            session: self,
+            samples: samples
+                .into_iter()
+                .map(|(key_expr, value)| {
+                    Ok((key_expr.try_into().map_err(Into::into)?, value.into()))
+                })
+                .collect(),
+            congestion_control: CongestionControl::default(),
+            priority: Priority::default(),
+            reliability: Reliability::Reliable,
+        }
+    }
+
     /// Delete data.
     ///
     /// # Arguments
@@ -770,8 +1014,11 @@ impl Session {
             target: QueryTarget::default(),
             consolidation: QueryConsolidation::default(),
             destination: Locality::default(),
+            priority: Priority::default(),
             timeout: Duration::from_millis(unwrap_or_default!(conf.queries_default_timeout())),
             value: None,
+            deduplication: None,
+            zids: None,
             handler: DefaultHandler,
         }
     }
@@ -807,6 +1054,7 @@ impl Session {
             state: self.state.clone(),
             id: self.id,
             alive: false,
+            owns_runtime: self.owns_runtime,
         }
     }
 
@@ -816,15 +1064,18 @@ impl Session {
             log::debug!("Config: {:?}", &config);
             let aggregated_subscribers = config.aggregation().subscribers().clone();
             let aggregated_publishers = config.aggregation().publishers().clone();
+            let transformers = Transformers::from_config(&config)?;
             match Runtime::init(config).await {
                 Ok(mut runtime) => {
-                    let session = Self::init(
+                    let mut session = Self::init(
                         runtime.clone(),
                         aggregated_subscribers,
                         aggregated_publishers,
+                        transformers,
                     )
                     .res_async()
                     .await;
+                    session.owns_runtime = true;
                     match runtime.start().await {
                         Ok(()) => {
                             // Workaround for the declare_and_shoot problem
@@ -839,6 +1090,38 @@ impl Session {
         })
     }
 
+    /// Declares the prefix of `key_expr` unless already done, so that it is sent as an integer
+    /// on the wire.
+    pub(crate) fn optimize_keyexpr<'b>(&self, key_expr: KeyExpr<'b>) -> KeyExpr<'b> {
+        if key_expr.is_fully_optimized(self) {
+            return key_expr;
+        }
+        let session_id = self.id;
+        let expr_id = self.declare_prefix(key_expr.as_str()).res_sync();
+        let prefix_len = key_expr
+            .len()
+            .try_into()
+            .expect("How did you get a key expression with a length over 2^32!?");
+        match key_expr.0 {
+            KeyExprInner::Borrowed(key_expr) | KeyExprInner::BorrowedWire { key_expr, .. } => {
+                KeyExpr(KeyExprInner::BorrowedWire {
+                    key_expr,
+                    expr_id,
+                    prefix_len,
+                    session_id,
+                })
+            }
+            KeyExprInner::Owned(key_expr) | KeyExprInner::Wire { key_expr, .. } => {
+                KeyExpr(KeyExprInner::Wire {
+                    key_expr,
+                    expr_id,
+                    prefix_len,
+                    session_id,
+                })
+            }
+        }
+    }
+
     pub(crate) fn declare_prefix<'a>(&'a self, prefix: &'a str) -> impl Resolve<u64> + Send + 'a {
         ResolveClosure::new(move || {
             trace!("declare_prefix({:?})", prefix);
@@ -966,8 +1249,13 @@ impl Session {
         scope: &Option<KeyExpr>,
         origin: Locality,
         callback: Callback<'static, Sample>,
+        filter: Option<SampleFilter>,
         info: &SubInfo,
     ) -> ZResult<Arc<SubscriberState>> {
+        let threshold = {
+            let conf = self.runtime.config.lock();
+            Duration::from_millis(unwrap_or_default!(conf.slow_consumer().threshold()))
+        };
         let mut state = zwrite!(self.state);
         log::trace!("subscribe({:?})", key_expr);
         let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
@@ -975,6 +1263,17 @@ impl Session {
             Some(scope) => scope / key_expr,
             None => key_expr.clone(),
         };
+        let callback = if threshold > Duration::ZERO {
+            detect_slow_subscriber(
+                id,
+                key_expr.to_string(),
+                threshold,
+                self.runtime.manager().slow_consumers().clone(),
+                callback,
+            )
+        } else {
+            callback
+        };
 
         let sub_state = Arc::new(SubscriberState {
             id,
@@ -982,6 +1281,7 @@ impl Session {
             scope: scope.clone().map(|e| e.into_owned()),
             origin,
             callback,
+            filter,
         });
 
         #[cfg(not(feature = "unstable"))]
@@ -1138,6 +1438,7 @@ impl Session {
         &self,
         key_expr: &WireExpr,
         complete: bool,
+        load_balanced: bool,
         origin: Locality,
         callback: Callback<'static, Query>,
     ) -> ZResult<Arc<QueryableState>> {
@@ -1148,6 +1449,7 @@ impl Session {
             id,
             key_expr: key_expr.to_owned(),
             complete,
+            load_balanced,
             origin,
             callback,
         });
@@ -1158,10 +1460,12 @@ impl Session {
             if origin != Locality::SessionLocal && complete {
                 let primitives = state.primitives.as_ref().unwrap().clone();
                 let complete = Session::complete_twin_qabls(&state, key_expr);
+                let load_balanced = Session::load_balanced_twin_qabls(&state, key_expr);
                 drop(state);
                 let qabl_info = QueryableInfo {
                     complete,
                     distance: 0,
+                    load_balanced,
                 };
                 primitives.decl_queryable(key_expr, &qabl_info, None);
             }
@@ -1170,17 +1474,25 @@ impl Session {
         {
             let twin_qabl = Session::twin_qabl(&state, key_expr);
             let complete_twin_qabl = twin_qabl && Session::complete_twin_qabl(&state, key_expr);
+            // The twin Queryables are no longer load balanced if this one isn't
+            let load_balanced_twin_qabl =
+                twin_qabl && Session::load_balanced_twin_qabls(&state, key_expr);
 
             state.queryables.insert(id, qable_state.clone());
 
-            if origin != Locality::SessionLocal && (!twin_qabl || (!complete_twin_qabl && complete))
+            if origin != Locality::SessionLocal
+                && (!twin_qabl
+                    || (!complete_twin_qabl && complete)
+                    || (load_balanced_twin_qabl && !load_balanced))
             {
                 let primitives = state.primitives.as_ref().unwrap().clone();
-                let complete = ZInt::from(!complete_twin_qabl && complete);
+                let complete = ZInt::from(complete_twin_qabl || complete);
+                let load_balanced = Session::load_balanced_twin_qabls(&state, key_expr);
                 drop(state);
                 let qabl_info = QueryableInfo {
                     complete,
                     distance: 0,
+                    load_balanced,
                 };
                 primitives.decl_queryable(key_expr, &qabl_info, None);
             }
@@ -1206,6 +1518,19 @@ impl Session {
         })
     }
 
+    // The queryables of a key expression are only declared load balanced if they all are
+    pub(crate) fn load_balanced_twin_qabls(state: &SessionState, key: &WireExpr) -> bool {
+        state
+            .queryables
+            .values()
+            .filter(|q| {
+                q.origin != Locality::SessionLocal
+                    && state.local_wireexpr_to_expr(&q.key_expr).unwrap()
+                        == state.local_wireexpr_to_expr(key).unwrap()
+            })
+            .all(|q| q.load_balanced)
+    }
+
     #[cfg(feature = "complete_n")]
     pub(crate) fn complete_twin_qabls(state: &SessionState, key: &WireExpr) -> ZInt {
         state
@@ -1228,28 +1553,36 @@ impl Session {
                 let primitives = state.primitives.as_ref().unwrap().clone();
                 if Session::twin_qabl(&state, &qable_state.key_expr) {
                     // There still exist Queryables on the same KeyExpr.
-                    if qable_state.complete {
-                        #[cfg(feature = "complete_n")]
-                        {
+                    let load_balanced =
+                        Session::load_balanced_twin_qabls(&state, &qable_state.key_expr);
+                    // The remaining Queryables may all be load balanced.
+                    let now_load_balanced = load_balanced && !qable_state.load_balanced;
+                    #[cfg(feature = "complete_n")]
+                    {
+                        if qable_state.complete || now_load_balanced {
                             let complete =
                                 Session::complete_twin_qabls(&state, &qable_state.key_expr);
                             drop(state);
                             let qabl_info = QueryableInfo {
                                 complete,
                                 distance: 0,
+                                load_balanced,
                             };
                             primitives.decl_queryable(&qable_state.key_expr, &qabl_info, None);
                         }
-                        #[cfg(not(feature = "complete_n"))]
-                        {
-                            if !Session::complete_twin_qabl(&state, &qable_state.key_expr) {
-                                drop(state);
-                                let qabl_info = QueryableInfo {
-                                    complete: 0,
-                                    distance: 0,
-                                };
-                                primitives.decl_queryable(&qable_state.key_expr, &qabl_info, None);
-                            }
+                    }
+                    #[cfg(not(feature = "complete_n"))]
+                    {
+                        let complete_twin_qabl =
+                            Session::complete_twin_qabl(&state, &qable_state.key_expr);
+                        if (qable_state.complete && !complete_twin_qabl) || now_load_balanced {
+                            drop(state);
+                            let qabl_info = QueryableInfo {
+                                complete: ZInt::from(complete_twin_qabl),
+                                distance: 0,
+                                load_balanced,
+                            };
+                            primitives.decl_queryable(&qable_state.key_expr, &qabl_info, None);
                         }
                     }
                 } else {
@@ -1310,16 +1643,22 @@ impl Session {
         local: bool,
         key_expr: &WireExpr,
         info: Option<DataInfo>,
-        payload: ZBuf,
+        mut payload: ZBuf,
     ) {
+        let span = tracing::trace_span!("deliver", local, trace = tracing::field::Empty);
+        if let Some(tc) = info.as_ref().and_then(|i| i.trace_context.as_ref()) {
+            span.record("trace", tracing::field::display(tc));
+        }
+        let _enter = span.enter();
+
         let mut callbacks = SingleOrVec::default();
         let state = zread!(self.state);
-        if key_expr.suffix.is_empty() {
+        let full_key_expr: KeyExpr<'static> = if key_expr.suffix.is_empty() {
             match state.get_res(&key_expr.scope, local) {
                 Some(Resource::Node(res)) => {
                     for sub in &res.subscribers {
-                        if sub.origin == Locality::Any
-                            || (local == (sub.origin == Locality::SessionLocal))
+                        if (sub.origin == Locality::Any
+                            || (local == (sub.origin == Locality::SessionLocal)))
                         {
                             match &sub.scope {
                                 Some(scope) => {
@@ -1335,6 +1674,7 @@ impl Session {
                                             Ok(key_expr) => callbacks.push((
                                                 sub.callback.clone(),
                                                 key_expr.into_owned(),
+                                                sub.filter.clone(),
                                             )),
                                             Err(e) => {
                                                 log::warn!(
@@ -1346,11 +1686,15 @@ impl Session {
                                         }
                                     }
                                 }
-                                None => callbacks
-                                    .push((sub.callback.clone(), res.key_expr.clone().into())),
+                                None => callbacks.push((
+                                    sub.callback.clone(),
+                                    res.key_expr.clone().into(),
+                                    sub.filter.clone(),
+                                )),
                             };
                         }
                     }
+                    res.key_expr.clone().into()
                 }
                 Some(Resource::Prefix { prefix }) => {
                     log::error!(
@@ -1385,6 +1729,7 @@ impl Session {
                                             Ok(key_expr) => callbacks.push((
                                                 sub.callback.clone(),
                                                 key_expr.into_owned(),
+                                                sub.filter.clone(),
                                             )),
                                             Err(e) => {
                                                 log::warn!(
@@ -1396,11 +1741,15 @@ impl Session {
                                         }
                                     }
                                 }
-                                None => callbacks
-                                    .push((sub.callback.clone(), key_expr.clone().into_owned())),
+                                None => callbacks.push((
+                                    sub.callback.clone(),
+                                    key_expr.clone().into_owned(),
+                                    sub.filter.clone(),
+                                )),
                             };
                         }
                     }
+                    key_expr.into_owned()
                 }
                 Err(err) => {
                     log::error!("Received Data for unkown key_expr: {}", err);
@@ -1409,7 +1758,31 @@ impl Session {
             }
         };
         drop(state);
-        let zenoh_collections::single_or_vec::IntoIter { drain, last } = callbacks.into_iter();
+
+        // The filters are evaluated out of the lock, on the payload as received, so that the
+        // payloads of the discarded samples are not decoded
+        let meta = SampleMetadata::new(info.as_ref(), &payload);
+        let mut accepted = SingleOrVec::default();
+        accepted.extend(
+            callbacks
+                .into_iter()
+                .filter(|(_, _, filter)| {
+                    filter
+                        .as_ref()
+                        .map_or(true, |filter| filter(&full_key_expr, &meta))
+                })
+                .map(|(callback, key_expr, _)| (callback, key_expr)),
+        );
+        if accepted.is_empty() {
+            return;
+        }
+        if !local {
+            match zread!(self.state).decode_payload(&full_key_expr, payload) {
+                Some(p) => payload = p,
+                None => return,
+            }
+        }
+        let zenoh_collections::single_or_vec::IntoIter { drain, last } = accepted.into_iter();
         for (cb, key_expr) in drain {
             cb(Sample::with_info(key_expr, payload.clone(), info.clone()));
         }
@@ -1437,12 +1810,15 @@ impl Session {
         target: QueryTarget,
         consolidation: QueryConsolidation,
         destination: Locality,
+        zids: Option<Vec<ZenohId>>,
+        priority: Priority,
         timeout: Duration,
         value: Option<Value>,
         callback: Callback<'static, Reply>,
     ) -> ZResult<()> {
         log::trace!("get({}, {:?}, {:?})", selector, target, consolidation);
         let mut state = zwrite!(self.state);
+        let sorted = consolidation.sorted;
         let consolidation = match consolidation.mode {
             Mode::Auto => {
                 if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) {
@@ -1454,23 +1830,47 @@ impl Session {
             Mode::Manual(mode) => mode,
         };
         let qid = state.qid_counter.fetch_add(1, Ordering::SeqCst);
+        // The session local queryables are skipped when the query is restricted to other runtimes
+        let local = destination != Locality::Remote
+            && zids
+                .as_ref()
+                .map_or(true, |zids| zids.contains(&self.runtime.zid));
         let nb_final = match destination {
-            Locality::Any => 2,
+            Locality::Any if local => 2,
             _ => 1,
         };
-        task::spawn({
+
+        let selector = match scope {
+            Some(scope) => Selector {
+                key_expr: scope / &*selector.key_expr,
+                parameters: selector.parameters.clone(),
+            },
+            None => selector.clone(),
+        };
+
+        // The body sent to the remote queryables is transformed like the published payloads
+        let body = match &value {
+            Some(v) if destination != Locality::SessionLocal => Some(QueryBody {
+                data_info: DataInfo {
+                    encoding: Some(v.encoding.clone()),
+                    ..Default::default()
+                },
+                payload: state.encode_payload(&selector.key_expr, v.payload.clone())?,
+            }),
+            _ => None,
+        };
+
+        executor::spawn({
             let state = self.state.clone();
             let zid = self.runtime.zid;
             async move {
                 task::sleep(timeout).await;
                 let mut state = zwrite!(state);
-                if let Some(query) = state.queries.remove(&qid) {
+                if let Some(mut query) = state.queries.remove(&qid) {
                     std::mem::drop(state);
                     log::debug!("Timout on query {}! Send error and close.", qid);
-                    if query.reception_mode == ConsolidationMode::Latest {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            (query.callback)(reply);
-                        }
+                    for reply in query.take_held_replies() {
+                        (query.callback)(reply);
                     }
                     (query.callback)(Reply {
                         sample: Err("Timeout".into()),
@@ -1480,14 +1880,6 @@ impl Session {
             }
         });
 
-        let selector = match scope {
-            Some(scope) => Selector {
-                key_expr: scope / &*selector.key_expr,
-                parameters: selector.parameters.clone(),
-            },
-            None => selector.clone(),
-        };
-
         log::trace!("Register query {} (nb_final = {})", qid, nb_final);
         let wexpr = selector.key_expr.to_wire(self);
         state.queries.insert(
@@ -1498,6 +1890,8 @@ impl Session {
                 scope: scope.clone().map(|e| e.into_owned()),
                 reception_mode: consolidation,
                 replies: (consolidation != ConsolidationMode::None).then(HashMap::new),
+                sorted: sorted.then(Vec::new),
+                zids: zids.clone(),
                 callback,
             },
         );
@@ -1512,20 +1906,14 @@ impl Session {
                 qid,
                 target,
                 consolidation,
-                value.as_ref().map(|v| {
-                    let data_info = DataInfo {
-                        encoding: Some(v.encoding.clone()),
-                        ..Default::default()
-                    };
-                    QueryBody {
-                        data_info,
-                        payload: v.payload.clone(),
-                    }
-                }),
+                Some(timeout),
+                zids.as_deref(),
+                body,
+                priority.into(),
                 None,
             );
         }
-        if destination != Locality::Remote {
+        if local {
             self.handle_query(
                 true,
                 &wexpr,
@@ -1533,6 +1921,7 @@ impl Session {
                 qid,
                 target,
                 consolidation,
+                Some(timeout),
                 value.map(|v| {
                     let data_info = DataInfo {
                         encoding: Some(v.encoding),
@@ -1543,7 +1932,11 @@ impl Session {
                         payload: v.payload,
                     }
                 }),
+                priority.into(),
             );
+        } else if destination == Locality::SessionLocal {
+            // No queryable can reply
+            self.send_reply_final(qid, priority.into());
         }
         Ok(())
     }
@@ -1555,14 +1948,40 @@ impl Session {
         key_expr: &WireExpr,
         parameters: &str,
         qid: ZInt,
-        _target: QueryTarget,
+        target: QueryTarget,
         _consolidation: ConsolidationMode,
-        body: Option<QueryBody>,
+        timeout: Option<Duration>,
+        mut body: Option<QueryBody>,
+        priority: zenoh_protocol::core::Priority,
     ) {
-        let (primitives, key_expr, callbacks) = {
+        // The complete targets only reach the complete queryables including the queried key_expr
+        let complete = !matches!(target, QueryTarget::BestMatching | QueryTarget::All);
+        #[cfg(feature = "complete_n")]
+        let max = match target {
+            QueryTarget::Complete(n) => n as usize,
+            _ => usize::MAX,
+        };
+        #[cfg(not(feature = "complete_n"))]
+        let max = usize::MAX;
+        let (primitives, key_expr, callbacks, transformers) = {
             let state = zread!(self.state);
             match state.wireexpr_to_keyexpr(key_expr, local) {
                 Ok(key_expr) => {
+                    if !local {
+                        if let Some(body) = body.as_mut() {
+                            let payload = std::mem::take(&mut body.payload);
+                            match state.decode_payload(&key_expr, payload) {
+                                Some(payload) => body.payload = payload,
+                                None => {
+                                    // Terminate the query, that will never reach the queryables
+                                    let primitives = state.primitives.as_ref().unwrap().clone();
+                                    drop(state);
+                                    primitives.send_reply_final(qid, priority);
+                                    return;
+                                }
+                            }
+                        }
+                    }
                     let callbacks = state
                         .queryables
                         .values()
@@ -1573,7 +1992,11 @@ impl Session {
                                 &&
                                 match state.local_wireexpr_to_expr(&queryable.key_expr) {
                                     Ok(qablname) => {
-                                        qablname.intersects(&key_expr)
+                                        if complete {
+                                            queryable.complete && qablname.includes(&key_expr)
+                                        } else {
+                                            qablname.intersects(&key_expr)
+                                        }
                                     }
                                     Err(err) => {
                                         error!(
@@ -1584,12 +2007,14 @@ impl Session {
                                     }
                                 }
                         )
+                        .take(max)
                         .map(|qable| qable.callback.clone())
                         .collect::<Vec<Arc<dyn Fn(Query) + Send + Sync>>>();
                     (
                         state.primitives.as_ref().unwrap().clone(),
                         key_expr.into_owned(),
                         callbacks,
+                        (!state.transformers.is_empty()).then(|| state.transformers.clone()),
                     )
                 }
                 Err(err) => {
@@ -1613,6 +2038,9 @@ impl Session {
                 }),
                 qid,
                 zid,
+                priority,
+                deadline: timeout.map(|timeout| Instant::now() + timeout),
+                transformers,
                 primitives: if local {
                     Arc::new(self.clone())
                 } else {
@@ -1620,6 +2048,10 @@ impl Session {
                 },
             }),
         };
+        if query.is_expired() {
+            log::debug!("Drop expired query {}", qid);
+            return;
+        }
         for callback in callbacks.iter() {
             callback(query.clone());
         }
@@ -1664,6 +2096,9 @@ impl SessionDeclarations for Arc<Session> {
             reliability: Reliability::default(),
             mode: PushMode,
             origin: Locality::default(),
+            background: false,
+            filter: None,
+            gap_listener: None,
             handler: DefaultHandler,
         }
     }
@@ -1707,7 +2142,9 @@ impl SessionDeclarations for Arc<Session> {
             session: SessionRef::Shared(self.clone()),
             key_expr: key_expr.try_into().map_err(Into::into),
             complete: false,
+            load_balanced: false,
             origin: Locality::default(),
+            background: false,
             handler: DefaultHandler,
         }
     }
@@ -1744,10 +2181,47 @@ impl SessionDeclarations for Arc<Session> {
             key_expr: key_expr.try_into().map_err(Into::into),
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
+            reliability: Reliability::Reliable,
             destination: Locality::default(),
+            block_timeout: None,
+            #[cfg(feature = "shared-memory")]
+            shm: None,
+            background: false,
+            latched: false,
+            sequenced: false,
         }
     }
 
+    /// Create a [`Querier`](crate::query::Querier) for the given key expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_expr` - The key expression matching resources to query
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap().into_arc();
+    /// let querier = session.declare_querier("key/expression")
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// let replies = querier.get().res().await.unwrap();
+    /// # })
+    /// ```
+    fn declare_querier<'b, TryIntoKeyExpr>(
+        &self,
+        key_expr: TryIntoKeyExpr,
+    ) -> QuerierBuilder<'static, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.querier_builder(SessionRef::Shared(self.clone()), key_expr)
+    }
+
     /// Obtain a [`Liveliness`] struct tied to this Zenoh [`Session`].
     ///
     /// # Examples
@@ -1899,7 +2373,10 @@ impl Primitives for Session {
         qid: ZInt,
         target: QueryTarget,
         consolidation: ConsolidationMode,
+        timeout: Option<Duration>,
+        _destinations: Option<&[ZenohId]>,
         body: Option<QueryBody>,
+        priority: zenoh_protocol::core::Priority,
         _routing_context: Option<RoutingContext>,
     ) {
         trace!(
@@ -1916,7 +2393,9 @@ impl Primitives for Session {
             qid,
             target,
             consolidation,
+            timeout,
             body,
+            priority,
         )
     }
 
@@ -1927,6 +2406,7 @@ impl Primitives for Session {
         key_expr: WireExpr,
         data_info: Option<DataInfo>,
         payload: ZBuf,
+        _priority: zenoh_protocol::core::Priority,
     ) {
         trace!(
             "recv ReplyData {:?} {:?} {:?} {:?} {:?}",
@@ -1944,8 +2424,23 @@ impl Primitives for Session {
                 return;
             }
         };
+        let payload = match state.decode_payload(&key_expr, payload) {
+            Some(payload) => payload,
+            None => return,
+        };
         match state.queries.get_mut(&qid) {
             Some(query) => {
+                // The routers not understanding the destinations forward the query to any queryable
+                if let Some(zids) = query.zids.as_ref() {
+                    if !zids.contains(&replier_id) {
+                        log::debug!(
+                            "Received ReplyData from `{}` not being a destination of query {}: dropping ReplyData.",
+                            replier_id,
+                            qid
+                        );
+                        return;
+                    }
+                }
                 if !matches!(
                     query
                         .selector
@@ -2050,6 +2545,16 @@ impl Primitives for Session {
                         None
                     }
                 };
+                let callback = match query.sorted.as_mut() {
+                    // The replies are delivered once the query is complete
+                    Some(sorted) => {
+                        if query.reception_mode == ConsolidationMode::None {
+                            sorted.extend(callback.map(|(_, new_reply)| new_reply));
+                        }
+                        None
+                    }
+                    None => callback,
+                };
                 std::mem::drop(state);
                 if let Some((callback, new_reply)) = callback {
                     callback(new_reply);
@@ -2061,19 +2566,17 @@ impl Primitives for Session {
         }
     }
 
-    fn send_reply_final(&self, qid: ZInt) {
+    fn send_reply_final(&self, qid: ZInt, _priority: zenoh_protocol::core::Priority) {
         trace!("recv ReplyFinal {:?}", qid);
         let mut state = zwrite!(self.state);
         match state.queries.get_mut(&qid) {
             Some(mut query) => {
                 query.nb_final -= 1;
                 if query.nb_final == 0 {
-                    let query = state.queries.remove(&qid).unwrap();
+                    let mut query = state.queries.remove(&qid).unwrap();
                     std::mem::drop(state);
-                    if query.reception_mode == ConsolidationMode::Latest {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            (query.callback)(reply);
-                        }
+                    for reply in query.take_held_replies() {
+                        (query.callback)(reply);
                     }
                     trace!("Close query {}", qid);
                 }
@@ -2103,6 +2606,33 @@ impl Primitives for Session {
     fn send_close(&self) {
         trace!("recv Close");
     }
+
+    fn send_congestion_drop(&self, key_expr: &str, dropped: ZInt) {
+        trace!("recv Congestion Drop {} {}", key_expr, dropped);
+        let key_expr = match keyexpr::new(key_expr) {
+            Ok(key_expr) => key_expr,
+            Err(e) => {
+                log::debug!(
+                    "Received congestion drop notification for {}: {}",
+                    key_expr,
+                    e
+                );
+                return;
+            }
+        };
+        let mut state = zwrite!(self.state);
+        state
+            .dropped_downstream
+            .retain(|(publisher, counter)| match counter.upgrade() {
+                Some(counter) => {
+                    if publisher.intersects(key_expr) {
+                        counter.fetch_add(dropped, Ordering::Relaxed);
+                    }
+                    true
+                }
+                None => false,
+            });
+    }
 }
 
 impl Drop for Session {
@@ -2239,6 +2769,33 @@ pub trait SessionDeclarations {
         TryIntoKeyExpr: TryInto<KeyExpr<'a>>,
         <TryIntoKeyExpr as TryInto<KeyExpr<'a>>>::Error: Into<zenoh_result::Error>;
 
+    /// Create a [`Querier`](crate::query::Querier) for the given key expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_expr` - The key expression matching resources to query
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap().into_arc();
+    /// let querier = session.declare_querier("key/expression")
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// let replies = querier.get().res().await.unwrap();
+    /// # })
+    /// ```
+    fn declare_querier<'a, TryIntoKeyExpr>(
+        &self,
+        key_expr: TryIntoKeyExpr,
+    ) -> QuerierBuilder<'static, 'a>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'a>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'a>>>::Error: Into<zenoh_result::Error>;
+
     /// Obtain a [`Liveliness`] struct tied to this Zenoh [`Session`].
     ///
     /// # Examples
@@ -15,15 +15,18 @@
 //! Subscribing primitives.
 use crate::handlers::{locked, Callback, DefaultHandler};
 use crate::prelude::Locality;
-use crate::prelude::{Id, IntoCallbackReceiverPair, KeyExpr, Sample};
+use crate::prelude::{keyexpr, Id, IntoCallbackReceiverPair, KeyExpr, Sample};
+use crate::sample::SampleMetadata;
 use crate::Undeclarable;
 use crate::{Result as ZResult, SessionRef};
 use std::fmt;
 use std::future::Ready;
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use zenoh_core::{AsyncResolve, Resolvable, Resolve, SyncResolve};
-use zenoh_protocol::core::SubInfo;
+use zenoh_protocol::core::{SubInfo, ZenohId};
+use zenoh_transport::slow_consumer::{SaturationDetector, SlowConsumerEvent, SlowConsumerEvents};
 
 /// The subscription mode.
 pub use zenoh_protocol::core::SubMode;
@@ -37,8 +40,14 @@ pub(crate) struct SubscriberState {
     pub(crate) scope: Option<KeyExpr<'static>>,
     pub(crate) origin: Locality,
     pub(crate) callback: Callback<'static, Sample>,
+    pub(crate) filter: Option<SampleFilter>,
 }
 
+/// A filter evaluated on the key expression and the [`SampleMetadata`] of the samples received
+/// by a subscriber, once their payload is received but before it is decoded: the samples for
+/// which it returns `false` are discarded.
+pub type SampleFilter = Arc<dyn Fn(&keyexpr, &SampleMetadata) -> bool + Send + Sync>;
+
 impl fmt::Debug for SubscriberState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Subscriber")
@@ -48,6 +57,145 @@ impl fmt::Debug for SubscriberState {
     }
 }
 
+/// Wraps the callback of the subscriber `id` to report it to `events` when the delivery of a sample
+/// blocks longer than `threshold`, e.g. because the channel it is delivered to is full.
+pub(crate) fn detect_slow_subscriber(
+    id: Id,
+    key_expr: String,
+    threshold: Duration,
+    events: Arc<SlowConsumerEvents>,
+    callback: Callback<'static, Sample>,
+) -> Callback<'static, Sample> {
+    let detector = SaturationDetector::new(threshold);
+    Arc::new(move |sample| {
+        let start = Instant::now();
+        callback(sample);
+        if start.elapsed() < threshold {
+            if detector.cleared().is_some() {
+                events.notify(SlowConsumerEvent::SubscriberRecovered {
+                    id,
+                    key_expr: key_expr.clone(),
+                });
+            }
+        } else if let Some(saturation) = detector.saturated(start, None, false) {
+            events.notify(SlowConsumerEvent::SlowSubscriber {
+                id,
+                key_expr: key_expr.clone(),
+                duration_ms: saturation.duration.as_millis() as u64,
+            });
+        }
+    })
+}
+
+/// A gap in the sequence numbers of the samples received by a subscriber from a source,
+/// reported to its [gap listener](SubscriberBuilder::gap_listener).
+#[derive(Debug, Clone)]
+pub struct SampleGap {
+    /// The [`ZenohId`] of the source of the missed samples.
+    pub source_id: ZenohId,
+    /// The key expression of the missed samples.
+    pub key_expr: KeyExpr<'static>,
+    /// The number of samples missed in this gap.
+    pub missed: u64,
+    /// The number of samples of this source and key expression missed since the subscriber
+    /// was declared.
+    pub total_missed: u64,
+}
+
+/// A listener of the [`SampleGap`]s of a subscriber.
+pub type GapListener = Arc<dyn Fn(SampleGap) + Send + Sync>;
+
+/// Wraps the callback of a subscriber to report to `listener` the gaps in the sequence numbers
+/// of the samples of each source and key expression. The samples older than the last one
+/// received, duplicated or reordered, are delivered without being accounted.
+#[zenoh_macros::unstable]
+fn detect_gaps(
+    callback: Callback<'static, Sample>,
+    listener: GapListener,
+) -> Callback<'static, Sample> {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use zenoh_core::zlock;
+    use zenoh_protocol::core::key_expr::OwnedKeyExpr;
+
+    // The last sequence number received and the number of missed samples per source
+    let sequences: Mutex<HashMap<ZenohId, HashMap<OwnedKeyExpr, (u64, u64)>>> =
+        Mutex::new(HashMap::new());
+    Arc::new(move |sample: Sample| {
+        let source = &sample.source_info;
+        if let (Some(source_id), Some(sn)) = (source.source_id, source.source_sn) {
+            let gap = {
+                let mut sequences = zlock!(sequences);
+                let keys = sequences.entry(source_id).or_default();
+                match keys.get_mut(sample.key_expr.as_keyexpr()) {
+                    Some((last, total)) if sn > *last + 1 => {
+                        let missed = sn - *last - 1;
+                        *last = sn;
+                        *total += missed;
+                        Some(SampleGap {
+                            source_id,
+                            key_expr: sample.key_expr.clone(),
+                            missed,
+                            total_missed: *total,
+                        })
+                    }
+                    Some((last, _)) => {
+                        *last = sn.max(*last);
+                        None
+                    }
+                    None => {
+                        keys.insert(sample.key_expr.clone().into(), (sn, 0));
+                        None
+                    }
+                }
+            };
+            if let Some(gap) = gap {
+                listener(gap);
+            }
+        }
+        callback(sample)
+    })
+}
+
+#[test]
+#[cfg(feature = "unstable")]
+fn detect_gaps_test() {
+    use crate::sample::SourceInfo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    let received = Arc::new(AtomicUsize::new(0));
+    let gaps = Arc::new(Mutex::new(vec![]));
+    let c_received = received.clone();
+    let c_gaps = gaps.clone();
+    let callback = detect_gaps(
+        Arc::new(move |_| {
+            c_received.fetch_add(1, Ordering::Relaxed);
+        }),
+        Arc::new(move |gap| c_gaps.lock().unwrap().push(gap)),
+    );
+
+    let source_id = ZenohId::rand();
+    let sample = |key_expr: &'static str, sn| {
+        Sample::new(KeyExpr::try_from(key_expr).unwrap(), vec![0u8]).with_source_info(SourceInfo {
+            source_id: Some(source_id),
+            source_sn: Some(sn),
+        })
+    };
+    for sn in [0, 1, 4, 3, 5, 9] {
+        callback(sample("demo/a", sn));
+    }
+    callback(sample("demo/b", 7));
+    callback(sample("demo/b", 8));
+    assert_eq!(received.load(Ordering::Relaxed), 8);
+
+    let gaps = gaps.lock().unwrap();
+    assert_eq!(gaps.len(), 2);
+    assert_eq!((gaps[0].missed, gaps[0].total_missed), (2, 2));
+    assert_eq!((gaps[1].missed, gaps[1].total_missed), (3, 5));
+    assert!(gaps.iter().all(|gap| gap.key_expr.as_str() == "demo/a"));
+}
+
 /// A subscriber that provides data through a callback.
 ///
 /// CallbackSubscribers can be created from a zenoh [`Session`](crate::Session)
@@ -158,7 +306,7 @@ impl<'a> PullSubscriberInner<'a> {
     /// # })
     /// ```
     #[inline]
-    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+    pub fn undeclare(self) -> SubscriberUndeclaration<'a> {
         Undeclarable::undeclare_inner(self.inner, ())
     }
 }
@@ -199,6 +347,10 @@ impl<'a> Undeclarable<(), SubscriberUndeclaration<'a>> for SubscriberInner<'a> {
 
 /// A [`Resolvable`] returned when undeclaring a subscriber.
 ///
+/// Once resolved, the subscriber's callback won't be called anymore and the undeclaration has been
+/// handed to the network, so that the same key expression can be subscribed to again right away.
+/// It can also be awaited directly, which is equivalent to [`res_async`](AsyncResolve::res_async).
+///
 /// # Examples
 /// ```
 /// # async_std::task::block_on(async {
@@ -238,10 +390,21 @@ impl AsyncResolve for SubscriberUndeclaration<'_> {
     }
 }
 
+impl std::future::IntoFuture for SubscriberUndeclaration<'_> {
+    type Output = ZResult<()>;
+    type IntoFuture = Ready<Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.res_async()
+    }
+}
+
 impl Drop for SubscriberInner<'_> {
     fn drop(&mut self) {
         if self.alive {
-            let _ = self.session.unsubscribe(self.state.id);
+            if let Err(e) = self.session.unsubscribe(self.state.id) {
+                log::warn!("Error undeclaring subscriber on drop: {}", e);
+            }
         }
     }
 }
@@ -313,6 +476,21 @@ pub struct SubscriberBuilder<'a, 'b, Mode, Handler> {
     #[cfg(not(feature = "unstable"))]
     pub(crate) origin: Locality,
 
+    #[cfg(feature = "unstable")]
+    pub background: bool,
+    #[cfg(not(feature = "unstable"))]
+    pub(crate) background: bool,
+
+    #[cfg(feature = "unstable")]
+    pub filter: Option<SampleFilter>,
+    #[cfg(not(feature = "unstable"))]
+    pub(crate) filter: Option<SampleFilter>,
+
+    #[cfg(feature = "unstable")]
+    pub gap_listener: Option<GapListener>,
+    #[cfg(not(feature = "unstable"))]
+    pub(crate) gap_listener: Option<GapListener>,
+
     #[cfg(feature = "unstable")]
     pub handler: Handler,
     #[cfg(not(feature = "unstable"))]
@@ -347,6 +525,9 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler: _,
         } = self;
         SubscriberBuilder {
@@ -355,6 +536,9 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler: callback,
         }
     }
@@ -420,6 +604,9 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler: _,
         } = self;
         SubscriberBuilder {
@@ -428,10 +615,46 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler,
         }
     }
 }
+impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler>
+where
+    Handler: Fn(Sample) + Send + Sync + 'static,
+{
+    /// Keep the subscriber declared until the session is closed, even if the returned
+    /// [`Subscriber`] is dropped.
+    ///
+    /// This is only available for subscribers with a callback, which keep receiving the samples
+    /// without the application holding the subscriber. Such a subscriber can still be undeclared
+    /// explicitly with [`Subscriber::undeclare`].
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session
+    ///     .declare_subscriber("key/expression")
+    ///     .callback(|sample| { println!("Received: {} {}", sample.key_expr, sample.value); })
+    ///     .background()
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn background(mut self) -> Self {
+        self.background = true;
+        self
+    }
+}
+
 impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
     /// Change the subscription reliability.
     #[inline]
@@ -454,6 +677,72 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
         self
     }
 
+    /// Only receive the samples for which the given `filter` returns `true`.
+    ///
+    /// The filter is evaluated on the key expression and the [`SampleMetadata`] of the samples,
+    /// once their payload is fully received, i.e. reassembled when fragmented, but before it is
+    /// decoded by the payload transformers of the session, so that cheap metadata filters avoid
+    /// the cost of decoding and delivering the discarded samples. It must not block.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let subscriber = session
+    ///     .declare_subscriber("key/expression/**")
+    ///     .filter(|_key_expr, meta| meta.len <= 1024)
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn filter<Filter>(mut self, filter: Filter) -> Self
+    where
+        Filter: Fn(&keyexpr, &SampleMetadata) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Report to `listener` the gaps in the sequence numbers of the samples received from each
+    /// source, i.e. the number of samples this subscriber missed, e.g. because they were
+    /// dropped on a lossy link of a best-effort route.
+    ///
+    /// Only the samples of [sequenced](crate::publication::PublisherBuilder::sequenced)
+    /// publishers carry sequence numbers. The sequences are tracked per source session and key
+    /// expression, so that the gaps of the samples of several sequenced publishers of a same
+    /// session on a same key expression are not meaningful, and the samples discarded by the
+    /// [`filter`](SubscriberBuilder::filter) of the subscriber count as missed. The listener
+    /// must not block.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let subscriber = session
+    ///     .declare_subscriber("key/expression")
+    ///     .best_effort()
+    ///     .gap_listener(|gap| println!("Missed {} samples from {}", gap.missed, gap.source_id))
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn gap_listener<Listener>(mut self, listener: Listener) -> Self
+    where
+        Listener: Fn(SampleGap) + Send + Sync + 'static,
+    {
+        self.gap_listener = Some(Arc::new(listener));
+        self
+    }
+
     /// Restrict the matching publications that will be receive by this [`Subscriber`]
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_macros::unstable]
@@ -472,6 +761,9 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: _,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler,
         } = self;
         SubscriberBuilder {
@@ -480,6 +772,9 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: PullMode,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler,
         }
     }
@@ -493,6 +788,9 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: _,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler,
         } = self;
         SubscriberBuilder {
@@ -501,6 +799,9 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: PushMode,
             origin,
+            background,
+            filter,
+            gap_listener,
             handler,
         }
     }
@@ -524,12 +825,18 @@ where
         let key_expr = self.key_expr?;
         let session = self.session;
         let (callback, receiver) = self.handler.into_cb_receiver_pair();
+        #[cfg(feature = "unstable")]
+        let callback = match self.gap_listener {
+            Some(listener) => detect_gaps(callback, listener),
+            None => callback,
+        };
         session
             .declare_subscriber_inner(
                 &key_expr,
                 &None,
                 self.origin,
                 callback,
+                self.filter,
                 &SubInfo {
                     reliability: self.reliability,
                     mode: self.mode.into(),
@@ -539,7 +846,8 @@ where
                 subscriber: SubscriberInner {
                     session,
                     state: sub_state,
-                    alive: true,
+                    // Background subscribers are not undeclared when dropped
+                    alive: !self.background,
                 },
                 receiver,
             })
@@ -576,12 +884,18 @@ where
         let key_expr = self.key_expr?;
         let session = self.session;
         let (callback, receiver) = self.handler.into_cb_receiver_pair();
+        #[cfg(feature = "unstable")]
+        let callback = match self.gap_listener {
+            Some(listener) => detect_gaps(callback, listener),
+            None => callback,
+        };
         session
             .declare_subscriber_inner(
                 &key_expr,
                 &None,
                 self.origin,
                 callback,
+                self.filter,
                 &SubInfo {
                     reliability: self.reliability,
                     mode: self.mode.into(),
@@ -592,7 +906,7 @@ where
                     inner: SubscriberInner {
                         session,
                         state: sub_state,
-                        alive: true,
+                        alive: !self.background,
                     },
                 },
                 receiver,
@@ -619,7 +933,9 @@ where
 /// and the [`with`](SubscriberBuilder::with) function
 /// of the resulting builder.
 ///
-/// Subscribers are automatically undeclared when dropped.
+/// Subscribers are automatically undeclared when dropped, before `drop` returns, unless declared
+/// in the [`background`](SubscriberBuilder::background). Errors are only logged then: await
+/// [`undeclare`](Subscriber::undeclare) to get them.
 ///
 /// # Examples
 /// ```no_run
@@ -655,7 +971,9 @@ pub struct Subscriber<'a, Receiver> {
 /// and the [`pull_mode`](SubscriberBuilder::pull_mode) function
 /// of the resulting builder.
 ///
-/// Subscribers are automatically undeclared when dropped.
+/// Subscribers are automatically undeclared when dropped, before `drop` returns, unless declared
+/// in the [`background`](SubscriberBuilder::background). Errors are only logged then: await
+/// [`undeclare`](PullSubscriber::undeclare) to get them.
 ///
 /// # Examples
 /// ```
@@ -737,7 +1055,7 @@ impl<'a, Receiver> PullSubscriber<'a, Receiver> {
     /// # })
     /// ```
     #[inline]
-    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+    pub fn undeclare(self) -> SubscriberUndeclaration<'a> {
         self.subscriber.undeclare()
     }
 }
@@ -0,0 +1,202 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Payload transformation hooks.
+//!
+//! A [`PayloadTransformer`] is applied on the payloads published on, queried on or replied
+//! on the key expressions it has been registered for: [`encode`](PayloadTransformer::encode)
+//! is called before the payload leaves the session and [`decode`](PayloadTransformer::decode)
+//! is called before a payload received from the network is delivered to the local subscribers,
+//! queryables or queriers. Publications and queries exchanged between entities of the same
+//! session are never transformed, while replies always are since they are received the same way
+//! whether they come from the session or from the network.
+//!
+//! The [`AesGcmTransformer`] is a reference implementation encrypting the payloads with
+//! AES-256-GCM. Such transformers can be configured through the `transform/encryption`
+//! section of the configuration.
+use crate::config::{Config, EncryptionRule};
+use crate::prelude::{keyexpr, OwnedKeyExpr};
+use std::sync::{Arc, Mutex};
+use zenoh_buffers::{SplitBuffer, ZBuf};
+use zenoh_crypto::{AeadCipher, PseudoRng};
+use zenoh_result::{bail, zerror, ZResult};
+
+/// A transformation applied on the payloads of the matching key expressions.
+pub trait PayloadTransformer: Send + Sync {
+    /// Transforms a payload before it is sent on the network.
+    fn encode(&self, key_expr: &keyexpr, payload: ZBuf) -> ZResult<ZBuf>;
+
+    /// Reverts the transformation on a payload received from the network.
+    fn decode(&self, key_expr: &keyexpr, payload: ZBuf) -> ZResult<ZBuf>;
+}
+
+/// A [`PayloadTransformer`] encrypting payloads with AES-256-GCM.
+pub struct AesGcmTransformer {
+    cipher: AeadCipher,
+    prng: Mutex<PseudoRng>,
+}
+
+impl AesGcmTransformer {
+    pub const KEY_SIZE: usize = AeadCipher::KEY_SIZE;
+
+    pub fn new(key: [u8; Self::KEY_SIZE]) -> Self {
+        use rand::SeedableRng;
+
+        AesGcmTransformer {
+            cipher: AeadCipher::new(key),
+            prng: Mutex::new(PseudoRng::from_entropy()),
+        }
+    }
+
+    /// Creates a transformer from a hex encoded key.
+    pub fn from_hex(key: &str) -> ZResult<Self> {
+        let bytes = hex::decode(key.trim()).map_err(|e| zerror!("Invalid hex key: {}", e))?;
+        let key: [u8; Self::KEY_SIZE] = bytes.try_into().map_err(|b: Vec<u8>| {
+            zerror!(
+                "Invalid key length: expected {} bytes, got {}",
+                Self::KEY_SIZE,
+                b.len()
+            )
+        })?;
+        Ok(Self::new(key))
+    }
+
+    fn from_rule(rule: &EncryptionRule) -> ZResult<Self> {
+        match (&rule.key, &rule.key_file) {
+            (Some(key), None) => Self::from_hex(key),
+            (None, Some(path)) => {
+                let key = std::fs::read_to_string(path)
+                    .map_err(|e| zerror!("Unable to read key file `{}`: {}", path, e))?;
+                Self::from_hex(&key)
+            }
+            (Some(_), Some(_)) => bail!("Only one of `key` and `key_file` can be provided"),
+            (None, None) => bail!("One of `key` or `key_file` must be provided"),
+        }
+    }
+}
+
+impl PayloadTransformer for AesGcmTransformer {
+    fn encode(&self, _key_expr: &keyexpr, payload: ZBuf) -> ZResult<ZBuf> {
+        let clear = payload.contiguous();
+        let encrypted = self.cipher.encrypt(&clear, &mut zlock!(self.prng))?;
+        Ok(encrypted.into())
+    }
+
+    fn decode(&self, _key_expr: &keyexpr, payload: ZBuf) -> ZResult<ZBuf> {
+        let encrypted = payload.contiguous();
+        let clear = self.cipher.decrypt(&encrypted)?;
+        Ok(clear.into())
+    }
+}
+
+#[derive(Clone)]
+struct TransformRule {
+    key_expr: OwnedKeyExpr,
+    transformer: Arc<dyn PayloadTransformer>,
+}
+
+/// The set of transformation rules of a session.
+#[derive(Clone, Default)]
+pub(crate) struct Transformers {
+    rules: Vec<TransformRule>,
+}
+
+impl Transformers {
+    pub(crate) fn from_config(config: &Config) -> ZResult<Self> {
+        let mut transformers = Transformers::default();
+        for rule in config.transform().encryption() {
+            let transformer: Arc<dyn PayloadTransformer> =
+                Arc::new(AesGcmTransformer::from_rule(rule)?);
+            for key_expr in &rule.key_exprs {
+                transformers.add(key_expr.clone(), transformer.clone());
+            }
+        }
+        Ok(transformers)
+    }
+
+    pub(crate) fn add(&mut self, key_expr: OwnedKeyExpr, transformer: Arc<dyn PayloadTransformer>) {
+        self.rules.push(TransformRule {
+            key_expr,
+            transformer,
+        });
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the transformer of the first rule including `key_expr`, if any.
+    fn get(&self, key_expr: &keyexpr) -> Option<&Arc<dyn PayloadTransformer>> {
+        self.rules
+            .iter()
+            .find(|r| r.key_expr.includes(key_expr))
+            .map(|r| &r.transformer)
+    }
+
+    pub(crate) fn encode(&self, key_expr: &keyexpr, payload: ZBuf) -> ZResult<ZBuf> {
+        match self.get(key_expr) {
+            Some(transformer) => transformer.encode(key_expr, payload),
+            None => Ok(payload),
+        }
+    }
+
+    pub(crate) fn decode(&self, key_expr: &keyexpr, payload: ZBuf) -> ZResult<ZBuf> {
+        match self.get(key_expr) {
+            Some(transformer) => transformer.decode(key_expr, payload),
+            None => Ok(payload),
+        }
+    }
+}
+
+mod tests {
+    #[test]
+    fn transformers() {
+        use super::{AesGcmTransformer, Transformers};
+        use crate::prelude::{keyexpr, OwnedKeyExpr, SplitBuffer};
+        use std::convert::TryFrom;
+        use std::sync::Arc;
+        use zenoh_buffers::ZBuf;
+
+        let mut transformers = Transformers::default();
+        transformers.add(
+            OwnedKeyExpr::try_from("demo/secret/**").unwrap(),
+            Arc::new(
+                AesGcmTransformer::from_hex(
+                    "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                )
+                .unwrap(),
+            ),
+        );
+
+        let clear: ZBuf = b"some payload".to_vec().into();
+
+        // Payloads of non-matching key expressions are left untouched
+        let public = keyexpr::new("demo/public").unwrap();
+        let encoded = transformers.encode(public, clear.clone()).unwrap();
+        assert_eq!(encoded, clear);
+
+        let secret = keyexpr::new("demo/secret/a").unwrap();
+        let encoded = transformers.encode(secret, clear.clone()).unwrap();
+        assert_ne!(encoded.contiguous(), clear.contiguous());
+        let decoded = transformers.decode(secret, encoded).unwrap();
+        assert_eq!(decoded.contiguous(), clear.contiguous());
+
+        // Clear payloads are rejected for encrypted key expressions
+        assert!(transformers.decode(secret, clear).is_err());
+
+        assert!(AesGcmTransformer::from_hex("0001").is_err());
+        assert!(AesGcmTransformer::from_hex("not hex").is_err());
+    }
+}
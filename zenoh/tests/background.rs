@@ -0,0 +1,68 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[test]
+fn background_declarations() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+
+    // The subscriber and the queryable are still declared once their guards are dropped
+    let (tx, rx) = flume::unbounded();
+    let subscriber = session
+        .declare_subscriber("test/background")
+        .callback(move |sample| tx.send(sample).unwrap())
+        .background()
+        .res_sync()
+        .unwrap();
+    drop(subscriber);
+    let queryable = session
+        .declare_queryable("test/background")
+        .callback(|query| {
+            let sample = Sample::try_from("test/background", "reply").unwrap();
+            query.reply(Ok(sample)).res_sync().unwrap();
+        })
+        .background()
+        .res_sync()
+        .unwrap();
+    drop(queryable);
+
+    session.put("test/background", "put").res_sync().unwrap();
+    let sample = rx.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.value.to_string(), "put");
+    let replies = session.get("test/background").res_sync().unwrap();
+    let reply = replies.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(reply.sample.unwrap().value.to_string(), "reply");
+
+    // Background entities can still be undeclared explicitly
+    let (tx, rx) = flume::unbounded();
+    let subscriber = session
+        .declare_subscriber("test/background/undeclared")
+        .callback(move |sample| tx.send(sample).unwrap())
+        .background()
+        .res_sync()
+        .unwrap();
+    subscriber.undeclare().res_sync().unwrap();
+    session
+        .put("test/background/undeclared", "put")
+        .res_sync()
+        .unwrap();
+    assert!(rx.recv_timeout(Duration::from_secs(1)).is_err());
+
+    session.close().res_sync().unwrap();
+}
@@ -0,0 +1,77 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[test]
+fn deduplicated_get() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+
+    let hlc = session.hlc().unwrap();
+    let first = hlc.new_timestamp();
+    let second = hlc.new_timestamp();
+    let _queryable = session
+        .declare_queryable("test/dedup/**")
+        .callback(move |query| {
+            // Each reply is sent twice, as if received through two routers
+            for (key, timestamp) in [("a", first), ("a", first), ("a", second), ("b", first)] {
+                for _ in 0..2 {
+                    let sample = Sample::new(
+                        KeyExpr::try_from(format!("test/dedup/{key}")).unwrap(),
+                        "value",
+                    )
+                    .with_timestamp(timestamp);
+                    query.reply(Ok(sample)).res_sync().unwrap();
+                }
+            }
+            // Replies without timestamp are never deduplicated
+            for _ in 0..2 {
+                let sample = Sample::new(KeyExpr::try_from("test/dedup/c").unwrap(), "value");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            }
+        })
+        .res_sync()
+        .unwrap();
+
+    let get = |window| {
+        session
+            .get("test/dedup/**")
+            .consolidation(ConsolidationMode::None)
+            .deduplicate(window)
+            .timeout(TIMEOUT)
+            .res_sync()
+            .unwrap()
+            .iter()
+            .map(|reply| reply.sample.unwrap().key_expr.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        get(16),
+        [
+            "test/dedup/a",
+            "test/dedup/a",
+            "test/dedup/b",
+            "test/dedup/c",
+            "test/dedup/c"
+        ]
+    );
+    // The replies which fell out of the window are delivered again
+    assert_eq!(get(0).len(), 10);
+
+    session.close().res_sync().unwrap();
+}
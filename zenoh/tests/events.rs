@@ -120,3 +120,30 @@ fn zenoh_events() {
         close_session(session).await;
     });
 }
+
+#[test]
+fn zenoh_connect_failure_events() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        // Nothing listens on this endpoint, the configured peer is retried in the background
+        let session = open_session(&[], &["tcp/127.0.0.1:18449"]).await;
+        let zid = session.zid();
+        let sub = session
+            .declare_subscriber(format!("@/session/{zid}/connect/failure"))
+            .res()
+            .await
+            .unwrap();
+
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        let failure: serde_json::Value =
+            serde_json::from_slice(&sample.value.payload.contiguous()).unwrap();
+        assert_eq!(failure["endpoint"], "tcp/127.0.0.1:18449");
+        assert_eq!(failure["reason"], "unreachable");
+        assert!(!failure["error"].as_str().unwrap().is_empty());
+        assert!(!failure["retry_in"].is_null());
+
+        sub.undeclare().res().await.unwrap();
+        close_session(session).await;
+    });
+}
@@ -0,0 +1,94 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh_core::SyncResolve;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+#[async_std::test]
+async fn get_stream() {
+    let locator = "tcp/127.0.0.1:38458";
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = vec![locator.parse().unwrap()];
+    let session1 = zenoh::open(config).res().await.unwrap();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.endpoints = vec![locator.parse().unwrap()];
+    let session2 = zenoh::open(config).res().await.unwrap();
+
+    // The queryable sends its second reply and its final marker only once signaled
+    let (signal_tx, signal_rx) = flume::unbounded::<()>();
+    let _queryable = session1
+        .declare_queryable("test/get_stream/*")
+        .callback(move |query| {
+            let signal_rx = signal_rx.clone();
+            std::thread::spawn(move || {
+                let first = Sample::try_from("test/get_stream/first", "first").unwrap();
+                query.reply(Ok(first)).res_sync().unwrap();
+                signal_rx.recv_timeout(TIMEOUT).unwrap();
+                let second = Sample::try_from("test/get_stream/second", "second").unwrap();
+                query.reply(Ok(second)).res_sync().unwrap();
+            });
+        })
+        .res()
+        .await
+        .unwrap();
+    async_std::task::sleep(SLEEP).await;
+
+    // The default consolidation buffers the replies until the query completes
+    let replies = session2
+        .get("test/get_stream/*")
+        .consolidation(ConsolidationMode::Monotonic)
+        .res()
+        .await
+        .unwrap();
+    let first = replies
+        .recv_deadline_async(Instant::now() + TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(first.replier_id, session1.zid());
+    assert_eq!(first.sample.unwrap().value.to_string(), "first");
+
+    // No other reply until the queryable is signaled
+    assert_eq!(
+        replies
+            .recv_deadline_async(Instant::now() + SLEEP)
+            .await
+            .unwrap_err(),
+        flume::RecvTimeoutError::Timeout
+    );
+    signal_tx.send(()).unwrap();
+    let second = replies
+        .recv_deadline_async(Instant::now() + TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(second.replier_id, session1.zid());
+    assert_eq!(second.sample.unwrap().value.to_string(), "second");
+
+    // The stream terminates on the final marker, long before the query timeout
+    assert_eq!(
+        replies
+            .recv_deadline_async(Instant::now() + TIMEOUT)
+            .await
+            .unwrap_err(),
+        flume::RecvTimeoutError::Disconnected
+    );
+
+    session2.close().res().await.unwrap();
+    session1.close().res().await.unwrap();
+}
@@ -0,0 +1,35 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh::ke;
+use zenoh::key_expr::{keyexpr, OwnedKeyExpr};
+
+#[test]
+fn keyexpr_algebra() {
+    let root: &'static keyexpr = ke!("demo/**");
+    let ke = ke!("demo/example/*");
+    assert!(root.includes(ke));
+    assert!(!ke.includes(root));
+    assert!(ke.intersects(ke!("demo/*/test")));
+    assert!(!ke.intersects(ke!("demo/example")));
+    assert_eq!(
+        ke!("demo").join("example/**").unwrap().as_str(),
+        "demo/example/**"
+    );
+    assert_eq!(
+        OwnedKeyExpr::autocanonize(String::from("demo/**/**/*"))
+            .unwrap()
+            .as_str(),
+        ke!("demo/*/**").as_str()
+    );
+}
@@ -0,0 +1,111 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::scouting::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+fn open_router(endpoint: &str, max_samples: Option<usize>) -> Session {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.routing.latched.set_max_samples(max_samples).unwrap();
+    zenoh::open(config).res_sync().unwrap()
+}
+
+fn open_client(endpoint: &str) -> Session {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Client)).unwrap();
+    config.connect.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    zenoh::open(config).res_sync().unwrap()
+}
+
+#[test]
+fn latched_late_subscriber() {
+    let endpoint = "tcp/127.0.0.1:17452";
+    let router = open_router(endpoint, None);
+
+    // The sample is published before any subscriber is declared
+    let publisher_client = open_client(endpoint);
+    let publisher = publisher_client
+        .declare_publisher("test/latched/a")
+        .latched(true)
+        .res_sync()
+        .unwrap();
+    publisher.put("value").res_sync().unwrap();
+    std::thread::sleep(SLEEP);
+
+    // A client joining afterwards receives the latched sample from the router
+    let subscriber_client = open_client(endpoint);
+    let subscriber = subscriber_client
+        .declare_subscriber("test/latched/**")
+        .res_sync()
+        .unwrap();
+    let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.key_expr.as_str(), "test/latched/a");
+    assert_eq!(sample.value.to_string(), "value");
+    drop(subscriber);
+
+    // A latched delete forgets the sample
+    publisher.delete().res_sync().unwrap();
+    std::thread::sleep(SLEEP);
+    let subscriber = subscriber_client
+        .declare_subscriber("test/latched/**")
+        .res_sync()
+        .unwrap();
+    std::thread::sleep(SLEEP);
+    assert!(subscriber.try_recv().is_err());
+    drop(subscriber);
+
+    drop(publisher);
+    subscriber_client.close().res_sync().unwrap();
+    publisher_client.close().res_sync().unwrap();
+    router.close().res_sync().unwrap();
+}
+
+#[test]
+fn latched_max_samples() {
+    let endpoint = "tcp/127.0.0.1:17453";
+    let router = open_router(endpoint, Some(1));
+
+    let publisher_client = open_client(endpoint);
+    for key_expr in ["test/latched_max/a", "test/latched_max/b"] {
+        publisher_client
+            .put(key_expr, "value")
+            .latched(true)
+            .res_sync()
+            .unwrap();
+    }
+    std::thread::sleep(SLEEP);
+
+    // Only the sample of the first key expression is kept by the router
+    let subscriber_client = open_client(endpoint);
+    let subscriber = subscriber_client
+        .declare_subscriber("test/latched_max/**")
+        .res_sync()
+        .unwrap();
+    let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.key_expr.as_str(), "test/latched_max/a");
+    std::thread::sleep(SLEEP);
+    assert!(subscriber.try_recv().is_err());
+    drop(subscriber);
+
+    subscriber_client.close().res_sync().unwrap();
+    publisher_client.close().res_sync().unwrap();
+    router.close().res_sync().unwrap();
+}
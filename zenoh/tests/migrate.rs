@@ -0,0 +1,82 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::scouting::WhatAmI;
+
+const SLEEP: Duration = Duration::from_secs(3);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn open_router(endpoint: &str) -> Session {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    zenoh::open(config).res_sync().unwrap()
+}
+
+fn migrate(router: &Session, locator: &str) {
+    let locator: Locator = locator.parse().unwrap();
+    async_std::task::block_on(router.runtime().migrate_clients(locator)).unwrap();
+}
+
+#[test]
+fn client_migration() {
+    let endpoint_a = "tcp/127.0.0.1:17473";
+    let endpoint_b = "tcp/127.0.0.1:17474";
+    // The routers are not connected: a sample only reaches the client through its own router
+    let router_a = open_router(endpoint_a);
+    let router_b = open_router(endpoint_b);
+
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Client)).unwrap();
+    config.connect.endpoints = vec![endpoint_a.parse().unwrap(), endpoint_b.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let client = zenoh::open(config).res_sync().unwrap();
+
+    let key_expr = "test/migrate";
+    let subscriber = client.declare_subscriber(key_expr).res_sync().unwrap();
+    std::thread::sleep(SLEEP);
+    router_a.put(key_expr, "a").res_sync().unwrap();
+    assert_eq!(
+        subscriber.recv_timeout(TIMEOUT).unwrap().value.to_string(),
+        "a"
+    );
+
+    // A locator the client is not configured to connect to is ignored: the client reconnects
+    // to its first endpoint and re-declares its subscriber there
+    migrate(&router_a, "tcp/127.0.0.1:17475");
+    std::thread::sleep(SLEEP);
+    router_a.put(key_expr, "a").res_sync().unwrap();
+    assert_eq!(
+        subscriber.recv_timeout(TIMEOUT).unwrap().value.to_string(),
+        "a"
+    );
+
+    // The client follows a configured endpoint, and re-declares its subscriber on the new router
+    migrate(&router_a, endpoint_b);
+    std::thread::sleep(SLEEP);
+    router_b.put(key_expr, "b").res_sync().unwrap();
+    assert_eq!(
+        subscriber.recv_timeout(TIMEOUT).unwrap().value.to_string(),
+        "b"
+    );
+    router_a.put(key_expr, "a").res_sync().unwrap();
+    assert!(subscriber.recv_timeout(Duration::from_secs(1)).is_err());
+
+    drop(subscriber);
+    client.close().res_sync().unwrap();
+    router_b.close().res_sync().unwrap();
+    router_a.close().res_sync().unwrap();
+}
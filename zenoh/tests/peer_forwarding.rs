@@ -0,0 +1,65 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::scouting::WhatAmI;
+
+const SLEEP: Duration = Duration::from_secs(1);
+const MSG_COUNT: usize = 10;
+
+fn open_peer(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Peer)).unwrap();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    // Without gossip, the peers forward between all their neighbors
+    config.scouting.gossip.set_enabled(Some(false)).unwrap();
+    config.routing.peer.set_forwarding(Some(true)).unwrap();
+    zenoh::open(config).res_sync().unwrap()
+}
+
+#[test]
+fn peer_forwarding_triangle() {
+    let endpoint_a = "tcp/127.0.0.1:17454";
+    let endpoint_b = "tcp/127.0.0.1:17455";
+    let peer_a = open_peer(&[endpoint_a], &[]);
+    let peer_b = open_peer(&[endpoint_b], &[endpoint_a]);
+    let peer_c = open_peer(&[], &[endpoint_a, endpoint_b]);
+    std::thread::sleep(SLEEP);
+
+    let key_expr = "test/peer_forwarding/triangle";
+    let subscriber_b = peer_b.declare_subscriber(key_expr).res_sync().unwrap();
+    let subscriber_c = peer_c.declare_subscriber(key_expr).res_sync().unwrap();
+    std::thread::sleep(SLEEP);
+
+    // Each sample reaches the other peers both directly and through the third one, and is
+    // delivered only once without looping in the triangle
+    for i in 0..MSG_COUNT {
+        peer_a.put(key_expr, i.to_string()).res_sync().unwrap();
+    }
+    std::thread::sleep(SLEEP);
+    assert_eq!(subscriber_b.try_iter().count(), MSG_COUNT);
+    assert_eq!(subscriber_c.try_iter().count(), MSG_COUNT);
+
+    std::thread::sleep(SLEEP);
+    assert!(subscriber_b.try_recv().is_err());
+    assert!(subscriber_c.try_recv().is_err());
+
+    drop(subscriber_b);
+    drop(subscriber_c);
+    peer_c.close().res_sync().unwrap();
+    peer_b.close().res_sync().unwrap();
+    peer_a.close().res_sync().unwrap();
+}
@@ -0,0 +1,200 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::plugins::{
+    Plugin, PluginsManager, Response, RunningPlugin, RunningPluginTrait, ValidationFunction,
+    ZResult, ZenohPlugin,
+};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{AdminSpace, Runtime};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+
+// The number of times the plugin fails to start before succeeding
+const FAILURES: usize = 2;
+static STARTS: AtomicUsize = AtomicUsize::new(0);
+static ALIVE: AtomicBool = AtomicBool::new(false);
+
+struct FlakyPlugin;
+
+impl ZenohPlugin for FlakyPlugin {}
+impl Plugin for FlakyPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = RunningPlugin;
+
+    const STATIC_NAME: &'static str = "flaky";
+
+    fn start(name: &str, _runtime: &Self::StartArgs) -> ZResult<Self::RunningPlugin> {
+        if STARTS.fetch_add(1, Ordering::SeqCst) < FAILURES {
+            return Err(format!("Plugin {name} failed to start").into());
+        }
+        ALIVE.store(true, Ordering::SeqCst);
+        Ok(Box::new(RunningFlakyPlugin))
+    }
+}
+
+struct RunningFlakyPlugin;
+
+impl RunningPluginTrait for RunningFlakyPlugin {
+    fn config_checker(&self) -> ValidationFunction {
+        Arc::new(|_, _, _| Ok(None))
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        _selector: &'a Selector<'a>,
+        _plugin_status_key: &str,
+    ) -> ZResult<Vec<Response>> {
+        Ok(vec![])
+    }
+
+    fn is_alive(&self) -> bool {
+        ALIVE.load(Ordering::SeqCst)
+    }
+}
+
+struct StaticPlugin;
+
+impl ZenohPlugin for StaticPlugin {}
+impl Plugin for StaticPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = RunningPlugin;
+
+    const STATIC_NAME: &'static str = "static";
+
+    fn start(_name: &str, _runtime: &Self::StartArgs) -> ZResult<Self::RunningPlugin> {
+        Ok(Box::new(RunningStaticPlugin))
+    }
+}
+
+struct RunningStaticPlugin;
+
+impl RunningPluginTrait for RunningStaticPlugin {
+    fn config_checker(&self) -> ValidationFunction {
+        Arc::new(|_, _, _| Ok(None))
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        _selector: &'a Selector<'a>,
+        _plugin_status_key: &str,
+    ) -> ZResult<Vec<Response>> {
+        Ok(vec![])
+    }
+}
+
+async fn wait_until(condition: impl Fn() -> bool) {
+    async {
+        while !condition() {
+            task::sleep(SLEEP).await;
+        }
+    }
+    .timeout(TIMEOUT)
+    .await
+    .unwrap();
+}
+
+#[test]
+fn plugin_restart() {
+    task::block_on(async {
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5("plugins/flaky", r#"{ __restart__: "on-failure" }"#)
+            .unwrap();
+        let session = zenoh::open(config).res().await.unwrap();
+        let runtime = session.runtime().clone();
+
+        let mut plugins = PluginsManager::static_plugins_only().add_static::<FlakyPlugin>();
+        assert!(plugins.start(FlakyPlugin::STATIC_NAME, &runtime).is_err());
+        AdminSpace::start(&runtime, plugins, "test".into()).await;
+
+        // The plugin failing to start is retried until it starts
+        wait_until(|| ALIVE.load(Ordering::SeqCst)).await;
+        assert_eq!(STARTS.load(Ordering::SeqCst), FAILURES + 1);
+
+        // The plugin exiting while running is restarted as well
+        ALIVE.store(false, Ordering::SeqCst);
+        wait_until(|| ALIVE.load(Ordering::SeqCst)).await;
+        assert_eq!(STARTS.load(Ordering::SeqCst), FAILURES + 2);
+
+        session.close().res().await.unwrap();
+    });
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn plugin_add_static() {
+    task::block_on(async {
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let session = zenoh::open(config).res().await.unwrap();
+        let runtime = session.runtime().clone();
+        AdminSpace::start(
+            &runtime,
+            PluginsManager::static_plugins_only(),
+            "test".into(),
+        )
+        .await;
+
+        runtime
+            .add_plugin::<StaticPlugin>(serde_json::json!({ "key": "value" }))
+            .unwrap();
+        assert!(runtime
+            .add_plugin::<StaticPlugin>(serde_json::json!({}))
+            .is_err());
+        let value = runtime
+            .config
+            .lock()
+            .get_json("plugins/static/key")
+            .unwrap();
+        assert_eq!(value, r#""value""#);
+
+        // The plugin is listed among the running plugins of the admin space
+        let router = format!("@/router/{}", session.zid());
+        let replies = session.get(&router).res().await.unwrap();
+        let reply = replies
+            .recv_async()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        let data: serde_json::Value =
+            serde_json::from_str(&reply.sample.unwrap().value.to_string()).unwrap();
+        assert_eq!(data["plugins"]["static"]["path"], "<statically_linked>");
+
+        let replies = session
+            .get(format!("{router}/status/plugins/static/__path__"))
+            .res()
+            .await
+            .unwrap();
+        let reply = replies
+            .recv_async()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            reply.sample.unwrap().value.to_string(),
+            "<statically_linked>"
+        );
+
+        session.close().res().await.unwrap();
+    });
+}
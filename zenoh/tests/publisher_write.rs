@@ -0,0 +1,74 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::publication::CongestionControl;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+#[test]
+fn publisher_write_kinds() {
+    let locator = "tcp/127.0.0.1:38462";
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = vec![locator.parse().unwrap()];
+    let session1 = zenoh::open(config).res_sync().unwrap();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.endpoints = vec![locator.parse().unwrap()];
+    let session2 = zenoh::open(config).res_sync().unwrap();
+
+    let subscriber = session2
+        .declare_subscriber("test/publisher_write")
+        .res_sync()
+        .unwrap();
+    let publisher = session1
+        .declare_publisher("test/publisher_write")
+        .congestion_control(CongestionControl::Block)
+        .priority(Priority::RealTime)
+        .res_sync()
+        .unwrap();
+    std::thread::sleep(SLEEP);
+
+    publisher.put("value").res_sync().unwrap();
+    publisher.delete().res_sync().unwrap();
+    publisher
+        .write(SampleKind::Put, "other")
+        .res_sync()
+        .unwrap();
+    publisher
+        .write(SampleKind::Delete, Value::empty())
+        .res_sync()
+        .unwrap();
+
+    let expected = [
+        (SampleKind::Put, "value"),
+        (SampleKind::Delete, ""),
+        (SampleKind::Put, "other"),
+        (SampleKind::Delete, ""),
+    ];
+    for (kind, value) in expected {
+        let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "test/publisher_write");
+        assert_eq!(sample.kind, kind);
+        assert_eq!(sample.value.to_string(), value);
+    }
+    drop(subscriber);
+    drop(publisher);
+
+    session2.close().res_sync().unwrap();
+    session1.close().res_sync().unwrap();
+}
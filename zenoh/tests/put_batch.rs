@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+#[test]
+fn put_batch() {
+    let locator = "tcp/127.0.0.1:38457";
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = vec![locator.parse().unwrap()];
+    let session1 = zenoh::open(config).res_sync().unwrap();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.endpoints = vec![locator.parse().unwrap()];
+    let session2 = zenoh::open(config).res_sync().unwrap();
+
+    let subscriber = session2
+        .declare_subscriber("test/put_batch/**")
+        .res_sync()
+        .unwrap();
+    std::thread::sleep(SLEEP);
+
+    for i in 0..10 {
+        session1
+            .put_batch([
+                ("test/put_batch/pose", format!("pose{i}")),
+                ("test/put_batch/covariance", format!("covariance{i}")),
+            ])
+            .res_sync()
+            .unwrap();
+    }
+    for i in 0..10 {
+        let pose = subscriber.recv_timeout(TIMEOUT).unwrap();
+        let covariance = subscriber.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(pose.key_expr.as_str(), "test/put_batch/pose");
+        assert_eq!(pose.value.to_string(), format!("pose{i}"));
+        assert_eq!(covariance.key_expr.as_str(), "test/put_batch/covariance");
+        assert_eq!(covariance.value.to_string(), format!("covariance{i}"));
+    }
+    drop(subscriber);
+
+    session2.close().res_sync().unwrap();
+    session1.close().res_sync().unwrap();
+}
+
+#[test]
+fn publisher_put_iter() {
+    let locator = "tcp/127.0.0.1:38460";
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = vec![locator.parse().unwrap()];
+    let session1 = zenoh::open(config).res_sync().unwrap();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.endpoints = vec![locator.parse().unwrap()];
+    let session2 = zenoh::open(config).res_sync().unwrap();
+
+    let subscriber = session2
+        .declare_subscriber("test/put_iter/**")
+        .res_sync()
+        .unwrap();
+    let publisher = session1
+        .declare_publisher("test/put_iter")
+        .res_sync()
+        .unwrap();
+    std::thread::sleep(SLEEP);
+
+    publisher
+        .put_iter((0..100).map(|i| (format!("tile/{i}"), format!("cell{i}"))))
+        .res_sync()
+        .unwrap();
+    for i in 0..100 {
+        let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(sample.key_expr.as_str(), format!("test/put_iter/tile/{i}"));
+        assert_eq!(sample.value.to_string(), format!("cell{i}"));
+    }
+    drop(subscriber);
+    drop(publisher);
+
+    session2.close().res_sync().unwrap();
+    session1.close().res_sync().unwrap();
+}
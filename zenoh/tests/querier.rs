@@ -0,0 +1,65 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::query::QueryTarget;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+#[test]
+fn querier() {
+    let locator = "tcp/127.0.0.1:38461";
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = vec![locator.parse().unwrap()];
+    let session1 = zenoh::open(config).res_sync().unwrap();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.endpoints = vec![locator.parse().unwrap()];
+    let session2 = zenoh::open(config).res_sync().unwrap();
+
+    let _queryable = session1
+        .declare_queryable("test/querier/*")
+        .callback(|query| {
+            let value = query.value().map_or_else(String::new, |v| v.to_string());
+            let sample = Sample::new(query.key_expr().clone(), value);
+            query.reply(Ok(sample)).res_sync().unwrap();
+        })
+        .res_sync()
+        .unwrap();
+    std::thread::sleep(SLEEP);
+
+    let querier = session2
+        .declare_querier("test/querier/a")
+        .target(QueryTarget::All)
+        .timeout(TIMEOUT)
+        .res_sync()
+        .unwrap();
+    assert_eq!(querier.key_expr().as_str(), "test/querier/a");
+    for i in 0..10 {
+        let replies = querier.get().with_value(format!("{i}")).res_sync().unwrap();
+        let reply = replies.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(reply.replier_id, session1.zid());
+        let sample = reply.sample.unwrap();
+        assert_eq!(sample.key_expr.as_str(), "test/querier/a");
+        assert_eq!(sample.value.to_string(), format!("{i}"));
+        assert!(replies.recv_timeout(TIMEOUT).is_err());
+    }
+    drop(querier);
+
+    session2.close().res_sync().unwrap();
+    session1.close().res_sync().unwrap();
+}
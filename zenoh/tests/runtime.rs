@@ -0,0 +1,43 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[test]
+fn sessions_sharing_runtime() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+    let shared = zenoh::init(session.runtime().clone()).res_sync().unwrap();
+    assert_eq!(shared.zid(), session.zid());
+
+    let key_expr = "test/sessions_sharing_runtime";
+    let subscriber = session.declare_subscriber(key_expr).res_sync().unwrap();
+    shared.put(key_expr, "value").res_sync().unwrap();
+    let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.key_expr.as_str(), key_expr);
+
+    // Closing a session initialized on the runtime doesn't close the runtime
+    shared.close().res_sync().unwrap();
+    let other = zenoh::init(session.runtime().clone()).res_sync().unwrap();
+    other.put(key_expr, "value").res_sync().unwrap();
+    let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.key_expr.as_str(), key_expr);
+    drop(subscriber);
+
+    other.close().res_sync().unwrap();
+    session.close().res_sync().unwrap();
+}
@@ -13,6 +13,7 @@
 //
 use async_std::prelude::FutureExt;
 use async_std::task;
+use std::net::SocketAddr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -160,6 +161,118 @@ async fn test_session_qryrep(peer01: &Session, peer02: &Session) {
     }
 }
 
+async fn test_session_qryrep_targets(peer01: &Session, peer02: &Session) {
+    let key_expr = "test/session/targets/a";
+
+    // A complete queryable including the key_expr, and an incomplete one
+    println!("[QT][01c] Queryables on peer01 session");
+    let mut qbls = vec![];
+    for (qbl_expr, complete) in [("test/session/targets/**", true), (key_expr, false)] {
+        let qbl = ztimeout!(peer01
+            .declare_queryable(qbl_expr)
+            .complete(complete)
+            .callback(move |query| {
+                let rep = Sample::try_from(key_expr, vec![0u8; 8]).unwrap();
+                task::block_on(async { ztimeout!(query.reply(Ok(rep)).res_async()).unwrap() });
+            })
+            .res_async())
+        .unwrap();
+        qbls.push(qbl);
+    }
+
+    // Wait for the declarations to propagate
+    task::sleep(SLEEP).await;
+
+    for (target, expected) in [(QueryTarget::All, 2), (QueryTarget::AllComplete, 1)] {
+        println!("[QT][02c] Getting on peer02 session with target {target:?}");
+        let rs = ztimeout!(peer02
+            .get(key_expr)
+            .target(target)
+            .consolidation(ConsolidationMode::None)
+            .res_async())
+        .unwrap();
+        let mut cnt = 0;
+        while let Ok(s) = ztimeout!(rs.recv_async()) {
+            assert!(s.sample.is_ok());
+            cnt += 1;
+        }
+        assert_eq!(cnt, expected);
+    }
+
+    println!("[QT][03c] Unqueryables on peer01 session");
+    for qbl in qbls {
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+    }
+    task::sleep(SLEEP).await;
+}
+
+async fn test_session_qryrep_destinations(peer01: &Session, peer02: &Session) {
+    let key_expr = "test/session/destinations";
+
+    println!("[QD][01c] Queryables on peer01 and peer02 sessions");
+    let mut qbls = vec![];
+    for peer in [peer01, peer02] {
+        let qbl = ztimeout!(peer
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                let rep = Sample::try_from(key_expr, vec![0u8; 8]).unwrap();
+                task::block_on(async { ztimeout!(query.reply(Ok(rep)).res_async()).unwrap() });
+            })
+            .res_async())
+        .unwrap();
+        qbls.push(qbl);
+    }
+
+    // Wait for the declarations to propagate
+    task::sleep(SLEEP).await;
+
+    println!("[QD][02c] Getting on peer02 session from peer01 only");
+    let zid01 = peer01.zid();
+    let rs = ztimeout!(peer02
+        .get(key_expr)
+        .destination_zids([zid01])
+        .consolidation(ConsolidationMode::None)
+        .res_async())
+    .unwrap();
+    let mut cnt = 0;
+    while let Ok(s) = ztimeout!(rs.recv_async()) {
+        assert!(s.sample.is_ok());
+        assert_eq!(s.replier_id, zid01);
+        cnt += 1;
+    }
+    assert_eq!(cnt, 1);
+
+    println!("[QD][03c] Getting on peer02 session local queryables from peer01 only");
+    let rs = ztimeout!(peer02
+        .get(key_expr)
+        .destination_zids([zid01])
+        .allowed_destination(Locality::SessionLocal)
+        .res_async())
+    .unwrap();
+    // The query completes right away, without waiting for its timeout
+    assert!(rs.recv_async().timeout(SLEEP).await.unwrap().is_err());
+
+    println!("[QD][04c] Unqueryables on peer01 and peer02 sessions");
+    for qbl in qbls {
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+    }
+    task::sleep(SLEEP).await;
+}
+
+async fn test_session_info(peer01: &Session, peer02: &Session) {
+    println!("[SI][01c] Session info on peer02 session");
+    let zid = ztimeout!(peer01.info().zid().res_async());
+    assert_eq!(
+        ztimeout!(peer02.info().mode().res_async()),
+        config::WhatAmI::Peer
+    );
+    let peers: Vec<_> = ztimeout!(peer02.info().peers().res_async()).collect();
+    let peer = peers.iter().find(|p| p.zid == zid).unwrap();
+    assert_eq!(peer.whatami, config::WhatAmI::Peer);
+    assert!(!peer.locators.is_empty());
+    assert_eq!(ztimeout!(peer02.info().routers().res_async()).count(), 0);
+}
+
 #[test]
 fn zenoh_session() {
     task::block_on(async {
@@ -167,8 +280,55 @@ fn zenoh_session() {
         let _ = env_logger::try_init();
 
         let (peer01, peer02) = open_session(&["tcp/127.0.0.1:17447"]).await;
+        test_session_info(&peer01, &peer02).await;
         test_session_pubsub(&peer01, &peer02).await;
         test_session_qryrep(&peer01, &peer02).await;
+        test_session_qryrep_targets(&peer01, &peer02).await;
+        test_session_qryrep_destinations(&peer01, &peer02).await;
+        close_session(peer01, peer02).await;
+    });
+}
+
+#[test]
+fn zenoh_session_port_zero() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let mut config = config::peer();
+        config.listen.endpoints = vec![
+            "tcp/127.0.0.1:0".parse().unwrap(),
+            "tcp/127.0.0.1:0".parse().unwrap(),
+        ];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        println!("[PZ][01a] Opening peer01 session on port 0");
+        let peer01 = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        // The listeners are bound to distinct ports assigned by the system
+        let listeners: Vec<_> = ztimeout!(peer01.info().listeners().res_async()).collect();
+        let ports: Vec<u16> = listeners
+            .iter()
+            .map(|l| l.address().as_str().parse::<SocketAddr>().unwrap().port())
+            .collect();
+        println!("[PZ][01b] peer01 listening on {listeners:?}");
+        assert_eq!(ports.len(), 2);
+        assert!(ports.iter().all(|p| *p != 0));
+        assert_ne!(ports[0], ports[1]);
+
+        let mut config = config::peer();
+        config.connect.endpoints = vec![listeners[0].to_string().parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        println!(
+            "[PZ][02a] Opening peer02 session connected to {}",
+            listeners[0]
+        );
+        let peer02 = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        task::sleep(SLEEP).await;
+        let zid = ztimeout!(peer01.info().zid().res_async());
+        let mut peers = ztimeout!(peer02.info().peers_zid().res_async());
+        assert!(peers.any(|p| p == zid));
+
         close_session(peer01, peer02).await;
     });
 }
@@ -0,0 +1,61 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[test]
+fn sorted_get() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+
+    let _queryable = session
+        .declare_queryable("test/sorted/**")
+        .callback(|query| {
+            for (key, value) in [("c", "1"), ("a", "2"), ("b", "3"), ("a", "4")] {
+                let sample = Sample::new(
+                    KeyExpr::try_from(format!("test/sorted/{key}")).unwrap(),
+                    value,
+                );
+                query.reply(Ok(sample)).res_sync().unwrap();
+            }
+        })
+        .res_sync()
+        .unwrap();
+
+    let replies = session
+        .get("test/sorted/**")
+        .consolidation(ConsolidationMode::None)
+        .sorted(true)
+        .timeout(TIMEOUT)
+        .res_sync()
+        .unwrap();
+    let keys = replies
+        .iter()
+        .map(|reply| reply.sample.unwrap().key_expr.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        [
+            "test/sorted/a",
+            "test/sorted/a",
+            "test/sorted/b",
+            "test/sorted/c"
+        ]
+    );
+
+    session.close().res_sync().unwrap();
+}
@@ -0,0 +1,59 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "stats")]
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::scouting::WhatAmI;
+
+const SLEEP: Duration = Duration::from_secs(1);
+const MSG_COUNT: usize = 10;
+
+fn open_peer(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Peer)).unwrap();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    zenoh::open(config).res_sync().unwrap()
+}
+
+#[test]
+fn session_stats_monotonic() {
+    let endpoint = "tcp/127.0.0.1:17472";
+    let peer_a = open_peer(&[endpoint], &[]);
+    let peer_b = open_peer(&[], &[endpoint]);
+    std::thread::sleep(SLEEP);
+
+    let key_expr = "test/stats/monotonic";
+    let subscriber = peer_b.declare_subscriber(key_expr).res_sync().unwrap();
+    std::thread::sleep(SLEEP);
+    for i in 0..MSG_COUNT {
+        peer_a.put(key_expr, i.to_string()).res_sync().unwrap();
+    }
+    std::thread::sleep(SLEEP);
+    assert_eq!(subscriber.try_iter().count(), MSG_COUNT);
+    let before = peer_a.stats();
+    assert!(before.tx.data.msgs >= MSG_COUNT);
+
+    // The statistics of the closed transport remain accounted
+    drop(subscriber);
+    peer_b.close().res_sync().unwrap();
+    std::thread::sleep(SLEEP);
+    let after = peer_a.stats();
+    assert!(after.tx.data.msgs >= before.tx.data.msgs);
+    assert!(after.tx.zenoh_msgs >= before.tx.zenoh_msgs);
+    assert!(after.rx.bytes >= before.rx.bytes);
+
+    peer_a.close().res_sync().unwrap();
+}
@@ -0,0 +1,47 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh::prelude::sync::*;
+
+#[test]
+fn subscriber_filter() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+
+    let subscriber = session
+        .declare_subscriber("test/filter/**")
+        .filter(|key_expr, meta| {
+            key_expr.ends_with("/keep") && meta.kind == SampleKind::Put && meta.len <= 8
+        })
+        .with(flume::unbounded())
+        .res_sync()
+        .unwrap();
+
+    session.put("test/filter/keep", "small").res_sync().unwrap();
+    session.put("test/filter/drop", "small").res_sync().unwrap();
+    session
+        .put("test/filter/keep", "a larger payload")
+        .res_sync()
+        .unwrap();
+    session.delete("test/filter/keep").res_sync().unwrap();
+    session.put("test/filter/keep", "last").res_sync().unwrap();
+
+    let values = subscriber
+        .try_iter()
+        .map(|sample| sample.value.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(values, ["small", "last"]);
+
+    session.close().res_sync().unwrap();
+}
@@ -0,0 +1,65 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::thread;
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+fn open_sessions(endpoint: &str) -> (SyncSession, SyncSession) {
+    let mut config = config::peer();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer01 = SyncSession::open(config).unwrap();
+
+    let mut config = config::peer();
+    config.connect.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer02 = SyncSession::open(config).unwrap();
+
+    (peer01, peer02)
+}
+
+#[test]
+fn sync_session() {
+    let (peer01, peer02) = open_sessions("tcp/127.0.0.1:17449");
+    let key_expr = "test/sync_session";
+
+    let subscriber = peer01.declare_subscriber(key_expr).unwrap();
+    thread::sleep(SLEEP);
+    peer02.put(key_expr, "value").unwrap();
+    let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.key_expr.as_str(), key_expr);
+    assert_eq!(sample.kind, SampleKind::Put);
+    drop(subscriber);
+
+    let queryable = peer01.declare_queryable(key_expr).unwrap();
+    thread::sleep(SLEEP);
+    let replies = peer02.get(key_expr).unwrap();
+    let query = queryable.recv_timeout(TIMEOUT).unwrap();
+    query
+        .reply(Ok(Sample::new(query.key_expr().clone(), "reply")))
+        .res_sync()
+        .unwrap();
+    drop(query);
+    let reply = replies.recv_timeout(TIMEOUT).unwrap();
+    assert!(reply.sample.is_ok());
+    // The channel is disconnected once the query is finalized
+    assert!(replies.recv_timeout(TIMEOUT).is_err());
+    drop(queryable);
+
+    peer02.close().unwrap();
+    peer01.close().unwrap();
+}
@@ -0,0 +1,48 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::time::{TimestampId, NTP64};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+fn fixed_clock() -> NTP64 {
+    NTP64(1 << 32)
+}
+
+#[test]
+fn timestamps_from_clock() {
+    zenoh::time::set_clock(fixed_clock);
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.insert_json5("timestamping/enabled", "true").unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+    let id = TimestampId::from(&session.zid());
+
+    let key_expr = "test/timestamps_from_clock";
+    let subscriber = session.declare_subscriber(key_expr).res_sync().unwrap();
+    session.put(key_expr, "first").res_sync().unwrap();
+    session.put(key_expr, "second").res_sync().unwrap();
+    let first = subscriber.recv_timeout(TIMEOUT).unwrap();
+    let second = subscriber.recv_timeout(TIMEOUT).unwrap();
+    let (first, second) = (first.timestamp().unwrap(), second.timestamp().unwrap());
+    assert_eq!(first.get_id(), &id);
+    assert_eq!(first.get_time().as_secs(), 1);
+    // The HLC keeps the timestamps increasing even though the clock is stopped
+    assert!(first < second);
+    drop(subscriber);
+
+    session.close().res_sync().unwrap();
+    zenoh::time::set_clock(zenoh::time::system_clock);
+}
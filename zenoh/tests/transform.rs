@@ -0,0 +1,111 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::queryable::Query;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+const ENCRYPTION: &str = r#"[{
+    key_exprs: ["test/transform/**"],
+    key: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+}]"#;
+
+fn open(listen: Option<&str>, connect: &[&str], encrypted: bool) -> Session {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = listen.iter().map(|l| l.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|c| c.parse().unwrap()).collect();
+    if encrypted {
+        config
+            .insert_json5("transform/encryption", ENCRYPTION)
+            .unwrap();
+    }
+    zenoh::open(config).res_sync().unwrap()
+}
+
+fn get(session: &Session, key_expr: &str, value: &str) -> Vec<Vec<u8>> {
+    session
+        .get(key_expr)
+        .with_value(value)
+        .timeout(TIMEOUT)
+        .res_sync()
+        .unwrap()
+        .iter()
+        .map(|reply| reply.sample.unwrap().value.payload.contiguous().to_vec())
+        .collect()
+}
+
+#[test]
+fn transform_queries_and_replies() {
+    let locator1 = "tcp/127.0.0.1:38463";
+    let locator3 = "tcp/127.0.0.1:38464";
+    let session1 = open(Some(locator1), &[], true);
+    let session3 = open(Some(locator3), &[locator1], false);
+    let session2 = open(None, &[locator1, locator3], true);
+
+    // Both queryables reply with the body of the query as they received it
+    let echo = |query: Query| {
+        let payload = match query.value() {
+            Some(value) => value.payload.clone(),
+            None => b"none".to_vec().into(),
+        };
+        let sample = Sample::new(query.key_expr().clone(), payload);
+        query.reply(Ok(sample)).res_sync().unwrap();
+    };
+    let _queryable1 = session1
+        .declare_queryable("test/transform/1")
+        .callback(echo)
+        .res_sync()
+        .unwrap();
+    let _queryable3 = session3
+        .declare_queryable("test/transform/3")
+        .callback(echo)
+        .res_sync()
+        .unwrap();
+    std::thread::sleep(SLEEP);
+
+    // The query body is decoded by the queryable and the reply by the querier
+    assert_eq!(
+        get(&session2, "test/transform/1", "secret"),
+        [b"secret".to_vec()]
+    );
+
+    // The queryable without the key echoes the body as encrypted by the querier, which can
+    // decode the reply only because it was not sent in clear
+    assert_eq!(
+        get(&session2, "test/transform/3", "secret"),
+        [b"secret".to_vec()]
+    );
+
+    // The querier without the key receives an encrypted reply
+    let replies = session3
+        .get("test/transform/1")
+        .timeout(TIMEOUT)
+        .res_sync()
+        .unwrap()
+        .iter()
+        .map(|reply| reply.sample.unwrap().value.payload.contiguous().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(replies.len(), 1);
+    assert_ne!(replies[0], b"none");
+
+    // Its clear query body can't be decoded, so that the query never reaches the queryable
+    assert!(get(&session3, "test/transform/1", "secret").is_empty());
+
+    session2.close().res_sync().unwrap();
+    session3.close().res_sync().unwrap();
+    session1.close().res_sync().unwrap();
+}
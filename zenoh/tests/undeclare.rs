@@ -0,0 +1,77 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+#[async_std::test]
+async fn undeclare_and_redeclare() {
+    let locator = "tcp/127.0.0.1:38459";
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = vec![locator.parse().unwrap()];
+    let session1 = zenoh::open(config).res().await.unwrap();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.endpoints = vec![locator.parse().unwrap()];
+    let session2 = zenoh::open(config).res().await.unwrap();
+
+    let key_expr = "test/undeclare";
+    let (tx, rx) = flume::unbounded();
+
+    // Awaiting the undeclaration guarantees the subscriber won't receive anything anymore
+    let first_tx = tx.clone();
+    let first = session2
+        .declare_subscriber(key_expr)
+        .callback(move |_| first_tx.send("first").unwrap())
+        .res()
+        .await
+        .unwrap();
+    async_std::task::sleep(SLEEP).await;
+    first.undeclare().await.unwrap();
+    let second_tx = tx.clone();
+    let second = session2
+        .declare_subscriber(key_expr)
+        .callback(move |_| second_tx.send("second").unwrap())
+        .res()
+        .await
+        .unwrap();
+    async_std::task::sleep(SLEEP).await;
+    session1.put(key_expr, "value").res().await.unwrap();
+    assert_eq!(rx.recv_timeout(TIMEOUT).unwrap(), "second");
+
+    // So does dropping it
+    drop(second);
+    let third_tx = tx.clone();
+    let _third = session2
+        .declare_subscriber(key_expr)
+        .callback(move |_| third_tx.send("third").unwrap())
+        .res()
+        .await
+        .unwrap();
+    async_std::task::sleep(SLEEP).await;
+    session1.put(key_expr, "value").res().await.unwrap();
+    assert_eq!(rx.recv_timeout(TIMEOUT).unwrap(), "third");
+    assert!(rx.recv_timeout(SLEEP).is_err());
+
+    // Queryables can be undeclared the same way
+    let queryable = session1.declare_queryable(key_expr).res().await.unwrap();
+    queryable.undeclare().await.unwrap();
+
+    session2.close().res().await.unwrap();
+    session1.close().res().await.unwrap();
+}
@@ -0,0 +1,88 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh::scouting::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[test]
+fn unicast_scouting() {
+    // A router replying to the unicast scouts, with multicast scouting disabled
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec!["tcp/127.0.0.1:17450".parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .scouting
+        .unicast
+        .set_listen(Some("127.0.0.1:17451".parse().unwrap()))
+        .unwrap();
+    let router = zenoh::open(config).res_sync().unwrap();
+
+    // A client finding the router through its gateway list only
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Client)).unwrap();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.scouting.unicast.gateways = vec!["127.0.0.1:17451".parse().unwrap()];
+    let client = zenoh::open(config).res_sync().unwrap();
+
+    let key_expr = "test/unicast_scouting";
+    let subscriber = router.declare_subscriber(key_expr).res_sync().unwrap();
+    std::thread::sleep(Duration::from_secs(1));
+    client.put(key_expr, "value").res_sync().unwrap();
+    let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.key_expr.as_str(), key_expr);
+    drop(subscriber);
+
+    client.close().res_sync().unwrap();
+    router.close().res_sync().unwrap();
+}
+
+#[test]
+fn unicast_scouting_without_multicast_interface() {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec!["tcp/127.0.0.1:17470".parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .scouting
+        .unicast
+        .set_listen(Some("127.0.0.1:17471".parse().unwrap()))
+        .unwrap();
+    let router = zenoh::open(config).res_sync().unwrap();
+
+    // The multicast scouting is skipped without any multicast interface, the gateways are still
+    // scouted over unicast
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Client)).unwrap();
+    config
+        .scouting
+        .multicast
+        .set_interface(Some("nonexistent0".into()))
+        .unwrap();
+    config.scouting.unicast.gateways = vec!["127.0.0.1:17471".parse().unwrap()];
+    let client = zenoh::open(config).res_sync().unwrap();
+
+    let key_expr = "test/unicast_scouting/without_multicast_interface";
+    let subscriber = router.declare_subscriber(key_expr).res_sync().unwrap();
+    std::thread::sleep(Duration::from_secs(1));
+    client.put(key_expr, "value").res_sync().unwrap();
+    let sample = subscriber.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(sample.key_expr.as_str(), key_expr);
+    drop(subscriber);
+
+    client.close().res_sync().unwrap();
+    router.close().res_sync().unwrap();
+}
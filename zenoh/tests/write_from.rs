@@ -0,0 +1,98 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use zenoh::prelude::sync::*;
+
+// Counts the allocations of the current thread, ignoring those of the zenoh tasks
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn write_from_without_allocation() {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+    let publisher = session
+        .declare_publisher("test/write_from")
+        .res_sync()
+        .unwrap();
+    // The publications are routed to a matching subscriber, which drops their sample
+    let received = Arc::new(AtomicUsize::new(0));
+    let c_received = received.clone();
+    let counter = session
+        .declare_subscriber("test/write_from")
+        .callback(move |_| {
+            c_received.fetch_add(1, Ordering::Relaxed);
+        })
+        .res_sync()
+        .unwrap();
+    let payload = [0u8; 256];
+
+    // The first publications allocate the buffer of the publisher and the routes
+    for _ in 0..10 {
+        publisher.write_from(&payload).res_sync().unwrap();
+    }
+    let before = allocations();
+    for _ in 0..1000 {
+        publisher.write_from(&payload).res_sync().unwrap();
+    }
+    assert_eq!(allocations() - before, 0);
+    assert_eq!(received.load(Ordering::Relaxed), 1010);
+    drop(counter);
+
+    // The buffer is copied on write while a sample still references it
+    let subscriber = session
+        .declare_subscriber("test/write_from")
+        .res_sync()
+        .unwrap();
+    publisher.write_from(&[1; 4]).res_sync().unwrap();
+    publisher.write_from(&[2; 4]).res_sync().unwrap();
+    let first = subscriber.recv().unwrap();
+    let second = subscriber.recv().unwrap();
+    assert_eq!(first.value.payload.contiguous().as_ref(), &[1; 4]);
+    assert_eq!(second.value.payload.contiguous().as_ref(), &[2; 4]);
+    drop(subscriber);
+
+    drop(publisher);
+    session.close().res_sync().unwrap();
+}
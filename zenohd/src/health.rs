@@ -0,0 +1,237 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Health endpoint answering the liveness and readiness probes over HTTP.
+//!
+//! A connection closed without sending a request is accepted too, so that the endpoint may also be
+//! used as a plain TCP probe.
+use async_std::io::{self, BufReader};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::config::{defaults, WhatAmI};
+use zenoh::runtime::Runtime;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HEADERS: usize = 64;
+
+struct Health {
+    runtime: Runtime,
+    started: Arc<AtomicBool>,
+    has_listeners: bool,
+    min_routers: usize,
+    min_peers: usize,
+}
+
+struct Status {
+    started: bool,
+    listeners: usize,
+    routers: usize,
+    peers: usize,
+    ready: bool,
+}
+
+impl Health {
+    fn status(&self) -> Status {
+        let started = self.started.load(Ordering::Acquire);
+        let listeners = self.runtime.get_locators().len();
+        let (mut routers, mut peers) = (0, 0);
+        for transport in self.runtime.manager().get_transports() {
+            match transport.get_whatami() {
+                Ok(WhatAmI::Router) => routers += 1,
+                Ok(WhatAmI::Peer) => peers += 1,
+                _ => {}
+            }
+        }
+        let ready = started
+            && (!self.has_listeners || listeners > 0)
+            && routers >= self.min_routers
+            && peers >= self.min_peers;
+        Status {
+            started,
+            listeners,
+            routers,
+            peers,
+            ready,
+        }
+    }
+}
+
+impl Status {
+    fn json(&self) -> String {
+        format!(
+            r#"{{"live":true,"ready":{},"started":{},"listeners":{},"routers":{},"peers":{}}}"#,
+            self.ready, self.started, self.listeners, self.routers, self.peers
+        )
+    }
+}
+
+/// Starts the health endpoint if configured.
+///
+/// zenohd is only ready once the returned flag has been set, after its startup has completed.
+pub(crate) async fn start(runtime: &Runtime) -> zenoh::Result<Arc<AtomicBool>> {
+    let started = Arc::new(AtomicBool::new(false));
+    let (listen, health) = {
+        let config = runtime.config.lock();
+        let conf = config.health();
+        let health = Health {
+            runtime: runtime.clone(),
+            started: started.clone(),
+            has_listeners: !config.listen.endpoints.is_empty(),
+            min_routers: conf.min_routers().unwrap_or(defaults::health::min_routers),
+            min_peers: conf.min_peers().unwrap_or(defaults::health::min_peers),
+        };
+        (conf.listen().clone(), health)
+    };
+    let listen = match listen {
+        Some(listen) => listen,
+        None => return Ok(started),
+    };
+    let listener = TcpListener::bind(&listen)
+        .await
+        .map_err(|e| format!("Unable to bind health endpoint on {listen}: {e}"))?;
+    log::info!("Health endpoint listening on {}", listen);
+
+    let health = Arc::new(health);
+    task::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    let health = health.clone();
+                    task::spawn(async move {
+                        if let Err(e) = io::timeout(REQUEST_TIMEOUT, handle(&health, stream)).await
+                        {
+                            log::debug!("Error answering health probe from {}: {}", addr, e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Error accepting health probe connection: {}", e),
+            }
+        }
+    });
+    Ok(started)
+}
+
+async fn handle(health: &Health, stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    if reader.read_line(&mut request).await? == 0 {
+        return Ok(());
+    }
+    // The headers are ignored
+    let mut line = String::new();
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut request = request.split_whitespace();
+    let (code, body) = match (request.next(), request.next()) {
+        (Some("GET"), Some("/health/live")) => ("200 OK", health.status().json()),
+        (Some("GET"), Some("/health/ready")) => {
+            let status = health.status();
+            match status.ready {
+                true => ("200 OK", status.json()),
+                false => ("503 Service Unavailable", status.json()),
+            }
+        }
+        (Some("GET"), Some(_)) => ("404 Not Found", String::new()),
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {code}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    (&stream).write_all(response.as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh::prelude::r#async::*;
+
+    async fn probe(health: &Health, request: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        client.write_all(request.as_bytes()).await.unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+        handle(health, stream).await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[test]
+    fn health_probes() {
+        task::block_on(async {
+            let mut config = zenoh::config::peer();
+            config.scouting.multicast.set_enabled(Some(false)).unwrap();
+            config.listen.endpoints = vec!["tcp/127.0.0.1:0".parse().unwrap()];
+            let session = zenoh::open(config).res().await.unwrap();
+            let health = Health {
+                runtime: session.runtime().clone(),
+                started: Arc::new(AtomicBool::new(false)),
+                has_listeners: false,
+                min_routers: 0,
+                min_peers: 0,
+            };
+
+            // zenohd is live but not ready until its startup has completed
+            let response = probe(&health, "GET /health/live HTTP/1.1\r\n\r\n").await;
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with(
+                r#"{"live":true,"ready":false,"started":false,"listeners":1,"routers":0,"peers":0}"#
+            ));
+            let response = probe(&health, "GET /health/ready HTTP/1.1\r\n\r\n").await;
+            assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+            assert!(response.contains(r#""ready":false"#));
+
+            health.started.store(true, Ordering::Release);
+            let response = probe(
+                &health,
+                "GET /health/ready HTTP/1.1\r\nHost: zenohd\r\n\r\n",
+            )
+            .await;
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with(
+                r#"{"live":true,"ready":true,"started":true,"listeners":1,"routers":0,"peers":0}"#
+            ));
+
+            // zenohd isn't ready while missing the required peers
+            let health = Health {
+                min_peers: 1,
+                ..health
+            };
+            let response = probe(&health, "GET /health/ready HTTP/1.1\r\n\r\n").await;
+            assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+
+            let response = probe(&health, "GET /metrics HTTP/1.1\r\n\r\n").await;
+            assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+            let response = probe(&health, "POST /health/ready HTTP/1.1\r\n\r\n").await;
+            assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+            // A plain TCP probe is answered with nothing
+            assert!(probe(&health, "").await.is_empty());
+
+            session.close().res().await.unwrap();
+        });
+    }
+}
@@ -12,14 +12,18 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use async_std::task;
-use clap::{ArgMatches, Command};
+use clap::{error::ErrorKind, ArgMatches, Command};
 use futures::future;
 use git_version::git_version;
+use log::Log;
 use zenoh::config::{
     Config, EndPoint, ModeDependentValue, PermissionsConf, PluginLoad, ValidatedMap,
 };
 use zenoh::plugins::PluginsManager;
 use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_util::{audit, log_filter};
+
+mod health;
 
 const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
 
@@ -31,16 +35,25 @@ const DEFAULT_LISTENER: &str = "tcp/[::]:7447";
 
 fn main() {
     task::block_on(async {
-        let mut log_builder =
-            env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("z=info"));
+        // RUST_LOG is applied through the runtime log filters, so that they can override it per module
+        let default_filter =
+            env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("z=info"))
+                .build();
+        let default_max_level = default_filter.filter();
+        let mut log_builder = env_logger::Builder::new();
+        log_builder.filter_level(log::LevelFilter::Trace);
         #[cfg(feature = "stats")]
-        log_builder.format_timestamp_millis().init();
-        #[cfg(not(feature = "stats"))]
-        log_builder.init();
+        log_builder.format_timestamp_millis();
+        log_filter::init(
+            log_builder.build(),
+            move |metadata| default_filter.enabled(metadata),
+            default_max_level,
+        )
+        .unwrap();
 
         log::info!("zenohd {}", *LONG_VERSION);
 
-        let app = Command::new("The zenoh router")
+        let mut app = Command::new("The zenoh router")
             .version(GIT_VERSION)
             .long_version(LONG_VERSION.as_str()).args(
                 &[
@@ -69,12 +82,18 @@ r#"Allows arbitrary configuration changes as column-separated KEY:VALUE pairs, w
 Examples:
 --cfg='startup/subscribe:["demo/**"]'
 --cfg='plugins/storage_manager/storages/demo:{key_expr:"demo/example/**",volume:"memory"}'"#),
-clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_name("[r|w|rw|none]").help(r"Configure the read and/or write permissions on the admin space. Default is read only."),
+clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_name("[r|w|rw|none]").possible_values(["r", "w", "rw", "none"]).help(r"Configure the read and/or write permissions on the admin space. Default is read only."),
                 ]
             );
-        let args = app.get_matches();
-        let config = config_from_args(&args);
+        let args = app.get_matches_mut();
+        // The invalid values are reported like the clap errors, pointing at the offending flag
+        let config = config_from_args(&args)
+            .unwrap_or_else(|e| app.error(ErrorKind::ValueValidation, e).exit());
         log::info!("Initial conf: {}", &config);
+        if let Err(e) = init_audit(&config) {
+            println!("{e}. Exiting...");
+            std::process::exit(-1);
+        }
 
         let mut plugins = PluginsManager::dynamic(config.libloader());
         // Static plugins are to be added here, with `.add_static::<PluginType>()`
@@ -83,6 +102,7 @@ clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_na
                 name,
                 paths,
                 required,
+                ..
             } = plugin_load;
             if let Err(e) = match paths {
                 None => plugins.load_plugin_by_name(name),
@@ -103,6 +123,13 @@ clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_na
                 std::process::exit(-1);
             }
         };
+        let started = match health::start(&runtime).await {
+            Ok(started) => started,
+            Err(e) => {
+                println!("{e}. Exiting...");
+                std::process::exit(-1);
+            }
+        };
 
         for (name, path, start_result) in plugins.start_all(&runtime) {
             match start_result {
@@ -128,26 +155,68 @@ clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_na
         }
 
         AdminSpace::start(&runtime, plugins, LONG_VERSION.clone()).await;
+        started.store(true, std::sync::atomic::Ordering::Release);
 
         future::pending::<()>().await;
     });
 }
 
-fn config_from_args(args: &ArgMatches) -> Config {
-    let mut config = args
-        .value_of("config")
-        .map_or_else(Config::default, |conf_file| {
-            Config::from_file(conf_file).unwrap()
-        });
+fn init_audit(config: &Config) -> Result<(), String> {
+    use zenoh::config::defaults;
+    let conf = config.audit();
+    if let Some(path) = conf.file().path() {
+        let max_size = conf
+            .file()
+            .max_size()
+            .unwrap_or(defaults::audit::file::max_size);
+        let max_files = conf
+            .file()
+            .max_files()
+            .unwrap_or(defaults::audit::file::max_files);
+        let sink = audit::FileAuditSink::new(path, max_size, max_files)
+            .map_err(|e| format!("Unable to open audit log {path}: {e}"))?;
+        audit::add_sink(std::sync::Arc::new(sink));
+    }
+    if conf
+        .syslog()
+        .enabled()
+        .unwrap_or(defaults::audit::syslog::enabled)
+    {
+        #[cfg(unix)]
+        {
+            let socket = conf
+                .syslog()
+                .socket()
+                .clone()
+                .unwrap_or_else(|| defaults::audit::syslog::socket.to_string());
+            let sink = audit::SyslogAuditSink::new(&socket, "zenohd")
+                .map_err(|e| format!("Unable to open syslog socket {socket}: {e}"))?;
+            audit::add_sink(std::sync::Arc::new(sink));
+        }
+        #[cfg(not(unix))]
+        log::warn!("Audit to syslog is only supported on unix platforms");
+    }
+    Ok(())
+}
+
+fn config_from_args(args: &ArgMatches) -> Result<Config, String> {
+    let mut config = match args.value_of("config") {
+        Some(conf_file) => Config::from_file(conf_file)
+            .map_err(|e| format!("Invalid --config={conf_file}: {e}"))?,
+        None => Config::default(),
+    };
     if config.mode().is_none() {
         config
             .set_mode(Some(zenoh::config::WhatAmI::Router))
             .unwrap();
     }
-    if args.occurrences_of("id") > 0 {
+    if let Some(value) = args.value_of("id") {
+        let id = value
+            .parse()
+            .map_err(|e| format!("Invalid --id={value}: {e}"))?;
         config
-            .set_id(args.value_of("id").unwrap().parse().unwrap())
-            .unwrap();
+            .set_id(id)
+            .map_err(|_| format!("Invalid --id={value}: rejected by the configuration"))?;
     }
     // apply '--rest-http-port' to config only if explicitly set (overwritting config),
     // or if no config file is set (to apply its default value)
@@ -156,60 +225,60 @@ fn config_from_args(args: &ArgMatches) -> Config {
         if !value.eq_ignore_ascii_case("none") {
             config
                 .insert_json5("plugins/rest/http_port", &format!(r#""{value}""#))
-                .unwrap();
+                .map_err(|e| format!("Invalid --rest-http-port={value}: {e}"))?;
         }
     }
     if let Some(plugins_search_dirs) = args.values_of("plugin-search-dir") {
         config
             .set_plugins_search_dirs(plugins_search_dirs.map(|c| c.to_owned()).collect())
-            .unwrap();
+            .map_err(|_| {
+                "Invalid --plugin-search-dir: rejected by the configuration".to_string()
+            })?;
     }
     if let Some(plugins) = args.values_of("plugin") {
         for plugin in plugins {
-            match plugin.split_once(':') {
-                Some((name, path)) => {
-                    config
-                        .insert_json5(&format!("plugins/{name}/__required__"), "true")
-                        .unwrap();
-                    config
-                        .insert_json5(&format!("plugins/{name}/__path__"), &format!("\"{path}\""))
-                        .unwrap();
-                }
-                None => config
-                    .insert_json5(&format!("plugins/{plugin}/__required__"), "true")
-                    .unwrap(),
+            let (name, path) = match plugin.split_once(':') {
+                Some((name, path)) => (name, Some(path)),
+                None => (plugin, None),
+            };
+            if name.is_empty() || name.contains('/') {
+                return Err(format!(
+                    "Invalid --plugin={plugin}: expected `<plugin_name>` or `<plugin_name>:<library_path>`"
+                ));
+            }
+            config
+                .insert_json5(&format!("plugins/{name}/__required__"), "true")
+                .map_err(|e| format!("Invalid --plugin={plugin}: {e}"))?;
+            if let Some(path) = path {
+                config
+                    .insert_json5(&format!("plugins/{name}/__path__"), &format!("\"{path}\""))
+                    .map_err(|e| format!("Invalid --plugin={plugin}: {e}"))?;
             }
         }
     }
     if let Some(peers) = args.values_of("connect") {
+        let endpoints = peers
+            .map(|v| {
+                v.parse::<EndPoint>()
+                    .map_err(|e| format!("Invalid --connect={v}: {e}"))
+            })
+            .collect::<Result<Vec<EndPoint>, String>>()?;
         config
             .connect
-            .set_endpoints(
-                peers
-                    .map(|v| match v.parse::<EndPoint>() {
-                        Ok(v) => v,
-                        Err(e) => {
-                            panic!("Couldn't parse option --peer={} into Locator: {}", v, e);
-                        }
-                    })
-                    .collect(),
-            )
-            .unwrap();
+            .set_endpoints(endpoints)
+            .map_err(|_| "Invalid --connect: rejected by the configuration".to_string())?;
     }
     if let Some(listeners) = args.values_of("listen") {
+        let endpoints = listeners
+            .map(|v| {
+                v.parse::<EndPoint>()
+                    .map_err(|e| format!("Invalid --listen={v}: {e}"))
+            })
+            .collect::<Result<Vec<EndPoint>, String>>()?;
         config
             .listen
-            .set_endpoints(
-                listeners
-                    .map(|v| match v.parse::<EndPoint>() {
-                        Ok(v) => v,
-                        Err(e) => {
-                            panic!("Couldn't parse option --listen={} into Locator: {}", v, e);
-                        }
-                    })
-                    .collect(),
-            )
-            .unwrap();
+            .set_endpoints(endpoints)
+            .map_err(|_| "Invalid --listen: rejected by the configuration".to_string())?;
     }
     if config.listen.endpoints.is_empty() {
         config
@@ -236,55 +305,28 @@ fn config_from_args(args: &ArgMatches) -> Config {
         (false, false) => {}
     };
     if let Some(permissions) = args.value_of("adminspace-permissions") {
-        match permissions {
-            "r" => config
-                .adminspace
-                .set_permissions(PermissionsConf {
-                    read: true,
-                    write: false,
-                })
-                .unwrap(),
-            "w" => config
-                .adminspace
-                .set_permissions(PermissionsConf {
-                    read: false,
-                    write: true,
-                })
-                .unwrap(),
-            "rw" => config
-                .adminspace
-                .set_permissions(PermissionsConf {
-                    read: true,
-                    write: true,
-                })
-                .unwrap(),
-            "none" => config
-                .adminspace
-                .set_permissions(PermissionsConf {
-                    read: false,
-                    write: false,
-                })
-                .unwrap(),
-            s => panic!(
-                r#"Invalid option: --adminspace-permissions={} - Accepted values: "r", "w", "rw" or "none""#,
-                s
-            ),
+        // The accepted values are checked by clap
+        let (read, write) = match permissions {
+            "r" => (true, false),
+            "w" => (false, true),
+            "rw" => (true, true),
+            _ => (false, false),
         };
+        config
+            .adminspace
+            .set_permissions(PermissionsConf { read, write })
+            .unwrap();
     };
     for json in args.values_of("cfg").unwrap_or_default() {
-        if let Some((key, value)) = json.split_once(':') {
-            match json5::Deserializer::from_str(value) {
-                Ok(mut deserializer) => {
-                    if let Err(e) =
-                        config.insert(key.strip_prefix('/').unwrap_or(key), &mut deserializer)
-                    {
-                        log::warn!("Couldn't perform configuration {}: {}", json, e);
-                    }
-                }
-                Err(e) => log::warn!("Couldn't perform configuration {}: {}", json, e),
-            }
-        }
+        let (key, value) = json
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid --cfg={json}: expected `KEY:VALUE`"))?;
+        let mut deserializer = json5::Deserializer::from_str(value)
+            .map_err(|e| format!("Invalid --cfg={json}: invalid JSON5 value: {e}"))?;
+        config
+            .insert(key.strip_prefix('/').unwrap_or(key), &mut deserializer)
+            .map_err(|e| format!("Invalid --cfg={json}: {e}"))?;
     }
     log::debug!("Config: {:?}", &config);
-    config
+    Ok(config)
 }